@@ -1,4 +1,7 @@
+// Managed by Web App Hub, remove this line to keep your own changes
 user_pref("toolkit.legacyUserProfileCustomizations.stylesheets", true);
 user_pref("browser.tabs.inTitlebar", 0);
 user_pref("browser.tabs.warnOnClose", false);
 user_pref("browser.link.open_newwindow", 2);
+user_pref("browser.shell.checkDefaultBrowser", false);
+user_pref("browser.sessionstore.resume_from_crash", false);
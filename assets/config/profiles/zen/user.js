@@ -1 +1,2 @@
+// Managed by Web App Hub, remove this line to keep your own changes
 user_pref("zen.welcome-screen.seen", true);
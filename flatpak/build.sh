@@ -29,7 +29,7 @@ flatpak-builder \
 
 flatpak build-bundle \
     "${target_dir}/repo" \
-    "${target_dir}/web-app-hub.flatpak" \
-    org.pvermeer.WebAppHub
+    "${target_dir}/web-app-hub-devel.flatpak" \
+    org.pvermeer.WebAppHub.Devel
 
 echo -e "\n==== Done ====\n"
@@ -74,18 +74,25 @@ fn copy_dev_web_apps(app_dirs: &AppDirs) {
     }
 }
 
+/// The shipped file is named after the stable id, a Devel build gets its own id in it
 fn install_app_desktop_file(app_dirs: &AppDirs) -> Result<()> {
-    let file_name = desktop_file_name();
-    let desktop_file = assets_path().join("desktop").join(&file_name);
-    let save_file = app_dirs.user_applications.join(file_name);
-
-    fs::copy(desktop_file, save_file).context("Desktop file copy failed")?;
+    let desktop_file = assets_path()
+        .join("desktop")
+        .join(desktop_file_name(config::BASE_APP_ID.get_value()));
+    let save_file = app_dirs
+        .user_applications
+        .join(desktop_file_name(config::APP_ID.get_value()));
+
+    let contents = fs::read_to_string(desktop_file).context("Desktop file read failed")?;
+    let contents = contents.replace(config::BASE_APP_ID.get_value(), config::APP_ID.get_value());
+    fs::write(save_file, contents).context("Desktop file copy failed")?;
     Ok(())
 }
 
 fn install_app_icon(app_dirs: &AppDirs) -> Result<()> {
-    let file_name = icon_file_name();
-    let icon_file = assets_path().join("desktop").join(&file_name);
+    let icon_file = assets_path()
+        .join("desktop")
+        .join(icon_file_name(config::BASE_APP_ID.get_value()));
     let save_dir = app_dirs
         .user_data
         .join("icons")
@@ -96,7 +103,7 @@ fn install_app_icon(app_dirs: &AppDirs) -> Result<()> {
         fs::create_dir_all(&save_dir).context("Failed to create icon dir")?;
     }
 
-    let save_file = save_dir.join(file_name);
+    let save_file = save_dir.join(icon_file_name(config::APP_ID.get_value()));
 
     fs::copy(icon_file, save_file).context("Icon copy failed")?;
     Ok(())
@@ -126,16 +133,14 @@ fn dev_assets_path() -> PathBuf {
     project_path().join("dev-assets")
 }
 
-fn desktop_file_name() -> String {
-    let app_id = config::APP_ID.get_value();
+fn desktop_file_name(app_id: &str) -> String {
     let extension = "desktop";
     let file_name = format!("{app_id}.{extension}");
 
     file_name
 }
 
-fn icon_file_name() -> String {
-    let app_id = config::APP_ID.get_value();
+fn icon_file_name(app_id: &str) -> String {
     let extension = "png";
     let file_name = format!("{app_id}.{extension}");
 
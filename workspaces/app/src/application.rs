@@ -1,25 +1,41 @@
 mod css_provider;
+pub mod dbus_service;
 mod error_dialog;
-mod pages;
+pub mod pages;
 mod window;
 
-use anyhow::{Error, Result};
+use anyhow::{Context, Error, Result};
 use common::{
     app_dirs::AppDirs,
-    assets::{self},
+    assets::{self, ConfigUpdate},
     browsers::BrowserConfigs,
     cache_settings::CacheSettings,
     config::{self},
+    desktop_file::{DesktopFile, migration::AppUpgradeNotes},
     fetch::Fetch,
+    policy,
+    preferences::Preferences,
+    site_hints::SiteHints,
+    url::parse_web_input,
     utils::{self, OnceLockExt},
 };
 use error_dialog::ErrorDialog;
-use gtk::{IconTheme, Image, Settings, gdk};
+use gtk::{
+    AccessibleAnnouncementPriority, IconTheme, Image, Settings, gdk,
+    gio::prelude::{ApplicationExt, ApplicationExtManual},
+    glib,
+    prelude::{AccessibleExt, GtkWindowExt},
+};
 use pages::{Page, Pages};
-use std::{cell::RefCell, path::Path, rc::Rc};
-use tracing::{debug, error};
+use std::{cell::RefCell, collections::HashSet, path::Path, rc::Rc};
+use tracing::{debug, error, info};
+use url::Url;
 use window::AppWindow;
 
+/// The app that handles calls from outside the window, the only owner that outlives it.
+/// Replaced when the app is restarted.
+pub type CurrentApp = Rc<RefCell<Option<Rc<App>>>>;
+
 pub struct Locale {
     current: String,
     default: String,
@@ -27,29 +43,48 @@ pub struct Locale {
 
 pub struct App {
     pub cache_settings: RefCell<CacheSettings>,
+    pub preferences: RefCell<Preferences>,
     pub dirs: Rc<AppDirs>,
     pub browser_configs: Rc<BrowserConfigs>,
+    pub site_hints: Rc<SiteHints>,
     pub error_dialog: ErrorDialog,
     pub locale: Locale,
     adw_application: libadwaita::Application,
+    current_app: CurrentApp,
     icon_theme: Rc<IconTheme>,
     window: AppWindow,
-    fetch: Fetch,
+    fetch: Rc<Fetch>,
     pages: Pages,
     has_created_apps: RefCell<bool>,
+    /// Browsers are detected and the pages can be used
+    is_ready: RefCell<bool>,
+    /// Opened as a new web app when the app is ready
+    pending_new_app: RefCell<Option<DesktopFile>>,
+    /// Shown on the Web Apps page when the app is ready
+    pending_toast: RefCell<Option<String>>,
+    /// The portal asks the user for every launcher, one question at a time
+    is_installing_launchers: RefCell<bool>,
+    /// Asked for while launchers were installed, they are checked again after
+    has_launcher_changes: RefCell<bool>,
+    /// Contents of launchers that were cancelled or failed, they are not tried again until they
+    /// change
+    skipped_launchers: RefCell<HashSet<String>>,
 }
 impl App {
-    pub fn new(adw_application: &libadwaita::Application) -> Rc<Self> {
+    fn new(adw_application: &libadwaita::Application, current_app: &CurrentApp) -> Rc<Self> {
         Rc::new({
             let display = gdk::Display::default().expect("Failed to connect to display");
             let icon_theme = Rc::new(IconTheme::for_display(&display));
             let app_dirs = AppDirs::new().expect("Failed to get all needed directories");
+            policy::init(&app_dirs);
             let settings = Settings::default().expect("Failed to load gtk settings");
             let cache_settings = RefCell::new(CacheSettings::new(&app_dirs));
+            let preferences = RefCell::new(Preferences::new(&app_dirs));
             let window = AppWindow::new(adw_application);
-            let fetch = Fetch::new();
+            let fetch = Rc::new(Fetch::new());
             let pages = Pages::new();
             let browsers = BrowserConfigs::new(&icon_theme, &app_dirs);
+            let site_hints = SiteHints::new(&app_dirs);
             let error_dialog = ErrorDialog::new();
             let locale = Locale {
                 current: rust_i18n::locale().to_string(),
@@ -61,21 +96,41 @@ impl App {
 
             Self {
                 cache_settings,
+                preferences,
                 dirs: app_dirs,
                 browser_configs: browsers,
+                site_hints,
                 error_dialog,
                 locale,
                 adw_application: adw_application.clone(),
+                current_app: current_app.clone(),
                 icon_theme,
                 window,
                 fetch,
                 pages,
                 has_created_apps: RefCell::new(false),
+                is_ready: RefCell::new(false),
+                pending_new_app: RefCell::new(None),
+                pending_toast: RefCell::new(None),
+                is_installing_launchers: RefCell::new(false),
+                has_launcher_changes: RefCell::new(false),
+                skipped_launchers: RefCell::new(HashSet::new()),
             }
         })
     }
 
-    pub fn init(self: &Rc<Self>) {
+    /// Builds the app on the first activation, a later one shows the open window
+    pub fn activate(adw_application: &libadwaita::Application, current_app: &CurrentApp) {
+        let open_app = current_app.borrow().clone();
+        if let Some(app) = open_app {
+            app.window.adw_window.present();
+            return;
+        }
+
+        Self::build(adw_application, current_app);
+    }
+
+    fn init(self: &Rc<Self>) {
         if let Err(error) = (|| -> Result<()> {
             debug!("Using icon theme: {}", self.icon_theme.theme_name());
 
@@ -83,31 +138,145 @@ impl App {
             self.window.init(self);
             self.error_dialog.init(self);
 
-            assets::init(&self.dirs)?;
+            let config_update = assets::init(&self.dirs)?;
             self.add_system_icon_paths();
-            self.browser_configs.init();
+            self.queue_config_update_toast(&config_update);
+
+            Ok(())
+        })() {
+            self.show_error(&error);
+            return;
+        }
+
+        // Browser detection can be slow, show the window first
+        self.window.view.show_loading();
+        let self_clone = self.clone();
+        glib::spawn_future_local(async move {
+            self_clone.browser_configs.init().await;
+            self_clone.browser_configs.watch_config_dirs();
+            self_clone.site_hints.init();
+            DesktopFile::migrate_sandbox_launchers(&self_clone.browser_configs, &self_clone.dirs);
 
             // Last
-            self.pages.init(self);
+            self_clone.pages.init(&self_clone);
+            *self_clone.is_ready.borrow_mut() = true;
 
-            if *self.has_created_apps.borrow() {
-                self.navigate(&Page::WebApps);
+            if let Some(desktop_file) = self_clone.pending_new_app.take() {
+                self_clone.open_new_app(desktop_file);
+            } else if let Some(message) = self_clone.pending_toast.take() {
+                self_clone.show_web_apps_toast(&message);
             } else {
-                self.navigate(&Page::Home);
+                self_clone.navigate(&self_clone.get_start_page());
             }
+        });
+    }
 
-            Ok(())
-        })() {
+    fn get_start_page(self: &Rc<Self>) -> Page {
+        if self.preferences.borrow().settings.restore_last_page
+            && let Some(page) = self
+                .cache_settings
+                .borrow()
+                .settings
+                .last_page
+                .as_deref()
+                .and_then(Page::from_key)
+        {
+            return page;
+        }
+
+        if *self.has_created_apps.borrow() {
+            Page::WebApps
+        } else {
+            Page::Home
+        }
+    }
+
+    /// Started without a window, like by D-Bus activation, activating creates the app
+    pub fn get_or_activate(
+        adw_application: &libadwaita::Application,
+        current_app: &CurrentApp,
+    ) -> Option<Rc<Self>> {
+        if current_app.borrow().is_none() {
+            adw_application.activate();
+        }
+        current_app.borrow().clone()
+    }
+
+    /// Url given on the command line or dropped on the app icon.
+    /// An invalid url shows a toast on the Web Apps page.
+    pub fn open_url_input(self: &Rc<Self>, input: &str) {
+        let Some(url) = parse_web_input(input) else {
+            debug!(input, "Not a web url");
+            let message = t!("web_apps.invalid_url", url = input).to_string();
+
+            self.window.adw_window.present();
+            if *self.is_ready.borrow() {
+                self.show_web_apps_toast(&message);
+            } else {
+                self.pending_toast.replace(Some(message));
+            }
+            return;
+        };
+
+        if let Err(error) = self.open_new_web_app(&url, "") {
             self.show_error(&error);
         }
     }
 
+    /// Opens a new web app for the url, once the browsers are detected.
+    /// Returns the id of the new web app, it is only saved when the user saves it.
+    pub fn open_new_web_app(self: &Rc<Self>, url: &Url, name: &str) -> Result<String> {
+        let mut desktop_file = DesktopFile::new(&self.browser_configs, &self.dirs);
+        desktop_file
+            .ensure_unique_id()
+            .context("Failed to create web app")?;
+        let app_id = desktop_file.get_id().context("No id on 'DesktopFile'")?;
+        self.preferences
+            .borrow()
+            .apply_defaults(&self.browser_configs, &mut desktop_file);
+        desktop_file.set_url(url.as_str());
+        if !name.trim().is_empty() {
+            desktop_file.set_name(name.trim());
+        }
+
+        self.window.adw_window.present();
+        if *self.is_ready.borrow() {
+            self.open_new_app(desktop_file);
+        } else {
+            debug!(app_id, "App is loading, opening the new web app when ready");
+            self.pending_new_app.replace(Some(desktop_file));
+        }
+
+        Ok(app_id)
+    }
+
+    fn open_new_app(self: &Rc<Self>, desktop_file: DesktopFile) {
+        self.navigate(&Page::WebApps);
+        self.pages
+            .get_web_apps()
+            .open_prefilled_app(self, desktop_file);
+    }
+
+    fn show_web_apps_toast(self: &Rc<Self>, message: &str) {
+        self.navigate(&Page::WebApps);
+        self.pages.get_web_apps().show_toast(message, true);
+    }
+
     pub fn add_icon_search_path(self: &Rc<Self>, path: &Path) {
         if !path.is_dir() {
             debug!("Not a valid icon path: {}", path.display());
             return;
         }
 
+        if self
+            .icon_theme
+            .search_path()
+            .iter()
+            .any(|search_path| search_path == path)
+        {
+            return;
+        }
+
         debug!("Adding icon path to icon theme: {}", path.display());
         self.icon_theme.add_search_path(path);
     }
@@ -121,30 +290,137 @@ impl App {
         self.window.view.navigate(self, page);
     }
 
+    /// Launchers of the portal are installed here instead of on save, so edits in the editor
+    /// don't each ask the user. Called after an explicit save and when the list is read again.
+    pub fn install_pending_launchers(self: &Rc<Self>) {
+        if !utils::env::is_flatpak_container() {
+            return;
+        }
+        if *self.is_installing_launchers.borrow() {
+            *self.has_launcher_changes.borrow_mut() = true;
+            return;
+        }
+        *self.is_installing_launchers.borrow_mut() = true;
+
+        let self_clone = self.clone();
+        glib::spawn_future_local(async move {
+            loop {
+                *self_clone.has_launcher_changes.borrow_mut() = false;
+                let desktop_files: Vec<DesktopFile> =
+                    DesktopFile::get_all_owned(&self_clone.browser_configs, &self_clone.dirs)
+                        .into_iter()
+                        .filter(DesktopFile::needs_launcher_install)
+                        .filter(|desktop_file| {
+                            !self_clone
+                                .skipped_launchers
+                                .borrow()
+                                .contains(&desktop_file.to_string())
+                        })
+                        .collect();
+
+                for desktop_file in desktop_files {
+                    let contents = desktop_file.to_string();
+                    let is_installed = match desktop_file.install_launcher().await {
+                        Ok(is_installed) => is_installed,
+                        Err(error) => {
+                            self_clone.show_error(&error);
+                            false
+                        }
+                    };
+                    if !is_installed {
+                        self_clone.skipped_launchers.borrow_mut().insert(contents);
+                    }
+                }
+
+                if !*self_clone.has_launcher_changes.borrow() {
+                    break;
+                }
+            }
+            *self_clone.is_installing_launchers.borrow_mut() = false;
+        });
+    }
+
     pub fn show_error(self: &Rc<Self>, error: &Error) {
         error!("{error:?}");
         self.error_dialog.show(self, error);
     }
 
+    /// Tell screen readers about a change that is only visible, like a spinner.
+    /// Toasts are announced by libadwaita.
+    pub fn announce(self: &Rc<Self>, message: &str) {
+        debug!(message, "Announcing");
+        self.window
+            .adw_window
+            .announce(message, AccessibleAnnouncementPriority::Medium);
+    }
+
     pub fn close(self: &Rc<Self>) {
         self.window.close();
     }
 
-    pub fn restart(mut self: Rc<Self>) {
+    /// Builds the app again from the files on disk. The old window and pages are dropped, so
+    /// every signal handler connects to the new app. Returns the new app.
+    pub fn restart(self: &Rc<Self>) -> Rc<Self> {
+        info!("Restarting app");
+        // Without a window the application would quit
+        let _hold_guard = self.adw_application.hold();
+
+        self.browser_configs.shutdown();
         self.close();
+        self.window.adw_window.destroy();
+        self.current_app.replace(None);
+
+        Self::build(&self.adw_application, &self.current_app)
+    }
+
+    /// Writes the shipped config files again and restarts, so the browsers and pages are read
+    /// from the fresh files. Cached settings are reset, the window keeps its size.
+    pub fn reset(self: &Rc<Self>) {
+        info!("Resetting app");
+        let reset_result = assets::reset_config_files(&self.dirs);
         self.cache_settings.borrow_mut().reset();
-        let new_self = Self::new(&self.adw_application);
-        self = new_self;
-        self.init();
+        let app = self.restart();
+
+        match reset_result {
+            Ok(()) => {
+                app.pending_toast
+                    .replace(Some(t!("app_menu.reset.done").to_string()));
+            }
+            Err(error) => app.show_error(&error),
+        }
+    }
+
+    /// The app is the current app before `init`, calls from outside see it while it loads
+    fn build(adw_application: &libadwaita::Application, current_app: &CurrentApp) -> Rc<Self> {
+        let app = Self::new(adw_application, current_app);
+        current_app.replace(Some(app.clone()));
+        app.init();
+        app
+    }
+
+    pub fn on_app_update(self: &Rc<Self>, upgrade_notes: Vec<AppUpgradeNotes>) {
+        self.window.view.on_app_update(upgrade_notes);
     }
 
-    pub fn on_app_update(self: &Rc<Self>) {
-        self.window.view.on_app_update();
+    /// Shown once, the next start finds the `.new` files already written
+    fn queue_config_update_toast(&self, config_update: &ConfigUpdate) {
+        let message = match config_update.new_version_paths.as_slice() {
+            [] => return,
+            [new_version_path] => t!("assets.kept_user_file", path = new_version_path.display()),
+            new_version_paths => t!(
+                "assets.kept_user_files",
+                count = new_version_paths.len(),
+                dir = self.dirs.app_config.display()
+            ),
+        };
+        if self.pending_toast.borrow().is_none() {
+            self.pending_toast.replace(Some(message.to_string()));
+        }
     }
 
     fn add_system_icon_paths(self: &Rc<Self>) {
         if utils::env::is_flatpak_container() {
-            for path in &self.dirs.system_icons {
+            for path in self.dirs.system_icons() {
                 debug!(path = %path.display(), "Adding system icon path");
                 self.add_icon_search_path(path);
             }
@@ -1,9 +1,17 @@
 use gtk::{CssProvider, gdk::Display, glib::object::IsA, style_context_add_provider_for_display};
+use std::sync::Once;
+
+/// The provider stays on the display, a restart of the app does not add it again
+static INIT: Once = Once::new();
 
 pub fn init(display: &impl IsA<Display>) {
+    INIT.call_once(|| add_provider(display));
+}
+
+fn add_provider(display: &impl IsA<Display>) {
     let css_provider = CssProvider::new();
 
-    css_provider.load_from_data(
+    css_provider.load_from_string(
         ".label-spaced {
             line-height: 2;
         }",
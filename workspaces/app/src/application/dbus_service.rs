@@ -0,0 +1,162 @@
+use crate::application::{App, CurrentApp};
+use anyhow::{Context, Result, bail};
+use common::{
+    app_dirs::AppDirs,
+    browsers::BrowserConfigs,
+    config::{self},
+    desktop_file::DesktopFile,
+    url::{parse_user_input, parse_web_input},
+    utils::OnceLockExt,
+};
+use gtk::{
+    gio::{self, DBusMethodInvocation, prelude::ApplicationExt},
+    glib::{Variant, prelude::ToVariant},
+};
+use tracing::{debug, error, info};
+use url::Url;
+
+const ERROR_INVALID_ARGS: &str = "org.freedesktop.DBus.Error.InvalidArgs";
+const ERROR_UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+const ERROR_FAILED: &str = "org.freedesktop.DBus.Error.Failed";
+
+/// On the object path of the application, so a call reaches the running instance or
+/// starts the app with D-Bus activation
+pub fn register(adw_application: &libadwaita::Application, current_app: &CurrentApp) -> Result<()> {
+    let (Some(connection), Some(object_path)) = (
+        adw_application.dbus_connection(),
+        adw_application.dbus_object_path(),
+    ) else {
+        debug!("Not registered on D-Bus, skipping the D-Bus interface");
+        return Ok(());
+    };
+
+    let interface_name = get_interface_name();
+    let node_info = gio::DBusNodeInfo::for_xml(&build_introspection_xml(&interface_name))?;
+    let interface_info = node_info
+        .lookup_interface(&interface_name)
+        .context("D-Bus interface is missing from the introspection")?;
+
+    let adw_application = adw_application.clone();
+    let current_app = current_app.clone();
+    connection
+        .register_object(&object_path, &interface_info)
+        .method_call(
+            move |_connection,
+                  _sender,
+                  _object_path,
+                  _interface_name,
+                  method_name,
+                  parameters,
+                  invocation| {
+                debug!(method_name, "D-Bus method call");
+
+                match method_name {
+                    "CreateWebApp" => {
+                        create_web_app(&adw_application, &current_app, &parameters, invocation);
+                    }
+                    "ListWebApps" => list_web_apps(invocation),
+                    _ => invocation.return_dbus_error(
+                        ERROR_UNKNOWN_METHOD,
+                        &format!("Unknown method '{method_name}'"),
+                    ),
+                }
+            },
+        )
+        .build()?;
+
+    info!(
+        interface_name,
+        object_path = object_path.as_str(),
+        "Registered D-Bus interface"
+    );
+    Ok(())
+}
+
+fn get_interface_name() -> String {
+    format!("{}.WebApps", config::APP_ID.get_value())
+}
+
+fn build_introspection_xml(interface_name: &str) -> String {
+    format!(
+        r#"<node>
+  <interface name="{interface_name}">
+    <method name="CreateWebApp">
+      <arg type="s" name="url" direction="in"/>
+      <arg type="s" name="name" direction="in"/>
+      <arg type="s" name="id" direction="out"/>
+    </method>
+    <method name="ListWebApps">
+      <arg type="a(ss)" name="web_apps" direction="out"/>
+    </method>
+  </interface>
+</node>"#
+    )
+}
+
+/// Opens a new web app with the url and name, the user saves it.
+/// Replies with the id the web app gets when it is saved.
+fn create_web_app(
+    adw_application: &libadwaita::Application,
+    current_app: &CurrentApp,
+    parameters: &Variant,
+    invocation: DBusMethodInvocation,
+) {
+    let Some((url, name)) = parameters.get::<(String, String)>() else {
+        invocation.return_dbus_error(ERROR_INVALID_ARGS, "Expected a url and a name");
+        return;
+    };
+    let url = match get_web_url(&url) {
+        Ok(url) => url,
+        Err(error) => {
+            invocation.return_dbus_error(ERROR_INVALID_ARGS, &error.to_string());
+            return;
+        }
+    };
+
+    // Started by D-Bus activation, there is no window yet
+    let Some(app) = App::get_or_activate(adw_application, current_app) else {
+        invocation.return_dbus_error(ERROR_FAILED, "The app did not start");
+        return;
+    };
+
+    match app.open_new_web_app(&url, &name) {
+        Ok(app_id) => invocation.return_value(Some(&(app_id,).to_variant())),
+        Err(error) => {
+            error!("{error:?}");
+            invocation.return_dbus_error(ERROR_FAILED, &error.to_string());
+        }
+    }
+}
+
+fn get_web_url(input: &str) -> Result<Url> {
+    if parse_user_input(input).is_none() {
+        bail!("Invalid url: '{input}'")
+    }
+    parse_web_input(input)
+        .with_context(|| format!("Only http and https urls can be a web app: '{input}'"))
+}
+
+/// Read from disk, works while the app is still loading
+fn list_web_apps(invocation: DBusMethodInvocation) {
+    let app_dirs = match AppDirs::new() {
+        Ok(app_dirs) => app_dirs,
+        Err(error) => {
+            error!("{error:?}");
+            invocation.return_dbus_error(ERROR_FAILED, &error.to_string());
+            return;
+        }
+    };
+    let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+
+    let web_apps: Vec<(String, String)> = DesktopFile::get_all_owned(&browser_configs, &app_dirs)
+        .iter()
+        .map(|desktop_file| {
+            (
+                desktop_file.get_id().unwrap_or_default(),
+                desktop_file.get_name().unwrap_or_default(),
+            )
+        })
+        .collect();
+
+    invocation.return_value(Some(&(web_apps,).to_variant()));
+}
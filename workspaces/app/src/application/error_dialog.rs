@@ -2,49 +2,152 @@ use crate::application::App;
 use anyhow::Error;
 use common::{
     config::{self},
-    utils::OnceLockExt,
+    utils::{self, OnceLockExt},
+};
+use gtk::{
+    Align, Button, Expander, Label, Orientation, PolicyType, ScrolledWindow, UriLauncher,
+    gio::Cancellable,
+    prelude::{BoxExt, ButtonExt, WidgetExt},
 };
-use gtk::Orientation;
 use libadwaita::{
     AlertDialog, ResponseAppearance,
     prelude::{AdwDialogExt, AlertDialogExt},
 };
-use std::rc::Rc;
+use std::{cell::RefCell, fmt::Write as _, rc::Rc};
+use tracing::error;
+use url::Url;
 
 pub struct ErrorDialog {
     dialog: AlertDialog,
+    details_label: Label,
+    copy_button: Button,
+    report_button: Button,
+    /// Error chain with the app and browser info, as copied for a bug report
+    details: Rc<RefCell<String>>,
+    /// First line of the error, the title of a new issue
+    summary: Rc<RefCell<String>>,
 }
 impl ErrorDialog {
     pub const DIALOG_EXIT: &str = "exit";
+    const DETAILS_MAX_HEIGHT: i32 = 240;
 
     pub fn new() -> Self {
-        let dialog = Self::build_dialog();
+        let details_label = Label::builder()
+            .selectable(true)
+            .wrap(true)
+            .wrap_mode(gtk::pango::WrapMode::WordChar)
+            .xalign(0.0)
+            .yalign(0.0)
+            .css_classes(["monospace", "caption"])
+            .build();
+        let copy_button = Button::builder()
+            .label(t!("error_dialog.copy"))
+            .css_classes(["pill"])
+            .build();
+        let report_button = Button::builder()
+            .label(t!("error_dialog.report"))
+            .css_classes(["pill"])
+            .build();
+        let dialog = Self::build_dialog(&details_label, &copy_button, &report_button);
 
-        Self { dialog }
+        Self {
+            dialog,
+            details_label,
+            copy_button,
+            report_button,
+            details: Rc::new(RefCell::new(String::new())),
+            summary: Rc::new(RefCell::new(String::new())),
+        }
     }
 
     pub fn init(&self, app: &Rc<App>) {
         self.connect_dialog(app);
+        self.connect_copy_button();
+        self.connect_report_button(app);
     }
 
     pub fn show(&self, app: &Rc<App>, error: &Error) {
+        let details = Self::build_details(app, error);
+        self.details_label.set_label(&format!("{error:?}"));
+        *self.details.borrow_mut() = details;
+        *self.summary.borrow_mut() = error.to_string();
+        self.copy_button.set_label(&t!("error_dialog.copy"));
+
         self.dialog.set_body(&error.to_string());
         self.dialog.present(Some(&app.window.adw_window));
     }
 
-    fn build_dialog() -> AlertDialog {
-        let content_box = gtk::Box::new(Orientation::Horizontal, 0);
+    fn build_dialog(
+        details_label: &Label,
+        copy_button: &Button,
+        report_button: &Button,
+    ) -> AlertDialog {
+        let scrolled_window = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .max_content_height(Self::DETAILS_MAX_HEIGHT)
+            .propagate_natural_height(true)
+            .child(details_label)
+            .build();
+        let expander = Expander::builder()
+            .label(t!("error_dialog.details"))
+            .child(&scrolled_window)
+            .build();
+
+        let button_box = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .halign(Align::Center)
+            .build();
+        button_box.append(copy_button);
+        button_box.append(report_button);
+
+        let content_box = gtk::Box::new(Orientation::Vertical, 12);
+        content_box.append(&expander);
+        content_box.append(&button_box);
+
         let dialog = AlertDialog::builder()
-            .heading(format!("{} Error:", config::APP_NAME.get_value()))
+            .heading(t!(
+                "error_dialog.heading",
+                app_name = config::APP_NAME.get_value()
+            ))
             .extra_child(&content_box)
             .build();
-        dialog.add_response(Self::DIALOG_EXIT, "Close");
+        dialog.add_response(Self::DIALOG_EXIT, &t!("error_dialog.close"));
         dialog.set_response_appearance(Self::DIALOG_EXIT, ResponseAppearance::Destructive);
         dialog.set_default_response(Some(Self::DIALOG_EXIT));
 
         dialog
     }
 
+    /// Everything a bug report needs, without the url or name of any web app
+    fn build_details(app: &Rc<App>, error: &Error) -> String {
+        let mut details = format!("{error:?}\n\n");
+        let _ = writeln!(details, "Version: {}", config::VERSION.get_value());
+        let _ = writeln!(details, "Flatpak: {}", utils::env::is_flatpak_container());
+        let _ = writeln!(details, "Dev container: {}", utils::env::is_devcontainer());
+
+        let browsers: Vec<String> = app
+            .browser_configs
+            .get_all_browsers()
+            .iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .map(|browser| {
+                format!(
+                    "- {} ({})",
+                    browser.get_name_with_installation(),
+                    browser.id
+                )
+            })
+            .collect();
+        if browsers.is_empty() {
+            let _ = writeln!(details, "Browsers: none detected");
+        } else {
+            let _ = writeln!(details, "Browsers:\n{}", browsers.join("\n"));
+        }
+
+        details
+    }
+
     fn connect_dialog(&self, app: &Rc<App>) {
         let app_clone = app.clone();
         self.dialog
@@ -52,4 +155,38 @@ impl ErrorDialog {
                 app_clone.close();
             });
     }
+
+    /// The gdk clipboard, so it also works on Wayland
+    fn connect_copy_button(&self) {
+        let details = self.details.clone();
+        self.copy_button.connect_clicked(move |button| {
+            button.clipboard().set_text(&details.borrow());
+            button.set_label(&t!("error_dialog.copied"));
+        });
+    }
+
+    fn connect_report_button(&self, app: &Rc<App>) {
+        let summary = self.summary.clone();
+        let app_clone = app.clone();
+        self.report_button.connect_clicked(move |_| {
+            let Ok(mut issue_url) = Url::parse(&format!("{}/new", config::ISSUES_URL.get_value()))
+            else {
+                error!("Invalid issues url: {}", config::ISSUES_URL.get_value());
+                return;
+            };
+            issue_url
+                .query_pairs_mut()
+                .append_pair("title", summary.borrow().lines().next().unwrap_or_default());
+
+            UriLauncher::new(issue_url.as_str()).launch(
+                Some(&app_clone.window.adw_window),
+                None::<&Cancellable>,
+                |result| {
+                    if let Err(error) = result {
+                        error!("Failed to open issues url: {error:?}");
+                    }
+                },
+            );
+        });
+    }
 }
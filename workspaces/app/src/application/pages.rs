@@ -1,10 +1,14 @@
 mod browsers;
+mod diagnostics;
 mod home;
 mod info;
-mod web_apps;
+mod logs;
+mod preferences;
+pub mod web_apps;
 
 use crate::application::App;
 use browsers::BrowsersPage;
+use diagnostics::DiagnosticsPage;
 use home::HomePage;
 use info::InfoPage;
 use libadwaita::{
@@ -13,6 +17,7 @@ use libadwaita::{
     gtk::{self, Image, Orientation, ScrolledWindow, prelude::WidgetExt},
     prelude::ActionRowExt,
 };
+use logs::LogsPage;
 use std::rc::Rc;
 use web_apps::WebAppsPage;
 
@@ -22,13 +27,46 @@ pub enum Page {
     Home,
     WebApps,
     Browsers,
+    Preferences,
+    Diagnostics,
+    Logs,
     Info,
 }
+impl Page {
+    /// Stored in the cached settings, don't change existing keys
+    pub fn get_key(&self) -> &'static str {
+        match self {
+            Self::Home => "home",
+            Self::WebApps => "web_apps",
+            Self::Browsers => "browsers",
+            Self::Preferences => "preferences",
+            Self::Diagnostics => "diagnostics",
+            Self::Logs => "logs",
+            Self::Info => "info",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "home" => Some(Self::Home),
+            "web_apps" => Some(Self::WebApps),
+            "browsers" => Some(Self::Browsers),
+            "preferences" => Some(Self::Preferences),
+            "diagnostics" => Some(Self::Diagnostics),
+            "logs" => Some(Self::Logs),
+            "info" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
 
 pub struct Pages {
     home: Rc<HomePage>,
     web_apps: Rc<WebAppsPage>,
     browsers: Rc<BrowsersPage>,
+    preferences: Rc<preferences::PreferencesPage>,
+    diagnostics: Rc<DiagnosticsPage>,
+    logs: Rc<LogsPage>,
     info: Rc<InfoPage>,
 }
 #[allow(clippy::unused_self)]
@@ -38,6 +76,9 @@ impl Pages {
             home: HomePage::new(),
             web_apps: WebAppsPage::new(),
             browsers: BrowsersPage::new(),
+            preferences: preferences::PreferencesPage::new(),
+            diagnostics: DiagnosticsPage::new(),
+            logs: LogsPage::new(),
             info: InfoPage::new(),
         }
     }
@@ -46,12 +87,18 @@ impl Pages {
         self.home.init(app);
         self.web_apps.init(app);
         self.browsers.init(app);
+        self.preferences.init(app);
+        self.diagnostics.init(app);
+        self.logs.init(app);
         self.info.init(app);
 
         let sidebar = &app.window.view.sidebar;
         sidebar.add_nav_row(app.clone(), Page::Home);
         sidebar.add_nav_row(app.clone(), Page::WebApps);
         sidebar.add_nav_row(app.clone(), Page::Browsers);
+        sidebar.add_nav_row(app.clone(), Page::Preferences);
+        sidebar.add_nav_row(app.clone(), Page::Diagnostics);
+        sidebar.add_nav_row(app.clone(), Page::Logs);
         sidebar.add_nav_row(app.clone(), Page::Info);
     }
 
@@ -60,9 +107,16 @@ impl Pages {
             Page::Home => self.home.clone(),
             Page::WebApps => self.web_apps.clone(),
             Page::Browsers => self.browsers.clone(),
+            Page::Preferences => self.preferences.clone(),
+            Page::Diagnostics => self.diagnostics.clone(),
+            Page::Logs => self.logs.clone(),
             Page::Info => self.info.clone(),
         }
     }
+
+    pub fn get_web_apps(&self) -> Rc<WebAppsPage> {
+        self.web_apps.clone()
+    }
 }
 
 struct ContentPage {
@@ -82,6 +136,8 @@ struct PrefNavPage {
     nav_row: ActionRow,
     nav_view: NavigationView,
     prefs_page: PreferencesPage,
+    toast_overlay: ToastOverlay,
+    header: HeaderBar,
 }
 pub struct PageBuilder {
     nav_page: NavigationPage,
@@ -135,7 +191,9 @@ impl PageBuilder {
         let nav_view = NavigationView::new();
         let prefs_page = PreferencesPage::new();
         let nav_view_page = NavigationPage::builder().child(&nav_view).build();
-        self.toolbar.set_content(Some(&prefs_page));
+        let toast_overlay = ToastOverlay::new();
+        toast_overlay.set_child(Some(&prefs_page));
+        self.toolbar.set_content(Some(&toast_overlay));
         nav_view.add(&self.nav_page);
 
         PrefNavPage {
@@ -143,6 +201,8 @@ impl PageBuilder {
             nav_row: self.nav_row,
             nav_view,
             prefs_page,
+            toast_overlay,
+            header: self.header,
         }
     }
 }
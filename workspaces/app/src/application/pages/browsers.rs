@@ -1,21 +1,33 @@
+mod browser_editor;
+mod template_editor;
+
 use super::NavPage;
-use crate::application::{App, pages::PrefPage};
+use crate::application::{App, pages::PrefNavPage};
+use browser_editor::BrowserEditor;
 use common::browsers::{Base, Browser};
 use gtk::{
-    Align, Label, Orientation,
-    prelude::{BoxExt, WidgetExt},
+    Align, Button, Label, Orientation, glib,
+    prelude::{BoxExt, ButtonExt, WidgetExt},
 };
 use libadwaita::{
-    ActionRow, ExpanderRow, NavigationPage, PreferencesGroup, PreferencesPage, StatusPage,
+    ActionRow, ExpanderRow, HeaderBar, NavigationPage, NavigationView, PreferencesGroup,
+    PreferencesPage, StatusPage, Toast, ToastOverlay, ToastPriority,
     prelude::{ExpanderRowExt, PreferencesGroupExt, PreferencesPageExt},
 };
 use std::fmt::Write as _;
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
+use template_editor::TemplateEditor;
 
 pub struct BrowsersPage {
     nav_page: NavigationPage,
     nav_row: ActionRow,
+    nav_view: Rc<NavigationView>,
     prefs_page: PreferencesPage,
+    pref_groups: RefCell<Vec<PreferencesGroup>>,
+    toast_overlay: ToastOverlay,
+    header: HeaderBar,
+    refresh_button: Button,
+    add_button: Button,
 }
 impl NavPage for BrowsersPage {
     fn get_navpage(&self) -> &NavigationPage {
@@ -27,38 +39,136 @@ impl NavPage for BrowsersPage {
     }
 }
 impl BrowsersPage {
+    const TOAST_MESSAGE_TIMEOUT: u32 = 4;
+
     pub fn new() -> Rc<Self> {
         let title = t!("browsers.title");
         let icon = "web-browser-symbolic";
 
-        let PrefPage {
+        let PrefNavPage {
             nav_page,
             nav_row,
+            nav_view,
             prefs_page,
-            ..
-        } = Self::build_nav_page(&title, icon).with_preference_page();
+            toast_overlay,
+            header,
+        } = Self::build_nav_page(&title, icon).with_preference_navigation_view();
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text(t!("browsers.refresh.button"))
+            .build();
+        let add_button = Button::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text(t!("browsers.editor.button"))
+            .build();
 
         Rc::new(Self {
             nav_page,
             nav_row,
+            nav_view: Rc::new(nav_view),
             prefs_page,
+            pref_groups: RefCell::new(Vec::new()),
+            toast_overlay,
+            header,
+            refresh_button,
+            add_button,
         })
     }
 
     pub fn init(self: &Rc<Self>, app: &Rc<App>) {
-        let browser_pref_groups = Self::build_browser_sections(app);
+        self.header.pack_start(&self.add_button);
+        self.header.pack_end(&self.refresh_button);
+        self.connect_add_button(app);
+        self.connect_refresh_button(app);
+        self.build_browser_pref_groups(app);
+
+        // Also after the editor saved a config and the background check at startup
+        let self_weak = Rc::downgrade(self);
+        let app_clone = app.clone();
+        app.browser_configs.connect_changed(move || {
+            let Some(self_clone) = self_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            self_clone.build_browser_pref_groups(&app_clone);
+            self_clone.show_load_errors(&app_clone);
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// A config that was edited by hand and can't be read is named, instead of just missing
+    fn show_load_errors(&self, app: &Rc<App>) {
+        for load_error in app.browser_configs.take_load_errors() {
+            let toast = Toast::builder()
+                .title(t!(
+                    "browsers.load_error",
+                    file = load_error.file_name,
+                    error = load_error.error
+                ))
+                .priority(ToastPriority::High)
+                .build();
+            self.toast_overlay.add_toast(toast);
+        }
+    }
+
+    fn connect_add_button(self: &Rc<Self>, app: &Rc<App>) {
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+
+        self.add_button.connect_clicked(move |_| {
+            let browser_editor = BrowserEditor::new(&app_clone, &self_clone.nav_view);
+            browser_editor.init();
+            self_clone.nav_view.push(browser_editor.get_navpage());
+        });
+    }
 
-        for pref_group in browser_pref_groups {
-            self.prefs_page.add(&pref_group);
+    fn build_browser_pref_groups(self: &Rc<Self>, app: &Rc<App>) {
+        for pref_group in self.pref_groups.borrow_mut().drain(..) {
+            self.prefs_page.remove(&pref_group);
         }
+
+        let browser_pref_groups = self.build_browser_sections(app);
+
+        for pref_group in &browser_pref_groups {
+            self.prefs_page.add(pref_group);
+        }
+        *self.pref_groups.borrow_mut() = browser_pref_groups;
     }
 
-    fn build_browser_sections(app: &Rc<App>) -> Vec<PreferencesGroup> {
+    fn connect_refresh_button(self: &Rc<Self>, app: &Rc<App>) {
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+
+        self.refresh_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            app_clone.announce(&t!("browsers.detecting"));
+
+            let self_clone = self_clone.clone();
+            let app_clone = app_clone.clone();
+            let button = button.clone();
+
+            glib::spawn_future_local(async move {
+                app_clone.browser_configs.refresh().await;
+                button.set_sensitive(true);
+
+                let toast = Toast::new(&t!("browsers.refresh.done"));
+                toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+                self_clone.toast_overlay.add_toast(toast);
+            });
+        });
+    }
+
+    fn build_browser_sections(self: &Rc<Self>, app: &Rc<App>) -> Vec<PreferencesGroup> {
         let flatpak_browsers = app.browser_configs.get_flatpak_browsers();
         let system_browsers = app.browser_configs.get_system_browsers();
+        let snap_browsers = app.browser_configs.get_snap_browsers();
+        let custom_browsers = app.browser_configs.get_custom_browsers();
         let uninstalled_browsers = app.browser_configs.get_uninstalled_browsers();
 
-        if flatpak_browsers.is_empty() && system_browsers.is_empty() {
+        if flatpak_browsers.is_empty()
+            && system_browsers.is_empty()
+            && snap_browsers.is_empty()
+            && custom_browsers.is_empty()
+        {
             let status_page = StatusPage::builder()
                 .title(t!("browsers.no_browsers.title"))
                 .description(t!("browsers.no_browsers.description"))
@@ -71,25 +181,49 @@ impl BrowsersPage {
             return Vec::from([pref_group]);
         }
 
-        let flatpak_pref_group = PreferencesGroup::builder().title("Flatpak").build();
-        let system_pref_group = PreferencesGroup::builder().title("System").build();
+        let flatpak_pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.sections.flatpak"))
+            .build();
+        let system_pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.sections.system"))
+            .build();
+        let snap_pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.sections.snap"))
+            .build();
+        let custom_pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.sections.custom"))
+            .build();
         let uninstalled_pref_group = PreferencesGroup::builder()
             .title(t!("browsers.not_installed.title"))
             .build();
 
         for browser in &flatpak_browsers {
-            let browser_row = Self::build_browser_row(app, browser);
+            let browser_row = self.build_browser_row(app, browser);
             flatpak_pref_group.add(&browser_row);
         }
         for browser in &system_browsers {
-            let browser_row = Self::build_browser_row(app, browser);
+            let browser_row = self.build_browser_row(app, browser);
             system_pref_group.add(&browser_row);
         }
+        for browser in &snap_browsers {
+            let browser_row = self.build_browser_row(app, browser);
+            snap_pref_group.add(&browser_row);
+        }
+        for browser in &custom_browsers {
+            let browser_row = self.build_browser_row(app, browser);
+            custom_pref_group.add(&browser_row);
+        }
         for browser in &uninstalled_browsers {
-            let browser_row = Self::build_browser_row(app, browser);
+            let browser_row = self.build_browser_row(app, browser);
             uninstalled_pref_group.add(&browser_row);
         }
 
+        if snap_browsers.is_empty() {
+            snap_pref_group.set_visible(false);
+        }
+        if custom_browsers.is_empty() {
+            custom_pref_group.set_visible(false);
+        }
         if uninstalled_browsers.is_empty() {
             uninstalled_pref_group.set_visible(false);
         }
@@ -97,22 +231,28 @@ impl BrowsersPage {
         Vec::from([
             flatpak_pref_group,
             system_pref_group,
+            snap_pref_group,
+            custom_pref_group,
             uninstalled_pref_group,
         ])
     }
 
-    fn build_browser_row(app: &Rc<App>, browser: &Browser) -> ExpanderRow {
+    fn build_browser_row(self: &Rc<Self>, app: &Rc<App>, browser: &Rc<Browser>) -> ExpanderRow {
         let row = ExpanderRow::builder().title(&browser.name).build();
         row.add_prefix(&browser.get_icon());
 
-        let browser_expand = Self::build_browser_expand_content(app, browser);
+        let browser_expand = self.build_browser_expand_content(app, browser);
         row.add_row(&browser_expand);
 
         row
     }
 
     #[allow(clippy::too_many_lines)]
-    fn build_browser_expand_content(app: &Rc<App>, browser: &Browser) -> gtk::Box {
+    fn build_browser_expand_content(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        browser: &Rc<Browser>,
+    ) -> gtk::Box {
         let content_box = gtk::Box::new(Orientation::Vertical, 12);
         content_box.set_margin_top(12);
         content_box.set_margin_bottom(12);
@@ -161,6 +301,28 @@ impl BrowsersPage {
             content_box.append(&executable_label);
         }
 
+        if browser.is_snap()
+            && let Some(snap_name) = &browser.snap_name
+        {
+            let snap_label = Label::builder()
+                .label(snap_name)
+                .css_classes(["subtitle"])
+                .valign(Align::Center)
+                .build();
+            content_box.append(&snap_label);
+        }
+
+        if browser.is_custom()
+            && let Some(custom_bin) = &browser.custom_bin
+        {
+            let custom_label = Label::builder()
+                .label(custom_bin.to_string_lossy())
+                .css_classes(["subtitle"])
+                .valign(Align::Center)
+                .build();
+            content_box.append(&custom_label);
+        }
+
         let mut capabilities_list = String::new();
         if browser.can_isolate {
             let _ = writeln!(
@@ -176,6 +338,27 @@ impl BrowsersPage {
                 t!("browsers.capabilities.maximize")
             );
         }
+        if browser.can_fullscreen {
+            let _ = writeln!(
+                capabilities_list,
+                "• {}",
+                t!("browsers.capabilities.fullscreen")
+            );
+        }
+        if browser.can_private {
+            let _ = writeln!(
+                capabilities_list,
+                "• {}",
+                t!("browsers.capabilities.private")
+            );
+        }
+        if browser.can_force_dark {
+            let _ = writeln!(
+                capabilities_list,
+                "• {}",
+                t!("browsers.capabilities.force_dark")
+            );
+        }
         match browser.base {
             Base::None => {}
             Base::Chromium => {
@@ -214,14 +397,9 @@ impl BrowsersPage {
             content_box.append(&more_info_label);
         }
 
-        let issues_from_locale = browser
-            .issues
-            .get(&app.locale.current)
-            .or(browser.issues.get(&app.locale.default));
+        let issues = browser.get_issues(&app.locale.current, &app.locale.default);
 
-        if let Some(issues) = issues_from_locale
-            && !issues.is_empty()
-        {
+        if !issues.is_empty() {
             let mut markup_issues = String::new();
             for issue in issues {
                 let _ = writeln!(markup_issues, "• {issue}");
@@ -242,6 +420,32 @@ impl BrowsersPage {
             content_box.append(&issues_list_label);
         }
 
+        let template_button = Button::builder()
+            .label(t!("browsers.template.button"))
+            .css_classes(["pill"])
+            .halign(Align::Center)
+            .build();
+        self.connect_template_button(app, browser, &template_button);
+        content_box.append(&template_button);
+
         content_box
     }
+
+    fn connect_template_button(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        browser: &Rc<Browser>,
+        template_button: &Button,
+    ) {
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let browser_clone = browser.clone();
+
+        template_button.connect_clicked(move |_| {
+            let template_editor =
+                TemplateEditor::new(&app_clone, &self_clone.nav_view, &browser_clone);
+            template_editor.init();
+            self_clone.nav_view.push(template_editor.get_navpage());
+        });
+    }
 }
@@ -0,0 +1,240 @@
+use crate::application::{
+    App,
+    pages::{NavPage, PrefPage},
+};
+use common::browsers::{Base, BrowserConfigs, UserBrowserConfig};
+use gtk::{
+    Align, Button, StringList, TextView, WrapMode, glib,
+    prelude::{ButtonExt, EditableExt, TextBufferExt, TextViewExt, WidgetExt},
+};
+use libadwaita::{
+    ActionRow, ComboRow, EntryRow, NavigationPage, NavigationView, PreferencesGroup,
+    PreferencesPage, SwitchRow, Toast, ToastOverlay, ToastPriority,
+    prelude::{ComboRowExt, PreferencesGroupExt, PreferencesPageExt},
+};
+use std::{cell::RefCell, rc::Rc};
+use tracing::{debug, error};
+
+/// Form for a user browser config, saved next to the shipped configs
+pub struct BrowserEditor {
+    nav_page: NavigationPage,
+    nav_view: Rc<NavigationView>,
+    app: Rc<App>,
+    prefs_page: PreferencesPage,
+    toast_overlay: ToastOverlay,
+    name_row: EntryRow,
+    flatpak_row: EntryRow,
+    system_bin_row: EntryRow,
+    base_row: ComboRow,
+    isolate_row: SwitchRow,
+    maximize_row: SwitchRow,
+    fullscreen_row: SwitchRow,
+    private_row: SwitchRow,
+    force_dark_row: SwitchRow,
+    desktop_file_name_prefix_row: EntryRow,
+    template_view: TextView,
+    save_button: Button,
+}
+impl NavPage for BrowserEditor {
+    fn get_navpage(&self) -> &NavigationPage {
+        &self.nav_page
+    }
+
+    fn get_nav_row(&self) -> Option<&ActionRow> {
+        None
+    }
+}
+impl BrowserEditor {
+    const TOAST_MESSAGE_TIMEOUT: u32 = 4;
+    /// Order of the base row
+    const BASES: [Base; 2] = [Base::Chromium, Base::Firefox];
+
+    pub fn new(app: &Rc<App>, nav_view: &Rc<NavigationView>) -> Rc<Self> {
+        let title = t!("browsers.editor.title");
+        let icon = "web-browser-symbolic";
+        let PrefPage {
+            nav_page,
+            prefs_page,
+            toast_overlay,
+            ..
+        } = Self::build_nav_page(&title, icon).with_preference_page();
+
+        let name_row = EntryRow::builder()
+            .title(t!("browsers.editor.name"))
+            .build();
+        let flatpak_row = EntryRow::builder()
+            .title(t!("browsers.editor.flatpak"))
+            .build();
+        let system_bin_row = EntryRow::builder()
+            .title(t!("browsers.editor.system_bin"))
+            .build();
+        let base_row = ComboRow::builder()
+            .title(t!("browsers.editor.base"))
+            .model(&StringList::new(&["Chromium", "Firefox"]))
+            .build();
+        let isolate_row = SwitchRow::builder()
+            .title(t!("browsers.editor.isolate"))
+            .active(true)
+            .build();
+        let maximize_row = SwitchRow::builder()
+            .title(t!("browsers.editor.maximize"))
+            .active(true)
+            .build();
+        let fullscreen_row = SwitchRow::builder()
+            .title(t!("browsers.editor.fullscreen"))
+            .active(true)
+            .build();
+        let private_row = SwitchRow::builder()
+            .title(t!("browsers.editor.private"))
+            .active(true)
+            .build();
+        let force_dark_row = SwitchRow::builder()
+            .title(t!("browsers.editor.force_dark"))
+            .build();
+        let desktop_file_name_prefix_row = EntryRow::builder()
+            .title(t!("browsers.editor.desktop_file_name_prefix"))
+            .build();
+        let template_view = TextView::builder()
+            .monospace(true)
+            .wrap_mode(WrapMode::WordChar)
+            .top_margin(12)
+            .bottom_margin(12)
+            .left_margin(12)
+            .right_margin(12)
+            .css_classes(["card"])
+            .build();
+        let save_button = Button::builder()
+            .label(t!("browsers.editor.save"))
+            .css_classes(["suggested-action", "pill"])
+            .halign(Align::Center)
+            .margin_top(12)
+            .build();
+
+        Rc::new(Self {
+            nav_page,
+            nav_view: nav_view.clone(),
+            app: app.clone(),
+            prefs_page,
+            toast_overlay,
+            name_row,
+            flatpak_row,
+            system_bin_row,
+            base_row,
+            isolate_row,
+            maximize_row,
+            fullscreen_row,
+            private_row,
+            force_dark_row,
+            desktop_file_name_prefix_row,
+            template_view,
+            save_button,
+        })
+    }
+
+    pub fn init(self: &Rc<Self>) {
+        let general_pref_group = PreferencesGroup::new();
+        general_pref_group.add(&self.name_row);
+        general_pref_group.add(&self.flatpak_row);
+        general_pref_group.add(&self.system_bin_row);
+        general_pref_group.add(&self.base_row);
+        general_pref_group.add(&self.isolate_row);
+        general_pref_group.add(&self.maximize_row);
+        general_pref_group.add(&self.fullscreen_row);
+        general_pref_group.add(&self.private_row);
+        general_pref_group.add(&self.force_dark_row);
+
+        let desktop_file_pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.editor.desktop_file.title"))
+            .description(t!("browsers.editor.desktop_file.description"))
+            .build();
+        desktop_file_pref_group.add(&self.desktop_file_name_prefix_row);
+        desktop_file_pref_group.add(&self.template_view);
+        desktop_file_pref_group.add(&self.save_button);
+
+        self.prefs_page.add(&general_pref_group);
+        self.prefs_page.add(&desktop_file_pref_group);
+
+        self.set_template(None);
+        self.connect_base_row();
+        self.connect_save_button();
+    }
+
+    fn get_base(self: &Rc<Self>) -> Base {
+        usize::try_from(self.base_row.selected())
+            .ok()
+            .and_then(|index| Self::BASES.get(index).copied())
+            .unwrap_or(Base::None)
+    }
+
+    fn get_template_text(self: &Rc<Self>) -> String {
+        let buffer = self.template_view.buffer();
+        buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), false)
+            .to_string()
+    }
+
+    /// Only replaces the template when it is unchanged from the previous base
+    fn set_template(self: &Rc<Self>, previous_base: Option<Base>) {
+        let previous_template = previous_base
+            .and_then(BrowserConfigs::get_desktop_template)
+            .unwrap_or_default();
+        if previous_base.is_some() && self.get_template_text() != previous_template {
+            debug!("Desktop file template is edited, keeping it");
+            return;
+        }
+
+        let template = BrowserConfigs::get_desktop_template(self.get_base()).unwrap_or_default();
+        self.template_view.buffer().set_text(template);
+    }
+
+    fn connect_base_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let previous_base = RefCell::new(self.get_base());
+
+        self.base_row.connect_selected_notify(move |_| {
+            self_clone.set_template(Some(*previous_base.borrow()));
+            *previous_base.borrow_mut() = self_clone.get_base();
+        });
+    }
+
+    fn connect_save_button(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.save_button.connect_clicked(move |button| {
+            let user_config = UserBrowserConfig {
+                name: self_clone.name_row.text().to_string(),
+                flatpak: Some(self_clone.flatpak_row.text().to_string()),
+                system_bin: Some(self_clone.system_bin_row.text().to_string()),
+                base: self_clone.get_base(),
+                can_isolate: self_clone.isolate_row.is_active(),
+                can_start_maximized: self_clone.maximize_row.is_active(),
+                can_fullscreen: self_clone.fullscreen_row.is_active(),
+                can_private: self_clone.private_row.is_active(),
+                can_force_dark: self_clone.force_dark_row.is_active(),
+                desktop_file_name_prefix: self_clone
+                    .desktop_file_name_prefix_row
+                    .text()
+                    .to_string(),
+                desktop_template: self_clone.get_template_text(),
+            };
+
+            if let Err(error) = self_clone.app.browser_configs.add_user_config(&user_config) {
+                error!("{error:?}");
+                let toast = Toast::new(&t!("browsers.editor.save_failed", error = error));
+                toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+                toast.set_priority(ToastPriority::High);
+                self_clone.toast_overlay.dismiss_all();
+                self_clone.toast_overlay.add_toast(toast);
+                return;
+            }
+
+            button.set_sensitive(false);
+            let self_clone = self_clone.clone();
+
+            glib::spawn_future_local(async move {
+                self_clone.app.browser_configs.refresh().await;
+                self_clone.nav_view.pop();
+            });
+        });
+    }
+}
@@ -0,0 +1,251 @@
+use crate::application::{
+    App,
+    pages::{NavPage, PrefPage},
+};
+use common::{
+    browsers::{Browser, BrowserConfigs, config_layers::ConfigLayer},
+    desktop_file::DesktopFile,
+};
+use gtk::{
+    Align, Button, Label, Orientation, TextView, WrapMode,
+    prelude::{BoxExt, ButtonExt, TextBufferExt, TextViewExt, WidgetExt},
+};
+use libadwaita::{
+    ActionRow, NavigationPage, NavigationView, PreferencesGroup, PreferencesPage, Toast,
+    ToastOverlay, ToastPriority,
+    prelude::{PreferencesGroupExt, PreferencesPageExt},
+};
+use std::rc::Rc;
+use tracing::error;
+
+/// Desktop file of a browser with a preview for an example app. Saved in the override layer,
+/// the config watcher reloads the browsers.
+pub struct TemplateEditor {
+    nav_page: NavigationPage,
+    nav_view: Rc<NavigationView>,
+    app: Rc<App>,
+    browser: Rc<Browser>,
+    prefs_page: PreferencesPage,
+    toast_overlay: ToastOverlay,
+    template_pref_group: PreferencesGroup,
+    template_view: TextView,
+    preview_view: TextView,
+    error_label: Label,
+    save_button: Button,
+    restore_button: Button,
+}
+impl NavPage for TemplateEditor {
+    fn get_navpage(&self) -> &NavigationPage {
+        &self.nav_page
+    }
+
+    fn get_nav_row(&self) -> Option<&ActionRow> {
+        None
+    }
+}
+impl TemplateEditor {
+    const TOAST_MESSAGE_TIMEOUT: u32 = 4;
+
+    pub fn new(app: &Rc<App>, nav_view: &Rc<NavigationView>, browser: &Rc<Browser>) -> Rc<Self> {
+        let title = t!("browsers.template.title", browser = browser.name);
+        let icon = "text-editor-symbolic";
+        let PrefPage {
+            nav_page,
+            prefs_page,
+            toast_overlay,
+            ..
+        } = Self::build_nav_page(&title, icon).with_preference_page();
+
+        let template_pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.template.template"))
+            .build();
+        let build_text_view = || {
+            TextView::builder()
+                .monospace(true)
+                .wrap_mode(WrapMode::WordChar)
+                .top_margin(12)
+                .bottom_margin(12)
+                .left_margin(12)
+                .right_margin(12)
+                .css_classes(["card"])
+                .build()
+        };
+        let template_view = build_text_view();
+        let preview_view = build_text_view();
+        preview_view.set_editable(false);
+        preview_view.set_cursor_visible(false);
+
+        let error_label = Label::builder()
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["error"])
+            .visible(false)
+            .build();
+        let save_button = Button::builder()
+            .label(t!("browsers.template.save"))
+            .css_classes(["suggested-action", "pill"])
+            .build();
+        let restore_button = Button::builder()
+            .label(t!("browsers.template.restore"))
+            .css_classes(["pill"])
+            .sensitive(BrowserConfigs::has_shipped_desktop_template(browser))
+            .build();
+
+        Rc::new(Self {
+            nav_page,
+            nav_view: nav_view.clone(),
+            app: app.clone(),
+            browser: browser.clone(),
+            prefs_page,
+            toast_overlay,
+            template_pref_group,
+            template_view,
+            preview_view,
+            error_label,
+            save_button,
+            restore_button,
+        })
+    }
+
+    pub fn init(self: &Rc<Self>) {
+        let button_box = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .halign(Align::Center)
+            .margin_top(12)
+            .build();
+        button_box.append(&self.restore_button);
+        button_box.append(&self.save_button);
+
+        self.template_pref_group.add(&self.template_view);
+        self.template_pref_group.add(&self.error_label);
+        self.template_pref_group.add(&button_box);
+
+        let preview_pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.template.preview.title"))
+            .description(t!("browsers.template.preview.description"))
+            .build();
+        preview_pref_group.add(&self.preview_view);
+
+        self.prefs_page.add(&self.template_pref_group);
+        self.prefs_page.add(&preview_pref_group);
+
+        self.load_template();
+        self.connect_template_view();
+        self.connect_save_button();
+        self.connect_restore_button();
+    }
+
+    fn get_template_text(&self) -> String {
+        let buffer = self.template_view.buffer();
+        buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), false)
+            .to_string()
+    }
+
+    fn load_template(&self) {
+        match self
+            .app
+            .browser_configs
+            .read_desktop_template(&self.browser)
+        {
+            Ok((template, layer)) => {
+                let description = match layer {
+                    ConfigLayer::Shipped => t!("browsers.template.shipped"),
+                    ConfigLayer::Override => t!("browsers.template.override"),
+                };
+                self.template_pref_group
+                    .set_description(Some(description.as_ref()));
+                self.template_view.buffer().set_text(&template);
+            }
+            Err(error) => {
+                error!("{error:?}");
+                self.show_toast(&t!("browsers.template.read_failed", error = error));
+            }
+        }
+        self.update_preview();
+    }
+
+    /// The save button only works for a template that renders to a valid desktop file
+    fn update_preview(&self) {
+        let preview = DesktopFile::preview_browser_template(
+            &self.browser,
+            &self.get_template_text(),
+            &self.app.dirs,
+        );
+        self.preview_view.buffer().set_text(&preview.rendered);
+
+        let mut errors = Vec::new();
+        if !preview.unknown_keys.is_empty() {
+            let keys = preview
+                .unknown_keys
+                .iter()
+                .map(|key| format!("%{{{key}}}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            errors.push(t!("browsers.template.unknown_keys", keys = keys).to_string());
+        }
+        if let Some(parse_error) = &preview.parse_error {
+            errors.push(t!("browsers.template.parse_error", error = parse_error).to_string());
+        }
+
+        self.error_label.set_label(&errors.join("\n"));
+        self.error_label.set_visible(!errors.is_empty());
+        self.save_button.set_sensitive(preview.is_valid());
+    }
+
+    fn show_toast(&self, message: &str) {
+        let toast = Toast::new(message);
+        toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+        toast.set_priority(ToastPriority::High);
+        self.toast_overlay.dismiss_all();
+        self.toast_overlay.add_toast(toast);
+    }
+
+    fn connect_template_view(self: &Rc<Self>) {
+        let self_weak = Rc::downgrade(self);
+
+        self.template_view.buffer().connect_changed(move |_| {
+            if let Some(self_clone) = self_weak.upgrade() {
+                self_clone.update_preview();
+            }
+        });
+    }
+
+    fn connect_save_button(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.save_button.connect_clicked(move |_| {
+            if let Err(error) = self_clone
+                .app
+                .browser_configs
+                .save_desktop_template(&self_clone.browser, &self_clone.get_template_text())
+            {
+                error!("{error:?}");
+                self_clone.show_toast(&t!("browsers.template.save_failed", error = error));
+                return;
+            }
+
+            self_clone.nav_view.pop();
+        });
+    }
+
+    fn connect_restore_button(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.restore_button.connect_clicked(move |_| {
+            if let Err(error) = self_clone
+                .app
+                .browser_configs
+                .restore_desktop_template(&self_clone.browser)
+            {
+                error!("{error:?}");
+                self_clone.show_toast(&t!("browsers.template.restore_failed", error = error));
+                return;
+            }
+
+            self_clone.load_template();
+            self_clone.show_toast(&t!("browsers.template.restored"));
+        });
+    }
+}
@@ -0,0 +1,517 @@
+use crate::application::{
+    App,
+    pages::{NavPage, PrefPage},
+};
+use common::{
+    browsers::{Browser, Installation},
+    diagnostics::{self, DiagnosticsInput, DiagnosticsReport},
+};
+use gtk::{
+    Align, Button, FileLauncher, Image,
+    gio::{self, Cancellable},
+    glib,
+    prelude::{ButtonExt, Cast, WidgetExt},
+};
+use libadwaita::{
+    ActionRow, ExpanderRow, HeaderBar, NavigationPage, PreferencesGroup, PreferencesPage, Spinner,
+    Toast, ToastOverlay,
+    prelude::{ActionRowExt, ExpanderRowExt, PreferencesGroupExt, PreferencesPageExt},
+};
+use std::{cell::RefCell, fs, path::Path, rc::Rc};
+use tracing::{debug, error};
+
+#[derive(Clone, Copy)]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+impl CheckStatus {
+    fn get_icon_name(self) -> &'static str {
+        match self {
+            Self::Pass => "emblem-ok-symbolic",
+            Self::Warn => "dialog-warning-symbolic",
+            Self::Fail => "dialog-error-symbolic",
+        }
+    }
+
+    fn get_css_class(self) -> &'static str {
+        match self {
+            Self::Pass => "success",
+            Self::Warn => "warning",
+            Self::Fail => "error",
+        }
+    }
+
+    fn get_label(self) -> String {
+        match self {
+            Self::Pass => t!("diagnostics.status.pass"),
+            Self::Warn => t!("diagnostics.status.warn"),
+            Self::Fail => t!("diagnostics.status.fail"),
+        }
+        .to_string()
+    }
+}
+
+pub struct DiagnosticsPage {
+    nav_page: NavigationPage,
+    nav_row: ActionRow,
+    prefs_page: PreferencesPage,
+    pref_groups: RefCell<Vec<PreferencesGroup>>,
+    toast_overlay: ToastOverlay,
+    header: HeaderBar,
+    refresh_button: Button,
+    spinner: Spinner,
+    is_running: RefCell<bool>,
+    has_run: RefCell<bool>,
+}
+impl NavPage for DiagnosticsPage {
+    fn get_navpage(&self) -> &NavigationPage {
+        &self.nav_page
+    }
+
+    fn get_nav_row(&self) -> Option<&ActionRow> {
+        Some(&self.nav_row)
+    }
+}
+impl DiagnosticsPage {
+    const TOAST_MESSAGE_TIMEOUT: u32 = 4;
+
+    pub fn new() -> Rc<Self> {
+        let title = t!("diagnostics.title");
+        let icon = "system-run-symbolic";
+
+        let PrefPage {
+            nav_page,
+            nav_row,
+            prefs_page,
+            toast_overlay,
+            header,
+        } = Self::build_nav_page(&title, icon).with_preference_page();
+
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text(t!("diagnostics.refresh"))
+            .build();
+        let spinner = Spinner::builder().visible(false).build();
+
+        Rc::new(Self {
+            nav_page,
+            nav_row,
+            prefs_page,
+            pref_groups: RefCell::new(Vec::new()),
+            toast_overlay,
+            header,
+            refresh_button,
+            spinner,
+            is_running: RefCell::new(false),
+            has_run: RefCell::new(false),
+        })
+    }
+
+    pub fn init(self: &Rc<Self>, app: &Rc<App>) {
+        self.header.pack_end(&self.refresh_button);
+        self.header.pack_end(&self.spinner);
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.refresh_button
+            .connect_clicked(move |_| self_clone.run_checks(&app_clone));
+
+        // Commands are run on the host, only when the page is opened
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.nav_page.connect_map(move |_| {
+            if !*self_clone.has_run.borrow() {
+                self_clone.run_checks(&app_clone);
+            }
+        });
+    }
+
+    fn run_checks(self: &Rc<Self>, app: &Rc<App>) {
+        if *self.is_running.borrow() {
+            return;
+        }
+        *self.is_running.borrow_mut() = true;
+        *self.has_run.borrow_mut() = true;
+        self.refresh_button.set_sensitive(false);
+        self.spinner.set_visible(true);
+        app.announce(&t!("diagnostics.running"));
+
+        let input = DiagnosticsInput::new(&app.dirs);
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+
+        glib::spawn_future_local(async move {
+            match gio::spawn_blocking(move || diagnostics::run_checks(&input)).await {
+                Ok(report) => self_clone.build_pref_groups(&app_clone, &report),
+                Err(_) => error!("Diagnostics thread panicked"),
+            }
+
+            *self_clone.is_running.borrow_mut() = false;
+            self_clone.refresh_button.set_sensitive(true);
+            self_clone.spinner.set_visible(false);
+            app_clone.announce(&t!("diagnostics.done"));
+        });
+    }
+
+    fn build_pref_groups(self: &Rc<Self>, app: &Rc<App>, report: &DiagnosticsReport) {
+        debug!("Showing diagnostics");
+
+        for pref_group in self.pref_groups.borrow_mut().drain(..) {
+            self.prefs_page.remove(&pref_group);
+        }
+
+        let system_group = PreferencesGroup::builder()
+            .title(t!("diagnostics.system.title"))
+            .build();
+        // Not checked without access, launchers are not written there
+        if report.has_host_applications {
+            system_group.add(&self.build_user_applications_row(app, report));
+        }
+        if report.is_flatpak_container {
+            system_group.add(&self.build_host_applications_row(report));
+            system_group.add(&self.build_host_access_row(report));
+        }
+        system_group.add(&Self::build_flatpak_row(report));
+        system_group.add(&self.build_icons_dir_row(app, report));
+        system_group.add(&self.build_desktop_templates_row(app, report));
+
+        let browsers_group = PreferencesGroup::builder()
+            .title(t!("diagnostics.browsers.title"))
+            .build();
+        for row in Self::build_browser_rows(app, report) {
+            browsers_group.add(&row);
+        }
+
+        let pref_groups = vec![system_group, browsers_group];
+        for pref_group in &pref_groups {
+            self.prefs_page.add(pref_group);
+        }
+        *self.pref_groups.borrow_mut() = pref_groups;
+    }
+
+    fn build_user_applications_row(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        report: &DiagnosticsReport,
+    ) -> ActionRow {
+        let path = report.user_applications.display().to_string();
+        let title = t!("diagnostics.user_applications.title");
+
+        match &report.user_applications_error {
+            None => Self::build_row(
+                CheckStatus::Pass,
+                &title,
+                &t!("diagnostics.user_applications.pass", path = path),
+            ),
+            Some(error) => {
+                let row = Self::build_row(
+                    CheckStatus::Fail,
+                    &title,
+                    &t!(
+                        "diagnostics.user_applications.fail",
+                        path = path,
+                        error = error
+                    ),
+                );
+                row.add_suffix(&self.build_open_folder_button(app, &report.user_applications));
+                row
+            }
+        }
+    }
+
+    fn build_host_applications_row(self: &Rc<Self>, report: &DiagnosticsReport) -> ActionRow {
+        let title = t!("diagnostics.host_applications.title");
+
+        if report.has_host_applications {
+            return Self::build_row(
+                CheckStatus::Pass,
+                &title,
+                &t!("diagnostics.host_applications.pass"),
+            );
+        }
+
+        let row = Self::build_row(
+            CheckStatus::Warn,
+            &title,
+            &t!(
+                "diagnostics.host_applications.warn",
+                command = diagnostics::get_applications_override_command()
+            ),
+        );
+        row.add_suffix(&self.build_copy_button(
+            &t!("diagnostics.host_access.copy"),
+            &diagnostics::get_applications_override_command(),
+        ));
+        row
+    }
+
+    fn build_host_access_row(self: &Rc<Self>, report: &DiagnosticsReport) -> ActionRow {
+        let title = t!("diagnostics.host_access.title");
+
+        if report.has_host_access {
+            return Self::build_row(
+                CheckStatus::Pass,
+                &title,
+                &t!("diagnostics.host_access.pass"),
+            );
+        }
+
+        let row = Self::build_row(
+            CheckStatus::Fail,
+            &title,
+            &t!(
+                "diagnostics.host_access.fail",
+                command = diagnostics::get_flatpak_override_command()
+            ),
+        );
+        row.add_suffix(&self.build_copy_button(
+            &t!("diagnostics.host_access.copy"),
+            &diagnostics::get_flatpak_override_command(),
+        ));
+        row
+    }
+
+    fn build_flatpak_row(report: &DiagnosticsReport) -> ActionRow {
+        let title = t!("diagnostics.flatpak.title");
+
+        if report.has_flatpak {
+            Self::build_row(CheckStatus::Pass, &title, &t!("diagnostics.flatpak.pass"))
+        } else if !report.has_host_access {
+            Self::build_row(
+                CheckStatus::Warn,
+                &title,
+                &t!("diagnostics.flatpak.no_host_access"),
+            )
+        } else {
+            Self::build_row(CheckStatus::Warn, &title, &t!("diagnostics.flatpak.warn"))
+        }
+    }
+
+    fn build_icons_dir_row(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        report: &DiagnosticsReport,
+    ) -> ActionRow {
+        let path = report.app_data_icons.display().to_string();
+        let title = t!("diagnostics.icons_dir.title");
+
+        if report.has_icons_dir {
+            return Self::build_row(
+                CheckStatus::Pass,
+                &title,
+                &t!("diagnostics.icons_dir.pass", path = path),
+            );
+        }
+
+        let row = Self::build_row(
+            CheckStatus::Fail,
+            &title,
+            &t!("diagnostics.icons_dir.fail", path = path),
+        );
+        let create_button = Self::build_suffix_button(&t!("diagnostics.icons_dir.create"));
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let icons_dir = report.app_data_icons.clone();
+        create_button.connect_clicked(move |_| {
+            if let Err(error) = fs::create_dir_all(&icons_dir) {
+                error!(path = %icons_dir.display(), "Failed to create icons dir: {error:?}");
+                self_clone.show_toast(&t!(
+                    "diagnostics.icons_dir.create_failed",
+                    error = error.to_string()
+                ));
+                return;
+            }
+            self_clone.run_checks(&app_clone);
+        });
+        row.add_suffix(&create_button);
+
+        row
+    }
+
+    fn build_desktop_templates_row(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        report: &DiagnosticsReport,
+    ) -> ActionRow {
+        let title = t!("diagnostics.desktop_templates.title");
+
+        if report.desktop_template_errors.is_empty() {
+            return Self::build_row(
+                CheckStatus::Pass,
+                &title,
+                &t!(
+                    "diagnostics.desktop_templates.pass",
+                    count = report.desktop_template_count
+                ),
+            );
+        }
+
+        let errors: Vec<String> = report
+            .desktop_template_errors
+            .iter()
+            .map(|template_error| format!("{}: {}", template_error.file_name, template_error.error))
+            .collect();
+        let row = Self::build_row(
+            CheckStatus::Fail,
+            &title,
+            &format!(
+                "{}\n{}",
+                t!("diagnostics.desktop_templates.fail"),
+                errors.join("\n")
+            ),
+        );
+        row.add_suffix(
+            &self.build_open_folder_button(app, &app.dirs.app_config_browser_desktop_files),
+        );
+
+        row
+    }
+
+    /// Installed browsers in one row, every missing browser with the reason it was not found
+    fn build_browser_rows(app: &Rc<App>, report: &DiagnosticsReport) -> Vec<gtk::Widget> {
+        let mut rows = Vec::new();
+        let installed_browsers: Vec<Rc<Browser>> = app
+            .browser_configs
+            .get_all_browsers()
+            .into_iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .collect();
+        let uninstalled_browsers = app.browser_configs.get_uninstalled_browsers();
+        let title = t!("diagnostics.browsers.detected");
+
+        if installed_browsers.is_empty() {
+            let description = if report.is_flatpak_container && !report.has_host_access {
+                t!("diagnostics.browsers.no_host_access")
+            } else {
+                t!("diagnostics.browsers.none")
+            };
+            rows.push(Self::build_row(CheckStatus::Fail, &title, &description).upcast());
+        } else {
+            let names: Vec<String> = installed_browsers
+                .iter()
+                .map(|browser| browser.get_name_with_installation())
+                .collect();
+            rows.push(Self::build_row(CheckStatus::Pass, &title, &names.join("\n")).upcast());
+        }
+
+        if !uninstalled_browsers.is_empty() {
+            let expander_row = ExpanderRow::builder()
+                .title(t!(
+                    "diagnostics.browsers.not_detected",
+                    count = uninstalled_browsers.len()
+                ))
+                .subtitle(t!("diagnostics.browsers.not_detected_subtitle"))
+                .use_markup(false)
+                .build();
+            expander_row.add_prefix(&Self::build_status_icon(CheckStatus::Warn));
+
+            for browser in &uninstalled_browsers {
+                let reasons: Vec<String> = browser
+                    .not_found
+                    .iter()
+                    .map(Self::get_not_found_reason)
+                    .collect();
+                expander_row.add_row(&Self::build_row(
+                    CheckStatus::Warn,
+                    &browser.get_name(),
+                    &reasons.join("\n"),
+                ));
+            }
+            rows.push(expander_row.upcast());
+        }
+
+        rows
+    }
+
+    fn get_not_found_reason(installation: &Installation) -> String {
+        match installation {
+            Installation::Flatpak(flatpak) => {
+                t!("diagnostics.browsers.reason.flatpak", flatpak = flatpak)
+            }
+            Installation::System(system_bin) => {
+                t!(
+                    "diagnostics.browsers.reason.system",
+                    system_bin = system_bin
+                )
+            }
+            Installation::Snap(snap) => t!("diagnostics.browsers.reason.snap", snap = snap),
+            Installation::Custom(path) => t!(
+                "diagnostics.browsers.reason.custom",
+                path = path.display().to_string()
+            ),
+            Installation::None => t!("diagnostics.browsers.reason.none"),
+        }
+        .to_string()
+    }
+
+    fn build_row(status: CheckStatus, title: &str, description: &str) -> ActionRow {
+        let row = ActionRow::builder()
+            .title(title)
+            .subtitle(description)
+            .subtitle_selectable(true)
+            .use_markup(false)
+            .build();
+        row.add_prefix(&Self::build_status_icon(status));
+
+        row
+    }
+
+    fn build_status_icon(status: CheckStatus) -> Image {
+        let icon = Image::from_icon_name(status.get_icon_name());
+        icon.add_css_class(status.get_css_class());
+        icon.set_tooltip_text(Some(&status.get_label()));
+
+        icon
+    }
+
+    fn build_suffix_button(label: &str) -> Button {
+        Button::builder().label(label).valign(Align::Center).build()
+    }
+
+    fn build_copy_button(self: &Rc<Self>, label: &str, text: &str) -> Button {
+        let button = Self::build_suffix_button(label);
+
+        let self_clone = self.clone();
+        let text = text.to_string();
+        button.connect_clicked(move |button| {
+            button.clipboard().set_text(&text);
+            self_clone.show_toast(&t!("diagnostics.copied"));
+        });
+
+        button
+    }
+
+    fn build_open_folder_button(self: &Rc<Self>, app: &Rc<App>, path: &Path) -> Button {
+        let button = Self::build_suffix_button(&t!("diagnostics.open_folder"));
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let path = path.to_path_buf();
+        button.connect_clicked(move |_| {
+            let self_clone = self_clone.clone();
+            FileLauncher::new(Some(&gio::File::for_path(&path))).launch(
+                Some(&app_clone.window.adw_window),
+                None::<&Cancellable>,
+                move |result| {
+                    if let Err(error) = result {
+                        error!("Failed to open folder: {error:?}");
+                        self_clone.show_toast(&t!("diagnostics.open_folder_failed"));
+                    }
+                },
+            );
+        });
+
+        button
+    }
+
+    fn show_toast(&self, message: &str) {
+        let toast = Toast::builder()
+            .title(message)
+            .timeout(Self::TOAST_MESSAGE_TIMEOUT)
+            .build();
+        self.toast_overlay.add_toast(toast);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    application::{
+        App,
+        pages::{NavPage, PrefPage},
+    },
+    logging,
+};
+use gtk::{
+    Align, Button, FileLauncher, Orientation, PolicyType, ScrolledWindow, TextView, WrapMode,
+    gio::{self, Cancellable},
+    prelude::{BoxExt, ButtonExt, TextBufferExt, TextViewExt, WidgetExt},
+};
+use libadwaita::{
+    ActionRow, HeaderBar, NavigationPage, PreferencesGroup, PreferencesPage, SwitchRow, Toast,
+    ToastOverlay,
+    prelude::{PreferencesGroupExt, PreferencesPageExt},
+};
+use std::{fs, io, rc::Rc};
+use tracing::{Level, error};
+
+pub struct LogsPage {
+    nav_page: NavigationPage,
+    nav_row: ActionRow,
+    prefs_page: PreferencesPage,
+    toast_overlay: ToastOverlay,
+    header: HeaderBar,
+    refresh_button: Button,
+    debug_row: SwitchRow,
+    log_view: TextView,
+    copy_button: Button,
+    open_folder_button: Button,
+}
+impl NavPage for LogsPage {
+    fn get_navpage(&self) -> &NavigationPage {
+        &self.nav_page
+    }
+
+    fn get_nav_row(&self) -> Option<&ActionRow> {
+        Some(&self.nav_row)
+    }
+}
+impl LogsPage {
+    /// Enough for the last few hundred lines, a whole file is slow in a `TextView`
+    const TAIL_MAX_BYTES: u64 = 64 * 1024;
+    const LOG_VIEW_HEIGHT: i32 = 400;
+    const TOAST_MESSAGE_TIMEOUT: u32 = 4;
+
+    pub fn new() -> Rc<Self> {
+        let title = t!("logs.title");
+        let icon = "utilities-terminal-symbolic";
+
+        let PrefPage {
+            nav_page,
+            nav_row,
+            prefs_page,
+            toast_overlay,
+            header,
+        } = Self::build_nav_page(&title, icon).with_preference_page();
+
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text(t!("logs.refresh"))
+            .build();
+        let debug_row = SwitchRow::builder()
+            .title(t!("logs.debug.title"))
+            .subtitle(t!("logs.debug.subtitle"))
+            .build();
+        let log_view = TextView::builder()
+            .monospace(true)
+            .editable(false)
+            .cursor_visible(false)
+            .wrap_mode(WrapMode::WordChar)
+            .top_margin(12)
+            .bottom_margin(12)
+            .left_margin(12)
+            .right_margin(12)
+            .build();
+        let copy_button = Button::builder()
+            .label(t!("logs.copy"))
+            .css_classes(["pill"])
+            .build();
+        let open_folder_button = Button::builder()
+            .label(t!("logs.open_folder"))
+            .css_classes(["pill"])
+            .build();
+
+        Rc::new(Self {
+            nav_page,
+            nav_row,
+            prefs_page,
+            toast_overlay,
+            header,
+            refresh_button,
+            debug_row,
+            log_view,
+            copy_button,
+            open_folder_button,
+        })
+    }
+
+    pub fn init(self: &Rc<Self>, app: &Rc<App>) {
+        self.header.pack_end(&self.refresh_button);
+
+        let level_group = PreferencesGroup::new();
+        level_group.add(&self.debug_row);
+        self.prefs_page.add(&level_group);
+
+        let scrolled_window = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .height_request(Self::LOG_VIEW_HEIGHT)
+            .child(&self.log_view)
+            .css_classes(["card"])
+            .build();
+        let button_box = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .halign(Align::Center)
+            .margin_top(12)
+            .build();
+        button_box.append(&self.copy_button);
+        button_box.append(&self.open_folder_button);
+
+        let log_group = PreferencesGroup::builder()
+            .title(t!("logs.log.title"))
+            .description(logging::get_log_file_path(&app.dirs).display().to_string())
+            .build();
+        log_group.add(&scrolled_window);
+        log_group.add(&button_box);
+        self.prefs_page.add(&log_group);
+
+        self.connect_debug_row();
+        self.connect_buttons(app);
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.nav_page.connect_map(move |_| {
+            self_clone
+                .debug_row
+                .set_active(logging::get_log_level() >= Level::DEBUG);
+            self_clone.load_log(&app_clone);
+        });
+    }
+
+    /// Only for this session, `RUST_LOG` still sets the level on start
+    fn connect_debug_row(self: &Rc<Self>) {
+        self.debug_row.connect_active_notify(|switch_row| {
+            let log_level = if switch_row.is_active() {
+                Level::DEBUG
+            } else {
+                Level::INFO
+            };
+            if logging::get_log_level() != log_level {
+                logging::set_log_level(log_level);
+            }
+        });
+    }
+
+    fn connect_buttons(self: &Rc<Self>, app: &Rc<App>) {
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.refresh_button
+            .connect_clicked(move |_| self_clone.load_log(&app_clone));
+
+        let self_clone = self.clone();
+        self.copy_button.connect_clicked(move |button| {
+            let buffer = self_clone.log_view.buffer();
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            button.clipboard().set_text(&text);
+            self_clone.show_toast(&t!("logs.copied"));
+        });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.open_folder_button.connect_clicked(move |_| {
+            let logs_dir = &app_clone.dirs.app_data_logs;
+            if let Err(error) = fs::create_dir_all(logs_dir) {
+                error!(path = %logs_dir.display(), "Failed to create logs dir: {error:?}");
+            }
+
+            let self_clone = self_clone.clone();
+            FileLauncher::new(Some(&gio::File::for_path(logs_dir))).launch(
+                Some(&app_clone.window.adw_window),
+                None::<&Cancellable>,
+                move |result| {
+                    if let Err(error) = result {
+                        error!("Failed to open logs dir: {error:?}");
+                        self_clone.show_toast(&t!("logs.open_folder_failed"));
+                    }
+                },
+            );
+        });
+    }
+
+    fn load_log(&self, app: &Rc<App>) {
+        let text = match logging::read_tail(&app.dirs, Self::TAIL_MAX_BYTES) {
+            Ok(text) if !text.is_empty() => text,
+            Ok(_) => t!("logs.empty").to_string(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => t!("logs.empty").to_string(),
+            Err(error) => {
+                error!("Failed to read log file: {error:?}");
+                t!("logs.empty").to_string()
+            }
+        };
+
+        let buffer = self.log_view.buffer();
+        buffer.set_text(&text);
+
+        // Newest lines are at the bottom, a mark also scrolls before the view has a size
+        let end_mark = buffer.create_mark(None, &buffer.end_iter(), false);
+        self.log_view
+            .scroll_to_mark(&end_mark, 0.0, false, 0.0, 0.0);
+    }
+
+    fn show_toast(&self, message: &str) {
+        let toast = Toast::builder()
+            .title(message)
+            .timeout(Self::TOAST_MESSAGE_TIMEOUT)
+            .build();
+        self.toast_overlay.add_toast(toast);
+    }
+}
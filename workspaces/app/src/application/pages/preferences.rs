@@ -0,0 +1,347 @@
+use crate::application::{
+    App,
+    pages::{NavPage, PrefPage, web_apps::web_app_view::WebAppView},
+};
+use common::{browsers::Browser, desktop_file::category::Category, preferences::AppSort};
+use gtk::{StringList, glib};
+use libadwaita::{
+    ActionRow, ComboRow, NavigationPage, PreferencesGroup, SwitchRow, Toast, ToastOverlay,
+    gtk::prelude::WidgetExt,
+    prelude::{ComboRowExt, PreferencesGroupExt, PreferencesPageExt},
+};
+use std::{cell::RefCell, rc::Rc};
+use tracing::debug;
+
+pub struct PreferencesPage {
+    nav_page: NavigationPage,
+    nav_row: ActionRow,
+    prefs_page: libadwaita::PreferencesPage,
+    toast_overlay: ToastOverlay,
+    browser_row: ComboRow,
+    /// Installed browsers in the order of the browser row, after the first installed item
+    browsers: RefCell<Vec<Rc<Browser>>>,
+    browsers_generation: RefCell<u64>,
+    /// Rows are set from the preferences, not by the user
+    is_syncing: RefCell<bool>,
+    isolate_row: SwitchRow,
+    maximize_row: SwitchRow,
+    category_row: ComboRow,
+    restore_last_page_row: SwitchRow,
+    app_sort_row: ComboRow,
+    show_app_usage_row: SwitchRow,
+}
+impl NavPage for PreferencesPage {
+    fn get_navpage(&self) -> &NavigationPage {
+        &self.nav_page
+    }
+
+    fn get_nav_row(&self) -> Option<&ActionRow> {
+        Some(&self.nav_row)
+    }
+}
+impl PreferencesPage {
+    pub fn new() -> Rc<Self> {
+        let title = t!("preferences.title");
+        let icon = "preferences-system-symbolic";
+
+        let PrefPage {
+            nav_page,
+            nav_row,
+            prefs_page,
+            toast_overlay,
+            ..
+        } = Self::build_nav_page(&title, icon).with_preference_page();
+
+        let browser_row = ComboRow::builder()
+            .title(t!("preferences.browser.title"))
+            .subtitle(t!("preferences.browser.subtitle"))
+            .build();
+        let isolate_row = SwitchRow::builder()
+            .title(t!("preferences.isolate.title"))
+            .subtitle(t!("preferences.isolate.subtitle"))
+            .build();
+        let maximize_row = SwitchRow::builder()
+            .title(t!("preferences.maximize.title"))
+            .subtitle(t!("preferences.maximize.subtitle"))
+            .build();
+        let category_row = Self::build_category_row();
+        let restore_last_page_row = SwitchRow::builder()
+            .title(t!("preferences.restore_last_page.title"))
+            .subtitle(t!("preferences.restore_last_page.subtitle"))
+            .build();
+        let app_sort_row = Self::build_app_sort_row();
+        let show_app_usage_row = SwitchRow::builder()
+            .title(t!("preferences.show_app_usage.title"))
+            .subtitle(t!("preferences.show_app_usage.subtitle"))
+            .build();
+
+        Rc::new(Self {
+            nav_page,
+            nav_row,
+            prefs_page,
+            toast_overlay,
+            browser_row,
+            browsers: RefCell::new(Vec::new()),
+            browsers_generation: RefCell::new(0),
+            is_syncing: RefCell::new(false),
+            isolate_row,
+            maximize_row,
+            category_row,
+            restore_last_page_row,
+            app_sort_row,
+            show_app_usage_row,
+        })
+    }
+
+    pub fn init(self: &Rc<Self>, app: &Rc<App>) {
+        let new_apps_group = PreferencesGroup::builder()
+            .title(t!("preferences.new_apps.title"))
+            .description(t!("preferences.new_apps.description"))
+            .build();
+        new_apps_group.add(&self.browser_row);
+        new_apps_group.add(&self.isolate_row);
+        new_apps_group.add(&self.maximize_row);
+        new_apps_group.add(&self.category_row);
+        self.prefs_page.add(&new_apps_group);
+
+        let window_group = PreferencesGroup::builder()
+            .title(t!("preferences.window.title"))
+            .build();
+        window_group.add(&self.restore_last_page_row);
+        self.prefs_page.add(&window_group);
+
+        let app_list_group = PreferencesGroup::builder()
+            .title(t!("preferences.app_list.title"))
+            .build();
+        app_list_group.add(&self.app_sort_row);
+        app_list_group.add(&self.show_app_usage_row);
+        self.prefs_page.add(&app_list_group);
+
+        self.sync_rows(app);
+        self.connect_rows(app);
+
+        // Browsers can be refreshed on the Browsers page
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.nav_page
+            .connect_map(move |_| self_clone.sync_browser_row(&app_clone));
+
+        let self_weak = Rc::downgrade(self);
+        let app_clone = app.clone();
+        app.browser_configs.connect_changed(move || {
+            let Some(self_clone) = self_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            self_clone.sync_browser_row(&app_clone);
+            glib::ControlFlow::Continue
+        });
+    }
+
+    fn sync_rows(self: &Rc<Self>, app: &Rc<App>) {
+        self.sync_browser_row(app);
+
+        let preferences = app.preferences.borrow();
+        let category = preferences
+            .get_default_category()
+            .unwrap_or(Category::Network);
+        let category_index = Category::get_all()
+            .iter()
+            .position(|category_iter| *category_iter == category)
+            .and_then(|index| index.try_into().ok())
+            .unwrap_or(0);
+        let app_sort_index = AppSort::get_all()
+            .iter()
+            .position(|app_sort| *app_sort == preferences.settings.app_sort)
+            .and_then(|index| index.try_into().ok())
+            .unwrap_or(0);
+
+        *self.is_syncing.borrow_mut() = true;
+        self.isolate_row
+            .set_active(preferences.settings.default_isolated);
+        self.maximize_row
+            .set_active(preferences.settings.default_maximized);
+        self.category_row.set_selected(category_index);
+        self.restore_last_page_row
+            .set_active(preferences.settings.restore_last_page);
+        self.app_sort_row.set_selected(app_sort_index);
+        self.show_app_usage_row
+            .set_active(preferences.settings.show_app_usage);
+        *self.is_syncing.borrow_mut() = false;
+    }
+
+    /// An uninstalled default browser is kept, it shows as the first installed browser
+    fn sync_browser_row(self: &Rc<Self>, app: &Rc<App>) {
+        let generation = app.browser_configs.get_generation();
+        if *self.browsers_generation.borrow() == generation && !self.browsers.borrow().is_empty() {
+            return;
+        }
+        *self.browsers_generation.borrow_mut() = generation;
+        debug!("Browsers have changed, rebuilding default browser list");
+
+        let browsers: Vec<Rc<Browser>> = app
+            .browser_configs
+            .get_all_browsers()
+            .into_iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .collect();
+
+        let first_installed = t!("preferences.browser.first_installed").to_string();
+        let mut names = vec![first_installed];
+        names.extend(
+            browsers
+                .iter()
+                .map(|browser| browser.get_name_with_installation()),
+        );
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        let default_browser = app.preferences.borrow().settings.default_browser.clone();
+        let browser_index = default_browser
+            .and_then(|browser_id| browsers.iter().position(|browser| browser.id == browser_id))
+            .and_then(|index| (index + 1).try_into().ok())
+            .unwrap_or(0);
+
+        *self.is_syncing.borrow_mut() = true;
+        self.browser_row.set_model(Some(&StringList::new(&names)));
+        self.browser_row.set_selected(browser_index);
+        *self.is_syncing.borrow_mut() = false;
+
+        *self.browsers.borrow_mut() = browsers;
+    }
+
+    fn connect_rows(self: &Rc<Self>, app: &Rc<App>) {
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.browser_row.connect_selected_notify(move |combo_row| {
+            if *self_clone.is_syncing.borrow() {
+                return;
+            }
+            let browser_id = usize::try_from(combo_row.selected())
+                .ok()
+                .and_then(|index| index.checked_sub(1))
+                .and_then(|index| self_clone.browsers.borrow().get(index).cloned())
+                .map(|browser| browser.id.clone());
+
+            app_clone.preferences.borrow_mut().settings.default_browser = browser_id;
+            self_clone.save(&app_clone);
+        });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.isolate_row.connect_active_notify(move |switch_row| {
+            if *self_clone.is_syncing.borrow() {
+                return;
+            }
+            app_clone.preferences.borrow_mut().settings.default_isolated = switch_row.is_active();
+            self_clone.save(&app_clone);
+        });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.maximize_row.connect_active_notify(move |switch_row| {
+            if *self_clone.is_syncing.borrow() {
+                return;
+            }
+            app_clone
+                .preferences
+                .borrow_mut()
+                .settings
+                .default_maximized = switch_row.is_active();
+            self_clone.save(&app_clone);
+        });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.category_row.connect_selected_notify(move |combo_row| {
+            if *self_clone.is_syncing.borrow() {
+                return;
+            }
+            let category = usize::try_from(combo_row.selected())
+                .ok()
+                .and_then(|index| Category::get_all().get(index).copied());
+
+            app_clone.preferences.borrow_mut().settings.default_category =
+                category.map(|category| category.to_string());
+            self_clone.save(&app_clone);
+        });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.restore_last_page_row
+            .connect_active_notify(move |switch_row| {
+                if *self_clone.is_syncing.borrow() {
+                    return;
+                }
+                app_clone
+                    .preferences
+                    .borrow_mut()
+                    .settings
+                    .restore_last_page = switch_row.is_active();
+                self_clone.save(&app_clone);
+            });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.app_sort_row.connect_selected_notify(move |combo_row| {
+            if *self_clone.is_syncing.borrow() {
+                return;
+            }
+            let app_sort = usize::try_from(combo_row.selected())
+                .ok()
+                .and_then(|index| AppSort::get_all().get(index).copied())
+                .unwrap_or_default();
+
+            app_clone.preferences.borrow_mut().settings.app_sort = app_sort;
+            self_clone.save(&app_clone);
+            app_clone.pages.get_web_apps().reset_app_usage(&app_clone);
+        });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.show_app_usage_row
+            .connect_active_notify(move |switch_row| {
+                if *self_clone.is_syncing.borrow() {
+                    return;
+                }
+                app_clone.preferences.borrow_mut().settings.show_app_usage = switch_row.is_active();
+                self_clone.save(&app_clone);
+                app_clone.pages.get_web_apps().reset_app_usage(&app_clone);
+            });
+    }
+
+    fn build_app_sort_row() -> ComboRow {
+        let names: Vec<String> = AppSort::get_all()
+            .into_iter()
+            .map(|app_sort| match app_sort {
+                AppSort::Name => t!("preferences.app_sort.name").to_string(),
+                AppSort::RecentlyUsed => t!("preferences.app_sort.recently_used").to_string(),
+            })
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        ComboRow::builder()
+            .title(t!("preferences.app_sort.title"))
+            .model(&StringList::new(&names))
+            .build()
+    }
+
+    fn build_category_row() -> ComboRow {
+        let names: Vec<String> = Category::get_all()
+            .into_iter()
+            .map(WebAppView::category_to_string_ui)
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        ComboRow::builder()
+            .title(t!("preferences.category.title"))
+            .subtitle(t!("preferences.category.subtitle"))
+            .model(&StringList::new(&names))
+            .build()
+    }
+
+    fn save(self: &Rc<Self>, app: &Rc<App>) {
+        if let Err(error) = app.preferences.borrow().save() {
+            let message = t!("preferences.save_failed", error = error.to_string());
+            self.toast_overlay.add_toast(Toast::new(&message));
+        }
+    }
+}
@@ -1,30 +1,101 @@
-mod web_app_view;
+mod backup;
+mod browser_migration;
+mod change_browser;
+mod foreign_apps;
+mod icon_repair;
+mod launcher_watch;
+pub mod web_app_view;
 
 use super::NavPage;
 use crate::application::{App, pages::PrefNavPage};
 use common::{
-    desktop_file::{DesktopFile, error::DesktopFileError},
-    utils,
+    desktop_file::{
+        DeleteOptions, DesktopFile,
+        error::{DesktopFileError, LaunchError},
+        launcher_backend,
+        migration::AppUpgradeNotes,
+        removal,
+        trash::{self, TrashedWebApp},
+    },
+    diagnostics, policy,
+    preferences::AppSort,
+    utils::{
+        self,
+        command::{self, StartupStatus},
+    },
 };
 use gtk::{
-    Button, Image,
-    prelude::{ButtonExt, WidgetExt},
+    Align, Button, CallbackAction, CheckButton, EventSequenceState, FileDialog, FileFilter,
+    GestureClick, GestureLongPress, Image, ListBox, ListBoxRow, Orientation, PopoverMenu,
+    SearchEntry, SelectionMode, Shortcut, ShortcutController, ShortcutTrigger, gdk,
+    gio::{self, Cancellable, ListStore, Menu, SimpleAction, SimpleActionGroup},
+    glib::{self, NormalizeMode, Unichar, object::Cast},
+    prelude::{
+        ActionMapExt, BoxExt, ButtonExt, CheckButtonExt, EditableExt, FileExt, GestureExt,
+        PopoverExt, WidgetExt,
+    },
 };
+use icon_repair::IconRepair;
 use libadwaita::{
-    ActionRow, ButtonContent, NavigationPage, NavigationView, PreferencesGroup, PreferencesPage,
-    StatusPage,
-    prelude::{ActionRowExt, PreferencesGroupExt, PreferencesPageExt},
+    ActionRow, AlertDialog, Banner, ButtonContent, NavigationPage, NavigationView,
+    PreferencesGroup, PreferencesPage, ResponseAppearance, StatusPage, Toast, ToastOverlay,
+    ToastPriority,
+    prelude::{
+        ActionRowExt, AdwDialogExt, AlertDialogExt, PreferencesGroupExt, PreferencesPageExt,
+        PreferencesRowExt,
+    },
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use std::{cell::RefCell, rc::Rc};
 use tracing::{debug, error};
 use web_app_view::WebAppView;
 
+/// A parsed desktop file, reused until the file on disk changes
+struct AppEntry {
+    modified: Option<SystemTime>,
+    desktop_file: Rc<RefCell<DesktopFile>>,
+    row: ActionRow,
+    /// Name, url and browser, folded for the search
+    search_text: String,
+}
+
 pub struct WebAppsPage {
     nav_page: NavigationPage,
     nav_row: ActionRow,
     nav_view: Rc<NavigationView>,
     prefs_page: PreferencesPage,
-    app_section: RefCell<PreferencesGroup>,
+    toast_overlay: ToastOverlay,
+    banner_section: PreferencesGroup,
+    browser_missing_banner: Banner,
+    /// Inside the sandbox without the filesystem permission every save asks the portal
+    applications_access_banner: Banner,
+    search_section: PreferencesGroup,
+    search_entry: SearchEntry,
+    app_section: PreferencesGroup,
+    app_list: ListBox,
+    no_apps_status: StatusPage,
+    app_entries: RefCell<HashMap<PathBuf, AppEntry>>,
+    /// Browser detection the rows were built from, a missing browser can be back
+    browsers_generation: RefCell<u64>,
+    /// Last launch of every row, the sort runs while the app entries are borrowed
+    row_last_launched: Rc<RefCell<HashMap<ActionRow, u64>>>,
+    icon_repair_queue: RefCell<VecDeque<IconRepair>>,
+    /// Ids of apps with a repair queued or done, a failed fetch is not repeated every refresh
+    icon_repair_ids: RefCell<HashSet<String>>,
+    is_repairing_icons: RefCell<bool>,
+    /// A desktop file of the last refresh could not be read or updated
+    has_unreadable_files: RefCell<bool>,
+    /// Apps with an editor on the navigation stack, a new one has no desktop file yet
+    open_editor_ids: RefCell<HashSet<String>>,
+    launcher_monitors: RefCell<Vec<gio::FileMonitor>>,
+    /// A launcher dir changed since the last refresh
+    is_list_stale: RefCell<bool>,
+    pending_refresh: RefCell<Option<glib::SourceId>>,
 }
 impl NavPage for WebAppsPage {
     fn get_navpage(&self) -> &NavigationPage {
@@ -36,41 +107,202 @@ impl NavPage for WebAppsPage {
     }
 }
 impl WebAppsPage {
+    const UNDO_TIMEOUT_SECONDS: u32 = 8;
+    /// A browser that can't start quits within this time
+    pub const LAUNCH_WATCH: Duration = Duration::from_secs(3);
+    const ACTION_LABEL_ROW: &str = "app-row";
+    const ACTION_OPEN: &str = "open";
+    const ACTION_EDIT: &str = "edit";
+    const ACTION_DUPLICATE: &str = "duplicate";
+    const ACTION_DELETE: &str = "delete";
+
     pub fn new() -> Rc<Self> {
         let title = t!("web_apps.title");
         let icon = "preferences-desktop-apps-symbolic";
-        let app_section = RefCell::new(PreferencesGroup::new());
 
         let PrefNavPage {
             nav_page,
             nav_row,
             nav_view,
             prefs_page,
+            toast_overlay,
             ..
         } = Self::build_nav_page(&title, icon).with_preference_navigation_view();
 
+        let banner_section = PreferencesGroup::new();
+        let browser_missing_banner = Banner::builder()
+            .button_label(t!("web_apps.browser_migration.banner_button"))
+            .build();
+        let applications_access_banner = Banner::builder()
+            .title(t!(
+                "web_apps.applications_access.banner",
+                command = diagnostics::get_applications_override_command()
+            ))
+            .button_label(t!("web_apps.applications_access.copy"))
+            .use_markup(false)
+            .build();
+        let search_section = PreferencesGroup::new();
+        let search_entry = SearchEntry::builder()
+            .placeholder_text(t!("web_apps.search.placeholder"))
+            .build();
+        let app_section = PreferencesGroup::new();
+        let app_list = Self::build_app_list();
+        let no_apps_status = Self::build_no_apps_status();
+
         Rc::new(Self {
             nav_page,
             nav_row,
             nav_view: Rc::new(nav_view),
             prefs_page,
+            toast_overlay,
+            banner_section,
+            browser_missing_banner,
+            applications_access_banner,
+            search_section,
+            search_entry,
             app_section,
+            app_list,
+            no_apps_status,
+            app_entries: RefCell::new(HashMap::new()),
+            browsers_generation: RefCell::new(0),
+            row_last_launched: Rc::new(RefCell::new(HashMap::new())),
+            icon_repair_queue: RefCell::new(VecDeque::new()),
+            icon_repair_ids: RefCell::new(HashSet::new()),
+            is_repairing_icons: RefCell::new(false),
+            has_unreadable_files: RefCell::new(false),
+            open_editor_ids: RefCell::new(HashSet::new()),
+            launcher_monitors: RefCell::new(Vec::new()),
+            is_list_stale: RefCell::new(false),
+            pending_refresh: RefCell::new(None),
         })
     }
 
     pub fn init(self: &Rc<Self>, app: &Rc<App>) {
-        let app_section = self.clone().build_apps_section(app);
-        self.prefs_page.add(&app_section);
-        *self.app_section.borrow_mut() = app_section;
+        self.connect_app_list_sort(app);
+        self.build_banner_section(app);
+        self.build_search_section();
+        self.build_apps_section(app);
+        self.prefs_page.add(&self.banner_section);
+        self.prefs_page.add(&self.search_section);
+        self.prefs_page.add(&self.app_section);
+        self.refresh_app_list(app);
+        self.remove_orphaned_icons(app);
+        self.watch_launcher_dirs(app);
 
         let self_clone = self.clone();
         let app_clone = app.clone();
 
         self.nav_view
-            .connect_popped(move |_, _| self_clone.reset_app_section(&app_clone));
+            .connect_popped(move |_, _| self_clone.refresh_if_stale(&app_clone));
+
+        // Browsers can be refreshed on the Browsers page
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.nav_page.connect_map(move |_| {
+            if *self_clone.browsers_generation.borrow()
+                != app_clone.browser_configs.get_generation()
+            {
+                self_clone.refresh_app_list(&app_clone);
+            } else {
+                self_clone.refresh_if_stale(&app_clone);
+            }
+        });
+
+        // A hidden list is rebuilt when it is shown again
+        let self_weak = Rc::downgrade(self);
+        let app_clone = app.clone();
+        app.browser_configs.connect_changed(move || {
+            let Some(self_clone) = self_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if self_clone.nav_page.is_mapped() {
+                self_clone.refresh_app_list(&app_clone);
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
+    fn build_app_list() -> ListBox {
+        ListBox::builder()
+            .selection_mode(SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build()
+    }
+
+    fn connect_app_list_sort(self: &Rc<Self>, app: &Rc<App>) {
+        let app_clone = app.clone();
+        let row_last_launched = self.row_last_launched.clone();
+
+        self.app_list.set_sort_func(move |row_a, row_b| {
+            let get_title = |row: &ListBoxRow| {
+                row.downcast_ref::<ActionRow>()
+                    .map(|action_row| action_row.title().to_lowercase())
+                    .unwrap_or_default()
+            };
+            let by_name = get_title(row_a).cmp(&get_title(row_b));
+
+            match app_clone.preferences.borrow().settings.app_sort {
+                AppSort::Name => by_name.into(),
+                AppSort::RecentlyUsed => {
+                    let row_last_launched = row_last_launched.borrow();
+                    let get_last_launched = |row: &ListBoxRow| {
+                        row.downcast_ref::<ActionRow>()
+                            .and_then(|action_row| row_last_launched.get(action_row).copied())
+                    };
+                    // Never launched is `None`, which sorts last when reversed
+                    get_last_launched(row_b)
+                        .cmp(&get_last_launched(row_a))
+                        .then(by_name)
+                        .into()
+                }
+            }
+        });
+    }
+
+    fn build_no_apps_status() -> StatusPage {
+        StatusPage::builder()
+            .title(t!("web_apps.no_apps.title"))
+            .description(t!("web_apps.no_apps.description"))
+            .icon_name("system-search-symbolic")
+            .build()
     }
 
-    fn build_apps_section(self: Rc<Self>, app: &Rc<App>) -> PreferencesGroup {
+    fn build_banner_section(self: &Rc<Self>, app: &Rc<App>) {
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        self.browser_missing_banner
+            .connect_button_clicked(move |_| {
+                self_clone.show_browser_migration_dialog(&app_clone, None);
+            });
+
+        self.banner_section.add(&self.browser_missing_banner);
+
+        let self_clone = self.clone();
+        self.applications_access_banner
+            .connect_button_clicked(move |banner| {
+                banner
+                    .clipboard()
+                    .set_text(&diagnostics::get_applications_override_command());
+                self_clone.show_toast(&t!("web_apps.applications_access.copied"), false);
+            });
+        self.applications_access_banner
+            .set_revealed(!app.dirs.has_host_applications);
+        self.banner_section.add(&self.applications_access_banner);
+    }
+
+    fn build_search_section(self: &Rc<Self>) {
+        // Typing anywhere on the page starts a search
+        self.search_entry
+            .set_key_capture_widget(Some(&self.prefs_page));
+
+        let self_clone = self.clone();
+        self.search_entry
+            .connect_search_changed(move |_| self_clone.apply_filter());
+
+        self.search_section.add(&self.search_entry);
+    }
+
+    fn build_apps_section(self: &Rc<Self>, app: &Rc<App>) {
         let button_content = ButtonContent::builder()
             .label(t!("web_apps.button.new_app"))
             .icon_name("list-add-symbolic")
@@ -80,61 +312,155 @@ impl WebAppsPage {
             .child(&button_content)
             .build();
 
+        let import_button_content = ButtonContent::builder()
+            .label(t!("web_apps.button.import"))
+            .icon_name("document-open-symbolic")
+            .build();
+        let import_button = Button::builder()
+            .css_classes(["flat"])
+            .child(&import_button_content)
+            .build();
+
         let self_clone = self.clone();
         let app_clone = app.clone();
 
         new_app_button.connect_clicked(move |_| {
-            let desktop_file = Rc::new(RefCell::new(DesktopFile::new(
-                &app_clone.browser_configs,
-                &app_clone.dirs,
+            let mut desktop_file = DesktopFile::new(&app_clone.browser_configs, &app_clone.dirs);
+            if let Err(error) = desktop_file.ensure_unique_id() {
+                error!("Failed to create web app: {error:?}");
+                self_clone.show_toast(&error.to_string(), true);
+                return;
+            }
+            app_clone
+                .preferences
+                .borrow()
+                .apply_defaults(&app_clone.browser_configs, &mut desktop_file);
+            self_clone.open_new_app(&app_clone, desktop_file);
+        });
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+
+        import_button.connect_clicked(move |_| self_clone.import_bundle(&app_clone));
+
+        let header_buttons = gtk::Box::new(Orientation::Horizontal, 6);
+        header_buttons.append(&import_button);
+        header_buttons.append(&new_app_button);
+
+        self.app_section.set_header_suffix(Some(&header_buttons));
+        if let Some(load_error) = policy::get().get_load_error() {
+            let path = policy::get()
+                .get_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default();
+            self.app_section.set_description(Some(&t!(
+                "web_apps.policy_error",
+                path = path,
+                error = load_error
             )));
-            let app_page = WebAppView::new(&app_clone, &self_clone.nav_view, &desktop_file, true);
-            app_page.init();
-
-            let nav_page = app_page.get_navpage();
-            let app_page_clone = app_page.clone();
-            nav_page.connect_unrealize(move |_| {
-                if app_page_clone.get_is_new() {
-                    let _ = desktop_file.borrow().delete();
-                }
-            });
+        }
+        self.app_section.add(&self.no_apps_status);
+        self.app_section.add(&self.app_list);
+    }
+
+    /// A new app with the url of the desktop file applied, like the user entered it
+    pub fn open_prefilled_app(self: &Rc<Self>, app: &Rc<App>, desktop_file: DesktopFile) {
+        let app_page = self.open_new_app(app, desktop_file);
+        app_page.apply_prefilled_url();
+    }
+
+    /// The desktop file is removed again when the view closes without saving
+    fn open_new_app(self: &Rc<Self>, app: &Rc<App>, desktop_file: DesktopFile) -> Rc<WebAppView> {
+        let desktop_file = Rc::new(RefCell::new(desktop_file));
+        let app_page = WebAppView::new(app, &self.nav_view, &desktop_file, true);
+        app_page.init();
+        let editor_id = self.add_open_editor(&desktop_file);
 
-            self_clone.nav_view.push(nav_page);
+        let nav_page = app_page.get_navpage();
+        let app_page_clone = app_page.clone();
+        let self_weak = Rc::downgrade(self);
+        nav_page.connect_unrealize(move |_| {
+            if let Some(self_clone) = self_weak.upgrade() {
+                self_clone.remove_open_editor(editor_id.as_deref());
+            }
+            // Stop pending icon fetches first, so they can't write files after the delete
+            app_page_clone.discard();
+
+            if app_page_clone.get_is_new() {
+                let _ = desktop_file.borrow().delete();
+            }
+            app_page_clone.delete_pending_profiles();
         });
 
-        let pref_group = PreferencesGroup::builder()
-            .header_suffix(&new_app_button)
+        self.nav_view.push(nav_page);
+
+        app_page
+    }
+
+    fn import_bundle(self: &Rc<Self>, app: &Rc<App>) {
+        let file_filter = FileFilter::new();
+        file_filter.set_name(Some(&t!("web_apps.import_dialog.filter")));
+        file_filter.add_suffix(DesktopFile::BUNDLE_EXTENSION);
+        let filters = ListStore::new::<FileFilter>();
+        filters.append(&file_filter);
+
+        let file_dialog = FileDialog::builder()
+            .title(t!("web_apps.import_dialog.title"))
+            .filters(&filters)
+            .default_filter(&file_filter)
             .build();
 
-        let (web_app_desktop_files, desktop_files_have_updated) =
-            Self::get_owned_desktop_files(app);
-        if web_app_desktop_files.is_empty() {
-            let status_page = StatusPage::builder()
-                .title(t!("web_apps.no_apps.title"))
-                .description(t!("web_apps.no_apps.description"))
-                .icon_name("system-search-symbolic")
-                .build();
+        let self_clone = self.clone();
+        let app_clone = app.clone();
 
-            pref_group.add(&status_page);
-        } else {
-            for desktop_file in web_app_desktop_files {
-                let web_app_row = self.clone().build_app_row(app, desktop_file);
-                pref_group.add(&web_app_row);
-            }
-        }
+        file_dialog.open(
+            Some(&app.window.adw_window),
+            None::<&Cancellable>,
+            move |file| {
+                let Ok(file) = file else {
+                    debug!("No file selected for import");
+                    return;
+                };
+                let Some(path) = file.path() else {
+                    error!("Failed to get path");
+                    return;
+                };
 
-        if desktop_files_have_updated {
-            app.on_app_update();
-        }
+                let desktop_file = match DesktopFile::import_bundle(
+                    &path,
+                    &app_clone.browser_configs,
+                    &app_clone.dirs,
+                ) {
+                    Ok(desktop_file) => desktop_file,
+                    Err(error) => {
+                        error!("Failed to import bundle: {error:?}");
+                        let toast = Toast::new(&t!(
+                            "web_apps.import_dialog.failed",
+                            error = error.to_string()
+                        ));
+                        toast.set_priority(ToastPriority::High);
+                        self_clone.toast_overlay.add_toast(toast);
+                        return;
+                    }
+                };
 
-        pref_group
+                // Opened anyway, a browser can be picked in the editor
+                let is_browser_missing = desktop_file
+                    .get_browser()
+                    .is_none_or(|browser| browser.is_no_browser());
+                let app_page = self_clone.open_new_app(&app_clone, desktop_file);
+                if is_browser_missing {
+                    app_page.show_warning(&t!("web_apps.import_dialog.browser_missing"));
+                }
+            },
+        );
     }
 
     fn build_app_row(
-        self: Rc<Self>,
+        self: &Rc<Self>,
         app: &Rc<App>,
-        desktop_file: Rc<RefCell<DesktopFile>>,
-    ) -> ActionRow {
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+    ) -> (ActionRow, Image) {
         let desktop_file_borrow = desktop_file.borrow();
 
         let app_name = desktop_file_borrow
@@ -146,43 +472,434 @@ impl WebAppsPage {
             .build();
 
         let app_icon = desktop_file_borrow.get_icon();
+        let launch_button = Button::builder()
+            .icon_name("media-playback-start-symbolic")
+            .tooltip_text(t!("web_apps.button.launch"))
+            .css_classes(["flat"])
+            .valign(Align::Center)
+            .sensitive(desktop_file_borrow.get_exec().is_some())
+            .build();
         let suffix = Image::from_icon_name("go-next-symbolic");
+        let is_browser_missing = desktop_file_borrow.is_browser_missing();
 
         app_row.add_prefix(&app_icon);
+        if is_browser_missing {
+            app_row.add_suffix(&self.build_browser_missing_button(app, desktop_file));
+        }
+        app_row.add_suffix(&launch_button);
         app_row.add_suffix(&suffix);
 
         drop(desktop_file_borrow);
+
+        // The button takes the click, so the row does not open the editor
+        let self_clone = self.clone();
         let app_clone = app.clone();
-        let nav_view_clone = self.nav_view.clone();
+        let desktop_file_clone = desktop_file.clone();
+        launch_button.connect_clicked(move |_| {
+            self_clone.launch_app(&app_clone, &desktop_file_clone);
+        });
+
+        let app_clone = app.clone();
+        let self_clone = self.clone();
+        let desktop_file_clone = desktop_file.clone();
 
         app_row.connect_activated(move |_| {
-            let app_page =
-                WebAppView::new(&app_clone, &nav_view_clone, &desktop_file.clone(), false);
-            app_page.init();
-            self.nav_view.push(app_page.get_navpage());
+            self_clone.open_app_view(&app_clone, &desktop_file_clone);
         });
 
-        app_row
+        self.connect_app_row_menu(app, desktop_file, &app_row);
+
+        (app_row, app_icon)
     }
 
-    fn get_owned_desktop_files(app: &Rc<App>) -> (Vec<Rc<RefCell<DesktopFile>>>, bool) {
+    /// Right click, a long press, Shift+F10 and the Menu key show the actions of the row
+    fn connect_app_row_menu(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+        app_row: &ActionRow,
+    ) {
+        let actions = [
+            (Self::ACTION_OPEN, t!("web_apps.row_menu.open")),
+            (Self::ACTION_EDIT, t!("web_apps.row_menu.edit")),
+            (Self::ACTION_DUPLICATE, t!("web_apps.row_menu.duplicate")),
+            (Self::ACTION_DELETE, t!("web_apps.row_menu.delete")),
+        ];
+        let action_group = SimpleActionGroup::new();
+        let menu = Menu::new();
+
+        for (action_name, label) in actions {
+            let action = SimpleAction::new(action_name, None);
+            if action_name == Self::ACTION_OPEN {
+                action.set_enabled(desktop_file.borrow().get_exec().is_some());
+            }
+
+            let self_clone = self.clone();
+            let app_clone = app.clone();
+            let desktop_file_clone = desktop_file.clone();
+            action.connect_activate(move |_, _| match action_name {
+                Self::ACTION_OPEN => self_clone.launch_app(&app_clone, &desktop_file_clone),
+                Self::ACTION_EDIT => self_clone.open_app_view(&app_clone, &desktop_file_clone),
+                Self::ACTION_DUPLICATE => {
+                    self_clone.duplicate_app(&app_clone, &desktop_file_clone);
+                }
+                Self::ACTION_DELETE => self_clone.delete_app(&app_clone, &desktop_file_clone),
+                _ => {}
+            });
+
+            action_group.add_action(&action);
+            menu.append(
+                Some(&label),
+                Some(&format!("{}.{action_name}", Self::ACTION_LABEL_ROW)),
+            );
+        }
+        app_row.insert_action_group(Self::ACTION_LABEL_ROW, Some(&action_group));
+
+        let popover_menu = PopoverMenu::builder()
+            .menu_model(&menu)
+            .has_arrow(false)
+            .halign(Align::Start)
+            .build();
+        popover_menu.set_parent(app_row);
+        // Not a child the row knows about, it is removed with the row
+        let popover_menu_clone = popover_menu.clone();
+        app_row.connect_destroy(move |_| popover_menu_clone.unparent());
+
+        let right_click = GestureClick::builder()
+            .button(gdk::BUTTON_SECONDARY)
+            .build();
+        let popover_menu_clone = popover_menu.clone();
+        right_click.connect_pressed(move |gesture, _, x, y| {
+            gesture.set_state(EventSequenceState::Claimed);
+            Self::show_app_row_menu(&popover_menu_clone, Some((x, y)));
+        });
+        app_row.add_controller(right_click);
+
+        let long_press = GestureLongPress::builder().touch_only(true).build();
+        let popover_menu_clone = popover_menu.clone();
+        long_press.connect_pressed(move |gesture, x, y| {
+            gesture.set_state(EventSequenceState::Claimed);
+            Self::show_app_row_menu(&popover_menu_clone, Some((x, y)));
+        });
+        app_row.add_controller(long_press);
+
+        let menu_action = CallbackAction::new(move |_, _| {
+            Self::show_app_row_menu(&popover_menu, None);
+            glib::Propagation::Stop
+        });
+        let shortcut_controller = ShortcutController::new();
+        shortcut_controller.add_shortcut(Shortcut::new(
+            ShortcutTrigger::parse_string("<Shift>F10|Menu"),
+            Some(menu_action),
+        ));
+        app_row.add_controller(shortcut_controller);
+    }
+
+    /// At the pointer, or below the row when opened with the keyboard
+    #[allow(clippy::cast_possible_truncation)]
+    fn show_app_row_menu(popover_menu: &PopoverMenu, position: Option<(f64, f64)>) {
+        let pointing_to =
+            position.map(|(x, y)| gdk::Rectangle::new(x.round() as i32, y.round() as i32, 1, 1));
+        popover_menu.set_pointing_to(pointing_to.as_ref());
+        popover_menu.popup();
+    }
+
+    fn launch_app(self: &Rc<Self>, app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>) {
+        let launch_result = desktop_file.borrow().launch();
+        match launch_result {
+            Ok(background_command) => {
+                self.on_app_launched(app, desktop_file);
+
+                let self_clone = self.clone();
+                glib::spawn_future_local(async move {
+                    if let StartupStatus::Failed { stderr_tail } =
+                        command::watch_startup(&background_command, Self::LAUNCH_WATCH).await
+                    {
+                        self_clone
+                            .show_toast(&Self::build_launch_failed_message(&stderr_tail), true);
+                    }
+                });
+            }
+            Err(LaunchError::NoExec) => {}
+            Err(LaunchError::BrowserMissing) => {
+                self.show_toast(&t!("web_apps.launch.browser_missing"), true);
+            }
+            Err(LaunchError::Other(error)) => {
+                error!(error = error.to_string(), "Failed to run app");
+                self.show_toast(
+                    &t!("web_apps.launch.failed", error = error.to_string()),
+                    true,
+                );
+            }
+        }
+    }
+
+    fn open_app_view(self: &Rc<Self>, app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>) {
+        // The editor changes the desktop file in memory, read it again on return
+        self.invalidate_app_entry(desktop_file);
+
+        let app_page = WebAppView::new(app, &self.nav_view, desktop_file, false);
+        app_page.init();
+        let editor_id = self.add_open_editor(desktop_file);
+
+        let nav_page = app_page.get_navpage();
+        let app_page_clone = app_page.clone();
+        let self_weak = Rc::downgrade(self);
+        nav_page.connect_unrealize(move |_| {
+            if let Some(self_clone) = self_weak.upgrade() {
+                self_clone.remove_open_editor(editor_id.as_deref());
+            }
+            app_page_clone.cancel_icon_fetch();
+            app_page_clone.flush_pending_save();
+            app_page_clone.delete_pending_profiles();
+        });
+
+        self.nav_view.push(nav_page);
+    }
+
+    /// Opened as a new app, it is only kept when the user saves it
+    fn duplicate_app(self: &Rc<Self>, app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>) {
+        let desktop_file_borrow = desktop_file.borrow();
+        let name = t!(
+            "web_apps.duplicate.name",
+            name = desktop_file_borrow.get_name().unwrap_or_default()
+        );
+        let duplicate_result = desktop_file_borrow.duplicate(&name);
+        drop(desktop_file_borrow);
+
+        match duplicate_result {
+            Ok(duplicate) => {
+                self.open_new_app(app, duplicate);
+            }
+            Err(error) => {
+                error!("Failed to duplicate web app: {error:?}");
+                self.show_toast(
+                    &t!("web_apps.duplicate.failed", error = error.to_string()),
+                    true,
+                );
+            }
+        }
+    }
+
+    fn delete_app(self: &Rc<Self>, app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>) {
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let desktop_file_clone = desktop_file.clone();
+
+        Self::show_delete_dialog(app, &desktop_file.borrow(), move |delete_options| {
+            // Files are kept until the undo toast is gone
+            let trash_result = desktop_file_clone.borrow().move_to_trash(delete_options);
+            match trash_result {
+                Ok(trashed_web_app) => {
+                    self_clone.refresh_app_list(&app_clone);
+                    self_clone.show_undo_delete_toast(&app_clone, trashed_web_app);
+                }
+                Err(error) => {
+                    error!("{error:?}");
+                    self_clone.show_toast(&t!("web_apps.web_app_view.error.delete"), true);
+                }
+            }
+        });
+    }
+
+    /// Asks before a delete, the profile is only offered for removal when it exists
+    pub fn show_delete_dialog(
+        app: &Rc<App>,
+        desktop_file: &DesktopFile,
+        on_delete: impl Fn(&DeleteOptions) + 'static,
+    ) {
+        let dialog_delete = "delete";
+        let dialog_cancel = "cancel";
+
+        let name = desktop_file.get_name().unwrap_or_default();
+        let profile_path = desktop_file
+            .get_profile_path()
+            .filter(|profile_path| profile_path.is_dir());
+
+        let dialog = AlertDialog::builder()
+            .heading(t!(
+                "web_apps.web_app_view.delete_dialog.title",
+                name = name.clone()
+            ))
+            .body(t!("web_apps.web_app_view.delete_dialog.text"))
+            .build();
+
+        let delete_profile_button = profile_path.map(|profile_path| {
+            // A small profile would show as 0 MB
+            let size = glib::format_size(utils::files::get_dir_size(&profile_path));
+            CheckButton::builder()
+                .label(t!(
+                    "web_apps.web_app_view.delete_dialog.delete_profile",
+                    size = size
+                ))
+                .active(true)
+                .build()
+        });
+        if let Some(delete_profile_button) = &delete_profile_button {
+            dialog.set_extra_child(Some(delete_profile_button));
+        }
+
+        dialog.add_response(
+            dialog_cancel,
+            &t!("web_apps.web_app_view.delete_dialog.cancel"),
+        );
+        dialog.add_response(
+            dialog_delete,
+            &t!("web_apps.web_app_view.delete_dialog.delete"),
+        );
+        dialog.set_response_appearance(dialog_delete, ResponseAppearance::Destructive);
+        dialog.set_default_response(Some(dialog_cancel));
+        dialog.set_close_response(dialog_cancel);
+
+        dialog.connect_response(Some(dialog_delete), move |_, _| {
+            let delete_options = DeleteOptions {
+                delete_profile: delete_profile_button
+                    .as_ref()
+                    .is_none_or(CheckButtonExt::is_active),
+            };
+
+            debug!(
+                delete_profile = delete_options.delete_profile,
+                "Deleting web app: {name}"
+            );
+            on_delete(&delete_options);
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+
+    /// The row is updated in place, reading the file again would rebuild the clicked row
+    /// The last lines of stderr tell most about a browser that quit on startup
+    pub fn build_launch_failed_message(stderr_tail: &str) -> String {
+        match stderr_tail.lines().last() {
+            Some(last_line) => t!("web_apps.launch.exited", error = last_line).to_string(),
+            None => t!("web_apps.launch.exited_no_output").to_string(),
+        }
+    }
+
+    fn on_app_launched(self: &Rc<Self>, app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>) {
+        if let Err(error) = desktop_file.borrow_mut().record_launch() {
+            error!("Failed to record launch: {error:?}");
+            return;
+        }
+
+        let path = desktop_file.borrow().get_path();
+        if let Some(app_entry) = self.app_entries.borrow_mut().get_mut(&path) {
+            app_entry.modified = launcher_watch::get_modified(&path);
+        }
+        self.reset_app_usage(app);
+    }
+
+    /// Subtitles and the recently used order follow the launch statistics and the preferences
+    pub fn reset_app_usage(self: &Rc<Self>, app: &Rc<App>) {
+        let show_app_usage = app.preferences.borrow().settings.show_app_usage;
+        let mut row_last_launched = self.row_last_launched.borrow_mut();
+        row_last_launched.clear();
+
+        for app_entry in self.app_entries.borrow().values() {
+            let desktop_file = app_entry.desktop_file.borrow();
+            if let Some(last_launched) = desktop_file.get_last_launched() {
+                row_last_launched.insert(app_entry.row.clone(), last_launched);
+            }
+
+            let subtitle = if show_app_usage {
+                Self::build_usage_subtitle(&desktop_file)
+            } else {
+                None
+            };
+            app_entry.row.set_subtitle(&subtitle.unwrap_or_default());
+        }
+        drop(row_last_launched);
+
+        self.app_list.invalidate_sort();
+    }
+
+    fn build_usage_subtitle(desktop_file: &DesktopFile) -> Option<String> {
+        let last_launched = desktop_file.get_last_launched()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(last_launched, |duration| duration.as_secs());
+
+        Some(
+            t!(
+                "web_apps.usage.subtitle",
+                time = Self::format_elapsed(now.saturating_sub(last_launched)),
+                count = desktop_file.get_launch_count().unwrap_or(1)
+            )
+            .to_string(),
+        )
+    }
+
+    fn format_elapsed(seconds: u64) -> String {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+
+        if seconds < MINUTE {
+            t!("web_apps.usage.just_now").to_string()
+        } else if seconds < HOUR {
+            t!("web_apps.usage.minutes_ago", count = seconds / MINUTE).to_string()
+        } else if seconds < DAY {
+            t!("web_apps.usage.hours_ago", count = seconds / HOUR).to_string()
+        } else {
+            t!("web_apps.usage.days_ago", count = seconds / DAY).to_string()
+        }
+    }
+
+    fn build_browser_missing_button(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+    ) -> Button {
+        let button = Button::builder()
+            .icon_name("dialog-warning-symbolic")
+            .tooltip_text(t!("web_apps.browser_migration.row_tooltip"))
+            .css_classes(["flat", "warning"])
+            .valign(Align::Center)
+            .build();
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let desktop_file_clone = desktop_file.clone();
+        button.connect_clicked(move |_| {
+            self_clone.show_browser_migration_dialog(&app_clone, Some(&desktop_file_clone));
+        });
+
+        button
+    }
+
+    /// Only parses desktop files that are new or changed on disk since the last refresh
+    fn refresh_app_list(self: &Rc<Self>, app: &Rc<App>) {
         debug!("Reading user desktop files");
 
-        let mut owned_desktop_files = Vec::new();
-        let applications_path = &app.dirs.user_applications;
-        let mut app_has_updated = false;
+        let mut app_entries = self.app_entries.borrow_mut();
 
-        for file in utils::files::get_entries_in_dir(applications_path).unwrap_or_default() {
-            if file
-                .path()
-                .extension()
-                .is_some_and(|extension| extension != "desktop")
-            {
-                continue;
+        // Rows show whether the browser is installed, build them again after a detection
+        let browsers_generation = app.browser_configs.get_generation();
+        if *self.browsers_generation.borrow() != browsers_generation {
+            *self.browsers_generation.borrow_mut() = browsers_generation;
+            for app_entry in app_entries.values_mut() {
+                app_entry.modified = None;
             }
+        }
+        *self.is_list_stale.borrow_mut() = false;
+        let mut has_unreadable_files = false;
+        let mut upgrade_notes: Option<Vec<AppUpgradeNotes>> = None;
 
-            let Ok(is_owned) = DesktopFile::is_owned(&file.path()) else {
-                error!(path = %file.path().display(), "Failed to read desktop file");
+        let launcher_scan = launcher_watch::scan_launcher_dirs(
+            &launcher_backend::get_launcher_dirs(&app.dirs),
+            |path| {
+                app_entries
+                    .get(path)
+                    .and_then(|app_entry| app_entry.modified)
+            },
+        );
+        let mut found_paths: HashSet<PathBuf> = launcher_scan.unchanged.into_iter().collect();
+
+        for path in launcher_scan.changed {
+            let Ok(is_owned) = DesktopFile::is_owned(&path) else {
+                error!(path = %path.display(), "Failed to read desktop file");
+                has_unreadable_files = true;
                 continue;
             };
             if !is_owned {
@@ -190,9 +907,10 @@ impl WebAppsPage {
             }
 
             let Ok(mut desktop_file) =
-                DesktopFile::from_path(&file.path(), &app.browser_configs, &app.dirs)
+                DesktopFile::from_path(&path, &app.browser_configs, &app.dirs)
             else {
-                error!(path = %file.path().display(), "Failed to read desktop file");
+                error!(path = %path.display(), "Failed to read desktop file");
+                has_unreadable_files = true;
                 continue;
             };
 
@@ -205,11 +923,12 @@ impl WebAppsPage {
 
             debug!(file_name = &file_name, "Found desktop file");
 
-            let is_updated = match desktop_file.update() {
-                Ok(is_updated) => is_updated,
+            let migration_reports = match desktop_file.update() {
+                Ok(migration_reports) => migration_reports,
                 Err(error) => {
                     match error {
-                        DesktopFileError::ValidationError(error) => error!(
+                        DesktopFileError::ValidationError(_)
+                        | DesktopFileError::ValidationErrors(_) => error!(
                             error = error.to_string(),
                             desktop_file = &file_name,
                             "Failed to validate after updating 'DesktopFile'"
@@ -220,34 +939,228 @@ impl WebAppsPage {
                             "Failed to update 'DesktopFile'"
                         ),
                     }
+                    // Not listed, its icon must not look orphaned
+                    has_unreadable_files = true;
                     continue;
                 }
             };
-            if is_updated {
+            if let Some(migration_reports) = migration_reports {
                 debug!(file_name = &file_name, "Updated desktop file");
-                app_has_updated = true;
+                let upgrade_notes = upgrade_notes.get_or_insert_default();
+                if !migration_reports.is_empty() {
+                    let app_name = desktop_file
+                        .get_name()
+                        .unwrap_or(t!("web_apps.no_name").to_string());
+                    upgrade_notes.push(AppUpgradeNotes {
+                        app_name,
+                        migration_reports,
+                    });
+                }
             }
 
             debug!(file_name = &file_name, "Checking paths");
             desktop_file.check_paths();
 
-            owned_desktop_files.push(Rc::new(RefCell::new(desktop_file)));
+            // Updating saves the file, possibly on a new path
+            let path = desktop_file.get_path();
+            let modified = launcher_watch::get_modified(&path);
+            let search_text = Self::build_search_text(&desktop_file);
+            let desktop_file = Rc::new(RefCell::new(desktop_file));
+            let (row, icon) = self.build_app_row(app, &desktop_file);
+            self.app_list.append(&row);
+            if desktop_file.borrow().is_icon_missing() {
+                self.queue_icon_repair(app, &desktop_file, &icon);
+            }
+
+            let app_entry = AppEntry {
+                modified,
+                desktop_file,
+                row,
+                search_text,
+            };
+            if let Some(old_app_entry) = app_entries.insert(path.clone(), app_entry) {
+                self.app_list.remove(&old_app_entry.row);
+            }
+            found_paths.insert(path);
         }
-        owned_desktop_files.sort_by_key(|desktop_file| {
-            desktop_file
-                .borrow()
-                .get_name()
-                .unwrap_or(char::MAX.to_string())
+
+        app_entries.retain(|path, app_entry| {
+            let is_found = found_paths.contains(path);
+            if !is_found {
+                debug!(path = %path.display(), "Desktop file is gone");
+                self.app_list.remove(&app_entry.row);
+            }
+            is_found
+        });
+
+        let has_apps = !app_entries.is_empty();
+        *app.has_created_apps.borrow_mut() = has_apps;
+
+        *self.has_unreadable_files.borrow_mut() = has_unreadable_files;
+        drop(app_entries);
+        self.reset_app_usage(app);
+        self.apply_filter();
+        self.reset_browser_missing_banner();
+
+        if let Some(upgrade_notes) = upgrade_notes {
+            app.on_app_update(upgrade_notes);
+        }
+        // Imports, restores and edits in a closed editor only saved a copy
+        app.install_pending_launchers();
+    }
+
+    pub fn get_owned_desktop_files(self: &Rc<Self>) -> Vec<Rc<RefCell<DesktopFile>>> {
+        self.app_entries
+            .borrow()
+            .values()
+            .map(|app_entry| app_entry.desktop_file.clone())
+            .collect()
+    }
+
+    /// Hides the rows that don't match, nothing is read from disk again
+    fn apply_filter(&self) {
+        let search_term = self.search_entry.text().trim().to_string();
+        let folded_term = Self::fold_search_text(&search_term);
+        let app_entries = self.app_entries.borrow();
+
+        let mut has_matches = false;
+        for app_entry in app_entries.values() {
+            let is_match = app_entry.search_text.contains(&folded_term);
+            app_entry.row.set_visible(is_match);
+            has_matches |= is_match;
+        }
+
+        let has_apps = !app_entries.is_empty();
+        self.search_section.set_visible(has_apps);
+        self.app_list.set_visible(has_matches);
+        self.no_apps_status.set_visible(!has_matches);
+
+        if has_apps {
+            self.no_apps_status
+                .set_title(&t!("web_apps.search.no_matches.title", term = search_term));
+            self.no_apps_status
+                .set_description(Some(&t!("web_apps.search.no_matches.description")));
+        } else {
+            self.no_apps_status.set_title(&t!("web_apps.no_apps.title"));
+            self.no_apps_status
+                .set_description(Some(&t!("web_apps.no_apps.description")));
+        }
+    }
+
+    fn build_search_text(desktop_file: &DesktopFile) -> String {
+        let browser_name = desktop_file
+            .get_browser()
+            .map(|browser| browser.name.clone());
+        let search_text = [
+            desktop_file.get_name(),
+            desktop_file.get_url(),
+            browser_name,
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        Self::fold_search_text(&search_text)
+    }
+
+    /// Case and accents are ignored, so "cafe" finds "Café"
+    fn fold_search_text(text: &str) -> String {
+        glib::normalize(glib::casefold(text), NormalizeMode::All)
+            .chars()
+            .filter(|character| character.combining_class() == 0)
+            .collect()
+    }
+
+    pub fn show_toast(self: &Rc<Self>, message: &str, is_error: bool) {
+        let toast = Toast::new(message);
+        if is_error {
+            toast.set_priority(ToastPriority::High);
+        }
+        self.toast_overlay.dismiss_all();
+        self.toast_overlay.add_toast(toast);
+    }
+
+    /// The web app is purged when the toast is gone without an undo
+    pub fn show_undo_delete_toast(self: &Rc<Self>, app: &Rc<App>, trashed_web_app: TrashedWebApp) {
+        let toast = Toast::builder()
+            .title(t!(
+                "web_apps.delete_toast.title",
+                name = trashed_web_app.get_name()
+            ))
+            .button_label(t!("web_apps.delete_toast.undo"))
+            .timeout(Self::UNDO_TIMEOUT_SECONDS)
+            .build();
+        let trashed_web_app = Rc::new(RefCell::new(Some(trashed_web_app)));
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let trashed_web_app_clone = trashed_web_app.clone();
+        toast.connect_button_clicked(move |_| {
+            let Some(trashed_web_app) = trashed_web_app_clone.take() else {
+                return;
+            };
+            if let Err(error) = trashed_web_app.restore() {
+                error!("{error:?}");
+                self_clone.show_toast(&t!("web_apps.delete_toast.undo_failed"), true);
+            }
+            self_clone.refresh_app_list(&app_clone);
+        });
+
+        // Also after the undo button, then there is nothing left to purge
+        toast.connect_dismissed(move |_| {
+            let Some(trashed_web_app) = trashed_web_app.take() else {
+                return;
+            };
+            let staging_dir = trashed_web_app.get_staging_dir();
+            gio::spawn_blocking(move || {
+                if let Err(error) = trash::purge_staging_dir(&staging_dir) {
+                    error!("{error:?}");
+                }
+            });
         });
 
-        *app.has_created_apps.borrow_mut() = !owned_desktop_files.is_empty();
+        self.toast_overlay.add_toast(toast);
+    }
 
-        (owned_desktop_files, app_has_updated)
+    fn invalidate_app_entry(self: &Rc<Self>, desktop_file: &Rc<RefCell<DesktopFile>>) {
+        if let Some(app_entry) = self
+            .app_entries
+            .borrow_mut()
+            .values_mut()
+            .find(|app_entry| Rc::ptr_eq(&app_entry.desktop_file, desktop_file))
+        {
+            app_entry.modified = None;
+        }
     }
 
-    fn reset_app_section(self: &Rc<Self>, app: &Rc<App>) {
-        self.prefs_page.remove(&*self.app_section.borrow());
-        *self.app_section.borrow_mut() = self.clone().build_apps_section(app);
-        self.prefs_page.add(&*self.app_section.borrow());
+    /// Only on start, a refresh can run while a new app is still being edited.
+    /// Can't tell which icons are in use if a desktop file could not be read.
+    fn remove_orphaned_icons(&self, app: &Rc<App>) {
+        if *self.has_unreadable_files.borrow() {
+            debug!("Not removing orphaned icons, a desktop file could not be read");
+            return;
+        }
+
+        let mut kept_ids: HashSet<String> = self
+            .app_entries
+            .borrow()
+            .values()
+            .filter_map(|app_entry| app_entry.desktop_file.borrow().get_id())
+            .collect();
+        kept_ids.extend(self.open_editor_ids.borrow().iter().cloned());
+        removal::remove_orphaned_icons(&app.dirs.app_data_icons, &kept_ids);
+    }
+
+    fn add_open_editor(&self, desktop_file: &Rc<RefCell<DesktopFile>>) -> Option<String> {
+        let id = desktop_file.borrow().get_id()?;
+        self.open_editor_ids.borrow_mut().insert(id.clone());
+        Some(id)
+    }
+
+    fn remove_open_editor(&self, id: Option<&str>) {
+        if let Some(id) = id {
+            self.open_editor_ids.borrow_mut().remove(id);
+        }
     }
 }
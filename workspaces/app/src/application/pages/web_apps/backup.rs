@@ -0,0 +1,333 @@
+use super::WebAppsPage;
+use crate::application::App;
+use anyhow::{Error, Result, anyhow, bail};
+use common::{
+    backup::{self, BackupApp, BackupSource, RestoreTarget},
+    desktop_file::DesktopFile,
+};
+use gtk::{
+    CheckButton, FileDialog, FileFilter,
+    gio::{self, Cancellable, ListStore},
+    glib,
+    prelude::{CheckButtonExt, FileExt},
+};
+use libadwaita::{
+    AlertDialog, ResponseAppearance,
+    prelude::{AdwDialogExt, AlertDialogExt},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{debug, error};
+
+/// Back up and restore all web apps at once, the file work runs off the main thread
+impl WebAppsPage {
+    pub fn backup_all(self: &Rc<Self>, app: &Rc<App>) {
+        self.refresh_app_list(app);
+        let app_count = self.get_owned_desktop_files().len();
+        if app_count == 0 {
+            self.show_toast(&t!("web_apps.backup.no_apps"), false);
+            return;
+        }
+
+        let dialog_cancel = "cancel";
+        let dialog_ok = "ok";
+
+        // Profiles hold the browser data of every app, that can be gigabytes
+        let include_profiles = CheckButton::builder()
+            .label(t!("web_apps.backup.dialog.include_profiles"))
+            .build();
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.backup.dialog.title"))
+            .body(t!("web_apps.backup.dialog.text", count = app_count))
+            .extra_child(&include_profiles)
+            .build();
+
+        dialog.add_response(dialog_cancel, &t!("web_apps.backup.dialog.cancel"));
+        dialog.add_response(dialog_ok, &t!("web_apps.backup.dialog.ok"));
+        dialog.set_response_appearance(dialog_ok, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(dialog_ok));
+        dialog.set_close_response(dialog_cancel);
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        dialog.connect_response(Some(dialog_ok), move |_, _| {
+            self_clone.save_backup(&app_clone, include_profiles.is_active());
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+
+    pub fn restore(self: &Rc<Self>, app: &Rc<App>) {
+        let file_filter = FileFilter::new();
+        file_filter.set_name(Some(&t!("web_apps.restore.filter")));
+        file_filter.add_suffix(backup::EXTENSION);
+        let filters = ListStore::new::<FileFilter>();
+        filters.append(&file_filter);
+
+        let file_dialog = FileDialog::builder()
+            .title(t!("web_apps.restore.title"))
+            .filters(&filters)
+            .default_filter(&file_filter)
+            .build();
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+
+        file_dialog.open(
+            Some(&app.window.adw_window),
+            None::<&Cancellable>,
+            move |file| {
+                let Ok(file) = file else {
+                    debug!("No file selected for restore");
+                    return;
+                };
+                let Some(path) = file.path() else {
+                    error!("Failed to get path");
+                    return;
+                };
+
+                let self_clone = self_clone.clone();
+                let app_clone = app_clone.clone();
+                glib::spawn_future_local(async move {
+                    self_clone.restore_backup(&app_clone, path).await;
+                });
+            },
+        );
+    }
+
+    fn save_backup(self: &Rc<Self>, app: &Rc<App>, include_profiles: bool) {
+        let date = glib::DateTime::now_local()
+            .and_then(|date_time| date_time.format("%Y-%m-%d-%H%M"))
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+        let file_dialog = FileDialog::builder()
+            .title(t!("web_apps.backup.title"))
+            .initial_name(format!("web-app-hub-backup-{date}.{}", backup::EXTENSION))
+            .build();
+
+        let self_clone = self.clone();
+
+        file_dialog.save(
+            Some(&app.window.adw_window),
+            None::<&Cancellable>,
+            move |file| {
+                let Ok(file) = file else {
+                    debug!("No file selected for backup");
+                    return;
+                };
+                let Some(path) = file.path() else {
+                    error!("Failed to get path");
+                    return;
+                };
+
+                let sources: Vec<BackupSource> = self_clone
+                    .get_owned_desktop_files()
+                    .iter()
+                    .filter_map(|desktop_file| {
+                        BackupSource::from_desktop_file(&desktop_file.borrow(), include_profiles)
+                    })
+                    .collect();
+                let app_count = sources.len();
+
+                let self_clone = self_clone.clone();
+                glib::spawn_future_local(async move {
+                    self_clone.show_toast(&t!("web_apps.backup.running"), false);
+
+                    let result = gio::spawn_blocking(move || backup::create(&path, &sources))
+                        .await
+                        .unwrap_or_else(|_| Err(anyhow!("Backup thread panicked")));
+
+                    match result {
+                        Ok(()) => self_clone
+                            .show_toast(&t!("web_apps.backup.done", count = app_count), false),
+                        Err(error) => {
+                            error!("Failed to create backup: {error:?}");
+                            self_clone.show_toast(
+                                &t!("web_apps.backup.failed", error = error.to_string()),
+                                true,
+                            );
+                        }
+                    }
+                });
+            },
+        );
+    }
+
+    async fn restore_backup(self: &Rc<Self>, app: &Rc<App>, path: PathBuf) {
+        let path_clone = path.clone();
+        let apps = match gio::spawn_blocking(move || backup::read_apps(&path_clone))
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("Restore thread panicked")))
+        {
+            Ok(apps) => apps,
+            Err(error) => {
+                error!("Failed to read backup: {error:?}");
+                self.show_restore_failed(&error);
+                return;
+            }
+        };
+
+        self.refresh_app_list(app);
+        let mut existing_ids: HashSet<String> = self
+            .get_owned_desktop_files()
+            .iter()
+            .filter_map(|desktop_file| desktop_file.borrow().get_id())
+            .collect();
+
+        let mut skipped = 0;
+        let mut failed = 0;
+        let mut desktop_files = Vec::new();
+        let mut targets = HashMap::new();
+
+        for backup_app in &apps {
+            // Also skips an app that is twice in the backup
+            if !existing_ids.insert(backup_app.app_id.clone()) {
+                debug!(app_id = backup_app.app_id, "Skipping existing web app");
+                skipped += 1;
+                continue;
+            }
+
+            match Self::build_restored_desktop_file(app, backup_app) {
+                Ok((desktop_file, target)) => {
+                    targets.insert(backup_app.app_id.clone(), target);
+                    desktop_files.push(desktop_file);
+                }
+                Err(error) => {
+                    error!(
+                        app_id = backup_app.app_id,
+                        "Failed to restore web app: {error:?}"
+                    );
+                    failed += 1;
+                }
+            }
+        }
+
+        if !targets.is_empty() {
+            self.show_toast(&t!("web_apps.restore.running"), false);
+
+            let result = gio::spawn_blocking(move || backup::restore(&path, &apps, &targets))
+                .await
+                .unwrap_or_else(|_| Err(anyhow!("Restore thread panicked")));
+            if let Err(error) = result {
+                error!("Failed to restore backup: {error:?}");
+                self.show_restore_failed(&error);
+                return;
+            }
+        }
+
+        let mut restored = 0;
+        for mut desktop_file in desktop_files {
+            match desktop_file.save() {
+                Ok(_) => restored += 1,
+                Err(error) => {
+                    error!("Failed to save restored web app: {error:?}");
+                    failed += 1;
+                }
+            }
+        }
+
+        // Same path as on startup, so restored apps of an older version get migrated
+        self.refresh_app_list(app);
+
+        let message = if failed == 0 {
+            t!(
+                "web_apps.restore.done",
+                restored = restored,
+                skipped = skipped
+            )
+        } else {
+            t!(
+                "web_apps.restore.done_with_failed",
+                restored = restored,
+                skipped = skipped,
+                failed = failed
+            )
+        };
+        self.show_toast(&message, failed > 0);
+    }
+
+    /// Paths of the other machine are replaced, the files are extracted to the new paths later
+    fn build_restored_desktop_file(
+        app: &Rc<App>,
+        backup_app: &BackupApp,
+    ) -> Result<(DesktopFile, RestoreTarget)> {
+        // Both end up in file names
+        if !backup_app.app_id.chars().all(char::is_alphanumeric) {
+            bail!("Invalid id in backup")
+        }
+        if Path::new(&backup_app.file_name).file_name()
+            != Some(Path::new(&backup_app.file_name).as_os_str())
+        {
+            bail!("Invalid file name in backup")
+        }
+
+        let desktop_path = app.dirs.user_applications.join(&backup_app.file_name);
+        if desktop_path.exists() {
+            bail!("Desktop file already exists: '{}'", desktop_path.display())
+        }
+
+        let mut desktop_file = DesktopFile::from_string(
+            &desktop_path,
+            &backup_app.desktop_entry,
+            &app.browser_configs,
+            &app.dirs,
+        )?;
+        if !desktop_file.get_is_owned_app()
+            || desktop_file.get_id() != Some(backup_app.app_id.clone())
+        {
+            bail!("Desktop file in backup is not a web app of this app")
+        }
+
+        let browser = DesktopFile::find_local_browser(
+            backup_app.browser_id.as_deref().unwrap_or_default(),
+            backup_app.browser_config_name.as_deref(),
+            &app.browser_configs,
+        );
+        if let Some(browser) = &browser {
+            desktop_file.set_browser(browser);
+        }
+        // Restored with its own id, a new id would not match the backup's icon and profile
+        if desktop_file.is_id_taken() {
+            bail!("A web app with the same id already exists")
+        }
+
+        let icon_path = backup_app.icon_file.as_ref().map(|icon_file| {
+            // Part of the file name, so only plain extensions
+            let extension = Path::new(icon_file)
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_string())
+                .filter(|extension| extension.chars().all(char::is_alphanumeric))
+                .map(|extension| format!(".{extension}"))
+                .unwrap_or_default();
+            app.dirs
+                .app_data_icons
+                .join(format!("{}{extension}", backup_app.app_id))
+        });
+        desktop_file.set_icon_path(icon_path.as_deref().unwrap_or(Path::new("")));
+
+        let can_isolate = browser.as_ref().is_some_and(|browser| browser.can_isolate);
+        let profile_path = if can_isolate && desktop_file.get_isolated().unwrap_or(false) {
+            Some(desktop_file.build_profile_path()?)
+        } else {
+            None
+        };
+        desktop_file.set_profile_path(profile_path.as_deref().unwrap_or(Path::new("")));
+
+        let target = RestoreTarget {
+            icon_path,
+            profile_path: profile_path.filter(|_| backup_app.has_profile),
+        };
+
+        Ok((desktop_file, target))
+    }
+
+    fn show_restore_failed(self: &Rc<Self>, error: &Error) {
+        self.show_toast(
+            &t!("web_apps.restore.failed", error = error.to_string()),
+            true,
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::{browsers::Browser, desktop_file::DesktopFile};
+use gtk::{ListBox, SelectionMode, StringList, gio, glib};
+use libadwaita::{
+    AlertDialog, ComboRow, ResponseAppearance, SwitchRow,
+    prelude::{AdwDialogExt, AlertDialogExt, ComboRowExt},
+};
+use std::{cell::RefCell, fs, rc::Rc};
+use tracing::{debug, error};
+
+/// Web apps of an uninstalled browser, moved to another browser one by one or all at once
+impl WebAppsPage {
+    fn get_browser_missing_desktop_files(&self) -> Vec<Rc<RefCell<DesktopFile>>> {
+        self.app_entries
+            .borrow()
+            .values()
+            .filter(|app_entry| app_entry.desktop_file.borrow().is_browser_missing())
+            .map(|app_entry| app_entry.desktop_file.clone())
+            .collect()
+    }
+
+    pub fn reset_browser_missing_banner(&self) {
+        let count = self.get_browser_missing_desktop_files().len();
+        if count == 0 {
+            self.browser_missing_banner.set_revealed(false);
+            return;
+        }
+
+        debug!(count, "Found web apps with a missing browser");
+        self.browser_missing_banner
+            .set_title(&t!("web_apps.browser_migration.banner", count = count));
+        self.browser_missing_banner.set_revealed(true);
+    }
+
+    /// With a desktop file the dialog is for that app, with an option to do all of them
+    pub fn show_browser_migration_dialog(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        desktop_file: Option<&Rc<RefCell<DesktopFile>>>,
+    ) {
+        let affected_desktop_files = self.get_browser_missing_desktop_files();
+        let browsers: Vec<Rc<Browser>> = app
+            .browser_configs
+            .get_all_browsers()
+            .into_iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .collect();
+        if browsers.is_empty() {
+            self.show_toast(&t!("web_apps.browser_migration.no_browsers"), true);
+            return;
+        }
+
+        let dialog_cancel = "cancel";
+        let dialog_migrate = "migrate";
+
+        let names: Vec<String> = browsers
+            .iter()
+            .map(|browser| browser.get_name_with_installation())
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let default_index = app
+            .preferences
+            .borrow()
+            .get_default_browser(&app.browser_configs)
+            .and_then(|default_browser| {
+                browsers
+                    .iter()
+                    .position(|browser| browser.id == default_browser.id)
+            })
+            .and_then(|index| index.try_into().ok())
+            .unwrap_or(0);
+        let browser_row = ComboRow::builder()
+            .title(t!("web_apps.browser_migration.dialog.browser"))
+            .model(&StringList::new(&names))
+            .selected(default_index)
+            .build();
+
+        let apply_all_row = SwitchRow::builder()
+            .title(t!(
+                "web_apps.browser_migration.dialog.apply_all",
+                count = affected_desktop_files.len()
+            ))
+            .active(desktop_file.is_none())
+            .visible(desktop_file.is_some() && affected_desktop_files.len() > 1)
+            .build();
+
+        let has_profiles = affected_desktop_files.iter().any(|desktop_file| {
+            desktop_file
+                .borrow()
+                .get_profile_path()
+                .is_some_and(|profile_path| profile_path.is_dir())
+        });
+        let remove_profiles_row = SwitchRow::builder()
+            .title(t!(
+                "web_apps.browser_migration.dialog.remove_profiles.title"
+            ))
+            .subtitle(t!(
+                "web_apps.browser_migration.dialog.remove_profiles.subtitle"
+            ))
+            .visible(has_profiles)
+            .build();
+
+        let option_list = ListBox::builder()
+            .selection_mode(SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        option_list.append(&browser_row);
+        option_list.append(&apply_all_row);
+        option_list.append(&remove_profiles_row);
+
+        let body = match desktop_file {
+            Some(desktop_file) => t!(
+                "web_apps.browser_migration.dialog.text_one",
+                name = desktop_file
+                    .borrow()
+                    .get_name()
+                    .unwrap_or(t!("web_apps.no_name").to_string())
+            ),
+            None => t!(
+                "web_apps.browser_migration.dialog.text",
+                count = affected_desktop_files.len()
+            ),
+        };
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.browser_migration.dialog.title"))
+            .body(body)
+            .extra_child(&option_list)
+            .build();
+
+        dialog.add_response(
+            dialog_cancel,
+            &t!("web_apps.browser_migration.dialog.cancel"),
+        );
+        dialog.add_response(
+            dialog_migrate,
+            &t!("web_apps.browser_migration.dialog.migrate"),
+        );
+        dialog.set_response_appearance(dialog_migrate, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(dialog_migrate));
+        dialog.set_close_response(dialog_cancel);
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let desktop_file = desktop_file.cloned();
+        dialog.connect_response(Some(dialog_migrate), move |_, _| {
+            let Some(browser) = usize::try_from(browser_row.selected())
+                .ok()
+                .and_then(|index| browsers.get(index))
+            else {
+                return;
+            };
+            let desktop_files = match &desktop_file {
+                Some(desktop_file) if !apply_all_row.is_active() => vec![desktop_file.clone()],
+                _ => affected_desktop_files.clone(),
+            };
+
+            self_clone.migrate_browser(
+                &app_clone,
+                &desktop_files,
+                browser,
+                remove_profiles_row.is_active(),
+            );
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+
+    /// Also used to change the browser of apps on purpose, the summary names the apps that failed
+    pub fn migrate_browser(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        desktop_files: &[Rc<RefCell<DesktopFile>>],
+        browser: &Rc<Browser>,
+        remove_old_profiles: bool,
+    ) {
+        let mut old_profile_paths = Vec::new();
+        let mut migrated_count = 0;
+        let mut failed_names = Vec::new();
+
+        for desktop_file in desktop_files {
+            let result = desktop_file.borrow_mut().migrate_browser(browser);
+            match result {
+                Ok(old_profile_path) => {
+                    migrated_count += 1;
+                    old_profile_paths.extend(old_profile_path);
+                }
+                Err(error) => {
+                    error!("Failed to migrate web app: {error:?}");
+                    failed_names.push(
+                        desktop_file
+                            .borrow()
+                            .get_name()
+                            .unwrap_or(t!("web_apps.no_name").to_string()),
+                    );
+                }
+            }
+        }
+
+        // Saving can rename the desktop files, they are read again
+        self.refresh_app_list(app);
+
+        if failed_names.is_empty() {
+            self.show_toast(
+                &t!(
+                    "web_apps.browser_migration.done",
+                    count = migrated_count,
+                    browser = browser.get_name_with_installation()
+                ),
+                false,
+            );
+        } else {
+            self.show_toast(
+                &t!(
+                    "web_apps.browser_migration.failed",
+                    count = migrated_count,
+                    names = failed_names.join(", ")
+                ),
+                true,
+            );
+        }
+
+        if !remove_old_profiles || old_profile_paths.is_empty() {
+            return;
+        }
+        // Profiles can be large, removing them should not block the window
+        glib::spawn_future_local(async move {
+            let _ = gio::spawn_blocking(move || {
+                for profile_path in old_profile_paths {
+                    if let Err(error) = fs::remove_dir_all(&profile_path) {
+                        error!(
+                            path = %profile_path.display(),
+                            "Failed to remove old profile: {error:?}"
+                        );
+                    }
+                }
+            })
+            .await;
+        });
+    }
+}
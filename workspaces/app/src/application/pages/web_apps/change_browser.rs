@@ -0,0 +1,260 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::{
+    browsers::Browser,
+    desktop_file::{DesktopFile, browser_migration::DroppedOption},
+};
+use gtk::{
+    Label, ListBox, Orientation, PolicyType, ScrolledWindow, SelectionMode, StringList,
+    glib::prelude::ObjectExt,
+    prelude::{BoxExt, WidgetExt},
+};
+use libadwaita::{
+    ActionRow, AlertDialog, ComboRow, ResponseAppearance, SwitchRow,
+    prelude::{AdwDialogExt, AlertDialogExt, ComboRowExt},
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Moves every web app of one browser to another, for switching to a different daily browser
+impl WebAppsPage {
+    pub fn show_change_browser_dialog(self: &Rc<Self>, app: &Rc<App>) {
+        let mut desktop_files: Vec<Rc<RefCell<DesktopFile>>> = self
+            .app_entries
+            .borrow()
+            .values()
+            .map(|app_entry| app_entry.desktop_file.clone())
+            .collect();
+        desktop_files.sort_by_key(|desktop_file| {
+            desktop_file
+                .borrow()
+                .get_name()
+                .unwrap_or_default()
+                .to_lowercase()
+        });
+
+        let all_browsers = app.browser_configs.get_all_browsers();
+        // Only browsers with web apps are worth moving away from
+        let source_browsers: Vec<Rc<Browser>> = all_browsers
+            .iter()
+            .filter(|browser| {
+                !browser.is_no_browser()
+                    && desktop_files.iter().any(|desktop_file| {
+                        desktop_file.borrow().get_browser_id().as_ref() == Some(&browser.id)
+                    })
+            })
+            .cloned()
+            .collect();
+        let target_browsers: Vec<Rc<Browser>> = all_browsers
+            .into_iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .collect();
+        if source_browsers.is_empty() {
+            self.show_toast(&t!("web_apps.change_browser.no_apps"), false);
+            return;
+        }
+        if target_browsers.is_empty() {
+            self.show_toast(&t!("web_apps.browser_migration.no_browsers"), true);
+            return;
+        }
+
+        let dialog_cancel = "cancel";
+        let dialog_apply = "apply";
+
+        let source_row = Self::build_browser_combo_row(
+            &t!("web_apps.change_browser.dialog.source"),
+            &source_browsers,
+            0,
+        );
+        let default_target_index = app
+            .preferences
+            .borrow()
+            .get_default_browser(&app.browser_configs)
+            .and_then(|default_browser| {
+                target_browsers
+                    .iter()
+                    .position(|browser| browser.id == default_browser.id)
+            })
+            .unwrap_or(0);
+        let target_row = Self::build_browser_combo_row(
+            &t!("web_apps.change_browser.dialog.target"),
+            &target_browsers,
+            default_target_index,
+        );
+        let remove_profiles_row = SwitchRow::builder()
+            .title(t!(
+                "web_apps.browser_migration.dialog.remove_profiles.title"
+            ))
+            .subtitle(t!(
+                "web_apps.browser_migration.dialog.remove_profiles.subtitle"
+            ))
+            .build();
+
+        let option_list = ListBox::builder()
+            .selection_mode(SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        option_list.append(&source_row);
+        option_list.append(&target_row);
+        option_list.append(&remove_profiles_row);
+
+        let preview_label = Label::builder().wrap(true).build();
+        let preview_list = ListBox::builder()
+            .selection_mode(SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        let preview_scrolled_window = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(240)
+            .child(&preview_list)
+            .build();
+
+        let content = gtk::Box::new(Orientation::Vertical, 12);
+        content.append(&option_list);
+        content.append(&preview_label);
+        content.append(&preview_scrolled_window);
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.change_browser.dialog.title"))
+            .body(t!("web_apps.change_browser.dialog.text"))
+            .extra_child(&content)
+            .build();
+
+        dialog.add_response(dialog_cancel, &t!("web_apps.change_browser.dialog.cancel"));
+        dialog.add_response(dialog_apply, &t!("web_apps.change_browser.dialog.apply"));
+        dialog.set_response_appearance(dialog_apply, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(dialog_apply));
+        dialog.set_close_response(dialog_cancel);
+
+        let affected_desktop_files: Rc<RefCell<Vec<Rc<RefCell<DesktopFile>>>>> =
+            Rc::new(RefCell::new(Vec::new()));
+        let source_browsers = Rc::new(source_browsers);
+        let target_browsers = Rc::new(target_browsers);
+
+        let update_preview = {
+            // The rows are in the dialog, a strong reference would keep it alive
+            let dialog = dialog.downgrade();
+            let source_row = source_row.clone();
+            let target_row = target_row.clone();
+            let source_browsers = source_browsers.clone();
+            let target_browsers = target_browsers.clone();
+            let affected_desktop_files = affected_desktop_files.clone();
+
+            Rc::new(move || {
+                let (Some(source_browser), Some(target_browser)) = (
+                    Self::get_selected_browser(&source_row, &source_browsers),
+                    Self::get_selected_browser(&target_row, &target_browsers),
+                ) else {
+                    return;
+                };
+
+                let affected: Vec<Rc<RefCell<DesktopFile>>> =
+                    if source_browser.id == target_browser.id {
+                        Vec::new()
+                    } else {
+                        desktop_files
+                            .iter()
+                            .filter(|desktop_file| {
+                                desktop_file.borrow().get_browser_id().as_ref()
+                                    == Some(&source_browser.id)
+                            })
+                            .cloned()
+                            .collect()
+                    };
+
+                preview_label.set_label(&t!(
+                    "web_apps.change_browser.dialog.preview",
+                    count = affected.len()
+                ));
+
+                // Only apps that lose an option are listed, the others change without surprises
+                preview_list.remove_all();
+                for desktop_file in &affected {
+                    let desktop_file_borrow = desktop_file.borrow();
+                    let dropped_options = desktop_file_borrow.get_dropped_options(&target_browser);
+                    if dropped_options.is_empty() {
+                        continue;
+                    }
+
+                    let dropped_labels: Vec<String> = dropped_options
+                        .into_iter()
+                        .map(Self::get_dropped_option_label)
+                        .collect();
+                    let row = ActionRow::builder()
+                        .title(
+                            desktop_file_borrow
+                                .get_name()
+                                .unwrap_or(t!("web_apps.no_name").to_string()),
+                        )
+                        .subtitle(t!(
+                            "web_apps.change_browser.dialog.dropped",
+                            options = dropped_labels.join(", ")
+                        ))
+                        .build();
+                    preview_list.append(&row);
+                }
+                preview_scrolled_window.set_visible(preview_list.first_child().is_some());
+
+                if let Some(dialog) = dialog.upgrade() {
+                    dialog.set_response_enabled(dialog_apply, !affected.is_empty());
+                }
+                *affected_desktop_files.borrow_mut() = affected;
+            })
+        };
+        update_preview();
+
+        let update_preview_clone = update_preview.clone();
+        source_row.connect_selected_notify(move |_| update_preview_clone());
+        target_row.connect_selected_notify(move |_| update_preview());
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        dialog.connect_response(Some(dialog_apply), move |_, _| {
+            let Some(target_browser) = Self::get_selected_browser(&target_row, &target_browsers)
+            else {
+                return;
+            };
+
+            self_clone.migrate_browser(
+                &app_clone,
+                &affected_desktop_files.borrow(),
+                &target_browser,
+                remove_profiles_row.is_active(),
+            );
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+
+    fn build_browser_combo_row(title: &str, browsers: &[Rc<Browser>], selected: usize) -> ComboRow {
+        let names: Vec<String> = browsers
+            .iter()
+            .map(|browser| browser.get_name_with_installation())
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        ComboRow::builder()
+            .title(title)
+            .model(&StringList::new(&names))
+            .selected(selected.try_into().unwrap_or(0))
+            .build()
+    }
+
+    fn get_selected_browser(combo_row: &ComboRow, browsers: &[Rc<Browser>]) -> Option<Rc<Browser>> {
+        usize::try_from(combo_row.selected())
+            .ok()
+            .and_then(|index| browsers.get(index))
+            .cloned()
+    }
+
+    fn get_dropped_option_label(dropped_option: DroppedOption) -> String {
+        match dropped_option {
+            DroppedOption::Isolation => t!("web_apps.web_app_view.isolate.title"),
+            DroppedOption::Maximize => t!("web_apps.web_app_view.maximize.title"),
+            DroppedOption::Fullscreen => t!("web_apps.web_app_view.fullscreen.title"),
+            DroppedOption::Private => t!("web_apps.web_app_view.private.title"),
+            DroppedOption::ForceDark => t!("web_apps.web_app_view.force_dark.title"),
+        }
+        .to_string()
+    }
+}
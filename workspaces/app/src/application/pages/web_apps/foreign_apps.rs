@@ -0,0 +1,141 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::desktop_file::{
+    DesktopFile,
+    foreign::{ForeignSource, ForeignWebApp},
+};
+use gtk::{
+    CheckButton, ListBox, Orientation, PolicyType, ScrolledWindow, SelectionMode,
+    prelude::{BoxExt, CheckButtonExt},
+};
+use libadwaita::{
+    ActionRow, AlertDialog, ResponseAppearance,
+    prelude::{ActionRowExt, AdwDialogExt, AlertDialogExt},
+};
+use std::rc::Rc;
+use tracing::{debug, error};
+
+/// Import web apps that Chrome or GNOME Web made, their launchers are left alone unless asked
+impl WebAppsPage {
+    pub fn import_foreign_apps(self: &Rc<Self>, app: &Rc<App>) {
+        let foreign_web_apps = DesktopFile::find_foreign_web_apps(&app.dirs);
+        if foreign_web_apps.is_empty() {
+            self.show_toast(&t!("web_apps.foreign_import.none_found"), false);
+            return;
+        }
+
+        let dialog_cancel = "cancel";
+        let dialog_ok = "ok";
+
+        let app_list = ListBox::builder()
+            .selection_mode(SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        let mut app_checks = Vec::new();
+
+        for foreign_web_app in &foreign_web_apps {
+            let source = match foreign_web_app.source {
+                ForeignSource::Chromium => t!("web_apps.foreign_import.source.chromium"),
+                ForeignSource::Epiphany => t!("web_apps.foreign_import.source.epiphany"),
+            };
+            let check = CheckButton::builder().active(true).build();
+            let row = ActionRow::builder()
+                .title(foreign_web_app.name.clone())
+                .subtitle(format!("{} · {source}", foreign_web_app.url))
+                .activatable_widget(&check)
+                .build();
+            row.add_prefix(&check);
+            app_list.append(&row);
+            app_checks.push(check);
+        }
+
+        let scrolled_window = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(300)
+            .child(&app_list)
+            .build();
+        let remove_launchers = CheckButton::builder()
+            .label(t!("web_apps.foreign_import.dialog.remove_launchers"))
+            .build();
+        let content = gtk::Box::new(Orientation::Vertical, 12);
+        content.append(&scrolled_window);
+        content.append(&remove_launchers);
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.foreign_import.dialog.title"))
+            .body(t!("web_apps.foreign_import.dialog.text"))
+            .extra_child(&content)
+            .build();
+
+        dialog.add_response(dialog_cancel, &t!("web_apps.foreign_import.dialog.cancel"));
+        dialog.add_response(dialog_ok, &t!("web_apps.foreign_import.dialog.ok"));
+        dialog.set_response_appearance(dialog_ok, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(dialog_ok));
+        dialog.set_close_response(dialog_cancel);
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        dialog.connect_response(Some(dialog_ok), move |_, _| {
+            let selected: Vec<&ForeignWebApp> = foreign_web_apps
+                .iter()
+                .zip(&app_checks)
+                .filter(|(_, check)| check.is_active())
+                .map(|(foreign_web_app, _)| foreign_web_app)
+                .collect();
+
+            self_clone.save_foreign_apps(&app_clone, &selected, remove_launchers.is_active());
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+
+    fn save_foreign_apps(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        foreign_web_apps: &[&ForeignWebApp],
+        remove_launchers: bool,
+    ) {
+        let mut imported = 0;
+        let mut failed = 0;
+
+        for foreign_web_app in foreign_web_apps {
+            let result =
+                DesktopFile::from_foreign_web_app(foreign_web_app, &app.browser_configs, &app.dirs)
+                    .and_then(|mut desktop_file| desktop_file.save().map_err(Into::into));
+
+            if let Err(error) = result {
+                error!(
+                    path = %foreign_web_app.path.display(),
+                    "Failed to import foreign web app: {error:?}"
+                );
+                failed += 1;
+                continue;
+            }
+            imported += 1;
+
+            // Only after the new app is saved, so nothing gets lost
+            if remove_launchers && let Err(error) = foreign_web_app.remove_launcher() {
+                error!("{error:?}");
+            }
+        }
+
+        debug!(
+            imported = imported,
+            failed = failed,
+            "Imported foreign web apps"
+        );
+        self.refresh_app_list(app);
+
+        let message = if failed == 0 {
+            t!("web_apps.foreign_import.done", count = imported)
+        } else {
+            t!(
+                "web_apps.foreign_import.done_with_failed",
+                count = imported,
+                failed = failed
+            )
+        };
+        self.show_toast(&message, failed > 0);
+    }
+}
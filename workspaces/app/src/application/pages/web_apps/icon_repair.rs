@@ -0,0 +1,108 @@
+use super::WebAppsPage;
+use crate::application::{App, pages::web_apps::web_app_view::icon_picker::IconPicker};
+use anyhow::{Context, Result};
+use common::desktop_file::DesktopFile;
+use gtk::{Image, glib, prelude::WidgetExt};
+use std::{cell::RefCell, path::PathBuf, rc::Rc, time::Duration};
+use tracing::{debug, error, info};
+
+/// A web app with a missing icon file and the image of its row
+pub struct IconRepair {
+    desktop_file: Rc<RefCell<DesktopFile>>,
+    icon: Image,
+}
+
+/// Missing icons are fetched again one app at a time, so many broken apps don't flood the
+/// network. The list shows right away, a row gets its icon when the fetch is done.
+impl WebAppsPage {
+    /// Pause between two fetches
+    const ICON_REPAIR_INTERVAL: Duration = Duration::from_secs(2);
+
+    pub(super) fn queue_icon_repair(
+        self: &Rc<Self>,
+        app: &Rc<App>,
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+        icon: &Image,
+    ) {
+        let desktop_file_borrow = desktop_file.borrow();
+        let Some(app_id) = desktop_file_borrow.get_id() else {
+            return;
+        };
+        if desktop_file_borrow
+            .get_url()
+            .is_none_or(|url| url.is_empty())
+        {
+            return;
+        }
+        drop(desktop_file_borrow);
+
+        if !self.icon_repair_ids.borrow_mut().insert(app_id.clone()) {
+            return;
+        }
+        debug!(app_id, "Queued icon repair");
+        self.icon_repair_queue.borrow_mut().push_back(IconRepair {
+            desktop_file: desktop_file.clone(),
+            icon: icon.clone(),
+        });
+
+        if self.is_repairing_icons.replace(true) {
+            return;
+        }
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        glib::spawn_future_local(async move {
+            self_clone.run_icon_repairs(&app_clone).await;
+        });
+    }
+
+    async fn run_icon_repairs(self: &Rc<Self>, app: &Rc<App>) {
+        loop {
+            let Some(icon_repair) = self.icon_repair_queue.borrow_mut().pop_front() else {
+                break;
+            };
+
+            match Self::repair_icon(app, &icon_repair.desktop_file).await {
+                Ok(Some(icon_path)) => {
+                    icon_repair.icon.set_from_file(Some(&icon_path));
+                    icon_repair.icon.remove_css_class("error");
+                }
+                Ok(None) => {}
+                Err(error) => error!("Failed to repair icon: {error:?}"),
+            }
+
+            if !self.icon_repair_queue.borrow().is_empty() {
+                glib::timeout_future(Self::ICON_REPAIR_INTERVAL).await;
+            }
+        }
+
+        *self.is_repairing_icons.borrow_mut() = false;
+    }
+
+    /// `None` when the icon is back already, the user could have picked one meanwhile
+    async fn repair_icon(
+        app: &Rc<App>,
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+    ) -> Result<Option<PathBuf>> {
+        if !desktop_file.borrow().is_icon_missing() {
+            return Ok(None);
+        }
+
+        // Fetched on a copy, the desktop file is not borrowed while waiting for the network
+        let mut repaired_desktop_file = desktop_file.borrow().clone();
+        IconPicker::save_best_online_icon(&app.fetch, &app.dirs, &mut repaired_desktop_file)
+            .await?;
+        let icon_path = repaired_desktop_file
+            .get_icon_path()
+            .context("No icon on 'DesktopFile'")?;
+
+        let mut desktop_file = desktop_file.borrow_mut();
+        desktop_file.set_icon_path(&icon_path);
+        desktop_file.save()?;
+
+        info!(
+            name = desktop_file.get_name().unwrap_or_default(),
+            "Repaired missing icon"
+        );
+        Ok(Some(icon_path))
+    }
+}
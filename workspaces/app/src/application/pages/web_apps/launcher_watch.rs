@@ -0,0 +1,210 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::{desktop_file::launcher_backend, utils};
+use gtk::{
+    gio, glib,
+    prelude::{FileExt, FileMonitorExt, WidgetExt},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, error};
+
+/// Desktop files of the launcher dirs, split by whether they need to be parsed again
+#[derive(Debug, Default)]
+pub struct LauncherScan {
+    /// New files and files changed on disk since they were parsed
+    pub changed: Vec<PathBuf>,
+    pub unchanged: Vec<PathBuf>,
+}
+
+/// `parsed_modified` is the modification time a file had when it was parsed. A file without a
+/// modification time is always parsed again.
+pub fn scan_launcher_dirs(
+    dirs: &[&Path],
+    parsed_modified: impl Fn(&Path) -> Option<SystemTime>,
+) -> LauncherScan {
+    let mut launcher_scan = LauncherScan::default();
+
+    for path in dirs
+        .iter()
+        .flat_map(|dir| utils::files::get_entries_in_dir(dir).unwrap_or_default())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "desktop")
+        })
+    {
+        let modified = get_modified(&path);
+        if modified.is_some() && parsed_modified(&path) == modified {
+            launcher_scan.unchanged.push(path);
+        } else {
+            launcher_scan.changed.push(path);
+        }
+    }
+
+    launcher_scan
+}
+
+pub fn get_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// The list is only read again when a launcher dir changed, like after a save in the editor or a
+/// launcher removed by another app. A hidden list is read when it is shown again.
+impl WebAppsPage {
+    /// Saves come in bursts, like a temp file and a rename, they get one refresh
+    const REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+    pub(super) fn watch_launcher_dirs(self: &Rc<Self>, app: &Rc<App>) {
+        let mut launcher_monitors = self.launcher_monitors.borrow_mut();
+
+        for dir in launcher_backend::get_launcher_dirs(&app.dirs) {
+            let monitor = match gio::File::for_path(dir)
+                .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => monitor,
+                Err(error) => {
+                    error!(path = %dir.display(), "Failed to watch launcher dir: {error}");
+                    continue;
+                }
+            };
+
+            let self_weak = Rc::downgrade(self);
+            let app_weak = Rc::downgrade(app);
+            monitor.connect_changed(move |_, file, _, event| {
+                if matches!(
+                    event,
+                    gio::FileMonitorEvent::AttributeChanged
+                        | gio::FileMonitorEvent::PreUnmount
+                        | gio::FileMonitorEvent::Unmounted
+                ) {
+                    return;
+                }
+                let (Some(self_clone), Some(app_clone)) = (self_weak.upgrade(), app_weak.upgrade())
+                else {
+                    return;
+                };
+                debug!(path = ?file.path(), ?event, "Launcher dir changed");
+                *self_clone.is_list_stale.borrow_mut() = true;
+                if self_clone.nav_page.is_mapped() {
+                    self_clone.queue_refresh(&app_clone);
+                }
+            });
+            launcher_monitors.push(monitor);
+        }
+    }
+
+    /// Shown again, only read when a launcher dir changed while it was hidden
+    pub(super) fn refresh_if_stale(self: &Rc<Self>, app: &Rc<App>) {
+        if *self.is_list_stale.borrow() {
+            self.refresh_app_list(app);
+        }
+    }
+
+    fn queue_refresh(self: &Rc<Self>, app: &Rc<App>) {
+        if let Some(source_id) = self.pending_refresh.take() {
+            source_id.remove();
+        }
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        let source_id = glib::timeout_add_local_once(Self::REFRESH_DELAY, move || {
+            *self_clone.pending_refresh.borrow_mut() = None;
+            self_clone.refresh_if_stale(&app_clone);
+        });
+        *self.pending_refresh.borrow_mut() = Some(source_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, fs::File};
+
+    const APP_COUNT: usize = 200;
+
+    fn write_launchers(dir: &Path) {
+        for index in 0..APP_COUNT {
+            fs::write(
+                dir.join(format!("web-app-{index}.desktop")),
+                format!("[Desktop Entry]\nType=Application\nName=App {index}\n"),
+            )
+            .unwrap();
+        }
+    }
+
+    /// Like the app entries of the page after a refresh
+    fn mark_parsed(launcher_scan: &LauncherScan) -> HashMap<PathBuf, Option<SystemTime>> {
+        launcher_scan
+            .changed
+            .iter()
+            .map(|path| (path.clone(), get_modified(path)))
+            .collect()
+    }
+
+    #[test]
+    fn first_scan_parses_every_launcher() {
+        let dir = tempfile::tempdir().unwrap();
+        write_launchers(dir.path());
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let launcher_scan = scan_launcher_dirs(&[dir.path()], |_| None);
+
+        assert_eq!(launcher_scan.changed.len(), APP_COUNT);
+        assert!(launcher_scan.unchanged.is_empty());
+    }
+
+    #[test]
+    fn unchanged_launchers_are_not_parsed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        write_launchers(dir.path());
+        let parsed = mark_parsed(&scan_launcher_dirs(&[dir.path()], |_| None));
+
+        let launcher_scan =
+            scan_launcher_dirs(&[dir.path()], |path| parsed.get(path).copied().flatten());
+
+        assert!(launcher_scan.changed.is_empty());
+        assert_eq!(launcher_scan.unchanged.len(), APP_COUNT);
+    }
+
+    #[test]
+    fn only_changed_and_new_launchers_are_parsed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        write_launchers(dir.path());
+        let parsed = mark_parsed(&scan_launcher_dirs(&[dir.path()], |_| None));
+        let changed_path = dir.path().join("web-app-7.desktop");
+        let new_path = dir.path().join("web-app-new.desktop");
+        File::options()
+            .write(true)
+            .open(&changed_path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        fs::write(&new_path, "[Desktop Entry]\n").unwrap();
+        fs::remove_file(dir.path().join("web-app-8.desktop")).unwrap();
+
+        let launcher_scan =
+            scan_launcher_dirs(&[dir.path()], |path| parsed.get(path).copied().flatten());
+        let mut changed_paths = launcher_scan.changed;
+        changed_paths.sort();
+
+        assert_eq!(changed_paths, vec![changed_path, new_path]);
+        assert_eq!(launcher_scan.unchanged.len(), APP_COUNT - 2);
+    }
+
+    #[test]
+    fn missing_dir_has_no_launchers() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let launcher_scan = scan_launcher_dirs(&[&dir.path().join("missing")], |_| None);
+
+        assert!(launcher_scan.changed.is_empty());
+        assert!(launcher_scan.unchanged.is_empty());
+    }
+}
@@ -1,67 +1,138 @@
-mod icon_picker;
-mod optional_settings;
+pub mod icon_picker;
 
 use crate::application::{
     App,
-    pages::{NavPage, PrefPage, web_apps::web_app_view::optional_settings::OptionalSettings},
+    pages::{NavPage, PrefPage, web_apps::WebAppsPage},
 };
 use anyhow::anyhow;
 use common::{
-    browsers::{Base, Browser},
-    desktop_file::{DesktopFile, error::DesktopFileError},
-    utils,
+    browsers::{Browser, version::BrowserVersion},
+    desktop_file::{
+        DesktopFile,
+        category::Category,
+        config_snippet::ConfigSnippet,
+        error::{DesktopFileError, LaunchError, ValidationError},
+        key::Key,
+        lint::LintLevel,
+    },
+    detached_profiles::{DetachedProfile, DetachedProfiles},
+    fetch::Reachability,
+    policy,
+    url::parse_user_input,
+    utils::{
+        self,
+        command::{self, BackgroundCommand, StartupStatus},
+    },
 };
 use gtk::{
-    Align, EventControllerMotion, ListItem, SignalListItemFactory, gio,
-    glib::{self, BoxedAnyObject, object::Cast},
+    Align, CallbackAction, EventControllerMotion, FileDialog, FileLauncher, ListItem, MenuButton,
+    Shortcut, ShortcutController, ShortcutTrigger, SignalListItemFactory,
+    gdk::RGBA,
+    gio::{
+        self, Cancellable, Menu, SimpleAction, SimpleActionGroup,
+        prelude::{ActionMapExt, CancellableExt, FileExt},
+    },
+    glib::{self, BoxedAnyObject, object::Cast, prelude::ObjectExt},
     prelude::ListItemExt,
 };
 use icon_picker::IconPicker;
 use libadwaita::{
-    ActionRow, ButtonContent, ComboRow, EntryRow, HeaderBar, NavigationPage, NavigationView,
-    PreferencesGroup, PreferencesPage, Spinner, SwitchRow, Toast, ToastOverlay, ToastPriority,
-    WrapBox,
+    ActionRow, AlertDialog, Banner, ButtonContent, ComboRow, EntryRow, ExpanderRow, HeaderBar,
+    NavigationPage, NavigationView, PreferencesGroup, PreferencesPage, ResponseAppearance, SpinRow,
+    Spinner, SwitchRow, Toast, ToastOverlay, ToastPriority, WrapBox,
     gtk::{
         self, Button, Image, InputPurpose, Label, Orientation,
         prelude::{BoxExt, ButtonExt, EditableExt, WidgetExt},
     },
     prelude::{
-        ActionRowExt, ComboRowExt, EntryRowExt, NavigationPageExt, PreferencesGroupExt,
-        PreferencesPageExt, PreferencesRowExt,
+        ActionRowExt, AdwDialogExt, AlertDialogExt, ComboRowExt, EntryRowExt, ExpanderRowExt,
+        NavigationPageExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
     },
 };
 use std::{
     cell::RefCell,
+    fs,
     path::{Path, PathBuf},
     rc::Rc,
+    time::Duration,
 };
-use std::{fmt::Write as _, fs};
 use tracing::{debug, error};
 use url::Url;
 
 pub struct WebAppView {
     is_new: RefCell<bool>,
+    is_discarded: RefCell<bool>,
     nav_page: NavigationPage,
     nav_view: Rc<NavigationView>,
     app: Rc<App>,
     header: HeaderBar,
+    back_button: Button,
     desktop_file: Rc<RefCell<DesktopFile>>,
     desktop_file_original: RefCell<DesktopFile>,
     prefs_page: PreferencesPage,
     pref_groups: RefCell<Vec<PreferencesGroup>>,
     toast_overlay: ToastOverlay,
+    /// Last shown, the view saves on every change and should not repeat them
+    lint_warnings: RefCell<Vec<String>>,
     reset_button: Button,
+    config_menu_button: MenuButton,
+    copy_config_action: SimpleAction,
+    paste_config_action: SimpleAction,
+    export_bundle_action: SimpleAction,
     change_icon_button: Button,
     run_app_button: Button,
     save_button: Button,
     delete_button: Button,
     name_row: EntryRow,
     url_row: EntryRow,
+    /// Marks rows of fields that failed validation, shown on save
+    field_error_icons: Vec<(Vec<Key>, Image)>,
+    url_reachability_icon: Image,
+    /// Replaced for every check, so a check of a previous url can't show its result
+    url_reachability_cancellable: RefCell<Cancellable>,
+    description_row: EntryRow,
     isolate_row: SwitchRow,
     maximize_row: SwitchRow,
+    fullscreen_row: SwitchRow,
+    minimal_ui_row: SwitchRow,
+    private_row: SwitchRow,
+    force_dark_row: SwitchRow,
+    autostart_row: SwitchRow,
+    window_width_row: SpinRow,
+    window_height_row: SpinRow,
     browser_row: ComboRow,
-    optional_row: ActionRow,
+    /// Browser detection the browser row was built from
+    browsers_generation: RefCell<u64>,
+    is_syncing_browsers: RefCell<bool>,
+    browser_issues_row: ExpanderRow,
+    browser_issue_labels: RefCell<Vec<Label>>,
+    category_row: ComboRow,
+    is_syncing_category: RefCell<bool>,
+    extra_args_row: EntryRow,
+    env_row: ExpanderRow,
+    /// One row per `KEY=VALUE` pair in the env row
+    env_entry_rows: RefCell<Vec<EntryRow>>,
+    keywords_row: EntryRow,
+    handler_url_row: EntryRow,
+    mime_types_row: EntryRow,
+    /// Only for a saved app, a new app has no file or command yet
+    show_launcher_file_row: ActionRow,
+    copy_launch_command_row: ActionRow,
+    site_hint_banner: Banner,
+    /// The installed version of the browser is older than its config supports
+    version_banner: Banner,
+    /// A flatpak browser that can't create the isolated profile
+    permission_banner: Banner,
+    permission_command: RefCell<Option<String>>,
+    /// The banner button checks again once the command is copied
+    is_permission_command_copied: RefCell<bool>,
     icon_picker: RefCell<Option<Rc<IconPicker>>>,
+    /// Name filled in from a site hint or the site, replaced by a later suggestion
+    suggested_name: RefCell<Option<String>>,
+    /// Profiles that are no longer used, deleted when the view closes so Reset can undo it
+    pending_profile_deletions: RefCell<Vec<PathBuf>>,
+    /// Changes in a burst, like flipping a switch back and forth, get one save
+    pending_save: RefCell<Option<glib::SourceId>>,
 }
 impl NavPage for WebAppView {
     fn get_navpage(&self) -> &NavigationPage {
@@ -74,6 +145,16 @@ impl NavPage for WebAppView {
 }
 impl WebAppView {
     const TOAST_MESSAGE_TIMEOUT: u32 = 4;
+    const SAVE_DEBOUNCE: Duration = Duration::from_millis(300);
+    const LAUNCHED_STATE_DURATION: Duration = Duration::from_secs(2);
+    const ACTION_LABEL: &str = "web-app-view";
+    const ACTION_COPY_CONFIG: &str = "copy-config";
+    const ACTION_PASTE_CONFIG: &str = "paste-config";
+    const ACTION_EXPORT_BUNDLE: &str = "export-bundle";
+    const ACTION_LABEL_HANDLERS: &str = "handlers";
+    const ACTION_ADD_HANDLER_SCHEME: &str = "add-handler-scheme";
+    const WINDOW_SIZE_MAX: f64 = 10000.0;
+    const WINDOW_SIZE_STEP: f64 = 10.0;
 
     pub fn new(
         app: &Rc<App>,
@@ -92,6 +173,15 @@ impl WebAppView {
         let browser_can_maximize = desktop_file_borrow
             .get_browser()
             .is_some_and(|browser| browser.can_start_maximized);
+        let browser_can_fullscreen = desktop_file_borrow
+            .get_browser()
+            .is_some_and(|browser| browser.can_fullscreen);
+        let browser_can_private = desktop_file_borrow
+            .get_browser()
+            .is_some_and(|browser| browser.can_private);
+        let browser_can_force_dark = desktop_file_borrow
+            .get_browser()
+            .is_some_and(|browser| browser.can_force_dark);
         let icon = "preferences-desktop-apps-symbolic";
         let PrefPage {
             nav_page,
@@ -102,59 +192,155 @@ impl WebAppView {
         } = Self::build_nav_page(&title, icon).with_preference_page();
         drop(desktop_file_borrow);
 
+        let back_button = Self::build_header_back_button();
         let reset_button = Self::build_header_reset_button();
+        let config_menu_button = Self::build_config_menu_button();
+        let copy_config_action = SimpleAction::new(Self::ACTION_COPY_CONFIG, None);
+        let paste_config_action = SimpleAction::new(Self::ACTION_PASTE_CONFIG, None);
+        let export_bundle_action = SimpleAction::new(Self::ACTION_EXPORT_BUNDLE, None);
         let change_icon_button = Self::build_change_icon_button();
         let run_app_button = Self::build_run_app_button(is_new);
         let save_button = Self::build_save_button(is_new);
         let delete_button = Self::build_delete_button();
         let name_row = Self::build_name_row(desktop_file);
         let url_row = Self::build_url_row(desktop_file);
+        let description_row = Self::build_description_row(desktop_file);
         let isolate_row = Self::build_isolate_row(desktop_file, browser_can_isolate);
         let maximize_row = Self::build_maximize_row(desktop_file, browser_can_maximize);
+        let fullscreen_row = Self::build_fullscreen_row(desktop_file, browser_can_fullscreen);
+        let minimal_ui_row = Self::build_minimal_ui_row(desktop_file);
+        let private_row = Self::build_private_row(desktop_file, browser_can_private);
+        let force_dark_row = Self::build_force_dark_row(desktop_file, browser_can_force_dark);
+        let autostart_row = Self::build_autostart_row(desktop_file, is_new);
+        let window_width = desktop_file.borrow().get_window_width().unwrap_or(0);
+        let window_width_row = Self::build_window_size_row(
+            &t!("web_apps.web_app_view.window_size.width"),
+            window_width,
+        );
+        let window_height = desktop_file.borrow().get_window_height().unwrap_or(0);
+        let window_height_row = Self::build_window_size_row(
+            &t!("web_apps.web_app_view.window_size.height"),
+            window_height,
+        );
         let browser_row = Self::build_browser_row(app, desktop_file, is_new);
-        let optional_row = Self::build_optional_row();
+        let field_error_icons = Self::build_field_error_icons(
+            &name_row,
+            &url_row,
+            &browser_row,
+            &isolate_row,
+            &maximize_row,
+        );
+        let browser_issues_row = ExpanderRow::builder()
+            .title(t!("browsers.issues.title"))
+            .build();
+        let category_row = Self::build_category_row(desktop_file);
+        let extra_args_row = Self::build_extra_args_row(desktop_file);
+        let env_row = Self::build_env_row();
+        let keywords_row = Self::build_keywords_row(desktop_file);
+        let handler_url_row = Self::build_handler_url_row(desktop_file);
+        let mime_types_row = Self::build_mime_types_row(desktop_file);
+        let show_launcher_file_row = Self::build_developer_row(
+            &t!("web_apps.web_app_view.advanced.show_launcher_file"),
+            "folder-open-symbolic",
+        );
+        let copy_launch_command_row = Self::build_developer_row(
+            &t!("web_apps.web_app_view.advanced.copy_launch_command"),
+            "edit-copy-symbolic",
+        );
+        let site_hint_banner = Banner::new("");
+        let version_banner = Banner::new("");
+        let permission_banner = Banner::builder()
+            .button_label(t!("web_apps.web_app_view.permission_banner.copy"))
+            .build();
 
         Rc::new(Self {
             is_new: RefCell::new(is_new),
+            is_discarded: RefCell::new(false),
             nav_page,
             nav_view: nav_view.clone(),
             app: app.clone(),
             header,
+            back_button,
             desktop_file: desktop_file.clone(),
             desktop_file_original: RefCell::new(desktop_file_original),
             prefs_page,
             pref_groups: RefCell::new(Vec::new()),
             toast_overlay,
+            lint_warnings: RefCell::new(Vec::new()),
             reset_button,
+            config_menu_button,
+            copy_config_action,
+            paste_config_action,
+            export_bundle_action,
             change_icon_button,
             run_app_button,
             save_button,
             delete_button,
             name_row,
             url_row,
+            field_error_icons,
+            url_reachability_icon: Self::build_url_reachability_icon(),
+            url_reachability_cancellable: RefCell::new(Cancellable::new()),
+            description_row,
             isolate_row,
             maximize_row,
+            fullscreen_row,
+            minimal_ui_row,
+            private_row,
+            force_dark_row,
+            autostart_row,
+            window_width_row,
+            window_height_row,
             browser_row,
-            optional_row,
+            browsers_generation: RefCell::new(app.browser_configs.get_generation()),
+            is_syncing_browsers: RefCell::new(false),
+            browser_issues_row,
+            browser_issue_labels: RefCell::new(Vec::new()),
+            category_row,
+            is_syncing_category: RefCell::new(false),
+            extra_args_row,
+            env_row,
+            env_entry_rows: RefCell::new(Vec::new()),
+            keywords_row,
+            handler_url_row,
+            mime_types_row,
+            show_launcher_file_row,
+            copy_launch_command_row,
+            site_hint_banner,
+            version_banner,
+            permission_banner,
+            permission_command: RefCell::new(None),
+            is_permission_command_copied: RefCell::new(false),
             icon_picker: RefCell::new(None),
+            suggested_name: RefCell::new(None),
+            pending_profile_deletions: RefCell::new(Vec::new()),
+            pending_save: RefCell::new(None),
         })
     }
 
     pub fn init(self: &Rc<Self>) {
         let self_clone = self.clone();
 
+        self.header.pack_end(&self.config_menu_button);
         self.header.pack_end(&self.reset_button);
         self.reset_button
             .connect_clicked(move |_| self_clone.reset_desktop_file());
+        self.connect_back_navigation();
+        self.connect_config_actions();
         let web_app_header = self.build_app_header();
+        let site_hint_group = PreferencesGroup::new();
+        site_hint_group.add(&self.site_hint_banner);
+        site_hint_group.add(&self.version_banner);
+        site_hint_group.add(&self.permission_banner);
         let general_pref_group = self.build_general_pref_group();
-        let bottom_pref_group = self.build_bottom_pref_group();
+        let advanced_pref_group = self.build_advanced_pref_group();
         let button_footer = self.build_button_footer();
 
         let mut pref_groups_borrow = self.pref_groups.borrow_mut();
         pref_groups_borrow.push(web_app_header);
+        pref_groups_borrow.push(site_hint_group);
         pref_groups_borrow.push(general_pref_group);
-        pref_groups_borrow.push(bottom_pref_group);
+        pref_groups_borrow.push(advanced_pref_group);
         pref_groups_borrow.push(button_footer);
 
         for pref_group in pref_groups_borrow.iter() {
@@ -164,12 +350,66 @@ impl WebAppView {
 
         self.connect_change_icon_button();
         self.connect_run_app_button();
+        self.connect_permission_banner();
+
+        // Browsers can be refreshed while this view is open on another page
+        let self_clone = self.clone();
+        self.nav_page
+            .connect_map(move |_| self_clone.sync_browser_row());
+
+        let self_weak = Rc::downgrade(self);
+        self.app.browser_configs.connect_changed(move || {
+            let Some(self_clone) = self_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            self_clone.sync_browser_row();
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Fetches the icon and suggestions for a url that was set before the view opened
+    pub fn apply_prefilled_url(self: &Rc<Self>) {
+        if self.url_row.text().is_empty() {
+            return;
+        }
+        self.url_row.emit_by_name::<()>("apply", &[]);
     }
 
     pub fn get_is_new(self: &Rc<Self>) -> bool {
         *self.is_new.borrow()
     }
 
+    /// Mark the view as gone, pending async work will not write anything anymore
+    pub fn discard(self: &Rc<Self>) {
+        debug!("Discarding web app view");
+        *self.is_discarded.borrow_mut() = true;
+
+        if let Some(icon_picker) = self.icon_picker.borrow().as_ref() {
+            icon_picker.discard();
+        }
+    }
+
+    /// The profile that is in use at this point is never deleted
+    pub fn delete_pending_profiles(self: &Rc<Self>) {
+        let current_profile_path = self.desktop_file.borrow().get_profile_path();
+
+        for profile_path in self.pending_profile_deletions.borrow_mut().drain(..) {
+            if current_profile_path.as_ref() == Some(&profile_path) || !profile_path.is_dir() {
+                continue;
+            }
+
+            debug!(
+                path = profile_path.display().to_string(),
+                "Deleting profile"
+            );
+            let _ = fs::remove_dir_all(profile_path);
+        }
+    }
+
+    pub fn get_is_discarded(self: &Rc<Self>) -> bool {
+        *self.is_discarded.borrow()
+    }
+
     pub fn get_icon_picker(self: &Rc<Self>) -> Rc<IconPicker> {
         if let Some(icon_picker) = self.icon_picker.borrow().clone() {
             icon_picker
@@ -180,8 +420,26 @@ impl WebAppView {
         }
     }
 
+    fn is_current_icon_picker(self: &Rc<Self>, icon_picker: &Rc<IconPicker>) -> bool {
+        self.icon_picker
+            .borrow()
+            .as_ref()
+            .is_some_and(|current_icon_picker| Rc::ptr_eq(current_icon_picker, icon_picker))
+    }
+
+    /// Leaving the page stops the requests of a running icon search and reachability check
+    pub fn cancel_icon_fetch(self: &Rc<Self>) {
+        if let Some(icon_picker) = self.icon_picker.borrow().as_ref() {
+            icon_picker.cancel_fetch();
+        }
+        self.url_reachability_cancellable.borrow().cancel();
+    }
+
     fn reset_icon_picker(self: &Rc<Self>) {
-        *self.icon_picker.borrow_mut() = None;
+        // A running fetch of the old picker should not overwrite the new icon
+        if let Some(icon_picker) = self.icon_picker.borrow_mut().take() {
+            icon_picker.discard();
+        }
     }
 
     fn reset_desktop_file(self: &Rc<Self>) {
@@ -194,26 +452,63 @@ impl WebAppView {
 
         let name = desktop_file_borrow.get_name().unwrap_or_default();
         let url = desktop_file_borrow.get_url().unwrap_or_default();
+        let description = desktop_file_borrow.get_description().unwrap_or_default();
         let is_isolated = desktop_file_borrow.get_isolated().unwrap_or(false);
+        let is_maximized = desktop_file_borrow.get_maximized().unwrap_or(false);
+        let is_fullscreen = desktop_file_borrow.get_fullscreen().unwrap_or(false);
+        let is_minimal_ui = desktop_file_borrow.get_minimal_ui().unwrap_or(true);
+        let is_private = desktop_file_borrow.get_private().unwrap_or(false);
         let browser_index = desktop_file_borrow
             .get_browser()
             .and_then(|browser| browser.get_index())
             .and_then(|index| index.try_into().ok())
             .unwrap_or(0);
+        let extra_args = desktop_file_borrow.get_extra_args().unwrap_or_default();
+        let keywords = desktop_file_borrow
+            .get_keywords()
+            .unwrap_or_default()
+            .join(", ");
+        let handler_url = desktop_file_borrow.get_handler_url().unwrap_or_default();
+        let mime_types = desktop_file_borrow.get_mime_types_ui();
+        let window_width = desktop_file_borrow.get_window_width().unwrap_or(0);
+        let window_height = desktop_file_borrow.get_window_height().unwrap_or(0);
+        let is_force_dark = desktop_file_borrow.get_force_dark().unwrap_or(false);
 
         drop(desktop_file_borrow);
 
         self.name_row.set_text(&name);
         self.url_row.set_text(&url);
+        self.description_row.set_text(&description);
         self.isolate_row.set_active(is_isolated);
         self.browser_row.set_selected(browser_index);
-
+        self.maximize_row.set_active(is_maximized);
+        self.fullscreen_row.set_active(is_fullscreen);
+        self.minimal_ui_row.set_active(is_minimal_ui);
+        self.private_row.set_active(is_private);
+        self.extra_args_row.set_text(&extra_args);
+        self.keywords_row.set_text(&keywords);
+        self.handler_url_row.set_text(&handler_url);
+        self.mime_types_row.set_text(&mime_types);
+        self.window_width_row.set_value(f64::from(window_width));
+        self.window_height_row.set_value(f64::from(window_height));
+        self.force_dark_row.set_active(is_force_dark);
+        self.reset_env_rows();
+        self.reset_category_row();
+
+        // Also brings the rows a browser can't use and the icon button in line with the file
         self.on_desktop_file_change();
 
         let toast = Self::build_reset_toast();
         self.toast_overlay.add_toast(toast);
     }
 
+    fn build_header_back_button() -> Button {
+        Button::builder()
+            .icon_name("go-previous-symbolic")
+            .tooltip_text(t!("web_apps.web_app_view.button.back"))
+            .build()
+    }
+
     fn build_header_reset_button() -> Button {
         let reset_button = Button::with_label(&t!("web_apps.web_app_view.button.reset"));
         reset_button.set_sensitive(false);
@@ -221,6 +516,61 @@ impl WebAppView {
         reset_button
     }
 
+    fn build_config_menu_button() -> MenuButton {
+        let menu = Menu::new();
+        menu.append(
+            Some(&t!("web_apps.web_app_view.menu.copy_config")),
+            Some(&format!(
+                "{}.{}",
+                Self::ACTION_LABEL,
+                Self::ACTION_COPY_CONFIG
+            )),
+        );
+        menu.append(
+            Some(&t!("web_apps.web_app_view.menu.paste_config")),
+            Some(&format!(
+                "{}.{}",
+                Self::ACTION_LABEL,
+                Self::ACTION_PASTE_CONFIG
+            )),
+        );
+        menu.append(
+            Some(&t!("web_apps.web_app_view.menu.export_bundle")),
+            Some(&format!(
+                "{}.{}",
+                Self::ACTION_LABEL,
+                Self::ACTION_EXPORT_BUNDLE
+            )),
+        );
+
+        MenuButton::builder()
+            .icon_name("view-more-symbolic")
+            .menu_model(&menu)
+            .build()
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn build_theme_color_label(theme_color: &str, rgba: &RGBA) -> Label {
+        let to_hex = |value: f32| (value * 255.0).round() as u8;
+        let hex_color = format!(
+            "#{:02x}{:02x}{:02x}",
+            to_hex(rgba.red()),
+            to_hex(rgba.green()),
+            to_hex(rgba.blue())
+        );
+
+        Label::builder()
+            .use_markup(true)
+            .label(format!(
+                "<span foreground=\"{hex_color}\">●</span> {}",
+                glib::markup_escape_text(theme_color)
+            ))
+            .css_classes(["caption"])
+            .sensitive(false)
+            .tooltip_text(t!("web_apps.web_app_view.theme_color"))
+            .build()
+    }
+
     fn build_app_header(&self) -> PreferencesGroup {
         let desktop_file_borrow = self.desktop_file.borrow_mut();
 
@@ -267,11 +617,27 @@ impl WebAppView {
             .build();
         if let Some(browser) = desktop_file_borrow.get_browser() {
             browser_label.set_label(&browser.get_name_with_installation());
+
+            let browser_label = browser_label.clone();
+            glib::spawn_future_local(async move {
+                if let Some(version) = browser.get_version().await {
+                    browser_label.set_label(&format!(
+                        "{} {version}",
+                        browser.get_name_with_installation()
+                    ));
+                }
+            });
         }
 
         content_box.append(&app_image);
         content_box.append(&app_label);
         content_box.append(&browser_label);
+
+        if let Some(theme_color) = desktop_file_borrow.get_theme_color()
+            && let Ok(rgba) = RGBA::parse(&theme_color)
+        {
+            content_box.append(&Self::build_theme_color_label(&theme_color, &rgba));
+        }
         content_box.append(&button_wrap_box);
 
         pref_group.add(&content_box);
@@ -286,29 +652,231 @@ impl WebAppView {
 
         pref_group.add(&self.name_row);
         pref_group.add(&self.url_row);
+        pref_group.add(&self.description_row);
         pref_group.add(&self.isolate_row);
         pref_group.add(&self.maximize_row);
+        pref_group.add(&self.window_width_row);
+        pref_group.add(&self.window_height_row);
+        pref_group.add(&self.fullscreen_row);
+        pref_group.add(&self.minimal_ui_row);
+        pref_group.add(&self.private_row);
+        pref_group.add(&self.autostart_row);
         pref_group.add(&self.browser_row);
+        pref_group.add(&self.browser_issues_row);
+        pref_group.add(&self.category_row);
 
+        self.reset_browser_issues();
+        self.reset_window_size();
         self.connect_name_row();
         self.connect_url_row();
+        self.connect_description_row();
         self.connect_isolate_row();
         self.connect_maximize_row();
+        self.connect_window_size_rows();
+        self.connect_fullscreen_row();
+        self.connect_minimal_ui_row();
+        self.connect_private_row();
+        self.connect_autostart_row();
         self.connect_browser_row();
+        self.connect_category_row();
 
         pref_group
     }
 
-    fn build_bottom_pref_group(self: &Rc<Self>) -> PreferencesGroup {
-        let pref_group = PreferencesGroup::builder().build();
-
-        pref_group.add(&self.optional_row);
+    fn build_advanced_pref_group(self: &Rc<Self>) -> PreferencesGroup {
+        let pref_group = PreferencesGroup::builder()
+            .title(t!("web_apps.web_app_view.advanced.title"))
+            .build();
 
-        self.connect_optional_row();
+        pref_group.add(&self.force_dark_row);
+        pref_group.add(&self.extra_args_row);
+        pref_group.add(&self.env_row);
+        pref_group.add(&self.keywords_row);
+        pref_group.add(&self.handler_url_row);
+        pref_group.add(&self.mime_types_row);
+        pref_group.add(&self.show_launcher_file_row);
+        pref_group.add(&self.copy_launch_command_row);
+
+        self.connect_force_dark_row();
+        self.connect_extra_args_row();
+        self.connect_env_row();
+        self.connect_keywords_row();
+        self.connect_handler_url_row();
+        self.connect_mime_types_row();
+        self.connect_developer_rows();
+        self.reset_developer_rows();
 
         pref_group
     }
 
+    fn build_developer_row(title: &str, icon_name: &str) -> ActionRow {
+        let action_row = ActionRow::builder().title(title).activatable(true).build();
+        action_row.add_suffix(&Image::from_icon_name(icon_name));
+
+        action_row
+    }
+
+    fn connect_developer_rows(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        self.show_launcher_file_row
+            .connect_activated(move |_| self_clone.show_launcher_file());
+
+        let self_clone = self.clone();
+        self.copy_launch_command_row
+            .connect_activated(move |_| self_clone.copy_launch_command());
+    }
+
+    fn reset_developer_rows(self: &Rc<Self>) {
+        let is_new = self.get_is_new();
+        let desktop_file_borrow = self.desktop_file.borrow();
+        let path = desktop_file_borrow.get_path();
+        let has_file = !is_new && path.is_file();
+        let launcher_file = if has_file {
+            path.display().to_string()
+        } else {
+            String::new()
+        };
+
+        self.show_launcher_file_row.set_sensitive(has_file);
+        self.show_launcher_file_row.set_subtitle(&launcher_file);
+        self.copy_launch_command_row
+            .set_sensitive(!is_new && desktop_file_borrow.get_exec().is_some());
+    }
+
+    /// Selects the file in the file manager through the portal. The dev setup links the
+    /// applications dir, the real path is shown. Without a file manager the folder is opened.
+    fn show_launcher_file(self: &Rc<Self>) {
+        let path = self.desktop_file.borrow().get_path();
+        let path = fs::canonicalize(&path).unwrap_or(path);
+
+        let self_clone = self.clone();
+        FileLauncher::new(Some(&gio::File::for_path(&path))).open_containing_folder(
+            Some(&self.app.window.adw_window),
+            None::<&Cancellable>,
+            move |result| {
+                let Err(error) = result else {
+                    return;
+                };
+                debug!("Failed to show launcher file, opening its folder: {error:?}");
+                let Some(dir) = path.parent() else {
+                    return;
+                };
+
+                let window = self_clone.app.window.adw_window.clone();
+                FileLauncher::new(Some(&gio::File::for_path(dir))).launch(
+                    Some(&window),
+                    None::<&Cancellable>,
+                    move |result| {
+                        if let Err(error) = result {
+                            self_clone.on_error(
+                                &t!("web_apps.web_app_view.error.show_launcher_file"),
+                                Some(&anyhow!(error)),
+                            );
+                        }
+                    },
+                );
+            },
+        );
+    }
+
+    /// The `Exec` as launchers run it, with every placeholder filled in
+    fn copy_launch_command(self: &Rc<Self>) {
+        self.flush_pending_save();
+        let Some(exec) = self.desktop_file.borrow().get_exec() else {
+            return;
+        };
+
+        self.nav_page.clipboard().set_text(&exec);
+        let toast = Toast::new(&t!("web_apps.web_app_view.toast.launch_command_copied"));
+        toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+        self.toast_overlay.add_toast(toast);
+    }
+
+    fn build_extra_args_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let extra_args = desktop_file.borrow().get_extra_args().unwrap_or_default();
+
+        EntryRow::builder()
+            .title(t!("web_apps.web_app_view.advanced.extra_args"))
+            .tooltip_text(t!("web_apps.web_app_view.advanced.extra_args_tooltip"))
+            .text(extra_args)
+            .show_apply_button(true)
+            // The policy leaves them out of the launcher
+            .visible(!policy::get().get_disable_custom_args())
+            .build()
+    }
+
+    fn build_env_row() -> ExpanderRow {
+        ExpanderRow::builder()
+            .title(t!("web_apps.web_app_view.advanced.env.title"))
+            .subtitle(t!("web_apps.web_app_view.advanced.env.subtitle"))
+            .build()
+    }
+
+    fn build_keywords_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let keywords = desktop_file
+            .borrow()
+            .get_keywords()
+            .unwrap_or_default()
+            .join(", ");
+
+        EntryRow::builder()
+            .title(t!("web_apps.web_app_view.advanced.keywords"))
+            .tooltip_text(t!("web_apps.web_app_view.advanced.keywords_tooltip"))
+            .text(keywords)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_handler_url_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let handler_url = desktop_file.borrow().get_handler_url().unwrap_or_default();
+
+        EntryRow::builder()
+            .title(t!("web_apps.web_app_view.advanced.handler_url"))
+            .tooltip_text(t!(
+                "web_apps.web_app_view.advanced.handler_url_tooltip",
+                placeholder = DesktopFile::HANDLER_PLACEHOLDER
+            ))
+            .text(handler_url)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::Url)
+            .build()
+    }
+
+    fn build_mime_types_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let mime_types = desktop_file.borrow().get_mime_types_ui();
+
+        let menu = Menu::new();
+        for scheme in DesktopFile::HANDLER_SCHEME_PRESETS {
+            menu.append(
+                Some(scheme),
+                Some(&format!(
+                    "{}.{}::{scheme}",
+                    Self::ACTION_LABEL_HANDLERS,
+                    Self::ACTION_ADD_HANDLER_SCHEME
+                )),
+            );
+        }
+        let presets_button = MenuButton::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text(t!("web_apps.web_app_view.advanced.mime_types_presets"))
+            .valign(Align::Center)
+            .css_classes(["flat"])
+            .menu_model(&menu)
+            .build();
+
+        let entry_row = EntryRow::builder()
+            .title(t!("web_apps.web_app_view.advanced.mime_types"))
+            .tooltip_text(t!("web_apps.web_app_view.advanced.mime_types_tooltip"))
+            .text(mime_types)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build();
+        entry_row.add_suffix(&presets_button);
+
+        entry_row
+    }
+
     fn build_name_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
         let name = desktop_file.borrow().get_name().unwrap_or_default();
 
@@ -331,6 +899,17 @@ impl WebAppView {
             .build()
     }
 
+    fn build_description_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let description = desktop_file.borrow().get_description().unwrap_or_default();
+
+        EntryRow::builder()
+            .title(t!("web_apps.web_app_view.description.title"))
+            .text(description)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
     fn build_isolate_row(
         desktop_file: &Rc<RefCell<DesktopFile>>,
         browser_can_isolate: bool,
@@ -391,21 +970,142 @@ impl WebAppView {
         switch_row
     }
 
+    /// Zero is the browser default
+    fn build_window_size_row(title: &str, value: u32) -> SpinRow {
+        let spin_row = SpinRow::with_range(0.0, Self::WINDOW_SIZE_MAX, Self::WINDOW_SIZE_STEP);
+        spin_row.set_title(title);
+        spin_row.set_subtitle(&t!("web_apps.web_app_view.window_size.subtitle"));
+        spin_row.set_tooltip_text(Some(&t!("web_apps.web_app_view.window_size.disabled")));
+        spin_row.set_has_tooltip(false);
+        spin_row.set_value(f64::from(value));
+
+        spin_row
+    }
+
+    fn build_fullscreen_row(
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+        browser_can_fullscreen: bool,
+    ) -> SwitchRow {
+        let mut desktop_file_borrow = desktop_file.borrow_mut();
+        let has_fullscreen = desktop_file_borrow.get_fullscreen();
+        let is_fullscreen = has_fullscreen.unwrap_or(false);
+
+        let switch_row = SwitchRow::builder()
+            .title(t!("web_apps.web_app_view.fullscreen.title"))
+            .subtitle(t!("web_apps.web_app_view.fullscreen.subtitle"))
+            .active(is_fullscreen)
+            .sensitive(browser_can_fullscreen)
+            .tooltip_text(t!("web_apps.web_app_view.fullscreen.disabled"))
+            .has_tooltip(!browser_can_fullscreen)
+            .build();
+
+        if !browser_can_fullscreen && is_fullscreen {
+            debug!("Found desktop file with fullscreen on a browser that is incapable");
+            switch_row.set_active(false);
+        }
+
+        // SwitchRow has already a setting on load, so sync this if empty
+        if has_fullscreen.is_none() {
+            desktop_file_borrow.set_fullscreen(switch_row.is_active());
+        }
+
+        switch_row
+    }
+
+    /// Not written until changed, apps without the key get the minimal UI
+    fn build_minimal_ui_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> SwitchRow {
+        let desktop_file_borrow = desktop_file.borrow();
+        let can_minimal_ui = desktop_file_borrow.can_minimal_ui();
+
+        SwitchRow::builder()
+            .title(t!("web_apps.web_app_view.minimal_ui.title"))
+            .subtitle(t!("web_apps.web_app_view.minimal_ui.subtitle"))
+            .active(desktop_file_borrow.get_minimal_ui().unwrap_or(true))
+            .sensitive(can_minimal_ui)
+            .tooltip_text(t!("web_apps.web_app_view.minimal_ui.disabled"))
+            .has_tooltip(!can_minimal_ui)
+            .build()
+    }
+
+    /// Independent of isolation, both can be on
+    fn build_private_row(
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+        browser_can_private: bool,
+    ) -> SwitchRow {
+        let mut desktop_file_borrow = desktop_file.borrow_mut();
+        let has_private = desktop_file_borrow.get_private();
+        let is_private = has_private.unwrap_or(false);
+
+        let switch_row = SwitchRow::builder()
+            .title(t!("web_apps.web_app_view.private.title"))
+            .subtitle(t!("web_apps.web_app_view.private.subtitle"))
+            .active(is_private)
+            .sensitive(browser_can_private)
+            .tooltip_text(t!("web_apps.web_app_view.private.disabled"))
+            .has_tooltip(!browser_can_private)
+            .build();
+
+        if !browser_can_private && is_private {
+            debug!("Found desktop file with private on a browser that is incapable");
+            switch_row.set_active(false);
+        }
+
+        // SwitchRow has already a setting on load, so sync this if empty
+        if has_private.is_none() {
+            desktop_file_borrow.set_private(switch_row.is_active());
+        }
+
+        switch_row
+    }
+
+    /// Not part of the desktop file, the state is read from the autostart dir
+    fn build_autostart_row(desktop_file: &Rc<RefCell<DesktopFile>>, is_new: bool) -> SwitchRow {
+        SwitchRow::builder()
+            .title(t!("web_apps.web_app_view.autostart.title"))
+            .subtitle(t!("web_apps.web_app_view.autostart.subtitle"))
+            .active(!is_new && desktop_file.borrow().is_autostart())
+            .visible(!is_new)
+            .build()
+    }
+
+    /// For sites that ignore the dark style preference
+    fn build_force_dark_row(
+        desktop_file: &Rc<RefCell<DesktopFile>>,
+        browser_can_force_dark: bool,
+    ) -> SwitchRow {
+        let mut desktop_file_borrow = desktop_file.borrow_mut();
+        let has_force_dark = desktop_file_borrow.get_force_dark();
+        let is_force_dark = has_force_dark.unwrap_or(false);
+
+        let switch_row = SwitchRow::builder()
+            .title(t!("web_apps.web_app_view.force_dark.title"))
+            .subtitle(t!("web_apps.web_app_view.force_dark.subtitle"))
+            .active(is_force_dark)
+            .sensitive(browser_can_force_dark)
+            .tooltip_text(t!("web_apps.web_app_view.force_dark.disabled"))
+            .has_tooltip(!browser_can_force_dark)
+            .build();
+
+        if !browser_can_force_dark && is_force_dark {
+            debug!("Found desktop file with force dark on a browser that is incapable");
+            switch_row.set_active(false);
+        }
+
+        // SwitchRow has already a setting on load, so sync this if empty
+        if has_force_dark.is_none() {
+            desktop_file_borrow.set_force_dark(switch_row.is_active());
+        }
+
+        switch_row
+    }
+
     fn build_browser_row(
         app: &Rc<App>,
         desktop_file: &Rc<RefCell<DesktopFile>>,
         is_new: bool,
     ) -> ComboRow {
         let all_browsers = app.browser_configs.get_all_browsers();
-
-        // Some weird factory setup where the list calls factory methods...
-        // First create all data structures, then set data from ListStore.
-        // Why is this so unnecessary complicated? ¯\_(ツ)_/¯
-        let list = gio::ListStore::new::<BoxedAnyObject>();
-        for browser in all_browsers {
-            let boxed = BoxedAnyObject::new(browser.clone());
-            list.append(&boxed);
-        }
+        let list = Self::build_browser_list(app);
         let factory = SignalListItemFactory::new();
         factory.connect_bind(|_, list_item| {
             let Some(list_item) = list_item.downcast_ref::<ListItem>() else {
@@ -437,6 +1137,17 @@ impl WebAppView {
 
             if browser.is_no_browser() {
                 label.set_label(&t!("web_apps.web_app_view.browser.no_browser"));
+            } else if browser.is_installed() {
+                let browser = browser.clone();
+                let box_container = box_container.clone();
+                glib::spawn_future_local(async move {
+                    if let Some(version) = browser.get_version().await {
+                        box_container.set_tooltip_text(Some(&t!(
+                            "web_apps.web_app_view.browser.version",
+                            version = version
+                        )));
+                    }
+                });
             }
 
             list_item.set_child(Some(&box_container));
@@ -470,57 +1181,272 @@ impl WebAppView {
         combo_row
     }
 
-    fn build_optional_row() -> ActionRow {
-        let row = ActionRow::builder()
-            .title(t!("web_apps.web_app_view.optional.title"))
-            .subtitle(t!("web_apps.web_app_view.optional.subtitle"))
-            .activatable(true)
-            .build();
-
-        let suffix = Image::from_icon_name("go-next-symbolic");
-        row.add_suffix(&suffix);
+    fn build_browser_list(app: &Rc<App>) -> gio::ListStore {
+        // Some weird factory setup where the list calls factory methods...
+        // First create all data structures, then set data from ListStore.
+        // Why is this so unnecessary complicated? ¯\_(ツ)_/¯
+        let list = gio::ListStore::new::<BoxedAnyObject>();
+        for browser in app.browser_configs.get_all_browsers() {
+            let boxed = BoxedAnyObject::new(browser);
+            list.append(&boxed);
+        }
 
-        row
+        list
     }
 
-    fn build_button_footer(self: &Rc<Self>) -> PreferencesGroup {
-        fn button_wrap_box(button: &Button) -> WrapBox {
-            let wrapbox = WrapBox::builder()
-                .align(0.5)
-                .margin_start(12)
-                .margin_end(12)
-                .build();
-            wrapbox.append(button);
-            wrapbox
+    /// Rebuild the browser list after a refresh, keeping the browser of the desktop file
+    fn sync_browser_row(self: &Rc<Self>) {
+        let generation = self.app.browser_configs.get_generation();
+        if *self.browsers_generation.borrow() == generation {
+            return;
         }
+        *self.browsers_generation.borrow_mut() = generation;
+        debug!("Browsers have changed, rebuilding browser list");
 
-        let pref_group = PreferencesGroup::new();
-        let content_box = gtk::Box::builder()
-            .orientation(Orientation::Horizontal)
-            .halign(Align::Center)
-            .margin_top(12)
-            .margin_bottom(12)
-            .build();
+        let browser_index = self
+            .desktop_file
+            .borrow()
+            .get_browser()
+            .or_else(|| self.app.browser_configs.get_all_browsers().last().cloned())
+            .and_then(|browser| browser.get_index())
+            .and_then(|index| index.try_into().ok())
+            .unwrap_or(0);
 
-        content_box.append(&button_wrap_box(&self.save_button));
-        content_box.append(&button_wrap_box(&self.delete_button));
-        pref_group.add(&content_box);
+        // Not a user change, so nothing in the desktop file should change
+        *self.is_syncing_browsers.borrow_mut() = true;
+        self.browser_row
+            .set_model(Some(&Self::build_browser_list(&self.app)));
+        self.browser_row.set_selected(browser_index);
+        *self.is_syncing_browsers.borrow_mut() = false;
 
-        self.connect_save_button();
-        self.connect_delete_button();
+        self.reset_browser_issues();
+    }
 
-        pref_group
+    /// Known issues of the selected browser, dimmed when there are none.
+    /// Issues of older versions follow once the version of the browser is read.
+    fn reset_browser_issues(self: &Rc<Self>) {
+        for label in self.browser_issue_labels.borrow_mut().drain(..) {
+            self.browser_issues_row.remove(&label);
+        }
+        self.version_banner.set_revealed(false);
+
+        let browser = self.desktop_file.borrow().get_browser();
+        let issues = browser
+            .as_ref()
+            .map(|browser| {
+                browser
+                    .get_issues(&self.app.locale.current, &self.app.locale.default)
+                    .to_vec()
+            })
+            .unwrap_or_default();
+        self.add_browser_issues(&issues);
+
+        let Some(browser) = browser.filter(|browser| browser.is_installed()) else {
+            return;
+        };
+        let self_clone = self.clone();
+        glib::spawn_future_local(async move {
+            let Some(version) = browser.get_version().await else {
+                return;
+            };
+            // Another browser can be picked while the version is read
+            if self_clone
+                .desktop_file
+                .borrow()
+                .get_browser()
+                .is_none_or(|selected_browser| selected_browser.id != browser.id)
+            {
+                return;
+            }
+            self_clone.show_version_warnings(&browser, &version);
+        });
     }
 
-    fn build_reset_toast() -> Toast {
-        let toast = Toast::new(&t!("web_apps.web_app_view.toast.reset"));
-        toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+    fn show_version_warnings(self: &Rc<Self>, browser: &Browser, version: &BrowserVersion) {
+        let version_issues =
+            browser.get_version_issues(version, &self.app.locale.current, &self.app.locale.default);
+        self.add_browser_issues(&version_issues);
 
-        toast
+        if browser.is_below_min_version(version)
+            && let Some(min_version) = &browser.min_version
+        {
+            self.version_banner.set_title(&t!(
+                "web_apps.web_app_view.version_banner.title",
+                browser = browser.get_name(),
+                version = version,
+                min_version = min_version
+            ));
+            self.version_banner.set_revealed(true);
+        }
     }
 
-    fn build_change_icon_button() -> Button {
-        let button_content = ButtonContent::builder()
+    fn add_browser_issues(&self, issues: &[String]) {
+        let mut labels_borrow = self.browser_issue_labels.borrow_mut();
+        for issue in issues {
+            let label = Label::builder()
+                .label(format!("• {issue}"))
+                .wrap(true)
+                .xalign(0.0)
+                .margin_top(6)
+                .margin_bottom(6)
+                .margin_start(12)
+                .margin_end(12)
+                .build();
+            self.browser_issues_row.add_row(&label);
+            labels_borrow.push(label);
+        }
+
+        if labels_borrow.is_empty() {
+            self.browser_issues_row.set_expanded(false);
+            self.browser_issues_row
+                .set_subtitle(&t!("web_apps.web_app_view.browser.no_issues"));
+            self.browser_issues_row.set_sensitive(false);
+            self.browser_issues_row.add_css_class("dimmed");
+        } else {
+            self.browser_issues_row
+                .set_subtitle(&t!("web_apps.web_app_view.browser.issues"));
+            self.browser_issues_row.set_sensitive(true);
+            self.browser_issues_row.remove_css_class("dimmed");
+        }
+    }
+
+    fn build_category_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> ComboRow {
+        // Same factory setup as the browser list
+        let list = gio::ListStore::new::<BoxedAnyObject>();
+        for category in Category::get_all() {
+            let boxed = BoxedAnyObject::new(category);
+            list.append(&boxed);
+        }
+        let factory = SignalListItemFactory::new();
+        factory.connect_bind(|_, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<ListItem>() else {
+                error!(?list_item, "Failed to downcast list item");
+                return;
+            };
+            let Some(category_item_boxed) = list_item
+                .item()
+                .and_then(|item| item.downcast::<BoxedAnyObject>().ok())
+            else {
+                error!(?list_item, "Failed to downcast boxed list item");
+                return;
+            };
+
+            let category = category_item_boxed.borrow::<Category>();
+            let box_container = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+            let icon = category.get_icon();
+
+            box_container.append(&icon);
+            box_container.append(&Label::new(Some(&Self::category_to_string_ui(*category))));
+
+            list_item.set_child(Some(&box_container));
+        });
+
+        let combo_row = ComboRow::builder()
+            .title(t!("web_apps.web_app_view.category.title"))
+            .subtitle(t!("web_apps.web_app_view.category.subtitle"))
+            .model(&list)
+            .factory(&factory)
+            .build();
+        combo_row.set_selected(Self::get_category_index(&desktop_file.borrow()));
+
+        combo_row
+    }
+
+    /// Unknown or missing categories show as Network, which new desktop files get by default
+    fn get_category_index(desktop_file: &DesktopFile) -> u32 {
+        let category = desktop_file
+            .get_category()
+            .and_then(|categories| Category::from_categories(&categories))
+            .unwrap_or(Category::Network);
+
+        Category::get_all()
+            .iter()
+            .position(|category_iter| *category_iter == category)
+            .and_then(|index| index.try_into().ok())
+            .unwrap_or(0)
+    }
+
+    pub fn category_to_string_ui(category: Category) -> String {
+        match category {
+            Category::AudioVideo => {
+                t!("web_apps.web_app_view.category.categories.audio_video").to_string()
+            }
+            Category::Audio => t!("web_apps.web_app_view.category.categories.audio").to_string(),
+            Category::Video => t!("web_apps.web_app_view.category.categories.video").to_string(),
+            Category::Development => {
+                t!("web_apps.web_app_view.category.categories.development").to_string()
+            }
+            Category::Education => {
+                t!("web_apps.web_app_view.category.categories.education").to_string()
+            }
+            Category::Game => t!("web_apps.web_app_view.category.categories.game").to_string(),
+            Category::Graphics => {
+                t!("web_apps.web_app_view.category.categories.graphics").to_string()
+            }
+            Category::Network => {
+                t!("web_apps.web_app_view.category.categories.network").to_string()
+            }
+            Category::Office => t!("web_apps.web_app_view.category.categories.office").to_string(),
+            Category::Science => {
+                t!("web_apps.web_app_view.category.categories.science").to_string()
+            }
+            Category::Settings => {
+                t!("web_apps.web_app_view.category.categories.settings").to_string()
+            }
+            Category::System => t!("web_apps.web_app_view.category.categories.system").to_string(),
+            Category::Utility => {
+                t!("web_apps.web_app_view.category.categories.utility").to_string()
+            }
+        }
+    }
+
+    /// Not a user change, so an unknown category in the desktop file is kept
+    fn reset_category_row(self: &Rc<Self>) {
+        let category_index = Self::get_category_index(&self.desktop_file.borrow());
+
+        *self.is_syncing_category.borrow_mut() = true;
+        self.category_row.set_selected(category_index);
+        *self.is_syncing_category.borrow_mut() = false;
+    }
+
+    fn build_button_footer(self: &Rc<Self>) -> PreferencesGroup {
+        fn button_wrap_box(button: &Button) -> WrapBox {
+            let wrapbox = WrapBox::builder()
+                .align(0.5)
+                .margin_start(12)
+                .margin_end(12)
+                .build();
+            wrapbox.append(button);
+            wrapbox
+        }
+
+        let pref_group = PreferencesGroup::new();
+        let content_box = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .halign(Align::Center)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        content_box.append(&button_wrap_box(&self.save_button));
+        content_box.append(&button_wrap_box(&self.delete_button));
+        pref_group.add(&content_box);
+
+        self.connect_save_button();
+        self.connect_delete_button();
+
+        pref_group
+    }
+
+    fn build_reset_toast() -> Toast {
+        let toast = Toast::new(&t!("web_apps.web_app_view.toast.reset"));
+        toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+
+        toast
+    }
+
+    fn build_change_icon_button() -> Button {
+        let button_content = ButtonContent::builder()
             .label(t!("web_apps.web_app_view.button.icon"))
             .icon_name("software-update-available-symbolic")
             .build();
@@ -566,6 +1492,13 @@ impl WebAppView {
         button
     }
 
+    fn build_url_reachability_icon() -> Image {
+        let reachability_icon = Image::new();
+        reachability_icon.set_visible(false);
+
+        reachability_icon
+    }
+
     fn build_validate_icon() -> Image {
         let validate_icon = Image::from_icon_name("dialog-warning-symbolic");
         validate_icon.set_visible(false);
@@ -574,6 +1507,389 @@ impl WebAppView {
         validate_icon
     }
 
+    /// The icon button has its own error style, the other fields have no row
+    fn build_field_error_icons(
+        name_row: &EntryRow,
+        url_row: &EntryRow,
+        browser_row: &ComboRow,
+        isolate_row: &SwitchRow,
+        maximize_row: &SwitchRow,
+    ) -> Vec<(Vec<Key>, Image)> {
+        let name_icon = Self::build_validate_icon();
+        name_row.add_suffix(&name_icon);
+        let url_icon = Self::build_validate_icon();
+        url_row.add_suffix(&url_icon);
+        let browser_icon = Self::build_validate_icon();
+        browser_row.add_suffix(&browser_icon);
+        let isolate_icon = Self::build_validate_icon();
+        isolate_row.add_suffix(&isolate_icon);
+        let maximize_icon = Self::build_validate_icon();
+        maximize_row.add_suffix(&maximize_icon);
+
+        vec![
+            (vec![Key::Name], name_icon),
+            (vec![Key::Url], url_icon),
+            (vec![Key::BrowserId], browser_icon),
+            (vec![Key::Isolate, Key::Profile], isolate_icon),
+            (vec![Key::Maximize], maximize_icon),
+        ]
+    }
+
+    fn connect_config_actions(self: &Rc<Self>) {
+        let actions = SimpleActionGroup::new();
+
+        let self_clone = self.clone();
+        self.copy_config_action
+            .connect_activate(move |_, _| self_clone.copy_config());
+
+        let self_clone = self.clone();
+        self.paste_config_action
+            .connect_activate(move |_, _| self_clone.paste_config());
+
+        let self_clone = self.clone();
+        self.export_bundle_action
+            .connect_activate(move |_, _| self_clone.export_bundle());
+
+        actions.add_action(&self.copy_config_action);
+        actions.add_action(&self.paste_config_action);
+        actions.add_action(&self.export_bundle_action);
+        self.nav_page
+            .insert_action_group(Self::ACTION_LABEL, Some(&actions));
+
+        self.reset_config_actions();
+    }
+
+    fn copy_config(self: &Rc<Self>) {
+        let config_snippet = ConfigSnippet::from_desktop_file(&self.desktop_file.borrow());
+        debug!(?config_snippet, "Copying configuration");
+
+        self.nav_page
+            .clipboard()
+            .set_text(&config_snippet.to_string());
+
+        let message = if config_snippet.has_possible_secrets() {
+            t!("web_apps.web_app_view.toast.config_copied_secret")
+        } else {
+            t!("web_apps.web_app_view.toast.config_copied")
+        };
+        let toast = Toast::new(&message);
+        toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+        self.toast_overlay.add_toast(toast);
+    }
+
+    fn paste_config(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let clipboard = self.nav_page.clipboard();
+
+        glib::spawn_future_local(async move {
+            let text = match clipboard.read_text_future().await {
+                Ok(Some(text)) => text,
+                Ok(None) => {
+                    self_clone
+                        .on_error(&t!("web_apps.web_app_view.toast.config_paste_empty"), None);
+                    return;
+                }
+                Err(error) => {
+                    self_clone.on_error(
+                        &t!("web_apps.web_app_view.error.read_clipboard"),
+                        Some(&error.into()),
+                    );
+                    return;
+                }
+            };
+
+            if self_clone.get_is_discarded() {
+                return;
+            }
+
+            match ConfigSnippet::parse(&text) {
+                Ok(config_snippet) => self_clone.apply_config_snippet(&config_snippet),
+                Err(error) => self_clone.on_error(
+                    &t!(
+                        "web_apps.web_app_view.toast.config_paste_invalid",
+                        error = error.to_string()
+                    ),
+                    Some(&error),
+                ),
+            }
+        });
+    }
+
+    /// Set the values through the rows, so everything reacts like it was user input
+    fn apply_config_snippet(self: &Rc<Self>, config_snippet: &ConfigSnippet) {
+        debug!(?config_snippet, "Applying pasted configuration");
+
+        if let Some(name) = &config_snippet.name {
+            self.name_row.set_text(name);
+            self.name_row.emit_by_name::<()>("apply", &[]);
+        }
+
+        if let Some(url) = &config_snippet.url {
+            self.url_row.set_text(url);
+            self.url_row.emit_by_name::<()>("apply", &[]);
+        }
+
+        if let Some(browser_id) = &config_snippet.browser_id {
+            match self
+                .app
+                .browser_configs
+                .get_by_id(browser_id)
+                .and_then(|browser| browser.get_index())
+                .and_then(|index| index.try_into().ok())
+            {
+                Some(index) => self.browser_row.set_selected(index),
+                None => debug!(browser_id, "Pasted browser is not available"),
+            }
+        }
+
+        if let Some(is_isolated) = config_snippet.isolate
+            && self.isolate_row.is_sensitive()
+        {
+            self.isolate_row.set_active(is_isolated);
+        }
+
+        if let Some(is_maximized) = config_snippet.maximize
+            && self.maximize_row.is_sensitive()
+        {
+            self.maximize_row.set_active(is_maximized);
+        }
+
+        if let Some(is_fullscreen) = config_snippet.fullscreen
+            && self.fullscreen_row.is_sensitive()
+        {
+            self.fullscreen_row.set_active(is_fullscreen);
+        }
+
+        if let Some(is_private) = config_snippet.private
+            && self.private_row.is_sensitive()
+        {
+            self.private_row.set_active(is_private);
+        }
+
+        if let Some(is_force_dark) = config_snippet.force_dark
+            && self.force_dark_row.is_sensitive()
+        {
+            self.force_dark_row.set_active(is_force_dark);
+        }
+
+        let mut desktop_file_borrow = self.desktop_file.borrow_mut();
+        if let Some(category) = config_snippet
+            .category
+            .as_ref()
+            .and_then(|category| Category::from_categories(category))
+        {
+            desktop_file_borrow.set_category(&category);
+        }
+        drop(desktop_file_borrow);
+        self.reset_category_row();
+
+        if let Some(description) = &config_snippet.description {
+            self.description_row.set_text(description);
+            self.description_row.emit_by_name::<()>("apply", &[]);
+        }
+
+        self.on_desktop_file_change();
+
+        let toast = Toast::new(&t!("web_apps.web_app_view.toast.config_pasted"));
+        toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+        self.toast_overlay.add_toast(toast);
+    }
+
+    fn reset_config_actions(self: &Rc<Self>) {
+        let is_new = self.get_is_new();
+
+        self.copy_config_action.set_enabled(!is_new);
+        self.paste_config_action.set_enabled(is_new);
+        self.export_bundle_action.set_enabled(!is_new);
+    }
+
+    fn export_bundle(self: &Rc<Self>) {
+        let name = self.desktop_file.borrow().get_name().unwrap_or_default();
+        let file_dialog = FileDialog::builder()
+            .title(t!("web_apps.web_app_view.export_dialog.title"))
+            .initial_name(format!(
+                "{}.{}",
+                sanitize_filename::sanitize(&name),
+                DesktopFile::BUNDLE_EXTENSION
+            ))
+            .build();
+
+        let self_clone = self.clone();
+
+        file_dialog.save(
+            Some(&self.app.window.adw_window),
+            None::<&Cancellable>,
+            move |file| {
+                let Ok(file) = file else {
+                    debug!("No file selected for export");
+                    return;
+                };
+                let Some(path) = file.path() else {
+                    error!("Failed to get path");
+                    return;
+                };
+
+                if let Err(error) = self_clone.desktop_file.borrow().export_bundle(&path) {
+                    self_clone.on_error(
+                        &t!("web_apps.web_app_view.toast.export_failed"),
+                        Some(&error),
+                    );
+                    return;
+                }
+
+                let toast = Toast::new(&t!("web_apps.web_app_view.toast.exported"));
+                toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+                self_clone.toast_overlay.add_toast(toast);
+            },
+        );
+    }
+
+    /// For problems found before the view was opened, like an import with a missing browser
+    pub fn show_warning(self: &Rc<Self>, message: &str) {
+        self.on_error(message, None);
+    }
+
+    /// Going back is checked for pending edits, so the own back button replaces the default one
+    fn connect_back_navigation(self: &Rc<Self>) {
+        self.nav_page.set_can_pop(false);
+        self.header.set_show_back_button(false);
+        self.header.pack_start(&self.back_button);
+
+        let self_clone = self.clone();
+        self.back_button
+            .connect_clicked(move |_| self_clone.on_back());
+
+        // Gestures stay disabled, the shortcuts of the navigation view are replaced
+        let self_clone = self.clone();
+        let back_action = CallbackAction::new(move |_, _| {
+            self_clone.on_back();
+            glib::Propagation::Stop
+        });
+        let shortcut_controller = ShortcutController::new();
+        shortcut_controller.add_shortcut(Shortcut::new(
+            ShortcutTrigger::parse_string("Escape|<Alt>Left"),
+            Some(back_action),
+        ));
+        self.nav_page.add_controller(shortcut_controller);
+    }
+
+    fn on_back(self: &Rc<Self>) {
+        // The app list reads the file again
+        self.flush_pending_save();
+        if self.has_unsaved_changes() {
+            self.show_unsaved_changes_dialog();
+        } else {
+            self.nav_view.pop();
+        }
+    }
+
+    /// A new app is unsaved as soon as anything is entered, an existing app only has the rows
+    fn has_unsaved_changes(self: &Rc<Self>) -> bool {
+        if self.is_dirty() {
+            return true;
+        }
+        if !self.get_is_new() {
+            return false;
+        }
+
+        let desktop_file_borrow = self.desktop_file.borrow();
+        desktop_file_borrow.get_name().is_some()
+            || desktop_file_borrow.get_url().is_some()
+            || desktop_file_borrow.get_description().is_some()
+            || desktop_file_borrow
+                .get_icon_path()
+                .is_some_and(|icon_path| !icon_path.as_os_str().is_empty())
+    }
+
+    fn show_unsaved_changes_dialog(self: &Rc<Self>) {
+        let dialog_stay = "stay";
+        let dialog_discard = "discard";
+        let dialog_apply = "apply";
+        let is_new = self.get_is_new();
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.web_app_view.unsaved_dialog.title"))
+            .body(if is_new {
+                t!("web_apps.web_app_view.unsaved_dialog.text_new")
+            } else {
+                t!("web_apps.web_app_view.unsaved_dialog.text")
+            })
+            .build();
+
+        dialog.add_response(
+            dialog_stay,
+            &t!("web_apps.web_app_view.unsaved_dialog.stay"),
+        );
+        dialog.add_response(
+            dialog_discard,
+            &t!("web_apps.web_app_view.unsaved_dialog.discard"),
+        );
+        dialog.set_response_appearance(dialog_discard, ResponseAppearance::Destructive);
+        dialog.set_default_response(Some(dialog_stay));
+        dialog.set_close_response(dialog_stay);
+
+        // A new app is only kept by saving it, applying the rows alone would be lost anyway
+        if !is_new {
+            dialog.add_response(
+                dialog_apply,
+                &t!("web_apps.web_app_view.unsaved_dialog.apply"),
+            );
+            dialog.set_response_appearance(dialog_apply, ResponseAppearance::Suggested);
+            if self.can_apply_rows() {
+                dialog.set_default_response(Some(dialog_apply));
+            } else {
+                dialog.set_response_enabled(dialog_apply, false);
+            }
+        }
+
+        let self_clone = self.clone();
+        dialog.connect_response(Some(dialog_discard), move |_, _| {
+            debug!("Discarding pending edits");
+            // The unrealize handler of a new app removes its files
+            self_clone.nav_view.pop();
+        });
+
+        let self_clone = self.clone();
+        dialog.connect_response(Some(dialog_apply), move |_, _| {
+            debug!("Applying pending edits");
+            self_clone.apply_rows();
+            self_clone.nav_view.pop();
+        });
+
+        dialog.present(Some(&self.app.window.adw_window));
+    }
+
+    /// Rows only show the apply button for valid input
+    fn can_apply_rows(self: &Rc<Self>) -> bool {
+        self.get_dirty_rows()
+            .iter()
+            .all(|entry_row| entry_row.shows_apply_button())
+    }
+
+    fn apply_rows(self: &Rc<Self>) {
+        for entry_row in self.get_dirty_rows() {
+            entry_row.emit_by_name::<()>("apply", &[]);
+        }
+    }
+
+    fn get_dirty_rows(self: &Rc<Self>) -> Vec<EntryRow> {
+        let desktop_file_borrow = self.desktop_file.borrow();
+        let name_saved = desktop_file_borrow.get_name().unwrap_or_default();
+        let url_saved = desktop_file_borrow.get_url().unwrap_or_default();
+        let description_saved = desktop_file_borrow.get_description().unwrap_or_default();
+        drop(desktop_file_borrow);
+
+        [
+            (&self.name_row, name_saved),
+            (&self.url_row, url_saved),
+            (&self.description_row, description_saved),
+        ]
+        .into_iter()
+        .filter(|(entry_row, saved)| entry_row.text() != *saved)
+        .map(|(entry_row, _)| entry_row.clone())
+        .collect()
+    }
+
     fn connect_change_icon_button(self: &Rc<Self>) {
         if *self.is_new.borrow() {
             self.change_icon_button.set_sensitive(false);
@@ -610,7 +1926,7 @@ impl WebAppView {
                         .set_icon_path(Path::new(&undo_icon_path));
 
                     self_clone_fail.on_desktop_file_change();
-                    self_clone_fail.on_error("Failed to save icon", None);
+                    self_clone_fail.on_error(&t!("web_apps.web_app_view.error.save_icon"), None);
                 }),
             );
         });
@@ -620,34 +1936,76 @@ impl WebAppView {
         let self_clone = self.clone();
 
         self.run_app_button.connect_clicked(move |_| {
-            let desktop_file_borrow = self_clone.desktop_file.borrow();
-            let Some(mut executable) = desktop_file_borrow.get_exec() else {
-                return;
-            };
-
-            if utils::env::is_devcontainer() {
-                if desktop_file_borrow
-                    .get_browser()
-                    .is_some_and(|browser| browser.base == Base::Chromium)
-                {
-                    let _ = write!(executable, " --no-sandbox");
+            // The `Exec` is only rendered on save
+            self_clone.flush_pending_save();
+            let launch_result = self_clone.desktop_file.borrow().launch();
+            match launch_result {
+                Ok(background_command) => {
+                    self_clone.record_launch();
+                    self_clone.watch_launch(background_command);
+                }
+                Err(LaunchError::NoExec) => {}
+                Err(LaunchError::BrowserMissing) => {
+                    self_clone.on_error(&t!("web_apps.web_app_view.toast.browser_missing"), None);
+                }
+                Err(LaunchError::Other(error)) => {
+                    error!(error = error.to_string(), "Failed to run app");
                 }
-                debug!("Running in dev-container");
-            }
-
-            debug!("Running web app: '{executable}'");
-            if let Err(error) = utils::command::run_command_background(&executable) {
-                error!(
-                    executable = executable,
-                    error = error.to_string(),
-                    "Failed to run app"
-                );
             }
         });
     }
 
-    fn connect_save_button(self: &Rc<Self>) {
-        let self_clone = self.clone();
+    /// Open stays off until the browser is known to run, so a second click can't start it twice.
+    /// A browser that quits right away turns it back on with its error.
+    fn watch_launch(self: &Rc<Self>, background_command: BackgroundCommand) {
+        self.run_app_button.set_sensitive(false);
+
+        let self_clone = self.clone();
+        glib::spawn_future_local(async move {
+            let startup_status =
+                command::watch_startup(&background_command, WebAppsPage::LAUNCH_WATCH).await;
+            match startup_status {
+                StartupStatus::Started => {
+                    self_clone
+                        .run_app_button
+                        .set_label(&t!("web_apps.web_app_view.button.launched"));
+                    glib::timeout_future(Self::LAUNCHED_STATE_DURATION).await;
+                    self_clone
+                        .run_app_button
+                        .set_label(&t!("web_apps.web_app_view.button.open"));
+                }
+                StartupStatus::Failed { stderr_tail } => {
+                    error!(stderr_tail, "Web app quit on startup");
+                    self_clone.on_error(
+                        &WebAppsPage::build_launch_failed_message(&stderr_tail),
+                        None,
+                    );
+                }
+            }
+            self_clone.run_app_button.set_sensitive(true);
+        });
+    }
+
+    /// The original gets the statistics too, a launch is not a change Reset can undo
+    fn record_launch(self: &Rc<Self>) {
+        let record_result = self.desktop_file.borrow_mut().record_launch();
+        if let Err(error) = record_result {
+            error!("Failed to record launch: {error:?}");
+            return;
+        }
+
+        let desktop_file_borrow = self.desktop_file.borrow();
+        let mut desktop_file_original = self.desktop_file_original.borrow_mut();
+        if let Some(last_launched) = desktop_file_borrow.get_last_launched() {
+            desktop_file_original.set_last_launched(last_launched);
+        }
+        if let Some(launch_count) = desktop_file_borrow.get_launch_count() {
+            desktop_file_original.set_launch_count(launch_count);
+        }
+    }
+
+    fn connect_save_button(self: &Rc<Self>) {
+        let self_clone = self.clone();
 
         self.save_button.connect_clicked(move |_| {
             self_clone.on_new_desktop_file_save();
@@ -658,23 +2016,49 @@ impl WebAppView {
         let self_clone = self.clone();
 
         self.delete_button.connect_clicked(move |_| {
-            debug!(
-                "Deleting web app: {}",
-                self_clone
-                    .desktop_file
-                    .borrow()
-                    .get_name()
-                    .unwrap_or_default()
-            );
-
-            if !*self_clone.is_new.borrow() && self_clone.desktop_file.borrow().delete().is_err() {
-                self_clone.on_error("Failed to delete all files", None);
+            // New apps are cleaned up when the page is gone
+            if *self_clone.is_new.borrow() {
+                self_clone.nav_view.pop();
+                return;
             }
 
-            self_clone.nav_view.pop();
+            self_clone.show_delete_dialog();
         });
     }
 
+    fn show_delete_dialog(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        WebAppsPage::show_delete_dialog(
+            &self.app,
+            &self.desktop_file.borrow(),
+            move |delete_options| {
+                // A later save would bring the file back
+                self_clone.cancel_pending_save();
+
+                // Files are kept until the undo toast is gone
+                let trash_result = self_clone
+                    .desktop_file
+                    .borrow()
+                    .move_to_trash(delete_options);
+                let trashed_web_app = match trash_result {
+                    Ok(trashed_web_app) => trashed_web_app,
+                    Err(error) => {
+                        self_clone
+                            .on_error(&t!("web_apps.web_app_view.error.delete"), Some(&error));
+                        return;
+                    }
+                };
+
+                self_clone.nav_view.pop();
+                self_clone
+                    .app
+                    .pages
+                    .get_web_apps()
+                    .show_undo_delete_toast(&self_clone.app, trashed_web_app);
+            },
+        );
+    }
+
     fn connect_name_row(self: &Rc<Self>) {
         let validate_icon = Self::build_validate_icon();
         self.name_row.add_suffix(&validate_icon);
@@ -716,31 +2100,283 @@ impl WebAppView {
         });
     }
 
+    fn connect_description_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.description_row.connect_apply(move |entry_row| {
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_description(&entry_row.text());
+
+            self_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_extra_args_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.extra_args_row.connect_apply(move |entry_row| {
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_extra_args(&entry_row.text());
+
+            self_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_env_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let add_button = Button::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text(t!("web_apps.web_app_view.advanced.env.add"))
+            .valign(Align::Center)
+            .css_classes(["flat"])
+            .build();
+
+        add_button.connect_clicked(move |_| {
+            let entry_row = self_clone.add_env_entry_row("");
+            self_clone.env_row.set_expanded(true);
+            entry_row.grab_focus();
+        });
+        self.env_row.add_suffix(&add_button);
+
+        self.reset_env_rows();
+    }
+
+    fn add_env_entry_row(self: &Rc<Self>, pair: &str) -> EntryRow {
+        let entry_row = EntryRow::builder()
+            .title(t!("web_apps.web_app_view.advanced.env.pair"))
+            .text(pair)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build();
+        let remove_button = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(t!("web_apps.web_app_view.advanced.env.remove"))
+            .valign(Align::Center)
+            .css_classes(["flat"])
+            .build();
+        entry_row.add_suffix(&remove_button);
+
+        let self_clone = self.clone();
+        entry_row.connect_apply(move |_| {
+            self_clone.apply_env();
+        });
+
+        let self_clone = self.clone();
+        let entry_row_clone = entry_row.clone();
+        remove_button.connect_clicked(move |_| {
+            self_clone.env_row.remove(&entry_row_clone);
+            self_clone
+                .env_entry_rows
+                .borrow_mut()
+                .retain(|env_entry_row| env_entry_row != &entry_row_clone);
+            self_clone.apply_env();
+        });
+
+        self.env_row.add_row(&entry_row);
+        self.env_entry_rows.borrow_mut().push(entry_row.clone());
+
+        entry_row
+    }
+
+    /// Saves the pairs of all rows, empty rows are left out
+    fn apply_env(self: &Rc<Self>) {
+        let env: Vec<String> = self
+            .env_entry_rows
+            .borrow()
+            .iter()
+            .map(|entry_row| entry_row.text().to_string())
+            .collect();
+        self.desktop_file.borrow_mut().set_env(&env);
+
+        self.on_desktop_file_change();
+    }
+
+    fn reset_env_rows(self: &Rc<Self>) {
+        for entry_row in self.env_entry_rows.take() {
+            self.env_row.remove(&entry_row);
+        }
+
+        let env = self.desktop_file.borrow().get_env().unwrap_or_default();
+        for pair in &env {
+            self.add_env_entry_row(pair);
+        }
+    }
+
+    fn connect_keywords_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.keywords_row.connect_apply(move |entry_row| {
+            let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+            desktop_file_borrow.set_keywords(&entry_row.text());
+            let keywords = desktop_file_borrow
+                .get_keywords()
+                .unwrap_or_default()
+                .join(", ");
+            drop(desktop_file_borrow);
+
+            // Show the words like they are saved
+            entry_row.set_text(&keywords);
+            self_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_handler_url_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.handler_url_row.connect_apply(move |entry_row| {
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_handler_url(&entry_row.text());
+
+            self_clone.on_desktop_file_change();
+            self_clone.offer_default_handlers();
+        });
+    }
+
+    fn connect_mime_types_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.mime_types_row.connect_apply(move |entry_row| {
+            let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+            desktop_file_borrow.set_mime_types(&entry_row.text());
+            desktop_file_borrow.unregister_removed_handlers();
+            let mime_types = desktop_file_borrow.get_mime_types_ui();
+            drop(desktop_file_borrow);
+
+            // Show the types like they are saved
+            entry_row.set_text(&mime_types);
+            self_clone.on_desktop_file_change();
+            self_clone.offer_default_handlers();
+        });
+
+        // Presets are added to the text, applying stays with the user
+        let actions = SimpleActionGroup::new();
+        let add_scheme_action = SimpleAction::new(
+            Self::ACTION_ADD_HANDLER_SCHEME,
+            Some(glib::VariantTy::STRING),
+        );
+        let self_clone = self.clone();
+        add_scheme_action.connect_activate(move |_, parameter| {
+            let Some(scheme) = parameter.and_then(|parameter| parameter.get::<String>()) else {
+                return;
+            };
+            let text = self_clone.mime_types_row.text();
+            let text = text.trim().trim_end_matches([',', ';']);
+
+            if text.is_empty() {
+                self_clone.mime_types_row.set_text(&scheme);
+            } else {
+                self_clone
+                    .mime_types_row
+                    .set_text(&format!("{text}, {scheme}"));
+            }
+            self_clone.mime_types_row.grab_focus();
+        });
+        actions.add_action(&add_scheme_action);
+        self.mime_types_row
+            .insert_action_group(Self::ACTION_LABEL_HANDLERS, Some(&actions));
+    }
+
+    /// Asks before taking over links from the current default, only for a saved and valid app
+    fn offer_default_handlers(self: &Rc<Self>) {
+        if self.get_is_new() || self.desktop_file.borrow().validate().is_err() {
+            return;
+        }
+        let mime_types = self.desktop_file.borrow().get_unregistered_mime_types();
+        if mime_types.is_empty() {
+            return;
+        }
+
+        let dialog_cancel = "cancel";
+        let dialog_ok = "ok";
+
+        let name = self.desktop_file.borrow().get_name().unwrap_or_default();
+        let dialog = AlertDialog::builder()
+            .heading(t!(
+                "web_apps.web_app_view.handlers_dialog.title",
+                name = name
+            ))
+            .body(t!(
+                "web_apps.web_app_view.handlers_dialog.text",
+                mime_types = mime_types.join(", ")
+            ))
+            .build();
+
+        dialog.add_response(
+            dialog_cancel,
+            &t!("web_apps.web_app_view.handlers_dialog.cancel"),
+        );
+        dialog.add_response(dialog_ok, &t!("web_apps.web_app_view.handlers_dialog.ok"));
+        dialog.set_response_appearance(dialog_ok, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(dialog_cancel));
+        dialog.set_close_response(dialog_cancel);
+
+        let self_clone = self.clone();
+        dialog.connect_response(Some(dialog_ok), move |_, _| {
+            let result = self_clone
+                .desktop_file
+                .borrow_mut()
+                .register_default_handlers();
+            if let Err(error) = result {
+                self_clone.on_error(
+                    &t!("web_apps.web_app_view.error.set_default_app"),
+                    Some(&error),
+                );
+            }
+
+            // Saves the recorded previous defaults
+            self_clone.on_desktop_file_change();
+        });
+
+        dialog.present(Some(&self.app.window.adw_window));
+    }
+
     fn connect_url_row(self: &Rc<Self>) {
         let validate_icon_url = Self::build_validate_icon();
         let spinner = Spinner::new();
         spinner.set_visible(false);
 
         self.url_row.add_suffix(&validate_icon_url);
+        self.url_row.add_suffix(&self.url_reachability_icon);
         self.url_row.add_suffix(&spinner);
 
         let self_clone = self.clone();
 
         self.url_row.connect_changed(move |entry_row| {
             let input = entry_row.text().to_string();
-            let is_valid = Url::parse(&input)
-                .is_ok_and(|url| url.domain().is_some() || url.host_str().is_some());
+            let url = parse_user_input(&input);
+            let is_url = url.is_some();
+            let policy_result = url
+                .as_ref()
+                .map_or(Ok(()), |url| policy::get().check_url(url.as_str()));
+            let is_valid = is_url && policy_result.is_ok();
 
             debug!(is_valid, input, "Validate input: {}", entry_row.title());
 
             validate_icon_url.set_visible(!is_valid);
+            // The status belongs to the saved url
+            if self_clone.desktop_file.borrow().get_url().as_deref() != Some(input.trim()) {
+                self_clone.url_reachability_icon.set_visible(false);
+            }
             if is_valid {
                 entry_row.set_show_apply_button(true);
                 entry_row.set_tooltip_text(None);
                 self_clone.change_icon_button.set_sensitive(true);
             } else {
+                let tooltip = match policy_result {
+                    Err(error) if is_url => t!(
+                        "web_apps.web_app_view.url.policy",
+                        reason = error.to_string()
+                    ),
+                    _ => t!("web_apps.web_app_view.url.validate"),
+                };
                 entry_row.set_show_apply_button(false);
-                entry_row.set_tooltip_text(Some(&t!("web_apps.web_app_view.url.validate")));
+                entry_row.set_tooltip_text(Some(&tooltip));
                 self_clone.change_icon_button.set_sensitive(false);
             }
 
@@ -748,102 +2384,490 @@ impl WebAppView {
         });
 
         let self_clone = self.clone();
-        let running_icon_search_id = Rc::new(RefCell::new(0));
+        self.url_row.connect_apply(move |entry_row| {
+            let Some(url) = parse_user_input(&entry_row.text()) else {
+                return;
+            };
+            // Shows what is saved, like the added scheme or the punycode of the domain
+            if entry_row.text() != url.as_str() {
+                entry_row.set_text(url.as_str());
+            }
+            self_clone.change_icon_button.set_sensitive(true);
+
+            self_clone.desktop_file.borrow_mut().set_url(url.as_str());
+
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_icon_path(Path::new(""));
+
+            if self_clone.get_is_new() {
+                self_clone.apply_site_hint();
+            }
+
+            self_clone.reset_app_header();
+            self_clone.reset_icon_picker();
+            self_clone.check_url_reachability(&url);
+
+            let self_clone = self_clone.clone();
+            let spinner_clone = spinner.clone();
+
+            glib::spawn_future_local(async move {
+                spinner_clone.set_visible(true);
+                self_clone.change_icon_button.set_sensitive(false);
+                self_clone
+                    .app
+                    .announce(&t!("web_apps.web_app_view.announce.fetching_icon"));
+
+                let icon_picker = self_clone.get_icon_picker();
+                let result = icon_picker.save_first_icon_found().await;
+
+                if self_clone.get_is_discarded() {
+                    debug!("Web app view is discarded, ignoring icon search result");
+                    return;
+                }
+                // A newer url replaced the picker, its own search updates the view
+                if !self_clone.is_current_icon_picker(&icon_picker) {
+                    return;
+                }
+                if icon_picker.is_fetch_cancelled() {
+                    debug!("Icon search cancelled");
+                    spinner_clone.set_visible(false);
+                    self_clone.change_icon_button.set_sensitive(true);
+                    return;
+                }
+
+                let is_icon_found = result.is_ok();
+                if let Err(error) = result {
+                    self_clone
+                        .desktop_file
+                        .borrow_mut()
+                        .set_icon_path(Path::new(""));
+                    error!("{error:?}");
+                }
+
+                if self_clone.get_is_new() {
+                    self_clone.apply_manifest_suggestions(&icon_picker);
+                }
+                self_clone.on_desktop_file_change();
+                spinner_clone.set_visible(false);
+                self_clone.change_icon_button.set_sensitive(true);
+                self_clone.app.announce(&if is_icon_found {
+                    t!("web_apps.web_app_view.announce.icon_updated")
+                } else {
+                    t!("web_apps.web_app_view.announce.no_icon")
+                });
+            });
+        });
+    }
+
+    /// Only informational, saving never waits for it
+    fn check_url_reachability(self: &Rc<Self>, url: &Url) {
+        let cancellable = Cancellable::new();
+        self.url_reachability_cancellable
+            .replace(cancellable.clone())
+            .cancel();
+        self.url_reachability_icon.set_visible(false);
+
+        let self_clone = self.clone();
+        let url = url.to_string();
+
+        glib::spawn_future_local(async move {
+            let Ok(reachability) = self_clone
+                .app
+                .fetch
+                .check_reachability(&url, Some(&cancellable))
+                .await
+            else {
+                return;
+            };
+            if self_clone.get_is_discarded() || cancellable.is_cancelled() {
+                return;
+            }
+
+            let (icon_name, css_class, tooltip) = match reachability {
+                Reachability::Reachable(status) => (
+                    "object-select-symbolic",
+                    "success",
+                    t!(
+                        "web_apps.web_app_view.url.reachability.reachable",
+                        status = status
+                    ),
+                ),
+                Reachability::HttpError(status) => (
+                    "dialog-warning-symbolic",
+                    "warning",
+                    t!(
+                        "web_apps.web_app_view.url.reachability.http_error",
+                        status = status
+                    ),
+                ),
+                Reachability::TlsFailed => (
+                    "network-offline-symbolic",
+                    "warning",
+                    t!("web_apps.web_app_view.url.reachability.tls_failed"),
+                ),
+                Reachability::HostNotFound => (
+                    "network-offline-symbolic",
+                    "dim-label",
+                    t!("web_apps.web_app_view.url.reachability.host_not_found"),
+                ),
+                Reachability::Unreachable => (
+                    "network-offline-symbolic",
+                    "dim-label",
+                    t!("web_apps.web_app_view.url.reachability.unreachable"),
+                ),
+            };
+
+            let reachability_icon = &self_clone.url_reachability_icon;
+            reachability_icon.set_icon_name(Some(icon_name));
+            reachability_icon.set_css_classes(&[css_class]);
+            reachability_icon.set_tooltip_text(Some(&tooltip));
+            reachability_icon.set_visible(true);
+        });
+    }
+
+    /// Hints for known sites go before the manifest suggestions, input of the user is kept
+    fn apply_site_hint(self: &Rc<Self>) {
+        let site_hint = self
+            .desktop_file
+            .borrow()
+            .get_domain()
+            .and_then(|domain| self.app.site_hints.get_hint(&domain));
+        let Some(site_hint) = site_hint else {
+            self.site_hint_banner.set_revealed(false);
+            return;
+        };
+        debug!(?site_hint, "Applying site hint");
+
+        if let Some(name) = &site_hint.name {
+            self.suggest_name(name);
+        }
+
+        if let Some(is_isolated) = site_hint.isolate
+            && self.isolate_row.is_sensitive()
+        {
+            self.isolate_row.set_active(is_isolated);
+        }
+
+        if let Some(category) = &site_hint.category {
+            let mut desktop_file_borrow = self.desktop_file.borrow_mut();
+            match Category::from_categories(category) {
+                Some(category) if desktop_file_borrow.get_category().is_none() => {
+                    desktop_file_borrow.set_category(&category);
+                }
+                Some(_) => {}
+                None => debug!(category, "Unknown category in site hint"),
+            }
+            drop(desktop_file_borrow);
+            self.reset_category_row();
+        }
+
+        match site_hint
+            .note
+            .get(&self.app.locale.current)
+            .or(site_hint.note.get(&self.app.locale.default))
+        {
+            Some(note) => {
+                self.site_hint_banner.set_title(note);
+                self.site_hint_banner.set_revealed(true);
+            }
+            None => self.site_hint_banner.set_revealed(false),
+        }
+    }
+
+    /// Suggestions from the site's manifest, the user can still change all of them
+    fn apply_manifest_suggestions(self: &Rc<Self>, icon_picker: &Rc<IconPicker>) {
+        let Some(manifest_metadata) = icon_picker.get_manifest_metadata() else {
+            return;
+        };
+        debug!(?manifest_metadata, "Applying manifest suggestions");
+
+        if let Some(name) = manifest_metadata.get_name() {
+            self.suggest_name(name);
+        }
+
+        if let Some(theme_color) = &manifest_metadata.theme_color {
+            self.desktop_file.borrow_mut().set_theme_color(theme_color);
+        }
+
+        if manifest_metadata.is_standalone() && self.maximize_row.is_sensitive() {
+            self.maximize_row.set_active(true);
+        }
+    }
+
+    /// A name typed by the user is never replaced
+    fn suggest_name(&self, name: &str) {
+        let current_name = self.name_row.text();
+        let is_suggested = self.suggested_name.borrow().as_deref() == Some(current_name.as_str());
+        if !current_name.is_empty() && !is_suggested {
+            return;
+        }
+
+        debug!(name, "Suggesting name");
+        self.name_row.set_text(name);
+        self.name_row.emit_by_name::<()>("apply", &[]);
+        *self.suggested_name.borrow_mut() = Some(name.to_string());
+    }
+
+    fn connect_isolate_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.isolate_row.connect_active_notify(move |switch_row| {
+            let is_isolated = switch_row.is_active();
+
+            // Reset and cancel set the row to the value that is already in the desktop file
+            if self_clone.desktop_file.borrow().get_isolated() == Some(is_isolated) {
+                return;
+            }
+
+            let profile_path = self_clone
+                .desktop_file
+                .borrow()
+                .get_profile_path()
+                .filter(|profile_path| profile_path.is_dir());
+
+            // Only ask when the user turned it off, not when the browser can't isolate
+            if !is_isolated
+                && switch_row.is_sensitive()
+                && let Some(profile_path) = profile_path
+            {
+                self_clone.show_isolation_off_dialog(&profile_path);
+                return;
+            }
+
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_isolated(is_isolated);
+
+            self_clone.on_isolation_change();
+            self_clone.on_desktop_file_change();
+        });
+    }
+
+    fn show_isolation_off_dialog(self: &Rc<Self>, profile_path: &Path) {
+        let dialog_cancel = "cancel";
+        let dialog_keep = "keep";
+        let dialog_delete = "delete";
+
+        let size_mb = utils::files::get_dir_size(profile_path) / 1_000_000;
+        let dialog = AlertDialog::builder()
+            .heading(t!(
+                "web_apps.web_app_view.isolation_off_dialog.title",
+                size = size_mb
+            ))
+            .body(t!("web_apps.web_app_view.isolation_off_dialog.text"))
+            .build();
+
+        dialog.add_response(
+            dialog_cancel,
+            &t!("web_apps.web_app_view.isolation_off_dialog.cancel"),
+        );
+        dialog.add_response(
+            dialog_keep,
+            &t!("web_apps.web_app_view.isolation_off_dialog.keep"),
+        );
+        dialog.add_response(
+            dialog_delete,
+            &t!("web_apps.web_app_view.isolation_off_dialog.delete"),
+        );
+        dialog.set_response_appearance(dialog_delete, ResponseAppearance::Destructive);
+        dialog.set_default_response(Some(dialog_cancel));
+        dialog.set_close_response(dialog_cancel);
+
+        let self_clone = self.clone();
+        dialog.connect_response(None, move |_, response| {
+            if response == dialog_cancel {
+                self_clone.isolate_row.set_active(true);
+                return;
+            }
+
+            let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+            desktop_file_borrow.set_isolated(false);
+
+            if response == dialog_keep {
+                // Turning isolation on again uses the same path, so it is picked up again
+                debug!("Keeping profile on disk");
+                desktop_file_borrow.set_profile_path(&PathBuf::default());
+                drop(desktop_file_borrow);
+            } else {
+                drop(desktop_file_borrow);
+                self_clone.on_isolation_change();
+            }
+
+            self_clone.on_desktop_file_change();
+        });
+
+        dialog.present(Some(&self.app.window.adw_window));
+    }
+
+    fn connect_maximize_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.maximize_row.connect_active_notify(move |switch_row| {
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_maximized(switch_row.is_active());
+
+            self_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_window_size_rows(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.window_width_row.connect_value_notify(move |spin_row| {
+            let width = Self::get_window_size_value(spin_row);
+            // Don't add the key when a reset restores a missing value
+            if self_clone
+                .desktop_file
+                .borrow()
+                .get_window_width()
+                .unwrap_or(0)
+                == width
+            {
+                return;
+            }
+            self_clone.desktop_file.borrow_mut().set_window_width(width);
+
+            self_clone.on_desktop_file_change();
+        });
+
+        let self_clone = self.clone();
 
-        self.url_row.connect_apply(move |entry_row| {
-            self_clone.change_icon_button.set_sensitive(true);
+        self.window_height_row
+            .connect_value_notify(move |spin_row| {
+                let height = Self::get_window_size_value(spin_row);
+                if self_clone
+                    .desktop_file
+                    .borrow()
+                    .get_window_height()
+                    .unwrap_or(0)
+                    == height
+                {
+                    return;
+                }
+                self_clone
+                    .desktop_file
+                    .borrow_mut()
+                    .set_window_height(height);
 
-            self_clone
-                .desktop_file
-                .borrow_mut()
-                .set_url(&entry_row.text());
+                self_clone.on_desktop_file_change();
+            });
+    }
 
-            self_clone
-                .desktop_file
-                .borrow_mut()
-                .set_icon_path(Path::new(""));
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn get_window_size_value(spin_row: &SpinRow) -> u32 {
+        // The range is 0 to WINDOW_SIZE_MAX, so it always fits
+        spin_row.value().round() as u32
+    }
 
-            self_clone.reset_app_header();
-            self_clone.reset_icon_picker();
+    fn connect_fullscreen_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
 
-            let self_clone = self_clone.clone();
-            let spinner_clone = spinner.clone();
-            let running_icon_search_id_clone = running_icon_search_id.clone();
+        self.fullscreen_row
+            .connect_active_notify(move |switch_row| {
+                self_clone
+                    .desktop_file
+                    .borrow_mut()
+                    .set_fullscreen(switch_row.is_active());
 
-            // Bug with clippy, make sure its dropped before await future
-            #[allow(clippy::await_holding_refcell_ref)]
-            glib::spawn_future_local(async move {
-                let mut is_running_icon_search_borrow = running_icon_search_id_clone.borrow_mut();
-                *is_running_icon_search_borrow += 1;
-                let run_id = *is_running_icon_search_borrow;
-                drop(is_running_icon_search_borrow);
+                self_clone.on_desktop_file_change();
+            });
+    }
 
-                spinner_clone.set_visible(true);
-                self_clone.change_icon_button.set_sensitive(false);
+    fn connect_minimal_ui_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
 
-                let icon_picker = self_clone.get_icon_picker();
-                if let Err(error) = icon_picker.save_first_icon_found().await {
-                    if *running_icon_search_id_clone.borrow() != run_id {
-                        return;
-                    }
+        self.minimal_ui_row
+            .connect_active_notify(move |switch_row| {
+                self_clone
+                    .desktop_file
+                    .borrow_mut()
+                    .set_minimal_ui(switch_row.is_active());
 
-                    self_clone
-                        .desktop_file
-                        .borrow_mut()
-                        .set_icon_path(Path::new(""));
-                    error!("{error:?}");
-                }
+                self_clone.on_desktop_file_change();
 
-                if *running_icon_search_id_clone.borrow() != run_id {
+                // A new app gets its profile config when it is saved
+                if self_clone.get_is_new() {
                     return;
                 }
-                self_clone.on_desktop_file_change();
-                spinner_clone.set_visible(false);
-                self_clone.change_icon_button.set_sensitive(true);
+                let result = self_clone.desktop_file.borrow().update_profile_config();
+                if let Err(error) = result {
+                    self_clone.on_error(
+                        &t!("web_apps.web_app_view.error.profile_config"),
+                        Some(&error),
+                    );
+                }
             });
-        });
     }
 
-    fn connect_isolate_row(self: &Rc<Self>) {
+    fn connect_private_row(self: &Rc<Self>) {
         let self_clone = self.clone();
 
-        self.isolate_row.connect_active_notify(move |switch_row| {
+        self.private_row.connect_active_notify(move |switch_row| {
             self_clone
                 .desktop_file
                 .borrow_mut()
-                .set_isolated(switch_row.is_active());
+                .set_private(switch_row.is_active());
 
-            self_clone.on_isolation_change();
             self_clone.on_desktop_file_change();
         });
     }
 
-    fn connect_maximize_row(self: &Rc<Self>) {
+    fn connect_autostart_row(self: &Rc<Self>) {
         let self_clone = self.clone();
 
-        self.maximize_row.connect_active_notify(move |switch_row| {
-            self_clone
-                .desktop_file
-                .borrow_mut()
-                .set_maximized(switch_row.is_active());
+        self.autostart_row.connect_active_notify(move |switch_row| {
+            let desktop_file_borrow = self_clone.desktop_file.borrow();
+            let result = if switch_row.is_active() {
+                desktop_file_borrow.enable_autostart()
+            } else {
+                desktop_file_borrow.disable_autostart()
+            };
+            let is_autostart = desktop_file_borrow.is_autostart();
+            drop(desktop_file_borrow);
 
-            self_clone.on_desktop_file_change();
+            if let Err(error) = result {
+                self_clone.on_error(&t!("web_apps.web_app_view.error.autostart"), Some(&error));
+            }
+            // Shows what is actually on disk when the change failed
+            switch_row.set_active(is_autostart);
         });
     }
 
+    fn connect_force_dark_row(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.force_dark_row
+            .connect_active_notify(move |switch_row| {
+                self_clone
+                    .desktop_file
+                    .borrow_mut()
+                    .set_force_dark(switch_row.is_active());
+
+                self_clone.on_desktop_file_change();
+            });
+    }
+
     fn connect_browser_row(self: &Rc<Self>) {
         let desktop_file_clone = self.desktop_file.clone();
         let self_clone = self.clone();
 
         self.browser_row
             .connect_selected_item_notify(move |combo_row| {
+                if *self_clone.is_syncing_browsers.borrow() {
+                    return;
+                }
                 let selected_item = combo_row.selected_item();
                 let Some(selected_item) = selected_item else {
                     return;
                 };
                 let Ok(browser_item_boxed) = selected_item.downcast::<BoxedAnyObject>() else {
                     self_clone.on_error(
-                        "Failed to save browser",
+                        &t!("web_apps.web_app_view.error.save_browser"),
                         Some(&anyhow!("Failed to downcast selected item in browser_row")),
                     );
                     return;
@@ -852,20 +2876,38 @@ impl WebAppView {
 
                 desktop_file_clone.borrow_mut().set_browser(&browser);
 
+                self_clone.reset_browser_issues();
                 self_clone.on_isolation_change();
                 self_clone.on_desktop_file_change();
             });
     }
 
-    fn connect_optional_row(self: &Rc<Self>) {
-        let app_clone = self.app.clone();
+    fn connect_category_row(self: &Rc<Self>) {
         let desktop_file_clone = self.desktop_file.clone();
         let self_clone = self.clone();
 
-        self.optional_row.connect_activated(move |_| {
-            let optional_dialog = OptionalSettings::new(&app_clone, &desktop_file_clone);
-            optional_dialog.show_dialog(&self_clone);
-        });
+        self.category_row
+            .connect_selected_item_notify(move |combo_row| {
+                if *self_clone.is_syncing_category.borrow() {
+                    return;
+                }
+                let selected_item = combo_row.selected_item();
+                let Some(selected_item) = selected_item else {
+                    return;
+                };
+                let Ok(category_item_boxed) = selected_item.downcast::<BoxedAnyObject>() else {
+                    self_clone.on_error(
+                        &t!("web_apps.web_app_view.error.save_category"),
+                        Some(&anyhow!("Failed to downcast selected item in category_row")),
+                    );
+                    return;
+                };
+                let category = category_item_boxed.borrow::<Category>();
+
+                desktop_file_clone.borrow_mut().set_category(&category);
+
+                self_clone.on_desktop_file_change();
+            });
     }
 
     fn reset_reset_button(self: &Rc<Self>) {
@@ -906,11 +2948,20 @@ impl WebAppView {
             .borrow()
             .get_browser()
             .is_some_and(|browser| browser.can_isolate);
-        self.isolate_row.set_sensitive(browser_can_isolate);
-        if browser_can_isolate {
+        let is_forced = browser_can_isolate && policy::get().get_force_isolation();
+        self.isolate_row
+            .set_sensitive(browser_can_isolate && !is_forced);
+        if is_forced {
+            self.isolate_row.set_active(true);
+            self.isolate_row
+                .set_tooltip_text(Some(&t!("web_apps.web_app_view.isolate.policy")));
+            self.isolate_row.set_has_tooltip(true);
+        } else if browser_can_isolate {
             self.isolate_row.set_has_tooltip(false);
         } else {
             self.isolate_row.set_active(false);
+            self.isolate_row
+                .set_tooltip_text(Some(&t!("web_apps.web_app_view.isolate.disabled")));
             self.isolate_row.set_has_tooltip(true);
         }
     }
@@ -931,6 +2982,70 @@ impl WebAppView {
         }
     }
 
+    fn reset_browser_fullscreen(self: &Rc<Self>) {
+        let browser_can_fullscreen = self
+            .desktop_file
+            .borrow()
+            .get_browser()
+            .is_some_and(|browser| browser.can_fullscreen);
+        self.fullscreen_row.set_sensitive(browser_can_fullscreen);
+
+        if browser_can_fullscreen {
+            self.fullscreen_row.set_has_tooltip(false);
+        } else {
+            self.fullscreen_row.set_active(false);
+            self.fullscreen_row.set_has_tooltip(true);
+        }
+    }
+
+    fn reset_minimal_ui(self: &Rc<Self>) {
+        let can_minimal_ui = self.desktop_file.borrow().can_minimal_ui();
+        self.minimal_ui_row.set_sensitive(can_minimal_ui);
+        self.minimal_ui_row.set_has_tooltip(!can_minimal_ui);
+    }
+
+    /// A maximized window ignores the size
+    fn reset_window_size(self: &Rc<Self>) {
+        let is_maximized = self.maximize_row.is_active();
+
+        for spin_row in [&self.window_width_row, &self.window_height_row] {
+            spin_row.set_sensitive(!is_maximized);
+            spin_row.set_has_tooltip(is_maximized);
+        }
+    }
+
+    fn reset_browser_private(self: &Rc<Self>) {
+        let browser_can_private = self
+            .desktop_file
+            .borrow()
+            .get_browser()
+            .is_some_and(|browser| browser.can_private);
+        self.private_row.set_sensitive(browser_can_private);
+
+        if browser_can_private {
+            self.private_row.set_has_tooltip(false);
+        } else {
+            self.private_row.set_active(false);
+            self.private_row.set_has_tooltip(true);
+        }
+    }
+
+    fn reset_browser_force_dark(self: &Rc<Self>) {
+        let browser_can_force_dark = self
+            .desktop_file
+            .borrow()
+            .get_browser()
+            .is_some_and(|browser| browser.can_force_dark);
+        self.force_dark_row.set_sensitive(browser_can_force_dark);
+
+        if browser_can_force_dark {
+            self.force_dark_row.set_has_tooltip(false);
+        } else {
+            self.force_dark_row.set_active(false);
+            self.force_dark_row.set_has_tooltip(true);
+        }
+    }
+
     fn reset_change_icon_button(self: &Rc<Self>) {
         if self
             .desktop_file
@@ -945,25 +3060,65 @@ impl WebAppView {
     }
 
     fn is_dirty(self: &Rc<Self>) -> bool {
-        let desktop_file_borrow = self.desktop_file.borrow();
-        let name_saved = desktop_file_borrow.get_name().unwrap_or_default();
-        let url_saved = desktop_file_borrow.get_url().unwrap_or_default();
-        drop(desktop_file_borrow);
-
-        let is_dirty = name_saved != self.name_row.text() || url_saved != self.url_row.text();
+        let dirty_fields = self.get_dirty_fields();
+        let is_dirty = !self.get_dirty_rows().is_empty() || !dirty_fields.is_empty();
 
-        debug!(is_dirty = is_dirty, "Desktop file dirty validation");
+        debug!(
+            is_dirty = is_dirty,
+            ?dirty_fields,
+            "Desktop file dirty validation"
+        );
 
         is_dirty
     }
 
+    /// Widgets other than the entry rows, they write on change so a difference is a missed sync
+    fn get_dirty_fields(self: &Rc<Self>) -> Vec<Key> {
+        let widget_state = WidgetState {
+            is_isolated: self.isolate_row.is_active(),
+            is_maximized: self.maximize_row.is_active(),
+            browser_id: self
+                .browser_row
+                .selected_item()
+                .and_then(|selected_item| selected_item.downcast::<BoxedAnyObject>().ok())
+                .map(|browser_item_boxed| browser_item_boxed.borrow::<Rc<Browser>>().id.clone()),
+            has_icon_error: self.change_icon_button.has_css_class("error"),
+        };
+
+        widget_state.get_dirty_fields(&self.desktop_file.borrow())
+    }
+
     fn is_valid(self: &Rc<Self>) -> bool {
-        let is_valid = !self.is_dirty() && self.desktop_file.borrow().validate().is_ok();
+        let validation = self.desktop_file.borrow().validate_all();
+        // Marks only appear on save, a fixed field loses its mark right away
+        let errors = validation
+            .as_ref()
+            .err()
+            .map(DesktopFileError::get_validation_errors)
+            .unwrap_or_default();
+        self.reset_field_error_icons(&errors, false);
+
+        let is_valid = !self.is_dirty() && validation.is_ok();
         debug!(is_valid = is_valid, "Desktop file Validation");
 
         is_valid
     }
 
+    /// With `show_new` failing fields get a mark, otherwise only marks of fixed fields are removed
+    fn reset_field_error_icons(&self, errors: &[ValidationError], show_new: bool) {
+        for (fields, icon) in &self.field_error_icons {
+            let messages: Vec<&str> = errors
+                .iter()
+                .filter(|error| fields.contains(&error.field))
+                .map(|error| error.message.as_str())
+                .collect();
+            let is_visible = !messages.is_empty() && (show_new || icon.is_visible());
+
+            icon.set_visible(is_visible);
+            icon.set_tooltip_text(is_visible.then(|| messages.join(", ")).as_deref());
+        }
+    }
+
     fn on_validate(self: &Rc<Self>) {
         if *self.is_new.borrow() && self.is_valid() {
             self.save_button.set_sensitive(true);
@@ -979,6 +3134,11 @@ impl WebAppView {
         self.reset_reset_button();
         self.reset_browser_isolation();
         self.reset_browser_maximize();
+        self.reset_window_size();
+        self.reset_browser_fullscreen();
+        self.reset_minimal_ui();
+        self.reset_browser_private();
+        self.reset_browser_force_dark();
 
         let is_new = *self.is_new.borrow();
 
@@ -986,42 +3146,230 @@ impl WebAppView {
             self.on_validate();
         }
 
-        if !is_new && let Err(error) = self.desktop_file.borrow_mut().save() {
-            match error {
-                DesktopFileError::ValidationError(error) => {
-                    self.on_error(
-                        &format!("Failed to save: '{}'", &error.to_string()),
-                        Some(&error.clone().into()),
-                    );
-                }
-                DesktopFileError::Other(error) => {
-                    self.on_error("Error saving document", Some(&error));
-                }
-            }
+        if !is_new {
+            self.schedule_save();
         }
 
         self.reset_app_header();
     }
 
+    fn schedule_save(self: &Rc<Self>) {
+        self.cancel_pending_save();
+
+        let self_clone = self.clone();
+        let source_id = glib::timeout_add_local_once(Self::SAVE_DEBOUNCE, move || {
+            // The source is done, removing it again would fail
+            self_clone.pending_save.borrow_mut().take();
+            self_clone.save_desktop_file();
+        });
+        *self.pending_save.borrow_mut() = Some(source_id);
+    }
+
+    /// Saves a change that is still waiting, for when the file is needed right away
+    pub fn flush_pending_save(self: &Rc<Self>) {
+        let pending_save = self.pending_save.borrow_mut().take();
+        if let Some(source_id) = pending_save {
+            source_id.remove();
+            self.save_desktop_file();
+        }
+    }
+
+    fn cancel_pending_save(self: &Rc<Self>) {
+        let pending_save = self.pending_save.borrow_mut().take();
+        if let Some(source_id) = pending_save {
+            source_id.remove();
+        }
+    }
+
+    fn save_desktop_file(self: &Rc<Self>) {
+        let save_result = self.desktop_file.borrow_mut().save();
+        match save_result {
+            // Back to what is on disk, the file was valid when it was written
+            Ok(false) => self.reset_field_error_icons(&[], false),
+            Ok(true) => {
+                self.reset_field_error_icons(&[], false);
+                self.show_lint_warnings();
+            }
+            Err(error) => self.on_save_error(error),
+        }
+
+        // Saving renders the file again, which can change what differs from the original
+        self.reset_reset_button();
+        self.reset_developer_rows();
+    }
+
+    fn on_save_error(self: &Rc<Self>, error: DesktopFileError) {
+        // Saving stops at the first problem, the form shows all of them
+        let error = if matches!(error, DesktopFileError::ValidationError(_)) {
+            self.desktop_file
+                .borrow()
+                .validate_all()
+                .err()
+                .unwrap_or(error)
+        } else {
+            error
+        };
+
+        self.app.announce(&t!(
+            "web_apps.web_app_view.announce.save_failed",
+            reason = error.to_string()
+        ));
+        self.reset_field_error_icons(&error.get_validation_errors(), true);
+        match error {
+            DesktopFileError::ValidationError(_) | DesktopFileError::ValidationErrors(_) => {
+                self.on_error(
+                    &t!(
+                        "web_apps.web_app_view.error.save",
+                        error = error.to_string()
+                    ),
+                    Some(&anyhow!(error.to_string())),
+                );
+            }
+            DesktopFileError::Other(error) => {
+                self.on_error(&t!("web_apps.web_app_view.error.save_failed"), Some(&error));
+            }
+        }
+    }
+
+    fn show_lint_warnings(&self) {
+        let warnings: Vec<String> = self
+            .desktop_file
+            .borrow()
+            .lint()
+            .into_iter()
+            .filter(|issue| issue.level == LintLevel::Warning)
+            .map(|issue| issue.to_string())
+            .collect();
+
+        if !warnings.is_empty() && *self.lint_warnings.borrow() != warnings {
+            let toast = Toast::new(&t!(
+                "web_apps.web_app_view.toast.lint_warnings",
+                warnings = warnings.join(", ")
+            ));
+            toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+            self.toast_overlay.add_toast(toast);
+        }
+        *self.lint_warnings.borrow_mut() = warnings;
+    }
+
     fn on_new_desktop_file_save(self: &Rc<Self>) {
-        if let Err(error) = self.desktop_file.borrow().validate() {
+        let validation = self.desktop_file.borrow().validate_all();
+        if let Err(error) = validation {
+            self.reset_field_error_icons(&error.get_validation_errors(), true);
             match error {
-                DesktopFileError::ValidationError(error) => {
-                    self.on_error("Invalid input", Some(&error.into()));
+                DesktopFileError::ValidationError(_) | DesktopFileError::ValidationErrors(_) => {
+                    self.on_error(
+                        &t!(
+                            "web_apps.web_app_view.error.invalid_input",
+                            error = error.to_string()
+                        ),
+                        Some(&anyhow!(error.to_string())),
+                    );
                 }
                 DesktopFileError::Other(error) => {
-                    self.on_error("Error saving document", Some(&error));
+                    self.on_error(&t!("web_apps.web_app_view.error.save_failed"), Some(&error));
                 }
             }
             return;
         }
 
+        if let Some(detached_profile) = self.get_matching_detached_profile() {
+            self.show_reattach_profile_dialog(detached_profile);
+            return;
+        }
+
+        self.save_new_desktop_file();
+    }
+
+    fn save_new_desktop_file(self: &Rc<Self>) {
         *self.is_new.borrow_mut() = false;
         *self.desktop_file_original.borrow_mut() = self.desktop_file.borrow().clone();
 
         self.run_app_button.set_visible(true);
+        self.autostart_row.set_visible(true);
         self.save_button.set_visible(false);
+        self.reset_config_actions();
         self.on_desktop_file_change();
+        // The first save is not delayed, the handlers need the file
+        self.flush_pending_save();
+        // An explicit save, later edits are only installed when the editor is closed
+        self.app.install_pending_launchers();
+        self.offer_default_handlers();
+    }
+
+    /// A profile kept from a removed app for the same site and browser base
+    fn get_matching_detached_profile(self: &Rc<Self>) -> Option<DetachedProfile> {
+        let desktop_file_borrow = self.desktop_file.borrow();
+        if !desktop_file_borrow.get_isolated().unwrap_or(false) {
+            return None;
+        }
+
+        let browser = desktop_file_borrow.get_browser()?;
+        let domain = desktop_file_borrow.get_domain()?;
+
+        DetachedProfiles::new(&self.app.dirs)
+            .find(&domain, &browser.base.to_string())
+            .cloned()
+    }
+
+    fn show_reattach_profile_dialog(self: &Rc<Self>, detached_profile: DetachedProfile) {
+        let dialog_reuse = "reuse";
+        let dialog_new = "new";
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.web_app_view.reattach_dialog.title"))
+            .body(t!(
+                "web_apps.web_app_view.reattach_dialog.text",
+                name = detached_profile.name,
+                domain = detached_profile.domain
+            ))
+            .build();
+
+        dialog.add_response(dialog_new, &t!("web_apps.web_app_view.reattach_dialog.new"));
+        dialog.add_response(
+            dialog_reuse,
+            &t!("web_apps.web_app_view.reattach_dialog.reuse"),
+        );
+        dialog.set_response_appearance(dialog_reuse, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(dialog_reuse));
+        dialog.set_close_response(dialog_new);
+
+        let self_clone = self.clone();
+        dialog.connect_response(None, move |_, response| {
+            if response == dialog_reuse {
+                self_clone.reattach_profile(&detached_profile);
+            }
+            self_clone.save_new_desktop_file();
+        });
+
+        dialog.present(Some(&self.app.window.adw_window));
+    }
+
+    /// Use a kept profile as is, it is already seeded
+    fn reattach_profile(self: &Rc<Self>, detached_profile: &DetachedProfile) {
+        let mut desktop_file_borrow = self.desktop_file.borrow_mut();
+        let new_profile_path = desktop_file_borrow.get_profile_path().unwrap_or_default();
+
+        if new_profile_path != detached_profile.profile_path && new_profile_path.is_dir() {
+            debug!(
+                path = new_profile_path.display().to_string(),
+                "Deleting unused new profile"
+            );
+            let _ = fs::remove_dir_all(&new_profile_path);
+        }
+
+        debug!(
+            path = detached_profile.profile_path.display().to_string(),
+            "Reattaching kept profile"
+        );
+        desktop_file_borrow.set_profile_path(&detached_profile.profile_path);
+        drop(desktop_file_borrow);
+
+        let mut detached_profiles = DetachedProfiles::new(&self.app.dirs);
+        detached_profiles.remove(&detached_profile.profile_path);
+        if let Err(error) = detached_profiles.save() {
+            error!("Failed to update detached profiles: {error:?}");
+        }
     }
 
     fn on_isolation_change(self: &Rc<Self>) {
@@ -1035,7 +3383,7 @@ impl WebAppView {
                 Err(error) => {
                     drop(desktop_file_borrow);
                     self.reset_desktop_file();
-                    self.on_error("Could not set isolation", Some(&error));
+                    self.on_error(&t!("web_apps.web_app_view.error.isolation"), Some(&error));
                     return;
                 }
                 Ok(profile) => profile,
@@ -1047,12 +3395,76 @@ impl WebAppView {
         if old_profile_path != new_profile_path && Path::new(&old_profile_path).is_dir() {
             debug!(
                 path = old_profile_path.display().to_string(),
-                "Deleting profile"
+                "Deleting profile when the view closes"
             );
-            let _ = fs::remove_dir_all(old_profile_path);
+            self.pending_profile_deletions
+                .borrow_mut()
+                .push(old_profile_path);
         }
 
         desktop_file_borrow.set_profile_path(&new_profile_path);
+        drop(desktop_file_borrow);
+
+        self.reset_permission_banner();
+    }
+
+    /// Launching fails silently without the permissions, so tell before saving
+    fn reset_permission_banner(self: &Rc<Self>) {
+        let browser = self.desktop_file.borrow().get_browser();
+        let missing_command =
+            browser
+                .filter(|_| self.isolate_row.is_active())
+                .and_then(|browser| match browser.check_isolation_permissions() {
+                    Ok(()) => None,
+                    Err(missing_permissions) => {
+                        browser.get_permission_override_command(&missing_permissions)
+                    }
+                });
+
+        *self.is_permission_command_copied.borrow_mut() = false;
+        self.permission_banner
+            .set_button_label(Some(&t!("web_apps.web_app_view.permission_banner.copy")));
+
+        match &missing_command {
+            Some(command) => {
+                self.permission_banner.set_title(&t!(
+                    "web_apps.web_app_view.permission_banner.title",
+                    command = command
+                ));
+                self.permission_banner.set_revealed(true);
+            }
+            None => self.permission_banner.set_revealed(false),
+        }
+        *self.permission_command.borrow_mut() = missing_command;
+    }
+
+    fn connect_permission_banner(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.permission_banner
+            .connect_button_clicked(move |banner| {
+                if *self_clone.is_permission_command_copied.borrow() {
+                    self_clone.reset_permission_banner();
+                    if self_clone.permission_banner.is_revealed() {
+                        self_clone.on_error(
+                            &t!("web_apps.web_app_view.permission_banner.still_missing"),
+                            None,
+                        );
+                    }
+                    return;
+                }
+
+                let Some(command) = self_clone.permission_command.borrow().clone() else {
+                    return;
+                };
+                banner.clipboard().set_text(&command);
+                *self_clone.is_permission_command_copied.borrow_mut() = true;
+                banner.set_button_label(Some(&t!("web_apps.web_app_view.permission_banner.check")));
+
+                let toast = Toast::new(&t!("web_apps.web_app_view.permission_banner.copied"));
+                toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+                self_clone.toast_overlay.add_toast(toast);
+            });
     }
 
     fn on_error(self: &Rc<Self>, message: &str, error: Option<&anyhow::Error>) {
@@ -1066,3 +3478,134 @@ impl WebAppView {
         self.toast_overlay.add_toast(toast);
     }
 }
+
+/// What the switches, the browser row and the icon button show
+struct WidgetState {
+    is_isolated: bool,
+    is_maximized: bool,
+    /// `None` while no browser is selected
+    browser_id: Option<String>,
+    has_icon_error: bool,
+}
+impl WidgetState {
+    fn get_dirty_fields(&self, desktop_file: &DesktopFile) -> Vec<Key> {
+        let has_icon = desktop_file
+            .get_icon_path()
+            .is_some_and(|icon_path| !icon_path.as_os_str().is_empty());
+
+        [
+            (
+                Key::Isolate,
+                self.is_isolated == desktop_file.get_isolated().unwrap_or(false),
+            ),
+            (
+                Key::Maximize,
+                self.is_maximized == desktop_file.get_maximized().unwrap_or(false),
+            ),
+            (
+                Key::BrowserId,
+                self.browser_id.is_none()
+                    || self.browser_id
+                        == desktop_file.get_browser().map(|browser| browser.id.clone()),
+            ),
+            (Key::Icon, self.has_icon_error != has_icon),
+        ]
+        .into_iter()
+        .filter(|(_, is_synced)| !is_synced)
+        .map(|(key, _)| key)
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{app_dirs::AppDirs, browsers::BrowserConfigs, config};
+
+    fn build_desktop_file() -> DesktopFile {
+        config::init();
+        let app_dirs = Rc::new(AppDirs::default());
+        let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+        let mut desktop_file = DesktopFile::from_string(
+            Path::new("/tmp/mail.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Mail\n",
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+        desktop_file.set_isolated(true);
+        desktop_file.set_maximized(false);
+
+        desktop_file
+    }
+
+    fn build_synced_state() -> WidgetState {
+        WidgetState {
+            is_isolated: true,
+            is_maximized: false,
+            browser_id: None,
+            has_icon_error: true,
+        }
+    }
+
+    #[test]
+    fn synced_widgets_are_not_dirty() {
+        let desktop_file = build_desktop_file();
+
+        assert!(
+            build_synced_state()
+                .get_dirty_fields(&desktop_file)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn changed_switches_are_dirty() {
+        let desktop_file = build_desktop_file();
+        let widget_state = WidgetState {
+            is_isolated: false,
+            is_maximized: true,
+            ..build_synced_state()
+        };
+
+        assert_eq!(
+            widget_state.get_dirty_fields(&desktop_file),
+            vec![Key::Isolate, Key::Maximize]
+        );
+    }
+
+    #[test]
+    fn browser_that_is_not_in_the_file_is_dirty() {
+        let desktop_file = build_desktop_file();
+        let widget_state = WidgetState {
+            browser_id: Some("firefox".to_string()),
+            ..build_synced_state()
+        };
+
+        assert_eq!(
+            widget_state.get_dirty_fields(&desktop_file),
+            vec![Key::BrowserId]
+        );
+    }
+
+    #[test]
+    fn icon_button_must_match_the_icon_of_the_file() {
+        let mut desktop_file = build_desktop_file();
+        let widget_state = WidgetState {
+            has_icon_error: false,
+            ..build_synced_state()
+        };
+        assert_eq!(
+            widget_state.get_dirty_fields(&desktop_file),
+            vec![Key::Icon]
+        );
+
+        desktop_file.set_icon_path(Path::new("/tmp/mail.png"));
+
+        assert!(widget_state.get_dirty_fields(&desktop_file).is_empty());
+        assert_eq!(
+            build_synced_state().get_dirty_fields(&desktop_file),
+            vec![Key::Icon]
+        );
+    }
+}
@@ -1,51 +1,72 @@
+mod ico;
 mod icon;
+mod icon_cache;
+mod icon_edit;
 mod icon_fetcher;
 
 use crate::application::App;
 use anyhow::{Context, Result, bail};
-use common::desktop_file::DesktopFile;
+use common::{app_dirs::AppDirs, desktop_file::DesktopFile, fetch::Fetch, letter_icon::LetterIcon};
 use gtk::{
-    self, Align, Button, ContentFit, FileDialog, FileFilter, FlowBox, Label, Orientation, Picture,
-    SelectionMode,
+    self, Align, Button, ContentFit, DropTarget, FileDialog, FileFilter, FlowBox, Image, Label,
+    Orientation, Overlay, Picture, SelectionMode, StringList,
+    gdk::{self, DragAction, prelude::TextureExt},
     gdk_pixbuf::{Pixbuf, PixbufFormat},
-    gio::prelude::FileExt,
-    glib::GString,
+    gio::{
+        self,
+        prelude::{FileExt, FileExtManual},
+    },
+    glib::{GString, types::StaticType},
     prelude::{BoxExt, ButtonExt, FlowBoxChildExt, ListBoxRowExt, WidgetExt},
 };
-use icon::Icon;
-use icon_fetcher::IconFetcher;
+use icon::{Icon, IconSource};
+use icon_cache::IconCache;
+use icon_edit::{IconEdit, Mask};
+use icon_fetcher::{IconFetcher, ManifestMetadata};
 use libadwaita::{
-    AlertDialog, ButtonContent, ButtonRow, PreferencesGroup, PreferencesPage, PreferencesRow,
-    ResponseAppearance, Spinner, StatusPage,
-    gio::Cancellable,
+    AlertDialog, ButtonContent, ButtonRow, ComboRow, PreferencesGroup, PreferencesPage,
+    PreferencesRow, ResponseAppearance, SpinRow, Spinner, StatusPage, SwitchRow, Toast,
+    ToastOverlay,
+    gio::{Cancellable, prelude::CancellableExt},
     glib,
-    prelude::{AdwDialogExt, AlertDialogExt, PreferencesGroupExt, PreferencesPageExt},
+    prelude::{
+        AdwDialogExt, AlertDialogExt, ComboRowExt, PreferencesGroupExt, PreferencesPageExt,
+        PreferencesRowExt,
+    },
 };
 use std::{
     cell::RefCell,
     cmp::Reverse,
     collections::HashMap,
     fs, mem,
+    path::Path,
     rc::Rc,
     time::{Duration, SystemTime},
 };
 use tracing::{debug, error};
+use url::Url;
 
 pub struct IconPicker {
     init: RefCell<bool>,
+    is_discarded: RefCell<bool>,
     fetched_icons_ts: RefCell<SystemTime>,
     prefs_page: PreferencesPage,
     app: Rc<App>,
     desktop_file: Rc<RefCell<DesktopFile>>,
     icons: Rc<RefCell<HashMap<String, Rc<Icon>>>>,
     icons_ordered: RefCell<Vec<(String, Rc<Icon>)>>,
+    manifest_metadata: RefCell<Option<ManifestMetadata>>,
     pref_row_icons: PreferencesRow,
     pref_row_icons_fail: PreferencesRow,
     pref_row_icons_flow_box: RefCell<Option<FlowBox>>,
     pref_group_icons_reset_button: Button,
+    pref_group_icons_edit_button: Button,
     pref_group_icons_add_button_row: ButtonRow,
     content_box: gtk::Box,
+    toast_overlay: ToastOverlay,
     spinner: Spinner,
+    dropped_images_count: RefCell<u32>,
+    fetch_cancellable: RefCell<Cancellable>,
 }
 impl IconPicker {
     pub const DIALOG_SAVE: &str = "save";
@@ -53,6 +74,10 @@ impl IconPicker {
     /// In seconds
     pub const ONLINE_FETCH_THROTTLE: u64 = 20;
     pub const CURRENT_ICON_KEY: &str = "current";
+    pub const EDIT_DIALOG_APPLY: &str = "apply";
+    const DROPPED_IMAGE_KEY: &str = "dropped";
+    const GENERATED_ICON_KEY: &str = "generated";
+    const TOAST_MESSAGE_TIMEOUT: u32 = 4;
 
     pub fn new(app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>) -> Rc<Self> {
         let icons = Rc::new(RefCell::new(HashMap::new()));
@@ -62,7 +87,8 @@ impl IconPicker {
         let prefs_page = PreferencesPage::new();
         let pref_row_icons = Self::build_pref_row_icons();
         let pref_row_icons_fail = Self::build_pref_row_icons_fail();
-        let (pref_group_icons, pref_group_icons_reset_button) = Self::build_pref_group_icons();
+        let (pref_group_icons, pref_group_icons_reset_button, pref_group_icons_edit_button) =
+            Self::build_pref_group_icons();
         let pref_group_icons_add_button_row = Self::build_pref_row_add_icon();
 
         prefs_page.add(&pref_group_icons);
@@ -72,6 +98,8 @@ impl IconPicker {
 
         content_box.append(&spinner);
         content_box.append(&prefs_page);
+        let toast_overlay = ToastOverlay::new();
+        toast_overlay.set_child(Some(&content_box));
 
         let fetched_icons_ts = RefCell::new(
             SystemTime::now()
@@ -81,19 +109,25 @@ impl IconPicker {
 
         Rc::new(Self {
             init: RefCell::new(false),
+            is_discarded: RefCell::new(false),
             fetched_icons_ts,
             prefs_page,
             app: app.clone(),
             desktop_file: desktop_file.clone(),
             icons,
             icons_ordered,
+            manifest_metadata: RefCell::new(None),
             pref_row_icons,
             pref_row_icons_fail,
             pref_row_icons_flow_box: RefCell::new(None),
             pref_group_icons_reset_button,
+            pref_group_icons_edit_button,
             pref_group_icons_add_button_row,
             content_box,
+            toast_overlay,
             spinner,
+            dropped_images_count: RefCell::new(0),
+            fetch_cancellable: RefCell::new(Cancellable::new()),
         })
     }
 
@@ -111,12 +145,19 @@ impl IconPicker {
                 self_clone.load_icons(true);
             });
 
+        let self_clone = self.clone();
+        self.pref_group_icons_edit_button.connect_clicked(move |_| {
+            self_clone.show_edit_dialog();
+        });
+
         let self_clone = self.clone();
         self.pref_group_icons_add_button_row
             .connect_activated(move |_| {
                 self_clone.load_icon_file_picker();
             });
 
+        self.connect_drop_target();
+
         *is_init = true;
     }
 
@@ -134,7 +175,7 @@ impl IconPicker {
         let dialog = AlertDialog::builder()
             .heading(t!("web_apps.web_app_view.icon.dialog.title"))
             .width_request(500)
-            .extra_child(&self.content_box)
+            .extra_child(&self.toast_overlay)
             .build();
         dialog.add_response(
             Self::DIALOG_CANCEL,
@@ -174,8 +215,17 @@ impl IconPicker {
         dialog
     }
 
+    /// Falls back to a generated letter icon, so a new web app always gets an icon
     pub async fn save_first_icon_found(self: &Rc<Self>) -> Result<()> {
-        self.set_online_icons(false).await?;
+        if let Err(error) = self.set_online_icons(false).await {
+            if self.is_fetch_cancelled() {
+                return Err(error);
+            }
+            error!("{error:?}");
+        }
+        if self.icons.borrow().is_empty() {
+            self.set_generated_icon()?;
+        }
         self.set_icons_ordered();
         let icons_ordered_borrow = self.icons_ordered.borrow();
 
@@ -187,7 +237,94 @@ impl IconPicker {
         Ok(())
     }
 
+    /// Like `save_first_icon_found` without a picker, for the command line
+    pub async fn save_first_online_icon(
+        fetch: &Rc<Fetch>,
+        app_dirs: &AppDirs,
+        desktop_file: &mut DesktopFile,
+    ) -> Result<()> {
+        let url = desktop_file.get_url().context("No url on DesktopFile")?;
+
+        let icon = match Self::fetch_best_online_icon(fetch, &url).await {
+            Ok(icon) => icon,
+            Err(error) => {
+                error!("{error:?}");
+                Rc::new(Self::build_generated_icon(desktop_file)?)
+            }
+        };
+
+        Self::save_to_fs(&icon, &app_dirs.app_data_icons, desktop_file)
+    }
+
+    /// Like `save_first_online_icon` without the generated fallback, a site that is offline now
+    /// can have its icon back later
+    pub async fn save_best_online_icon(
+        fetch: &Rc<Fetch>,
+        app_dirs: &AppDirs,
+        desktop_file: &mut DesktopFile,
+    ) -> Result<()> {
+        let url = desktop_file.get_url().context("No url on DesktopFile")?;
+        let icon = Self::fetch_best_online_icon(fetch, &url).await?;
+
+        Self::save_to_fs(&icon, &app_dirs.app_data_icons, desktop_file)
+    }
+
+    async fn fetch_best_online_icon(fetch: &Rc<Fetch>, url: &str) -> Result<Rc<Icon>> {
+        let mut icon_fetcher = IconFetcher::new(fetch, url, &Cancellable::new())?;
+
+        let mut icons: Vec<Rc<Icon>> = icon_fetcher
+            .get_online_icons()
+            .await?
+            .into_iter()
+            .map(|(_url, icon)| icon)
+            .collect();
+        icons.sort_by_key(|icon| (icon.get_rank(), Reverse(icon.pixbuf.byte_length())));
+
+        icons.into_iter().next().context("No icons found")
+    }
+
+    /// Manifest metadata found during the last online fetch
+    pub fn get_manifest_metadata(&self) -> Option<ManifestMetadata> {
+        self.manifest_metadata.borrow().clone()
+    }
+
+    /// Stop this picker from writing icons, pending fetches will not save anymore
+    pub fn discard(&self) {
+        debug!("Discarding icon picker");
+        *self.is_discarded.borrow_mut() = true;
+        self.cancel_fetch();
+    }
+
+    /// Stops the requests of the running online fetch, nothing of it is added to the icons
+    pub fn cancel_fetch(&self) {
+        debug!("Cancelling online icon fetch");
+        self.fetch_cancellable.borrow().cancel();
+    }
+
+    pub fn is_fetch_cancelled(&self) -> bool {
+        self.fetch_cancellable.borrow().is_cancelled()
+    }
+
+    /// Only one online fetch runs at a time, a new one cancels the previous one
+    fn start_fetch(&self) -> Cancellable {
+        let cancellable = Cancellable::new();
+        self.fetch_cancellable.replace(cancellable.clone()).cancel();
+        cancellable
+    }
+
     fn get_selected_icon(self: &Rc<Self>) -> Result<Rc<Icon>> {
+        let url_or_path = self.get_selected_key()?;
+
+        let icon = self
+            .icons
+            .borrow()
+            .get(&url_or_path)
+            .context("Cannot find icon in HashMap???")?
+            .clone();
+        Ok(icon)
+    }
+
+    fn get_selected_key(self: &Rc<Self>) -> Result<String> {
         let url_or_path = self
             .clone()
             .pref_row_icons_flow_box
@@ -202,16 +339,12 @@ impl IconPicker {
             .widget_name()
             .to_string();
 
-        let icon = self
-            .icons
-            .borrow()
-            .get(&url_or_path)
-            .context("Cannot find icon in HashMap???")?
-            .clone();
-        Ok(icon)
+        Ok(url_or_path)
     }
 
     fn set_icons_loading(&self) {
+        self.app
+            .announce(&t!("web_apps.web_app_view.icon.announce.loading"));
         self.prefs_page.set_visible(false);
         self.spinner.set_visible(true);
         self.pref_row_icons.set_visible(false);
@@ -219,6 +352,8 @@ impl IconPicker {
     }
 
     fn set_no_icons(&self) {
+        self.app
+            .announce(&t!("web_apps.web_app_view.icon.announce.no_icons"));
         self.prefs_page.set_visible(true);
         self.spinner.set_visible(false);
         self.pref_row_icons.set_visible(false);
@@ -226,6 +361,8 @@ impl IconPicker {
     }
 
     fn set_show_icons(&self) {
+        self.app
+            .announce(&t!("web_apps.web_app_view.icon.announce.loaded"));
         self.prefs_page.set_visible(true);
         self.spinner.set_visible(false);
         self.pref_row_icons.set_visible(true);
@@ -257,6 +394,11 @@ impl IconPicker {
 
             if let Err(error) = self_clone.set_online_icons(force).await {
                 error!("{error:?}");
+                if !self_clone.is_fetch_cancelled()
+                    && let Err(error) = self_clone.set_generated_icon()
+                {
+                    error!("{error:?}");
+                }
             }
             if let Err(error) = self_clone.set_local_icon() {
                 error!("{error:?}");
@@ -287,13 +429,37 @@ impl IconPicker {
 
             let frame = gtk::Box::new(Orientation::Vertical, 0);
             frame.set_widget_name(key);
+            frame.set_tooltip_text(Some(&Self::get_source_text(key, icon)));
             let picture = Picture::new();
-            picture.set_pixbuf(Some(&icon.pixbuf));
+            picture.set_paintable(Some(&gdk::Texture::for_pixbuf(&icon.pixbuf)));
             picture.set_content_fit(ContentFit::ScaleDown);
-            frame.append(&picture);
-
-            let size_text = format!("{} x {}", icon.pixbuf.width(), icon.pixbuf.height());
+            let overlay = Overlay::builder().child(&picture).build();
+            if icon.source == IconSource::Current {
+                overlay.add_overlay(&Self::build_current_icon_badge());
+            }
+            frame.append(&overlay);
+
+            let size = format!("{} x {}", icon.pixbuf.width(), icon.pixbuf.height());
+            let size_text = if icon.is_vector() {
+                t!("web_apps.web_app_view.icon.scalable").to_string()
+            } else if icon.ico_frame_sizes.is_empty() {
+                size
+            } else {
+                t!("web_apps.web_app_view.icon.from_ico", size = size).to_string()
+            };
             let label = Label::builder().label(&size_text).build();
+            if !icon.ico_frame_sizes.is_empty() {
+                let frame_sizes = icon
+                    .ico_frame_sizes
+                    .iter()
+                    .map(|(width, height)| format!("{width} x {height}"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                label.set_tooltip_text(Some(&t!(
+                    "web_apps.web_app_view.icon.ico_frames",
+                    sizes = frame_sizes
+                )));
+            }
             frame.append(&label);
 
             flow_box.insert(&frame, -1);
@@ -314,22 +480,88 @@ impl IconPicker {
         }
     }
 
+    /// Where the icon came from, with the file name of the url or path
+    fn get_source_text(key: &str, icon: &Icon) -> String {
+        let source = match icon.source {
+            IconSource::Current => t!("web_apps.web_app_view.icon.source.current"),
+            IconSource::LocalFile => t!("web_apps.web_app_view.icon.source.local_file"),
+            IconSource::Favicon => t!("web_apps.web_app_view.icon.source.favicon"),
+            IconSource::Manifest => t!("web_apps.web_app_view.icon.source.manifest"),
+            IconSource::TouchIcon => t!("web_apps.web_app_view.icon.source.touch_icon"),
+            IconSource::RootPath => t!("web_apps.web_app_view.icon.source.root_path"),
+            IconSource::OpenGraph => t!("web_apps.web_app_view.icon.source.open_graph"),
+            IconSource::Generated => t!("web_apps.web_app_view.icon.source.generated"),
+        };
+
+        let file_name = if let Ok(url) = Url::parse(key) {
+            url.path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|segment| !segment.is_empty())
+                .map(ToString::to_string)
+                .or_else(|| url.host_str().map(ToString::to_string))
+        } else {
+            let path = Path::new(key);
+            path.file_name()
+                .filter(|_| path.is_absolute())
+                .map(|file_name| file_name.to_string_lossy().to_string())
+        };
+
+        match file_name {
+            Some(file_name) => format!("{source} · {file_name}"),
+            None => source.to_string(),
+        }
+    }
+
     async fn set_online_icons(self: &Rc<Self>, force: bool) -> Result<()> {
         if !force && self.should_throttle() {
             return Ok(());
         }
 
-        debug!("Fetching online icons");
-
         let Some(url) = self.desktop_file.borrow().get_url() else {
             bail!("No url on desktop file")
         };
-        let Ok(mut icon_fetcher) = IconFetcher::new(&self.app, &url) else {
+        let icon_cache = IconCache::new(&self.app);
+        let cache_url = Url::parse(&url).ok();
+
+        // Reset skips the cache
+        if !force
+            && let Some(cached_icons) = cache_url
+                .as_ref()
+                .and_then(|cache_url| icon_cache.load(cache_url))
+        {
+            if let Some(manifest_metadata) = cached_icons.manifest_metadata {
+                *self.manifest_metadata.borrow_mut() = Some(manifest_metadata);
+            }
+            self.icons.borrow_mut().extend(cached_icons.icons);
+            return Ok(());
+        }
+
+        debug!("Fetching online icons");
+
+        let cancellable = self.start_fetch();
+        let Ok(mut icon_fetcher) = IconFetcher::new(&self.app.fetch, &url, &cancellable) else {
             bail!("Invalid url")
         };
-        let Ok(icons) = icon_fetcher.get_online_icons().await else {
+        let icons = icon_fetcher.get_online_icons().await;
+        if cancellable.is_cancelled() {
+            bail!("Fetching online icons cancelled")
+        }
+        let Ok(icons) = icons else {
             bail!("Failed to get online icons")
         };
+        if let Some(manifest_metadata) = icon_fetcher.get_manifest_metadata() {
+            *self.manifest_metadata.borrow_mut() = Some(manifest_metadata.clone());
+        }
+        if let Some(cache_url) = &cache_url
+            && !icon_fetcher.get_fetched_images().is_empty()
+            && let Err(error) = icon_cache.store(
+                cache_url,
+                icon_fetcher.get_fetched_images(),
+                icon_fetcher.get_manifest_metadata(),
+            )
+        {
+            error!("Failed to cache icons: {error:?}");
+        }
 
         let mut self_icons_borrow = self.icons.borrow_mut();
 
@@ -348,8 +580,10 @@ impl IconPicker {
         let Some(current_icon_path) = self.desktop_file.borrow().get_icon_path() else {
             bail!("No icon saved")
         };
-        let current_icon =
-            Rc::new(Icon::from_path(&current_icon_path).context("Could not load current image")?);
+        let mut current_icon =
+            Icon::from_path(&current_icon_path).context("Could not load current image")?;
+        current_icon.source = IconSource::Current;
+        let current_icon = Rc::new(current_icon);
         let mut icons_ordered_borrow = self.icons_ordered.borrow_mut();
 
         let is_new_icon = self
@@ -373,11 +607,35 @@ impl IconPicker {
         Ok(())
     }
 
+    /// Letter tile of the app name, any icon found later still goes before it
+    fn set_generated_icon(&self) -> Result<()> {
+        let icon = Self::build_generated_icon(&self.desktop_file.borrow())?;
+
+        self.icons
+            .borrow_mut()
+            .insert(Self::GENERATED_ICON_KEY.into(), Rc::new(icon));
+        Ok(())
+    }
+
+    fn build_generated_icon(desktop_file: &DesktopFile) -> Result<Icon> {
+        let letter_icon = LetterIcon::new(
+            &desktop_file.get_name().unwrap_or_default(),
+            &desktop_file.get_domain().unwrap_or_default(),
+        );
+
+        let mut icon = Icon::from_pixbuf(letter_icon.to_pixbuf()?);
+        icon.source = IconSource::Generated;
+        debug!(letters = letter_icon.letters, "Generated letter icon");
+
+        Ok(icon)
+    }
+
     fn set_icons_ordered(&self) {
         let mut self_icons_ordered_borrow = self.icons_ordered.borrow_mut();
 
         *self_icons_ordered_borrow = self.icons.borrow().clone().into_iter().collect();
-        self_icons_ordered_borrow.sort_by_key(|(_, a)| Reverse(a.pixbuf.byte_length()));
+        self_icons_ordered_borrow
+            .sort_by_key(|(_, a)| (a.get_rank(), Reverse(a.pixbuf.byte_length())));
     }
 
     fn should_throttle(self: &Rc<Self>) -> bool {
@@ -427,66 +685,300 @@ impl IconPicker {
                     error!("Failed to get file");
                     return;
                 };
-                let Some(path) = file.path() else {
-                    error!("Failed to get path");
-                    return;
-                };
-                let filename = file.parse_name().to_string();
 
-                debug!("Loading image: '{filename}'");
+                self_clone.add_icon_files(&[file]);
+            },
+        );
+    }
 
-                let icon = match Icon::from_path(&path) {
-                    Ok(icon) => icon,
-                    Err(error) => {
-                        error!("Failed to load image: '{error:?}'");
-                        return;
-                    }
-                };
+    /// Files from the file picker or dropped on the dialog, the first one is selected
+    fn add_icon_files(self: &Rc<Self>, files: &[gio::File]) {
+        let mut first_filename = None;
 
-                self_clone
-                    .icons
-                    .borrow_mut()
-                    .insert(filename.clone(), Rc::new(icon));
+        for file in files {
+            let filename = file.parse_name().to_string();
+            debug!("Loading image: '{filename}'");
 
-                self_clone.set_icons_ordered();
-                self_clone.reload_icon_flowbox();
-                self_clone.select_icon(&filename);
+            match Self::load_icon_file(file) {
+                Ok(icon) => {
+                    self.icons
+                        .borrow_mut()
+                        .insert(filename.clone(), Rc::new(icon));
+                    first_filename.get_or_insert(filename);
+                }
+                Err(error) => {
+                    error!("Failed to load image: '{error:?}'");
+                    let name = file
+                        .basename()
+                        .map(|basename| basename.to_string_lossy().to_string())
+                        .unwrap_or(filename);
+                    self.show_toast(&t!("web_apps.web_app_view.icon.unsupported", name = name));
+                }
+            }
+        }
+
+        if let Some(filename) = first_filename {
+            self.set_icons_ordered();
+            self.reload_icon_flowbox();
+            self.select_icon(&filename);
+        }
+    }
+
+    /// Files without a local path, like remote files, are read into memory
+    fn load_icon_file(file: &gio::File) -> Result<Icon> {
+        if let Some(path) = file.path() {
+            return Icon::from_path(&path);
+        }
+
+        let (bytes, _etag) = file
+            .load_contents(None::<&Cancellable>)
+            .context("Failed to read image")?;
+        Icon::from_bytes(&bytes.to_vec(), None)
+    }
+
+    fn connect_drop_target(self: &Rc<Self>) {
+        let drop_target = DropTarget::new(glib::Type::INVALID, DragAction::COPY);
+        drop_target.set_types(&[gdk::FileList::static_type(), gdk::Texture::static_type()]);
+
+        let self_clone = self.clone();
+        drop_target.connect_drop(move |_, value, _, _| {
+            if let Ok(file_list) = value.get::<gdk::FileList>() {
+                self_clone.add_icon_files(&file_list.files());
+                return true;
+            }
+
+            if let Ok(texture) = value.get::<gdk::Texture>() {
+                self_clone.add_icon_texture(&texture);
+                return true;
+            }
+
+            false
+        });
+
+        self.content_box.add_controller(drop_target);
+    }
+
+    /// Images dragged from a browser or image viewer without a file
+    fn add_icon_texture(self: &Rc<Self>, texture: &gdk::Texture) {
+        let bytes = texture.save_to_png_bytes();
+        let icon = match Icon::from_bytes(&bytes.to_vec(), Some("image/png".to_string())) {
+            Ok(icon) => icon,
+            Err(error) => {
+                error!("Failed to load dropped image: '{error:?}'");
+                self.show_toast(&t!("web_apps.web_app_view.icon.unsupported_dropped"));
+                return;
+            }
+        };
+
+        let mut count_borrow = self.dropped_images_count.borrow_mut();
+        *count_borrow += 1;
+        let key = format!("{}-{}", Self::DROPPED_IMAGE_KEY, *count_borrow);
+        drop(count_borrow);
+
+        self.icons.borrow_mut().insert(key.clone(), Rc::new(icon));
+        self.set_icons_ordered();
+        self.reload_icon_flowbox();
+        self.select_icon(&key);
+    }
+
+    /// Edits the selected icon, the edited icon replaces it in the picker
+    fn show_edit_dialog(self: &Rc<Self>) {
+        let key = match self.get_selected_key() {
+            Ok(key) => key,
+            Err(error) => {
+                error!("No icon to edit: {error:?}");
+                return;
+            }
+        };
+        let Some(icon) = self.icons.borrow().get(&key).cloned() else {
+            error!("Cannot find icon to edit: '{key}'");
+            return;
+        };
+
+        let picture = Picture::builder()
+            .content_fit(ContentFit::ScaleDown)
+            .height_request(128)
+            .build();
+        picture.set_paintable(Some(&gdk::Texture::for_pixbuf(&icon.pixbuf)));
+
+        let crop_row = SwitchRow::builder()
+            .title(t!("web_apps.web_app_view.icon.edit.crop.title"))
+            .subtitle(t!("web_apps.web_app_view.icon.edit.crop.subtitle"))
+            .build();
+        let padding_row = SpinRow::with_range(0.0, f64::from(IconEdit::PADDING_PERCENT_MAX), 1.0);
+        padding_row.set_title(&t!("web_apps.web_app_view.icon.edit.padding"));
+        let mask_names: Vec<String> = Mask::ALL
+            .iter()
+            .map(|mask| Self::mask_to_string_ui(*mask))
+            .collect();
+        let mask_names: Vec<&str> = mask_names.iter().map(String::as_str).collect();
+        let mask_row = ComboRow::builder()
+            .title(t!("web_apps.web_app_view.icon.edit.mask.title"))
+            .model(&StringList::new(&mask_names))
+            .build();
+
+        let pref_group = PreferencesGroup::new();
+        pref_group.add(&crop_row);
+        pref_group.add(&padding_row);
+        pref_group.add(&mask_row);
+
+        let content_box = gtk::Box::new(Orientation::Vertical, 12);
+        content_box.append(&picture);
+        content_box.append(&pref_group);
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.web_app_view.icon.edit.title"))
+            .extra_child(&content_box)
+            .build();
+        dialog.add_response(
+            Self::DIALOG_CANCEL,
+            &t!("web_apps.web_app_view.icon.edit.cancel"),
+        );
+        dialog.add_response(
+            Self::EDIT_DIALOG_APPLY,
+            &t!("web_apps.web_app_view.icon.edit.apply"),
+        );
+        dialog.set_response_appearance(Self::EDIT_DIALOG_APPLY, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(Self::EDIT_DIALOG_APPLY));
+        dialog.set_close_response(Self::DIALOG_CANCEL);
+
+        let get_icon_edit = {
+            let crop_row = crop_row.clone();
+            let padding_row = padding_row.clone();
+            let mask_row = mask_row.clone();
+            move || IconEdit {
+                crop_to_content: crop_row.is_active(),
+                padding_percent: Self::get_padding_value(&padding_row),
+                mask: usize::try_from(mask_row.selected())
+                    .ok()
+                    .and_then(|index| Mask::ALL.get(index).copied())
+                    .unwrap_or_default(),
+            }
+        };
+        let update_preview = {
+            let icon = icon.clone();
+            let get_icon_edit = get_icon_edit.clone();
+            Rc::new(move || match get_icon_edit().apply(&icon.pixbuf) {
+                Ok(pixbuf) => picture.set_paintable(Some(&gdk::Texture::for_pixbuf(&pixbuf))),
+                Err(error) => error!("Failed to edit icon: {error:?}"),
+            })
+        };
+
+        let update_preview_clone = update_preview.clone();
+        crop_row.connect_active_notify(move |_| update_preview_clone());
+        let update_preview_clone = update_preview.clone();
+        padding_row.connect_value_notify(move |_| update_preview_clone());
+        mask_row.connect_selected_notify(move |_| update_preview());
+
+        let self_clone = self.clone();
+        dialog.connect_response(
+            Some(Self::EDIT_DIALOG_APPLY),
+            move |_, _| match get_icon_edit().apply(&icon.pixbuf) {
+                Ok(pixbuf) => {
+                    let mut edited_icon = Icon::from_pixbuf(pixbuf);
+                    edited_icon.source = icon.source;
+                    self_clone
+                        .icons
+                        .borrow_mut()
+                        .insert(key.clone(), Rc::new(edited_icon));
+                    self_clone.set_icons_ordered();
+                    self_clone.reload_icon_flowbox();
+                    self_clone.select_icon(&key);
+                }
+                Err(error) => {
+                    error!("Failed to edit icon: {error:?}");
+                    self_clone.show_toast(&t!("web_apps.web_app_view.icon.edit.failed"));
+                }
             },
         );
+
+        dialog.present(Some(&self.app.window.adw_window));
+    }
+
+    fn mask_to_string_ui(mask: Mask) -> String {
+        match mask {
+            Mask::None => t!("web_apps.web_app_view.icon.edit.mask.none"),
+            Mask::Rounded => t!("web_apps.web_app_view.icon.edit.mask.rounded"),
+            Mask::Circle => t!("web_apps.web_app_view.icon.edit.mask.circle"),
+        }
+        .to_string()
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn get_padding_value(spin_row: &SpinRow) -> u32 {
+        spin_row.value().round().max(0.0) as u32
+    }
+
+    fn show_toast(&self, message: &str) {
+        let toast = Toast::new(message);
+        toast.set_timeout(Self::TOAST_MESSAGE_TIMEOUT);
+        self.toast_overlay.add_toast(toast);
     }
 
     fn save(self: &Rc<Self>, icon: &Rc<Icon>) -> Result<()> {
-        let mut desktop_file_borrow = self.desktop_file.borrow_mut();
-        if let Some(old_icon_path) = desktop_file_borrow.get_icon_path()
+        if *self.is_discarded.borrow() {
+            bail!("Icon picker is discarded, not saving icon")
+        }
+
+        Self::save_to_fs(
+            icon,
+            &self.app.dirs.app_data_icons,
+            &mut self.desktop_file.borrow_mut(),
+        )
+    }
+
+    /// Replaces the icon of the desktop file, the file itself is not saved
+    fn save_to_fs(icon: &Icon, icon_dir: &Path, desktop_file: &mut DesktopFile) -> Result<()> {
+        if let Some(old_icon_path) = desktop_file.get_icon_path()
             && old_icon_path.is_file()
         {
             fs::remove_file(old_icon_path).context("Failed to remove old icon")?;
         }
 
-        let app_id = desktop_file_borrow
-            .get_id()
-            .context("No file id on DesktopFile")?;
+        let app_id = desktop_file.get_id().context("No file id on DesktopFile")?;
 
-        let icon_dir = &self.app.dirs.app_data_icons;
         let file_name = sanitize_filename::sanitize(format!("{app_id}.png"));
-        let save_path = icon_dir.join(&file_name);
+        let png_path = icon_dir.join(&file_name);
 
-        debug!(
-            "Saving icon '{}' to fs: {}",
-            &file_name,
-            save_path.display()
-        );
+        debug!("Saving icon '{}' to fs: {}", &file_name, png_path.display());
 
         icon.pixbuf
-            .savev(save_path.clone(), "png", &[])
+            .savev(png_path.clone(), "png", &[])
             .context("Failed to save icon to fs")?;
 
-        desktop_file_borrow.set_icon_path(&save_path);
-        drop(desktop_file_borrow);
+        // The icon theme scales an SVG sharply, the PNG stays as a rasterized fallback
+        let save_path = if let Some(svg) = &icon.svg {
+            let svg_file_name = sanitize_filename::sanitize(format!("{app_id}.svg"));
+            let svg_path = icon_dir.join(&svg_file_name);
+
+            debug!(
+                "Saving original SVG icon '{}' to fs: {}",
+                &svg_file_name,
+                svg_path.display()
+            );
+
+            fs::write(&svg_path, svg).context("Failed to save SVG icon to fs")?;
+            svg_path
+        } else {
+            png_path
+        };
+
+        desktop_file.set_icon_path(&save_path);
 
         Ok(())
     }
 
+    /// Marks the icon the web app uses now
+    fn build_current_icon_badge() -> Image {
+        let badge = Image::from_icon_name("object-select-symbolic");
+        badge.add_css_class("accent");
+        badge.set_halign(Align::End);
+        badge.set_valign(Align::Start);
+        badge.set_tooltip_text(Some(&t!("web_apps.web_app_view.icon.source.current")));
+
+        badge
+    }
+
     fn build_spinner() -> Spinner {
         Spinner::builder()
             .height_request(48)
@@ -498,7 +990,7 @@ impl IconPicker {
             .build()
     }
 
-    fn build_pref_group_icons() -> (PreferencesGroup, Button) {
+    fn build_pref_group_icons() -> (PreferencesGroup, Button, Button) {
         let content = ButtonContent::builder()
             .label(t!("web_apps.web_app_view.icon.button.reset"))
             .icon_name("folder-download-symbolic")
@@ -508,12 +1000,25 @@ impl IconPicker {
             .child(&content)
             .build();
 
+        let edit_content = ButtonContent::builder()
+            .label(t!("web_apps.web_app_view.icon.button.edit"))
+            .icon_name("document-edit-symbolic")
+            .build();
+        let edit_button = Button::builder()
+            .css_classes(["flat"])
+            .child(&edit_content)
+            .build();
+
+        let header_suffix = gtk::Box::new(Orientation::Horizontal, 6);
+        header_suffix.append(&edit_button);
+        header_suffix.append(&button);
+
         let pref_group = PreferencesGroup::builder()
             .title(t!("web_apps.web_app_view.icon.title"))
-            .header_suffix(&button)
+            .header_suffix(&header_suffix)
             .build();
 
-        (pref_group, button)
+        (pref_group, button, edit_button)
     }
 
     fn build_pref_row_add_icon() -> ButtonRow {
@@ -0,0 +1,309 @@
+use anyhow::{Result, bail};
+use gtk::{
+    gdk_pixbuf::Pixbuf,
+    gio::{Cancellable, MemoryInputStream},
+    glib,
+};
+use tracing::{debug, error};
+
+/// A frame in the directory of an ICO file
+struct IcoEntry {
+    /// The 16 bytes of the directory entry, a BMP frame is decoded with it
+    entry_bytes: [u8; Ico::ENTRY_LENGTH],
+    bit_count: u16,
+    offset: usize,
+    length: usize,
+}
+
+/// All frames of an ICO file, only the largest one is shown and saved
+pub struct Ico {
+    pub pixbuf: Pixbuf,
+    /// Sizes of the frames that could be decoded, smallest first
+    pub frame_sizes: Vec<(i32, i32)>,
+}
+impl Ico {
+    const HEADER_LENGTH: usize = 6;
+    const ENTRY_LENGTH: usize = 16;
+    const ICON_TYPE: u16 = 1;
+    const MAX_FRAME_SIDE: i32 = 1024;
+    const MAX_FRAME_LENGTH: usize = 5 * 1024 * 1024;
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    pub fn is_ico(bytes: &[u8]) -> bool {
+        bytes.len() >= Self::HEADER_LENGTH
+            && Self::read_u16(bytes, 0) == Some(0)
+            && Self::read_u16(bytes, 2) == Some(Self::ICON_TYPE)
+    }
+
+    /// Frames that are corrupt or too big are skipped, it fails only without any usable frame
+    pub fn from_bytes(bytes: &[u8]) -> Result<Ico> {
+        let entries = Self::parse_directory(bytes)?;
+
+        let mut frames: Vec<(u16, Pixbuf)> = entries
+            .iter()
+            .filter_map(|entry| match Self::decode_frame(bytes, entry) {
+                Ok(pixbuf) => Some((entry.bit_count, pixbuf)),
+                Err(error) => {
+                    error!("Skipping ICO frame: {error:?}");
+                    None
+                }
+            })
+            .collect();
+        frames.sort_by_key(|(bit_count, pixbuf)| (pixbuf.width() * pixbuf.height(), *bit_count));
+
+        let mut frame_sizes: Vec<(i32, i32)> = frames
+            .iter()
+            .map(|(_, pixbuf)| (pixbuf.width(), pixbuf.height()))
+            .collect();
+        frame_sizes.dedup();
+
+        let Some((_, pixbuf)) = frames.pop() else {
+            bail!("No usable frames in ICO")
+        };
+        debug!(?frame_sizes, "Decoded ICO frames");
+
+        Ok(Self {
+            pixbuf,
+            frame_sizes,
+        })
+    }
+
+    fn parse_directory(bytes: &[u8]) -> Result<Vec<IcoEntry>> {
+        if !Self::is_ico(bytes) {
+            bail!("Not an ICO file")
+        }
+        let Some(count) = Self::read_u16(bytes, 4).map(usize::from) else {
+            bail!("Corrupt ICO directory: no frame count")
+        };
+        if count == 0 || bytes.len() < Self::HEADER_LENGTH + count * Self::ENTRY_LENGTH {
+            bail!("Corrupt ICO directory: {count} frames do not fit")
+        }
+
+        let entries = (0..count)
+            .filter_map(|index| {
+                let start = Self::HEADER_LENGTH + index * Self::ENTRY_LENGTH;
+                let entry_bytes: [u8; Self::ENTRY_LENGTH] = bytes
+                    .get(start..start + Self::ENTRY_LENGTH)?
+                    .try_into()
+                    .ok()?;
+
+                Some(IcoEntry {
+                    bit_count: Self::read_u16(&entry_bytes, 6)?,
+                    length: usize::try_from(Self::read_u32(&entry_bytes, 8)?).ok()?,
+                    offset: usize::try_from(Self::read_u32(&entry_bytes, 12)?).ok()?,
+                    entry_bytes,
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn decode_frame(bytes: &[u8], entry: &IcoEntry) -> Result<Pixbuf> {
+        if entry.length > Self::MAX_FRAME_LENGTH {
+            bail!("Frame of {} bytes is too big", entry.length)
+        }
+        let Some(frame_bytes) = entry
+            .offset
+            .checked_add(entry.length)
+            .and_then(|end| bytes.get(entry.offset..end))
+        else {
+            bail!("Frame is outside of the file")
+        };
+
+        if frame_bytes.starts_with(Self::PNG_SIGNATURE) {
+            Self::decode_png_frame(frame_bytes)
+        } else {
+            Self::decode_bmp_frame(frame_bytes, entry)
+        }
+    }
+
+    /// The size is read from the header first, a huge image is not decompressed at all
+    fn decode_png_frame(frame_bytes: &[u8]) -> Result<Pixbuf> {
+        let (Some(width), Some(height)) = (
+            Self::read_u32_be(frame_bytes, 16),
+            Self::read_u32_be(frame_bytes, 20),
+        ) else {
+            bail!("PNG frame has no header")
+        };
+        let max_frame_side = Self::MAX_FRAME_SIDE.unsigned_abs();
+        if width > max_frame_side || height > max_frame_side {
+            bail!("PNG frame of {width} x {height} is too big")
+        }
+
+        Self::load_pixbuf(frame_bytes)
+    }
+
+    /// gdk-pixbuf decodes the bitmap and its mask when it is the only frame of an ICO
+    fn decode_bmp_frame(frame_bytes: &[u8], entry: &IcoEntry) -> Result<Pixbuf> {
+        let offset = u32::try_from(Self::HEADER_LENGTH + Self::ENTRY_LENGTH)?;
+        let mut entry_bytes = entry.entry_bytes;
+        entry_bytes[12..16].copy_from_slice(&offset.to_le_bytes());
+
+        let mut single_frame_ico =
+            Vec::with_capacity(Self::HEADER_LENGTH + Self::ENTRY_LENGTH + frame_bytes.len());
+        single_frame_ico.extend_from_slice(&[0, 0]);
+        single_frame_ico.extend_from_slice(&Self::ICON_TYPE.to_le_bytes());
+        single_frame_ico.extend_from_slice(&1_u16.to_le_bytes());
+        single_frame_ico.extend_from_slice(&entry_bytes);
+        single_frame_ico.extend_from_slice(frame_bytes);
+
+        let pixbuf = Self::load_pixbuf(&single_frame_ico)?;
+        if pixbuf.width() > Self::MAX_FRAME_SIDE || pixbuf.height() > Self::MAX_FRAME_SIDE {
+            bail!(
+                "BMP frame of {} x {} is too big",
+                pixbuf.width(),
+                pixbuf.height()
+            )
+        }
+
+        Ok(pixbuf)
+    }
+
+    fn load_pixbuf(bytes: &[u8]) -> Result<Pixbuf> {
+        let g_bytes = glib::Bytes::from(bytes);
+        let stream = MemoryInputStream::from_bytes(&g_bytes);
+
+        match Pixbuf::from_stream(&stream, Cancellable::NONE) {
+            Err(error) => {
+                bail!("Could not load ICO frame into a Pixbuf: '{error:?}'");
+            }
+            Ok(pixbuf) => Ok(pixbuf),
+        }
+    }
+
+    fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+        let bytes = bytes.get(offset..offset + 2)?;
+        Some(u16::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+        let bytes = bytes.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    /// PNG headers are big endian
+    fn read_u32_be(bytes: &[u8], offset: usize) -> Option<u32> {
+        let bytes = bytes.get(offset..offset + 4)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtk::gdk_pixbuf::Colorspace;
+
+    fn build_pixbuf(side: i32) -> Pixbuf {
+        let pixbuf = Pixbuf::new(Colorspace::Rgb, true, 8, side, side).unwrap();
+        pixbuf.fill(0x3584_e4ff);
+        pixbuf
+    }
+
+    fn build_png(side: i32) -> Vec<u8> {
+        build_pixbuf(side).save_to_bufferv("png", &[]).unwrap()
+    }
+
+    /// The entry and bitmap of a single frame ICO written by gdk-pixbuf
+    fn build_bmp(side: i32) -> ([u8; Ico::ENTRY_LENGTH], Vec<u8>) {
+        let ico = build_pixbuf(side).save_to_bufferv("ico", &[]).unwrap();
+        let start = Ico::HEADER_LENGTH;
+        let entry_bytes = ico[start..start + Ico::ENTRY_LENGTH].try_into().unwrap();
+
+        (entry_bytes, ico[start + Ico::ENTRY_LENGTH..].to_vec())
+    }
+
+    /// Frames are entry bytes without the length and offset, and the frame data
+    fn build_ico(frames: &[([u8; Ico::ENTRY_LENGTH], Vec<u8>)]) -> Vec<u8> {
+        let count = u16::try_from(frames.len()).unwrap();
+        let mut bytes = vec![0, 0];
+        bytes.extend_from_slice(&Ico::ICON_TYPE.to_le_bytes());
+        bytes.extend_from_slice(&count.to_le_bytes());
+
+        let mut offset = Ico::HEADER_LENGTH + frames.len() * Ico::ENTRY_LENGTH;
+        for (entry_bytes, frame_bytes) in frames {
+            let mut entry_bytes = *entry_bytes;
+            let length = u32::try_from(frame_bytes.len()).unwrap();
+            entry_bytes[8..12].copy_from_slice(&length.to_le_bytes());
+            entry_bytes[12..16].copy_from_slice(&u32::try_from(offset).unwrap().to_le_bytes());
+            bytes.extend_from_slice(&entry_bytes);
+            offset += frame_bytes.len();
+        }
+        for (_, frame_bytes) in frames {
+            bytes.extend_from_slice(frame_bytes);
+        }
+
+        bytes
+    }
+
+    fn png_entry() -> [u8; Ico::ENTRY_LENGTH] {
+        let mut entry_bytes = [0; Ico::ENTRY_LENGTH];
+        entry_bytes[6..8].copy_from_slice(&32_u16.to_le_bytes());
+        entry_bytes
+    }
+
+    #[test]
+    fn only_an_icon_header_is_an_ico() {
+        assert!(Ico::is_ico(&[0, 0, 1, 0, 1, 0]));
+        assert!(!Ico::is_ico(&[0, 0, 2, 0, 1, 0]));
+        assert!(!Ico::is_ico(&build_png(16)));
+        assert!(!Ico::is_ico(&[0, 0, 1]));
+    }
+
+    #[test]
+    fn largest_frame_is_used() {
+        let bytes = build_ico(&[
+            (png_entry(), build_png(32)),
+            (png_entry(), build_png(16)),
+            (png_entry(), build_png(48)),
+        ]);
+
+        let ico = Ico::from_bytes(&bytes).unwrap();
+
+        assert_eq!(ico.pixbuf.width(), 48);
+        assert_eq!(ico.frame_sizes, vec![(16, 16), (32, 32), (48, 48)]);
+    }
+
+    #[test]
+    fn bitmap_frame_is_decoded() {
+        let bytes = build_ico(&[build_bmp(24), (png_entry(), build_png(16))]);
+
+        let ico = Ico::from_bytes(&bytes).unwrap();
+
+        assert_eq!(ico.pixbuf.width(), 24);
+        assert_eq!(ico.frame_sizes, vec![(16, 16), (24, 24)]);
+    }
+
+    #[test]
+    fn corrupt_frames_are_skipped() {
+        let mut huge_png = build_png(16);
+        huge_png[16..20].copy_from_slice(&4096_u32.to_be_bytes());
+        let bytes = build_ico(&[
+            (png_entry(), huge_png),
+            (png_entry(), b"\x89PNG\r\n\x1a\nbroken".to_vec()),
+            (png_entry(), build_png(16)),
+        ]);
+
+        let ico = Ico::from_bytes(&bytes).unwrap();
+
+        assert_eq!(ico.frame_sizes, vec![(16, 16)]);
+    }
+
+    #[test]
+    fn ico_without_usable_frames_fails() {
+        let mut outside_entry = png_entry();
+        outside_entry[8..12].copy_from_slice(&1000_u32.to_le_bytes());
+        outside_entry[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut bytes = vec![0, 0, 1, 0, 1, 0];
+        bytes.extend_from_slice(&outside_entry);
+
+        assert!(Ico::from_bytes(&bytes).is_err());
+        assert!(Ico::from_bytes(&build_ico(&[(png_entry(), b"garbage".to_vec())])).is_err());
+    }
+
+    #[test]
+    fn directory_that_does_not_fit_fails() {
+        assert!(Ico::from_bytes(&[0, 0, 1, 0, 0, 0]).is_err());
+        assert!(Ico::from_bytes(&[0, 0, 1, 0, 2, 0, 0, 0]).is_err());
+    }
+}
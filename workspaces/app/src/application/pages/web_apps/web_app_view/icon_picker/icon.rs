@@ -1,4 +1,5 @@
-use anyhow::{Result, bail};
+use crate::application::pages::web_apps::web_app_view::icon_picker::ico::Ico;
+use anyhow::{Context, Result, bail};
 use gtk::{
     gdk_pixbuf::{Pixbuf, PixbufFormat},
     gio::{
@@ -7,15 +8,87 @@ use gtk::{
     },
     glib,
 };
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use tracing::error;
+
+/// Where an icon was found, icons of a better source are picked first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum IconSource {
+    /// The icon the web app uses now
+    Current,
+    /// A file or a dropped image
+    #[default]
+    LocalFile,
+    /// `<link rel="icon">`
+    Favicon,
+    Manifest,
+    /// `<link rel="apple-touch-icon">`
+    TouchIcon,
+    /// Conventional paths like `/favicon.ico` that the site does not link to
+    RootPath,
+    /// `<meta property="og:image">`, meant for link previews
+    OpenGraph,
+    /// A letter tile made from the app name, when the site has no usable icon
+    Generated,
+}
 
 pub struct Icon {
     pub pixbuf: Pixbuf,
+    /// The original source of a vector icon, saved next to the rasterized one
+    pub svg: Option<Vec<u8>>,
+    pub source: IconSource,
+    /// Sizes of all frames of an ICO file, the pixbuf is the largest one
+    pub ico_frame_sizes: Vec<(i32, i32)>,
 }
 impl Icon {
     const SCALE_HEIGHT: i32 = 512;
+    const SVG_MIMETYPE: &str = "image/svg+xml";
+    const SVG_EXTENSION: &str = "svg";
+    /// An xml declaration, comments or a doctype can go before the root element
+    const SVG_SNIFF_LENGTH: usize = 4096;
+    const SVG_UNSAFE_CONTENT: [&str; 2] = ["<script", "javascript:"];
+    /// Longest side divided by the shortest, above it an og:image is a banner
+    const BANNER_ASPECT_RATIO: f64 = 1.5;
+    /// Offset and bytes of the formats gdk-pixbuf loads, gzip is a compressed SVG
+    const MAGIC_BYTES: [(usize, &[u8]); 11] = [
+        (0, b"\x89PNG"),
+        (0, b"\xFF\xD8\xFF"),
+        (0, b"GIF8"),
+        (0, b"\x00\x00\x01\x00"),
+        (0, b"\x00\x00\x02\x00"),
+        (0, b"BM"),
+        (8, b"WEBP"),
+        (4, b"ftyp"),
+        (0, b"\x1F\x8B"),
+        (0, b"II*\x00"),
+        (0, b"MM\x00*"),
+    ];
+
+    /// An edited icon, even from an SVG, is only the pixels
+    pub fn from_pixbuf(pixbuf: Pixbuf) -> Icon {
+        Self {
+            pixbuf,
+            svg: None,
+            source: IconSource::default(),
+            ico_frame_sizes: Vec::new(),
+        }
+    }
 
     pub fn from_path(path: &PathBuf) -> Result<Icon> {
+        let is_svg_extension = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case(Self::SVG_EXTENSION));
+        let bytes = fs::read(path).context("Could not read image")?;
+        if is_svg_extension || Self::is_svg(&bytes) {
+            return Self::from_svg_bytes(&bytes);
+        }
+        if Ico::is_ico(&bytes)
+            && let Some(icon) = Self::from_ico_bytes(&bytes)
+        {
+            return Ok(icon);
+        }
+
         let file = gio::File::for_path(path);
 
         let pixbuf = if let Some(pixbuf_format) = Self::get_pixbuf_format_from_file(&file)
@@ -33,10 +106,28 @@ impl Icon {
             Ok(pixbuf) => pixbuf,
         };
 
-        Ok(Self { pixbuf })
+        Ok(Self {
+            pixbuf,
+            svg: None,
+            source: IconSource::default(),
+            ico_frame_sizes: Vec::new(),
+        })
     }
 
     pub fn from_bytes(bytes: &Vec<u8>, mimetype: Option<String>) -> Result<Icon> {
+        if mimetype
+            .as_ref()
+            .is_some_and(|mimetype| mimetype.starts_with(Self::SVG_MIMETYPE))
+            || Self::is_svg(bytes)
+        {
+            return Self::from_svg_bytes(bytes);
+        }
+        if Ico::is_ico(bytes)
+            && let Some(icon) = Self::from_ico_bytes(bytes)
+        {
+            return Ok(icon);
+        }
+
         let pixbuf_format =
             mimetype.and_then(|mimetype| Self::get_pixbuf_format_from_mimetype(&mimetype));
         let g_bytes = glib::Bytes::from(bytes);
@@ -57,7 +148,103 @@ impl Icon {
             Ok(pixbuf) => pixbuf,
         };
 
-        Ok(Self { pixbuf })
+        Ok(Self {
+            pixbuf,
+            svg: None,
+            source: IconSource::default(),
+            ico_frame_sizes: Vec::new(),
+        })
+    }
+
+    /// Sites often serve only a tiny `viewBox`, so the icon is rendered at a fixed size
+    fn from_svg_bytes(bytes: &[u8]) -> Result<Icon> {
+        let svg_text = String::from_utf8_lossy(bytes).to_lowercase();
+        if Self::SVG_UNSAFE_CONTENT
+            .iter()
+            .any(|unsafe_content| svg_text.contains(unsafe_content))
+        {
+            bail!("SVG contains scripts, not loading it")
+        }
+
+        let g_bytes = glib::Bytes::from(bytes);
+        let stream = MemoryInputStream::from_bytes(&g_bytes);
+        let pixbuf = match Pixbuf::from_stream_at_scale(
+            &stream,
+            Self::SCALE_HEIGHT,
+            Self::SCALE_HEIGHT,
+            true,
+            Cancellable::NONE,
+        ) {
+            Err(error) => {
+                bail!("Could not render SVG into a Pixbuf: '{error:?}'");
+            }
+            Ok(pixbuf) => pixbuf,
+        };
+
+        Ok(Self {
+            pixbuf,
+            svg: Some(bytes.to_vec()),
+            source: IconSource::default(),
+            ico_frame_sizes: Vec::new(),
+        })
+    }
+
+    /// A broken ICO is still given to gdk-pixbuf, it may load one of the frames
+    fn from_ico_bytes(bytes: &[u8]) -> Option<Icon> {
+        let ico = match Ico::from_bytes(bytes) {
+            Ok(ico) => ico,
+            Err(error) => {
+                error!("Could not decode ICO frames: {error:?}");
+                return None;
+            }
+        };
+
+        Some(Self {
+            pixbuf: ico.pixbuf,
+            svg: None,
+            source: IconSource::default(),
+            ico_frame_sizes: ico.frame_sizes,
+        })
+    }
+
+    pub fn is_vector(&self) -> bool {
+        self.svg.is_some()
+    }
+
+    /// Lower is picked first, the current icon always goes first and the biggest one wins
+    /// between local files, favicons and manifest icons
+    pub fn get_rank(&self) -> u8 {
+        match self.source {
+            IconSource::Current => 0,
+            IconSource::LocalFile | IconSource::Favicon | IconSource::Manifest => 1,
+            IconSource::TouchIcon => 2,
+            IconSource::RootPath => 3,
+            IconSource::OpenGraph if self.is_banner() => 5,
+            IconSource::OpenGraph => 4,
+            IconSource::Generated => 6,
+        }
+    }
+
+    fn is_banner(&self) -> bool {
+        let width = f64::from(self.pixbuf.width());
+        let height = f64::from(self.pixbuf.height());
+        width.max(height) > width.min(height) * Self::BANNER_ASPECT_RATIO
+    }
+
+    /// Sites can answer with an html error page and a success status, those are not images
+    pub fn is_image_bytes(bytes: &[u8]) -> bool {
+        Self::MAGIC_BYTES.iter().any(|(offset, magic_bytes)| {
+            bytes
+                .get(*offset..offset + magic_bytes.len())
+                .is_some_and(|bytes| bytes == *magic_bytes)
+        }) || Self::is_svg(bytes)
+    }
+
+    /// An html page with an inline icon is not an SVG
+    fn is_svg(bytes: &[u8]) -> bool {
+        let start = &bytes[..bytes.len().min(Self::SVG_SNIFF_LENGTH)];
+        let start_text = String::from_utf8_lossy(start).to_lowercase();
+        start_text.contains("<svg") && !start_text.contains("<html")
     }
 
     fn get_pixbuf_format_from_mimetype(mimetype: &str) -> Option<PixbufFormat> {
@@ -0,0 +1,219 @@
+use crate::application::{
+    App,
+    pages::web_apps::web_app_view::icon_picker::{
+        icon::{Icon, IconSource},
+        icon_fetcher::{FetchedImage, ManifestMetadata},
+    },
+};
+use anyhow::{Context, Result};
+use common::utils::files;
+use gtk::glib::{self, ChecksumType};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, error};
+use url::Url;
+
+#[derive(Serialize, Deserialize)]
+struct CachedImage {
+    url: String,
+    file_name: String,
+    mimetype: Option<String>,
+    source: IconSource,
+    width: i32,
+    height: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheMetadata {
+    origin: String,
+    /// Seconds since the unix epoch
+    fetched_at: u64,
+    images: Vec<CachedImage>,
+    manifest_metadata: Option<ManifestMetadata>,
+}
+
+/// Icons of a site from the cache
+pub struct CachedIcons {
+    pub icons: Vec<(String, Rc<Icon>)>,
+    pub manifest_metadata: Option<ManifestMetadata>,
+}
+
+/// Fetched icons per origin, shared by all web apps of a site. Deleting a web app leaves it
+/// alone, the oldest sites are evicted when the cache grows too big.
+pub struct IconCache {
+    app: Rc<App>,
+}
+impl IconCache {
+    const METADATA_FILE: &str = "metadata.json";
+    const DEFAULT_TTL_HOURS: u64 = 24 * 7;
+    const MAX_SIZE: u64 = 50 * 1024 * 1024;
+
+    pub fn new(app: &Rc<App>) -> Self {
+        Self { app: app.clone() }
+    }
+
+    /// `None` when nothing is cached or the cache is older than the ttl
+    pub fn load(&self, url: &Url) -> Option<CachedIcons> {
+        let origin_dir = self.get_origin_dir(url)?;
+        let metadata = Self::read_metadata(&origin_dir.join(Self::METADATA_FILE))?;
+
+        let age = Self::now().saturating_sub(metadata.fetched_at);
+        if Duration::from_secs(age) > self.get_ttl() {
+            debug!(origin = metadata.origin, "Icon cache expired");
+            return None;
+        }
+
+        let icons = metadata
+            .images
+            .iter()
+            .filter_map(|image| {
+                let bytes = fs::read(origin_dir.join(&image.file_name)).ok()?;
+                let mut icon = Icon::from_bytes(&bytes, image.mimetype.clone())
+                    .inspect_err(|error| error!(url = image.url, ?error, "Invalid cached icon"))
+                    .ok()?;
+                icon.source = image.source;
+                Some((image.url.clone(), Rc::new(icon)))
+            })
+            .collect::<Vec<_>>();
+        if icons.is_empty() {
+            return None;
+        }
+
+        debug!(
+            origin = metadata.origin,
+            count = icons.len(),
+            "Loaded icons from cache"
+        );
+        Some(CachedIcons {
+            icons,
+            manifest_metadata: metadata.manifest_metadata,
+        })
+    }
+
+    /// Replaces the cached icons of the origin
+    pub fn store(
+        &self,
+        url: &Url,
+        fetched_images: &[FetchedImage],
+        manifest_metadata: Option<&ManifestMetadata>,
+    ) -> Result<()> {
+        let origin_dir = self.get_origin_dir(url).context("Url has no origin")?;
+        if origin_dir.is_dir() {
+            fs::remove_dir_all(&origin_dir).context("Failed to clear cached icons")?;
+        }
+        fs::create_dir_all(&origin_dir).context(format!(
+            "Could not create icon cache dir: {}",
+            origin_dir.display()
+        ))?;
+
+        let mut images = Vec::new();
+        for fetched_image in fetched_images {
+            let file_name = Self::get_checksum(&fetched_image.url)?;
+            files::write_atomic(&origin_dir.join(&file_name), &fetched_image.bytes)?;
+
+            images.push(CachedImage {
+                url: fetched_image.url.clone(),
+                file_name,
+                mimetype: fetched_image.mimetype.clone(),
+                source: fetched_image.source,
+                width: fetched_image.width,
+                height: fetched_image.height,
+            });
+        }
+
+        let metadata = CacheMetadata {
+            origin: url.origin().ascii_serialization(),
+            fetched_at: Self::now(),
+            images,
+            manifest_metadata: manifest_metadata.cloned(),
+        };
+        let metadata_json = serde_json::to_string_pretty(&metadata)?;
+        files::write_atomic(
+            &origin_dir.join(Self::METADATA_FILE),
+            metadata_json.as_bytes(),
+        )?;
+        debug!(origin = metadata.origin, "Stored icons in cache");
+
+        self.evict();
+        Ok(())
+    }
+
+    /// Removes the least recently fetched origins until the cache fits
+    fn evict(&self) {
+        let cache_dir = &self.app.dirs.app_data_icon_cache;
+        let mut origins: Vec<(u64, u64, PathBuf)> = files::get_entries_in_dir(cache_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .map(|path| {
+                let fetched_at = Self::read_metadata(&path.join(Self::METADATA_FILE))
+                    .map(|metadata| metadata.fetched_at)
+                    .unwrap_or_default();
+                (fetched_at, files::get_dir_size(&path), path)
+            })
+            .collect();
+        origins.sort_by_key(|(fetched_at, _, _)| *fetched_at);
+
+        let mut cache_size: u64 = origins.iter().map(|(_, size, _)| size).sum();
+        // The newest origin is kept, even when it is too big on its own
+        for (_, size, path) in origins.iter().take(origins.len().saturating_sub(1)) {
+            if cache_size <= Self::MAX_SIZE {
+                break;
+            }
+
+            debug!(path = %path.display(), "Evicting cached icons");
+            match fs::remove_dir_all(path) {
+                Ok(()) => cache_size = cache_size.saturating_sub(*size),
+                Err(error) => error!("Failed to evict cached icons: {error:?}"),
+            }
+        }
+    }
+
+    fn get_origin_dir(&self, url: &Url) -> Option<PathBuf> {
+        let origin = url.origin();
+        if !origin.is_tuple() {
+            return None;
+        }
+        let checksum = Self::get_checksum(&origin.ascii_serialization()).ok()?;
+
+        Some(self.app.dirs.app_data_icon_cache.join(checksum))
+    }
+
+    fn get_ttl(&self) -> Duration {
+        let ttl_hours = self
+            .app
+            .cache_settings
+            .borrow()
+            .settings
+            .icon_cache_ttl_hours
+            .unwrap_or(Self::DEFAULT_TTL_HOURS);
+
+        Duration::from_secs(ttl_hours.saturating_mul(60 * 60))
+    }
+
+    fn read_metadata(path: &Path) -> Option<CacheMetadata> {
+        let metadata_json = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&metadata_json)
+            .inspect_err(|error| error!(path = %path.display(), ?error, "Invalid icon cache"))
+            .ok()
+    }
+
+    fn get_checksum(value: &str) -> Result<String> {
+        glib::compute_checksum_for_string(ChecksumType::Sha256, value)
+            .map(|checksum| checksum.to_string())
+            .context("Failed to compute checksum")
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+}
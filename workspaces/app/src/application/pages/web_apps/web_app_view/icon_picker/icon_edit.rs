@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use gtk::{
+    gdk_pixbuf::{Colorspace, Pixbuf},
+    glib,
+};
+
+/// Shape of the icon, outside of it is transparent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mask {
+    #[default]
+    None,
+    Rounded,
+    Circle,
+}
+impl Mask {
+    /// Order of the mask row
+    pub const ALL: [Mask; 3] = [Mask::None, Mask::Rounded, Mask::Circle];
+}
+
+/// Edits of a picked icon, each step only works on the pixels of the previous one
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IconEdit {
+    pub crop_to_content: bool,
+    pub padding_percent: u32,
+    pub mask: Mask,
+}
+impl IconEdit {
+    pub const PADDING_PERCENT_MAX: u32 = 40;
+    /// Relative to the shortest side
+    const ROUNDED_RADIUS_RATIO: f64 = 0.2;
+    const ALPHA_CHANNEL: usize = 3;
+
+    /// The result is always square, the icon is centered when it is not
+    pub fn apply(&self, pixbuf: &Pixbuf) -> Result<Pixbuf> {
+        let mut pixbuf = Self::with_alpha(pixbuf)?;
+
+        if self.crop_to_content {
+            pixbuf = Self::crop_to_content(&pixbuf)?;
+        }
+        pixbuf = Self::apply_mask(&pixbuf, self.mask)?;
+        pixbuf = Self::add_padding(&pixbuf, self.padding_percent)?;
+
+        Ok(pixbuf)
+    }
+
+    /// All edits read and write the alpha channel
+    pub fn with_alpha(pixbuf: &Pixbuf) -> Result<Pixbuf> {
+        pixbuf
+            .add_alpha(false, 0, 0, 0)
+            .context("Failed to add alpha channel to icon")
+    }
+
+    /// Trims fully transparent borders, an empty icon is returned as is
+    pub fn crop_to_content(pixbuf: &Pixbuf) -> Result<Pixbuf> {
+        let pixbuf = Self::with_alpha(pixbuf)?;
+        let width = usize::try_from(pixbuf.width())?;
+        let height = usize::try_from(pixbuf.height())?;
+        let rowstride = usize::try_from(pixbuf.rowstride())?;
+        let n_channels = usize::try_from(pixbuf.n_channels())?;
+        let pixel_bytes = pixbuf.read_pixel_bytes();
+
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for y in 0..height {
+            for x in 0..width {
+                if pixel_bytes[y * rowstride + x * n_channels + Self::ALPHA_CHANNEL] == 0 {
+                    continue;
+                }
+                bounds = Some(match bounds {
+                    None => (x, y, x, y),
+                    Some((min_x, min_y, max_x, max_y)) => {
+                        (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                    }
+                });
+            }
+        }
+
+        let Some((min_x, min_y, max_x, max_y)) = bounds else {
+            return Ok(pixbuf);
+        };
+
+        pixbuf
+            .new_subpixbuf(
+                i32::try_from(min_x)?,
+                i32::try_from(min_y)?,
+                i32::try_from(max_x - min_x + 1)?,
+                i32::try_from(max_y - min_y + 1)?,
+            )
+            .copy()
+            .context("Failed to copy cropped icon")
+    }
+
+    /// Centers the icon on a transparent square, the padding is a percentage of the longest side
+    pub fn add_padding(pixbuf: &Pixbuf, padding_percent: u32) -> Result<Pixbuf> {
+        let pixbuf = Self::with_alpha(pixbuf)?;
+        let width = pixbuf.width();
+        let height = pixbuf.height();
+        let side = width.max(height);
+        let padding = side * i32::try_from(padding_percent.min(Self::PADDING_PERCENT_MAX))? / 100;
+        let canvas_side = side + padding * 2;
+
+        let canvas = Pixbuf::new(Colorspace::Rgb, true, 8, canvas_side, canvas_side)
+            .context("Failed to create icon canvas")?;
+        canvas.fill(0);
+        pixbuf.copy_area(
+            0,
+            0,
+            width,
+            height,
+            &canvas,
+            (canvas_side - width) / 2,
+            (canvas_side - height) / 2,
+        );
+
+        Ok(canvas)
+    }
+
+    /// Edges are anti-aliased by the part of the pixel inside the shape
+    pub fn apply_mask(pixbuf: &Pixbuf, mask: Mask) -> Result<Pixbuf> {
+        let pixbuf = Self::with_alpha(pixbuf)?;
+        if mask == Mask::None {
+            return Ok(pixbuf);
+        }
+
+        let width = usize::try_from(pixbuf.width())?;
+        let height = usize::try_from(pixbuf.height())?;
+        let rowstride = usize::try_from(pixbuf.rowstride())?;
+        let n_channels = usize::try_from(pixbuf.n_channels())?;
+        let mut pixel_data = pixbuf.read_pixel_bytes().to_vec();
+
+        for y in 0..height {
+            for x in 0..width {
+                let coverage = Self::get_mask_coverage(mask, x, y, width, height);
+                let alpha_index = y * rowstride + x * n_channels + Self::ALPHA_CHANNEL;
+                pixel_data[alpha_index] = Self::scale_alpha(pixel_data[alpha_index], coverage);
+            }
+        }
+
+        Ok(Pixbuf::from_bytes(
+            &glib::Bytes::from_owned(pixel_data),
+            Colorspace::Rgb,
+            true,
+            8,
+            pixbuf.width(),
+            pixbuf.height(),
+            pixbuf.rowstride(),
+        ))
+    }
+
+    /// Distance of the pixel center to a rounded rectangle, a circle has the radius of half the
+    /// shortest side
+    #[allow(clippy::cast_precision_loss)]
+    fn get_mask_coverage(mask: Mask, x: usize, y: usize, width: usize, height: usize) -> f64 {
+        let (width, height) = (width as f64, height as f64);
+        let (half_width, half_height, radius) = match mask {
+            Mask::None => return 1.0,
+            Mask::Rounded => (
+                width / 2.0,
+                height / 2.0,
+                width.min(height) * Self::ROUNDED_RADIUS_RATIO,
+            ),
+            Mask::Circle => {
+                let half_side = width.min(height) / 2.0;
+                (half_side, half_side, half_side)
+            }
+        };
+
+        let distance_x = ((x as f64 + 0.5 - width / 2.0).abs() - (half_width - radius)).max(0.0);
+        let distance_y = ((y as f64 + 0.5 - height / 2.0).abs() - (half_height - radius)).max(0.0);
+
+        (radius - distance_x.hypot(distance_y) + 0.5).clamp(0.0, 1.0)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn scale_alpha(alpha: u8, coverage: f64) -> u8 {
+        (f64::from(alpha) * coverage).round() as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLUE: u32 = 0x3584_e4ff;
+
+    fn build_pixbuf(width: i32, height: i32, pixel: u32) -> Pixbuf {
+        let pixbuf = Pixbuf::new(Colorspace::Rgb, true, 8, width, height).unwrap();
+        pixbuf.fill(pixel);
+        pixbuf
+    }
+
+    /// Red, green, blue and alpha of the pixel
+    fn get_pixel(pixbuf: &Pixbuf, x: i32, y: i32) -> [u8; 4] {
+        let index = usize::try_from(y * pixbuf.rowstride() + x * pixbuf.n_channels()).unwrap();
+        pixbuf.read_pixel_bytes()[index..index + 4]
+            .try_into()
+            .unwrap()
+    }
+
+    /// A blue square on a transparent canvas
+    fn build_framed_pixbuf(side: i32, content_x: i32, content_y: i32, content_side: i32) -> Pixbuf {
+        let canvas = build_pixbuf(side, side, 0);
+        build_pixbuf(content_side, content_side, BLUE).copy_area(
+            0,
+            0,
+            content_side,
+            content_side,
+            &canvas,
+            content_x,
+            content_y,
+        );
+        canvas
+    }
+
+    #[test]
+    fn transparent_border_is_cropped_away() {
+        let pixbuf = build_framed_pixbuf(32, 4, 8, 10);
+
+        let cropped = IconEdit::crop_to_content(&pixbuf).unwrap();
+
+        assert_eq!((cropped.width(), cropped.height()), (10, 10));
+        assert_eq!(get_pixel(&cropped, 0, 0), [0x35, 0x84, 0xe4, 0xff]);
+        assert_eq!(get_pixel(&cropped, 9, 9), [0x35, 0x84, 0xe4, 0xff]);
+    }
+
+    #[test]
+    fn empty_icon_is_not_cropped() {
+        let pixbuf = build_pixbuf(16, 16, 0);
+
+        let cropped = IconEdit::crop_to_content(&pixbuf).unwrap();
+
+        assert_eq!((cropped.width(), cropped.height()), (16, 16));
+    }
+
+    #[test]
+    fn padding_centers_the_icon_on_a_larger_canvas() {
+        let pixbuf = build_pixbuf(40, 20, BLUE);
+
+        let padded = IconEdit::add_padding(&pixbuf, 10).unwrap();
+
+        // 10% of the longest side on both sides
+        assert_eq!((padded.width(), padded.height()), (48, 48));
+        assert_eq!(get_pixel(&padded, 3, 24)[3], 0);
+        assert_eq!(get_pixel(&padded, 4, 14)[3], 0xff);
+        assert_eq!(get_pixel(&padded, 43, 33)[3], 0xff);
+        assert_eq!(get_pixel(&padded, 24, 13)[3], 0);
+        assert_eq!(get_pixel(&padded, 24, 34)[3], 0);
+    }
+
+    #[test]
+    fn padding_is_capped() {
+        let pixbuf = build_pixbuf(10, 10, BLUE);
+
+        let padded = IconEdit::add_padding(&pixbuf, 100).unwrap();
+
+        assert_eq!(padded.width(), 10 + 2 * 4);
+    }
+
+    #[test]
+    fn circle_mask_clears_the_corners() {
+        let pixbuf = build_pixbuf(32, 32, BLUE);
+
+        let masked = IconEdit::apply_mask(&pixbuf, Mask::Circle).unwrap();
+
+        assert_eq!(get_pixel(&masked, 0, 0)[3], 0);
+        assert_eq!(get_pixel(&masked, 31, 31)[3], 0);
+        assert_eq!(get_pixel(&masked, 16, 16), [0x35, 0x84, 0xe4, 0xff]);
+        // The edge is anti-aliased
+        assert!(get_pixel(&masked, 16, 0)[3] > 0);
+    }
+
+    #[test]
+    fn no_mask_keeps_every_pixel() {
+        let pixbuf = build_pixbuf(8, 8, BLUE);
+
+        let masked = IconEdit::apply_mask(&pixbuf, Mask::None).unwrap();
+
+        assert_eq!(get_pixel(&masked, 0, 0)[3], 0xff);
+    }
+
+    #[test]
+    fn edits_make_a_square_icon() {
+        let pixbuf = build_framed_pixbuf(32, 2, 2, 12);
+        let icon_edit = IconEdit {
+            crop_to_content: true,
+            padding_percent: 25,
+            mask: Mask::Rounded,
+        };
+
+        let edited = icon_edit.apply(&pixbuf).unwrap();
+
+        assert_eq!((edited.width(), edited.height()), (18, 18));
+        assert_eq!(get_pixel(&edited, 0, 0)[3], 0);
+        assert_eq!(get_pixel(&edited, 9, 9)[3], 0xff);
+    }
+}
@@ -1,13 +1,17 @@
-use crate::application::{App, pages::web_apps::web_app_view::icon_picker::icon::Icon};
+use crate::application::pages::web_apps::web_app_view::icon_picker::icon::{Icon, IconSource};
 use anyhow::{Result, bail};
-use common::{fetch::Response, url::UrlExt};
-use gtk::glib::{self};
-use scraper::{Html, Selector};
-use serde::Deserialize;
-use std::{
-    collections::{HashMap, HashSet},
-    rc::Rc,
+use common::{
+    fetch::{Fetch, Response},
+    url::UrlExt,
+};
+use gtk::{
+    gdk::RGBA,
+    gio::{Cancellable, prelude::CancellableExt},
+    glib::{self},
 };
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, rc::Rc};
 use tracing::{debug, error, info};
 use url::Url;
 
@@ -18,18 +22,100 @@ struct ManifestIcon {
 #[derive(Deserialize)]
 struct ManifestJson {
     icons: Option<Vec<ManifestIcon>>,
+    name: Option<String>,
+    short_name: Option<String>,
+    theme_color: Option<String>,
+    display: Option<String>,
+}
+
+/// App metadata from a web app manifest, only used as suggestions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestMetadata {
+    pub name: Option<String>,
+    pub theme_color: Option<String>,
+    pub display: Option<String>,
+    /// From `og:site_name` or the `<title>` of the page, for sites without a manifest name
+    #[serde(default)]
+    pub page_name: Option<String>,
+}
+impl ManifestMetadata {
+    fn from_manifest(manifest: &ManifestJson) -> Option<Self> {
+        let metadata = Self {
+            // The short name is meant for launchers
+            name: manifest
+                .short_name
+                .clone()
+                .or(manifest.name.clone())
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty()),
+            theme_color: manifest
+                .theme_color
+                .as_ref()
+                .filter(|theme_color| RGBA::parse(theme_color.trim()).is_ok())
+                .map(|theme_color| theme_color.trim().to_string()),
+            display: manifest.display.clone(),
+            page_name: None,
+        };
+
+        if metadata.name.is_none() && metadata.theme_color.is_none() && metadata.display.is_none() {
+            None
+        } else {
+            Some(metadata)
+        }
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref().or(self.page_name.as_deref())
+    }
+
+    /// The site wants to run in its own window without browser UI
+    pub fn is_standalone(&self) -> bool {
+        self.display
+            .as_ref()
+            .is_some_and(|display| display == "standalone" || display == "fullscreen")
+    }
+}
+
+/// The response of an icon that could be loaded, kept for the icon cache
+pub struct FetchedImage {
+    pub url: String,
+    pub bytes: Vec<u8>,
+    pub mimetype: Option<String>,
+    pub source: IconSource,
+    pub width: i32,
+    pub height: i32,
 }
 
 pub struct IconFetcher {
-    app: Rc<App>,
+    fetch: Rc<Fetch>,
     url: Url,
     base_url: Option<Url>,
-    icon_urls: HashSet<String>,
+    /// The best source is kept for an url found more than once
+    icon_urls: HashMap<String, IconSource>,
     /// `HashMap<manifest_url_string, (manifest_url, base_url)>`
     manifest_urls: HashMap<String, (Url, Url)>,
+    manifest_metadata: Option<ManifestMetadata>,
+    page_name: Option<String>,
+    fetched_images: Vec<FetchedImage>,
+    cancellable: Cancellable,
 }
 impl IconFetcher {
-    pub fn new(app: &Rc<App>, url: &str) -> Result<Self> {
+    const TITLE_SEPARATORS: [&str; 5] = [" | ", " - ", " – ", " — ", " · "];
+    const TITLE_BOILERPLATE: [&str; 9] = [
+        "login",
+        "log in",
+        "sign in",
+        "sign up",
+        "home",
+        "homepage",
+        "home page",
+        "welcome",
+        "dashboard",
+    ];
+    const PAGE_NAME_MAX_LENGTH: usize = 64;
+
+    /// A cancelled fetch stops its requests and returns an error instead of icons
+    pub fn new(fetch: &Rc<Fetch>, url: &str, cancellable: &Cancellable) -> Result<Self> {
         let Some(url) = Url::parse(url).ok() else {
             bail!("Invalid url")
         };
@@ -40,11 +126,15 @@ impl IconFetcher {
         };
 
         Ok(Self {
-            app: app.clone(),
+            fetch: fetch.clone(),
             url,
             base_url,
-            icon_urls: HashSet::new(),
+            icon_urls: HashMap::new(),
             manifest_urls: HashMap::new(),
+            manifest_metadata: None,
+            page_name: None,
+            fetched_images: Vec::new(),
+            cancellable: cancellable.clone(),
         })
     }
 
@@ -59,20 +149,47 @@ impl IconFetcher {
             };
             let Response {
                 data: html_text, ..
-            } = self.app.fetch.get_as_string(url.as_str()).await?;
+            } = self
+                .fetch
+                .get_as_string(url.as_str(), Some(&self.cancellable))
+                .await?;
             let fragment = Html::parse_document(&html_text);
 
+            if self.page_name.is_none() {
+                self.page_name = Self::get_page_name_from_html(&fragment);
+            }
             self.set_default_icon_urls(&url);
             self.set_manifest_urls_from_html(&fragment, &url);
             self.set_icon_urls_from_html(&fragment, &url);
+            self.set_touch_icon_urls_from_html(&fragment, &url);
+            self.set_open_graph_urls_from_html(&fragment, &url);
         }
         self.set_icon_urls_from_manifests().await;
+        if let Some(page_name) = self.page_name.take() {
+            debug!(page_name, "Page name found");
+            self.manifest_metadata
+                .get_or_insert_with(ManifestMetadata::default)
+                .page_name = Some(page_name);
+        }
 
         let icons = self.fetch_icons_from_urls().await;
+        if self.cancellable.is_cancelled() {
+            bail!("Fetching online icons cancelled")
+        }
 
         Ok(icons)
     }
 
+    /// Metadata of the first manifest that has any, available after fetching icons
+    pub fn get_manifest_metadata(&self) -> Option<&ManifestMetadata> {
+        self.manifest_metadata.as_ref()
+    }
+
+    /// Responses of the icons, available after fetching icons
+    pub fn get_fetched_images(&self) -> &[FetchedImage] {
+        &self.fetched_images
+    }
+
     #[allow(clippy::unused_self)]
     fn get_href_as_absolute_url(&self, href: &str, url: &Url) -> Result<Url> {
         let sanitized_url = url.sanitize();
@@ -80,15 +197,25 @@ impl IconFetcher {
         Ok(new_url)
     }
 
+    fn insert_icon_url(&mut self, icon_url: &Url, source: IconSource) {
+        self.icon_urls
+            .entry(icon_url.to_string())
+            .and_modify(|source_iter| *source_iter = (*source_iter).min(source))
+            .or_insert(source);
+    }
+
     fn set_default_icon_urls(&mut self, url: &Url) {
         let sanitized_url = url.sanitize();
-        let default_urls = [sanitized_url.join("favicon.ico").ok()];
+        let default_urls = [
+            sanitized_url.join("favicon.ico").ok(),
+            sanitized_url.join("apple-touch-icon.png").ok(),
+        ];
 
         for default_url in default_urls {
             let Some(default) = default_url else {
                 continue;
             };
-            self.icon_urls.insert(default.to_string());
+            self.insert_icon_url(&default, IconSource::RootPath);
         }
     }
 
@@ -124,7 +251,84 @@ impl IconFetcher {
                     continue;
                 };
                 info!(url = icon_url.to_string(), "Favicon icon url found");
-                self.icon_urls.insert(icon_url.to_string());
+                self.insert_icon_url(&icon_url, IconSource::Favicon);
+            }
+        }
+    }
+
+    fn set_touch_icon_urls_from_html(&mut self, html_fragment: &Html, url: &Url) {
+        let Ok(touch_icon_selector) = Selector::parse(
+            "link[rel~=\"apple-touch-icon\"], link[rel~=\"apple-touch-icon-precomposed\"]",
+        ) else {
+            return;
+        };
+
+        for element in html_fragment.select(&touch_icon_selector) {
+            if let Some(href) = element.value().attr("href") {
+                debug!(href, "Touch icon href found");
+                let Ok(icon_url) = self.get_href_as_absolute_url(href, url) else {
+                    continue;
+                };
+                info!(url = icon_url.to_string(), "Touch icon url found");
+                self.insert_icon_url(&icon_url, IconSource::TouchIcon);
+            }
+        }
+    }
+
+    /// The site name of Open Graph is a better name than the title
+    fn get_page_name_from_html(html_fragment: &Html) -> Option<String> {
+        let site_name_selector = Selector::parse("meta[property=\"og:site_name\"]").ok()?;
+        let title_selector = Selector::parse("title").ok()?;
+
+        let site_name = html_fragment
+            .select(&site_name_selector)
+            .find_map(|element| element.value().attr("content"))
+            .and_then(Self::clean_page_name);
+
+        site_name.or_else(|| {
+            html_fragment
+                .select(&title_selector)
+                .next()
+                .and_then(|element| Self::clean_page_name(&element.text().collect::<String>()))
+        })
+    }
+
+    /// Drops parts like "Login" of a title like "Login – Example", of the other parts the
+    /// shortest one is the name of the site most of the time
+    fn clean_page_name(title: &str) -> Option<String> {
+        let title = title.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let mut parts = vec![title.as_str()];
+        for separator in Self::TITLE_SEPARATORS {
+            parts = parts
+                .into_iter()
+                .flat_map(|part| part.split(separator))
+                .collect();
+        }
+
+        parts
+            .into_iter()
+            .map(str::trim)
+            .filter(|part| {
+                !part.is_empty() && !Self::TITLE_BOILERPLATE.contains(&part.to_lowercase().as_str())
+            })
+            .min_by_key(|part| part.chars().count())
+            .filter(|part| part.chars().count() <= Self::PAGE_NAME_MAX_LENGTH)
+            .map(str::to_string)
+    }
+
+    fn set_open_graph_urls_from_html(&mut self, html_fragment: &Html, url: &Url) {
+        let Ok(open_graph_selector) = Selector::parse("meta[property=\"og:image\"]") else {
+            return;
+        };
+
+        for element in html_fragment.select(&open_graph_selector) {
+            if let Some(content) = element.value().attr("content") {
+                debug!(content, "Open Graph image found");
+                let Ok(icon_url) = self.get_href_as_absolute_url(content, url) else {
+                    continue;
+                };
+                info!(url = icon_url.to_string(), "Open Graph image url found");
+                self.insert_icon_url(&icon_url, IconSource::OpenGraph);
             }
         }
     }
@@ -133,13 +337,16 @@ impl IconFetcher {
         let mut manifest_handles = HashMap::new();
 
         for (manifest_url, base_path_url) in self.manifest_urls.values() {
-            let app_clone = self.app.clone();
+            let fetch_clone = self.fetch.clone();
             let url_clone = manifest_url.clone();
+            let cancellable_clone = self.cancellable.clone();
             // Spawn in parallel on main thread
             let handle = glib::spawn_future_local(async move {
-                app_clone.fetch.get_as_string(url_clone.as_str()).await
+                fetch_clone
+                    .get_as_string(url_clone.as_str(), Some(&cancellable_clone))
+                    .await
             });
-            manifest_handles.insert((base_path_url, manifest_url), handle);
+            manifest_handles.insert((base_path_url.clone(), manifest_url.clone()), handle);
         }
 
         for ((base_path_url, manifest_url), handle) in manifest_handles {
@@ -154,6 +361,10 @@ impl IconFetcher {
             let Ok(manifest) = serde_json::from_str::<ManifestJson>(&manifest_json) else {
                 continue;
             };
+            if self.manifest_metadata.is_none() {
+                self.manifest_metadata = ManifestMetadata::from_manifest(&manifest);
+                debug!(metadata = ?self.manifest_metadata, "Manifest metadata found");
+            }
             let Some(icons) = manifest.icons else {
                 continue;
             };
@@ -162,11 +373,11 @@ impl IconFetcher {
                     continue;
                 };
                 debug!(href = icon_href, "Manifest href found");
-                let Ok(icon_url) = self.get_href_as_absolute_url(&icon_href, base_path_url) else {
+                let Ok(icon_url) = self.get_href_as_absolute_url(&icon_href, &base_path_url) else {
                     continue;
                 };
                 info!(icon_url = icon_url.to_string(), "Manifest icon url found");
-                self.icon_urls.insert(icon_url.to_string());
+                self.insert_icon_url(&icon_url, IconSource::Manifest);
             }
         }
     }
@@ -175,19 +386,21 @@ impl IconFetcher {
         let mut icon_handles = HashMap::new();
         let mut icons = Vec::new();
 
-        for icon_url in &self.icon_urls {
-            let app_clone = self.app.clone();
+        for (icon_url, source) in &self.icon_urls {
+            let fetch_clone = self.fetch.clone();
             let url_clone = icon_url.clone();
+            let cancellable_clone = self.cancellable.clone();
             // Spawn in parallel on main thread
-            let handle =
-                glib::spawn_future_local(
-                    async move { app_clone.fetch.get_as_bytes(&url_clone).await },
-                );
+            let handle = glib::spawn_future_local(async move {
+                fetch_clone
+                    .get_as_bytes(&url_clone, Some(&cancellable_clone))
+                    .await
+            });
 
-            icon_handles.insert(icon_url, handle);
+            icon_handles.insert(icon_url, (*source, handle));
         }
 
-        for (url, handle) in icon_handles {
+        for (url, (source, handle)) in icon_handles {
             let Ok(Ok(response)) = handle.await else {
                 error!(url, "Failed to fetch image");
                 continue;
@@ -196,16 +409,110 @@ impl IconFetcher {
                 data: image_bytes,
                 mimetype,
             } = response;
-            let icon = match Icon::from_bytes(&image_bytes, mimetype) {
+            if !Icon::is_image_bytes(&image_bytes) {
+                error!(url, ?mimetype, "Response is not an image");
+                continue;
+            }
+            let mut icon = match Icon::from_bytes(&image_bytes, mimetype.clone()) {
                 Ok(icon) => icon,
                 Err(error) => {
                     error!(url, ?error, "Failed to convert image");
                     continue;
                 }
             };
+            icon.source = source;
+            self.fetched_images.push(FetchedImage {
+                url: url.clone(),
+                bytes: image_bytes,
+                mimetype,
+                source,
+                width: icon.pixbuf.width(),
+                height: icon.pixbuf.height(),
+            });
             icons.push((url.clone(), Rc::new(icon)));
         }
 
         icons
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL_MANIFEST: &str = r##"{
+        "name": "Example Mail for Work",
+        "short_name": " Mail ",
+        "theme_color": "#1c71d8",
+        "display": "standalone",
+        "icons": [
+            { "src": "/icons/192.png", "sizes": "192x192" },
+            { "sizes": "512x512" }
+        ]
+    }"##;
+    const FULLSCREEN_MANIFEST: &str = r#"{ "name": "Game", "display": "fullscreen" }"#;
+    const INVALID_THEME_COLOR_MANIFEST: &str =
+        r#"{ "name": "Mail", "theme_color": "not a color", "display": "browser" }"#;
+    const ICONS_ONLY_MANIFEST: &str = r#"{ "icons": [{ "src": "icon.png" }], "name": " " }"#;
+
+    fn parse_manifest(manifest_json: &str) -> ManifestJson {
+        serde_json::from_str(manifest_json).unwrap()
+    }
+
+    #[test]
+    fn manifest_icons_are_parsed() {
+        let manifest = parse_manifest(FULL_MANIFEST);
+
+        let icon_srcs: Vec<Option<String>> = manifest
+            .icons
+            .unwrap()
+            .into_iter()
+            .map(|icon| icon.src)
+            .collect();
+        assert_eq!(icon_srcs, vec![Some("/icons/192.png".to_string()), None]);
+    }
+
+    #[test]
+    fn short_name_goes_before_the_name() {
+        let metadata = ManifestMetadata::from_manifest(&parse_manifest(FULL_MANIFEST)).unwrap();
+
+        assert_eq!(metadata.get_name(), Some("Mail"));
+        assert_eq!(metadata.theme_color.as_deref(), Some("#1c71d8"));
+    }
+
+    #[test]
+    fn invalid_theme_color_is_dropped() {
+        let metadata =
+            ManifestMetadata::from_manifest(&parse_manifest(INVALID_THEME_COLOR_MANIFEST)).unwrap();
+
+        assert_eq!(metadata.get_name(), Some("Mail"));
+        assert!(metadata.theme_color.is_none());
+        assert!(!metadata.is_standalone());
+    }
+
+    #[test]
+    fn standalone_and_fullscreen_display_suggest_an_own_window() {
+        let standalone = ManifestMetadata::from_manifest(&parse_manifest(FULL_MANIFEST)).unwrap();
+        let fullscreen =
+            ManifestMetadata::from_manifest(&parse_manifest(FULLSCREEN_MANIFEST)).unwrap();
+
+        assert!(standalone.is_standalone());
+        assert!(fullscreen.is_standalone());
+    }
+
+    #[test]
+    fn manifest_without_metadata_has_none() {
+        assert!(ManifestMetadata::from_manifest(&parse_manifest(ICONS_ONLY_MANIFEST)).is_none());
+        assert!(ManifestMetadata::from_manifest(&parse_manifest("{}")).is_none());
+    }
+
+    #[test]
+    fn page_name_is_only_a_fallback() {
+        let metadata = ManifestMetadata {
+            page_name: Some("Example".to_string()),
+            ..ManifestMetadata::default()
+        };
+
+        assert_eq!(metadata.get_name(), Some("Example"));
+    }
+}
@@ -5,7 +5,12 @@ use common::{
     config::{self},
     utils::OnceLockExt,
 };
-use gtk::{glib::Propagation, prelude::WidgetExt};
+use gtk::{
+    gdk,
+    gio::prelude::ListModelExtManual,
+    glib::Propagation,
+    prelude::{DisplayExt, MonitorExt, WidgetExt},
+};
 use libadwaita::{ApplicationWindow, gtk::prelude::GtkWindowExt, prelude::AdwApplicationWindowExt};
 use std::rc::Rc;
 use view::View;
@@ -28,6 +33,10 @@ impl AppWindow {
             .icon_name(config::APP_ID.get_value())
             .content(&view.nav_split)
             .build();
+        // Striped header bars of libadwaita, so it is not mistaken for the stable build
+        if *config::IS_DEVEL.get_value() {
+            window.add_css_class("devel");
+        }
 
         Self {
             adw_window: window,
@@ -66,6 +75,15 @@ impl AppWindow {
             window_settings.height
         };
 
+        // A size saved on a bigger monitor should not overflow this one
+        let (width, height) = match Self::get_max_monitor_size() {
+            Some((max_width, max_height)) => (
+                width.min(max_width).max(Self::MIN_WIDTH),
+                height.min(max_height).max(Self::MIN_HEIGHT),
+            ),
+            None => (width, height),
+        };
+
         let is_maximized = window_settings.maximized;
 
         self.adw_window.set_default_width(width);
@@ -87,4 +105,20 @@ impl AppWindow {
             Propagation::Proceed
         });
     }
+
+    /// GTK has no work area of a monitor, and no monitor before the window is shown.
+    /// The largest connected monitor is the most the window can use.
+    fn get_max_monitor_size() -> Option<(i32, i32)> {
+        let display = gdk::Display::default()?;
+
+        display
+            .monitors()
+            .iter::<gdk::Monitor>()
+            .filter_map(Result::ok)
+            .map(|monitor| {
+                let geometry = monitor.geometry();
+                (geometry.width(), geometry.height())
+            })
+            .max_by_key(|(width, height)| i64::from(*width) * i64::from(*height))
+    }
 }
@@ -1,21 +1,24 @@
 mod about;
 mod app_menu;
 mod sidebar_page;
+mod upgrade_notes;
 
 use crate::application::{
     App,
     pages::{NavPage, Page},
 };
 use app_menu::AppMenu;
+use common::{config, desktop_file::migration::AppUpgradeNotes, utils::OnceLockExt};
 use gtk::{
     Button,
     prelude::{ButtonExt, WidgetExt},
 };
 use libadwaita::{
-    Breakpoint, BreakpointCondition, NavigationSplitView, glib::Value, prelude::AdwDialogExt,
+    Breakpoint, BreakpointCondition, NavigationPage, NavigationSplitView, Spinner, StatusPage,
+    glib::Value, prelude::AdwDialogExt,
 };
 use sidebar_page::SidebarPage;
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 pub struct View {
     pub app_menu: AppMenu,
@@ -23,6 +26,7 @@ pub struct View {
     pub nav_split: NavigationSplitView,
     pub breakpoint: Breakpoint,
     pub updated_button: Button,
+    upgrade_notes: Rc<RefCell<Vec<AppUpgradeNotes>>>,
 }
 impl View {
     pub fn new() -> Rc<Self> {
@@ -42,6 +46,7 @@ impl View {
             nav_split,
             breakpoint,
             updated_button,
+            upgrade_notes: Rc::new(RefCell::new(Vec::new())),
         })
     }
 
@@ -54,14 +59,33 @@ impl View {
         self.connect_updated_button(app);
     }
 
+    /// Placeholder content until the pages are ready
+    pub fn show_loading(self: &Rc<Self>) {
+        let spinner = Spinner::new();
+        let status_page = StatusPage::builder()
+            .title(t!("browsers.detecting"))
+            .child(&spinner)
+            .build();
+        let loading_page = NavigationPage::builder()
+            .title(config::APP_NAME.get_value())
+            .child(&status_page)
+            .build();
+
+        self.nav_split.set_content(Some(&loading_page));
+    }
+
     pub fn navigate(self: &Rc<Self>, app: &Rc<App>, page: &Page) {
         let nav_page = app.pages.get(page);
         nav_page.load_page(&self.nav_split);
         app.window.view.nav_split.set_show_content(true);
         app.window.view.sidebar.select_nav_row(app, page);
+        app.cache_settings
+            .borrow_mut()
+            .set_last_page(page.get_key());
     }
 
-    pub fn on_app_update(self: &Rc<Self>) {
+    pub fn on_app_update(self: &Rc<Self>, upgrade_notes: Vec<AppUpgradeNotes>) {
+        self.upgrade_notes.borrow_mut().extend(upgrade_notes);
         self.updated_button.set_visible(true);
     }
 
@@ -84,7 +108,7 @@ impl View {
         Button::builder()
             .icon_name("software-update-available-symbolic")
             .css_classes(["accent", "flat"])
-            .tooltip_text("Apps have been updated")
+            .tooltip_text(t!("upgrade_notes.updated_button"))
             .visible(false)
             .build()
     }
@@ -92,8 +116,16 @@ impl View {
     fn connect_updated_button(self: &Rc<Self>, app: &Rc<App>) {
         let app_clone = app.clone();
 
+        let upgrade_notes = self.upgrade_notes.clone();
+
         self.updated_button.connect_clicked(move |button| {
-            View::show_about(&app_clone);
+            let upgrade_notes = upgrade_notes.take();
+            if upgrade_notes.is_empty() {
+                View::show_about(&app_clone);
+            } else {
+                let dialog = upgrade_notes::get_dialog(&app_clone, &upgrade_notes);
+                dialog.present(Some(&app_clone.window.adw_window));
+            }
             button.set_visible(false);
         });
     }
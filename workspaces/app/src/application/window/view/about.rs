@@ -22,10 +22,20 @@ pub fn get_dialog() -> AboutDialog {
         _ => panic!("Could not convert license"),
     };
 
+    let version = if *config::IS_DEVEL.get_value() {
+        t!(
+            "app_menu.about.devel_version",
+            version = config::VERSION.get_value()
+        )
+        .to_string()
+    } else {
+        config::VERSION.get_value().clone()
+    };
+
     AboutDialog::builder()
         .application_icon(config::APP_ID.get_value())
         .application_name(config::APP_NAME.get_value())
-        .version(config::VERSION.get_value())
+        .version(version)
         .developer_name(config::DEVELOPER.get_value())
         .license_type(license)
         .issue_url(config::ISSUES_URL.get_value())
@@ -1,6 +1,5 @@
-use crate::application::{App, window::view::View};
+use crate::application::{App, pages::Page, window::view::View};
 use common::{
-    assets,
     config::{self},
     utils::OnceLockExt,
 };
@@ -49,9 +48,17 @@ impl AppMenu {
         let section_1 = Menu::new();
         let section_2 = Menu::new();
 
+        let backup = self.build_backup(app);
+        let restore = self.build_restore(app);
+        let import_foreign = self.build_import_foreign(app);
+        let change_browser = self.build_change_browser(app);
         let reset = self.build_reset(app);
         let about = self.build_about(app);
 
+        section_1.append_item(&backup);
+        section_1.append_item(&restore);
+        section_1.append_item(&import_foreign);
+        section_1.append_item(&change_browser);
         section_1.append_item(&reset);
         section_2.append_item(&about);
 
@@ -72,6 +79,56 @@ impl AppMenu {
         )
     }
 
+    fn build_backup(&self, app: &Rc<App>) -> MenuItem {
+        let app_clone = app.clone();
+        self.build_menu_item(
+            &t!("app_menu.backup.title"),
+            ("backup_apps", move || {
+                app_clone.navigate(&Page::WebApps);
+                app_clone.pages.get_web_apps().backup_all(&app_clone);
+            }),
+        )
+    }
+
+    fn build_restore(&self, app: &Rc<App>) -> MenuItem {
+        let app_clone = app.clone();
+        self.build_menu_item(
+            &t!("app_menu.restore.title"),
+            ("restore_apps", move || {
+                app_clone.navigate(&Page::WebApps);
+                app_clone.pages.get_web_apps().restore(&app_clone);
+            }),
+        )
+    }
+
+    fn build_import_foreign(&self, app: &Rc<App>) -> MenuItem {
+        let app_clone = app.clone();
+        self.build_menu_item(
+            &t!("app_menu.import_foreign.title"),
+            ("import_foreign_apps", move || {
+                app_clone.navigate(&Page::WebApps);
+                app_clone
+                    .pages
+                    .get_web_apps()
+                    .import_foreign_apps(&app_clone);
+            }),
+        )
+    }
+
+    fn build_change_browser(&self, app: &Rc<App>) -> MenuItem {
+        let app_clone = app.clone();
+        self.build_menu_item(
+            &t!("app_menu.change_browser.title"),
+            ("change_browser", move || {
+                app_clone.navigate(&Page::WebApps);
+                app_clone
+                    .pages
+                    .get_web_apps()
+                    .show_change_browser_dialog(&app_clone);
+            }),
+        )
+    }
+
     fn build_reset(&self, app: &Rc<App>) -> MenuItem {
         let app_clone = app.clone();
         self.build_menu_item(
@@ -93,10 +150,7 @@ impl AppMenu {
 
                 let app_clone_response = app_clone.clone();
                 dialog.connect_response(Some(dialog_ok), move |_, _| {
-                    if let Err(error) = assets::reset_config_files(&app_clone_response.dirs) {
-                        app_clone_response.show_error(&error);
-                    }
-                    app_clone_response.clone().restart();
+                    app_clone_response.reset();
                 });
 
                 dialog.present(Some(&app_clone.window.adw_window));
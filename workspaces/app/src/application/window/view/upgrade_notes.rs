@@ -0,0 +1,87 @@
+use crate::application::{App, window::view::View};
+use common::{config, desktop_file::migration::AppUpgradeNotes, utils::OnceLockExt};
+use gtk::{Align, Button, Expander, Label, prelude::ButtonExt};
+use libadwaita::{
+    ActionRow, Dialog, HeaderBar, PreferencesGroup, PreferencesPage, ToolbarView,
+    prelude::{AdwDialogExt, PreferencesGroupExt, PreferencesPageExt},
+};
+use std::{fmt::Write as _, rc::Rc};
+
+/// Changes made by migrations, grouped per app
+pub fn get_dialog(app: &Rc<App>, upgrade_notes: &[AppUpgradeNotes]) -> Dialog {
+    let prefs_page = PreferencesPage::new();
+    let toolbar = ToolbarView::new();
+    toolbar.add_top_bar(&HeaderBar::new());
+    toolbar.set_content(Some(&prefs_page));
+
+    let dialog = Dialog::builder()
+        .title(t!("upgrade_notes.title"))
+        .content_width(500)
+        .content_height(600)
+        .child(&toolbar)
+        .build();
+
+    let release_notes_button = Button::builder()
+        .label(t!("upgrade_notes.release_notes"))
+        .css_classes(["flat"])
+        .valign(Align::Center)
+        .build();
+    let intro_group = PreferencesGroup::builder()
+        .description(t!(
+            "upgrade_notes.description",
+            app_name = config::APP_NAME.get_value()
+        ))
+        .header_suffix(&release_notes_button)
+        .build();
+    prefs_page.add(&intro_group);
+
+    let app_clone = app.clone();
+    let dialog_clone = dialog.clone();
+    release_notes_button.connect_clicked(move |_| {
+        dialog_clone.close();
+        View::show_about(&app_clone);
+    });
+
+    for app_upgrade_notes in upgrade_notes {
+        prefs_page.add(&build_app_group(app_upgrade_notes));
+    }
+
+    dialog
+}
+
+fn build_app_group(app_upgrade_notes: &AppUpgradeNotes) -> PreferencesGroup {
+    let app_group = PreferencesGroup::builder()
+        .title(&app_upgrade_notes.app_name)
+        .build();
+    let mut raw_changes = String::new();
+
+    for migration_report in &app_upgrade_notes.migration_reports {
+        let migration_row = ActionRow::builder()
+            .title(t!(migration_report.description))
+            .build();
+        app_group.add(&migration_row);
+
+        for key in &migration_report.change_set.keys {
+            let _ = writeln!(raw_changes, "{key}");
+        }
+        for file in &migration_report.change_set.files {
+            let _ = writeln!(raw_changes, "{}", file.display());
+        }
+    }
+
+    let raw_changes_label = Label::builder()
+        .label(raw_changes.trim_end())
+        .css_classes(["monospace", "dim-label"])
+        .halign(Align::Start)
+        .selectable(true)
+        .wrap(true)
+        .build();
+    let raw_changes_expander = Expander::builder()
+        .label(t!("upgrade_notes.raw_changes"))
+        .child(&raw_changes_label)
+        .margin_top(6)
+        .build();
+    app_group.add(&raw_changes_expander);
+
+    app_group
+}
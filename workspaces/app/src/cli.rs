@@ -0,0 +1,294 @@
+use crate::application::pages::web_apps::web_app_view::icon_picker::IconPicker;
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand};
+use common::{
+    app_dirs::AppDirs,
+    assets,
+    browsers::{Browser, BrowserConfigs},
+    desktop_file::{DesktopFile, desktop_database},
+    fetch::Fetch,
+    policy,
+    url::parse_user_input,
+};
+use gtk::glib;
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    rc::Rc,
+};
+use tracing::{error, info};
+
+/// Without a command the window opens
+#[derive(Parser)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Opens a new web app for the first url, in the running window when there is one
+    #[arg(value_name = "URL")]
+    pub urls: Vec<String>,
+    /// Set by D-Bus activation, GTK handles it
+    #[arg(long, hide = true)]
+    pub gapplication_service: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a web app without opening the window
+    Create(CreateArgs),
+    /// List the web apps made with this app
+    List(ListArgs),
+    /// Delete a web app with its icon and profile
+    Delete(DeleteArgs),
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Name in the app launcher
+    #[arg(long)]
+    name: String,
+    /// Address of the site, a domain without a scheme gets https
+    #[arg(long)]
+    url: String,
+    /// Browser id like `org.mozilla.firefox`, or config name like `firefox`
+    #[arg(long)]
+    browser: String,
+    /// Use a separate browser profile
+    #[arg(long)]
+    isolate: bool,
+    /// Start the window maximized
+    #[arg(long)]
+    maximize: bool,
+    /// Don't fetch an icon from the site
+    #[arg(long)]
+    no_icon: bool,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Print the web apps as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    /// Id of the web app, as shown by `list`
+    id: String,
+}
+
+/// Output of `list --json`, only add fields so scripts keep working
+#[derive(Serialize)]
+struct ListedWebApp {
+    id: String,
+    name: String,
+    url: String,
+    browser_id: String,
+    /// `None` when the browser is not installed
+    browser_name: Option<String>,
+    isolated: bool,
+    path: PathBuf,
+}
+impl ListedWebApp {
+    fn new(desktop_file: &DesktopFile) -> Self {
+        Self {
+            id: desktop_file.get_id().unwrap_or_default(),
+            name: desktop_file.get_name().unwrap_or_default(),
+            url: desktop_file.get_url().unwrap_or_default(),
+            browser_id: desktop_file.get_browser_id().unwrap_or_default(),
+            browser_name: desktop_file
+                .get_browser()
+                .map(|browser| browser.get_name_with_installation()),
+            isolated: desktop_file.get_isolated().unwrap_or(false),
+            path: desktop_file.get_path(),
+        }
+    }
+}
+
+/// Runs without GTK windows, errors are printed to stderr
+pub fn run(command: Command) -> ExitCode {
+    let result = glib::MainContext::default().block_on(async move {
+        match command {
+            Command::Create(create_args) => create(&create_args).await,
+            Command::List(list_args) => list(&list_args).await,
+            Command::Delete(delete_args) => delete(&delete_args).await,
+        }
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Same setup as the window, without an icon theme
+async fn init() -> Result<(Rc<AppDirs>, Rc<BrowserConfigs>)> {
+    let app_dirs = AppDirs::new().context("Failed to get all needed directories")?;
+    policy::init(&app_dirs);
+    assets::init(&app_dirs)?;
+
+    let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+    // Probed every time, a command exits before a background check of the cache could finish
+    browser_configs.refresh().await;
+
+    Ok((app_dirs, browser_configs))
+}
+
+async fn create(create_args: &CreateArgs) -> Result<()> {
+    let (app_dirs, browser_configs) = init().await?;
+    let browser = find_browser(&browser_configs, &create_args.browser)?;
+    let Some(url) = parse_user_input(&create_args.url) else {
+        bail!("Invalid url: '{}'", create_args.url)
+    };
+
+    if create_args.isolate && !browser.can_isolate {
+        bail!("Browser '{}' can't isolate profiles", browser.get_name())
+    }
+    if create_args.maximize && !browser.can_start_maximized {
+        bail!("Browser '{}' can't start maximized", browser.get_name())
+    }
+
+    let mut desktop_file = DesktopFile::new(&browser_configs, &app_dirs);
+    desktop_file
+        .ensure_unique_id()
+        .context("Failed to create web app")?;
+    let app_id = desktop_file.get_id().context("No id on 'DesktopFile'")?;
+    desktop_file.set_name(create_args.name.trim());
+    desktop_file.set_url(url.as_str());
+    desktop_file.set_browser(&browser);
+    desktop_file.set_isolated(create_args.isolate);
+    desktop_file.set_maximized(create_args.maximize);
+    desktop_file.set_profile_path(Path::new(""));
+    desktop_file.set_icon_path(Path::new(""));
+
+    // Before fetching the icon, so an invalid app leaves nothing behind
+    desktop_file.validate().context("Invalid web app")?;
+
+    if !create_args.no_icon {
+        let fetch = Rc::new(Fetch::new());
+        if let Err(error) =
+            IconPicker::save_first_online_icon(&fetch, &app_dirs, &mut desktop_file).await
+        {
+            error!("{error:?}");
+            eprintln!("Warning: saving without an icon, {error}");
+        }
+    }
+
+    desktop_file.save().context("Failed to save web app")?;
+    // Inside the sandbox the portal asks the user, the window is not there to do it later
+    if desktop_file.needs_launcher_install()
+        && !desktop_file
+            .clone()
+            .install_launcher()
+            .await
+            .context("Failed to install launcher")?
+    {
+        eprintln!("Warning: the launcher was not installed, open the app to try again");
+    }
+    desktop_database::flush();
+    info!(app_id, browser_id = browser.id, "Created web app");
+    println!("{app_id}");
+
+    Ok(())
+}
+
+async fn list(list_args: &ListArgs) -> Result<()> {
+    let (app_dirs, browser_configs) = init().await?;
+    let listed_web_apps: Vec<ListedWebApp> =
+        DesktopFile::get_all_owned(&browser_configs, &app_dirs)
+            .iter()
+            .map(ListedWebApp::new)
+            .collect();
+
+    if list_args.json {
+        let json = serde_json::to_string_pretty(&listed_web_apps)
+            .context("Failed to serialize web apps")?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    for listed_web_app in &listed_web_apps {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            listed_web_app.id,
+            listed_web_app.name,
+            listed_web_app.url,
+            listed_web_app
+                .browser_name
+                .as_ref()
+                .unwrap_or(&listed_web_app.browser_id),
+            if listed_web_app.isolated {
+                "isolated"
+            } else {
+                "shared"
+            }
+        );
+    }
+
+    Ok(())
+}
+
+async fn delete(delete_args: &DeleteArgs) -> Result<()> {
+    let (app_dirs, browser_configs) = init().await?;
+    let matches: Vec<DesktopFile> = DesktopFile::get_all_owned(&browser_configs, &app_dirs)
+        .into_iter()
+        .filter(|desktop_file| desktop_file.get_id().as_deref() == Some(delete_args.id.as_str()))
+        .collect();
+
+    let desktop_file = match matches.as_slice() {
+        [] => bail!("No web app with id '{}'", delete_args.id),
+        [desktop_file] => desktop_file,
+        _ => {
+            let paths = matches
+                .iter()
+                .map(|desktop_file| desktop_file.get_path().display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!(
+                "More than one web app with id '{}', remove one by hand: {paths}",
+                delete_args.id
+            )
+        }
+    };
+
+    desktop_file.delete()?;
+    desktop_database::flush();
+    println!("{}", desktop_file.get_path().display());
+
+    Ok(())
+}
+
+/// An id is exact, a config name picks the first installation of that browser
+fn find_browser(browser_configs: &BrowserConfigs, id_or_name: &str) -> Result<Rc<Browser>> {
+    let browsers: Vec<Rc<Browser>> = browser_configs
+        .get_all_browsers()
+        .into_iter()
+        .filter(|browser| !browser.is_no_browser())
+        .collect();
+
+    if let Some(browser) = browsers
+        .iter()
+        .find(|browser| browser.id == id_or_name)
+        .or_else(|| {
+            browsers
+                .iter()
+                .find(|browser| browser.config_name.eq_ignore_ascii_case(id_or_name))
+        })
+    {
+        return Ok(browser.clone());
+    }
+
+    let installed = browsers
+        .iter()
+        .map(|browser| format!("{} ({})", browser.config_name, browser.id))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if installed.is_empty() {
+        bail!("Unknown browser '{id_or_name}', no browsers are installed")
+    }
+    bail!("Unknown browser '{id_or_name}', installed browsers: {installed}")
+}
@@ -0,0 +1,309 @@
+use common::{
+    app_dirs::AppDirs,
+    config::{self},
+    utils::{self, OnceLockExt},
+};
+use regex::Regex;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, mpsc},
+    thread,
+};
+use tracing::{Level, error, info};
+use tracing_subscriber::{
+    EnvFilter, Registry, fmt::MakeWriter, layer::SubscriberExt, reload, util::SubscriberInitExt,
+};
+
+static RELOAD_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static LOG_LEVEL: Mutex<Level> = Mutex::new(Level::INFO);
+
+const LOG_FILE_NAME: &str = "web-app-hub.log";
+const MAX_FILE_SIZE: u64 = 1_000_000;
+/// The current file and the rotated ones
+const MAX_FILES: usize = 3;
+
+/// Logs to stderr and to a file in the logs dir, the level can be changed while running
+pub fn init(app_dirs: Option<&AppDirs>) {
+    let mut log_level = if cfg!(debug_assertions) {
+        Level::DEBUG
+    } else {
+        Level::INFO
+    };
+    log_level = utils::env::get_log_level().unwrap_or(log_level);
+    if let Ok(mut current_level) = LOG_LEVEL.lock() {
+        *current_level = log_level;
+    }
+
+    let (filter, reload_handle) = reload::Layer::new(build_filter(log_level));
+    let _ = RELOAD_HANDLE.set(reload_handle);
+
+    // Keeps stdout for command line output
+    let stderr_layer = tracing_subscriber::fmt::layer().with_writer(io::stderr);
+    let file_layer = app_dirs.map(|app_dirs| {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(LogFile::new(&app_dirs.app_data_logs))
+    });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(stderr_layer)
+        .with(file_layer)
+        .init();
+}
+
+pub fn get_log_level() -> Level {
+    LOG_LEVEL
+        .lock()
+        .map(|log_level| *log_level)
+        .unwrap_or(Level::INFO)
+}
+
+pub fn set_log_level(log_level: Level) {
+    let Some(reload_handle) = RELOAD_HANDLE.get() else {
+        return;
+    };
+    if let Err(error) = reload_handle.modify(|filter| *filter = build_filter(log_level)) {
+        error!("Failed to change log level: {error:?}");
+        return;
+    }
+    if let Ok(mut current_level) = LOG_LEVEL.lock() {
+        *current_level = log_level;
+    }
+    info!("Log level changed to: {log_level}");
+}
+
+pub fn get_log_file_path(app_dirs: &AppDirs) -> PathBuf {
+    app_dirs.app_data_logs.join(LOG_FILE_NAME)
+}
+
+/// The last part of the current log file, starting at a whole line
+pub fn read_tail(app_dirs: &AppDirs, max_bytes: u64) -> io::Result<String> {
+    let mut file = File::open(get_log_file_path(app_dirs))?;
+    let size = file.metadata()?.len();
+    let start = size.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes).to_string();
+
+    if start == 0 {
+        return Ok(text);
+    }
+    Ok(text
+        .split_once('\n')
+        .map(|(_, text)| text.to_string())
+        .unwrap_or(text))
+}
+
+/// Disable > info logging for external crates
+fn build_filter(log_level: Level) -> EnvFilter {
+    EnvFilter::new(format!(
+        "{}={log_level},common={log_level}",
+        config::APP_NAME_UNDERSCORE.get_value()
+    ))
+}
+
+/// Lines are written on a separate thread, logging never waits on the disk
+struct LogFile {
+    sender: mpsc::Sender<Vec<u8>>,
+}
+impl LogFile {
+    fn new(logs_dir: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel::<Vec<u8>>();
+        let logs_dir = logs_dir.to_path_buf();
+
+        thread::spawn(move || {
+            let mut log_file_writer = LogFileWriter::new(logs_dir);
+            for bytes in receiver {
+                log_file_writer.write(&bytes);
+            }
+        });
+
+        Self { sender }
+    }
+}
+impl<'a> MakeWriter<'a> for LogFile {
+    type Writer = LogFileSender;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogFileSender {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+struct LogFileSender {
+    sender: mpsc::Sender<Vec<u8>>,
+}
+impl Write for LogFileSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A stopped writer thread should not break logging to stderr
+        let _ = self.sender.send(buf.to_vec());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct LogFileWriter {
+    logs_dir: PathBuf,
+    file: Option<File>,
+    size: u64,
+    max_file_size: u64,
+    /// Query strings and fragments of urls can hold tokens
+    url_re: Option<Regex>,
+}
+impl LogFileWriter {
+    fn new(logs_dir: PathBuf) -> Self {
+        Self {
+            logs_dir,
+            file: None,
+            size: 0,
+            max_file_size: MAX_FILE_SIZE,
+            url_re: Regex::new(r#"(https?://[^\s?#"']+)(\?[^\s#"']*)?(#[^\s"']*)?"#).ok(),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let text = String::from_utf8_lossy(bytes);
+        let text = match &self.url_re {
+            Some(url_re) => url_re
+                .replace_all(&text, |captures: &regex::Captures| {
+                    let mut url = captures[1].to_string();
+                    if captures.get(2).is_some() {
+                        url.push_str("?<redacted>");
+                    }
+                    if captures.get(3).is_some() {
+                        url.push_str("#<redacted>");
+                    }
+                    url
+                })
+                .to_string(),
+            None => text.to_string(),
+        };
+
+        if self.size >= self.max_file_size {
+            self.rotate();
+        }
+        let Some(file) = self.get_file() else {
+            return;
+        };
+        if file.write_all(text.as_bytes()).is_ok() {
+            self.size += text.len() as u64;
+        }
+    }
+
+    fn get_file(&mut self) -> Option<&mut File> {
+        if self.file.is_none() {
+            fs::create_dir_all(&self.logs_dir).ok()?;
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.logs_dir.join(LOG_FILE_NAME))
+                .ok()?;
+            self.size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            self.file = Some(file);
+        }
+
+        self.file.as_mut()
+    }
+
+    /// `web-app-hub.log` becomes `web-app-hub.log.1`, the oldest file is removed
+    fn rotate(&mut self) {
+        self.file = None;
+        self.size = 0;
+
+        let path = |index: usize| {
+            if index == 0 {
+                self.logs_dir.join(LOG_FILE_NAME)
+            } else {
+                self.logs_dir.join(format!("{LOG_FILE_NAME}.{index}"))
+            }
+        };
+
+        let _ = fs::remove_file(path(MAX_FILES - 1));
+        for index in (0..MAX_FILES - 1).rev() {
+            let _ = fs::rename(path(index), path(index + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_lines(log_file_writer: &mut LogFileWriter, lines: &[&str]) {
+        for line in lines {
+            log_file_writer.write(format!("{line}\n").as_bytes());
+        }
+    }
+
+    fn read_log(logs_dir: &Path, file_name: &str) -> String {
+        fs::read_to_string(logs_dir.join(file_name)).unwrap()
+    }
+
+    #[test]
+    fn query_and_fragment_of_urls_are_redacted() {
+        let logs_dir = tempfile::tempdir().unwrap();
+        let mut log_file_writer = LogFileWriter::new(logs_dir.path().to_path_buf());
+
+        write_lines(
+            &mut log_file_writer,
+            &[
+                "Fetching https://example.com/icon.png?token=secret",
+                "Opened url='https://example.com/callback#access_token=secret&state=1'",
+                "Loaded http://example.com/a?b=secret#c, done",
+                "Plain https://example.com/mail/inbox and /home/user/file?.txt",
+            ],
+        );
+
+        assert_eq!(
+            read_log(logs_dir.path(), LOG_FILE_NAME),
+            "Fetching https://example.com/icon.png?<redacted>\n\
+            Opened url='https://example.com/callback#<redacted>'\n\
+            Loaded http://example.com/a?<redacted>#<redacted> done\n\
+            Plain https://example.com/mail/inbox and /home/user/file?.txt\n"
+        );
+    }
+
+    #[test]
+    fn full_log_file_is_rotated_and_old_files_are_removed() {
+        let logs_dir = tempfile::tempdir().unwrap();
+        let mut log_file_writer = LogFileWriter::new(logs_dir.path().to_path_buf());
+        log_file_writer.max_file_size = 10;
+
+        write_lines(
+            &mut log_file_writer,
+            &["first line", "second line", "third line", "fourth line"],
+        );
+
+        let mut file_names: Vec<String> = fs::read_dir(logs_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        file_names.sort();
+        assert_eq!(
+            file_names,
+            [
+                LOG_FILE_NAME.to_string(),
+                format!("{LOG_FILE_NAME}.1"),
+                format!("{LOG_FILE_NAME}.2")
+            ]
+        );
+        assert_eq!(read_log(logs_dir.path(), LOG_FILE_NAME), "fourth line\n");
+        assert_eq!(
+            read_log(logs_dir.path(), &format!("{LOG_FILE_NAME}.1")),
+            "third line\n"
+        );
+        assert_eq!(
+            read_log(logs_dir.path(), &format!("{LOG_FILE_NAME}.2")),
+            "second line\n"
+        );
+    }
+}
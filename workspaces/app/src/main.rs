@@ -1,39 +1,29 @@
 mod application;
+mod cli;
+mod logging;
 
-use application::App;
+use application::{App, CurrentApp, dbus_service};
+use clap::Parser;
+use cli::Cli;
 use common::{
+    app_dirs::AppDirs,
     config::{self},
+    desktop_file::{desktop_database, trash},
+    url::parse_web_input,
     utils::{self, OnceLockExt},
 };
-use libadwaita::gio::prelude::{ApplicationExt, ApplicationExtManual};
+use libadwaita::gio::{
+    self,
+    prelude::{ApplicationExt, ApplicationExtManual, FileExt},
+};
 use rust_i18n::locale;
-use tracing::{Level, debug, info};
-use tracing_subscriber::{FmtSubscriber, util::SubscriberInitExt};
+use std::process::ExitCode;
+use tracing::{debug, error, info};
 
 #[macro_use]
 extern crate rust_i18n;
 i18n!("translations", fallback = "en");
 
-fn init_logging() {
-    let mut log_level = if cfg!(debug_assertions) {
-        Level::DEBUG
-    } else {
-        Level::INFO
-    };
-    log_level = utils::env::get_log_level().unwrap_or(log_level);
-    // Disable > info logging for external crates
-    let filter = format!(
-        "{}={log_level},common={log_level}",
-        config::APP_NAME_UNDERSCORE.get_value()
-    );
-
-    let logger = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .with_env_filter(filter)
-        .finish();
-    logger.init();
-}
-
 fn init_locale() {
     if let Some(language) = utils::env::get_language() {
         debug!(locale = language, "Trying to use user locale");
@@ -69,25 +59,96 @@ fn init_locale() {
     info!(locale = &*locale(), "Init locale");
 }
 
-fn main() {
+/// Deleted web apps of a previous run that were still waiting for an undo toast, or left behind
+/// by a crash. Not on shutdown, a large profile would keep the app from closing.
+fn purge_trash() {
+    match AppDirs::new() {
+        Ok(app_dirs) => {
+            let staging_dirs = trash::get_staging_dirs(&app_dirs);
+            if !staging_dirs.is_empty() {
+                gio::spawn_blocking(move || trash::purge_staging_dirs(&staging_dirs));
+            }
+        }
+        Err(error) => error!("{error:?}"),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
     if cfg!(debug_assertions) {
-        println!("======== Running debug build ========");
+        eprintln!("======== Running debug build ========");
     }
 
     config::init();
-    init_logging();
+    // Without the app dirs there is no log file, stderr still works
+    let app_dirs = AppDirs::new()
+        .inspect_err(|error| eprintln!("{error:?}"))
+        .ok();
+    logging::init(app_dirs.as_deref());
     info!("Version: {}", config::VERSION.get_value());
     init_locale();
 
     config::log_all_values_debug();
 
+    if let Some(command) = cli.command {
+        return cli::run(command);
+    }
+
     let adw_application = libadwaita::Application::builder()
         .application_id(config::APP_ID.get_value())
+        .flags(gio::ApplicationFlags::HANDLES_OPEN)
         .build();
 
-    adw_application.connect_activate(|adw_application| {
-        App::new(adw_application).init();
+    let current_app = CurrentApp::default();
+    let current_app_clone = current_app.clone();
+    adw_application.connect_startup(move |adw_application| {
+        if let Err(error) = dbus_service::register(adw_application, &current_app_clone) {
+            error!("Failed to register D-Bus interface: {error:?}");
+        }
+        purge_trash();
+    });
+
+    adw_application.connect_shutdown(|_| {
+        // A save right before closing still reaches the launchers
+        desktop_database::flush();
+    });
+
+    let current_app_clone = current_app.clone();
+    adw_application.connect_activate(move |adw_application| {
+        App::activate(adw_application, &current_app_clone);
+    });
+
+    // Runs in the first instance, a second process only forwards the urls
+    adw_application.connect_open(move |adw_application, files, _hint| {
+        let Some(app) = App::get_or_activate(adw_application, &current_app) else {
+            error!("The app did not start, can't open the url");
+            return;
+        };
+        let uris: Vec<String> = files.iter().map(|file| file.uri().to_string()).collect();
+        debug!(?uris, "Opening urls");
+
+        if let Some(uri) = uris
+            .iter()
+            .find(|uri| parse_web_input(uri).is_some())
+            .or_else(|| uris.first())
+        {
+            app.open_url_input(uri);
+        }
     });
 
-    adw_application.run();
+    // The arguments are parsed already, GTK only gets the ones it handles
+    let mut gtk_args: Vec<String> = std::env::args().take(1).collect();
+    if cli.gapplication_service {
+        gtk_args.push("--gapplication-service".to_string());
+    }
+    // GIO would take a bare domain for a relative file path
+    gtk_args.extend(
+        cli.urls.iter().map(|input| {
+            parse_web_input(input).map_or_else(|| input.clone(), |url| url.to_string())
+        }),
+    );
+    adw_application.run_with_args(&gtk_args);
+
+    ExitCode::SUCCESS
 }
@@ -1,6 +1,6 @@
 use crate::{
     config::{self},
-    utils::OnceLockExt,
+    utils::{OnceLockExt, env},
 };
 use anyhow::{Context, Result};
 use gtk::glib;
@@ -9,7 +9,55 @@ use std::{
     path::{Path, PathBuf},
     rc::Rc,
 };
-use tracing::debug;
+use tracing::{debug, info};
+
+/// Dirs of the user and the system the app dirs are made in
+#[derive(Debug, Clone)]
+pub struct BaseDirs {
+    pub home: PathBuf,
+    pub data: PathBuf,
+    pub config: PathBuf,
+    pub cache: PathBuf,
+    pub system_data: Vec<PathBuf>,
+}
+impl BaseDirs {
+    /// Read by glib once, a later change of the environment is not seen
+    pub fn from_glib() -> Self {
+        Self {
+            home: glib::home_dir(),
+            data: glib::user_data_dir(),
+            config: glib::user_config_dir(),
+            cache: glib::user_cache_dir(),
+            system_data: glib::system_data_dirs(),
+        }
+    }
+
+    /// The `XDG_*` variables as they are now, glib for the ones that are not set. A relative
+    /// path is ignored, like the spec says.
+    pub fn from_env() -> Self {
+        let glib_dirs = Self::from_glib();
+        let get_dir = |name: &str| {
+            std::env::var_os(name)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        let system_data = std::env::var_os("XDG_DATA_DIRS")
+            .map(|value| {
+                std::env::split_paths(&value)
+                    .filter(|path| path.is_absolute())
+                    .collect::<Vec<PathBuf>>()
+            })
+            .filter(|paths| !paths.is_empty());
+
+        Self {
+            home: get_dir("HOME").unwrap_or(glib_dirs.home),
+            data: get_dir("XDG_DATA_HOME").unwrap_or(glib_dirs.data),
+            config: get_dir("XDG_CONFIG_HOME").unwrap_or(glib_dirs.config),
+            cache: get_dir("XDG_CACHE_HOME").unwrap_or(glib_dirs.cache),
+            system_data: system_data.unwrap_or(glib_dirs.system_data),
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct AppDirs {
@@ -18,45 +66,83 @@ pub struct AppDirs {
     pub user_config: PathBuf,
     pub user_cache: PathBuf,
     pub user_applications: PathBuf,
+    /// Launchers in `user_applications` show up on the host. Not inside the sandbox without
+    /// the filesystem permission, launchers go through the portal then.
+    pub has_host_applications: bool,
+    /// Created on demand, most users never start an app at login
+    pub user_autostart: PathBuf,
     pub user_flatpak: PathBuf,
     pub system_data: Vec<PathBuf>,
-    pub system_icons: Vec<PathBuf>,
+    pub(crate) system_icons: Vec<PathBuf>,
     pub app_data: PathBuf,
     pub app_config: PathBuf,
     pub app_cache: PathBuf,
     pub app_data_profiles: PathBuf,
     pub app_data_icons: PathBuf,
+    /// Created on demand, icons fetched online per site
+    pub app_data_icon_cache: PathBuf,
+    /// Created on demand, deleted web apps that can still be restored
+    pub app_data_trash: PathBuf,
+    /// Created on demand by the file logger
+    pub app_data_logs: PathBuf,
+    /// Created on demand, copies of launchers installed through the portal, the sandbox can't read
+    /// those back
+    pub app_data_launchers: PathBuf,
     pub app_config_browser_configs: PathBuf,
     pub app_config_browser_desktop_files: PathBuf,
+    /// Configs of the user, a file here replaces the shipped config with the same name
+    pub app_config_browser_config_overrides: PathBuf,
+    /// Desktop files of the user, a file here replaces the shipped one with the same name
+    pub app_config_browser_desktop_file_overrides: PathBuf,
+    pub app_config_site_hints: PathBuf,
 }
 impl AppDirs {
+    /// Made in the dirs of the environment, see `BaseDirs::from_env`
     pub fn new() -> Result<Rc<Self>> {
-        Rc::new(Self::default());
+        let app_dirs = Self::from_base_dirs(BaseDirs::from_env());
+        app_dirs.init()?;
+        Ok(Rc::new(app_dirs))
+    }
 
-        let user_home = glib::home_dir();
-        let user_data = glib::user_data_dir();
-        let user_config = glib::user_config_dir();
-        let user_cache = glib::user_cache_dir();
-        let user_applications = Self::build_applications_path(&user_data)?;
+    /// Only the paths, nothing is created before `init`
+    pub fn from_base_dirs(base_dirs: BaseDirs) -> Self {
+        let BaseDirs {
+            home: user_home,
+            data: user_data,
+            config: user_config,
+            cache: user_cache,
+            system_data,
+        } = base_dirs;
+        let has_host_applications = Self::detect_host_applications();
+        let user_applications = Self::build_user_applications_path(&user_home, &user_data);
+        let user_autostart = user_config.join("autostart");
         let user_flatpak = Self::build_flatpak_path(&user_home);
 
-        let system_data = glib::system_data_dirs();
         let system_icons = Self::build_system_icon_paths(&system_data);
 
         let app_data = user_data.join(config::APP_NAME_HYPHEN.get_value());
         let app_config = user_config.join(config::APP_NAME_HYPHEN.get_value());
         let app_cache = user_cache.join(config::APP_NAME_HYPHEN.get_value());
-        let app_data_profiles = Self::build_profiles_path(&app_data)?;
-        let app_data_icons = Self::build_icons_path(&app_data)?;
-        let app_config_browser_configs = Self::build_browser_configs_path(&app_config)?;
-        let app_config_browser_desktop_files = Self::build_browser_desktop_files_path(&app_config)?;
+        let app_data_profiles = app_data.join("profiles");
+        let app_data_icons = app_data.join("icons");
+        let app_data_icon_cache = app_data.join("icon-cache");
+        let app_data_trash = app_data.join("trash");
+        let app_data_logs = app_data.join("logs");
+        let app_data_launchers = app_data.join("launchers");
+        let app_config_browser_configs = app_config.join("browsers");
+        let app_config_browser_desktop_files = app_config.join("desktop-files");
+        let app_config_browser_config_overrides = app_config.join("browsers.d");
+        let app_config_browser_desktop_file_overrides = app_config.join("desktop-files.d");
+        let app_config_site_hints = app_config.join("site-hints");
 
-        Ok(Rc::new(Self {
+        Self {
             user_home,
             user_data,
             user_config,
             user_cache,
             user_applications,
+            has_host_applications,
+            user_autostart,
             user_flatpak,
 
             system_data,
@@ -67,9 +153,58 @@ impl AppDirs {
             app_cache,
             app_data_profiles,
             app_data_icons,
+            app_data_icon_cache,
+            app_data_trash,
+            app_data_logs,
+            app_data_launchers,
             app_config_browser_configs,
             app_config_browser_desktop_files,
-        }))
+            app_config_browser_config_overrides,
+            app_config_browser_desktop_file_overrides,
+            app_config_site_hints,
+        }
+    }
+
+    /// Creates the missing dirs, except the ones that are made on demand. The host applications
+    /// dir is left alone without access to it.
+    pub fn init(&self) -> Result<()> {
+        if self.has_host_applications {
+            Self::create_dir(&self.user_applications, "user applications")?;
+        } else {
+            info!(
+                "No access to the host applications dir: {}",
+                self.user_applications.display()
+            );
+        }
+
+        let dirs = [
+            (&self.app_data_profiles, "profiles"),
+            (&self.app_data_icons, "icons"),
+            (&self.app_config_browser_configs, "browsers"),
+            (
+                &self.app_config_browser_desktop_files,
+                "browser desktop-files",
+            ),
+            (
+                &self.app_config_browser_config_overrides,
+                "browser config overrides",
+            ),
+            (
+                &self.app_config_browser_desktop_file_overrides,
+                "browser desktop file overrides",
+            ),
+            (&self.app_config_site_hints, "site hints"),
+        ];
+        for (path, label) in dirs {
+            Self::create_dir(path, label)?;
+        }
+
+        Ok(())
+    }
+
+    /// The `icons` dirs of the system data dirs that existed when the dirs were built
+    pub fn system_icons(&self) -> &[PathBuf] {
+        &self.system_icons
     }
 
     fn build_system_icon_paths(system_data: &[PathBuf]) -> Vec<PathBuf> {
@@ -81,96 +216,270 @@ impl AppDirs {
             .collect()
     }
 
-    fn build_applications_path(user_data: &Path) -> Result<PathBuf> {
-        let user_applications_path = user_data.join("applications");
-
-        debug!(
-            "Using system applications path: {}",
-            user_applications_path.display()
-        );
-
-        if !user_applications_path.is_dir() {
-            fs::create_dir_all(&user_applications_path).context(format!(
-                "Could not create user applications dir: {}",
-                user_applications_path.display()
-            ))?;
+    /// The data dir of the sandbox is private, the host one is read from the home dir like
+    /// `flatpak` does
+    fn build_user_applications_path(home: &Path, user_data: &Path) -> PathBuf {
+        let applications_dir_name = "applications";
+        if !env::is_flatpak_container() {
+            return user_data.join(applications_dir_name);
         }
 
-        Ok(user_applications_path)
+        let host_data = std::env::var_os("HOST_XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .unwrap_or_else(|| home.join(".local").join("share"));
+        host_data.join(applications_dir_name)
     }
 
-    fn build_profiles_path(app_data: &Path) -> Result<PathBuf> {
-        let profiles_dir_name = "profiles";
-        let profiles_path = app_data.join(profiles_dir_name);
+    fn detect_host_applications() -> bool {
+        !env::is_flatpak_container()
+            || Self::has_applications_grant(&env::get_flatpak_filesystems())
+    }
 
-        debug!("Using profile path: {}", profiles_path.display());
+    /// A grant on the dir or one of its parents, `:ro` is not enough to save launchers. A negated
+    /// grant on one of them wins.
+    fn has_applications_grant(filesystems: &[String]) -> bool {
+        let covering_filesystems = [
+            "host",
+            "home",
+            "~",
+            "~/.local",
+            "~/.local/share",
+            "~/.local/share/applications",
+            "xdg-data",
+            "xdg-data/applications",
+        ];
+        let is_covering =
+            |filesystem: &str| covering_filesystems.contains(&filesystem.trim_end_matches('/'));
+        let mut has_grant = false;
 
-        if !profiles_path.is_dir() {
-            fs::create_dir_all(&profiles_path).context(format!(
-                "Could not create profiles dir: {}",
-                profiles_path.display()
-            ))?;
+        for filesystem in filesystems {
+            if let Some(negated) = filesystem.strip_prefix('!') {
+                if is_covering(negated) {
+                    return false;
+                }
+                continue;
+            }
+            let (path, mode) = filesystem.rsplit_once(':').unwrap_or((filesystem, "rw"));
+            if mode != "ro" && is_covering(path) {
+                has_grant = true;
+            }
         }
 
-        Ok(profiles_path)
+        has_grant
     }
 
-    fn build_icons_path(app_data: &Path) -> Result<PathBuf> {
-        let icons_dir_name = "icons";
-        let icons_path = app_data.join(icons_dir_name);
-
-        debug!("Using icons path: {}", icons_path.display());
+    /// Creates the dir when it is missing, `label` is only for the log and the error
+    fn create_dir(path: &Path, label: &str) -> Result<()> {
+        debug!("Using {label} path: {}", path.display());
 
-        if !icons_path.is_dir() {
-            fs::create_dir_all(&icons_path).context(format!(
-                "Could not create icons dir: {}",
-                icons_path.display()
-            ))?;
+        if !path.is_dir() {
+            fs::create_dir_all(path)
+                .context(format!("Could not create {label} dir: {}", path.display()))?;
         }
 
-        Ok(icons_path)
+        Ok(())
     }
 
-    fn build_browser_configs_path(app_config: &Path) -> Result<PathBuf> {
-        let browsers_dir_name = "browsers";
-        let browser_configs_path = app_config.join(browsers_dir_name);
+    fn build_flatpak_path(home: &Path) -> PathBuf {
+        let flatpak_path = home.join(".var").join("app");
+
+        debug!("Using flatpak path: {}", flatpak_path.display());
+
+        flatpak_path
+    }
+}
 
-        debug!("Using browsers path: {}", browser_configs_path.display());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if !browser_configs_path.is_dir() {
-            fs::create_dir_all(&browser_configs_path).context(format!(
-                "Could not create browsers dir: {}",
-                browser_configs_path.display()
-            ))?;
+    fn get_base_dirs(root: &Path) -> BaseDirs {
+        BaseDirs {
+            home: root.join("home"),
+            data: root.join("data"),
+            config: root.join("config"),
+            cache: root.join("cache"),
+            system_data: vec![root.join("system-a"), root.join("system-b")],
         }
+    }
 
-        Ok(browser_configs_path)
+    fn get_app_dirs(root: &Path) -> AppDirs {
+        config::init();
+        AppDirs::from_base_dirs(get_base_dirs(root))
     }
 
-    fn build_browser_desktop_files_path(app_config: &Path) -> Result<PathBuf> {
-        let browsers_desktop_files_dir_name = "desktop-files";
-        let browser_desktop_files_path = app_config.join(browsers_desktop_files_dir_name);
+    #[test]
+    fn paths_are_made_in_the_base_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let app_dirs = get_app_dirs(root);
+        let app_name = config::APP_NAME_HYPHEN.get_value();
+        let app_data = root.join("data").join(app_name);
+        let app_config = root.join("config").join(app_name);
+
+        assert_eq!(app_dirs.user_home, root.join("home"));
+        assert_eq!(app_dirs.user_data, root.join("data"));
+        assert_eq!(app_dirs.user_config, root.join("config"));
+        assert_eq!(app_dirs.user_cache, root.join("cache"));
+        assert_eq!(
+            app_dirs.user_applications,
+            root.join("data").join("applications")
+        );
+        assert!(app_dirs.has_host_applications);
+        assert_eq!(
+            app_dirs.user_autostart,
+            root.join("config").join("autostart")
+        );
+        assert_eq!(app_dirs.user_flatpak, root.join("home").join(".var/app"));
+        assert_eq!(
+            app_dirs.system_data,
+            vec![root.join("system-a"), root.join("system-b")]
+        );
 
-        debug!(
-            "Using browser desktop-files path: {}",
-            browser_desktop_files_path.display()
+        assert_eq!(app_dirs.app_data, app_data);
+        assert_eq!(app_dirs.app_config, app_config);
+        assert_eq!(app_dirs.app_cache, root.join("cache").join(app_name));
+        assert_eq!(app_dirs.app_data_profiles, app_data.join("profiles"));
+        assert_eq!(app_dirs.app_data_icons, app_data.join("icons"));
+        assert_eq!(app_dirs.app_data_icon_cache, app_data.join("icon-cache"));
+        assert_eq!(app_dirs.app_data_trash, app_data.join("trash"));
+        assert_eq!(app_dirs.app_data_logs, app_data.join("logs"));
+        assert_eq!(app_dirs.app_data_launchers, app_data.join("launchers"));
+        assert_eq!(
+            app_dirs.app_config_browser_configs,
+            app_config.join("browsers")
+        );
+        assert_eq!(
+            app_dirs.app_config_browser_desktop_files,
+            app_config.join("desktop-files")
+        );
+        assert_eq!(
+            app_dirs.app_config_browser_config_overrides,
+            app_config.join("browsers.d")
+        );
+        assert_eq!(
+            app_dirs.app_config_browser_desktop_file_overrides,
+            app_config.join("desktop-files.d")
+        );
+        assert_eq!(
+            app_dirs.app_config_site_hints,
+            app_config.join("site-hints")
         );
+    }
+
+    #[test]
+    fn nothing_is_created_before_init() {
+        let root = tempfile::tempdir().unwrap();
+        get_app_dirs(root.path());
+
+        assert_eq!(fs::read_dir(root.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn init_creates_the_missing_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(root.path());
 
-        if !browser_desktop_files_path.is_dir() {
-            fs::create_dir_all(&browser_desktop_files_path).context(format!(
-                "Could not create browser desktop-files dir: {}",
-                browser_desktop_files_path.display()
-            ))?;
+        app_dirs.init().unwrap();
+
+        for path in [
+            &app_dirs.user_applications,
+            &app_dirs.app_data_profiles,
+            &app_dirs.app_data_icons,
+            &app_dirs.app_config_browser_configs,
+            &app_dirs.app_config_browser_desktop_files,
+            &app_dirs.app_config_browser_config_overrides,
+            &app_dirs.app_config_browser_desktop_file_overrides,
+            &app_dirs.app_config_site_hints,
+        ] {
+            assert!(path.is_dir(), "Missing dir: {}", path.display());
+        }
+        for path in [
+            &app_dirs.user_autostart,
+            &app_dirs.app_cache,
+            &app_dirs.app_data_icon_cache,
+            &app_dirs.app_data_trash,
+            &app_dirs.app_data_logs,
+            &app_dirs.app_data_launchers,
+        ] {
+            assert!(!path.exists(), "Dir made on demand: {}", path.display());
         }
+    }
+
+    #[test]
+    fn init_keeps_existing_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(root.path());
+        fs::create_dir_all(&app_dirs.app_data_icons).unwrap();
+        let icon_path = app_dirs.app_data_icons.join("app.png");
+        fs::write(&icon_path, b"icon").unwrap();
 
-        Ok(browser_desktop_files_path)
+        app_dirs.init().unwrap();
+        app_dirs.init().unwrap();
+
+        assert_eq!(fs::read(&icon_path).unwrap(), b"icon");
     }
 
-    fn build_flatpak_path(home: &Path) -> PathBuf {
-        let flatpak_path = home.join(".var").join("app");
+    #[test]
+    fn init_fails_when_a_file_is_in_the_way() {
+        let root = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(root.path());
+        fs::create_dir_all(&app_dirs.app_data).unwrap();
+        fs::write(&app_dirs.app_data_profiles, b"").unwrap();
 
-        debug!("Using flatpak path: {}", flatpak_path.display());
+        assert!(app_dirs.init().is_err());
+    }
 
-        flatpak_path
+    #[test]
+    fn system_icons_are_the_existing_icon_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let icons_path = root.path().join("system-b").join("icons");
+        fs::create_dir_all(&icons_path).unwrap();
+
+        let app_dirs = get_app_dirs(root.path());
+
+        assert_eq!(app_dirs.system_icons(), [icons_path]);
+    }
+
+    fn has_grant(filesystems: &[&str]) -> bool {
+        let filesystems: Vec<String> = filesystems.iter().map(ToString::to_string).collect();
+        AppDirs::has_applications_grant(&filesystems)
+    }
+
+    #[test]
+    fn grant_on_the_dir_or_a_parent_counts() {
+        for filesystem in [
+            "xdg-data/applications",
+            "xdg-data",
+            "~/.local/share/applications/",
+            "~/.local",
+            "home",
+            "host",
+            "home:rw",
+            "home:create",
+        ] {
+            assert!(has_grant(&[filesystem]), "{filesystem}");
+        }
+    }
+
+    #[test]
+    fn read_only_or_other_grants_do_not_count() {
+        assert!(!has_grant(&[]));
+        assert!(!has_grant(&["home:ro"]));
+        assert!(!has_grant(&["xdg-data/applications:ro"]));
+        assert!(!has_grant(&[
+            "xdg-download",
+            "~/.local/share/icons",
+            "host-os"
+        ]));
+    }
+
+    #[test]
+    fn negated_grant_wins() {
+        assert!(!has_grant(&["!home"]));
+        assert!(!has_grant(&["home", "!home"]));
+        assert!(!has_grant(&["xdg-data/applications", "!xdg-data"]));
+        assert!(has_grant(&["xdg-data/applications", "!xdg-download"]));
     }
 }
@@ -1,13 +1,19 @@
 use crate::{
     app_dirs::AppDirs,
     config::{self},
-    utils::OnceLockExt,
+    utils::{self, OnceLockExt},
 };
 use anyhow::{Context, Result};
 use freedesktop_desktop_entry::DesktopEntry;
-use include_dir::{Dir, include_dir};
-use std::fs::{self};
-use tracing::{debug, info};
+use gtk::glib::{self, ChecksumType};
+use include_dir::{Dir, DirEntry, include_dir};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self},
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, info};
 
 // Calling extract on a subdir does not work and seems bugged.
 // Using indivudal imports.
@@ -18,22 +24,105 @@ static ICON_IN: &[u8] = include_bytes!("../../../assets/app-icon.png");
 static DESKTOP_FILE_IN: &str = include_str!("../../../assets/app.desktop");
 static META_INFO_IN: &str = include_str!("../../../assets/app.metainfo.xml");
 static APP_DESCRIPTION: &str = include_str!("../../../assets/app-description.markup");
+/// Checksums of every version of a config file that was shipped, see `update_config_dir`
+const SHIPPED_MANIFEST_FILE: &str = "shipped-config.json";
+/// Suffix of a shipped update next to a file the user changed
+const NEW_VERSION_SUFFIX: &str = "new";
 
-pub fn init(app_dirs: &AppDirs) -> Result<()> {
-    info!("Creating / overwriting assets");
-    extract_config_dir(app_dirs)?;
-    Ok(())
+#[derive(Default, Serialize, Deserialize)]
+struct ShippedManifest {
+    /// Relative path in the config dir, to the checksums of its shipped versions
+    files: BTreeMap<String, BTreeSet<String>>,
 }
+impl ShippedManifest {
+    /// `None` when there is no manifest yet, an unreadable one counts as missing
+    fn load(app_dirs: &AppDirs) -> Option<Self> {
+        let path = app_dirs.app_data.join(SHIPPED_MANIFEST_FILE);
+        let manifest_json = fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&manifest_json)
+            .inspect_err(|error| error!(path = %path.display(), ?error, "Invalid config manifest"))
+            .ok()
+    }
 
+    fn save(&self, app_dirs: &AppDirs) -> Result<()> {
+        let path = app_dirs.app_data.join(SHIPPED_MANIFEST_FILE);
+        let manifest_json =
+            serde_json::to_string_pretty(self).context("Failed to serialize config manifest")?;
+        write_config_file(&path, manifest_json.as_bytes())
+    }
+
+    fn add(&mut self, relative_path: String, contents: &[u8]) {
+        self.files
+            .entry(relative_path)
+            .or_default()
+            .insert(get_checksum(contents));
+    }
+}
+
+/// Shipped files that were changed by the user, the update is saved next to each one
+#[derive(Debug, Default)]
+pub struct ConfigUpdate {
+    /// Paths of the `.new` files written on this start, an earlier one is not listed again
+    pub new_version_paths: Vec<PathBuf>,
+}
+
+/// Updates the shipped config files, without losing changes the user made to them
+pub fn init(app_dirs: &AppDirs) -> Result<ConfigUpdate> {
+    info!("Updating config assets");
+    update_config_dir(app_dirs)
+}
+
+/// Every shipped file is written again and its `.new` version is removed, changes of the user
+/// to them are lost. Files of the user, like overrides, site hints and the preferences, are kept.
 pub fn reset_config_files(app_dirs: &AppDirs) -> Result<()> {
+    info!("Resetting shipped config files");
     let config_dir = &app_dirs.app_config;
+    let previous_manifest = ShippedManifest::load(app_dirs);
+    let mut manifest = ShippedManifest::default();
 
-    if config_dir.is_dir() {
-        info!("Deleting config files");
-        fs::remove_dir_all(config_dir)?;
+    for (relative_path, contents) in get_config_files(&CONFIG) {
+        let path = config_dir.join(&relative_path);
+        write_config_file(&path, contents)?;
+        remove_new_version(&path)?;
+        manifest.add(relative_path, contents);
     }
 
-    extract_config_dir(app_dirs)?;
+    if let Some(previous_manifest) = &previous_manifest {
+        for relative_path in previous_manifest.files.keys() {
+            if manifest.files.contains_key(relative_path) {
+                continue;
+            }
+            let path = config_dir.join(relative_path);
+            if path.is_file() {
+                info!(path = %path.display(), "Removing config file that is no longer shipped");
+                fs::remove_file(&path)
+                    .context(format!("Failed to remove: '{}'", path.display()))?;
+            }
+            remove_new_version(&path)?;
+        }
+    }
+
+    manifest.save(app_dirs)
+}
+
+/// Writes one shipped file again, like `desktop-files/chromium.desktop`. A pending `.new`
+/// version is removed, the shipped file is the newest.
+pub fn restore_config_file(app_dirs: &AppDirs, relative_path: &str) -> Result<()> {
+    let contents = CONFIG
+        .get_file(relative_path)
+        .context(format!("Not a shipped config file: '{relative_path}'"))?
+        .contents();
+    let path = app_dirs.app_config.join(relative_path);
+
+    info!(path = %path.display(), "Restoring shipped config file");
+    write_config_file(&path, contents)?;
+    remove_new_version(&path)?;
+
+    // Missing manifest is fine, without one every file counts as shipped
+    if let Some(mut manifest) = ShippedManifest::load(app_dirs) {
+        manifest.add(relative_path.to_string(), contents);
+        manifest.save(app_dirs)?;
+    }
 
     Ok(())
 }
@@ -68,7 +157,7 @@ pub fn get_meta_info_in() -> &'static str {
 }
 
 pub fn get_meta_info() -> &'static str {
-    let app_id = config::APP_ID.get_value();
+    let app_id = config::BASE_APP_ID.get_value();
     DESKTOP
         .get_file(format!("{app_id}.metainfo.xml"))
         .and_then(|file| file.contents_utf8())
@@ -83,14 +172,325 @@ pub fn get_desktop_file_in() -> &'static str {
     DESKTOP_FILE_IN
 }
 
-fn extract_config_dir(app_dirs: &AppDirs) -> Result<()> {
-    debug!("Extracting config dir");
+/// Shipped desktop file of a browser config, like `chromium`
+pub fn get_browser_desktop_template(config_name: &str) -> Option<&'static str> {
+    CONFIG
+        .get_file(format!("desktop-files/{config_name}.desktop"))
+        .and_then(|file| file.contents_utf8())
+}
+
+/// Shipped config files are updated on every start, unless the user changed them
+pub fn is_config_asset(relative_path: &Path) -> bool {
+    CONFIG.get_file(relative_path).is_some()
+}
+
+/// A shipped file is only written over when it is missing or still a shipped version.
+/// Before the manifest existed every start wrote over all files, so without one every file
+/// counts as shipped. A file that is no longer shipped is removed when the user did not change it.
+fn update_config_dir(app_dirs: &AppDirs) -> Result<ConfigUpdate> {
+    debug!("Updating config dir");
     let config_dir = &app_dirs.app_config;
+    let previous_manifest = ShippedManifest::load(app_dirs);
+    let mut manifest = ShippedManifest::default();
+    let mut config_update = ConfigUpdate::default();
 
-    CONFIG.extract(config_dir).context(format!(
-        "Failed to extract config dir from ASSETS in: {}",
-        config_dir.display()
-    ))?;
+    for (relative_path, contents) in get_config_files(&CONFIG) {
+        let path = config_dir.join(&relative_path);
+        let shipped_checksums = previous_manifest.as_ref().map(|previous_manifest| {
+            previous_manifest
+                .files
+                .get(&relative_path)
+                .cloned()
+                .unwrap_or_default()
+        });
+        if let Some(shipped_checksums) = &shipped_checksums {
+            manifest
+                .files
+                .insert(relative_path.clone(), shipped_checksums.clone());
+        }
+        manifest.add(relative_path.clone(), contents);
 
+        let on_disk = fs::read(&path).ok();
+        let is_user_changed = match (&on_disk, &shipped_checksums) {
+            (None, _) | (Some(_), None) => false,
+            (Some(on_disk), Some(shipped_checksums)) => {
+                on_disk != contents && !shipped_checksums.contains(&get_checksum(on_disk))
+            }
+        };
+
+        if !is_user_changed {
+            if on_disk.as_deref() != Some(contents) {
+                debug!(path = %path.display(), "Writing shipped config file");
+                write_config_file(&path, contents)?;
+            }
+            continue;
+        }
+
+        let new_version_path = get_new_version_path(&path);
+        if fs::read(&new_version_path).is_ok_and(|new_version| new_version == contents) {
+            continue;
+        }
+        info!(
+            path = %path.display(),
+            "Keeping config file changed by the user, saving the update next to it"
+        );
+        write_config_file(&new_version_path, contents)?;
+        config_update.new_version_paths.push(new_version_path);
+    }
+
+    if let Some(previous_manifest) = &previous_manifest {
+        for (relative_path, shipped_checksums) in &previous_manifest.files {
+            if !manifest.files.contains_key(relative_path) {
+                remove_if_shipped(&config_dir.join(relative_path), shipped_checksums);
+            }
+        }
+    }
+
+    manifest.save(app_dirs)?;
+    Ok(config_update)
+}
+
+/// A file the user changed is kept, it is a file of the user now
+fn remove_if_shipped(path: &Path, shipped_checksums: &BTreeSet<String>) {
+    let Ok(on_disk) = fs::read(path) else {
+        return;
+    };
+    if !shipped_checksums.contains(&get_checksum(&on_disk)) {
+        debug!(path = %path.display(), "Keeping config file that is no longer shipped");
+        return;
+    }
+
+    info!(path = %path.display(), "Removing config file that is no longer shipped");
+    if let Err(error) = fs::remove_file(path) {
+        error!(path = %path.display(), "Failed to remove config file: {error}");
+    }
+}
+
+/// A pending update is stale once the shipped file itself is written
+fn remove_new_version(path: &Path) -> Result<()> {
+    let new_version_path = get_new_version_path(path);
+    if new_version_path.is_file() {
+        fs::remove_file(&new_version_path).context(format!(
+            "Failed to remove: '{}'",
+            new_version_path.display()
+        ))?;
+    }
     Ok(())
 }
+
+fn get_new_version_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{NEW_VERSION_SUFFIX}"));
+    path.with_file_name(file_name)
+}
+
+/// Every file in the dir and its subdirs, with its path relative to the root of `CONFIG`
+fn get_config_files(dir: &'static Dir) -> Vec<(String, &'static [u8])> {
+    let mut files = Vec::new();
+
+    for entry in dir.entries() {
+        match entry {
+            DirEntry::Dir(sub_dir) => files.extend(get_config_files(sub_dir)),
+            DirEntry::File(file) => {
+                files.push((file.path().to_string_lossy().to_string(), file.contents()));
+            }
+        }
+    }
+
+    files
+}
+
+fn write_config_file(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .context(format!("Failed to create dir: {}", parent.display()))?;
+    }
+    utils::files::write_atomic(path, contents)
+}
+
+fn get_checksum(contents: &[u8]) -> String {
+    glib::compute_checksum_for_data(ChecksumType::Sha256, contents)
+        .map(|checksum| checksum.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROWSER_CONFIG: &str = "browsers/chromium.yml";
+    const USER_CONTENTS: &[u8] = b"# Changed by the user";
+    const OLD_CONTENTS: &[u8] = b"# Shipped by an older version";
+
+    fn get_app_dirs(dir: &Path) -> AppDirs {
+        AppDirs {
+            app_data: dir.join("data"),
+            app_config: dir.join("config"),
+            ..AppDirs::default()
+        }
+    }
+
+    fn get_shipped_contents(relative_path: &str) -> &'static [u8] {
+        CONFIG.get_file(relative_path).unwrap().contents()
+    }
+
+    /// Manifest as an older version left it, with `OLD_CONTENTS` shipped for every file
+    fn save_old_manifest(app_dirs: &AppDirs, extra_paths: &[&str]) {
+        let mut manifest = ShippedManifest::default();
+        for (relative_path, _) in get_config_files(&CONFIG) {
+            manifest.add(relative_path, OLD_CONTENTS);
+        }
+        for relative_path in extra_paths {
+            manifest.add(relative_path.to_string(), OLD_CONTENTS);
+        }
+        manifest.save(app_dirs).unwrap();
+    }
+
+    fn write(app_dirs: &AppDirs, relative_path: &str, contents: &[u8]) -> PathBuf {
+        let path = app_dirs.app_config.join(relative_path);
+        write_config_file(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn missing_manifest_writes_every_shipped_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+
+        let config_update = update_config_dir(&app_dirs).unwrap();
+
+        assert!(config_update.new_version_paths.is_empty());
+        for (relative_path, contents) in get_config_files(&CONFIG) {
+            assert_eq!(
+                fs::read(app_dirs.app_config.join(relative_path)).unwrap(),
+                contents
+            );
+        }
+        assert!(ShippedManifest::load(&app_dirs).is_some());
+    }
+
+    /// Older versions wrote over every file on start, so a file on disk counts as shipped
+    #[test]
+    fn missing_manifest_writes_over_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        let path = write(&app_dirs, BROWSER_CONFIG, USER_CONTENTS);
+
+        let config_update = update_config_dir(&app_dirs).unwrap();
+
+        assert!(config_update.new_version_paths.is_empty());
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            get_shipped_contents(BROWSER_CONFIG)
+        );
+        assert!(!get_new_version_path(&path).exists());
+    }
+
+    #[test]
+    fn unchanged_file_is_updated() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        save_old_manifest(&app_dirs, &[]);
+        let path = write(&app_dirs, BROWSER_CONFIG, OLD_CONTENTS);
+
+        let config_update = update_config_dir(&app_dirs).unwrap();
+
+        assert!(config_update.new_version_paths.is_empty());
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            get_shipped_contents(BROWSER_CONFIG)
+        );
+        assert!(!get_new_version_path(&path).exists());
+    }
+
+    #[test]
+    fn user_changed_file_is_kept_with_the_update_next_to_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        save_old_manifest(&app_dirs, &[]);
+        let path = write(&app_dirs, BROWSER_CONFIG, USER_CONTENTS);
+        let new_version_path = get_new_version_path(&path);
+
+        let config_update = update_config_dir(&app_dirs).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), USER_CONTENTS);
+        assert_eq!(
+            fs::read(&new_version_path).unwrap(),
+            get_shipped_contents(BROWSER_CONFIG)
+        );
+        assert_eq!(config_update.new_version_paths, vec![new_version_path]);
+
+        // Only listed on the start that wrote it
+        let config_update = update_config_dir(&app_dirs).unwrap();
+
+        assert!(config_update.new_version_paths.is_empty());
+        assert_eq!(fs::read(&path).unwrap(), USER_CONTENTS);
+    }
+
+    #[test]
+    fn file_no_longer_shipped_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        save_old_manifest(&app_dirs, &["browsers/retired.yml"]);
+        let path = write(&app_dirs, "browsers/retired.yml", OLD_CONTENTS);
+
+        update_config_dir(&app_dirs).unwrap();
+
+        assert!(!path.exists());
+        assert!(
+            !ShippedManifest::load(&app_dirs)
+                .unwrap()
+                .files
+                .contains_key("browsers/retired.yml")
+        );
+    }
+
+    #[test]
+    fn user_changed_file_no_longer_shipped_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        save_old_manifest(&app_dirs, &["browsers/retired.yml"]);
+        let path = write(&app_dirs, "browsers/retired.yml", USER_CONTENTS);
+
+        update_config_dir(&app_dirs).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), USER_CONTENTS);
+    }
+
+    #[test]
+    fn file_of_the_user_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        save_old_manifest(&app_dirs, &[]);
+        let path = write(&app_dirs, "browsers/custom.yml", USER_CONTENTS);
+
+        update_config_dir(&app_dirs).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), USER_CONTENTS);
+    }
+
+    #[test]
+    fn reset_writes_shipped_files_and_keeps_files_of_the_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        save_old_manifest(&app_dirs, &["browsers/retired.yml"]);
+        let changed_path = write(&app_dirs, BROWSER_CONFIG, USER_CONTENTS);
+        let new_version_path = write(
+            &app_dirs,
+            &format!("{BROWSER_CONFIG}.{NEW_VERSION_SUFFIX}"),
+            OLD_CONTENTS,
+        );
+        let retired_path = write(&app_dirs, "browsers/retired.yml", USER_CONTENTS);
+        let user_path = write(&app_dirs, "browsers/custom.yml", USER_CONTENTS);
+
+        reset_config_files(&app_dirs).unwrap();
+
+        assert_eq!(
+            fs::read(&changed_path).unwrap(),
+            get_shipped_contents(BROWSER_CONFIG)
+        );
+        assert!(!new_version_path.exists());
+        assert!(!retired_path.exists());
+        assert_eq!(fs::read(&user_path).unwrap(), USER_CONTENTS);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{
+    config::{self},
+    desktop_file::DesktopFile,
+    utils::{
+        OnceLockExt,
+        archive::{self, TarWriter},
+    },
+};
+use anyhow::{Context, Result, bail};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, info};
+
+pub const EXTENSION: &str = "tar.gz";
+const FORMAT: u32 = 1;
+/// Always the first entry, so restoring can be planned without reading the profiles
+const MANIFEST_FILE: &str = "manifest.json";
+const MANIFEST_MAX_SIZE: u64 = 50_000_000;
+const ICONS_DIR: &str = "icons";
+const PROFILES_DIR: &str = "profiles";
+
+/// One web app in a backup, the desktop entry holds the actual configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupApp {
+    pub app_id: String,
+    pub name: String,
+    /// File name in the applications folder
+    pub file_name: String,
+    pub desktop_entry: String,
+    pub browser_id: Option<String>,
+    /// Finds the same browser when it is installed differently on the other machine
+    pub browser_config_name: Option<String>,
+    /// Entry name of the icon in the archive
+    pub icon_file: Option<String>,
+    pub has_profile: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct BackupManifest {
+    format: u32,
+    version: String,
+    apps: Vec<BackupApp>,
+}
+
+/// Plain data of a web app, collected on the main thread because `DesktopFile` can't be sent
+pub struct BackupSource {
+    pub app: BackupApp,
+    pub icon_path: Option<PathBuf>,
+    pub profile_path: Option<PathBuf>,
+}
+impl BackupSource {
+    pub fn from_desktop_file(desktop_file: &DesktopFile, with_profile: bool) -> Option<Self> {
+        let app_id = desktop_file.get_id()?;
+        let file_name = desktop_file
+            .get_path()
+            .file_name()?
+            .to_string_lossy()
+            .to_string();
+
+        let icon_path = desktop_file
+            .get_icon_path()
+            .filter(|icon_path| icon_path.is_file());
+        let icon_file = icon_path.as_ref().map(|icon_path| {
+            let extension = icon_path
+                .extension()
+                .map(|extension| format!(".{}", extension.to_string_lossy()))
+                .unwrap_or_default();
+            format!("{ICONS_DIR}/{app_id}{extension}")
+        });
+
+        let profile_path = desktop_file
+            .get_profile_path()
+            .filter(|_| with_profile && desktop_file.get_isolated().unwrap_or(false))
+            .filter(|profile_path| profile_path.is_dir());
+
+        let app = BackupApp {
+            app_id,
+            name: desktop_file.get_name().unwrap_or_default(),
+            file_name,
+            desktop_entry: desktop_file.to_string(),
+            browser_id: desktop_file.get_browser().map(|browser| browser.id.clone()),
+            browser_config_name: desktop_file
+                .get_browser()
+                .map(|browser| browser.config_name.clone()),
+            icon_file,
+            has_profile: profile_path.is_some(),
+        };
+
+        Some(Self {
+            app,
+            icon_path,
+            profile_path,
+        })
+    }
+}
+
+/// Where the files of a restored web app go, decided on the main thread
+#[derive(Debug, Default)]
+pub struct RestoreTarget {
+    pub icon_path: Option<PathBuf>,
+    pub profile_path: Option<PathBuf>,
+}
+
+/// Blocking, profiles can be big. Written next to the path first, so a failed backup leaves nothing behind.
+pub fn create(path: &Path, sources: &[BackupSource]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .context(format!("No file name in path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    debug!("Creating backup: {}", path.display());
+    if let Err(error) = write_archive(&temp_path, sources) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(error);
+    }
+    if let Err(error) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        bail!(
+            "Failed to move temp file to: {}. Error: {error:?}",
+            path.display()
+        );
+    }
+
+    info!(count = sources.len(), "Created backup: {}", path.display());
+    Ok(())
+}
+
+/// Blocking, only reads up to the manifest
+pub fn read_apps(path: &Path) -> Result<Vec<BackupApp>> {
+    let mut manifest: Option<BackupManifest> = None;
+
+    archive::read_entries(open_archive(path)?, |name, entry| {
+        if name != MANIFEST_FILE {
+            bail!("Backup does not start with a manifest")
+        }
+
+        let mut manifest_json = Vec::new();
+        entry
+            .take(MANIFEST_MAX_SIZE + 1)
+            .read_to_end(&mut manifest_json)?;
+        if u64::try_from(manifest_json.len())? > MANIFEST_MAX_SIZE {
+            bail!("Manifest in backup is too big")
+        }
+        manifest =
+            Some(serde_json::from_slice(&manifest_json).context("Invalid manifest in backup")?);
+
+        Ok(ControlFlow::Break(()))
+    })
+    .context("Backup is not a valid archive")?;
+
+    let manifest = manifest.context("No manifest in backup")?;
+    if manifest.format != FORMAT {
+        bail!("Unsupported backup format: {}", manifest.format)
+    }
+
+    Ok(manifest.apps)
+}
+
+/// Blocking, extracts the icons and profiles of the apps in `targets`, everything else is skipped
+pub fn restore(
+    path: &Path,
+    apps: &[BackupApp],
+    targets: &HashMap<String, RestoreTarget>,
+) -> Result<()> {
+    debug!("Restoring backup: {}", path.display());
+
+    let icon_targets: HashMap<&str, &Path> = apps
+        .iter()
+        .filter_map(|app| {
+            let icon_file = app.icon_file.as_deref()?;
+            let icon_path = targets.get(&app.app_id)?.icon_path.as_deref()?;
+            Some((icon_file, icon_path))
+        })
+        .collect();
+
+    archive::read_entries(open_archive(path)?, |name, entry| {
+        if let Some(icon_path) = icon_targets.get(name) {
+            write_entry(icon_path, entry)?;
+        } else if let Some(profile_entry) = name.strip_prefix(&format!("{PROFILES_DIR}/")) {
+            let Some((app_id, relative_path)) = profile_entry.split_once('/') else {
+                return Ok(ControlFlow::Continue(()));
+            };
+            let Some(profile_path) = targets
+                .get(app_id)
+                .and_then(|target| target.profile_path.as_deref())
+            else {
+                return Ok(ControlFlow::Continue(()));
+            };
+            let Some(file_path) = archive::get_safe_path(profile_path, relative_path) else {
+                debug!("Skipping unsafe path in backup: '{name}'");
+                return Ok(ControlFlow::Continue(()));
+            };
+            write_entry(&file_path, entry)?;
+        }
+
+        Ok(ControlFlow::Continue(()))
+    })
+    .context("Failed to restore backup")?;
+
+    info!(count = targets.len(), "Restored backup: {}", path.display());
+    Ok(())
+}
+
+fn write_archive(path: &Path, sources: &[BackupSource]) -> Result<()> {
+    let manifest = BackupManifest {
+        format: FORMAT,
+        version: config::VERSION.get_value().clone(),
+        apps: sources.iter().map(|source| source.app.clone()).collect(),
+    };
+    let manifest_json =
+        serde_json::to_vec_pretty(&manifest).context("Failed to serialize manifest")?;
+
+    let file = File::create(path).context(format!("Failed to create: '{}'", path.display()))?;
+    let mut tar = TarWriter::new(GzEncoder::new(BufWriter::new(file), Compression::default()));
+    tar.append(MANIFEST_FILE, &manifest_json)?;
+
+    for source in sources {
+        if let (Some(icon_file), Some(icon_path)) = (&source.app.icon_file, &source.icon_path) {
+            tar.append_file(icon_file, icon_path)?;
+        }
+    }
+    for source in sources {
+        if let Some(profile_path) = &source.profile_path {
+            debug!(app_id = source.app.app_id, "Adding profile to backup");
+            tar.append_dir(
+                &format!("{PROFILES_DIR}/{}", source.app.app_id),
+                profile_path,
+            )?;
+        }
+    }
+
+    tar.finish()?
+        .finish()
+        .context("Failed to compress backup")?
+        .into_inner()
+        .map_err(|error| error.into_error())
+        .context("Failed to write backup")?
+        .sync_all()?;
+
+    Ok(())
+}
+
+fn open_archive(path: &Path) -> Result<GzDecoder<BufReader<File>>> {
+    let file = File::open(path).context(format!("Failed to open backup: '{}'", path.display()))?;
+    Ok(GzDecoder::new(BufReader::new(file)))
+}
+
+fn write_entry(path: &Path, entry: &mut dyn Read) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .context(format!("Failed to create dir: '{}'", parent.display()))?;
+    }
+    let mut file = File::create(path).context(format!("Failed to create: '{}'", path.display()))?;
+    io::copy(entry, &mut file).context(format!("Failed to write: '{}'", path.display()))?;
+
+    Ok(())
+}
@@ -1,29 +1,62 @@
+pub mod config_layers;
+mod detection_cache;
+pub mod version;
+
 use crate::utils::{self, OnceLockExt};
 use crate::{
     app_dirs::AppDirs,
+    assets,
     config::{self},
 };
 use anyhow::{Context, Result, bail};
+use config_layers::{ConfigDirs, ConfigLayer};
 use freedesktop_desktop_entry::DesktopEntry;
-use gtk::{IconTheme, Image};
+use gtk::{
+    IconTheme, Image,
+    gio::{
+        self,
+        prelude::{FileExt, FileMonitorExt},
+    },
+    glib,
+};
 use std::{
-    cell::OnceCell,
+    cell::RefCell,
     collections::{HashMap, HashSet},
     fs,
+    os::unix::fs::PermissionsExt,
     path::Path,
     rc::Rc,
+    thread,
+    time::Duration,
 };
 use std::{fmt::Write as _, path::PathBuf};
 use tracing::{debug, error, info};
+use version::{BrowserVersion, VersionIssue};
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub enum Installation {
     Flatpak(String),
     System(String),
+    Snap(String),
+    Custom(PathBuf),
     None,
 }
 
-#[derive(PartialEq)]
+/// A sandbox permission a flatpak browser needs for a feature
+#[derive(Debug, PartialEq, Clone)]
+pub enum MissingPermission {
+    /// Value of `--filesystem`, like `~/.var/app:create`
+    Filesystem(String),
+}
+impl MissingPermission {
+    pub fn get_override_arg(&self) -> String {
+        match self {
+            Self::Filesystem(filesystem) => format!("--filesystem={filesystem}"),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
 pub enum Base {
     Chromium,
     Firefox,
@@ -38,20 +71,76 @@ impl Base {
         }
     }
 }
+impl std::fmt::Display for Base {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Chromium => write!(f, "chromium"),
+            Self::Firefox => write!(f, "firefox"),
+            Self::None => write!(f, "none"),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct BrowserYaml {
     name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     flatpak: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     system_bin: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snap: Option<String>,
+    /// Absolute path, for browsers that are not on the `PATH`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    custom_bin: Option<PathBuf>,
     #[serde(default)]
     can_isolate: bool,
     #[serde(default)]
     can_start_maximized: bool,
+    #[serde(default)]
+    can_fullscreen: bool,
+    #[serde(default)]
+    can_private: bool,
+    /// Chromium only, Firefox has no flag for it
+    #[serde(default)]
+    can_force_dark: bool,
     desktop_file_name_prefix: String,
     base: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     issues: HashMap<String, Vec<String>>,
+    /// Older versions are known to break web apps, like the `--app` flag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    version_issues: Vec<VersionIssue>,
+}
+
+/// A browser config made in the app, saved next to the shipped configs
+pub struct UserBrowserConfig {
+    pub name: String,
+    pub flatpak: Option<String>,
+    pub system_bin: Option<String>,
+    pub base: Base,
+    pub can_isolate: bool,
+    pub can_start_maximized: bool,
+    pub can_fullscreen: bool,
+    pub can_private: bool,
+    pub can_force_dark: bool,
+    pub desktop_file_name_prefix: String,
+    pub desktop_template: String,
+}
+
+/// A browser config that was skipped, or replaced by its last working copy
+#[derive(Debug, Clone)]
+pub struct ConfigLoadError {
+    pub file_name: String,
+    pub error: String,
+}
+
+enum VersionProbe {
+    NotStarted,
+    Running,
+    Done(Option<BrowserVersion>),
 }
 
 struct BrowserConfig {
@@ -67,43 +156,97 @@ pub struct Browser {
     pub installation: Installation,
     pub can_isolate: bool,
     pub can_start_maximized: bool,
+    pub can_fullscreen: bool,
+    pub can_private: bool,
+    pub can_force_dark: bool,
     pub flatpak_id: Option<String>,
     pub executable: Option<String>,
+    pub snap_name: Option<String>,
+    pub custom_bin: Option<PathBuf>,
     pub desktop_file: DesktopEntry,
     pub desktop_file_name_prefix: String,
     pub base: Base,
     pub issues: HashMap<String, Vec<String>>,
+    pub min_version: Option<BrowserVersion>,
+    /// Parsed `version_issues` of the config
+    version_issues: Vec<(BrowserVersion, HashMap<String, Vec<String>>)>,
+    /// Read once with `--version`, see `get_version`
+    version: RefCell<VersionProbe>,
     pub config_name: String,
+    /// Installations of the config that were looked for but not found
+    pub not_found: Vec<Installation>,
     configs: Rc<BrowserConfigs>,
-    icon_theme: Rc<IconTheme>,
+    icon_theme: Option<Rc<IconTheme>>,
     icon_names: HashSet<String>,
     app_dirs: Rc<AppDirs>,
 }
 impl Browser {
     const FALLBACK_IMAGE: &str = "web-browser-symbolic";
+    /// See `get_profile_path`, Firefox can't create an isolated profile without it
+    const ISOLATION_FILESYSTEM: &str = "~/.var/app:create";
+    /// A flatpak browser can take a while on the first start
+    const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+    const VERSION_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
     fn new(
         browser_config: &BrowserConfig,
         installation: Installation,
+        not_found: &[Installation],
         browser_configs: &Rc<BrowserConfigs>,
-        icon_theme: &Rc<IconTheme>,
+        icon_theme: Option<&Rc<IconTheme>>,
         app_dirs: &Rc<AppDirs>,
     ) -> Self {
         let icon_names = Self::get_icon_names_from_config(browser_config);
         let name = browser_config.config.name.clone();
         let can_isolate = browser_config.config.can_isolate;
         let can_start_maximized = browser_config.config.can_start_maximized;
+        let can_fullscreen = browser_config.config.can_fullscreen;
+        let can_private = browser_config.config.can_private;
+        let can_force_dark = browser_config.config.can_force_dark;
         let flatpak_id = browser_config.config.flatpak.clone();
         let executable = browser_config.config.system_bin.clone();
+        let snap_name = browser_config.config.snap.clone();
+        let custom_bin = browser_config.config.custom_bin.clone();
         let desktop_file = browser_config.desktop_file.clone();
         let desktop_file_name_prefix = browser_config.config.desktop_file_name_prefix.clone();
         let config_name = browser_config.config_name.clone();
         let base = Base::from_string(&browser_config.config.base);
         let issues = browser_config.config.issues.clone();
+        let min_version = browser_config
+            .config
+            .min_version
+            .as_ref()
+            .and_then(|min_version| {
+                BrowserVersion::parse(min_version).or_else(|| {
+                    error!(
+                        config = browser_config.file_name,
+                        "Invalid min_version: '{min_version}'"
+                    );
+                    None
+                })
+            });
+        let version_issues = browser_config
+            .config
+            .version_issues
+            .iter()
+            .filter_map(|version_issue| {
+                let Some(below) = BrowserVersion::parse(&version_issue.below) else {
+                    error!(
+                        config = browser_config.file_name,
+                        "Invalid version in version_issues: '{}'", version_issue.below
+                    );
+                    return None;
+                };
+                Some((below, version_issue.issues.clone()))
+            })
+            .collect();
 
         let id = match &installation {
             Installation::Flatpak(id) => id.clone(),
             Installation::System(executable) => executable.clone(),
+            // Prefixed, snap names are often the same as the system binary
+            Installation::Snap(name) => format!("{}{name}", BrowserConfigs::SNAP_ID_PREFIX),
+            Installation::Custom(path) => path.to_string_lossy().to_string(),
             Installation::None => "Not installed".to_string(),
         };
 
@@ -113,16 +256,25 @@ impl Browser {
             installation,
             can_isolate,
             can_start_maximized,
+            can_fullscreen,
+            can_private,
+            can_force_dark,
             flatpak_id,
             executable,
+            snap_name,
+            custom_bin,
             desktop_file,
             desktop_file_name_prefix,
             config_name,
+            not_found: not_found.to_vec(),
             configs: browser_configs.clone(),
             icon_names,
             base,
             issues,
-            icon_theme: icon_theme.clone(),
+            min_version,
+            version_issues,
+            version: RefCell::new(VersionProbe::NotStarted),
+            icon_theme: icon_theme.cloned(),
             app_dirs: app_dirs.clone(),
         }
     }
@@ -135,8 +287,90 @@ impl Browser {
         matches!(self.installation, Installation::System(_))
     }
 
+    pub fn is_snap(&self) -> bool {
+        matches!(self.installation, Installation::Snap(_))
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self.installation, Installation::Custom(_))
+    }
+
+    /// Known issues in the language, or else in the default language
+    pub fn get_issues(&self, language: &str, default_language: &str) -> &[String] {
+        self.issues
+            .get(language)
+            .or(self.issues.get(default_language))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Issues that apply to the installed version, in the language or the default language
+    pub fn get_version_issues(
+        &self,
+        version: &BrowserVersion,
+        language: &str,
+        default_language: &str,
+    ) -> Vec<String> {
+        self.version_issues
+            .iter()
+            .filter(|(below, _)| version < below)
+            .filter_map(|(_, issues)| issues.get(language).or(issues.get(default_language)))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_below_min_version(&self, version: &BrowserVersion) -> bool {
+        self.min_version
+            .as_ref()
+            .is_some_and(|min_version| version < min_version)
+    }
+
+    /// Runs `--version` once on a thread, later calls get the same result. `None` when the
+    /// browser printed no version, or took too long.
+    pub async fn get_version(&self) -> Option<BrowserVersion> {
+        loop {
+            match &*self.version.borrow() {
+                VersionProbe::NotStarted => break,
+                VersionProbe::Running => {}
+                VersionProbe::Done(version) => return version.clone(),
+            }
+            glib::timeout_future(Self::VERSION_POLL_INTERVAL).await;
+        }
+        *self.version.borrow_mut() = VersionProbe::Running;
+
+        let version = match self.get_run_command() {
+            Ok(run_command) => {
+                let command = format!("{run_command} --version");
+                let probe = gio::spawn_blocking(move || {
+                    let response = utils::command::run_command_sync(&command).ok()?;
+                    // Some browsers print it on stderr
+                    BrowserVersion::from_output(&response.stdout)
+                        .or_else(|| BrowserVersion::from_output(&response.stderr))
+                });
+                glib::future_with_timeout(Self::VERSION_TIMEOUT, probe)
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .flatten()
+            }
+            Err(_) => None,
+        };
+
+        match &version {
+            Some(version) => debug!(browser = self.id, %version, "Read browser version"),
+            None => debug!(browser = self.id, "No version found for browser"),
+        }
+        *self.version.borrow_mut() = VersionProbe::Done(version.clone());
+        version
+    }
+
+    /// A custom binary is checked every time, it can be moved or deleted at any moment
     pub fn is_installed(&self) -> bool {
-        !matches!(self.installation, Installation::None)
+        match &self.installation {
+            Installation::Custom(path) => BrowserConfigs::is_installed_custom(path),
+            Installation::None => false,
+            _ => true,
+        }
     }
 
     pub fn is_no_browser(&self) -> bool {
@@ -158,6 +392,12 @@ impl Browser {
             Installation::System(_) => {
                 let _ = write!(txt, " (System)");
             }
+            Installation::Snap(_) => {
+                let _ = write!(txt, " (Snap)");
+            }
+            Installation::Custom(_) => {
+                let _ = write!(txt, " (Custom)");
+            }
             Installation::None => {}
         }
 
@@ -168,13 +408,62 @@ impl Browser {
         match &self.installation {
             Installation::Flatpak(id) => Ok(format!("flatpak run {id}")),
             Installation::System(executable) => Ok(executable.clone()),
+            Installation::Snap(name) => Ok(format!("snap run {name}")),
+            Installation::Custom(path) => {
+                if !BrowserConfigs::is_installed_custom(path) {
+                    bail!(
+                        "Custom browser binary is missing or not executable: '{}'",
+                        path.display()
+                    )
+                }
+                Ok(utils::command::quote_exec_arg(&path.to_string_lossy()))
+            }
             Installation::None => bail!("Browser is not installed"),
         }
     }
 
+    /// Program for `TryExec`, launchers hide the web app when it is gone.
+    /// A flatpak can't be checked this way, only `flatpak` itself.
+    pub fn get_try_exec(&self) -> Option<String> {
+        match &self.installation {
+            Installation::Flatpak(_) => Some("flatpak".to_string()),
+            Installation::System(executable) => Some(executable.clone()),
+            Installation::Snap(name) => Some(format!("{}{name}", BrowserConfigs::SNAP_BIN_DIR)),
+            Installation::Custom(path) => Some(path.to_string_lossy().to_string()),
+            Installation::None => None,
+        }
+    }
+
+    /// `env` would only reach the `flatpak` command, a sandboxed browser needs `--env`
+    pub fn get_run_command_with_env(&self, env: &[(String, String)]) -> Result<String> {
+        let command = self.get_run_command()?;
+        if env.is_empty() {
+            return Ok(command);
+        }
+
+        let get_env_args = |prefix: &str| {
+            env.iter()
+                .map(|(key, value)| {
+                    utils::command::quote_exec_arg(&format!("{prefix}{key}={value}"))
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        match &self.installation {
+            Installation::Flatpak(id) => Ok(format!("flatpak run {} {id}", get_env_args("--env="))),
+            _ => Ok(format!("env {} {command}", get_env_args(""))),
+        }
+    }
+
+    /// Needs GTK, not available for browser configs made with `new_headless`
     pub fn get_icon(&self) -> Image {
         for icon in &self.icon_names {
-            if !self.icon_theme.has_icon(icon) {
+            if !self
+                .icon_theme
+                .as_ref()
+                .is_some_and(|icon_theme| icon_theme.has_icon(icon))
+            {
                 continue;
             }
             let image = Image::from_icon_name(icon);
@@ -210,6 +499,19 @@ impl Browser {
             Ok(path)
         };
 
+        // Snaps are confined, the common user data dir is writable and kept between revisions
+        let snap_profile_path = |name: &str| -> Result<PathBuf> {
+            let path = self
+                .app_dirs
+                .user_home
+                .join("snap")
+                .join(name)
+                .join("common")
+                .join(config::APP_NAME_HYPHEN.get_value())
+                .join("profiles");
+            Ok(path)
+        };
+
         let profile = match self.base {
             /*
                Firefox has a method to create profiles (-CreateProfile <name> and -P) but is poorly implemented.
@@ -221,9 +523,10 @@ impl Browser {
 
                Chromium based just created the provided profile path
             */
-            Base::Chromium | Base::Firefox => match self.installation {
+            Base::Chromium | Base::Firefox => match &self.installation {
                 Installation::Flatpak(_) => browser_profile_path()?,
-                Installation::System(_) => app_profile_path()?,
+                Installation::System(_) | Installation::Custom(_) => app_profile_path()?,
+                Installation::Snap(name) => snap_profile_path(name)?,
                 Installation::None => bail!("Browser is not installed"),
             },
 
@@ -239,6 +542,106 @@ impl Browser {
         self.configs.get_index(self)
     }
 
+    /// Only flatpak Firefox browsers need extra permissions, others are always `Ok`.
+    /// When the permissions can't be read this is `Ok` as well, launching will tell.
+    pub fn check_isolation_permissions(&self) -> Result<(), Vec<MissingPermission>> {
+        let Installation::Flatpak(flatpak_id) = &self.installation else {
+            return Ok(());
+        };
+        if !self.can_isolate || self.base != Base::Firefox {
+            return Ok(());
+        }
+
+        let command = format!("flatpak info --show-permissions {flatpak_id}");
+        let filesystems = match utils::command::run_command_sync(&command) {
+            Ok(response) if response.success => Self::get_flatpak_filesystems(&response.stdout),
+            Ok(response) => {
+                error!(
+                    error = response.stderr,
+                    "Could not read permissions of: {flatpak_id}"
+                );
+                return Ok(());
+            }
+            Err(error) => {
+                error!("Could not run command '{command}'. Error: {error:?}");
+                return Ok(());
+            }
+        };
+
+        if filesystems
+            .iter()
+            .any(|filesystem| Self::is_var_app_writable(filesystem))
+        {
+            return Ok(());
+        }
+
+        debug!(
+            flatpak_id,
+            ?filesystems,
+            "Browser can't create isolated profiles"
+        );
+        Err(vec![MissingPermission::Filesystem(
+            Self::ISOLATION_FILESYSTEM.to_string(),
+        )])
+    }
+
+    /// Command for the user to run, `None` for a browser that is not a flatpak
+    pub fn get_permission_override_command(
+        &self,
+        missing_permissions: &[MissingPermission],
+    ) -> Option<String> {
+        let Installation::Flatpak(flatpak_id) = &self.installation else {
+            return None;
+        };
+        let args: Vec<String> = missing_permissions
+            .iter()
+            .map(MissingPermission::get_override_arg)
+            .collect();
+
+        Some(format!(
+            "flatpak override --user {} {flatpak_id}",
+            args.join(" ")
+        ))
+    }
+
+    /// `filesystems` of the `[Context]` group, overrides of the user included
+    fn get_flatpak_filesystems(permissions: &str) -> Vec<String> {
+        let key_file = glib::KeyFile::new();
+        if let Err(error) = key_file.load_from_data(permissions, glib::KeyFileFlags::NONE) {
+            error!("Could not parse flatpak permissions: {error:?}");
+            return Vec::new();
+        }
+
+        key_file
+            .string_list("Context", "filesystems")
+            .map(|filesystems| {
+                filesystems
+                    .iter()
+                    .map(std::string::ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Access to a parent of `~/.var/app` works as well, a negated or read-only one doesn't
+    fn is_var_app_writable(filesystem: &str) -> bool {
+        if filesystem.starts_with('!') {
+            return false;
+        }
+        let (path, mode) = match filesystem.rsplit_once(':') {
+            Some((path, mode)) if ["ro", "rw", "create"].contains(&mode) => (path, mode),
+            _ => (filesystem, "rw"),
+        };
+        if mode == "ro" {
+            return false;
+        }
+
+        matches!(
+            path.trim_end_matches('/'),
+            "host" | "home" | "~" | "~/.var" | "~/.var/app"
+        )
+    }
+
     fn get_icon_names_from_config(browser_config: &BrowserConfig) -> HashSet<String> {
         let mut icon_names = HashSet::new();
 
@@ -246,40 +649,304 @@ impl Browser {
             icon_names.insert(flatpak.trim().to_string());
         }
 
+        if let Some(snap) = &browser_config.config.snap {
+            icon_names.insert(snap.trim().to_string());
+        }
+
         if let Some(bin) = &browser_config.config.system_bin {
             icon_names.insert(bin.trim().to_string());
         }
 
+        if let Some(file_name) = browser_config
+            .config
+            .custom_bin
+            .as_ref()
+            .and_then(|custom_bin| custom_bin.file_name())
+        {
+            icon_names.insert(file_name.to_string_lossy().trim().to_string());
+        }
+
         icon_names.insert(browser_config.config.name.trim().to_string());
 
         icon_names
     }
 }
 
+/// Installations to look for, can be sent to a detection thread
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct InstallationCheck {
+    flatpak: Option<String>,
+    system_bin: Option<String>,
+    snap: Option<String>,
+    custom_bin: Option<PathBuf>,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct DetectedInstallation {
+    is_flatpak: bool,
+    is_system: bool,
+    is_snap: bool,
+    is_custom: bool,
+    /// Where the system binary was found on the PATH
+    system_bin_path: Option<String>,
+}
+
 pub struct BrowserConfigs {
-    all_browsers: OnceCell<Vec<Rc<Browser>>>,
-    uninstalled_browsers: OnceCell<Vec<Rc<Browser>>>,
-    icon_theme: Rc<IconTheme>,
+    all_browsers: RefCell<Vec<Rc<Browser>>>,
+    uninstalled_browsers: RefCell<Vec<Rc<Browser>>>,
+    /// Increased on every detection, so views can tell their browser list is outdated
+    generation: RefCell<u64>,
+    /// Called after the browsers changed, a callback that returns `Break` is removed
+    changed_callbacks: RefCell<Vec<Box<dyn Fn() -> glib::ControlFlow>>>,
+    /// Configs that failed to load on the last read, see `take_load_errors`
+    load_errors: RefCell<Vec<ConfigLoadError>>,
+    /// Kept alive while the config dirs are watched
+    config_monitors: RefCell<Vec<gio::FileMonitor>>,
+    /// Edits come in bursts, like a temp file and a rename, they get one reload
+    pending_reload: RefCell<Option<glib::SourceId>>,
+    /// `None` without GTK, like on the command line
+    icon_theme: Option<Rc<IconTheme>>,
     app_dirs: Rc<AppDirs>,
 }
 impl BrowserConfigs {
     pub const NO_BROWSER_NAME: &str = "No browser";
+    const SNAP_ID_PREFIX: &str = "snap.";
+    const SNAP_BIN_DIR: &str = "/snap/bin/";
+    const SNAP_DIR: &str = "/snap";
+    const LAST_GOOD_CONFIGS_DIR: &str = "browser-configs";
+    const LAST_GOOD_OVERRIDES_DIR: &str = "overrides";
+    const RECENT_WRITE: Duration = Duration::from_secs(2);
+    const RETRY_DELAY: Duration = Duration::from_millis(250);
+    const RELOAD_DELAY: Duration = Duration::from_millis(500);
 
     pub fn new(icon_theme: &Rc<IconTheme>, app_dirs: &Rc<AppDirs>) -> Rc<Self> {
+        Self::build(Some(icon_theme.clone()), app_dirs)
+    }
+
+    /// Without an icon theme, so it works without a display. Browser icons can't be used.
+    pub fn new_headless(app_dirs: &Rc<AppDirs>) -> Rc<Self> {
+        Self::build(None, app_dirs)
+    }
+
+    fn build(icon_theme: Option<Rc<IconTheme>>, app_dirs: &Rc<AppDirs>) -> Rc<Self> {
         Rc::new(Self {
-            all_browsers: OnceCell::new(),
-            uninstalled_browsers: OnceCell::new(),
-            icon_theme: icon_theme.clone(),
+            all_browsers: RefCell::new(Vec::new()),
+            uninstalled_browsers: RefCell::new(Vec::new()),
+            generation: RefCell::new(0),
+            changed_callbacks: RefCell::new(Vec::new()),
+            load_errors: RefCell::new(Vec::new()),
+            config_monitors: RefCell::new(Vec::new()),
+            pending_reload: RefCell::new(None),
+            icon_theme,
             app_dirs: app_dirs.clone(),
         })
     }
 
-    pub fn init(self: &Rc<Self>) {
-        self.set_browsers_from_files();
+    /// Starts with the detection of the last run when the configs did not change since, it is
+    /// checked again in the background. Detection runs off the main loop, so the window can
+    /// render in the meantime.
+    pub async fn init(self: &Rc<Self>) {
+        let browser_configs = self.get_browsers_from_files();
+        let installation_checks = Self::build_installation_checks(&browser_configs);
+
+        let Some(cached_installations) =
+            detection_cache::load(&self.app_dirs, &browser_configs, &installation_checks)
+        else {
+            self.refresh().await;
+            return;
+        };
+        self.apply_detection(&browser_configs, &cached_installations);
+
+        let self_clone = self.clone();
+        glib::spawn_future_local(async move {
+            let detected_installations =
+                Self::detect_installations_async(installation_checks.clone()).await;
+            if detected_installations == cached_installations {
+                debug!("Browser detection cache is up to date");
+                return;
+            }
+
+            info!("Browser installations changed since the last run");
+            self_clone.apply_detection(&browser_configs, &detected_installations);
+            self_clone.save_detection(
+                &browser_configs,
+                &installation_checks,
+                &detected_installations,
+            );
+        });
+    }
+
+    /// Reads the configs and detects installations again, the cache of the last run is skipped.
+    /// Existing `Rc<Browser>` keep working, lookups by id resolve to the new browsers.
+    pub async fn refresh(self: &Rc<Self>) {
+        let browser_configs = self.get_browsers_from_files();
+        let installation_checks = Self::build_installation_checks(&browser_configs);
+
+        let detected_installations =
+            Self::detect_installations_async(installation_checks.clone()).await;
+
+        self.apply_detection(&browser_configs, &detected_installations);
+        self.save_detection(
+            &browser_configs,
+            &installation_checks,
+            &detected_installations,
+        );
+    }
+
+    /// The callback runs after every detection that was applied, also the background check of
+    /// `init`. Return `Break` to stop listening, like when the widget is gone.
+    pub fn connect_changed(&self, callback: impl Fn() -> glib::ControlFlow + 'static) {
+        self.changed_callbacks.borrow_mut().push(Box::new(callback));
+    }
+
+    /// Reloads the browsers when a config or desktop file of a browser is added, edited or
+    /// removed, so a change shows without a restart
+    pub fn watch_config_dirs(self: &Rc<Self>) {
+        let mut config_monitors = self.config_monitors.borrow_mut();
+        config_monitors.clear();
+
+        for dir in [
+            &self.app_dirs.app_config_browser_configs,
+            &self.app_dirs.app_config_browser_desktop_files,
+            &self.app_dirs.app_config_browser_config_overrides,
+            &self.app_dirs.app_config_browser_desktop_file_overrides,
+        ] {
+            let monitor = match gio::File::for_path(dir)
+                .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => monitor,
+                Err(error) => {
+                    error!(path = %dir.display(), "Failed to watch browser config dir: {error}");
+                    continue;
+                }
+            };
+
+            let self_weak = Rc::downgrade(self);
+            monitor.connect_changed(move |_, file, _, event| {
+                if matches!(
+                    event,
+                    gio::FileMonitorEvent::AttributeChanged
+                        | gio::FileMonitorEvent::PreUnmount
+                        | gio::FileMonitorEvent::Unmounted
+                ) {
+                    return;
+                }
+                let Some(self_clone) = self_weak.upgrade() else {
+                    return;
+                };
+                debug!(path = ?file.path(), ?event, "Browser config dir changed");
+                self_clone.queue_reload();
+            });
+            config_monitors.push(monitor);
+        }
+    }
+
+    /// Stops watching and drops the listeners and browsers, for an app that is rebuilt.
+    /// Listeners often hold the app and every browser holds these configs, both keep it alive.
+    pub fn shutdown(&self) {
+        debug!("Shutting down browser configs");
+        for monitor in self.config_monitors.take() {
+            monitor.cancel();
+        }
+        if let Some(source_id) = self.pending_reload.take() {
+            source_id.remove();
+        }
+        self.changed_callbacks.borrow_mut().clear();
+        self.all_browsers.borrow_mut().clear();
+        self.uninstalled_browsers.borrow_mut().clear();
+    }
+
+    /// Configs that failed to load on the last read, with the file name and the error.
+    /// Taken, so every error is shown once.
+    pub fn take_load_errors(&self) -> Vec<ConfigLoadError> {
+        self.load_errors.take()
     }
 
-    pub fn get_all_browsers(&self) -> &Vec<Rc<Browser>> {
-        self.all_browsers.get().expect("Browsers are uninitialized")
+    fn queue_reload(self: &Rc<Self>) {
+        if let Some(source_id) = self.pending_reload.take() {
+            source_id.remove();
+        }
+
+        let self_clone = self.clone();
+        let source_id = glib::timeout_add_local_once(Self::RELOAD_DELAY, move || {
+            *self_clone.pending_reload.borrow_mut() = None;
+            info!("Reloading browser configs");
+
+            let self_clone = self_clone.clone();
+            glib::spawn_future_local(async move {
+                self_clone.refresh().await;
+            });
+        });
+        *self.pending_reload.borrow_mut() = Some(source_id);
+    }
+
+    pub fn get_generation(&self) -> u64 {
+        *self.generation.borrow()
+    }
+
+    fn build_installation_checks(browser_configs: &[Rc<BrowserConfig>]) -> Vec<InstallationCheck> {
+        browser_configs
+            .iter()
+            .map(|browser_config| InstallationCheck {
+                flatpak: browser_config.config.flatpak.clone(),
+                system_bin: browser_config.config.system_bin.clone(),
+                snap: browser_config.config.snap.clone(),
+                custom_bin: browser_config.config.custom_bin.clone(),
+            })
+            .collect()
+    }
+
+    async fn detect_installations_async(
+        installation_checks: Vec<InstallationCheck>,
+    ) -> Vec<DetectedInstallation> {
+        gio::spawn_blocking(move || Self::detect_installations(&installation_checks))
+            .await
+            .unwrap_or_else(|_| {
+                error!("Browser detection thread panicked");
+                Vec::new()
+            })
+    }
+
+    fn apply_detection(
+        self: &Rc<Self>,
+        browser_configs: &[Rc<BrowserConfig>],
+        detected_installations: &[DetectedInstallation],
+    ) {
+        self.set_browsers(browser_configs, detected_installations);
+        *self.generation.borrow_mut() += 1;
+
+        // Taken out while calling, so a callback can connect a new one
+        let callbacks = self.changed_callbacks.take();
+        let mut kept_callbacks: Vec<_> = callbacks
+            .into_iter()
+            .filter(|callback| callback() == glib::ControlFlow::Continue)
+            .collect();
+        let mut changed_callbacks = self.changed_callbacks.borrow_mut();
+        kept_callbacks.append(&mut changed_callbacks);
+        *changed_callbacks = kept_callbacks;
+    }
+
+    fn save_detection(
+        &self,
+        browser_configs: &[Rc<BrowserConfig>],
+        installation_checks: &[InstallationCheck],
+        detected_installations: &[DetectedInstallation],
+    ) {
+        // A panicked detection has no results, the next run probes again
+        if detected_installations.len() != browser_configs.len() {
+            return;
+        }
+        let _ = detection_cache::save(
+            &self.app_dirs,
+            browser_configs,
+            installation_checks,
+            detected_installations,
+        )
+        .inspect_err(|error| error!("Failed to save browser detection cache: {error:?}"));
+    }
+
+    pub fn get_all_browsers(&self) -> Vec<Rc<Browser>> {
+        self.all_browsers.borrow().clone()
     }
 
     pub fn get_flatpak_browsers(&self) -> Vec<Rc<Browser>> {
@@ -300,22 +967,39 @@ impl BrowserConfigs {
             .collect()
     }
 
+    pub fn get_snap_browsers(&self) -> Vec<Rc<Browser>> {
+        let all_browsers_borrow = self.get_all_browsers();
+        all_browsers_borrow
+            .iter()
+            .filter(|browser| browser.is_snap())
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_custom_browsers(&self) -> Vec<Rc<Browser>> {
+        let all_browsers_borrow = self.get_all_browsers();
+        all_browsers_borrow
+            .iter()
+            .filter(|browser| browser.is_custom())
+            .cloned()
+            .collect()
+    }
+
     pub fn get_uninstalled_browsers(&self) -> Vec<Rc<Browser>> {
-        self.uninstalled_browsers
-            .get()
-            .expect("Uninstalled browsers are uninitialized")
-            .clone()
+        self.uninstalled_browsers.borrow().clone()
     }
 
     pub fn get_by_id(&self, id: &str) -> Option<Rc<Browser>> {
-        self.get_all_browsers()
+        self.all_browsers
+            .borrow()
             .iter()
             .find(|browser| browser.id == id)
             .cloned()
     }
 
     pub fn get_index(&self, browser: &Browser) -> Option<usize> {
-        self.get_all_browsers()
+        self.all_browsers
+            .borrow()
             .iter()
             .position(|browser_iter| browser_iter.id == browser.id)
     }
@@ -326,8 +1010,217 @@ impl BrowserConfigs {
             return;
         }
 
+        let Some(icon_theme) = &self.icon_theme else {
+            return;
+        };
+        // The icon theme of the display outlives a restart of the app
+        if icon_theme
+            .search_path()
+            .iter()
+            .any(|search_path| search_path == path)
+        {
+            return;
+        }
+
         debug!("Adding icon path to icon theme: {}", path.display());
-        self.icon_theme.add_search_path(path);
+        icon_theme.add_search_path(path);
+    }
+
+    /// Shipped desktop file to start a user config from
+    pub fn get_desktop_template(base: Base) -> Option<&'static str> {
+        match base {
+            Base::Chromium => assets::get_browser_desktop_template("chromium"),
+            Base::Firefox => assets::get_browser_desktop_template("firefox"),
+            Base::None => None,
+        }
+    }
+
+    /// Writes a config with its desktop file in the override layer, app updates never touch it.
+    /// Call `refresh` afterwards to use it. Returns the path of the config file.
+    pub fn add_user_config(&self, user_config: &UserBrowserConfig) -> Result<PathBuf> {
+        let name = user_config.name.trim();
+        let desktop_file_name_prefix = user_config.desktop_file_name_prefix.trim();
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let flatpak = non_empty(&user_config.flatpak);
+        let system_bin = non_empty(&user_config.system_bin);
+
+        if name.is_empty() {
+            bail!("Name is missing")
+        }
+        if flatpak.is_none() && system_bin.is_none() {
+            bail!("A flatpak id or system binary is needed")
+        }
+        if [&flatpak, &system_bin]
+            .into_iter()
+            .flatten()
+            .any(|value| value.contains(char::is_whitespace))
+        {
+            bail!("Flatpak id and system binary can't contain spaces")
+        }
+        if user_config.base == Base::None {
+            bail!("Base must be chromium or firefox")
+        }
+        if desktop_file_name_prefix.is_empty() {
+            bail!("Desktop file name prefix is missing")
+        }
+        if self.get_browsers_from_files().iter().any(|browser_config| {
+            browser_config.config.desktop_file_name_prefix == desktop_file_name_prefix
+        }) {
+            bail!("Desktop file name prefix '{desktop_file_name_prefix}' is already used")
+        }
+
+        let config_name: String = name
+            .to_lowercase()
+            .chars()
+            .map(|char| if char.is_alphanumeric() { char } else { '-' })
+            .collect::<String>()
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        if config_name.is_empty() {
+            bail!("Name needs at least one letter or number")
+        }
+        let config_file_name = format!("{config_name}.yml");
+        let config_path = self
+            .app_dirs
+            .app_config_browser_config_overrides
+            .join(&config_file_name);
+        let desktop_file_path = self
+            .app_dirs
+            .app_config_browser_desktop_file_overrides
+            .join(format!("{config_name}.desktop"));
+
+        if assets::is_config_asset(&Path::new("browsers").join(&config_file_name)) {
+            bail!("'{name}' is a default browser and would be replaced by this config")
+        }
+        // Configs made before the override layer existed are in the shipped dirs
+        if config_path.exists()
+            || desktop_file_path.exists()
+            || self
+                .app_dirs
+                .app_config_browser_configs
+                .join(&config_file_name)
+                .exists()
+        {
+            bail!("A browser config '{config_file_name}' already exists")
+        }
+
+        DesktopEntry::from_str(
+            &desktop_file_path,
+            &user_config.desktop_template,
+            None::<&[String]>,
+        )
+        .context("Desktop file template is invalid")?;
+
+        let browser_yaml = BrowserYaml {
+            name: name.to_string(),
+            flatpak,
+            system_bin,
+            snap: None,
+            custom_bin: None,
+            can_isolate: user_config.can_isolate,
+            can_start_maximized: user_config.can_start_maximized,
+            can_fullscreen: user_config.can_fullscreen,
+            can_private: user_config.can_private,
+            can_force_dark: user_config.can_force_dark,
+            desktop_file_name_prefix: desktop_file_name_prefix.to_string(),
+            base: user_config.base.to_string(),
+            issues: HashMap::new(),
+            min_version: None,
+            version_issues: Vec::new(),
+        };
+        let yaml_string =
+            serde_yaml::to_string(&browser_yaml).context("Failed to serialize browser config")?;
+
+        // Desktop file first, a config without one is skipped on load
+        utils::files::write_atomic(&desktop_file_path, user_config.desktop_template.as_bytes())?;
+        utils::files::write_atomic(&config_path, yaml_string.as_bytes())?;
+        info!(
+            path = config_path.display().to_string(),
+            "Added user browser config"
+        );
+
+        Ok(config_path)
+    }
+
+    /// Desktop file of a browser as it is read on load, with the layer it comes from
+    pub fn read_desktop_template(&self, browser: &Browser) -> Result<(String, ConfigLayer)> {
+        let layered_config = config_layers::get_layered_configs(&ConfigDirs::new(&self.app_dirs))
+            .into_iter()
+            .find(|layered_config| layered_config.config_name == browser.config_name)
+            .context(format!("No browser config '{}'", browser.config_name))?;
+
+        let template = fs::read_to_string(&layered_config.desktop_file_path).context(format!(
+            "Failed to read to string: '{}'",
+            layered_config.desktop_file_path.display()
+        ))?;
+
+        Ok((template, layered_config.desktop_file_layer))
+    }
+
+    /// The app ships a desktop file for this browser, so it can be restored
+    pub fn has_shipped_desktop_template(browser: &Browser) -> bool {
+        assets::is_config_asset(&Self::get_shipped_desktop_template_path(browser))
+    }
+
+    /// Writes the desktop file of a browser in the override layer, the shipped one stays as is.
+    /// The config watcher reloads the browsers.
+    pub fn save_desktop_template(&self, browser: &Browser, template: &str) -> Result<PathBuf> {
+        let desktop_file_path = self
+            .app_dirs
+            .app_config_browser_desktop_file_overrides
+            .join(format!("{}.desktop", browser.config_name));
+
+        DesktopEntry::from_str(&desktop_file_path, template, None::<&[String]>)
+            .context("Desktop file template is invalid")?;
+
+        utils::files::write_atomic(&desktop_file_path, template.as_bytes())?;
+        info!(
+            path = desktop_file_path.display().to_string(),
+            "Saved browser desktop file override"
+        );
+
+        Ok(desktop_file_path)
+    }
+
+    /// Removes the override and writes the shipped desktop file again, in case it was edited
+    pub fn restore_desktop_template(&self, browser: &Browser) -> Result<()> {
+        if !Self::has_shipped_desktop_template(browser) {
+            bail!(
+                "Browser config '{}' has no shipped desktop file",
+                browser.config_name
+            )
+        }
+
+        let override_path = self
+            .app_dirs
+            .app_config_browser_desktop_file_overrides
+            .join(format!("{}.desktop", browser.config_name));
+        if override_path.is_file() {
+            fs::remove_file(&override_path)
+                .context(format!("Failed to remove: '{}'", override_path.display()))?;
+        }
+
+        assets::restore_config_file(
+            &self.app_dirs,
+            &Self::get_shipped_desktop_template_path(browser).to_string_lossy(),
+        )?;
+        info!(
+            config_name = browser.config_name,
+            "Restored shipped browser desktop file"
+        );
+
+        Ok(())
+    }
+
+    fn get_shipped_desktop_template_path(browser: &Browser) -> PathBuf {
+        Path::new("desktop-files").join(format!("{}.desktop", browser.config_name))
     }
 
     fn get_no_browser(self: &Rc<Self>) -> Browser {
@@ -337,40 +1230,121 @@ impl BrowserConfigs {
             installation: Installation::None,
             can_isolate: false,
             can_start_maximized: false,
+            can_fullscreen: false,
+            can_private: false,
+            can_force_dark: false,
             flatpak_id: None,
             executable: None,
+            snap_name: None,
+            custom_bin: None,
             desktop_file: DesktopEntry::from_appid("No browser".to_string()),
             desktop_file_name_prefix: String::default(),
             config_name: String::default(),
+            not_found: Vec::new(),
             configs: self.clone(),
             icon_names: HashSet::from(["dialog-warning-symbolic".to_string()]),
             base: Base::None,
             issues: HashMap::new(),
+            min_version: None,
+            version_issues: Vec::new(),
+            version: RefCell::new(VersionProbe::Done(None)),
             icon_theme: self.icon_theme.clone(),
             app_dirs: self.app_dirs.clone(),
         }
     }
 
-    fn set_browsers_from_files(self: &Rc<Self>) {
-        let browser_configs = self.get_browsers_from_files();
+    /// Every check runs on its own thread, results keep the order of the checks
+    fn detect_installations(
+        installation_checks: &[InstallationCheck],
+    ) -> Vec<DetectedInstallation> {
+        let has_snaps = installation_checks
+            .iter()
+            .any(|installation_check| installation_check.snap.is_some())
+            && Self::has_snaps();
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = installation_checks
+                .iter()
+                .map(|installation_check| {
+                    let flatpak_handle = installation_check
+                        .flatpak
+                        .as_ref()
+                        .map(|flatpak| scope.spawn(|| Self::is_installed_flatpak(flatpak)));
+                    let system_handle = installation_check
+                        .system_bin
+                        .as_ref()
+                        .map(|system_bin| scope.spawn(|| Self::get_system_bin_path(system_bin)));
+                    let snap_handle = installation_check
+                        .snap
+                        .as_ref()
+                        .filter(|_| has_snaps)
+                        .map(|snap| scope.spawn(|| Self::is_installed_snap(snap)));
+                    let custom_handle = installation_check
+                        .custom_bin
+                        .as_ref()
+                        .map(|custom_bin| scope.spawn(|| Self::is_installed_custom(custom_bin)));
+                    (flatpak_handle, system_handle, snap_handle, custom_handle)
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(
+                    |(flatpak_handle, system_handle, snap_handle, custom_handle)| {
+                        let is_flatpak =
+                            flatpak_handle.is_some_and(|handle| handle.join().unwrap_or(false));
+                        let is_snap =
+                            snap_handle.is_some_and(|handle| handle.join().unwrap_or(false));
+                        let is_custom =
+                            custom_handle.is_some_and(|handle| handle.join().unwrap_or(false));
+                        let system_bin_path =
+                            system_handle.and_then(|handle| handle.join().unwrap_or(None));
+                        // A snap on the PATH is not a system installation
+                        let is_system = system_bin_path.as_ref().is_some_and(|system_bin_path| {
+                            !(is_snap && system_bin_path.starts_with(Self::SNAP_BIN_DIR))
+                        });
+
+                        DetectedInstallation {
+                            is_flatpak,
+                            is_system,
+                            is_snap,
+                            is_custom,
+                            system_bin_path,
+                        }
+                    },
+                )
+                .collect()
+        })
+    }
+
+    /// Config order with flatpak, system, snap and then custom, so browser indexes are stable
+    fn set_browsers(
+        self: &Rc<Self>,
+        browser_configs: &[Rc<BrowserConfig>],
+        detected_installations: &[DetectedInstallation],
+    ) {
         let mut installed_browsers = Vec::new();
         let mut uninstalled_browsers = Vec::new();
 
-        for browser_config in browser_configs {
+        for (browser_config, detected_installation) in
+            browser_configs.iter().zip(detected_installations)
+        {
             let mut is_installed = false;
+            let not_found = Self::get_not_found(&browser_config.config, detected_installation);
 
             if let Some(flatpak) = &browser_config.config.flatpak {
-                if Self::is_installed_flatpak(flatpak) {
+                if detected_installation.is_flatpak {
                     info!(
                         "Found flatpak browser '{flatpak}' for config '{}'",
                         browser_config.file_name
                     );
 
                     let browser = Rc::new(Browser::new(
-                        &browser_config,
+                        browser_config,
                         Installation::Flatpak(flatpak.clone()),
+                        &not_found,
                         self,
-                        &self.icon_theme,
+                        self.icon_theme.as_ref(),
                         &self.app_dirs,
                     ));
 
@@ -391,17 +1365,18 @@ impl BrowserConfigs {
             }
 
             if let Some(system_bin) = &browser_config.config.system_bin {
-                if Self::is_installed_system(system_bin) {
+                if detected_installation.is_system {
                     info!(
                         "Found system browser '{system_bin}' for config '{}'",
                         browser_config.file_name
                     );
 
                     let browser = Rc::new(Browser::new(
-                        &browser_config,
+                        browser_config,
                         Installation::System(system_bin.clone()),
+                        &not_found,
                         self,
-                        &self.icon_theme,
+                        self.icon_theme.as_ref(),
                         &self.app_dirs,
                     ));
 
@@ -415,12 +1390,67 @@ impl BrowserConfigs {
                 }
             }
 
+            if let Some(snap) = &browser_config.config.snap {
+                if detected_installation.is_snap {
+                    info!(
+                        "Found snap browser '{snap}' for config '{}'",
+                        browser_config.file_name
+                    );
+
+                    let browser = Rc::new(Browser::new(
+                        browser_config,
+                        Installation::Snap(snap.clone()),
+                        &not_found,
+                        self,
+                        self.icon_theme.as_ref(),
+                        &self.app_dirs,
+                    ));
+
+                    installed_browsers.push(browser);
+                    is_installed = true;
+                } else {
+                    debug!(
+                        "Snap browser '{snap}' for '{}' is not installed",
+                        browser_config.file_name
+                    );
+                }
+            }
+
+            if let Some(custom_bin) = &browser_config.config.custom_bin {
+                if detected_installation.is_custom {
+                    info!(
+                        "Found custom browser '{}' for config '{}'",
+                        custom_bin.display(),
+                        browser_config.file_name
+                    );
+
+                    let browser = Rc::new(Browser::new(
+                        browser_config,
+                        Installation::Custom(custom_bin.clone()),
+                        &not_found,
+                        self,
+                        self.icon_theme.as_ref(),
+                        &self.app_dirs,
+                    ));
+
+                    installed_browsers.push(browser);
+                    is_installed = true;
+                } else {
+                    debug!(
+                        "Custom browser '{}' for '{}' is not installed",
+                        custom_bin.display(),
+                        browser_config.file_name
+                    );
+                }
+            }
+
             if !is_installed {
                 let browser = Rc::new(Browser::new(
-                    &browser_config,
+                    browser_config,
                     Installation::None,
+                    &not_found,
                     self,
-                    &self.icon_theme,
+                    self.icon_theme.as_ref(),
                     &self.app_dirs,
                 ));
                 uninstalled_browsers.push(browser);
@@ -430,11 +1460,54 @@ impl BrowserConfigs {
         let no_browser = self.get_no_browser();
         installed_browsers.push(Rc::new(no_browser));
 
-        let _ = self.all_browsers.set(installed_browsers);
-        let _ = self.uninstalled_browsers.set(uninstalled_browsers);
+        *self.all_browsers.borrow_mut() = installed_browsers;
+        *self.uninstalled_browsers.borrow_mut() = uninstalled_browsers;
+    }
+
+    /// Kept on the browsers, so the diagnostics can tell why a browser is missing
+    fn get_not_found(
+        config: &BrowserYaml,
+        detected_installation: &DetectedInstallation,
+    ) -> Vec<Installation> {
+        let mut not_found = Vec::new();
+
+        if let Some(flatpak) = &config.flatpak
+            && !detected_installation.is_flatpak
+        {
+            not_found.push(Installation::Flatpak(flatpak.clone()));
+        }
+        if let Some(system_bin) = &config.system_bin
+            && !detected_installation.is_system
+        {
+            not_found.push(Installation::System(system_bin.clone()));
+        }
+        if let Some(snap) = &config.snap
+            && !detected_installation.is_snap
+        {
+            not_found.push(Installation::Snap(snap.clone()));
+        }
+        if let Some(custom_bin) = &config.custom_bin
+            && !detected_installation.is_custom
+        {
+            not_found.push(Installation::Custom(custom_bin.clone()));
+        }
+
+        not_found
     }
 
+    /// Read from the installation dirs, the sandbox can read them too
     fn is_installed_flatpak(flatpak: &str) -> bool {
+        let installation_dirs = get_flatpak_installation_dirs();
+        if utils::env::is_command_detection_forced()
+            || !installation_dirs.iter().any(|dir| dir.is_dir())
+        {
+            return Self::is_installed_flatpak_command(flatpak);
+        }
+
+        is_installed_flatpak(flatpak, &installation_dirs)
+    }
+
+    fn is_installed_flatpak_command(flatpak: &str) -> bool {
         let command = format!("flatpak info {flatpak}");
         let result = utils::command::run_command_sync(&command);
 
@@ -447,10 +1520,93 @@ impl BrowserConfigs {
         }
     }
 
-    fn is_installed_system(system_bin: &str) -> bool {
+    /// Resolved binary path, `None` when not installed.
+    /// The sandbox has a `PATH` of its own, there the host is asked.
+    fn get_system_bin_path(system_bin: &str) -> Option<String> {
+        if utils::env::is_command_detection_forced() || utils::env::is_flatpak_container() {
+            return Self::get_system_bin_path_command(system_bin);
+        }
+
+        let path_dirs: Vec<PathBuf> = std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).collect())
+            .unwrap_or_default();
+        find_system_bin(system_bin, &path_dirs).map(|path| path.to_string_lossy().to_string())
+    }
+
+    fn get_system_bin_path_command(system_bin: &str) -> Option<String> {
         let command = format!("which {system_bin}");
         let result = utils::command::run_command_sync(&command);
 
+        match result {
+            Err(error) => {
+                error!("Could not run command '{command}'. Error: {error:?}");
+                None
+            }
+            Ok(response) => response.success.then_some(response.stdout),
+        }
+    }
+
+    /// Most hosts have no snapd, then no `snap` command is run for every snap browser. A failed
+    /// check counts as no snaps, like on a host without them.
+    fn has_snaps() -> bool {
+        if !utils::env::is_command_detection_forced() && !utils::env::is_flatpak_container() {
+            return Path::new(Self::SNAP_DIR).is_dir();
+        }
+
+        let command = format!("test -d {}", Self::SNAP_DIR);
+        match utils::command::run_command_sync(&command) {
+            Err(error) => {
+                debug!("Could not run command '{command}', skipping snaps. Error: {error:?}");
+                false
+            }
+            Ok(response) => {
+                if !response.success {
+                    debug!("No snaps on the host");
+                }
+                response.success
+            }
+        }
+    }
+
+    fn is_installed_snap(snap: &str) -> bool {
+        if !utils::env::is_command_detection_forced() && !utils::env::is_flatpak_container() {
+            return Path::new(Self::SNAP_DIR)
+                .join(snap)
+                .join("current")
+                .is_dir();
+        }
+
+        let command = format!("snap list {snap}");
+        let result = utils::command::run_command_sync(&command);
+
+        match result {
+            Err(error) => {
+                debug!("Could not run command '{command}'. Error: {error:?}");
+                false
+            }
+            Ok(response) => response.success,
+        }
+    }
+
+    /// Checked on the host, the binary is usually outside the sandbox
+    fn is_installed_custom(custom_bin: &Path) -> bool {
+        if !custom_bin.is_absolute() {
+            error!(
+                "Custom browser binary is not an absolute path: '{}'",
+                custom_bin.display()
+            );
+            return false;
+        }
+        if !utils::env::is_command_detection_forced() && !utils::env::is_flatpak_container() {
+            return is_executable_file(custom_bin);
+        }
+
+        let command = format!(
+            "test -f {path} -a -x {path}",
+            path = glib::shell_quote(custom_bin).to_string_lossy()
+        );
+        let result = utils::command::run_command_sync(&command);
+
         match result {
             Err(error) => {
                 error!("Could not run command '{command}'. Error: {error:?}");
@@ -498,69 +1654,114 @@ impl BrowserConfigs {
         }
     }
 
-    fn get_browsers_from_files(&self) -> Vec<Rc<BrowserConfig>> {
-        debug!("Loading browsers config files");
-
-        let mut browser_configs = Vec::new();
-        let browser_config_files =
-            utils::files::get_entries_in_dir(&self.app_dirs.app_config_browser_configs)
-                .unwrap_or_default();
-
-        for file in &browser_config_files {
-            let file_name = file.file_name().to_string_lossy().to_string();
-            let file_path = file.path();
-            let Some(config_name) = file
-                .path()
-                .file_stem()
-                .map(|stem| stem.to_string_lossy().to_string())
-            else {
-                debug!("Invalid file, failed to get file stem: '{file_name}'");
-                continue;
-            };
+    /// Read a browser config, a file that is being written to is retried once.
+    /// Falls back to the last config that parsed, so a broken edit does not remove the browser.
+    fn read_browser_yaml(
+        &self,
+        file_path: &Path,
+        file_name: &str,
+        layer: ConfigLayer,
+    ) -> Result<BrowserYaml> {
+        let parse_file = |path: &Path| -> Result<(String, BrowserYaml)> {
+            let file_string = fs::read_to_string(path)
+                .context(format!("Failed to read to string: '{}'", path.display()))?;
+            let browser = serde_yaml::from_str(&file_string)?;
+            Ok((file_string, browser))
+        };
+        // An override has the same file name as the shipped config it replaces
+        let last_good_dir = self.app_dirs.app_cache.join(Self::LAST_GOOD_CONFIGS_DIR);
+        let last_good_path = match layer {
+            ConfigLayer::Shipped => last_good_dir.join(file_name),
+            ConfigLayer::Override => last_good_dir
+                .join(Self::LAST_GOOD_OVERRIDES_DIR)
+                .join(file_name),
+        };
 
-            let extension = file_path.extension().unwrap_or_default().to_string_lossy();
-            debug!("Loading browser config: '{file_name}'");
+        let mut result = parse_file(file_path);
+        if result.is_err() && utils::files::is_recently_modified(file_path, Self::RECENT_WRITE) {
+            debug!("Browser config '{file_name}' might still be written to, retrying");
+            thread::sleep(Self::RETRY_DELAY);
+            result = parse_file(file_path);
+        }
 
-            if extension != "yml" && extension != "yaml" {
-                debug!("Not a yml file: '{file_name}'");
-                continue;
+        match result {
+            Ok((file_string, browser)) => {
+                if let Some(parent) = last_good_path.parent()
+                    && fs::create_dir_all(parent).is_ok()
+                    && let Err(error) =
+                        utils::files::write_atomic(&last_good_path, file_string.as_bytes())
+                {
+                    error!(?error, "Failed to cache browser config '{file_name}'");
+                }
+                Ok(browser)
+            }
+            Err(error) if last_good_path.is_file() => {
+                error!(
+                    ?error,
+                    "Failed to parse browser config '{file_name}', using last working config"
+                );
+                let (_, browser) = parse_file(&last_good_path)?;
+                self.add_load_error(file_name, &error);
+                Ok(browser)
             }
+            Err(error) => Err(error),
+        }
+    }
 
-            let Ok(file_string) = fs::read_to_string(&file_path) else {
-                error!("Failed to read to string: '{file_name}'");
-                continue;
-            };
-            let browser: BrowserYaml = match serde_yaml::from_str(&file_string) {
+    fn add_load_error(&self, file_name: &str, error: &anyhow::Error) {
+        self.load_errors.borrow_mut().push(ConfigLoadError {
+            file_name: file_name.to_string(),
+            // The root cause has the line and column of a YAML error
+            error: error.root_cause().to_string(),
+        });
+    }
+
+    /// Each config and desktop file is read from the override layer when it has one, see
+    /// `config_layers`
+    fn get_browsers_from_files(&self) -> Vec<Rc<BrowserConfig>> {
+        debug!("Loading browsers config files");
+        self.load_errors.borrow_mut().clear();
+
+        let mut browser_configs = Vec::new();
+        let layered_configs = config_layers::get_layered_configs(&ConfigDirs::new(&self.app_dirs));
+
+        for layered_config in layered_configs {
+            let file_name = layered_config.get_file_name();
+            info!(
+                "Loading browser config '{file_name}' from the {} layer, desktop file from the {} layer",
+                layered_config.config_layer, layered_config.desktop_file_layer
+            );
+
+            let browser = match self.read_browser_yaml(
+                &layered_config.config_path,
+                &file_name,
+                layered_config.config_layer,
+            ) {
                 Ok(result) => result,
                 Err(error) => {
                     error!("Failed to parse yml: '{file_name}'. Error: '{error:?}'");
+                    self.add_load_error(&file_name, &error);
                     continue;
                 }
             };
 
-            let desktop_file = match (|| -> Result<DesktopEntry> {
-                let desktop_file_path = self
-                    .app_dirs
-                    .app_config_browser_desktop_files
-                    .join(
-                        file_path
-                            .file_stem()
-                            .context("Could not get the file stem")?,
-                    )
-                    .with_extension("desktop");
-                let desktop_file = DesktopEntry::from_path(&desktop_file_path, None::<&[String]>)?;
-                Ok(desktop_file)
-            })() {
-                Ok(result) => result,
-                Err(error) => {
-                    error!("Failed to parse .desktop file for: '{file_name}'. Error: '{error:?}'");
-                    continue;
-                }
-            };
+            let desktop_file =
+                match DesktopEntry::from_path(&layered_config.desktop_file_path, None::<&[String]>)
+                {
+                    Ok(result) => result,
+                    Err(error) => {
+                        let error = anyhow::Error::new(error);
+                        error!(
+                            "Failed to parse .desktop file for: '{file_name}'. Error: '{error:?}'"
+                        );
+                        self.add_load_error(&file_name, &error);
+                        continue;
+                    }
+                };
 
             let browser_config = BrowserConfig {
                 config: browser,
-                config_name,
+                config_name: layered_config.config_name,
                 file_name,
                 desktop_file,
             };
@@ -570,3 +1771,262 @@ impl BrowserConfigs {
         browser_configs
     }
 }
+
+/// The user installation first, like `flatpak` itself. Inside the sandbox the data dir is the one
+/// of this app, the user installation is read from the home dir.
+fn get_flatpak_installation_dirs() -> Vec<PathBuf> {
+    let user_dir = std::env::var_os("FLATPAK_USER_DIR").map_or_else(
+        || {
+            if utils::env::is_flatpak_container() {
+                glib::home_dir().join(".local/share/flatpak")
+            } else {
+                glib::user_data_dir().join("flatpak")
+            }
+        },
+        PathBuf::from,
+    );
+    let system_dir = std::env::var_os("FLATPAK_SYSTEM_DIR")
+        .map_or_else(|| PathBuf::from("/var/lib/flatpak"), PathBuf::from);
+
+    vec![user_dir, system_dir]
+}
+
+/// Deployed apps have an `active` link to the installed commit, a removed app can leave its dir
+fn is_installed_flatpak(flatpak: &str, installation_dirs: &[PathBuf]) -> bool {
+    installation_dirs.iter().any(|installation_dir| {
+        installation_dir
+            .join("app")
+            .join(flatpak)
+            .join("current")
+            .join("active")
+            .is_dir()
+    })
+}
+
+/// Like `which`, the first executable file in the dirs. A name with a slash is used as is.
+fn find_system_bin(system_bin: &str, path_dirs: &[PathBuf]) -> Option<PathBuf> {
+    if system_bin.contains('/') {
+        let path = PathBuf::from(system_bin);
+        return is_executable_file(&path).then_some(path);
+    }
+
+    path_dirs
+        .iter()
+        .map(|dir| dir.join(system_bin))
+        .find(|path| is_executable_file(path))
+}
+
+fn is_executable_file(path: &Path) -> bool {
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(test)]
+impl BrowserConfigs {
+    /// Reads the configs of the app dirs, every config is installed as its flatpak and its system
+    /// binary
+    pub(crate) fn load_as_installed_browsers(self: &Rc<Self>) {
+        let browser_configs = self.get_browsers_from_files();
+        let detected_installations: Vec<_> = browser_configs
+            .iter()
+            .map(|browser_config| DetectedInstallation {
+                is_flatpak: browser_config.config.flatpak.is_some(),
+                is_system: browser_config.config.system_bin.is_some(),
+                is_snap: false,
+                is_custom: false,
+                system_bin_path: None,
+            })
+            .collect();
+
+        self.apply_detection(&browser_configs, &detected_installations);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, time::SystemTime};
+
+    const FIREFOX_YAML: &str = "name: Firefox\n\
+        system_bin: firefox\n\
+        desktop_file_name_prefix: org.mozilla.firefox\n\
+        base: firefox\n";
+    const PARTIAL_YAML: &str = "name: Firefox\nsystem_bin: [firef";
+
+    fn get_browser_configs(dir: &Path) -> Rc<BrowserConfigs> {
+        BrowserConfigs::new_headless(&Rc::new(AppDirs {
+            app_cache: dir.join("cache"),
+            ..AppDirs::default()
+        }))
+    }
+
+    /// Written long ago, so a failed parse is not retried
+    fn write_old_config(path: &Path, contents: &str) {
+        fs::write(path, contents).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+    }
+
+    #[test]
+    fn parsed_config_is_kept_as_last_working_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let browser_configs = get_browser_configs(dir.path());
+        let path = dir.path().join("firefox.yml");
+        fs::write(&path, FIREFOX_YAML).unwrap();
+
+        let browser = browser_configs
+            .read_browser_yaml(&path, "firefox.yml", ConfigLayer::Shipped)
+            .unwrap();
+
+        assert_eq!(browser.name, "Firefox");
+        assert_eq!(
+            fs::read_to_string(
+                dir.path()
+                    .join("cache")
+                    .join(BrowserConfigs::LAST_GOOD_CONFIGS_DIR)
+                    .join("firefox.yml")
+            )
+            .unwrap(),
+            FIREFOX_YAML
+        );
+    }
+
+    #[test]
+    fn partial_config_falls_back_to_the_last_working_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let browser_configs = get_browser_configs(dir.path());
+        let path = dir.path().join("firefox.yml");
+        fs::write(&path, FIREFOX_YAML).unwrap();
+        browser_configs
+            .read_browser_yaml(&path, "firefox.yml", ConfigLayer::Shipped)
+            .unwrap();
+        write_old_config(&path, PARTIAL_YAML);
+
+        let browser = browser_configs
+            .read_browser_yaml(&path, "firefox.yml", ConfigLayer::Shipped)
+            .unwrap();
+
+        assert_eq!(browser.system_bin.as_deref(), Some("firefox"));
+        let load_errors = browser_configs.take_load_errors();
+        assert_eq!(load_errors.len(), 1);
+        assert_eq!(load_errors[0].file_name, "firefox.yml");
+    }
+
+    #[test]
+    fn partial_config_without_a_last_working_config_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let browser_configs = get_browser_configs(dir.path());
+        let path = dir.path().join("firefox.yml");
+        write_old_config(&path, PARTIAL_YAML);
+
+        assert!(
+            browser_configs
+                .read_browser_yaml(&path, "firefox.yml", ConfigLayer::Shipped)
+                .is_err()
+        );
+        assert!(!dir.path().join("cache").exists());
+    }
+
+    #[test]
+    fn override_does_not_replace_the_last_working_shipped_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let browser_configs = get_browser_configs(dir.path());
+        let shipped_path = dir.path().join("firefox.yml");
+        let override_path = dir.path().join("override.yml");
+        fs::write(&shipped_path, FIREFOX_YAML).unwrap();
+        fs::write(
+            &override_path,
+            FIREFOX_YAML.replace("name: Firefox", "name: Firefox Nightly"),
+        )
+        .unwrap();
+        browser_configs
+            .read_browser_yaml(&shipped_path, "firefox.yml", ConfigLayer::Shipped)
+            .unwrap();
+        browser_configs
+            .read_browser_yaml(&override_path, "firefox.yml", ConfigLayer::Override)
+            .unwrap();
+        write_old_config(&shipped_path, PARTIAL_YAML);
+
+        let browser = browser_configs
+            .read_browser_yaml(&shipped_path, "firefox.yml", ConfigLayer::Shipped)
+            .unwrap();
+
+        assert_eq!(browser.name, "Firefox");
+    }
+
+    fn write_executable(path: &Path, mode: u32) {
+        fs::write(path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn flatpak_is_installed_only_with_an_active_deployment() {
+        let user_dir = tempfile::tempdir().unwrap();
+        let system_dir = tempfile::tempdir().unwrap();
+        let installation_dirs = vec![
+            user_dir.path().to_path_buf(),
+            system_dir.path().to_path_buf(),
+        ];
+        fs::create_dir_all(
+            system_dir
+                .path()
+                .join("app/org.mozilla.firefox/current/active"),
+        )
+        .unwrap();
+        // Left behind by a removed app
+        fs::create_dir_all(user_dir.path().join("app/com.google.Chrome/current")).unwrap();
+
+        assert!(is_installed_flatpak(
+            "org.mozilla.firefox",
+            &installation_dirs
+        ));
+        assert!(!is_installed_flatpak(
+            "com.google.Chrome",
+            &installation_dirs
+        ));
+        assert!(!is_installed_flatpak(
+            "org.chromium.Chromium",
+            &installation_dirs
+        ));
+    }
+
+    #[test]
+    fn system_bin_is_the_first_executable_on_the_path() {
+        let first_dir = tempfile::tempdir().unwrap();
+        let second_dir = tempfile::tempdir().unwrap();
+        let path_dirs = vec![
+            first_dir.path().to_path_buf(),
+            second_dir.path().to_path_buf(),
+        ];
+        write_executable(&first_dir.path().join("firefox"), 0o644);
+        write_executable(&second_dir.path().join("firefox"), 0o755);
+        fs::create_dir(first_dir.path().join("chromium")).unwrap();
+
+        assert_eq!(
+            find_system_bin("firefox", &path_dirs),
+            Some(second_dir.path().join("firefox"))
+        );
+        assert_eq!(find_system_bin("chromium", &path_dirs), None);
+        assert_eq!(find_system_bin("brave", &path_dirs), None);
+    }
+
+    #[test]
+    fn system_bin_with_a_slash_is_not_searched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("firefox");
+        write_executable(&path, 0o755);
+
+        assert_eq!(
+            find_system_bin(&path.to_string_lossy(), &[]),
+            Some(path.clone())
+        );
+        assert_eq!(
+            find_system_bin("bin/firefox", &[dir.path().to_path_buf()]),
+            None
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{app_dirs::AppDirs, utils};
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, info};
+
+/// Where a browser config or desktop file is read from. A file in the override layer
+/// completely replaces the shipped file with the same name, shipped files are updated with the
+/// app and overrides are never touched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigLayer {
+    Shipped,
+    Override,
+}
+impl Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Shipped => write!(f, "shipped"),
+            Self::Override => write!(f, "override"),
+        }
+    }
+}
+
+/// The dirs of both layers, can be sent to a blocking thread unlike `Rc<AppDirs>`
+#[derive(Clone)]
+pub struct ConfigDirs {
+    configs: PathBuf,
+    desktop_files: PathBuf,
+    config_overrides: PathBuf,
+    desktop_file_overrides: PathBuf,
+}
+impl ConfigDirs {
+    pub fn new(app_dirs: &AppDirs) -> Self {
+        Self {
+            configs: app_dirs.app_config_browser_configs.clone(),
+            desktop_files: app_dirs.app_config_browser_desktop_files.clone(),
+            config_overrides: app_dirs.app_config_browser_config_overrides.clone(),
+            desktop_file_overrides: app_dirs.app_config_browser_desktop_file_overrides.clone(),
+        }
+    }
+}
+
+/// The config and desktop file of one browser, each from the highest layer that has it
+pub struct LayeredConfig {
+    /// File stem, the config and desktop file share it
+    pub config_name: String,
+    pub config_path: PathBuf,
+    pub config_layer: ConfigLayer,
+    pub desktop_file_path: PathBuf,
+    pub desktop_file_layer: ConfigLayer,
+}
+impl LayeredConfig {
+    pub fn get_file_name(&self) -> String {
+        self.config_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// Only the flag, a config with just `disabled: true` has none of the other fields
+#[derive(serde::Deserialize)]
+struct DisabledFlag {
+    #[serde(default)]
+    disabled: bool,
+}
+
+/// Shipped configs keep the order of their dir, configs that only exist as an override follow.
+/// A config that sets `disabled: true` is left out, so an override can hide a shipped browser.
+pub fn get_layered_configs(config_dirs: &ConfigDirs) -> Vec<LayeredConfig> {
+    let mut config_paths: Vec<(String, PathBuf, ConfigLayer)> = Vec::new();
+
+    for (dir, layer) in [
+        (&config_dirs.configs, ConfigLayer::Shipped),
+        (&config_dirs.config_overrides, ConfigLayer::Override),
+    ] {
+        for (config_name, config_path) in get_yaml_files(dir) {
+            match config_paths
+                .iter_mut()
+                .find(|(name, _, _)| *name == config_name)
+            {
+                Some(config_path_entry) => *config_path_entry = (config_name, config_path, layer),
+                None => config_paths.push((config_name, config_path, layer)),
+            }
+        }
+    }
+
+    config_paths
+        .into_iter()
+        .filter(|(config_name, config_path, layer)| {
+            if is_disabled(config_path) {
+                info!("Browser config '{config_name}' is disabled in the {layer} layer");
+                return false;
+            }
+            true
+        })
+        .map(|(config_name, config_path, config_layer)| {
+            let override_path = config_dirs
+                .desktop_file_overrides
+                .join(format!("{config_name}.desktop"));
+            let (desktop_file_path, desktop_file_layer) = if override_path.is_file() {
+                (override_path, ConfigLayer::Override)
+            } else {
+                (
+                    config_dirs
+                        .desktop_files
+                        .join(format!("{config_name}.desktop")),
+                    ConfigLayer::Shipped,
+                )
+            };
+
+            LayeredConfig {
+                config_name,
+                config_path,
+                config_layer,
+                desktop_file_path,
+                desktop_file_layer,
+            }
+        })
+        .collect()
+}
+
+fn get_yaml_files(dir: &Path) -> Vec<(String, PathBuf)> {
+    utils::files::get_entries_in_dir(dir)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let is_yaml = path
+                .extension()
+                .is_some_and(|extension| extension == "yml" || extension == "yaml");
+            if !is_yaml {
+                debug!("Not a yml file: '{}'", path.display());
+                return None;
+            }
+            let config_name = path.file_stem()?.to_string_lossy().to_string();
+            Some((config_name, path))
+        })
+        .collect()
+}
+
+/// An unreadable file is not disabled, loading it reports the error
+fn is_disabled(config_path: &Path) -> bool {
+    fs::read_to_string(config_path)
+        .ok()
+        .and_then(|contents| serde_yaml::from_str::<DisabledFlag>(&contents).ok())
+        .is_some_and(|flag| flag.disabled)
+}
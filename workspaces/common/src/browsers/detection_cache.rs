@@ -0,0 +1,107 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::{BrowserConfig, DetectedInstallation, InstallationCheck},
+    utils::files,
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::PathBuf,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::debug;
+
+const DETECTION_CACHE_FILE: &str = "browser-detection.json";
+
+/// The result of the last detection, for a start without probing every browser
+#[derive(Serialize, Deserialize)]
+struct DetectionCache {
+    /// Seconds since the Unix epoch
+    probed_at: u64,
+    entries: Vec<CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    config_file_name: String,
+    /// What was checked, a changed config is probed again
+    installation_check: InstallationCheck,
+    detected_installation: DetectedInstallation,
+}
+
+/// `None` when a config has no entry or was changed since, then every browser is probed.
+/// A file that can't be read or parsed is ignored, the next detection writes a new one.
+pub(super) fn load(
+    app_dirs: &AppDirs,
+    browser_configs: &[Rc<BrowserConfig>],
+    installation_checks: &[InstallationCheck],
+) -> Option<Vec<DetectedInstallation>> {
+    let file_path = get_file_path(app_dirs);
+    let contents = fs::read(&file_path).ok()?;
+    let Ok(detection_cache) = serde_json::from_slice::<DetectionCache>(&contents) else {
+        debug!(path = %file_path.display(), "Ignoring unreadable browser detection cache");
+        return None;
+    };
+
+    let detected_installations = browser_configs
+        .iter()
+        .zip(installation_checks)
+        .map(|(browser_config, installation_check)| {
+            detection_cache
+                .entries
+                .iter()
+                .find(|entry| {
+                    entry.config_file_name == browser_config.file_name
+                        && entry.installation_check == *installation_check
+                })
+                .map(|entry| entry.detected_installation.clone())
+        })
+        .collect::<Option<Vec<DetectedInstallation>>>()?;
+
+    debug!(
+        probed_at = detection_cache.probed_at,
+        "Loaded browser detection cache"
+    );
+    Some(detected_installations)
+}
+
+pub(super) fn save(
+    app_dirs: &AppDirs,
+    browser_configs: &[Rc<BrowserConfig>],
+    installation_checks: &[InstallationCheck],
+    detected_installations: &[DetectedInstallation],
+) -> Result<()> {
+    let probed_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("System time is before the Unix epoch")?
+        .as_secs();
+    let entries = browser_configs
+        .iter()
+        .zip(installation_checks)
+        .zip(detected_installations)
+        .map(
+            |((browser_config, installation_check), detected_installation)| CacheEntry {
+                config_file_name: browser_config.file_name.clone(),
+                installation_check: installation_check.clone(),
+                detected_installation: detected_installation.clone(),
+            },
+        )
+        .collect();
+
+    let json = serde_json::to_vec_pretty(&DetectionCache { probed_at, entries })
+        .context("Failed to serialize browser detection cache")?;
+    let file_path = get_file_path(app_dirs);
+    if let Some(dir) = file_path.parent() {
+        fs::create_dir_all(dir).context(format!("Failed to create dir: {}", dir.display()))?;
+    }
+    files::write_atomic(&file_path, &json)?;
+
+    debug!(path = %file_path.display(), "Saved browser detection cache");
+    Ok(())
+}
+
+fn get_file_path(app_dirs: &AppDirs) -> PathBuf {
+    app_dirs.app_data.join(DETECTION_CACHE_FILE)
+}
@@ -0,0 +1,76 @@
+use std::{cmp::Ordering, collections::HashMap, fmt::Display};
+
+/// Issues of browser versions older than `below`, like a broken `--app` flag
+#[derive(Debug, PartialEq, Clone, serde::Serialize, serde::Deserialize)]
+pub struct VersionIssue {
+    pub below: String,
+    /// Same as `issues` of a browser config, keyed by language
+    pub issues: HashMap<String, Vec<String>>,
+}
+
+/// A version like `120.0.6099.109`. Browsers use more parts than semver, every part counts and
+/// missing parts are zero, so `120` equals `120.0`.
+#[derive(Debug, Clone)]
+pub struct BrowserVersion {
+    parts: Vec<u64>,
+    text: String,
+}
+impl BrowserVersion {
+    /// Only the leading digits and dots are read, `121.0b3` is `121.0`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches(['v', 'V']);
+        let end = text
+            .find(|char: char| !char.is_ascii_digit() && char != '.')
+            .unwrap_or(text.len());
+        let text = text[..end].trim_end_matches('.');
+
+        let parts = text
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()?;
+
+        Some(Self {
+            parts,
+            text: text.to_string(),
+        })
+    }
+
+    /// First version in the output of `--version`. Some browsers print it on stderr, or print
+    /// a banner line first, so every line is searched for a word with a dot.
+    pub fn from_output(output: &str) -> Option<Self> {
+        output
+            .lines()
+            .flat_map(str::split_whitespace)
+            .filter(|word| word.contains('.'))
+            .find_map(Self::parse)
+    }
+}
+impl Display for BrowserVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+impl Ord for BrowserVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.parts.len().max(other.parts.len());
+        (0..len)
+            .map(|index| {
+                let part = self.parts.get(index).copied().unwrap_or(0);
+                let other_part = other.parts.get(index).copied().unwrap_or(0);
+                part.cmp(&other_part)
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+impl PartialOrd for BrowserVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl PartialEq for BrowserVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for BrowserVersion {}
@@ -19,6 +19,12 @@ pub struct CacheWindowSettings {
 pub struct CacheSettingsYaml {
     #[serde(default)]
     pub window: CacheWindowSettings,
+    /// Hours before the cached icons of a site are fetched again
+    #[serde(default)]
+    pub icon_cache_ttl_hours: Option<u64>,
+    /// Key of the page shown when the window closed
+    #[serde(default)]
+    pub last_page: Option<String>,
 }
 
 #[derive(Debug)]
@@ -45,12 +51,19 @@ impl CacheSettings {
         }
     }
 
+    /// A maximized window keeps the size it had before, to restore it when unmaximized
     pub fn set_window_size(&mut self, width: i32, height: i32, maximized: bool) {
-        self.settings.window.width = width;
-        self.settings.window.height = height;
+        if !maximized {
+            self.settings.window.width = width;
+            self.settings.window.height = height;
+        }
         self.settings.window.maximized = maximized;
     }
 
+    pub fn set_last_page(&mut self, page_key: &str) {
+        self.settings.last_page = Some(page_key.to_string());
+    }
+
     pub fn reset(&mut self) {
         self.settings = CacheSettingsYaml::default();
         let _ = self.save();
@@ -21,6 +21,19 @@ pub static LICENSE: OnceLock<String> = OnceLock::new();
 pub static REPOSITORY: OnceLock<String> = OnceLock::new();
 pub static ISSUES_URL: OnceLock<String> = OnceLock::new();
 pub static BIN_NAME: OnceLock<String> = OnceLock::new();
+/// A Devel build installs next to the stable one, with its own id, dirs and web apps
+pub static IS_DEVEL: OnceLock<bool> = OnceLock::new();
+/// Id of the stable build, the shipped desktop assets are named after it
+pub static BASE_APP_ID: OnceLock<String> = OnceLock::new();
+/// `APP_NAME_SHORT` of the other build, stable for Devel and Devel for stable
+pub static OTHER_APP_NAME_SHORT: OnceLock<String> = OnceLock::new();
+
+pub const DEVEL_APP_ID_SUFFIX: &str = ".Devel";
+const DEVEL_NAME_HYPHEN_SUFFIX: &str = "-devel";
+/// No hyphen, the keys of stable web apps are `X-WAH-*` and would match `X-WAH-DEVEL`
+const DEVEL_NAME_SHORT_SUFFIX: &str = "dev";
+/// Set when building, like in the Devel flatpak manifest, or when running
+const DEVEL_ENV: &str = "APP_DEVEL";
 
 #[derive(Deserialize)]
 struct CargoPackageToml {
@@ -77,7 +90,23 @@ fn set_from_cargo_toml() {
         .collect::<String>()
         .to_lowercase();
 
-    let id = format!("org.pvermeer.{name_dense}");
+    let base_id = format!("org.pvermeer.{name_dense}");
+    let is_devel = is_devel();
+    let (id, name_hyphen, name_short, other_name_short) = if is_devel {
+        (
+            format!("{base_id}{DEVEL_APP_ID_SUFFIX}"),
+            format!("{name_hyphen}{DEVEL_NAME_HYPHEN_SUFFIX}"),
+            format!("{name_short}{DEVEL_NAME_SHORT_SUFFIX}"),
+            name_short,
+        )
+    } else {
+        (
+            base_id.clone(),
+            name_hyphen,
+            name_short.clone(),
+            format!("{name_short}{DEVEL_NAME_SHORT_SUFFIX}"),
+        )
+    };
     let developer = authors
         .first()
         .expect("Could not load developer / author")
@@ -98,6 +127,16 @@ fn set_from_cargo_toml() {
     let _ = REPOSITORY.set(repository);
     let _ = ISSUES_URL.set(issues_url);
     let _ = BIN_NAME.set(bin_name);
+    let _ = IS_DEVEL.set(is_devel);
+    let _ = BASE_APP_ID.set(base_id);
+    let _ = OTHER_APP_NAME_SHORT.set(other_name_short);
+}
+
+fn is_devel() -> bool {
+    let is_enabled = |value: &str| matches!(value, "1" | "true");
+
+    option_env!("APP_DEVEL").is_some_and(is_enabled)
+        || std::env::var(DEVEL_ENV).is_ok_and(|value| is_enabled(&value))
 }
 
 fn set_from_assets() {
@@ -111,6 +150,7 @@ fn set_from_assets() {
 pub fn log_all_values_debug() {
     debug!(
         APP_ID = APP_ID.get_value(),
+        IS_DEVEL = IS_DEVEL.get_value(),
         VERSION = VERSION.get_value(),
         APP_NAME = APP_NAME.get_value(),
         APP_NAME_HYPHEN = APP_NAME_HYPHEN.get_value(),
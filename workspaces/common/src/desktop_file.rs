@@ -1,31 +1,61 @@
+mod actions;
+mod autostart;
+pub mod browser_migration;
+pub mod bundle;
 pub mod category;
+pub mod config_snippet;
+mod custom_keys;
+pub mod desktop_database;
+mod duplicate;
 pub mod error;
-mod key;
+pub mod foreign;
+mod handlers;
+pub mod key;
+pub mod launcher_backend;
+pub mod lint;
+pub mod migration;
+mod profile_config;
+pub mod removal;
+mod template;
+pub mod template_preview;
+pub mod trash;
 mod utils;
 
 use crate::{
     app_dirs::AppDirs,
     browsers::{Base, Browser, BrowserConfigs},
     config::{self},
-    utils::OnceLockExt,
+    detached_profiles::{DetachedProfile, DetachedProfiles},
+    policy,
+    utils::{
+        OnceLockExt,
+        command::{self, BackgroundCommand},
+        env, files,
+    },
 };
 use anyhow::{Context, Result, anyhow, bail};
 use category::Category;
-use error::{DesktopFileError, ValidationError};
+use error::{DeleteError, DesktopFileError, LaunchError, ValidationError};
 use freedesktop_desktop_entry::DesktopEntry;
-use gtk::{Image, prelude::WidgetExt};
+use gtk::{Image, glib, prelude::WidgetExt};
 use key::Key;
+use lint::LintLevel;
+use migration::MigrationReport;
 use rand::{Rng, distributions::Alphanumeric};
-use regex::Regex;
+use removal::{DeleteOutcome, DeleteReport, ItemKind};
 use semver::Version;
 use std::{
+    collections::HashMap,
+    fmt::Write as _,
     fs::{self},
     path::{Path, PathBuf},
     rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
 };
+use template::{Replacement, UnknownKeys};
 use tracing::{debug, error, info};
 use url::Url;
-use utils::{map_to_bool_option, map_to_path_option, map_to_string_option};
+use utils::{FromEntryValue, ToEntryValue};
 
 pub struct DesktopFileEntries {
     name: String,
@@ -37,10 +67,27 @@ pub struct DesktopFileEntries {
     domain: String,
     isolate: bool,
     maximize: bool,
+    fullscreen: bool,
+    private: bool,
+    force_dark: bool,
+    /// Width and height, `None` is the browser default
+    window_size: Option<(u32, u32)>,
     icon_path: PathBuf,
     profile_path: PathBuf,
 }
 
+pub struct DeleteOptions {
+    /// Remove the isolated profile, otherwise it is kept as a detached profile
+    pub delete_profile: bool,
+}
+impl Default for DeleteOptions {
+    fn default() -> Self {
+        Self {
+            delete_profile: true,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct DesktopFile {
     desktop_entry: DesktopEntry,
@@ -48,11 +95,26 @@ pub struct DesktopFile {
     app_dirs: Rc<AppDirs>,
 }
 impl DesktopFile {
+    const UNIQUE_ID_ATTEMPTS: usize = 10;
+
     pub fn is_owned(desktop_file_path: &Path) -> Result<bool> {
-        let desktop_entry = DesktopEntry::from_path(desktop_file_path, None::<&[String]>)?;
+        Self::has_owned_key(desktop_file_path, &Key::Gwa.to_string())
+    }
+
+    /// A web app of the other build, stable or Devel. Not owned, but not foreign either.
+    pub fn is_owned_by_other_build(desktop_file_path: &Path) -> Result<bool> {
+        let other_app_key = format!(
+            "X-{}",
+            config::OTHER_APP_NAME_SHORT.get_value().to_uppercase()
+        );
+        Self::has_owned_key(desktop_file_path, &other_app_key)
+    }
+
+    fn has_owned_key(desktop_file_path: &Path, key: &str) -> Result<bool> {
+        let desktop_entry = utils::read_desktop_entry(desktop_file_path)?;
         let is_owned = desktop_entry
-            .desktop_entry(&Key::Gwa.to_string())
-            .and_then(map_to_bool_option)
+            .desktop_entry(key)
+            .and_then(bool::from_entry_value)
             .is_some_and(|is_owned| is_owned);
 
         Ok(is_owned)
@@ -61,12 +123,7 @@ impl DesktopFile {
     pub fn new(browser_configs: &Rc<BrowserConfigs>, app_dirs: &Rc<AppDirs>) -> Self {
         let mut desktop_entry = DesktopEntry::from_appid(String::new());
 
-        let random_id: String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(8)
-            .map(char::from)
-            .collect();
-        desktop_entry.add_desktop_entry(Key::Id.to_string(), random_id);
+        desktop_entry.add_desktop_entry(Key::Id.to_string(), Self::build_random_id());
 
         let version = config::VERSION.get_value().clone();
         desktop_entry.add_desktop_entry(Key::Version.to_string(), version);
@@ -78,12 +135,95 @@ impl DesktopFile {
         }
     }
 
+    /// Web apps in the applications dir and launchers of the portal, files that can't be read are
+    /// skipped
+    pub fn get_all_owned(
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Vec<Self> {
+        let mut desktop_files: Vec<Self> = launcher_backend::get_launcher_dirs(app_dirs)
+            .into_iter()
+            .flat_map(|dir| files::get_entries_in_dir(dir).unwrap_or_default())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "desktop")
+            })
+            .filter(|path| Self::is_owned(path).unwrap_or(false))
+            .filter_map(|path| {
+                Self::from_path(&path, browser_configs, app_dirs)
+                    .inspect_err(|error| {
+                        error!(path = %path.display(), ?error, "Failed to read desktop file");
+                    })
+                    .ok()
+            })
+            .collect();
+        desktop_files.sort_by_key(Self::get_path);
+
+        desktop_files
+    }
+
+    fn build_random_id() -> String {
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect()
+    }
+
+    /// Another web app launcher has the same id, or the file name this app saves to
+    pub fn is_id_taken(&self) -> bool {
+        let Some(id) = self.get_id() else {
+            return false;
+        };
+        let own_path = &self.desktop_entry.path;
+        let save_path = self.get_save_path().ok();
+        let id_key = Key::Id.to_string();
+
+        launcher_backend::get_launcher_dirs(&self.app_dirs)
+            .into_iter()
+            .flat_map(|dir| files::get_entries_in_dir(dir).unwrap_or_default())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path != own_path
+                    && path
+                        .extension()
+                        .is_some_and(|extension| extension == "desktop")
+            })
+            .any(|path| {
+                save_path.as_ref() == Some(&path)
+                    || utils::read_desktop_entry(&path).is_ok_and(|desktop_entry| {
+                        desktop_entry.desktop_entry(&id_key) == Some(id.as_str())
+                    })
+            })
+    }
+
+    /// New and imported apps get a random id, saving must not overwrite another launcher.
+    /// Call it before the id is used for icon or profile paths.
+    pub fn ensure_unique_id(&mut self) -> Result<(), DesktopFileError> {
+        for _ in 0..Self::UNIQUE_ID_ATTEMPTS {
+            if !self.is_id_taken() {
+                return Ok(());
+            }
+
+            let id = Self::build_random_id();
+            debug!(id, "Id is already taken, trying a new one");
+            self.set_id(&id);
+        }
+
+        Err(ValidationError {
+            field: Key::Id,
+            message: "Could not find an id that is not taken".to_string(),
+        }
+        .into())
+    }
+
     pub fn from_path(
         path: &Path,
         browser_configs: &Rc<BrowserConfigs>,
         app_dirs: &Rc<AppDirs>,
     ) -> Result<Self> {
-        let desktop_entry = DesktopEntry::from_path(path, None::<&[String]>)?;
+        let desktop_entry = utils::read_desktop_entry(path)?;
 
         Ok(Self {
             desktop_entry,
@@ -98,7 +238,7 @@ impl DesktopFile {
         browser_configs: &Rc<BrowserConfigs>,
         app_dirs: &Rc<AppDirs>,
     ) -> Result<Self> {
-        let desktop_entry = DesktopEntry::from_str(path, str, None::<&[String]>)?;
+        let desktop_entry = utils::parse_desktop_entry(path, str)?;
 
         Ok(Self {
             desktop_entry,
@@ -118,170 +258,159 @@ impl DesktopFile {
     }
 
     pub fn get_is_owned_app(&self) -> bool {
-        self.desktop_entry
-            .desktop_entry(&Key::Gwa.to_string())
-            .and_then(map_to_bool_option)
+        self.get_typed::<bool>(Key::Gwa)
             .is_some_and(|is_owned| is_owned)
     }
 
     pub fn set_is_owned_app(&mut self) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Gwa.to_string(), true.to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Gwa.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Gwa.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::Gwa, &true);
     }
 
     pub fn get_name(&self) -> Option<String> {
-        self.desktop_entry
-            .desktop_entry(&Key::Name.to_string())
-            .and_then(map_to_string_option)
+        self.get_typed(Key::Name)
     }
 
     pub fn set_name(&mut self, id: &str) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Name.to_string(), id.to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Name.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Name.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::Name, id);
     }
 
     pub fn get_version(&self) -> Option<Version> {
-        self.desktop_entry
-            .desktop_entry(&Key::Version.to_string())
-            .map(|result| Version::parse(result).unwrap_or(Version::new(0, 0, 0)))
+        self.get_typed(Key::Version)
     }
 
     pub fn set_version(&mut self, version: &Version) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Version.to_string(), version.to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Version.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Version.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::Version, version);
     }
 
     pub fn get_exec(&self) -> Option<String> {
-        self.desktop_entry
-            .desktop_entry(&Key::Exec.to_string())
-            .and_then(map_to_string_option)
+        self.get_typed(Key::Exec)
     }
 
     pub fn get_id(&self) -> Option<String> {
-        self.desktop_entry
-            .desktop_entry(&Key::Id.to_string())
-            .and_then(map_to_string_option)
+        self.get_typed(Key::Id)
     }
 
     pub fn set_id(&mut self, id: &str) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Id.to_string(), id.to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Id.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Id.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::Id, id);
     }
 
     pub fn get_url(&self) -> Option<String> {
-        self.desktop_entry
-            .desktop_entry(&Key::Url.to_string())
-            .and_then(map_to_string_option)
+        self.get_typed(Key::Url)
     }
 
     pub fn set_url(&mut self, url: &str) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Url.to_string(), url.to_string());
+        self.set_typed(Key::Url, url);
+    }
 
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Url.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Url.to_string())
-                .unwrap_or_default()
-        );
+    pub fn get_domain(&self) -> Option<String> {
+        self.get_url()
+            .and_then(|url| Url::parse(&url).ok())
+            .and_then(|url| {
+                url.domain()
+                    .or_else(|| url.host_str())
+                    .map(std::string::ToString::to_string)
+            })
+    }
+
+    /// Also set when the browser is not installed anymore
+    pub fn get_browser_id(&self) -> Option<String> {
+        self.get_typed(Key::BrowserId)
     }
 
     pub fn get_browser(&self) -> Option<Rc<Browser>> {
-        self.desktop_entry
-            .desktop_entry(&Key::BrowserId.to_string())
-            .and_then(map_to_string_option)
+        self.get_browser_id()
             .and_then(|browser_id| self.browser_configs.get_by_id(&browser_id))
     }
 
     pub fn set_browser(&mut self, browser: &Rc<Browser>) {
-        self.desktop_entry
-            .add_desktop_entry(Key::BrowserId.to_string(), browser.id.clone());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::BrowserId.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::BrowserId.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::BrowserId, &browser.id);
     }
 
     pub fn get_isolated(&self) -> Option<bool> {
-        self.desktop_entry
-            .desktop_entry(&Key::Isolate.to_string())
-            .and_then(map_to_bool_option)
+        self.get_typed(Key::Isolate)
     }
 
     pub fn set_isolated(&mut self, is_isolated: bool) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Isolate.to_string(), is_isolated.to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Isolate.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Isolate.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::Isolate, &is_isolated);
     }
 
     pub fn get_maximized(&self) -> Option<bool> {
-        self.desktop_entry
-            .desktop_entry(&Key::Maximize.to_string())
-            .and_then(map_to_bool_option)
+        self.get_typed(Key::Maximize)
     }
 
     pub fn set_maximized(&mut self, is_maximized: bool) {
-        let key = Key::Maximize.to_string();
+        self.set_typed(Key::Maximize, &is_maximized);
+    }
 
-        self.desktop_entry
-            .add_desktop_entry(key.clone(), is_maximized.to_string());
+    pub fn get_fullscreen(&self) -> Option<bool> {
+        self.get_typed(Key::Fullscreen)
+    }
 
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &key,
-            &self.desktop_entry.desktop_entry(&key).unwrap_or_default()
-        );
+    pub fn set_fullscreen(&mut self, is_fullscreen: bool) {
+        self.set_typed(Key::Fullscreen, &is_fullscreen);
+    }
+
+    /// Hides the tab and url bar with the shipped profile config, `None` is on
+    pub fn get_minimal_ui(&self) -> Option<bool> {
+        self.get_typed(Key::MinimalUi)
+    }
+
+    pub fn set_minimal_ui(&mut self, is_minimal_ui: bool) {
+        self.set_typed(Key::MinimalUi, &is_minimal_ui);
+    }
+
+    /// Only Firefox based browsers get the profile config, and only in their own profile
+    pub fn can_minimal_ui(&self) -> bool {
+        self.get_isolated().unwrap_or(false)
+            && self
+                .get_browser()
+                .is_some_and(|browser| browser.can_isolate && browser.base == Base::Firefox)
+    }
+
+    /// Applies a changed minimal UI to the profile, it is used on the next start
+    pub fn update_profile_config(&self) -> Result<()> {
+        let Some(profile_path) = self
+            .get_profile_path()
+            .filter(|profile_path| self.can_minimal_ui() && profile_path.is_dir())
+        else {
+            return Ok(());
+        };
+
+        self.copy_profile_config_to_profile_path(&profile_path)
+    }
+
+    pub fn get_private(&self) -> Option<bool> {
+        self.get_typed(Key::Private)
+    }
+
+    pub fn set_private(&mut self, is_private: bool) {
+        self.set_typed(Key::Private, &is_private);
+    }
+
+    pub fn get_force_dark(&self) -> Option<bool> {
+        self.get_typed(Key::ForceDark)
+    }
+
+    pub fn set_force_dark(&mut self, is_force_dark: bool) {
+        self.set_typed(Key::ForceDark, &is_force_dark);
+    }
+
+    /// Zero is the browser default
+    pub fn get_window_width(&self) -> Option<u32> {
+        self.get_typed(Key::WindowWidth)
+    }
+
+    pub fn set_window_width(&mut self, width: u32) {
+        self.set_typed(Key::WindowWidth, &width);
+    }
+
+    /// Zero is the browser default
+    pub fn get_window_height(&self) -> Option<u32> {
+        self.get_typed(Key::WindowHeight)
+    }
+
+    pub fn set_window_height(&mut self, height: u32) {
+        self.set_typed(Key::WindowHeight, &height);
     }
 
     pub fn get_icon(&self) -> Image {
@@ -300,99 +429,185 @@ impl DesktopFile {
     }
 
     pub fn get_icon_path(&self) -> Option<PathBuf> {
-        self.desktop_entry
-            .desktop_entry(&Key::Icon.to_string())
-            .and_then(map_to_path_option)
+        self.get_typed(Key::Icon)
     }
 
-    pub fn set_icon_path(&mut self, path: &Path) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Icon.to_string(), path.to_string_lossy().to_string());
+    /// The icon file is gone, with an url it can be fetched again
+    pub fn is_icon_missing(&self) -> bool {
+        !self
+            .get_icon_path()
+            .is_some_and(|icon_path| icon_path.is_file())
+    }
 
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Icon.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Icon.to_string())
-                .unwrap_or_default()
-        );
+    pub fn set_icon_path(&mut self, path: &Path) {
+        self.set_typed(Key::Icon, path);
     }
 
     pub fn get_profile_path(&self) -> Option<PathBuf> {
-        self.desktop_entry
-            .desktop_entry(&Key::Profile.to_string())
-            .and_then(map_to_path_option)
+        self.get_typed(Key::Profile)
     }
 
     pub fn set_profile_path(&mut self, path: &Path) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Profile.to_string(), path.to_string_lossy().to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Profile.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Profile.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::Profile, path);
     }
 
     pub fn get_category(&self) -> Option<String> {
-        self.desktop_entry
-            .desktop_entry(&Key::Categories.to_string())
-            .and_then(map_to_string_option)
+        self.get_typed(Key::Categories)
     }
 
     pub fn set_category(&mut self, category: &Category) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Categories.to_string(), category.to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Categories.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Categories.to_string())
-                .unwrap_or_default()
-        );
+        self.set_typed(Key::Categories, &category.to_categories());
     }
 
     fn set_category_str(&mut self, category: &str) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Categories.to_string(), category.to_string());
-
-        debug!(
-            "Set '{}' on desktop file: {}",
-            &Key::Categories.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Categories.to_string())
-                .unwrap_or_default()
-        );
+        if category.ends_with(';') {
+            self.set_typed(Key::Categories, category);
+        } else {
+            self.set_typed(Key::Categories, &format!("{category};"));
+        }
     }
 
     pub fn get_description(&self) -> Option<String> {
-        self.desktop_entry
-            .desktop_entry(&Key::Comment.to_string())
-            .and_then(map_to_string_option)
+        self.get_typed(Key::Comment)
     }
 
+    /// An empty description removes the key, an empty `Comment=` is of no use to launchers
     pub fn set_description(&mut self, description: &str) {
+        if description.trim().is_empty() {
+            self.remove_typed(Key::Comment);
+        } else {
+            self.set_typed(Key::Comment, description);
+        }
+    }
+
+    pub fn get_keywords(&self) -> Option<Vec<String>> {
+        self.get_typed(Key::Keywords)
+    }
+
+    /// Comma or semicolon separated words, the list is terminated like the spec wants
+    pub fn set_keywords(&mut self, keywords: &str) {
+        let keyword_list: Vec<String> = keywords.split([',', ';']).map(str::to_string).collect();
+        self.set_keyword_list(&keyword_list);
+    }
+
+    /// Keywords are trimmed, empty and repeated ones are left out
+    fn set_keyword_list(&mut self, keywords: &[String]) {
+        let mut keyword_list: Vec<String> = Vec::new();
+        for keyword in keywords
+            .iter()
+            .map(|keyword| keyword.trim())
+            .filter(|keyword| !keyword.is_empty())
+        {
+            if !keyword_list
+                .iter()
+                .any(|keyword_iter| keyword_iter == keyword)
+            {
+                keyword_list.push(keyword.to_string());
+            }
+        }
+
+        if keyword_list.is_empty() {
+            self.remove_typed(Key::Keywords);
+        } else {
+            self.set_typed(
+                Key::Keywords,
+                &format!("{};", keyword_list.to_entry_value()),
+            );
+        }
+    }
+
+    /// Set when the launcher was installed through the portal instead of written to disk
+    pub fn get_launcher_id(&self) -> Option<String> {
+        self.get_typed(Key::LauncherId)
+    }
+
+    pub fn set_launcher_id(&mut self, launcher_id: &str) {
+        self.set_typed(Key::LauncherId, launcher_id);
+    }
+
+    /// Seconds since the Unix epoch, only launches from this app are counted
+    pub fn get_last_launched(&self) -> Option<u64> {
+        self.get_typed(Key::LastLaunched)
+    }
+
+    pub fn set_last_launched(&mut self, last_launched: u64) {
+        self.set_typed(Key::LastLaunched, &last_launched);
+    }
+
+    pub fn get_launch_count(&self) -> Option<u32> {
+        self.get_typed(Key::LaunchCount)
+    }
+
+    pub fn set_launch_count(&mut self, launch_count: u32) {
+        self.set_typed(Key::LaunchCount, &launch_count);
+    }
+
+    /// Theme color from the web app manifest
+    pub fn get_theme_color(&self) -> Option<String> {
+        self.get_typed(Key::ThemeColor)
+    }
+
+    pub fn set_theme_color(&mut self, theme_color: &str) {
+        self.set_typed(Key::ThemeColor, theme_color);
+    }
+
+    /// As typed by the user, shell quoting is kept
+    pub fn get_extra_args(&self) -> Option<String> {
+        self.get_typed(Key::ExtraArgs)
+    }
+
+    pub fn set_extra_args(&mut self, extra_args: &str) {
+        self.set_typed(Key::ExtraArgs, extra_args.trim());
+    }
+
+    /// `KEY=VALUE` pairs as typed, only set for the browser of this web app
+    pub fn get_env(&self) -> Option<Vec<String>> {
+        self.get_typed(Key::Env)
+    }
+
+    pub fn set_env(&mut self, env: &[String]) {
+        let env_list: Vec<&str> = env
+            .iter()
+            .map(String::as_str)
+            .filter(|pair| !pair.trim().is_empty())
+            .collect();
+
+        if env_list.is_empty() {
+            self.remove_typed(Key::Env);
+        } else {
+            self.set_typed(Key::Env, &format!("{};", env_list.join(";")));
+        }
+    }
+
+    fn get_typed<T: FromEntryValue>(&self, key: Key) -> Option<T> {
+        self.desktop_entry
+            .desktop_entry(&key.to_string())
+            .and_then(T::from_entry_value)
+    }
+
+    fn set_typed<T: ToEntryValue + ?Sized>(&mut self, key: Key, value: &T) {
+        let key = key.to_string();
+
         self.desktop_entry
-            .add_desktop_entry(Key::Comment.to_string(), description.to_string());
+            .add_desktop_entry(key.clone(), value.to_entry_value());
 
         debug!(
             "Set '{}' on desktop file: {}",
-            &Key::Comment.to_string(),
-            &self
-                .desktop_entry
-                .desktop_entry(&Key::Comment.to_string())
-                .unwrap_or_default()
+            &key,
+            &self.desktop_entry.desktop_entry(&key).unwrap_or_default()
         );
     }
 
+    fn remove_typed(&mut self, key: Key) {
+        let key = key.to_string();
+
+        if let Some(group) = self.desktop_entry.groups.0.get_mut("Desktop Entry") {
+            group.0.remove(&key);
+        }
+
+        debug!("Removed '{}' from desktop file", &key);
+    }
+
     pub fn copy_profile_config_to_profile_path(&self, profile_path: &Path) -> Result<()> {
         let browser = self.get_browser().context("No browser on 'DesktopFile'")?;
 
@@ -407,12 +622,6 @@ impl DesktopFile {
             ))?;
         }
 
-        let copy_options = fs_extra::dir::CopyOptions {
-            overwrite: true,
-            content_only: true,
-            ..fs_extra::dir::CopyOptions::default()
-        };
-
         let copy_profile_config = move |config_path: &PathBuf| -> Result<()> {
             debug!(
                 config_path = config_path.display().to_string(),
@@ -420,7 +629,7 @@ impl DesktopFile {
                 "Copying profile config"
             );
             if config_path.is_dir() {
-                fs_extra::dir::copy(config_path, profile_path, &copy_options)?;
+                self.copy_managed_profile_config(config_path, profile_path)?;
             }
             Ok(())
         };
@@ -460,8 +669,9 @@ impl DesktopFile {
 
         let id = self.get_id().context("No id on 'DesktopFile'")?;
         let profile_path = browser.get_profile_path()?.join(&id);
+        let is_new_profile = !profile_path.is_dir();
 
-        if !profile_path.is_dir() {
+        if is_new_profile {
             debug!(
                 path = profile_path.to_string_lossy().to_string(),
                 "Creating profile path"
@@ -474,12 +684,19 @@ impl DesktopFile {
 
         debug!("Using profile path: {}", &profile_path.display());
         self.copy_profile_config_to_profile_path(&profile_path)?;
+        // Only once, the browser owns these files after the first start
+        if is_new_profile && browser.base == Base::Chromium {
+            self.seed_chromium_profile(&profile_path)?;
+        }
 
         Ok(profile_path)
     }
 
     pub fn validate(&self) -> Result<(), DesktopFileError> {
-        match self.to_new_from_browser() {
+        match self
+            .check_policy()
+            .and_then(|()| self.to_new_from_browser())
+        {
             Err(error) => {
                 error!(
                     validation_error = error.to_string(),
@@ -491,79 +708,330 @@ impl DesktopFile {
         }
     }
 
-    pub fn save(&mut self) -> Result<(), DesktopFileError> {
-        let new_desktop_file = self.to_new_from_browser()?;
+    /// Like `validate`, but reports every failing field for the form to mark.
+    /// Problems of the generated file itself only show when the fields are fine.
+    pub fn validate_all(&self) -> Result<(), DesktopFileError> {
+        let mut errors = Vec::new();
 
-        if self.desktop_entry.path.is_file() && !self.desktop_entry.path.is_symlink() {
-            match fs::remove_file(&self.desktop_entry.path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove desktop file before saving new: {error:?}");
-                }
-            }
+        match self.check_policy() {
+            Ok(()) => {}
+            Err(DesktopFileError::Other(error)) => return Err(error.into()),
+            Err(error) => errors.extend(error.get_validation_errors()),
+        }
+        if let Err(entry_errors) = self.get_all_entries() {
+            // An invalid url is already reported by the policy check
+            let entry_errors: Vec<_> = entry_errors
+                .into_iter()
+                .filter(|entry_error| !errors.iter().any(|error| error.field == entry_error.field))
+                .collect();
+            errors.extend(entry_errors);
         }
 
-        let save_path = new_desktop_file.desktop_entry.path.clone();
-
-        debug!("Saving desktop file to: {}", save_path.display());
-        fs::write(&save_path, new_desktop_file.desktop_entry.to_string())
-            .context("Saving desktop file")?;
-        self.desktop_entry = new_desktop_file.desktop_entry;
+        if errors.is_empty() {
+            return self.validate();
+        }
 
-        Ok(())
+        let error = DesktopFileError::ValidationErrors(errors);
+        error!(
+            validation_error = error.to_string(),
+            "Invalid 'DesktopFile'"
+        );
+        Err(error)
     }
 
-    pub fn delete(&self) -> Result<()> {
-        let mut is_error = false;
-
-        if self.desktop_entry.path.is_file() {
-            match fs::remove_file(&self.desktop_entry.path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove desktop file: {error:?}");
-                    is_error = true;
-                }
-            }
-        }
+    /// Managed deployments can restrict urls and require isolation
+    pub fn check_policy(&self) -> Result<(), DesktopFileError> {
+        let policy = policy::get();
 
-        if let Some(icon_path) = self.get_icon_path()
-            && icon_path.is_file()
+        if let Some(url) = self.get_url()
+            && let Err(error) = policy.check_url(&url)
         {
-            match fs::remove_file(icon_path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove icon file: {error:?}");
-                    is_error = true;
-                }
+            return Err(ValidationError {
+                field: Key::Url,
+                message: error.to_string(),
             }
+            .into());
         }
 
-        if let Some(profile_path) = self.get_profile_path()
-            && Path::new(&profile_path).is_dir()
+        if policy.get_force_isolation()
+            && self
+                .get_browser()
+                .is_some_and(|browser| browser.can_isolate)
+            && !self.get_isolated().unwrap_or(false)
         {
-            match fs::remove_dir_all(profile_path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove profile: {error:?}");
-                    is_error = true;
-                }
+            return Err(ValidationError {
+                field: Key::Isolate,
+                message: "Isolation is required by policy".to_string(),
             }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Existing apps that break the policy are kept and can still be updated by migrations
+    /// Returns `false` when the file on disk already has these contents and nothing was written
+    pub fn save(&mut self) -> Result<bool, DesktopFileError> {
+        self.check_policy()?;
+        if !self.has_changes() {
+            debug!(path = %self.desktop_entry.path.display(), "Desktop file unchanged, not saving");
+            return Ok(false);
+        }
+
+        self.write()?;
+        Ok(true)
+    }
+
+    /// Compared with the file itself, so a copy like the one Reset restores can't be out of date
+    fn has_changes(&self) -> bool {
+        !fs::read_to_string(&self.desktop_entry.path)
+            .is_ok_and(|contents| contents == self.to_string())
+    }
+
+    /// Writes the new file before removing the old one, so a failed write keeps the app
+    fn write(&mut self) -> Result<(), DesktopFileError> {
+        let old_path = self.desktop_entry.path.clone();
+        if self.can_use_portal() && !self.app_dirs.has_host_applications {
+            info!("No access to the host applications dir, using the launcher portal");
+            self.switch_to_portal()?;
+        }
+        let new_desktop_file = match self.write_new() {
+            // Without access to the applications dir the launcher would only exist in the sandbox
+            Err(DesktopFileError::Other(error)) if self.can_use_portal() => {
+                error!("Failed to write desktop file, using the launcher portal: {error:?}");
+                self.switch_to_portal()?;
+                self.write_new()?
+            }
+            result => result?,
+        };
+        let save_path = new_desktop_file.desktop_entry.path.clone();
+
+        if old_path != save_path && old_path.is_file() && !old_path.is_symlink() {
+            debug!("Removing previous desktop file: {}", old_path.display());
+            match fs::remove_file(&old_path) {
+                Ok(()) => {}
+                Err(error) => {
+                    error!("Failed to remove previous desktop file: {error:?}");
+                }
+            }
+        }
+
+        self.desktop_entry = new_desktop_file.desktop_entry;
+        self.sync_autostart();
+        if old_path != save_path {
+            self.sync_default_handlers(&old_path);
+        }
+        self.notify_launchers();
+
+        Ok(())
+    }
+
+    fn write_new(&self) -> Result<DesktopFile, DesktopFileError> {
+        let new_desktop_file = self.to_new_from_browser()?;
+
+        debug!(
+            "Saving desktop file to: {}",
+            new_desktop_file.desktop_entry.path.display()
+        );
+        new_desktop_file
+            .get_launcher_backend()
+            .save(&new_desktop_file)?;
+
+        Ok(new_desktop_file)
+    }
+
+    /// The path an owned file should move to, when its file name is outdated.
+    /// This happens when the browser prefix or app name changed, the id must still match.
+    pub fn get_renamed_path(&self) -> Option<PathBuf> {
+        let path = &self.desktop_entry.path;
+        if !self.get_is_owned_app()
+            || path.parent()
+                != Some(
+                    self.get_launcher_backend()
+                        .get_dir(&self.app_dirs)
+                        .as_path(),
+                )
+        {
+            return None;
+        }
+
+        let id = self.get_id()?;
+        let file_stem = path.file_stem()?.to_str()?;
+        if !file_stem.ends_with(&format!("-{id}")) {
+            return None;
+        }
+
+        let save_path = self.get_save_path().ok()?;
+        if save_path == *path {
+            return None;
+        }
+        if save_path.exists() {
+            error!(
+                path = %path.display(),
+                save_path = %save_path.display(),
+                "Can't rename desktop file, new path already exists"
+            );
+            return None;
+        }
+
+        Some(save_path)
+    }
+
+    pub fn delete(&self) -> Result<DeleteReport, DeleteError> {
+        self.delete_with_options(&DeleteOptions::default())
+    }
+
+    /// Files go to the trash where the filesystem has one, the report tells which went where
+    pub fn delete_with_options(
+        &self,
+        options: &DeleteOptions,
+    ) -> Result<DeleteReport, DeleteError> {
+        let mut report = DeleteReport::default();
+        let mut other_errors = Vec::new();
+
+        if let Err(error) = self.disable_autostart() {
+            error!("{error:?}");
+            other_errors.push(error);
+        }
+        self.restore_previous_handlers(&[]);
+
+        match self.get_launcher_backend().delete(self) {
+            Ok(Some(outcome)) => report.add(ItemKind::DesktopFile, self.get_path(), Ok(outcome)),
+            Ok(None) => {}
+            Err(error) => report.add(ItemKind::DesktopFile, self.get_path(), Err(error)),
+        }
+
+        if let Some(icon_path) = self.get_icon_path()
+            && icon_path.is_file()
+        {
+            // An SVG icon has a rasterized PNG next to it
+            let png_path = icon_path.with_extension("png");
+            if icon_path
+                .extension()
+                .is_some_and(|extension| extension == "svg")
+                && png_path.is_file()
+            {
+                let outcome = removal::trash_or_remove(&png_path);
+                report.add(ItemKind::RasterizedIcon, png_path, outcome);
+            }
+
+            let outcome = removal::trash_or_remove(&icon_path);
+            report.add(ItemKind::Icon, icon_path, outcome);
+        }
+
+        if let Some(profile_path) = self
+            .get_profile_path()
+            .filter(|profile_path| profile_path.is_dir())
+        {
+            let outcome = if options.delete_profile {
+                removal::trash_or_remove(&profile_path)
+            } else {
+                self.detach_profile(&profile_path)
+                    .map(|()| DeleteOutcome::Kept)
+            };
+            report.add(ItemKind::Profile, profile_path, outcome);
+        }
+        self.notify_launchers();
+
+        for item in report.get_failed() {
+            if let Err(error) = &item.outcome {
+                error!(kind = %item.kind, "{error:?}");
+            }
+        }
+        if report.has_failed() || !other_errors.is_empty() {
+            return Err(DeleteError {
+                report,
+                other_errors,
+            });
         }
 
-        if is_error {
-            bail!("Some files could not be removed, check logs")
-        }
-
         info!(
             "Succesfully removed web app: {}",
             self.get_name().unwrap_or_default()
         );
+        Ok(report)
+    }
+
+    fn detach_profile(&self, profile_path: &Path) -> Result<()> {
+        let browser = self.get_browser().context("No browser on 'DesktopFile'")?;
+
+        let detached_profile = DetachedProfile {
+            profile_path: profile_path.to_path_buf(),
+            name: self.get_name().unwrap_or_default(),
+            url: self.get_url().unwrap_or_default(),
+            domain: self.get_domain().context("No domain on 'DesktopFile'")?,
+            browser_id: browser.id.clone(),
+            browser_base: browser.base.to_string(),
+        };
+
+        info!(
+            profile_path = %profile_path.display(),
+            "Keeping profile of removed web app"
+        );
+
+        let mut detached_profiles = DetachedProfiles::new(&self.app_dirs);
+        detached_profiles.add(detached_profile);
+        detached_profiles.save()
+    }
+
+    /// Starts the web app the same way as its launcher, the command is for `command::watch_startup`
+    pub fn launch(&self) -> Result<BackgroundCommand, LaunchError> {
+        let exec = self.get_exec().ok_or(LaunchError::NoExec)?;
+        // Nothing expands field codes after this, the command is not started by a launcher
+        let mut executable = utils::expand_field_codes(
+            &exec,
+            self.get_icon_path()
+                .map(|icon_path| icon_path.to_string_lossy().to_string())
+                .as_deref(),
+            self.get_name().as_deref(),
+            Some(&self.get_path()),
+        );
+
+        // A moved custom binary would otherwise fail without any feedback
+        let browser = self
+            .get_browser()
+            .filter(|browser| browser.is_installed())
+            .ok_or(LaunchError::BrowserMissing)?;
+
+        if env::is_devcontainer() {
+            if browser.base == Base::Chromium {
+                let _ = write!(executable, " --no-sandbox");
+            }
+            debug!("Running in dev-container");
+        }
+
+        debug!("Running web app: '{executable}'");
+        command::run_command_background(&executable).map_err(LaunchError::Other)
+    }
+
+    /// Counts a launch from the app. Only the statistics are written, changes that are not saved
+    /// yet stay in memory. The file is written without the launcher backend, launchers don't
+    /// use these keys and the portal would ask the user again.
+    pub fn record_launch(&mut self) -> Result<()> {
+        let last_launched = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("System time is before the Unix epoch")?
+            .as_secs();
+        let launch_count = self.get_launch_count().unwrap_or(0).saturating_add(1);
+        self.set_last_launched(last_launched);
+        self.set_launch_count(launch_count);
+
+        let path = self.get_path();
+        let mut written_desktop_file =
+            Self::from_path(&path, &self.browser_configs, &self.app_dirs)?;
+        written_desktop_file.set_last_launched(last_launched);
+        written_desktop_file.set_launch_count(launch_count);
+        files::write_atomic(&path, written_desktop_file.to_string().as_bytes())
+            .context("Failed to save launch statistics")?;
+        debug!(launch_count, "Recorded launch of web app");
+
         Ok(())
     }
 
-    /// Run update actions when app has been updated, returns true if actions have been applied
-    #[allow(clippy::collapsible_if)]
-    pub fn update(&mut self) -> Result<bool, DesktopFileError> {
+    /// Run migrations when app has been updated or the file name is outdated,
+    /// returns `None` if the desktop file is up to date.
+    /// Reports only contain migrations that actually changed something.
+    pub fn update(&mut self) -> Result<Option<Vec<MigrationReport>>, DesktopFileError> {
         let app_version =
             Version::parse(config::VERSION.get_value()).context("Failed to get app version")?;
         let desktop_file_version = match self.get_version() {
@@ -574,34 +1042,61 @@ impl DesktopFile {
             }
             Some(version) => version,
         };
+        let is_outdated = desktop_file_version < app_version;
+        let renamed_path = self.get_renamed_path();
+
+        if !is_outdated && renamed_path.is_none() {
+            return Ok(None);
+        }
 
-        if desktop_file_version < app_version {
+        let mut reports = Vec::new();
+
+        if is_outdated {
             info!(
                 "Older desktop file version detected, {} has been updated",
                 config::APP_NAME.get_value()
             );
 
-            if self.get_isolated().is_some()
-                && let Some(profile_path) = self.get_profile_path()
-            {
+            for migration in migration::get_migrations() {
+                if !migration.applies_to(&desktop_file_version) {
+                    continue;
+                }
+
+                let change_set = migration.run(self)?;
+                if change_set.is_empty() {
+                    continue;
+                }
+
                 debug!(
-                    profile_path = profile_path.to_string_lossy().to_string(),
-                    "Updating profile config"
+                    migration = migration.description,
+                    keys = ?change_set.keys,
+                    files = ?change_set.files,
+                    "Applied migration"
                 );
 
-                self.copy_profile_config_to_profile_path(&profile_path)?;
+                reports.push(MigrationReport {
+                    description: migration.description,
+                    change_set,
+                });
             }
-        } else {
-            return Ok(false);
-        }
 
-        // if desktop_file_version <= Version::new(0, 0, 0) {
+            self.set_version(&app_version);
+        }
 
-        // }
+        if let Some(renamed_path) = renamed_path {
+            info!(
+                path = %self.desktop_entry.path.display(),
+                renamed_path = %renamed_path.display(),
+                "Outdated desktop file name detected"
+            );
+            reports.push(migration::get_rename_report(
+                &self.desktop_entry.path,
+                &renamed_path,
+            ));
+        }
 
-        self.set_version(&app_version);
-        self.save()?;
-        Ok(true)
+        self.write()?;
+        Ok(Some(reports))
     }
 
     /// Check paths, try to fix and print errors
@@ -615,7 +1110,9 @@ impl DesktopFile {
                         DesktopFileError::ValidationError(error) => {
                             format!("Field: {}, Error: {}", error.field, error.message)
                         }
-                        DesktopFileError::Other(error) => error.to_string(),
+                        DesktopFileError::ValidationErrors(_) | DesktopFileError::Other(_) => {
+                            error.to_string()
+                        }
                     },
                     "Failed to get entries on 'DesktopFile'"
                 );
@@ -632,94 +1129,147 @@ impl DesktopFile {
         }
 
         if !entries.icon_path.is_file() {
-            error!(name = entries.name, "Icon file does not exists");
+            error!(
+                name = entries.name,
+                "Icon file does not exists, the app list fetches it again"
+            );
+        }
+
+        if self.is_try_exec_missing() {
+            error!(
+                name = entries.name,
+                "TryExec program not found, launchers hide the app"
+            );
         }
     }
 
     fn get_entries(&self) -> Result<DesktopFileEntries, DesktopFileError> {
-        let name = self.get_name().ok_or(ValidationError {
-            field: Key::Name,
-            message: "Missing".to_string(),
-        })?;
-        let app_id = self.get_id().ok_or(ValidationError {
-            field: Key::Id,
-            message: "Missing".to_string(),
-        })?;
-        let version = self.get_version().ok_or(ValidationError {
-            field: Key::Version,
-            message: "Missing".to_string(),
-        })?;
-
-        let url_object = self
-            .get_url()
-            .ok_or(ValidationError {
-                field: Key::Url,
-                message: "Missing".to_string(),
-            })
-            .and_then(|url| {
-                Url::parse(&url).map_err(|_| ValidationError {
-                    field: Key::Url,
-                    message: "Invalid".to_string(),
+        self.get_all_entries().map_err(|validation_errors| {
+            validation_errors.into_iter().next().map_or_else(
+                || anyhow!("Failed to get entries on 'DesktopFile'").into(),
+                Into::into,
+            )
+        })
+    }
+
+    /// Checks every field instead of stopping at the first problem
+    fn get_all_entries(&self) -> Result<DesktopFileEntries, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        let name = Self::require(self.get_name(), Key::Name, &mut errors);
+        let app_id = Self::require(self.get_id(), Key::Id, &mut errors);
+        let version = Self::require(self.get_version(), Key::Version, &mut errors);
+
+        let url_object = Self::require(self.get_url(), Key::Url, &mut errors).and_then(|url| {
+            Url::parse(&url)
+                .inspect_err(|_| {
+                    errors.push(ValidationError {
+                        field: Key::Url,
+                        message: "Invalid".to_string(),
+                    });
                 })
-            })?;
-        let url = url_object.to_string();
-        let domain = url_object
-            .domain()
-            .or_else(|| url_object.host_str())
-            .ok_or(ValidationError {
-                field: Key::Url,
-                message: "Invalid domain".to_string(),
-            })?
-            .to_string();
-        let url_path = url_object.path().to_string();
-
-        let browser = self.get_browser().ok_or(ValidationError {
-            field: Key::BrowserId,
-            message: "Missing".to_string(),
-        })?;
-        let isolate = self.get_isolated().ok_or(ValidationError {
-            field: Key::Isolate,
-            message: "Missing".to_string(),
-        })?;
-        let maximize = self.get_maximized().ok_or(ValidationError {
-            field: Key::Maximize,
-            message: "Missing".to_string(),
-        })?;
-        let icon = self.get_icon_path().ok_or(ValidationError {
-            field: Key::Icon,
-            message: "Missing".to_string(),
-        })?;
-        let profile_path = self
-            .get_profile_path()
-            .or_else(|| {
-                if isolate {
+                .ok()
+        });
+        let domain = url_object.as_ref().and_then(|url_object| {
+            let domain = url_object
+                .domain()
+                .or_else(|| url_object.host_str())
+                .map(ToString::to_string);
+            if domain.is_none() {
+                errors.push(ValidationError {
+                    field: Key::Url,
+                    message: "Invalid domain".to_string(),
+                });
+            }
+            domain
+        });
+
+        let browser = Self::require(self.get_browser(), Key::BrowserId, &mut errors);
+        let isolate = Self::require(self.get_isolated(), Key::Isolate, &mut errors);
+        let maximize = Self::require(self.get_maximized(), Key::Maximize, &mut errors);
+        // Apps from before fullscreen, private and force dark existed don't have the keys
+        let fullscreen = self.get_fullscreen().unwrap_or(false);
+        let private = self.get_private().unwrap_or(false);
+        let force_dark = self.get_force_dark().unwrap_or(false);
+        // A maximized window ignores the size anyway
+        let window_size = match (self.get_window_width(), self.get_window_height()) {
+            (Some(width), Some(height)) if width > 0 && height > 0 && maximize != Some(true) => {
+                Some((width, height))
+            }
+            _ => None,
+        };
+        let icon = Self::require(self.get_icon_path(), Key::Icon, &mut errors);
+        // A missing isolate key is already reported, the profile is not blamed for it
+        let profile_path = Self::require(
+            self.get_profile_path().or_else(|| {
+                if isolate == Some(true) {
                     None
                 } else {
                     Some(PathBuf::default())
                 }
-            })
-            .ok_or(ValidationError {
-                field: Key::Profile,
-                message: "Missing".to_string(),
-            })?;
+            }),
+            Key::Profile,
+            &mut errors,
+        );
+
+        let (
+            Some(name),
+            Some(app_id),
+            Some(version),
+            Some(url_object),
+            Some(domain),
+            Some(browser),
+            Some(isolate),
+            Some(maximize),
+            Some(icon),
+            Some(profile_path),
+        ) = (
+            name,
+            app_id,
+            version,
+            url_object,
+            domain,
+            browser,
+            isolate,
+            maximize,
+            icon,
+            profile_path,
+        )
+        else {
+            return Err(errors);
+        };
 
         Ok(DesktopFileEntries {
             name,
             app_id,
             version,
             browser,
-            url,
-            url_path,
+            url: url_object.to_string(),
+            url_path: url_object.path().to_string(),
             domain,
             isolate,
             maximize,
+            fullscreen,
+            private,
+            force_dark,
+            window_size,
             icon_path: icon,
             profile_path,
         })
     }
 
+    fn require<T>(value: Option<T>, field: Key, errors: &mut Vec<ValidationError>) -> Option<T> {
+        if value.is_none() {
+            errors.push(ValidationError {
+                field,
+                message: "Missing".to_string(),
+            });
+        }
+        value
+    }
+
     fn get_save_path(&self) -> Result<PathBuf> {
-        let applications_dir = &self.app_dirs.user_applications;
+        let applications_dir = self.get_launcher_backend().get_dir(&self.app_dirs);
         let file_name = format!(
             "{}-{}-{}",
             self.get_browser()
@@ -734,40 +1284,75 @@ impl DesktopFile {
         Ok(desktop_file_path)
     }
 
-    fn replace_conditional(
-        conditional_key: &str,
-        set_value: bool,
-        with_value: Option<&str>,
-        d_str: &mut String,
-    ) -> Result<()> {
-        let optional_replace_value = Regex::new(&format!(r"%\{{{conditional_key}\s*\?\s*([^}}]+)"))
-            .context(format!(
-                "Failed to compile regex for captures with conditional key: {conditional_key}"
-            ))
-            .inspect_err(|error| error!(?error))?
-            .captures(&*d_str)
-            .and_then(|caps| caps.get(1).map(|value| value.as_str().to_string()));
-
-        if let Some(replace_value) = optional_replace_value {
-            let re = Regex::new(&format!(r"%\{{{conditional_key}\s*\?\s*[^}}]+\}}",))
-                .context(format!(
-                    "Failed to compile regex for replacement with conditional key:
-                    {conditional_key}"
-                ))
-                .inspect_err(|error| error!(?error))?;
-
-            let replacement = if set_value && let Some(with_value) = with_value {
-                format!("{replace_value}={with_value}")
-            } else if set_value {
-                replace_value
-            } else {
-                String::new()
+    /// Extra args quoted for the `Exec` key, newlines and field codes would corrupt it.
+    /// Left out when the policy disables them, the key itself is kept.
+    fn get_extra_args_exec(&self) -> Result<Option<String>, DesktopFileError> {
+        let Some(extra_args) = self.get_extra_args() else {
+            return Ok(None);
+        };
+        if policy::get().get_disable_custom_args() {
+            debug!(
+                extra_args,
+                "Extra args are disabled by policy, leaving them out"
+            );
+            return Ok(None);
+        }
+        let invalid = |message: &str| ValidationError {
+            field: Key::ExtraArgs,
+            message: message.to_string(),
+        };
+
+        if extra_args.contains(['\n', '\r']) {
+            return Err(invalid("Can't contain a new line").into());
+        }
+        if extra_args.contains('%') {
+            return Err(invalid("Can't contain '%' field codes").into());
+        }
+
+        let args = glib::shell_parse_argv(&extra_args)
+            .map_err(|error| invalid(&format!("Invalid quoting: {error}")))?;
+        let exec_args = args
+            .iter()
+            .map(|arg| command::quote_exec_arg(&arg.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(Some(exec_args))
+    }
+
+    /// Env pairs split in key and value, a key with '=' or whitespace can't be set by `env`
+    fn get_env_exec(&self) -> Result<Vec<(String, String)>, DesktopFileError> {
+        let Some(env) = self.get_env() else {
+            return Ok(Vec::new());
+        };
+        let invalid = |message: &str| ValidationError {
+            field: Key::Env,
+            message: message.to_string(),
+        };
+
+        let mut env_exec = Vec::new();
+        for pair in env {
+            let Some((key, value)) = pair.split_once('=') else {
+                return Err(invalid(&format!("Missing '=' in '{pair}'")).into());
             };
 
-            *d_str = re.replace_all(&*d_str, replacement).to_string();
+            if key.is_empty() {
+                return Err(invalid(&format!("Missing a name in '{pair}'")).into());
+            }
+            if key.contains(char::is_whitespace) {
+                return Err(invalid(&format!("Name '{key}' can't contain whitespace")).into());
+            }
+            if value.contains(['\n', '\r']) {
+                return Err(invalid("Can't contain a new line").into());
+            }
+            if value.contains('%') {
+                return Err(invalid("Can't contain '%' field codes").into());
+            }
+
+            env_exec.push((key.to_string(), value.to_string()));
         }
 
-        Ok(())
+        Ok(env_exec)
     }
 
     fn to_new_from_browser(&self) -> Result<DesktopFile, DesktopFileError> {
@@ -776,48 +1361,35 @@ impl DesktopFile {
         let app_name_short = config::APP_NAME_SHORT.get_value();
         let app_id = format!("{}-{}", app_name_short, entries.app_id);
 
-        let domain_path = match self.get_browser() {
-            None => &entries.domain,
-            Some(browser) => &match browser.base {
-                Base::Chromium => {
-                    let domain = format!("{}/", entries.domain);
-                    let domain_path = format!("{domain}{}", entries.url_path);
-                    domain_path.replace('/', "_")
-                }
-                // Not needed for other browser atm
-                _ => {
-                    format!("{}{}", entries.domain, entries.url_path)
-                }
-            },
-        };
-
-        let mut d_str = entries.browser.desktop_file.clone().to_string();
-        d_str = d_str.replace("%{command}", &entries.browser.get_run_command()?);
-        d_str = d_str.replace("%{name}", &entries.name);
-        d_str = d_str.replace("%{url}", &entries.url);
-        d_str = d_str.replace("%{domain}", &entries.domain);
-        d_str = d_str.replace("%{domain_path}", domain_path);
-        d_str = d_str.replace("%{icon}", &entries.icon_path.to_string_lossy());
-        d_str = d_str.replace("%{app_id}", &app_id);
-
-        if Self::replace_conditional(
-            "is_isolated",
-            entries.isolate,
-            Some(&entries.profile_path.to_string_lossy()),
-            &mut d_str,
-        )
-        .is_err()
-        {
-            return Err(DesktopFileError::Other(anyhow!(
-                "Failed to replace conditional 'is_isolated' in desktop file"
-            )));
+        let mut command = entries
+            .browser
+            .get_run_command_with_env(&self.get_env_exec()?)?;
+        if let Some(extra_args) = self.get_extra_args_exec()? {
+            let _ = write!(command, " {extra_args}");
         }
 
-        if Self::replace_conditional("is_maximized", entries.maximize, None, &mut d_str).is_err() {
-            return Err(DesktopFileError::Other(anyhow!(
-                "Failed to replace conditional 'is_maximized' in desktop file"
-            )));
-        }
+        let mut browser_template = entries.browser.desktop_file.clone().to_string();
+        let exec_url = Self::build_exec_url(
+            &mut browser_template,
+            self.get_handler_exec_url()?,
+            &entries.url,
+        );
+        let mut replacements = Self::build_replacements(entries, &command, &exec_url, app_id);
+        let d_str = template::render(&browser_template, &replacements, UnknownKeys::Keep);
+
+        // Same command as the app, so the private window also gets the app's window and profile.
+        // A typo already shows up in the main `Exec`, the action does not repeat it.
+        let private_exec = if entries.browser.can_private && !entries.private {
+            replacements.insert("is_private", Some(Replacement::Flag));
+            let d_str_private =
+                template::render(&browser_template, &replacements, UnknownKeys::Strip);
+
+            DesktopEntry::from_str(&save_path, &d_str_private, None::<&[String]>)
+                .ok()
+                .and_then(|desktop_entry| desktop_entry.exec().map(ToString::to_string))
+        } else {
+            None
+        };
 
         let mut new_desktop_file =
             Self::from_string(&save_path, &d_str, &self.browser_configs, &self.app_dirs)?;
@@ -827,24 +1399,750 @@ impl DesktopFile {
         new_desktop_file.set_version(&entries.version);
         new_desktop_file.set_url(&entries.url);
         new_desktop_file.set_browser(&entries.browser);
+        if let Some(launcher_id) = self.get_launcher_id() {
+            new_desktop_file.set_launcher_id(&launcher_id);
+        }
+        if let Some(try_exec) = entries.browser.get_try_exec() {
+            new_desktop_file.set_typed(Key::TryExec, &try_exec);
+        }
         new_desktop_file.set_isolated(entries.isolate);
         new_desktop_file.set_maximized(entries.maximize);
+        new_desktop_file.set_fullscreen(entries.fullscreen);
+        new_desktop_file.set_private(entries.private);
+        new_desktop_file.set_force_dark(entries.force_dark);
         new_desktop_file.set_profile_path(&entries.profile_path);
+        new_desktop_file.add_window_actions(private_exec.as_deref());
+        self.copy_handlers_to(&mut new_desktop_file);
 
         if let Some(description) = self.get_description() {
             new_desktop_file.set_description(&description);
         }
+        if let Some(keywords) = self.get_keywords() {
+            new_desktop_file.set_keyword_list(&keywords);
+        }
+        if let Some(theme_color) = self.get_theme_color() {
+            new_desktop_file.set_theme_color(&theme_color);
+        }
+        if let Some(is_minimal_ui) = self.get_minimal_ui() {
+            new_desktop_file.set_minimal_ui(is_minimal_ui);
+        }
+        if let Some(last_launched) = self.get_last_launched() {
+            new_desktop_file.set_last_launched(last_launched);
+        }
+        if let Some(launch_count) = self.get_launch_count() {
+            new_desktop_file.set_launch_count(launch_count);
+        }
+        if let Some(extra_args) = self.get_extra_args() {
+            new_desktop_file.set_extra_args(&extra_args);
+        }
+        if let Some(env) = self.get_env() {
+            new_desktop_file.set_env(&env);
+        }
+        if let Some(width) = self.get_window_width() {
+            new_desktop_file.set_window_width(width);
+        }
+        if let Some(height) = self.get_window_height() {
+            new_desktop_file.set_window_height(height);
+        }
         if let Some(category) = self.get_category() {
             new_desktop_file.set_category_str(&category);
         } else {
             new_desktop_file.set_category(&Category::Network);
         }
+        // Last, so translations of the keys set above are kept too
+        self.copy_custom_keys_to(&mut new_desktop_file);
+
+        // Caught before writing, launchers skip a file that breaks the spec
+        if let Some(issue) = new_desktop_file
+            .lint()
+            .into_iter()
+            .find(|issue| issue.level == LintLevel::Error)
+        {
+            return Err(ValidationError {
+                field: issue.field,
+                message: issue.message,
+            }
+            .into());
+        }
 
         Ok(new_desktop_file)
     }
+
+    /// Handler urls get the link as a field code, which is not allowed in a quoted argument
+    fn build_exec_url(
+        browser_template: &mut String,
+        handler_exec_url: Option<String>,
+        url: &str,
+    ) -> String {
+        if let Some(handler_exec_url) = handler_exec_url {
+            *browser_template = browser_template.replace("\"%{url}\"", "%{url}");
+            handler_exec_url
+        } else if browser_template.contains("\"%{url}\"") {
+            utils::escape_quoted_exec_arg(url)
+        } else {
+            utils::escape_exec_arg(url)
+        }
+    }
+
+    /// Values are escaped for the key file too, the template is parsed after rendering
+    fn build_replacements(
+        entries: &DesktopFileEntries,
+        command: &str,
+        exec_url: &str,
+        app_id: String,
+    ) -> HashMap<&'static str, Option<Replacement>> {
+        let domain_path = match entries.browser.base {
+            Base::Chromium => {
+                let domain = format!("{}/", entries.domain);
+                let domain_path = format!("{domain}{}", entries.url_path);
+                domain_path.replace('/', "_")
+            }
+            // Not needed for other browser atm
+            _ => {
+                format!("{}{}", entries.domain, entries.url_path)
+            }
+        };
+
+        HashMap::from([
+            (
+                "command",
+                Some(Replacement::Text(utils::escape_value(command))),
+            ),
+            (
+                "name",
+                Some(Replacement::Text(utils::escape_value(&entries.name))),
+            ),
+            (
+                "url",
+                Some(Replacement::Text(utils::escape_value(exec_url))),
+            ),
+            ("domain", Some(Replacement::Text(entries.domain.clone()))),
+            ("domain_path", Some(Replacement::Text(domain_path))),
+            (
+                "icon",
+                Some(Replacement::Text(utils::escape_value(
+                    &entries.icon_path.to_string_lossy(),
+                ))),
+            ),
+            ("app_id", Some(Replacement::Text(app_id))),
+            (
+                "is_isolated",
+                entries.isolate.then(|| {
+                    Replacement::FlagValue(entries.profile_path.to_string_lossy().to_string())
+                }),
+            ),
+            (
+                "is_maximized",
+                entries.maximize.then_some(Replacement::Flag),
+            ),
+            (
+                "is_fullscreen",
+                entries.fullscreen.then_some(Replacement::Flag),
+            ),
+            (
+                "is_force_dark",
+                entries.force_dark.then_some(Replacement::Flag),
+            ),
+            ("is_private", entries.private.then_some(Replacement::Flag)),
+            (
+                "window_size",
+                entries
+                    .window_size
+                    .map(|(width, height)| Replacement::Size(width, height)),
+            ),
+        ])
+    }
 }
+/// Like the `DesktopEntry` output, but the main group goes first as the spec wants
 impl std::fmt::Display for DesktopFile {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        self.desktop_entry.fmt(f)
+        let main_group = "Desktop Entry";
+        let groups = &self.desktop_entry.groups.0;
+
+        for (group_name, group) in groups.get_key_value(main_group).into_iter().chain(
+            groups
+                .iter()
+                .filter(|(group_name, _)| *group_name != main_group),
+        ) {
+            writeln!(f, "[{group_name}]")?;
+            for (key, (value, localizations)) in &group.0 {
+                writeln!(f, "{key}={}", utils::escape_value(value))?;
+                for (locale, localized) in localizations {
+                    writeln!(f, "{key}[{locale}]={}", utils::escape_value(localized))?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app_dirs::BaseDirs, assets};
+
+    /// Shipped browser configs, each installed as its flatpak and its system binary
+    fn get_configs(root: &Path) -> (Rc<BrowserConfigs>, Rc<AppDirs>) {
+        config::init();
+        let app_dirs = AppDirs::from_base_dirs(BaseDirs {
+            home: root.join("home"),
+            data: root.join("data"),
+            config: root.join("config"),
+            cache: root.join("cache"),
+            system_data: Vec::new(),
+        });
+        app_dirs.init().unwrap();
+        let app_dirs = Rc::new(app_dirs);
+        assets::reset_config_files(&app_dirs).unwrap();
+        let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+        browser_configs.load_as_installed_browsers();
+
+        (browser_configs, app_dirs)
+    }
+
+    /// Saved Firefox web app
+    fn save_desktop_file(
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> DesktopFile {
+        let mut desktop_file = DesktopFile::new(browser_configs, app_dirs);
+        desktop_file.set_name("Mail");
+        desktop_file.set_url("https://mail.example.com/");
+        desktop_file.set_browser(&browser_configs.get_by_id("firefox").unwrap());
+        desktop_file.set_isolated(false);
+        desktop_file.set_maximized(false);
+        desktop_file.set_icon_path(&app_dirs.app_data_icons.join("mail.png"));
+        desktop_file.write().unwrap();
+
+        desktop_file
+    }
+
+    /// Like a file saved before the browser prefix changed
+    fn move_to_file_name(
+        desktop_file: &DesktopFile,
+        file_name: &str,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> DesktopFile {
+        let path = desktop_file.get_path().with_file_name(file_name);
+        fs::rename(desktop_file.get_path(), &path).unwrap();
+
+        DesktopFile::from_path(&path, browser_configs, app_dirs).unwrap()
+    }
+
+    fn get_old_file_name(desktop_file: &DesktopFile) -> String {
+        format!(
+            "org.mozilla.old-{}-{}.desktop",
+            config::APP_NAME_SHORT.get_value(),
+            desktop_file.get_id().unwrap()
+        )
+    }
+
+    #[test]
+    fn saved_file_is_not_renamed() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+
+        let desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+
+        assert!(desktop_file.get_path().is_file());
+        assert!(desktop_file.get_renamed_path().is_none());
+    }
+
+    #[test]
+    fn outdated_file_name_is_renamed_on_update() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let saved_desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let save_path = saved_desktop_file.get_path();
+        let mut desktop_file = move_to_file_name(
+            &saved_desktop_file,
+            &get_old_file_name(&saved_desktop_file),
+            &browser_configs,
+            &app_dirs,
+        );
+        let old_path = desktop_file.get_path();
+
+        assert_eq!(desktop_file.get_renamed_path(), Some(save_path.clone()));
+
+        let reports = desktop_file.update().unwrap().unwrap();
+
+        assert_eq!(reports.len(), 1);
+        assert_eq!(
+            reports[0].change_set.files,
+            vec![old_path.clone(), save_path.clone()]
+        );
+        assert!(!old_path.exists());
+        assert!(save_path.is_file());
+        assert_eq!(desktop_file.get_path(), save_path);
+        assert!(desktop_file.update().unwrap().is_none());
+    }
+
+    #[test]
+    fn file_of_another_id_is_not_renamed() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let saved_desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let file_name = format!(
+            "org.mozilla.old-{}-other.desktop",
+            config::APP_NAME_SHORT.get_value()
+        );
+
+        let desktop_file =
+            move_to_file_name(&saved_desktop_file, &file_name, &browser_configs, &app_dirs);
+
+        assert!(desktop_file.get_renamed_path().is_none());
+    }
+
+    #[test]
+    fn existing_target_is_not_overwritten() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let saved_desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let save_path = saved_desktop_file.get_path();
+        let desktop_file = move_to_file_name(
+            &saved_desktop_file,
+            &get_old_file_name(&saved_desktop_file),
+            &browser_configs,
+            &app_dirs,
+        );
+        fs::write(&save_path, "[Desktop Entry]\n").unwrap();
+
+        assert!(desktop_file.get_renamed_path().is_none());
+        assert_eq!(fs::read_to_string(&save_path).unwrap(), "[Desktop Entry]\n");
+    }
+
+    #[test]
+    fn file_outside_the_launcher_dir_is_not_renamed() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let saved_desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let path = root.path().join(get_old_file_name(&saved_desktop_file));
+        fs::rename(saved_desktop_file.get_path(), &path).unwrap();
+
+        let desktop_file = DesktopFile::from_path(&path, &browser_configs, &app_dirs).unwrap();
+
+        assert!(desktop_file.get_renamed_path().is_none());
+    }
+
+    #[test]
+    fn keywords_are_split_trimmed_and_unique() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let mut desktop_file = DesktopFile::new(&browser_configs, &app_dirs);
+
+        desktop_file.set_keywords(" mail, inbox;;mail ,work ");
+
+        assert_eq!(
+            desktop_file.get_keywords(),
+            Some(vec![
+                "mail".to_string(),
+                "inbox".to_string(),
+                "work".to_string()
+            ])
+        );
+        assert_eq!(
+            desktop_file.get_typed::<String>(Key::Keywords).as_deref(),
+            Some("mail;inbox;work;")
+        );
+
+        desktop_file.set_keywords(" , ;");
+
+        assert!(desktop_file.get_keywords().is_none());
+        assert!(desktop_file.get_typed::<String>(Key::Keywords).is_none());
+    }
+
+    #[test]
+    fn keywords_are_kept_on_save() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let mut desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        desktop_file.set_keywords("mail, work");
+
+        desktop_file.write().unwrap();
+
+        let saved_desktop_file =
+            DesktopFile::from_path(&desktop_file.get_path(), &browser_configs, &app_dirs).unwrap();
+        assert_eq!(
+            saved_desktop_file.get_keywords(),
+            Some(vec!["mail".to_string(), "work".to_string()])
+        );
+    }
+
+    #[test]
+    fn keywords_with_spaces_non_ascii_and_escaped_separators_round_trip() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let mut desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        desktop_file.set_keywords(" google drive, münchen ");
+        desktop_file.write().unwrap();
+        let path = desktop_file.get_path();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("Keywords=google drive;münchen;\n"));
+        fs::write(
+            &path,
+            contents.replace(
+                "Keywords=google drive;münchen;",
+                "Keywords=google drive;münchen;a\\;b;",
+            ),
+        )
+        .unwrap();
+
+        let mut desktop_file = DesktopFile::from_path(&path, &browser_configs, &app_dirs).unwrap();
+        desktop_file.set_maximized(true);
+        desktop_file.write().unwrap();
+
+        let saved_desktop_file =
+            DesktopFile::from_path(&path, &browser_configs, &app_dirs).unwrap();
+        assert_eq!(
+            saved_desktop_file.get_keywords(),
+            Some(vec![
+                "google drive".to_string(),
+                "münchen".to_string(),
+                "a;b".to_string()
+            ])
+        );
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("Keywords=google drive;münchen;a\\;b;\n")
+        );
+    }
+
+    #[test]
+    fn new_id_is_not_taken() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        save_desktop_file(&browser_configs, &app_dirs);
+
+        let desktop_file = DesktopFile::new(&browser_configs, &app_dirs);
+
+        assert!(!desktop_file.is_id_taken());
+    }
+
+    #[test]
+    fn id_of_another_launcher_is_replaced() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let saved_desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let saved_id = saved_desktop_file.get_id().unwrap();
+        let mut desktop_file = DesktopFile::new(&browser_configs, &app_dirs);
+        desktop_file.set_id(&saved_id);
+
+        assert!(desktop_file.is_id_taken());
+
+        desktop_file.ensure_unique_id().unwrap();
+
+        assert_ne!(desktop_file.get_id().unwrap(), saved_id);
+        assert!(!desktop_file.is_id_taken());
+        assert!(!saved_desktop_file.is_id_taken());
+    }
+
+    #[test]
+    fn file_at_the_save_path_takes_the_id() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let mut desktop_file = DesktopFile::new(&browser_configs, &app_dirs);
+        desktop_file.set_browser(&browser_configs.get_by_id("firefox").unwrap());
+        fs::write(
+            desktop_file.get_save_path().unwrap(),
+            "[Desktop Entry]\nType=Application\nName=Other\n",
+        )
+        .unwrap();
+
+        assert!(desktop_file.is_id_taken());
+    }
+
+    fn get_error_fields(error: &DesktopFileError) -> Vec<Key> {
+        error
+            .get_validation_errors()
+            .into_iter()
+            .map(|validation_error| validation_error.field)
+            .collect()
+    }
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let mut desktop_file = DesktopFile::new(&browser_configs, &app_dirs);
+        desktop_file.set_url("not a url");
+
+        let error = desktop_file.validate_all().unwrap_err();
+
+        assert!(matches!(error, DesktopFileError::ValidationErrors(_)));
+        assert_eq!(
+            get_error_fields(&error),
+            vec![
+                Key::Url,
+                Key::Name,
+                Key::BrowserId,
+                Key::Isolate,
+                Key::Maximize,
+                Key::Icon
+            ]
+        );
+        assert_eq!(
+            get_error_fields(&desktop_file.validate().unwrap_err()),
+            vec![Key::Url]
+        );
+    }
+
+    #[test]
+    fn valid_fields_pass_validation() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+
+        assert!(desktop_file.validate_all().is_ok());
+    }
+
+    /// Firefox web app saved with the given installation
+    fn save_with_browser(
+        browser_id: &str,
+        is_private: bool,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> DesktopFile {
+        let mut desktop_file = DesktopFile::new(browser_configs, app_dirs);
+        desktop_file.set_name("Mail");
+        desktop_file.set_url("https://mail.example.com/");
+        desktop_file.set_browser(&browser_configs.get_by_id(browser_id).unwrap());
+        desktop_file.set_isolated(false);
+        desktop_file.set_maximized(false);
+        desktop_file.set_private(is_private);
+        desktop_file.set_icon_path(&app_dirs.app_data_icons.join("mail.png"));
+        desktop_file.write().unwrap();
+
+        DesktopFile::from_path(&desktop_file.get_path(), browser_configs, app_dirs).unwrap()
+    }
+
+    fn get_action_exec(desktop_file: &DesktopFile, action: &str) -> Option<String> {
+        desktop_file
+            .desktop_entry
+            .groups
+            .0
+            .get(&format!("Desktop Action {action}"))
+            .and_then(|group| group.0.get(&Key::Exec.to_string()))
+            .map(|(exec, _)| exec.clone())
+    }
+
+    fn assert_window_actions(browser_id: &str, run_command: &str) {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+
+        let desktop_file = save_with_browser(browser_id, false, &browser_configs, &app_dirs);
+        let exec = desktop_file.get_exec().unwrap();
+        let app_class = format!(
+            "--class={0} --name={0}",
+            desktop_file
+                .get_typed::<String>(Key::StartupWMClass)
+                .unwrap()
+        );
+
+        assert!(exec.starts_with(&format!("{run_command} {app_class} ")));
+        assert!(!exec.contains("--private-window"));
+        assert_eq!(
+            desktop_file.get_actions().unwrap(),
+            ["new-window", "private-window"]
+        );
+        assert_eq!(get_action_exec(&desktop_file, "new-window").unwrap(), exec);
+        assert_eq!(
+            get_action_exec(&desktop_file, "private-window").unwrap(),
+            format!(
+                "{run_command} {app_class}   --private-window  --no-remote https://mail.example.com/"
+            )
+        );
+    }
+
+    fn assert_private_window_action_is_omitted(browser_id: &str) {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+
+        let desktop_file = save_with_browser(browser_id, true, &browser_configs, &app_dirs);
+
+        assert!(
+            desktop_file
+                .get_exec()
+                .unwrap()
+                .contains("--private-window")
+        );
+        assert_eq!(desktop_file.get_actions().unwrap(), ["new-window"]);
+        assert_eq!(
+            get_action_exec(&desktop_file, "new-window"),
+            desktop_file.get_exec()
+        );
+        assert!(get_action_exec(&desktop_file, "private-window").is_none());
+    }
+
+    #[test]
+    fn flatpak_browser_gets_window_actions() {
+        assert_window_actions("org.mozilla.firefox", "flatpak run org.mozilla.firefox");
+    }
+
+    #[test]
+    fn system_browser_gets_window_actions() {
+        assert_window_actions("firefox", "firefox");
+    }
+
+    #[test]
+    fn private_flatpak_app_has_no_private_window_action() {
+        assert_private_window_action_is_omitted("org.mozilla.firefox");
+    }
+
+    #[test]
+    fn private_system_app_has_no_private_window_action() {
+        assert_private_window_action_is_omitted("firefox");
+    }
+
+    #[test]
+    fn exported_bundle_is_imported_as_a_new_app() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let bundle_path = root.path().join("mail.tar.gz");
+        desktop_file.export_bundle(&bundle_path).unwrap();
+
+        let mut imported_desktop_file =
+            DesktopFile::import_bundle(&bundle_path, &browser_configs, &app_dirs).unwrap();
+        // The saved app has no icon file, so the bundle has none
+        imported_desktop_file.set_icon_path(&app_dirs.app_data_icons.join("mail.png"));
+        imported_desktop_file.write().unwrap();
+
+        assert_ne!(imported_desktop_file.get_id(), desktop_file.get_id());
+        assert_eq!(
+            imported_desktop_file.get_url().as_deref(),
+            Some("https://mail.example.com/")
+        );
+        assert!(imported_desktop_file.get_path().is_file());
+        assert_ne!(imported_desktop_file.get_path(), desktop_file.get_path());
+    }
+
+    const CUSTOM_MAIN_KEYS: &str =
+        "X-GNOME-UsesNotifications=true\nName[de]=Post\nPath=/home/user/mail\n";
+    const CUSTOM_GROUPS: &str = "\n[Desktop Action compose]\nName=Compose\nExec=mail-compose\n\n\
+        [Desktop Action stale]\nName=Stale\nExec=stale-command\n\n\
+        [X-Custom Group]\nX-Setting=on\n";
+
+    /// A saved file edited by hand, `compose` is listed and `stale` is not
+    fn add_custom_keys(contents: &str) -> String {
+        contents
+            .replace(
+                "[Desktop Entry]\n",
+                &format!("[Desktop Entry]\n{CUSTOM_MAIN_KEYS}"),
+            )
+            .replace(
+                "Actions=new-window;private-window;",
+                "Actions=new-window;private-window;compose;",
+            )
+            + CUSTOM_GROUPS
+    }
+
+    fn get_main_key(desktop_file: &DesktopFile, key: &str) -> Option<String> {
+        desktop_file
+            .desktop_entry
+            .groups
+            .0
+            .get("Desktop Entry")
+            .and_then(|group| group.0.get(key))
+            .map(|(value, _)| value.clone())
+    }
+
+    #[test]
+    fn custom_keys_and_listed_actions_of_a_launcher_survive_a_save() {
+        let root = tempfile::tempdir().unwrap();
+        let (_, app_dirs) = get_configs(root.path());
+        // A key only the Firefox template has, it must not stick after a browser change
+        let template_path = app_dirs
+            .app_config_browser_desktop_files
+            .join("firefox.desktop");
+        let template = fs::read_to_string(&template_path).unwrap();
+        fs::write(&template_path, format!("{template}X-Firefox-Only=true\n")).unwrap();
+        let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+        browser_configs.load_as_installed_browsers();
+
+        let saved_desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let path = saved_desktop_file.get_path();
+        fs::write(&path, add_custom_keys(&fs::read_to_string(&path).unwrap())).unwrap();
+        let mut desktop_file = DesktopFile::from_path(&path, &browser_configs, &app_dirs).unwrap();
+        assert_eq!(
+            get_main_key(&desktop_file, "X-Firefox-Only").as_deref(),
+            Some("true")
+        );
+        desktop_file.set_browser(&browser_configs.get_by_id("chromium-browser").unwrap());
+        desktop_file.set_maximized(true);
+        desktop_file.write().unwrap();
+
+        let desktop_file =
+            DesktopFile::from_path(&desktop_file.get_path(), &browser_configs, &app_dirs).unwrap();
+        assert_eq!(
+            get_main_key(&desktop_file, "X-GNOME-UsesNotifications").as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            get_main_key(&desktop_file, "Path").as_deref(),
+            Some("/home/user/mail")
+        );
+        assert_eq!(
+            desktop_file.desktop_entry.name(&["de"]).as_deref(),
+            Some("Post")
+        );
+        assert!(get_main_key(&desktop_file, "X-Firefox-Only").is_none());
+        assert_eq!(
+            desktop_file.get_actions().unwrap(),
+            ["new-window", "private-window", "compose"]
+        );
+        assert_eq!(
+            get_action_exec(&desktop_file, "compose").as_deref(),
+            Some("mail-compose")
+        );
+        assert!(get_action_exec(&desktop_file, "stale").is_none());
+        assert!(
+            desktop_file
+                .desktop_entry
+                .groups
+                .0
+                .contains_key("X-Custom Group")
+        );
+    }
+
+    #[test]
+    fn imported_bundle_drops_custom_actions_and_groups() {
+        let root = tempfile::tempdir().unwrap();
+        let (browser_configs, app_dirs) = get_configs(root.path());
+        let saved_desktop_file = save_desktop_file(&browser_configs, &app_dirs);
+        let path = saved_desktop_file.get_path();
+        fs::write(&path, add_custom_keys(&fs::read_to_string(&path).unwrap())).unwrap();
+        let bundle_path = root.path().join("mail.tar.gz");
+        DesktopFile::from_path(&path, &browser_configs, &app_dirs)
+            .unwrap()
+            .export_bundle(&bundle_path)
+            .unwrap();
+
+        let mut desktop_file =
+            DesktopFile::import_bundle(&bundle_path, &browser_configs, &app_dirs).unwrap();
+        desktop_file.set_icon_path(&app_dirs.app_data_icons.join("mail.png"));
+        desktop_file.write().unwrap();
+
+        let desktop_file =
+            DesktopFile::from_path(&desktop_file.get_path(), &browser_configs, &app_dirs).unwrap();
+        assert_eq!(
+            get_main_key(&desktop_file, "X-GNOME-UsesNotifications").as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            desktop_file.get_actions().unwrap(),
+            ["new-window", "private-window"]
+        );
+        assert!(get_action_exec(&desktop_file, "compose").is_none());
+        assert!(
+            !desktop_file
+                .desktop_entry
+                .groups
+                .0
+                .contains_key("X-Custom Group")
+        );
     }
 }
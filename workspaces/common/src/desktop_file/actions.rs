@@ -0,0 +1,120 @@
+use crate::desktop_file::{DesktopFile, key::Key};
+use freedesktop_desktop_entry::{Group, LocaleMap};
+use tracing::{debug, info};
+
+/// Right click actions of the launcher, like in a dock or the app grid
+impl DesktopFile {
+    const ACTION_NEW_WINDOW: &str = "new-window";
+    const ACTION_PRIVATE_WINDOW: &str = "private-window";
+    const DESKTOP_ACTION_GROUP: &str = "Desktop Action";
+    // The common crate has no translations, so the launcher names are kept here
+    const NEW_WINDOW_NAMES: [(&str, &str); 4] = [
+        ("en", "New Window"),
+        ("nl", "Nieuw venster"),
+        ("es", "Nueva ventana"),
+        ("it", "Nuova finestra"),
+    ];
+    const PRIVATE_WINDOW_NAMES: [(&str, &str); 4] = [
+        ("en", "New Private Window"),
+        ("nl", "Nieuw privévenster"),
+        ("es", "Nueva ventana privada"),
+        ("it", "Nuova finestra privata"),
+    ];
+
+    /// Adds a `[Desktop Action]` group and lists it in `Actions`, the first name is the default
+    pub fn add_action(&mut self, action: &str, names: &[(&str, &str)], exec: &str) {
+        let Some(((_, name), localized_names)) = names.split_first() else {
+            return;
+        };
+
+        let mut locales = LocaleMap::default();
+        for (locale, localized_name) in localized_names {
+            locales.insert((*locale).to_string(), (*localized_name).to_string());
+        }
+
+        let mut group = Group::default();
+        group
+            .0
+            .insert("Name".to_string(), ((*name).to_string(), locales));
+        group.0.insert(
+            Key::Exec.to_string(),
+            (exec.to_string(), LocaleMap::default()),
+        );
+        self.desktop_entry
+            .groups
+            .0
+            .insert(format!("{} {action}", Self::DESKTOP_ACTION_GROUP), group);
+        self.list_action(action);
+
+        debug!(action, "Added desktop action");
+    }
+
+    fn list_action(&mut self, action: &str) {
+        let mut actions = self.get_actions().unwrap_or_default();
+        if !actions.iter().any(|action_iter| action_iter == action) {
+            actions.push(action.to_string());
+        }
+        self.set_typed(Key::Actions, &format!("{};", actions.join(";")));
+    }
+
+    /// The action of a `[Desktop Action]` group name
+    pub fn get_action_name(group_name: &str) -> Option<&str> {
+        group_name
+            .strip_prefix(Self::DESKTOP_ACTION_GROUP)
+            .map(str::trim_start)
+    }
+
+    /// Actions added by hand to a local launcher are kept, the window actions are always made again
+    pub fn copy_custom_actions_to(&self, desktop_file: &mut DesktopFile) {
+        let actions = self.get_actions().unwrap_or_default();
+        let is_local_launcher = self.is_local_launcher();
+
+        for (group_name, group) in &self.desktop_entry.groups.0 {
+            let Some(action) = Self::get_action_name(group_name) else {
+                continue;
+            };
+            if action == Self::ACTION_NEW_WINDOW
+                || action == Self::ACTION_PRIVATE_WINDOW
+                || !actions.iter().any(|action_iter| action_iter == action)
+            {
+                continue;
+            }
+            // An imported `Exec` was never shown to the user
+            if !is_local_launcher {
+                info!(action, "Dropped desktop action of imported file");
+                continue;
+            }
+
+            desktop_file
+                .desktop_entry
+                .groups
+                .0
+                .insert(group_name.clone(), group.clone());
+            desktop_file.list_action(action);
+            debug!(action, "Kept custom desktop action");
+        }
+    }
+
+    pub fn get_actions(&self) -> Option<Vec<String>> {
+        self.get_typed(Key::Actions)
+    }
+
+    /// The private window is only added when the app does not already open private
+    pub fn add_window_actions(&mut self, private_exec: Option<&str>) {
+        let Some(exec) = self.get_exec() else {
+            return;
+        };
+
+        self.add_action(Self::ACTION_NEW_WINDOW, &Self::NEW_WINDOW_NAMES, &exec);
+
+        if let Some(private_exec) = private_exec
+            && private_exec != exec
+        {
+            self.add_action(
+                Self::ACTION_PRIVATE_WINDOW,
+                &Self::PRIVATE_WINDOW_NAMES,
+                private_exec,
+            );
+        }
+    }
+}
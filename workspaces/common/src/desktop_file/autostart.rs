@@ -0,0 +1,91 @@
+use crate::{
+    config::{self},
+    desktop_file::{DesktopFile, key::Key, utils::read_desktop_entry},
+    utils::{OnceLockExt, files},
+};
+use anyhow::{Context, Result};
+use std::{fs, path::PathBuf};
+use tracing::{debug, error};
+
+/// A copy of the saved desktop entry in the autostart dir, named by id so a new browser or name
+/// does not leave an old copy behind
+impl DesktopFile {
+    const AUTOSTART_DELAY_KEY: &str = "X-GNOME-Autostart-Delay";
+    const AUTOSTART_DELAY_SECONDS: u32 = 5;
+
+    fn get_autostart_path(&self) -> Option<PathBuf> {
+        let id = self.get_id()?;
+        let mut autostart_path = self
+            .app_dirs
+            .user_autostart
+            .join(format!("{}-{id}", config::APP_NAME_SHORT.get_value()));
+        autostart_path.add_extension("desktop");
+
+        Some(autostart_path)
+    }
+
+    /// Only an entry with the id of this app counts, a file with the same name is left alone
+    pub fn is_autostart(&self) -> bool {
+        let Some(autostart_path) = self.get_autostart_path() else {
+            return false;
+        };
+        if !autostart_path.is_file() {
+            return false;
+        }
+
+        read_desktop_entry(&autostart_path).is_ok_and(|desktop_entry| {
+            desktop_entry.desktop_entry(&Key::Id.to_string()) == self.get_id().as_deref()
+        })
+    }
+
+    /// The app must be saved first, the autostart entry is a copy of the saved file
+    pub fn enable_autostart(&self) -> Result<()> {
+        let autostart_path = self
+            .get_autostart_path()
+            .context("No id on 'DesktopFile'")?;
+        let autostart_dir = &self.app_dirs.user_autostart;
+
+        if !autostart_dir.is_dir() {
+            fs::create_dir_all(autostart_dir).context(format!(
+                "Could not create autostart dir: {}",
+                autostart_dir.display()
+            ))?;
+        }
+
+        let mut desktop_file = self.clone();
+        desktop_file.desktop_entry.add_desktop_entry(
+            Self::AUTOSTART_DELAY_KEY.to_string(),
+            Self::AUTOSTART_DELAY_SECONDS.to_string(),
+        );
+
+        debug!("Writing autostart entry: {}", autostart_path.display());
+        files::write_atomic(&autostart_path, desktop_file.to_string().as_bytes()).context(format!(
+            "Failed to write autostart entry: '{}'",
+            autostart_path.display()
+        ))
+    }
+
+    pub fn disable_autostart(&self) -> Result<()> {
+        if !self.is_autostart() {
+            return Ok(());
+        }
+        let autostart_path = self
+            .get_autostart_path()
+            .context("No id on 'DesktopFile'")?;
+
+        debug!("Removing autostart entry: {}", autostart_path.display());
+        fs::remove_file(&autostart_path).context(format!(
+            "Failed to remove autostart entry: '{}'",
+            autostart_path.display()
+        ))
+    }
+
+    /// Keeps a renamed or changed app in sync, called after every save
+    pub fn sync_autostart(&self) {
+        if self.is_autostart()
+            && let Err(error) = self.enable_autostart()
+        {
+            error!("Failed to update autostart entry: {error:?}");
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{browsers::Browser, desktop_file::DesktopFile};
+use anyhow::{Context, Result};
+use std::{path::PathBuf, rc::Rc};
+use tracing::{debug, info};
+
+/// An option of the web app the other browser can't do, it is turned off on a browser change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DroppedOption {
+    Isolation,
+    Maximize,
+    Fullscreen,
+    Private,
+    ForceDark,
+}
+
+impl DesktopFile {
+    /// The browser of the app was uninstalled or its `TryExec` is gone, launching it does nothing
+    pub fn is_browser_missing(&self) -> bool {
+        if self
+            .get_browser_id()
+            .is_none_or(|browser_id| browser_id.is_empty())
+        {
+            return false;
+        }
+
+        self.get_browser()
+            .is_none_or(|browser| !browser.is_installed())
+            || self.is_try_exec_missing()
+    }
+
+    /// Moves the app to another browser and saves it, so the `Exec` is made for the new browser.
+    /// An isolated app gets a new profile, the old profile path is returned when it is no longer used.
+    /// Running it again with the same browser changes nothing.
+    pub fn migrate_browser(&mut self, browser: &Rc<Browser>) -> Result<Option<PathBuf>> {
+        if !self.is_browser_missing() && self.get_browser_id().as_ref() == Some(&browser.id) {
+            debug!(browser_id = browser.id, "Web app already uses this browser");
+            return Ok(None);
+        }
+
+        let old_profile_path = self
+            .get_profile_path()
+            .filter(|profile_path| !profile_path.as_os_str().is_empty());
+
+        self.set_browser(browser);
+        // Options the browser can't do are left off, like on a new web app
+        for dropped_option in self.get_dropped_options(browser) {
+            match dropped_option {
+                DroppedOption::Isolation => self.set_isolated(false),
+                DroppedOption::Maximize => self.set_maximized(false),
+                DroppedOption::Fullscreen => self.set_fullscreen(false),
+                DroppedOption::Private => self.set_private(false),
+                DroppedOption::ForceDark => self.set_force_dark(false),
+            }
+        }
+
+        let is_isolated = self.get_isolated().unwrap_or(false);
+        let new_profile_path = if is_isolated {
+            self.build_profile_path()?
+        } else {
+            PathBuf::default()
+        };
+        self.set_profile_path(&new_profile_path);
+
+        self.save()
+            .context("Failed to save web app with the new browser")?;
+        info!(
+            name = self.get_name().unwrap_or_default(),
+            browser_id = browser.id,
+            "Migrated web app to another browser"
+        );
+
+        Ok(old_profile_path.filter(|old_profile_path| {
+            old_profile_path != &new_profile_path && old_profile_path.is_dir()
+        }))
+    }
+
+    /// Enabled options that are lost when the app moves to this browser
+    pub fn get_dropped_options(&self, browser: &Browser) -> Vec<DroppedOption> {
+        [
+            (
+                DroppedOption::Isolation,
+                self.get_isolated(),
+                browser.can_isolate,
+            ),
+            (
+                DroppedOption::Maximize,
+                self.get_maximized(),
+                browser.can_start_maximized,
+            ),
+            (
+                DroppedOption::Fullscreen,
+                self.get_fullscreen(),
+                browser.can_fullscreen,
+            ),
+            (
+                DroppedOption::Private,
+                self.get_private(),
+                browser.can_private,
+            ),
+            (
+                DroppedOption::ForceDark,
+                self.get_force_dark(),
+                browser.can_force_dark,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, is_enabled, is_supported)| is_enabled.unwrap_or(false) && !is_supported)
+        .map(|(dropped_option, _, _)| dropped_option)
+        .collect()
+    }
+}
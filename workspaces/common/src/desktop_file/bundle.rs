@@ -0,0 +1,246 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::{Browser, BrowserConfigs},
+    desktop_file::{DesktopFile, key::Key},
+    utils::{
+        archive::{self, TarWriter},
+        files,
+    },
+};
+use anyhow::{Context, Result, bail};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{debug, info};
+
+/// Describes the content of a bundle, the desktop entry holds the actual configuration
+#[derive(Serialize, Deserialize, Debug)]
+struct BundleManifest {
+    format: u32,
+    app_id: String,
+    version: String,
+    browser_id: Option<String>,
+    /// Finds the same browser when it is installed differently on the other machine
+    browser_config_name: Option<String>,
+    icon_file: Option<String>,
+}
+
+/// Portable `.tar.gz` of a web app, the profile is left out
+impl DesktopFile {
+    pub const BUNDLE_EXTENSION: &str = "tar.gz";
+    const BUNDLE_FORMAT: u32 = 1;
+    const BUNDLE_MANIFEST_FILE: &str = "manifest.json";
+    const BUNDLE_DESKTOP_FILE: &str = "app.desktop";
+    const BUNDLE_ICON_FILE: &str = "icon";
+    /// Icons and desktop files are small, anything bigger is not a bundle of this app
+    const BUNDLE_MAX_ENTRY_SIZE: u64 = 10_000_000;
+
+    pub fn export_bundle(&self, path: &Path) -> Result<()> {
+        let app_id = self.get_id().context("No id on 'DesktopFile'")?;
+        let version = self.get_version().context("No version on 'DesktopFile'")?;
+
+        let icon = self
+            .get_icon_path()
+            .filter(|icon_path| icon_path.is_file())
+            .map(|icon_path| -> Result<(String, Vec<u8>)> {
+                let extension = icon_path
+                    .extension()
+                    .map(|extension| format!(".{}", extension.to_string_lossy()))
+                    .unwrap_or_default();
+                let icon_bytes = fs::read(&icon_path)
+                    .context(format!("Failed to read icon: '{}'", icon_path.display()))?;
+                Ok((format!("{}{extension}", Self::BUNDLE_ICON_FILE), icon_bytes))
+            })
+            .transpose()?;
+
+        let manifest = BundleManifest {
+            format: Self::BUNDLE_FORMAT,
+            app_id,
+            version: version.to_string(),
+            browser_id: self.get_browser().map(|browser| browser.id.clone()),
+            browser_config_name: self
+                .get_browser()
+                .map(|browser| browser.config_name.clone()),
+            icon_file: icon.as_ref().map(|(icon_file, _)| icon_file.clone()),
+        };
+        let manifest_json =
+            serde_json::to_vec_pretty(&manifest).context("Failed to serialize manifest")?;
+
+        let mut tar = TarWriter::new(GzEncoder::new(Vec::new(), Compression::default()));
+        tar.append(Self::BUNDLE_MANIFEST_FILE, &manifest_json)?;
+        tar.append(Self::BUNDLE_DESKTOP_FILE, self.to_string().as_bytes())?;
+        if let Some((icon_file, icon_bytes)) = &icon {
+            tar.append(icon_file, icon_bytes)?;
+        }
+        let bundle = tar
+            .finish()?
+            .finish()
+            .context("Failed to compress bundle")?;
+
+        debug!("Exporting bundle to: {}", path.display());
+        files::write_atomic(path, &bundle).context("Failed to write bundle")?;
+
+        Ok(())
+    }
+
+    /// Always a new app with a fresh id, so importing twice doesn't collide.
+    /// An unknown browser is mapped to an installed one of the same config or to "No browser".
+    pub fn import_bundle(
+        path: &Path,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<Self> {
+        debug!("Importing bundle: {}", path.display());
+
+        let bundle =
+            fs::File::open(path).context(format!("Failed to open bundle: '{}'", path.display()))?;
+        let mut entries = Self::read_bundle_entries(GzDecoder::new(bundle))?;
+
+        let manifest_json = entries
+            .remove(Self::BUNDLE_MANIFEST_FILE)
+            .context("No manifest in bundle")?;
+        let manifest: BundleManifest =
+            serde_json::from_slice(&manifest_json).context("Invalid manifest in bundle")?;
+        if manifest.format != Self::BUNDLE_FORMAT {
+            bail!("Unsupported bundle format: {}", manifest.format)
+        }
+
+        let desktop_entry = entries
+            .remove(Self::BUNDLE_DESKTOP_FILE)
+            .context("No desktop file in bundle")?;
+        let desktop_entry =
+            String::from_utf8(desktop_entry).context("Invalid desktop file in bundle")?;
+        // The parser needs a `.desktop` path, the save path is only known after the id is set
+        let mut desktop_file = Self::from_string(
+            Path::new(Self::BUNDLE_DESKTOP_FILE),
+            &desktop_entry,
+            browser_configs,
+            app_dirs,
+        )?;
+
+        if !desktop_file.get_is_owned_app() {
+            bail!("Desktop file in bundle is not a web app of this app")
+        }
+
+        desktop_file.set_id(&Self::build_random_id());
+        desktop_file.ensure_unique_id()?;
+        let app_id = desktop_file.get_id().context("No id on 'DesktopFile'")?;
+
+        let browser_id = manifest
+            .browser_id
+            .or_else(|| desktop_file.get_typed(Key::BrowserId))
+            .unwrap_or_default();
+        let browser = Self::find_local_browser(
+            &browser_id,
+            manifest.browser_config_name.as_deref(),
+            browser_configs,
+        );
+        if let Some(browser) = &browser {
+            desktop_file.set_browser(browser);
+        }
+
+        let icon_path = match manifest
+            .icon_file
+            .and_then(|icon_file| entries.remove(&icon_file).map(|icon| (icon_file, icon)))
+        {
+            Some((icon_file, icon_bytes)) => {
+                // Part of the file name, so only plain extensions
+                let extension = Path::new(&icon_file)
+                    .extension()
+                    .map(|extension| extension.to_string_lossy().to_string())
+                    .filter(|extension| extension.chars().all(char::is_alphanumeric))
+                    .map(|extension| format!(".{extension}"))
+                    .unwrap_or_default();
+                let icon_path = app_dirs.app_data_icons.join(format!("{app_id}{extension}"));
+
+                fs::write(&icon_path, icon_bytes)
+                    .context(format!("Failed to save icon: '{}'", icon_path.display()))?;
+                icon_path
+            }
+            None => PathBuf::default(),
+        };
+        desktop_file.set_icon_path(&icon_path);
+
+        // The old profile is on the other machine, a new one is created for the new id
+        let can_isolate = browser.as_ref().is_some_and(|browser| browser.can_isolate);
+        let profile_path = if can_isolate && desktop_file.get_isolated().unwrap_or(false) {
+            desktop_file.build_profile_path()?
+        } else {
+            PathBuf::default()
+        };
+        desktop_file.set_profile_path(&profile_path);
+
+        info!(
+            app_id = manifest.app_id,
+            version = manifest.version,
+            browser_id = desktop_file.get_browser().map(|browser| browser.id.clone()),
+            "Imported bundle as: '{app_id}'"
+        );
+
+        Ok(desktop_file)
+    }
+
+    /// Installed browser for an app from another machine, falls back to "No browser"
+    pub fn find_local_browser(
+        browser_id: &str,
+        browser_config_name: Option<&str>,
+        browser_configs: &Rc<BrowserConfigs>,
+    ) -> Option<Rc<Browser>> {
+        let all_browsers = browser_configs.get_all_browsers();
+
+        if let Some(browser) = all_browsers
+            .iter()
+            .find(|browser| browser.id == browser_id && browser.is_installed())
+        {
+            return Some(browser.clone());
+        }
+
+        // Same browser with another installation type, like Flatpak on one machine and system on the other
+        if let Some(browser) = all_browsers.iter().find(|browser| {
+            browser.is_installed() && browser_config_name == Some(browser.config_name.as_str())
+        }) {
+            debug!(
+                browser_id = browser_id,
+                "Browser not installed, using: '{}'", browser.id
+            );
+            return Some(browser.clone());
+        }
+
+        debug!(
+            browser_id = browser_id,
+            "Browser not installed, using no browser"
+        );
+        all_browsers
+            .iter()
+            .find(|browser| browser.is_no_browser())
+            .cloned()
+    }
+
+    /// Only the entries of a bundle, a bigger entry is refused before it is read
+    fn read_bundle_entries(reader: impl Read) -> Result<HashMap<String, Vec<u8>>> {
+        let mut entries = HashMap::new();
+
+        archive::read_entries(reader, |name, entry| {
+            let mut content = Vec::new();
+            entry
+                .take(Self::BUNDLE_MAX_ENTRY_SIZE + 1)
+                .read_to_end(&mut content)?;
+            if u64::try_from(content.len())? > Self::BUNDLE_MAX_ENTRY_SIZE {
+                bail!("Entry '{name}' in bundle is too big")
+            }
+
+            entries.insert(name.to_string(), content);
+            Ok(ControlFlow::Continue(()))
+        })
+        .context("Bundle is not a valid archive")?;
+
+        Ok(entries)
+    }
+}
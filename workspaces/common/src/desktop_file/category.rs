@@ -58,6 +58,30 @@ impl Category {
         list
     }
 
+    /// The most specific known category in a `Categories` value like "AudioVideo;Audio;"
+    pub fn from_categories(categories: &str) -> Option<Self> {
+        let categories: Vec<&str> = categories
+            .split(';')
+            .map(str::trim)
+            .filter(|category| !category.is_empty())
+            .collect();
+
+        Self::get_all()
+            .into_iter()
+            .filter(|category| {
+                category
+                    .to_string()
+                    .split(';')
+                    .all(|part| categories.contains(&part))
+            })
+            .max_by_key(|category| category.to_string().split(';').count())
+    }
+
+    /// The spec wants every item in the list terminated, so also the last one
+    pub fn to_categories(self) -> String {
+        format!("{self};")
+    }
+
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn get_icon(&self) -> Image {
         let icon_name = match self {
@@ -0,0 +1,127 @@
+use crate::desktop_file::{DesktopFile, key::Key, launcher_backend};
+use std::collections::HashSet;
+use tracing::{debug, info};
+
+/// Keys a user added by hand, like `X-GNOME-UsesNotifications` or `Name[de]`.
+/// The file is made again from the browser template on every save, these are copied over.
+/// Groups and actions can run commands, they are only kept for a launcher the user already has.
+impl DesktopFile {
+    const MAIN_GROUP: &str = "Desktop Entry";
+    /// Always written by the app, an old value would be wrong after a browser change
+    const OWNED_KEYS: [Key; 6] = [
+        Key::Exec,
+        Key::TryExec,
+        Key::Icon,
+        Key::Actions,
+        Key::StartupWMClass,
+        Key::MimeTypes,
+    ];
+
+    pub fn copy_custom_keys_to(&self, desktop_file: &mut DesktopFile) {
+        let template_keys = self.get_template_keys();
+        let is_local_launcher = self.is_local_launcher();
+
+        for (group_name, group) in &self.desktop_entry.groups.0 {
+            if group_name == Self::MAIN_GROUP {
+                self.copy_custom_main_keys_to(desktop_file, &template_keys);
+                continue;
+            }
+            // Actions are only kept when they are listed
+            if Self::get_action_name(group_name).is_some()
+                || desktop_file.desktop_entry.groups.0.contains_key(group_name)
+            {
+                continue;
+            }
+            if !is_local_launcher {
+                info!(
+                    group = group_name,
+                    "Dropped desktop file group of imported file"
+                );
+                continue;
+            }
+
+            debug!(group = group_name, "Kept custom desktop file group");
+            desktop_file
+                .desktop_entry
+                .groups
+                .0
+                .insert(group_name.clone(), group.clone());
+        }
+
+        self.copy_custom_actions_to(desktop_file);
+    }
+
+    fn copy_custom_main_keys_to(
+        &self,
+        desktop_file: &mut DesktopFile,
+        template_keys: &HashSet<String>,
+    ) {
+        let Some(group) = self.desktop_entry.groups.0.get(Self::MAIN_GROUP) else {
+            return;
+        };
+        let Some(new_group) = desktop_file
+            .desktop_entry
+            .groups
+            .0
+            .get_mut(Self::MAIN_GROUP)
+        else {
+            return;
+        };
+
+        for (key, (value, localizations)) in &group.0 {
+            if Self::is_owned_key(key) {
+                continue;
+            }
+
+            match new_group.0.get_mut(key) {
+                // Only translations the template does not have, the value itself is from the template
+                Some((_, new_localizations)) => {
+                    for (locale, localized) in localizations {
+                        if !new_localizations.contains_key(locale) {
+                            debug!(key, locale, "Kept custom translation");
+                            new_localizations.insert(locale.clone(), localized.clone());
+                        }
+                    }
+                }
+                // A key of the old browser template, the new browser does not want it
+                None if template_keys.contains(key) => {}
+                None => {
+                    debug!(key, "Kept custom desktop file key");
+                    new_group
+                        .0
+                        .insert(key.clone(), (value.clone(), localizations.clone()));
+                }
+            }
+        }
+    }
+
+    /// An imported file (bundle, backup) has no launcher yet, its path is new
+    pub(crate) fn is_local_launcher(&self) -> bool {
+        let path = self.get_path();
+        let is_in_launcher_dir = launcher_backend::get_launcher_dirs(&self.app_dirs)
+            .into_iter()
+            .any(|dir| path.parent() == Some(dir));
+
+        is_in_launcher_dir && path.is_file()
+    }
+
+    fn is_owned_key(key: &str) -> bool {
+        let app_key = Key::Gwa.to_string();
+
+        key == app_key
+            || key.starts_with(&format!("{app_key}-"))
+            || Self::OWNED_KEYS
+                .iter()
+                .any(|owned_key| owned_key.to_string() == key)
+    }
+
+    /// Keys of every browser template, so a key of the previous browser does not stick
+    fn get_template_keys(&self) -> HashSet<String> {
+        self.browser_configs
+            .get_all_browsers()
+            .iter()
+            .filter_map(|browser| browser.desktop_file.groups.0.get(Self::MAIN_GROUP))
+            .flat_map(|group| group.0.keys().cloned())
+            .collect()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{desktop_file::DesktopFile, utils::command};
+use anyhow::Result;
+use gtk::{
+    gio,
+    glib::{self, SourceId},
+};
+use std::{
+    cell::RefCell,
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, error};
+
+/// Bulk changes like an import save many apps, they get one refresh after the last one
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+thread_local! {
+    static PENDING: RefCell<Option<(SourceId, PathBuf)>> = const { RefCell::new(None) };
+}
+
+impl DesktopFile {
+    /// Launchers cache the applications dir, without this a new app only shows after a new login
+    pub(super) fn notify_launchers(&self) {
+        schedule_refresh(&self.app_dirs.user_applications);
+    }
+}
+
+pub fn schedule_refresh(applications_dir: &Path) {
+    PENDING.with_borrow_mut(|pending| {
+        if let Some((source_id, _)) = pending.take() {
+            source_id.remove();
+        }
+
+        let dir = applications_dir.to_path_buf();
+        let source_id = glib::timeout_add_local_once(DEBOUNCE, move || {
+            PENDING.with_borrow_mut(|pending| *pending = None);
+            glib::spawn_future_local(async move {
+                let _ = gio::spawn_blocking(move || refresh(&dir)).await;
+            });
+        });
+        *pending = Some((source_id, applications_dir.to_path_buf()));
+    });
+}
+
+/// Runs a pending refresh now, for when the main loop stops before it is due
+pub fn flush() {
+    let Some((source_id, applications_dir)) = PENDING.with_borrow_mut(Option::take) else {
+        return;
+    };
+    source_id.remove();
+    refresh(&applications_dir);
+}
+
+/// Failures only delay launchers until they notice the change, they are logged and ignored
+fn refresh(applications_dir: &Path) {
+    // Inside the sandbox this runs on the host, without host access it is not found
+    if command::test_command_available_sync("update-desktop-database") {
+        let update_command = format!(
+            "update-desktop-database {}",
+            command::quote_exec_arg(&applications_dir.to_string_lossy())
+        );
+        match command::run_command_sync(&update_command) {
+            Ok(response) if response.success => {
+                debug!(path = %applications_dir.display(), "Updated desktop database");
+                return;
+            }
+            Ok(response) => error!(
+                stderr = response.stderr,
+                "Failed to update desktop database"
+            ),
+            Err(error) => error!("Failed to update desktop database: {error:?}"),
+        }
+    }
+
+    match touch(applications_dir) {
+        Ok(()) => debug!(path = %applications_dir.display(), "Touched applications dir"),
+        Err(error) => error!("Failed to touch applications dir: {error:?}"),
+    }
+}
+
+/// Launchers also watch the dir, a newer modified time makes them read it again
+fn touch(dir: &Path) -> Result<()> {
+    File::open(dir)?.set_modified(SystemTime::now())?;
+    Ok(())
+}
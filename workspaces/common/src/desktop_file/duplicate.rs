@@ -0,0 +1,72 @@
+use crate::desktop_file::{DesktopFile, key::Key};
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// A copy of a web app to open as a new app, it is saved like any other new app
+impl DesktopFile {
+    /// The copy gets a fresh id with its own icon file, so removing one app keeps the icon of the
+    /// other. An isolated app gets a new profile, the logins of the original are not shared.
+    pub fn duplicate(&self, name: &str) -> Result<Self> {
+        let mut desktop_file = Self::from_string(
+            Path::new(""),
+            &self.to_string(),
+            &self.browser_configs,
+            &self.app_dirs,
+        )?;
+
+        desktop_file.set_id(&Self::build_random_id());
+        desktop_file.ensure_unique_id()?;
+        let app_id = desktop_file.get_id().context("No id on 'DesktopFile'")?;
+        desktop_file.set_name(name);
+
+        // State of the original app, not its settings
+        for key in [
+            Key::LauncherId,
+            Key::PreviousHandlers,
+            Key::LastLaunched,
+            Key::LaunchCount,
+        ] {
+            desktop_file.remove_typed(key);
+        }
+
+        let icon_path = match self.get_icon_path().filter(|icon_path| icon_path.is_file()) {
+            Some(source_path) => {
+                let extension = source_path
+                    .extension()
+                    .map(|extension| format!(".{}", extension.to_string_lossy()))
+                    .unwrap_or_default();
+                let icon_path = self
+                    .app_dirs
+                    .app_data_icons
+                    .join(format!("{app_id}{extension}"));
+
+                fs::copy(&source_path, &icon_path)
+                    .context(format!("Failed to copy icon: '{}'", source_path.display()))?;
+                icon_path
+            }
+            None => PathBuf::default(),
+        };
+        desktop_file.set_icon_path(&icon_path);
+
+        let can_isolate = desktop_file
+            .get_browser()
+            .is_some_and(|browser| browser.can_isolate);
+        let profile_path = if can_isolate && desktop_file.get_isolated().unwrap_or(false) {
+            desktop_file.build_profile_path()?
+        } else {
+            PathBuf::default()
+        };
+        desktop_file.set_profile_path(&profile_path);
+
+        info!(
+            original_app_id = self.get_id(),
+            "Duplicated web app as: '{app_id}'"
+        );
+
+        Ok(desktop_file)
+    }
+}
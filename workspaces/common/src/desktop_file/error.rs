@@ -1,9 +1,11 @@
-use crate::desktop_file::key::Key;
+use crate::desktop_file::{key::Key, removal::DeleteReport};
 use std::fmt::Display;
 
 #[derive(Debug)]
 pub enum DesktopFileError {
     ValidationError(ValidationError),
+    /// Every field problem at once, so a form can mark all of them
+    ValidationErrors(Vec<ValidationError>),
     Other(anyhow::Error),
 }
 impl From<ValidationError> for DesktopFileError {
@@ -22,6 +24,11 @@ impl Display for DesktopFileError {
             Self::ValidationError(validation_error) => {
                 write!(f, "{validation_error}")
             }
+            Self::ValidationErrors(validation_errors) => {
+                let messages: Vec<String> =
+                    validation_errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", messages.join(", "))
+            }
             Self::Other(error) => {
                 write!(f, "{error}")
             }
@@ -29,6 +36,59 @@ impl Display for DesktopFileError {
     }
 }
 impl std::error::Error for DesktopFileError {}
+impl DesktopFileError {
+    /// The field problems of either validation variant, empty for other errors
+    pub fn get_validation_errors(&self) -> Vec<ValidationError> {
+        match self {
+            Self::ValidationError(validation_error) => vec![validation_error.clone()],
+            Self::ValidationErrors(validation_errors) => validation_errors.clone(),
+            Self::Other(_) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LaunchError {
+    NoExec,
+    BrowserMissing,
+    Other(anyhow::Error),
+}
+impl Display for LaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::NoExec => write!(f, "No exec on 'DesktopFile'"),
+            Self::BrowserMissing => write!(f, "Browser is not installed"),
+            Self::Other(error) => {
+                write!(f, "{error}")
+            }
+        }
+    }
+}
+impl std::error::Error for LaunchError {}
+
+/// A delete that left something behind, the report has what did go
+#[derive(Debug)]
+pub struct DeleteError {
+    pub report: DeleteReport,
+    /// Failures that are not about a file of the app, like the autostart entry
+    pub other_errors: Vec<anyhow::Error>,
+}
+impl Display for DeleteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut messages: Vec<String> = self
+            .report
+            .get_failed()
+            .map(|item| format!("{} '{}'", item.kind, item.path.display()))
+            .collect();
+        messages.extend(self.other_errors.iter().map(ToString::to_string));
+        write!(
+            f,
+            "Some files could not be removed: {}",
+            messages.join(", ")
+        )
+    }
+}
+impl std::error::Error for DeleteError {}
 #[derive(Debug, Clone)]
 pub struct ValidationError {
     pub field: Key,
@@ -0,0 +1,231 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::{Browser, BrowserConfigs, Installation},
+    desktop_file::{DesktopFile, utils::read_desktop_entry},
+    utils::files,
+};
+use anyhow::{Context, Result, bail};
+use gtk::glib;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{debug, info};
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignSource {
+    /// "Create shortcut" of Chrome and other Chromium browsers, `--app=URL`
+    Chromium,
+    /// Web apps of GNOME Web, `epiphany --application-mode`
+    Epiphany,
+}
+
+/// A web app made by a browser itself, read from its launcher
+#[derive(Debug, Clone)]
+pub struct ForeignWebApp {
+    pub path: PathBuf,
+    pub name: String,
+    pub url: String,
+    pub icon: Option<String>,
+    pub source: ForeignSource,
+    args: Vec<String>,
+}
+impl ForeignWebApp {
+    const CHROMIUM_APP_ARG: &str = "--app=";
+    const EPIPHANY_APP_ARG: &str = "--application-mode";
+    const ICON_SIZES: [&str; 8] = [
+        "512x512", "256x256", "192x192", "128x128", "96x96", "64x64", "48x48", "32x32",
+    ];
+
+    fn from_path(path: &Path) -> Option<Self> {
+        let desktop_entry = read_desktop_entry(path).ok()?;
+        let exec = desktop_entry.exec()?;
+        let args: Vec<String> = glib::shell_parse_argv(exec)
+            .ok()?
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+
+        let (source, url) = if let Some(url) = args
+            .iter()
+            .find_map(|arg| arg.strip_prefix(Self::CHROMIUM_APP_ARG))
+        {
+            (ForeignSource::Chromium, url.to_string())
+        } else if args.iter().any(|arg| arg == Self::EPIPHANY_APP_ARG) {
+            let url = args.iter().rev().find(|arg| Self::is_web_url(arg))?;
+            (ForeignSource::Epiphany, url.clone())
+        } else {
+            return None;
+        };
+        if !Self::is_web_url(&url) {
+            return None;
+        }
+
+        let name = desktop_entry
+            .name(&[] as &[&str])
+            .map(|name| name.to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| url.clone());
+
+        Some(Self {
+            path: path.to_path_buf(),
+            name,
+            url,
+            icon: desktop_entry.icon().map(ToString::to_string),
+            source,
+            args,
+        })
+    }
+
+    fn is_web_url(url: &str) -> bool {
+        Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+    }
+
+    /// The installed browser that made the launcher, found by its command
+    fn find_browser(&self, browser_configs: &BrowserConfigs) -> Option<Rc<Browser>> {
+        let command = self.args.first()?;
+        let command_name = Path::new(command)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        browser_configs
+            .get_all_browsers()
+            .into_iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .find(|browser| match &browser.installation {
+                Installation::Flatpak(id) => self.args.iter().any(|arg| arg == id),
+                // Chrome's launchers use "google-chrome", the config "google-chrome-stable"
+                Installation::System(executable) => {
+                    *executable == command_name
+                        || executable.starts_with(&format!("{command_name}-"))
+                }
+                Installation::Snap(name) => {
+                    *name == command_name || self.args.get(2).is_some_and(|arg| arg == name)
+                }
+                Installation::Custom(path) => Path::new(command) == path,
+                Installation::None => false,
+            })
+    }
+
+    /// A path in the launcher or the biggest icon in the user icon theme or portal icons
+    fn find_icon_path(&self, app_dirs: &AppDirs) -> Option<PathBuf> {
+        let icon = self.icon.as_deref().filter(|icon| !icon.is_empty())?;
+        let icon_path = Path::new(icon);
+        if icon_path.is_absolute() {
+            return Some(icon_path.to_path_buf()).filter(|icon_path| icon_path.is_file());
+        }
+
+        let hicolor_path = app_dirs.user_data.join("icons").join("hicolor");
+        let portal_icons_path = app_dirs.user_data.join("xdg-desktop-portal").join("icons");
+
+        Self::ICON_SIZES.iter().find_map(|size| {
+            ["png", "svg"].iter().find_map(|extension| {
+                let file_name = format!("{icon}.{extension}");
+                [
+                    hicolor_path.join(size).join("apps").join(&file_name),
+                    portal_icons_path.join(size).join(&file_name),
+                ]
+                .into_iter()
+                .find(|path| path.is_file())
+            })
+        })
+    }
+
+    /// Only on request of the user, the browser may still know about the app
+    pub fn remove_launcher(&self) -> Result<()> {
+        debug!(
+            "Removing launcher of foreign web app: {}",
+            self.path.display()
+        );
+        fs::remove_file(&self.path).context(format!(
+            "Failed to remove launcher: '{}'",
+            self.path.display()
+        ))
+    }
+}
+
+/// Web apps that browsers made on their own, the launchers are only read
+impl DesktopFile {
+    pub fn find_foreign_web_apps(app_dirs: &AppDirs) -> Vec<ForeignWebApp> {
+        let portal_applications = app_dirs
+            .user_data
+            .join("xdg-desktop-portal")
+            .join("applications");
+
+        let mut foreign_web_apps: Vec<ForeignWebApp> =
+            [&app_dirs.user_applications, &portal_applications]
+                .into_iter()
+                .flat_map(|dir| files::get_entries_in_dir(dir).unwrap_or_default())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|extension| extension == "desktop")
+                })
+                .filter(|path| !Self::is_owned(path).unwrap_or(true))
+                .filter(|path| !Self::is_owned_by_other_build(path).unwrap_or(true))
+                .filter_map(|path| ForeignWebApp::from_path(&path))
+                .collect();
+        foreign_web_apps.sort_by_key(|foreign_web_app| foreign_web_app.name.to_lowercase());
+
+        debug!(count = foreign_web_apps.len(), "Found foreign web apps");
+        foreign_web_apps
+    }
+
+    /// A new app with a fresh id, the icon is copied so the launcher can be removed
+    pub fn from_foreign_web_app(
+        foreign_web_app: &ForeignWebApp,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<Self> {
+        let browser = foreign_web_app.find_browser(browser_configs).or_else(|| {
+            debug!(
+                path = %foreign_web_app.path.display(),
+                "Browser of foreign web app not installed, using the first browser"
+            );
+            browser_configs
+                .get_all_browsers()
+                .into_iter()
+                .find(|browser| browser.is_installed() && !browser.is_no_browser())
+        });
+        let Some(browser) = browser else {
+            bail!("No browser installed")
+        };
+
+        let mut desktop_file = Self::new(browser_configs, app_dirs);
+        desktop_file.ensure_unique_id()?;
+        let app_id = desktop_file.get_id().context("No id on 'DesktopFile'")?;
+        desktop_file.set_name(&foreign_web_app.name);
+        desktop_file.set_url(&foreign_web_app.url);
+        desktop_file.set_browser(&browser);
+        desktop_file.set_isolated(false);
+        desktop_file.set_maximized(false);
+        desktop_file.set_profile_path(Path::new(""));
+
+        let icon_path = match foreign_web_app.find_icon_path(app_dirs) {
+            Some(source_path) => {
+                let extension = source_path
+                    .extension()
+                    .map(|extension| format!(".{}", extension.to_string_lossy()))
+                    .unwrap_or_default();
+                let icon_path = app_dirs.app_data_icons.join(format!("{app_id}{extension}"));
+
+                fs::copy(&source_path, &icon_path)
+                    .context(format!("Failed to copy icon: '{}'", source_path.display()))?;
+                icon_path
+            }
+            None => PathBuf::default(),
+        };
+        desktop_file.set_icon_path(&icon_path);
+
+        info!(
+            path = %foreign_web_app.path.display(),
+            browser_id = browser.id,
+            "Imported foreign web app as: '{app_id}'"
+        );
+
+        Ok(desktop_file)
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{
+    desktop_file::{DesktopFile, error::ValidationError, key::Key},
+    utils::command,
+};
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use tracing::{debug, error, info};
+use url::Url;
+
+/// Links of a scheme like `mailto:` or files of a MIME type open in the web app, the link goes
+/// into the handler url at the placeholder
+impl DesktopFile {
+    pub const HANDLER_PLACEHOLDER: &str = "%s";
+    pub const HANDLER_SCHEME_PRESETS: [&str; 3] = ["mailto", "webcal", "irc"];
+    const SCHEME_HANDLER_PREFIX: &str = "x-scheme-handler/";
+
+    pub fn get_mime_types(&self) -> Option<Vec<String>> {
+        self.get_typed(Key::MimeTypes)
+    }
+
+    /// Separated by commas, semicolons or spaces, a bare scheme like "mailto" is a scheme handler
+    pub fn set_mime_types(&mut self, mime_types: &str) {
+        let mut mime_type_list: Vec<String> = Vec::new();
+        for mime_type in mime_types
+            .split([',', ';', ' '])
+            .map(|mime_type| mime_type.trim().trim_end_matches(':').to_lowercase())
+            .filter(|mime_type| !mime_type.is_empty())
+        {
+            let mime_type = if mime_type.contains('/') {
+                mime_type
+            } else {
+                format!("{}{mime_type}", Self::SCHEME_HANDLER_PREFIX)
+            };
+            if !mime_type_list.contains(&mime_type) {
+                mime_type_list.push(mime_type);
+            }
+        }
+
+        if mime_type_list.is_empty() {
+            self.remove_typed(Key::MimeTypes);
+        } else {
+            self.set_typed(Key::MimeTypes, &format!("{};", mime_type_list.join(";")));
+        }
+    }
+
+    /// Shows scheme handlers as the bare scheme, like they are typed
+    pub fn get_mime_types_ui(&self) -> String {
+        self.get_mime_types()
+            .unwrap_or_default()
+            .iter()
+            .map(|mime_type| {
+                mime_type
+                    .strip_prefix(Self::SCHEME_HANDLER_PREFIX)
+                    .unwrap_or(mime_type)
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    pub fn get_handler_url(&self) -> Option<String> {
+        self.get_typed(Key::HandlerUrl)
+    }
+
+    pub fn set_handler_url(&mut self, handler_url: &str) {
+        let handler_url = handler_url.trim();
+
+        if handler_url.is_empty() {
+            self.remove_typed(Key::HandlerUrl);
+        } else {
+            self.set_typed(Key::HandlerUrl, handler_url);
+        }
+    }
+
+    /// The url for the `Exec` line with the `%u` field code, `None` when the app handles nothing
+    pub fn get_handler_exec_url(&self) -> Result<Option<String>, ValidationError> {
+        if self.get_mime_types().is_none() {
+            return Ok(None);
+        }
+
+        let handler_url = self
+            .get_handler_url()
+            .filter(|handler_url| handler_url.contains(Self::HANDLER_PLACEHOLDER))
+            .ok_or(ValidationError {
+                field: Key::HandlerUrl,
+                message: format!("Missing placeholder '{}'", Self::HANDLER_PLACEHOLDER),
+            })?;
+        Url::parse(&handler_url.replace(Self::HANDLER_PLACEHOLDER, "link")).map_err(|_| {
+            ValidationError {
+                field: Key::HandlerUrl,
+                message: "Invalid".to_string(),
+            }
+        })?;
+
+        // A literal '%' must be doubled in an `Exec` line
+        Ok(Some(handler_url.replace('%', "%%").replace(
+            &Self::HANDLER_PLACEHOLDER.replace('%', "%%"),
+            "%u",
+        )))
+    }
+
+    /// Previous defaults as "mime/type=app.desktop", so they can be restored later
+    fn get_previous_handlers(&self) -> Vec<(String, String)> {
+        self.get_typed::<Vec<String>>(Key::PreviousHandlers)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|previous_handler| previous_handler.split_once('='))
+            .map(|(mime_type, desktop_file_name)| {
+                (mime_type.to_string(), desktop_file_name.to_string())
+            })
+            .collect()
+    }
+
+    fn set_previous_handlers(&mut self, previous_handlers: &[(String, String)]) {
+        if previous_handlers.is_empty() {
+            self.remove_typed(Key::PreviousHandlers);
+            return;
+        }
+
+        let previous_handlers: Vec<String> = previous_handlers
+            .iter()
+            .map(|(mime_type, desktop_file_name)| format!("{mime_type}={desktop_file_name}"))
+            .collect();
+        self.set_typed(
+            Key::PreviousHandlers,
+            &format!("{};", previous_handlers.join(";")),
+        );
+    }
+
+    pub fn copy_handlers_to(&self, desktop_file: &mut DesktopFile) {
+        if let Some(mime_types) = self.get_mime_types() {
+            desktop_file.set_mime_types(&mime_types.join(";"));
+        }
+        if let Some(handler_url) = self.get_handler_url() {
+            desktop_file.set_handler_url(&handler_url);
+        }
+        desktop_file.set_previous_handlers(&self.get_previous_handlers());
+    }
+
+    fn get_desktop_file_name(path: &Path) -> Result<String> {
+        path.file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .context("No file name on desktop file")
+    }
+
+    fn query_default_handler(mime_type: &str) -> Option<String> {
+        let command = format!(
+            "xdg-mime query default {}",
+            command::quote_exec_arg(mime_type)
+        );
+
+        command::run_command_sync(&command)
+            .ok()
+            .filter(|response| response.success)
+            .map(|response| response.stdout)
+            .filter(|desktop_file_name| !desktop_file_name.is_empty())
+    }
+
+    fn set_default_handler(mime_type: &str, desktop_file_name: &str) -> Result<()> {
+        let command = format!(
+            "xdg-mime default {} {}",
+            command::quote_exec_arg(desktop_file_name),
+            command::quote_exec_arg(mime_type)
+        );
+
+        let response = command::run_command_sync(&command)?;
+        if !response.success {
+            bail!(
+                "Failed to set default handler for '{mime_type}': {}",
+                response.stderr
+            )
+        }
+
+        Ok(())
+    }
+
+    /// Mime types that open something else at the moment, asked before registering
+    pub fn get_unregistered_mime_types(&self) -> Vec<String> {
+        let Ok(desktop_file_name) = Self::get_desktop_file_name(&self.get_path()) else {
+            return Vec::new();
+        };
+
+        self.get_mime_types()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|mime_type| {
+                Self::query_default_handler(mime_type).as_ref() != Some(&desktop_file_name)
+            })
+            .collect()
+    }
+
+    /// Mime types that open this app at the moment
+    pub fn get_registered_mime_types(&self) -> Vec<String> {
+        let unregistered_mime_types = self.get_unregistered_mime_types();
+
+        self.get_mime_types()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|mime_type| !unregistered_mime_types.contains(mime_type))
+            .collect()
+    }
+
+    /// Undo of `restore_previous_handlers`, the previous defaults are still recorded
+    pub fn reregister_default_handlers(&self, mime_types: &[String]) -> Result<()> {
+        let desktop_file_name = Self::get_desktop_file_name(&self.get_path())?;
+
+        for mime_type in mime_types {
+            Self::set_default_handler(mime_type, &desktop_file_name)?;
+        }
+
+        Ok(())
+    }
+
+    /// Only on confirmation of the user, the current defaults are recorded first
+    pub fn register_default_handlers(&mut self) -> Result<()> {
+        if !command::test_command_available_sync("xdg-mime") {
+            bail!("'xdg-mime' is not available")
+        }
+        let desktop_file_name = Self::get_desktop_file_name(&self.get_path())?;
+        let mut previous_handlers = self.get_previous_handlers();
+
+        for mime_type in self.get_unregistered_mime_types() {
+            if let Some(previous_handler) = Self::query_default_handler(&mime_type)
+                && !previous_handlers
+                    .iter()
+                    .any(|(mime_type_iter, _)| *mime_type_iter == mime_type)
+            {
+                previous_handlers.push((mime_type.clone(), previous_handler));
+            }
+
+            Self::set_default_handler(&mime_type, &desktop_file_name)?;
+            info!(mime_type, "Registered web app as default handler");
+        }
+
+        self.set_previous_handlers(&previous_handlers);
+        Ok(())
+    }
+
+    /// Restores the previous defaults of mime types the app does not handle anymore
+    pub fn unregister_removed_handlers(&mut self) {
+        let mime_types = self.get_mime_types().unwrap_or_default();
+        let previous_handlers = self.restore_previous_handlers(&mime_types);
+        self.set_previous_handlers(&previous_handlers);
+    }
+
+    /// Returns the records that are kept, a default the user changed since is left alone
+    pub fn restore_previous_handlers(&self, keep_mime_types: &[String]) -> Vec<(String, String)> {
+        let desktop_file_name = Self::get_desktop_file_name(&self.get_path()).ok();
+
+        self.get_previous_handlers()
+            .into_iter()
+            .filter(|(mime_type, previous_handler)| {
+                if keep_mime_types.contains(mime_type) {
+                    return true;
+                }
+                if Self::query_default_handler(mime_type) != desktop_file_name {
+                    return false;
+                }
+
+                debug!(
+                    mime_type,
+                    previous_handler, "Restoring previous default handler"
+                );
+                if let Err(error) = Self::set_default_handler(mime_type, previous_handler) {
+                    error!("{error:?}");
+                }
+                false
+            })
+            .collect()
+    }
+
+    /// A new browser gives the desktop file a new name, the defaults follow it
+    pub fn sync_default_handlers(&self, old_path: &Path) {
+        let (Ok(old_file_name), Ok(desktop_file_name)) = (
+            Self::get_desktop_file_name(old_path),
+            Self::get_desktop_file_name(&self.get_path()),
+        ) else {
+            return;
+        };
+        if old_file_name == desktop_file_name {
+            return;
+        }
+
+        for mime_type in self.get_mime_types().unwrap_or_default() {
+            if Self::query_default_handler(&mime_type).as_ref() == Some(&old_file_name)
+                && let Err(error) = Self::set_default_handler(&mime_type, &desktop_file_name)
+            {
+                error!("{error:?}");
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
 };
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Key {
     Gwa,
     Version,
@@ -13,13 +13,32 @@ pub enum Key {
     BrowserId,
     Isolate,
     Maximize,
+    Fullscreen,
+    Private,
+    ForceDark,
+    MinimalUi,
+    WindowWidth,
+    WindowHeight,
     Profile,
+    ThemeColor,
+    ExtraArgs,
+    Env,
+    HandlerUrl,
+    PreviousHandlers,
+    LauncherId,
+    LastLaunched,
+    LaunchCount,
     Name,
     Exec,
     Icon,
     StartupWMClass,
     Categories,
     Comment,
+    Keywords,
+    Actions,
+    MimeTypes,
+    Type,
+    TryExec,
 }
 impl Display for Key {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -33,13 +52,32 @@ impl Display for Key {
             Self::BrowserId => write!(f, "X-{}-BROWSER-ID", &identifier),
             Self::Isolate => write!(f, "X-{}-ISOLATE", &identifier),
             Self::Maximize => write!(f, "X-{}-MAXIMIZE", &identifier),
+            Self::Fullscreen => write!(f, "X-{}-FULLSCREEN", &identifier),
+            Self::Private => write!(f, "X-{}-PRIVATE", &identifier),
+            Self::ForceDark => write!(f, "X-{}-FORCE-DARK", &identifier),
+            Self::MinimalUi => write!(f, "X-{}-MINIMAL-UI", &identifier),
+            Self::WindowWidth => write!(f, "X-{}-WINDOW-WIDTH", &identifier),
+            Self::WindowHeight => write!(f, "X-{}-WINDOW-HEIGHT", &identifier),
             Self::Profile => write!(f, "X-{}-PROFILE", &identifier),
+            Self::ThemeColor => write!(f, "X-{}-THEME-COLOR", &identifier),
+            Self::ExtraArgs => write!(f, "X-{}-EXTRA-ARGS", &identifier),
+            Self::Env => write!(f, "X-{}-ENV", &identifier),
+            Self::HandlerUrl => write!(f, "X-{}-HANDLER-URL", &identifier),
+            Self::PreviousHandlers => write!(f, "X-{}-PREVIOUS-HANDLERS", &identifier),
+            Self::LauncherId => write!(f, "X-{}-LAUNCHER-ID", &identifier),
+            Self::LastLaunched => write!(f, "X-{}-LAST-LAUNCHED", &identifier),
+            Self::LaunchCount => write!(f, "X-{}-LAUNCH-COUNT", &identifier),
             Self::Name => write!(f, "Name"),
             Self::Exec => write!(f, "Exec"),
             Self::Icon => write!(f, "Icon"),
             Self::StartupWMClass => write!(f, "StartupWMClass"),
             Self::Categories => write!(f, "Categories"),
             Self::Comment => write!(f, "Comment"),
+            Self::Keywords => write!(f, "Keywords"),
+            Self::Actions => write!(f, "Actions"),
+            Self::MimeTypes => write!(f, "MimeType"),
+            Self::Type => write!(f, "Type"),
+            Self::TryExec => write!(f, "TryExec"),
         }
     }
 }
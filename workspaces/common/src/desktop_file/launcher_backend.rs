@@ -0,0 +1,558 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::BrowserConfigs,
+    config::{self},
+    desktop_file::{
+        DesktopFile,
+        key::Key,
+        removal::{self, DeleteOutcome},
+    },
+    utils::{OnceLockExt, env, files},
+};
+use anyhow::{Context, Result, bail};
+use futures_channel::oneshot;
+use gtk::{
+    gio::{self, DBusCallFlags, DBusConnection, DBusSignalFlags, prelude::IconExt},
+    glib::{self, Variant, VariantDict, prelude::ToVariant},
+};
+use rand::{Rng, distributions::Alphanumeric};
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+use tracing::{debug, error, info};
+
+/// Where the launcher of a web app ends up, so launchers see it
+pub trait LauncherBackend {
+    /// The dir the desktop file is kept in, the app list is read from it
+    fn get_dir(&self, app_dirs: &AppDirs) -> PathBuf;
+    /// The desktop file has its final path and content
+    fn save(&self, desktop_file: &DesktopFile) -> Result<()>;
+    /// `None` when there was no desktop file to remove
+    fn delete(&self, desktop_file: &DesktopFile) -> Result<Option<DeleteOutcome>>;
+}
+
+/// Writes to the applications dir, which needs filesystem access inside the sandbox
+pub struct FileBackend;
+impl LauncherBackend for FileBackend {
+    fn get_dir(&self, app_dirs: &AppDirs) -> PathBuf {
+        app_dirs.user_applications.clone()
+    }
+
+    fn save(&self, desktop_file: &DesktopFile) -> Result<()> {
+        let path = desktop_file.get_path();
+        files::write_atomic(&path, desktop_file.to_string().as_bytes())
+            .context("Saving desktop file")
+    }
+
+    fn delete(&self, desktop_file: &DesktopFile) -> Result<Option<DeleteOutcome>> {
+        let path = desktop_file.get_path();
+        if !path.is_file() {
+            return Ok(None);
+        }
+        removal::trash_or_remove(&path)
+            .context("Failed to remove desktop file")
+            .map(Some)
+    }
+}
+
+/// Installs through `org.freedesktop.portal.DynamicLauncher`, for a sandbox without access to
+/// the applications dir. The portal asks the user to confirm every install, so saving only keeps
+/// a copy in the app data and `DesktopFile::install_launcher` installs it when the user is asked.
+/// The sandbox can't read the installed launcher back, a record tells what the portal has.
+pub struct PortalBackend;
+impl PortalBackend {
+    const BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+    const OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+    const INTERFACE: &str = "org.freedesktop.portal.DynamicLauncher";
+    const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+    /// The user answers the confirmation, a portal that never answers is given up on
+    const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+    const RESPONSE_CANCELLED: u32 = 1;
+    const INSTALLED_EXTENSION: &str = "installed";
+
+    /// The portal only accepts ids that start with the app id of the sandbox
+    pub fn build_launcher_id(desktop_file: &DesktopFile) -> Result<String> {
+        let id = desktop_file.get_id().context("No id on 'DesktopFile'")?;
+
+        Ok(format!(
+            "{}.{}-{id}.desktop",
+            config::APP_ID.get_value(),
+            config::APP_NAME_SHORT.get_value()
+        ))
+    }
+
+    /// `false` when the user cancelled
+    async fn install(connection: &DBusConnection, desktop_file: &DesktopFile) -> Result<bool> {
+        let launcher_id = desktop_file
+            .get_launcher_id()
+            .context("No launcher id on 'DesktopFile'")?;
+        let name = desktop_file
+            .get_name()
+            .context("No name on 'DesktopFile'")?;
+        let icon_path = desktop_file
+            .get_icon_path()
+            .filter(|icon_path| icon_path.is_file())
+            .context("No icon on 'DesktopFile', the portal needs one")?;
+        let icon_bytes = fs::read(&icon_path).context("Failed to read icon")?;
+        let icon = gio::BytesIcon::new(&glib::Bytes::from_owned(icon_bytes))
+            .serialize()
+            .context("Failed to serialize icon")?;
+
+        let Some(token) = Self::prepare_install(connection, &name, &icon).await? else {
+            info!(launcher_id, "Install of the launcher was cancelled");
+            return Ok(false);
+        };
+
+        connection
+            .call_future(
+                Some(Self::BUS_NAME),
+                Self::OBJECT_PATH,
+                Self::INTERFACE,
+                "Install",
+                Some(
+                    &(
+                        token,
+                        launcher_id.as_str(),
+                        Self::build_portal_entry(desktop_file),
+                        VariantDict::new(None),
+                    )
+                        .to_variant(),
+                ),
+                None,
+                DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+            .context("Failed to install launcher through the portal")?;
+
+        info!(launcher_id, "Installed launcher through the portal");
+        Ok(true)
+    }
+
+    /// Shows the confirmation of the portal, the token of the answer allows one install.
+    /// `None` when the user cancelled.
+    async fn prepare_install(
+        connection: &DBusConnection,
+        name: &str,
+        icon: &Variant,
+    ) -> Result<Option<String>> {
+        let handle_token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+        let handle_token = format!("webapphub{handle_token}");
+        // Subscribed before the call, the answer can come before the call returns
+        let sender = connection
+            .unique_name()
+            .context("No unique name on the session bus")?;
+        let request_path = format!(
+            "{}/request/{}/{handle_token}",
+            Self::OBJECT_PATH,
+            sender.trim_start_matches(':').replace('.', "_")
+        );
+
+        let (response_sender, response_receiver) = oneshot::channel::<(u32, VariantDict)>();
+        let response_sender = RefCell::new(Some(response_sender));
+        let subscription = connection.subscribe_to_signal(
+            Some(Self::BUS_NAME),
+            Some(Self::REQUEST_INTERFACE),
+            Some("Response"),
+            Some(&request_path),
+            None,
+            DBusSignalFlags::NO_MATCH_RULE,
+            move |signal| {
+                if let Some(response) = signal.parameters.get::<(u32, VariantDict)>()
+                    && let Some(response_sender) = response_sender.borrow_mut().take()
+                {
+                    let _ = response_sender.send(response);
+                }
+            },
+        );
+
+        let options = VariantDict::new(None);
+        options.insert("handle_token", handle_token.as_str());
+        options.insert("editable_name", false);
+        options.insert("editable_icon", false);
+        connection
+            .call_future(
+                Some(Self::BUS_NAME),
+                Self::OBJECT_PATH,
+                Self::INTERFACE,
+                "PrepareInstall",
+                Some(&("", name, icon, options).to_variant()),
+                None,
+                DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+            .context("Failed to ask the portal to install a launcher")?;
+
+        let response = glib::future_with_timeout(Self::RESPONSE_TIMEOUT, response_receiver).await;
+        drop(subscription);
+        let (code, results) = match response {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => bail!("No answer from the portal"),
+            Err(_) => {
+                Self::close_request(connection, &request_path).await;
+                bail!("No answer from the portal in time")
+            }
+        };
+        if code == Self::RESPONSE_CANCELLED {
+            return Ok(None);
+        }
+        if code != 0 {
+            bail!("Install of the launcher failed in the portal")
+        }
+        results
+            .lookup::<String>("token")
+            .ok()
+            .flatten()
+            .context("No token in the answer of the portal")
+            .map(Some)
+    }
+
+    /// Closes the confirmation that was not answered in time
+    async fn close_request(connection: &DBusConnection, request_path: &str) {
+        if let Err(error) = connection
+            .call_future(
+                Some(Self::BUS_NAME),
+                request_path,
+                Self::REQUEST_INTERFACE,
+                "Close",
+                None,
+                None,
+                DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+        {
+            debug!(request_path, "Failed to close portal request: {error}");
+        }
+    }
+
+    fn uninstall(connection: &DBusConnection, launcher_id: &str) -> Result<()> {
+        connection
+            .call_sync(
+                Some(Self::BUS_NAME),
+                Self::OBJECT_PATH,
+                Self::INTERFACE,
+                "Uninstall",
+                Some(&(launcher_id, VariantDict::new(None)).to_variant()),
+                None,
+                DBusCallFlags::NONE,
+                -1,
+                gio::Cancellable::NONE,
+            )
+            .context("Failed to uninstall launcher through the portal")?;
+
+        info!(launcher_id, "Uninstalled launcher through the portal");
+        Ok(())
+    }
+
+    /// The portal runs `Exec` inside this sandbox, the browser is on the host
+    fn build_portal_entry(desktop_file: &DesktopFile) -> String {
+        let mut portal_desktop_file = desktop_file.clone();
+        for group in portal_desktop_file.desktop_entry.groups.0.values_mut() {
+            if let Some((exec, _)) = group.0.get_mut("Exec") {
+                *exec = format!("flatpak-spawn --host {exec}");
+            }
+        }
+
+        portal_desktop_file.to_string()
+    }
+
+    /// Removes only the installed launcher, the copy in the app data is left alone
+    pub fn uninstall_launcher(launcher_id: &str) -> Result<()> {
+        Self::uninstall(&Self::get_connection()?, launcher_id)
+    }
+
+    fn get_connection() -> Result<DBusConnection> {
+        gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)
+            .context("Failed to connect to the session bus")
+    }
+
+    /// Next to the copies, by launcher id so it stays when the file name changes
+    fn get_installed_path(app_dirs: &AppDirs, launcher_id: &str) -> PathBuf {
+        app_dirs
+            .app_data_launchers
+            .join(format!("{launcher_id}.{}", Self::INSTALLED_EXTENSION))
+    }
+
+    /// What the user sees of the launcher. Keys of this app change on every launch, they are
+    /// left out so counting a launch does not ask the user again.
+    fn build_fingerprint(desktop_file: &DesktopFile) -> Option<String> {
+        let app_key = Key::Gwa.to_string();
+        let portal_entry: String = Self::build_portal_entry(desktop_file)
+            .lines()
+            .filter(|line| !line.starts_with(&app_key))
+            .flat_map(|line| [line, "\n"])
+            .collect();
+        let icon_bytes = fs::read(desktop_file.get_icon_path()?).ok()?;
+
+        glib::compute_checksum_for_data(
+            glib::ChecksumType::Sha256,
+            &[portal_entry.as_bytes(), &icon_bytes].concat(),
+        )
+        .map(|checksum| checksum.to_string())
+    }
+
+    fn record_installed(desktop_file: &DesktopFile, launcher_id: &str) -> Result<()> {
+        let Some(fingerprint) = Self::build_fingerprint(desktop_file) else {
+            return Ok(());
+        };
+        let path = Self::get_installed_path(&desktop_file.app_dirs, launcher_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create launchers dir")?;
+        }
+
+        files::write_atomic(&path, fingerprint.as_bytes()).context("Failed to record launcher")
+    }
+}
+impl LauncherBackend for PortalBackend {
+    fn get_dir(&self, app_dirs: &AppDirs) -> PathBuf {
+        app_dirs.app_data_launchers.clone()
+    }
+
+    fn save(&self, desktop_file: &DesktopFile) -> Result<()> {
+        let path = desktop_file.get_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create launchers dir")?;
+        }
+        files::write_atomic(&path, desktop_file.to_string().as_bytes())
+            .context("Saving copy of the launcher")
+    }
+
+    fn delete(&self, desktop_file: &DesktopFile) -> Result<Option<DeleteOutcome>> {
+        if let Some(launcher_id) = desktop_file.get_launcher_id() {
+            Self::uninstall_launcher(&launcher_id)?;
+            let _ = fs::remove_file(Self::get_installed_path(
+                &desktop_file.app_dirs,
+                &launcher_id,
+            ));
+        }
+
+        FileBackend.delete(desktop_file)
+    }
+}
+
+impl DesktopFile {
+    pub fn get_launcher_backend(&self) -> Box<dyn LauncherBackend> {
+        if self.get_launcher_id().is_some() {
+            Box::new(PortalBackend)
+        } else {
+            Box::new(FileBackend)
+        }
+    }
+
+    /// Only inside the sandbox, and only for apps that are not on the portal yet
+    pub(super) fn can_use_portal(&self) -> bool {
+        env::is_flatpak_container() && self.get_launcher_id().is_none()
+    }
+
+    /// A launcher of the portal that is missing or differs from what the user last confirmed.
+    /// Without an icon the portal refuses the install, asking would only fail.
+    pub fn needs_launcher_install(&self) -> bool {
+        let Some(launcher_id) = self.get_launcher_id() else {
+            return false;
+        };
+        let Some(fingerprint) = PortalBackend::build_fingerprint(self) else {
+            return false;
+        };
+
+        fs::read_to_string(PortalBackend::get_installed_path(
+            &self.app_dirs,
+            &launcher_id,
+        ))
+        .ok()
+        .is_none_or(|installed| installed != fingerprint)
+    }
+
+    /// Asks the user through the portal, `false` when they cancelled.
+    /// Takes a copy, nothing is borrowed while the portal waits for the user.
+    pub async fn install_launcher(self) -> Result<bool> {
+        let launcher_id = self
+            .get_launcher_id()
+            .context("No launcher id on 'DesktopFile'")?;
+        let connection = gio::bus_get_future(gio::BusType::Session)
+            .await
+            .context("Failed to connect to the session bus")?;
+
+        if !PortalBackend::install(&connection, &self).await? {
+            return Ok(false);
+        }
+        PortalBackend::record_installed(&self, &launcher_id)?;
+
+        Ok(true)
+    }
+
+    /// When the applications dir can't be written, the app moves to the portal for good
+    pub(super) fn switch_to_portal(&mut self) -> Result<()> {
+        let launcher_id = PortalBackend::build_launcher_id(self)?;
+        debug!(launcher_id, "Switching web app to the launcher portal");
+        self.set_launcher_id(&launcher_id);
+
+        Ok(())
+    }
+
+    /// Web apps that older versions saved in the data dir of the sandbox are saved again, to the
+    /// host applications dir or through the portal. Returns the paths that were moved, a file that
+    /// fails stays and is tried again on the next start.
+    pub fn migrate_sandbox_launchers(
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Vec<PathBuf> {
+        let Some(sandbox_dir) = get_sandbox_applications_dir(app_dirs) else {
+            return Vec::new();
+        };
+        let mut moved_paths = Vec::new();
+
+        for path in files::get_entries_in_dir(&sandbox_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "desktop")
+                    && Self::is_owned(path).unwrap_or(false)
+            })
+        {
+            let mut desktop_file = match Self::from_path(&path, browser_configs, app_dirs) {
+                Ok(desktop_file) => desktop_file,
+                Err(error) => {
+                    error!(path = %path.display(), ?error, "Failed to read desktop file in the sandbox");
+                    continue;
+                }
+            };
+            if desktop_file
+                .get_save_path()
+                .is_ok_and(|save_path| save_path.exists())
+            {
+                info!(path = %path.display(), "Web app is already on the host, keeping the sandbox copy");
+                continue;
+            }
+
+            info!(path = %path.display(), "Moving web app out of the data dir of the sandbox");
+            match desktop_file.write() {
+                Ok(()) => moved_paths.push(path),
+                Err(error) => {
+                    error!(path = %path.display(), ?error, "Failed to move web app out of the sandbox");
+                }
+            }
+        }
+
+        moved_paths
+    }
+}
+
+/// Private data dir of the sandbox, older versions saved launchers there and the host never saw
+/// them. `None` outside the sandbox.
+pub fn get_sandbox_applications_dir(app_dirs: &AppDirs) -> Option<PathBuf> {
+    let sandbox_dir = app_dirs.user_data.join("applications");
+    (env::is_flatpak_container() && sandbox_dir != app_dirs.user_applications)
+        .then_some(sandbox_dir)
+}
+
+/// Dirs web apps are read from, a dir that does not exist is skipped by the caller
+pub fn get_launcher_dirs(app_dirs: &AppDirs) -> [&Path; 2] {
+    [&app_dirs.user_applications, &app_dirs.app_data_launchers]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_dirs::BaseDirs;
+
+    fn build_desktop_file(root: &Path) -> DesktopFile {
+        config::init();
+        let app_dirs = AppDirs::from_base_dirs(BaseDirs {
+            home: root.join("home"),
+            data: root.join("data"),
+            config: root.join("config"),
+            cache: root.join("cache"),
+            system_data: Vec::new(),
+        });
+        app_dirs.init().unwrap();
+        let app_dirs = Rc::new(app_dirs);
+        let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+        let icon_path = app_dirs.app_data_icons.join("mail.png");
+        fs::write(&icon_path, b"icon").unwrap();
+
+        let mut desktop_file = DesktopFile::from_string(
+            &app_dirs.app_data_launchers.join("mail.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Mail\nExec=firefox https://mail.example.com/\n",
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+        desktop_file.set_icon_path(&icon_path);
+        desktop_file.set_id("mail");
+        desktop_file
+    }
+
+    fn record_installed(desktop_file: &DesktopFile) {
+        PortalBackend::record_installed(desktop_file, &desktop_file.get_launcher_id().unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn launcher_outside_the_portal_is_not_installed() {
+        let root = tempfile::tempdir().unwrap();
+        let desktop_file = build_desktop_file(root.path());
+
+        assert!(!desktop_file.needs_launcher_install());
+    }
+
+    #[test]
+    fn new_launcher_of_the_portal_is_installed_once() {
+        let root = tempfile::tempdir().unwrap();
+        let mut desktop_file = build_desktop_file(root.path());
+        desktop_file.switch_to_portal().unwrap();
+
+        assert!(desktop_file.needs_launcher_install());
+        record_installed(&desktop_file);
+        assert!(!desktop_file.needs_launcher_install());
+    }
+
+    #[test]
+    fn keys_of_the_app_do_not_install_again() {
+        let root = tempfile::tempdir().unwrap();
+        let mut desktop_file = build_desktop_file(root.path());
+        desktop_file.switch_to_portal().unwrap();
+        record_installed(&desktop_file);
+
+        desktop_file.set_launch_count(3);
+
+        assert!(!desktop_file.needs_launcher_install());
+    }
+
+    #[test]
+    fn changed_name_or_icon_installs_again() {
+        let root = tempfile::tempdir().unwrap();
+        let mut desktop_file = build_desktop_file(root.path());
+        desktop_file.switch_to_portal().unwrap();
+        record_installed(&desktop_file);
+
+        desktop_file.set_name("Webmail");
+        assert!(desktop_file.needs_launcher_install());
+        record_installed(&desktop_file);
+
+        fs::write(desktop_file.get_icon_path().unwrap(), b"new icon").unwrap();
+        assert!(desktop_file.needs_launcher_install());
+    }
+
+    #[test]
+    fn launcher_without_icon_is_not_installed() {
+        let root = tempfile::tempdir().unwrap();
+        let mut desktop_file = build_desktop_file(root.path());
+        desktop_file.switch_to_portal().unwrap();
+
+        fs::remove_file(desktop_file.get_icon_path().unwrap()).unwrap();
+
+        assert!(!desktop_file.needs_launcher_install());
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{
+    desktop_file::{DesktopFile, key::Key, utils},
+    utils::command,
+};
+use std::{fmt::Display, path::Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    /// Launchers still show the app, `desktop-file-validate` complains
+    Warning,
+    /// Launchers can skip the app, the file is not saved
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintIssue {
+    pub level: LintLevel,
+    pub field: Key,
+    pub message: String,
+}
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.message, self.field)
+    }
+}
+
+/// Checks of the Desktop Entry spec on the file as it will be written
+impl DesktopFile {
+    const REQUIRED_KEYS: [Key; 3] = [Key::Type, Key::Name, Key::Exec];
+    const LIST_KEYS: [Key; 4] = [Key::Categories, Key::Keywords, Key::MimeTypes, Key::Actions];
+    const TEXT_KEYS: [Key; 6] = [
+        Key::Name,
+        Key::Comment,
+        Key::Keywords,
+        Key::Icon,
+        Key::Exec,
+        Key::StartupWMClass,
+    ];
+    /// Only one of these may be in an `Exec`, each of them takes the files or urls to open
+    const FILE_FIELD_CODES: [&str; 4] = ["%f", "%F", "%u", "%U"];
+    const ICON_EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+
+    pub fn lint(&self) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        self.lint_required(&mut issues);
+        self.lint_lists(&mut issues);
+        self.lint_exec(&mut issues);
+        self.lint_icon(&mut issues);
+        self.lint_try_exec(&mut issues);
+        self.lint_control_characters(&mut issues);
+
+        issues
+    }
+
+    fn get_lint_value(&self, key: Key) -> Option<&str> {
+        self.desktop_entry.desktop_entry(&key.to_string())
+    }
+
+    fn lint_required(&self, issues: &mut Vec<LintIssue>) {
+        for key in Self::REQUIRED_KEYS {
+            if self.get_lint_value(key).is_none_or(str::is_empty) {
+                issues.push(LintIssue {
+                    level: LintLevel::Error,
+                    field: key,
+                    message: "Missing a required key".to_string(),
+                });
+            }
+        }
+
+        if let Some(entry_type) = self.get_lint_value(Key::Type)
+            && !entry_type.is_empty()
+            && entry_type != "Application"
+        {
+            issues.push(LintIssue {
+                level: LintLevel::Error,
+                field: Key::Type,
+                message: format!("Type '{entry_type}' is not an application"),
+            });
+        }
+    }
+
+    fn lint_lists(&self, issues: &mut Vec<LintIssue>) {
+        for key in Self::LIST_KEYS {
+            if let Some(value) = self.get_lint_value(key)
+                && !value.is_empty()
+                && !value.ends_with(';')
+            {
+                issues.push(LintIssue {
+                    level: LintLevel::Warning,
+                    field: key,
+                    message: "A list must end with a semicolon".to_string(),
+                });
+            }
+        }
+    }
+
+    fn lint_exec(&self, issues: &mut Vec<LintIssue>) {
+        let Some(exec) = self.get_lint_value(Key::Exec) else {
+            return;
+        };
+
+        if let Err(message) = utils::validate_exec(exec) {
+            issues.push(LintIssue {
+                level: LintLevel::Error,
+                field: Key::Exec,
+                message,
+            });
+            return;
+        }
+
+        // A doubled '%' is a literal, not a field code
+        let field_codes = exec.replace("%%", "");
+        let file_field_code_count = Self::FILE_FIELD_CODES
+            .iter()
+            .map(|field_code| field_codes.matches(field_code).count())
+            .sum::<usize>();
+        if file_field_code_count > 1 {
+            issues.push(LintIssue {
+                level: LintLevel::Error,
+                field: Key::Exec,
+                message: "Only one of %f, %F, %u or %U is allowed".to_string(),
+            });
+        }
+    }
+
+    /// An icon is an absolute path or a theme icon name, without an extension
+    fn lint_icon(&self, issues: &mut Vec<LintIssue>) {
+        let Some(icon) = self
+            .get_lint_value(Key::Icon)
+            .filter(|icon| !icon.is_empty())
+        else {
+            return;
+        };
+        let icon_path = Path::new(icon);
+
+        if icon_path.is_absolute() {
+            if !icon_path.is_file() {
+                issues.push(LintIssue {
+                    level: LintLevel::Warning,
+                    field: Key::Icon,
+                    message: format!("Icon file does not exist: {icon}"),
+                });
+            }
+        } else if icon.contains('/') {
+            issues.push(LintIssue {
+                level: LintLevel::Error,
+                field: Key::Icon,
+                message: "A relative icon path is not allowed".to_string(),
+            });
+        } else if icon_path.extension().is_some_and(|extension| {
+            Self::ICON_EXTENSIONS
+                .iter()
+                .any(|icon_extension| extension.eq_ignore_ascii_case(icon_extension))
+        }) {
+            issues.push(LintIssue {
+                level: LintLevel::Warning,
+                field: Key::Icon,
+                message: "An icon name should not have an extension".to_string(),
+            });
+        }
+    }
+
+    fn lint_try_exec(&self, issues: &mut Vec<LintIssue>) {
+        if self.is_try_exec_missing() {
+            issues.push(LintIssue {
+                level: LintLevel::Warning,
+                field: Key::TryExec,
+                message: "Program not found".to_string(),
+            });
+        }
+    }
+
+    /// Launchers hide the app when `TryExec` is not found.
+    /// A program name is looked up with a command, unless browser detection already did.
+    pub fn is_try_exec_missing(&self) -> bool {
+        let Some(try_exec) = self
+            .get_lint_value(Key::TryExec)
+            .filter(|try_exec| !try_exec.is_empty())
+        else {
+            return false;
+        };
+
+        if let Some(browser) = self.get_browser()
+            && browser.get_try_exec().as_deref() == Some(try_exec)
+        {
+            return !browser.is_installed();
+        }
+
+        if Path::new(try_exec).is_absolute() {
+            !Path::new(try_exec).is_file()
+        } else {
+            !command::test_command_available_sync(try_exec)
+        }
+    }
+
+    fn lint_control_characters(&self, issues: &mut Vec<LintIssue>) {
+        for key in Self::TEXT_KEYS {
+            if self
+                .get_lint_value(key)
+                .is_some_and(|value| value.contains(char::is_control))
+            {
+                issues.push(LintIssue {
+                    level: LintLevel::Error,
+                    field: key,
+                    message: "Can't contain control characters, like a new line".to_string(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app_dirs::AppDirs, browsers::BrowserConfigs, config};
+    use std::{fs, rc::Rc};
+
+    /// Levels and fields of the issues, the lines are added to a valid file
+    fn lint(lines: &str) -> Vec<(LintLevel, Key)> {
+        config::init();
+        let app_dirs = Rc::new(AppDirs::default());
+        let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+        let desktop_file_string = format!(
+            "[Desktop Entry]\nType=Application\nName=Mail\nExec=browser --app=https://example.com %u\n{lines}"
+        );
+        let desktop_file = DesktopFile::from_string(
+            Path::new("/tmp/mail.desktop"),
+            &desktop_file_string,
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+
+        desktop_file
+            .lint()
+            .into_iter()
+            .map(|issue| (issue.level, issue.field))
+            .collect()
+    }
+
+    #[test]
+    fn valid_file_has_no_issues() {
+        assert!(
+            lint("Icon=mail\nCategories=Network;Office;\nKeywords=mail;\nTryExec=/bin/sh\n")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn missing_keys_and_other_types_are_errors() {
+        let issues = lint("Type=Link\nName=\n");
+
+        assert!(issues.contains(&(LintLevel::Error, Key::Name)));
+        assert!(issues.contains(&(LintLevel::Error, Key::Type)));
+    }
+
+    #[test]
+    fn list_without_a_semicolon_is_a_warning() {
+        assert_eq!(
+            lint("Categories=Network;Office\n"),
+            vec![(LintLevel::Warning, Key::Categories)]
+        );
+    }
+
+    #[test]
+    fn more_than_one_file_field_code_is_an_error() {
+        assert!(lint("Exec=browser %u %F\n").contains(&(LintLevel::Error, Key::Exec)));
+        assert!(lint("Exec=browser --app=https://example.com/100%% %u\n").is_empty());
+    }
+
+    #[test]
+    fn invalid_exec_is_an_error() {
+        assert_eq!(
+            lint("Exec=browser %z\n"),
+            vec![(LintLevel::Error, Key::Exec)]
+        );
+    }
+
+    #[test]
+    fn icons_are_a_name_or_an_existing_absolute_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let icon_path = dir.path().join("mail.png");
+        fs::write(&icon_path, b"").unwrap();
+
+        assert!(lint(&format!("Icon={}\n", icon_path.display())).is_empty());
+        assert_eq!(
+            lint(&format!(
+                "Icon={}\n",
+                dir.path().join("missing.png").display()
+            )),
+            vec![(LintLevel::Warning, Key::Icon)]
+        );
+        assert_eq!(
+            lint("Icon=icons/mail\n"),
+            vec![(LintLevel::Error, Key::Icon)]
+        );
+        assert_eq!(
+            lint("Icon=mail.PNG\n"),
+            vec![(LintLevel::Warning, Key::Icon)]
+        );
+    }
+
+    #[test]
+    fn missing_try_exec_is_a_warning() {
+        assert_eq!(
+            lint("TryExec=/nonexistent/browser\n"),
+            vec![(LintLevel::Warning, Key::TryExec)]
+        );
+    }
+
+    #[test]
+    fn control_characters_are_errors() {
+        assert_eq!(
+            lint("Comment=Work\tmail\n"),
+            vec![(LintLevel::Error, Key::Comment)]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::desktop_file::{DesktopFile, error::DesktopFileError, key::Key};
+use semver::Version;
+use std::path::{Path, PathBuf};
+
+/// Keys and files a migration has touched
+#[derive(Debug, Default, Clone)]
+pub struct ChangeSet {
+    pub keys: Vec<String>,
+    pub files: Vec<PathBuf>,
+}
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.files.is_empty()
+    }
+}
+
+type MigrationFn = fn(&mut DesktopFile) -> Result<ChangeSet, DesktopFileError>;
+
+pub struct Migration {
+    /// Translation key of the note shown to users
+    pub description: &'static str,
+    /// Only migrate desktop files older than this version, `None` runs on every update
+    pub before_version: Option<Version>,
+    run: MigrationFn,
+}
+impl Migration {
+    pub fn applies_to(&self, desktop_file_version: &Version) -> bool {
+        self.before_version
+            .as_ref()
+            .is_none_or(|before_version| desktop_file_version < before_version)
+    }
+
+    pub fn run(&self, desktop_file: &mut DesktopFile) -> Result<ChangeSet, DesktopFileError> {
+        (self.run)(desktop_file)
+    }
+}
+
+/// A migration that has changed something on a desktop file
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    pub description: &'static str,
+    pub change_set: ChangeSet,
+}
+
+/// Migration reports of one web app
+#[derive(Debug, Clone)]
+pub struct AppUpgradeNotes {
+    pub app_name: String,
+    pub migration_reports: Vec<MigrationReport>,
+}
+
+/// Renames follow the id and are not tied to a version, so they are not in the migration list
+pub fn get_rename_report(path: &Path, renamed_path: &Path) -> MigrationReport {
+    MigrationReport {
+        description: "upgrade_notes.migrations.file_name",
+        change_set: ChangeSet {
+            keys: Vec::new(),
+            files: vec![path.to_path_buf(), renamed_path.to_path_buf()],
+        },
+    }
+}
+
+/// All migrations in the order they are applied
+pub fn get_migrations() -> Vec<Migration> {
+    vec![
+        Migration {
+            description: "upgrade_notes.migrations.profile_config",
+            before_version: None,
+            run: update_profile_config,
+        },
+        Migration {
+            description: "upgrade_notes.migrations.try_exec",
+            before_version: None,
+            run: add_try_exec,
+        },
+    ]
+}
+
+fn update_profile_config(desktop_file: &mut DesktopFile) -> Result<ChangeSet, DesktopFileError> {
+    let mut change_set = ChangeSet::default();
+
+    if desktop_file.get_isolated().is_some()
+        && let Some(profile_path) = desktop_file.get_profile_path()
+    {
+        desktop_file.copy_profile_config_to_profile_path(&profile_path)?;
+        change_set.files.push(profile_path);
+    }
+
+    Ok(change_set)
+}
+
+/// Launchers hide the app when the browser is gone, older files don't have the key
+fn add_try_exec(desktop_file: &mut DesktopFile) -> Result<ChangeSet, DesktopFileError> {
+    let mut change_set = ChangeSet::default();
+
+    if desktop_file.get_typed::<String>(Key::TryExec).is_none()
+        && let Some(try_exec) = desktop_file
+            .get_browser()
+            .and_then(|browser| browser.get_try_exec())
+    {
+        desktop_file.set_typed(Key::TryExec, &try_exec);
+        change_set.keys.push(Key::TryExec.to_string());
+    }
+
+    Ok(change_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app_dirs::AppDirs, browsers::BrowserConfigs, config};
+    use std::rc::Rc;
+
+    /// The browser of the file is not installed
+    fn build_desktop_file() -> DesktopFile {
+        config::init();
+        let app_dirs = Rc::new(AppDirs::default());
+        let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+        let mut desktop_file = DesktopFile::from_string(
+            Path::new("/tmp/mail.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Mail\n",
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+        desktop_file.set_typed(Key::BrowserId, "org.mozilla.firefox");
+        desktop_file.set_profile_path(Path::new("/tmp/profile"));
+
+        desktop_file
+    }
+
+    fn build_migration(before_version: Option<Version>) -> Migration {
+        Migration {
+            description: "upgrade_notes.migrations.test",
+            before_version,
+            run: |_| Ok(ChangeSet::default()),
+        }
+    }
+
+    #[test]
+    fn migration_without_version_applies_to_every_file() {
+        let migration = build_migration(None);
+
+        assert!(migration.applies_to(&Version::new(0, 1, 0)));
+        assert!(migration.applies_to(&Version::new(99, 0, 0)));
+    }
+
+    #[test]
+    fn migration_applies_only_to_older_files() {
+        let migration = build_migration(Some(Version::new(1, 2, 0)));
+
+        assert!(migration.applies_to(&Version::new(1, 1, 9)));
+        assert!(!migration.applies_to(&Version::new(1, 2, 0)));
+        assert!(!migration.applies_to(&Version::new(2, 0, 0)));
+    }
+
+    #[test]
+    fn change_set_with_keys_or_files_is_not_empty() {
+        assert!(ChangeSet::default().is_empty());
+        assert!(
+            !ChangeSet {
+                keys: vec!["TryExec".to_string()],
+                files: Vec::new(),
+            }
+            .is_empty()
+        );
+        assert!(
+            !ChangeSet {
+                keys: Vec::new(),
+                files: vec![PathBuf::from("/tmp/profile")],
+            }
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn rename_report_has_the_old_and_new_path() {
+        let report =
+            get_rename_report(Path::new("/tmp/old.desktop"), Path::new("/tmp/new.desktop"));
+
+        assert_eq!(report.description, "upgrade_notes.migrations.file_name");
+        assert!(report.change_set.keys.is_empty());
+        assert_eq!(
+            report.change_set.files,
+            vec![
+                PathBuf::from("/tmp/old.desktop"),
+                PathBuf::from("/tmp/new.desktop")
+            ]
+        );
+    }
+
+    #[test]
+    fn profile_config_is_migrated_before_try_exec() {
+        let descriptions: Vec<_> = get_migrations()
+            .iter()
+            .map(|migration| migration.description)
+            .collect();
+
+        assert_eq!(
+            descriptions,
+            vec![
+                "upgrade_notes.migrations.profile_config",
+                "upgrade_notes.migrations.try_exec"
+            ]
+        );
+    }
+
+    #[test]
+    fn existing_try_exec_is_kept() {
+        let mut desktop_file = build_desktop_file();
+        desktop_file.set_typed(Key::TryExec, "firefox");
+
+        let change_set = add_try_exec(&mut desktop_file).unwrap();
+
+        assert!(change_set.is_empty());
+        assert_eq!(
+            desktop_file.get_typed::<String>(Key::TryExec).as_deref(),
+            Some("firefox")
+        );
+    }
+
+    #[test]
+    fn try_exec_is_not_added_without_a_browser() {
+        let mut desktop_file = build_desktop_file();
+
+        let change_set = add_try_exec(&mut desktop_file).unwrap();
+
+        assert!(change_set.is_empty());
+        assert!(desktop_file.get_typed::<String>(Key::TryExec).is_none());
+    }
+
+    #[test]
+    fn shared_profile_has_no_profile_config_to_migrate() {
+        let mut desktop_file = build_desktop_file();
+
+        let change_set = update_profile_config(&mut desktop_file).unwrap();
+
+        assert!(change_set.is_empty());
+    }
+
+    #[test]
+    fn isolated_profile_without_a_browser_fails() {
+        let mut desktop_file = build_desktop_file();
+        desktop_file.set_isolated(true);
+
+        assert!(update_profile_config(&mut desktop_file).is_err());
+    }
+}
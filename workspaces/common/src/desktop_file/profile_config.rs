@@ -0,0 +1,328 @@
+use crate::{desktop_file::DesktopFile, utils::files};
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use std::{fs, path::Path};
+use tracing::debug;
+
+/// First line of every shipped profile file, without it the user changed the file
+const MANAGED_MARKER: &str = "Managed by Web App Hub";
+/// Hides the tab and url bar, left out for apps with the full browser UI
+const MINIMAL_UI_DIR: &str = "chrome";
+
+/// Shipped profile files are updated on every app update, files of the user are left alone
+impl DesktopFile {
+    pub(super) fn copy_managed_profile_config(
+        &self,
+        config_path: &Path,
+        profile_path: &Path,
+    ) -> Result<()> {
+        let is_minimal_ui = self.get_minimal_ui().unwrap_or(true);
+
+        for entry in files::get_entries_in_dir(config_path)? {
+            let source_path = entry.path();
+            let target_path = profile_path.join(entry.file_name());
+
+            if !is_minimal_ui && entry.file_name() == MINIMAL_UI_DIR {
+                remove_managed_files(&target_path)?;
+                continue;
+            }
+            copy_managed(&source_path, &target_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Skips the welcome, sync and default browser prompts of a new profile.
+    /// Values from a user profile config in `Default/Preferences` win.
+    pub(super) fn seed_chromium_profile(&self, profile_path: &Path) -> Result<()> {
+        let first_run_path = profile_path.join("First Run");
+        if !first_run_path.exists() {
+            fs::write(&first_run_path, b"").context(format!(
+                "Failed to create first run file: {}",
+                first_run_path.display()
+            ))?;
+        }
+
+        let preferences_path = profile_path.join("Default").join("Preferences");
+        let mut preferences = match fs::read_to_string(&preferences_path) {
+            Ok(contents) => serde_json::from_str(&contents).context(format!(
+                "Failed to parse preferences: {}",
+                preferences_path.display()
+            ))?,
+            Err(_) => Value::Object(Map::new()),
+        };
+        merge_missing(&mut preferences, self.build_chromium_preferences());
+
+        if let Some(dir) = preferences_path.parent() {
+            fs::create_dir_all(dir)
+                .context(format!("Failed to create profile dir: {}", dir.display()))?;
+        }
+        files::write_atomic(
+            &preferences_path,
+            serde_json::to_string_pretty(&preferences)?.as_bytes(),
+        )?;
+        debug!(path = %preferences_path.display(), "Seeded Chromium preferences");
+
+        Ok(())
+    }
+
+    fn build_chromium_preferences(&self) -> Value {
+        let mut preferences = json!({
+            "browser": {
+                "check_default_browser": false,
+                "has_seen_welcome_page": true,
+            },
+            "sync_promo": {
+                "show_on_first_run_allowed": false,
+            },
+        });
+
+        // A new app can get its profile before the url is filled in
+        if let Some(url) = self.get_url().filter(|url| !url.is_empty())
+            && let Some(preferences) = preferences.as_object_mut()
+        {
+            preferences.insert("homepage".to_string(), Value::String(url));
+            preferences.insert("homepage_is_newtabpage".to_string(), Value::Bool(false));
+        }
+
+        preferences
+    }
+}
+
+/// Adds the keys `target` does not have yet, nested objects are merged too
+fn merge_missing(target: &mut Value, defaults: Value) {
+    let (Some(target), Value::Object(defaults)) = (target.as_object_mut(), defaults) else {
+        return;
+    };
+
+    for (key, default) in defaults {
+        match target.get_mut(&key) {
+            Some(value) if value.is_object() => merge_missing(value, default),
+            Some(_) => {}
+            None => {
+                target.insert(key, default);
+            }
+        }
+    }
+}
+
+fn copy_managed(source_path: &Path, target_path: &Path) -> Result<()> {
+    if source_path.is_dir() {
+        fs::create_dir_all(target_path).context(format!(
+            "Failed to create profile config dir: {}",
+            target_path.display()
+        ))?;
+        for entry in files::get_entries_in_dir(source_path)? {
+            copy_managed(&entry.path(), &target_path.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(source_path).context(format!(
+        "Failed to read profile config file: {}",
+        source_path.display()
+    ))?;
+    if target_path.exists()
+        && !is_managed(target_path)
+        && !is_copy_without_marker(target_path, &contents)
+    {
+        debug!(
+            path = %target_path.display(),
+            "Keeping profile config file changed by the user"
+        );
+        return Ok(());
+    }
+
+    files::write_atomic(target_path, contents.as_bytes())
+}
+
+fn remove_managed_files(dir: &Path) -> Result<()> {
+    for entry in files::get_entries_in_dir(dir).unwrap_or_default() {
+        let path = entry.path();
+        if path.is_file() && is_managed(&path) {
+            debug!(path = %path.display(), "Removing profile config file");
+            fs::remove_file(&path).context(format!(
+                "Failed to remove profile config file: {}",
+                path.display()
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copied before the marker existed, the user did not add anything when every line is still shipped
+fn is_copy_without_marker(path: &Path, shipped_contents: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| {
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .all(|line| {
+                shipped_contents
+                    .lines()
+                    .any(|shipped_line| shipped_line == line)
+            })
+    })
+}
+
+fn is_managed(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| {
+        contents
+            .lines()
+            .next()
+            .is_some_and(|line| line.contains(MANAGED_MARKER))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app_dirs::AppDirs, browsers::BrowserConfigs, config};
+    use std::rc::Rc;
+
+    fn build_desktop_file() -> DesktopFile {
+        config::init();
+        let app_dirs = Rc::new(AppDirs::default());
+        let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+
+        DesktopFile::from_string(
+            Path::new("/tmp/mail.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Mail\n",
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap()
+    }
+
+    fn read_preferences(profile_path: &Path) -> Value {
+        let contents =
+            fs::read_to_string(profile_path.join("Default").join("Preferences")).unwrap();
+        serde_json::from_str(&contents).unwrap()
+    }
+
+    #[test]
+    fn missing_keys_are_merged_and_existing_values_win() {
+        let mut target = json!({ "browser": { "theme": "dark", "check_default_browser": true } });
+
+        merge_missing(
+            &mut target,
+            json!({
+                "browser": { "check_default_browser": false, "has_seen_welcome_page": true },
+                "homepage": "https://example.com/",
+            }),
+        );
+
+        assert_eq!(
+            target,
+            json!({
+                "browser": {
+                    "theme": "dark",
+                    "check_default_browser": true,
+                    "has_seen_welcome_page": true,
+                },
+                "homepage": "https://example.com/",
+            })
+        );
+    }
+
+    #[test]
+    fn new_chromium_profile_skips_the_first_run_prompts() {
+        let profile = tempfile::tempdir().unwrap();
+        let mut desktop_file = build_desktop_file();
+        desktop_file.set_url("https://mail.example.com/");
+
+        desktop_file.seed_chromium_profile(profile.path()).unwrap();
+
+        assert!(profile.path().join("First Run").is_file());
+        let preferences = read_preferences(profile.path());
+        assert_eq!(preferences["browser"]["check_default_browser"], false);
+        assert_eq!(preferences["browser"]["has_seen_welcome_page"], true);
+        assert_eq!(
+            preferences["sync_promo"]["show_on_first_run_allowed"],
+            false
+        );
+        assert_eq!(preferences["homepage"], "https://mail.example.com/");
+        assert_eq!(preferences["homepage_is_newtabpage"], false);
+    }
+
+    #[test]
+    fn user_preferences_are_kept_when_seeding() {
+        let profile = tempfile::tempdir().unwrap();
+        let default_dir = profile.path().join("Default");
+        fs::create_dir_all(&default_dir).unwrap();
+        fs::write(
+            default_dir.join("Preferences"),
+            r#"{ "browser": { "check_default_browser": true }, "homepage": "https://example.com/" }"#,
+        )
+        .unwrap();
+        let mut desktop_file = build_desktop_file();
+        desktop_file.set_url("https://mail.example.com/");
+
+        desktop_file.seed_chromium_profile(profile.path()).unwrap();
+
+        let preferences = read_preferences(profile.path());
+        assert_eq!(preferences["browser"]["check_default_browser"], true);
+        assert_eq!(preferences["browser"]["has_seen_welcome_page"], true);
+        assert_eq!(preferences["homepage"], "https://example.com/");
+    }
+
+    #[test]
+    fn app_without_url_gets_no_homepage() {
+        let profile = tempfile::tempdir().unwrap();
+
+        build_desktop_file()
+            .seed_chromium_profile(profile.path())
+            .unwrap();
+
+        assert!(read_preferences(profile.path()).get("homepage").is_none());
+    }
+
+    #[test]
+    fn managed_files_are_updated_and_user_files_are_kept() {
+        let config = tempfile::tempdir().unwrap();
+        let profile = tempfile::tempdir().unwrap();
+        let shipped = format!("// {MANAGED_MARKER}\nuser_pref(\"a\", true);\n");
+        for file_name in ["managed.js", "old.js", "user.js"] {
+            fs::write(config.path().join(file_name), &shipped).unwrap();
+        }
+        fs::write(
+            profile.path().join("managed.js"),
+            format!("// {MANAGED_MARKER}\nuser_pref(\"a\", false);\n"),
+        )
+        .unwrap();
+        fs::write(profile.path().join("old.js"), "user_pref(\"a\", true);\n").unwrap();
+        fs::write(profile.path().join("user.js"), "user_pref(\"b\", true);\n").unwrap();
+
+        build_desktop_file()
+            .copy_managed_profile_config(config.path(), profile.path())
+            .unwrap();
+
+        let read = |file_name: &str| fs::read_to_string(profile.path().join(file_name)).unwrap();
+        assert_eq!(read("managed.js"), shipped);
+        assert_eq!(read("old.js"), shipped);
+        assert_eq!(read("user.js"), "user_pref(\"b\", true);\n");
+    }
+
+    #[test]
+    fn full_browser_ui_removes_the_managed_minimal_ui_files() {
+        let config = tempfile::tempdir().unwrap();
+        let profile = tempfile::tempdir().unwrap();
+        let minimal_ui_config = config.path().join(MINIMAL_UI_DIR);
+        let minimal_ui_profile = profile.path().join(MINIMAL_UI_DIR);
+        fs::create_dir_all(&minimal_ui_config).unwrap();
+        fs::create_dir_all(&minimal_ui_profile).unwrap();
+        let shipped = format!("/* {MANAGED_MARKER} */\n#nav-bar {{ display: none; }}\n");
+        fs::write(minimal_ui_config.join("userChrome.css"), &shipped).unwrap();
+        fs::write(minimal_ui_profile.join("userChrome.css"), &shipped).unwrap();
+        fs::write(minimal_ui_profile.join("user.css"), "#tabs {}\n").unwrap();
+        let mut desktop_file = build_desktop_file();
+        desktop_file.set_minimal_ui(false);
+
+        desktop_file
+            .copy_managed_profile_config(config.path(), profile.path())
+            .unwrap();
+
+        assert!(!minimal_ui_profile.join("userChrome.css").exists());
+        assert!(minimal_ui_profile.join("user.css").exists());
+    }
+}
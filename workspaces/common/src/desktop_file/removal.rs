@@ -0,0 +1,267 @@
+use crate::utils::files;
+use anyhow::{Context, Result};
+use gtk::{
+    gio::{self, prelude::FileExt},
+    glib,
+};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, info};
+
+/// What happened to a file of a deleted web app
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeleteOutcome {
+    /// In the trash of the desktop, the user can restore it from the file manager
+    Trashed,
+    /// Gone for good, the filesystem has no trash
+    Removed,
+    /// The profile stays on disk as a detached profile
+    Kept,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    DesktopFile,
+    Icon,
+    /// The PNG next to an SVG icon
+    RasterizedIcon,
+    Profile,
+}
+impl Display for ItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::DesktopFile => write!(f, "desktop file"),
+            Self::Icon => write!(f, "icon"),
+            Self::RasterizedIcon => write!(f, "rasterized icon"),
+            Self::Profile => write!(f, "profile"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DeletedItem {
+    pub kind: ItemKind,
+    pub path: PathBuf,
+    pub outcome: Result<DeleteOutcome>,
+}
+
+/// Every file a delete touched, files that did not exist are left out
+#[derive(Debug, Default)]
+pub struct DeleteReport {
+    pub items: Vec<DeletedItem>,
+}
+impl DeleteReport {
+    pub(super) fn add(&mut self, kind: ItemKind, path: PathBuf, outcome: Result<DeleteOutcome>) {
+        self.items.push(DeletedItem {
+            kind,
+            path,
+            outcome,
+        });
+    }
+
+    pub fn get_failed(&self) -> impl Iterator<Item = &DeletedItem> {
+        self.items.iter().filter(|item| item.outcome.is_err())
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.get_failed().next().is_some()
+    }
+}
+
+/// Moves a file or dir to the trash. Network mounts and some sandboxes have no trash, there it
+/// is removed for good instead.
+pub fn trash_or_remove(path: &Path) -> Result<DeleteOutcome> {
+    trash_or_remove_with(path, |path| {
+        gio::File::for_path(path).trash(gio::Cancellable::NONE)
+    })
+}
+
+fn trash_or_remove_with(
+    path: &Path,
+    trash: impl FnOnce(&Path) -> Result<(), glib::Error>,
+) -> Result<DeleteOutcome> {
+    match trash(path) {
+        Ok(()) => Ok(DeleteOutcome::Trashed),
+        Err(error) if error.matches(gio::IOErrorEnum::NotSupported) => {
+            debug!(
+                path = %path.display(),
+                "No trash for this path, removing it: {error}"
+            );
+            remove(path)?;
+            Ok(DeleteOutcome::Removed)
+        }
+        Err(error) => Err(anyhow::Error::new(error)
+            .context(format!("Failed to move to trash: '{}'", path.display()))),
+    }
+}
+
+/// Removes icons whose id is not in `kept_ids`, left behind by web apps that were never saved
+/// or have been removed. Returns the removed paths.
+pub fn remove_orphaned_icons(icons_dir: &Path, kept_ids: &HashSet<String>) -> Vec<PathBuf> {
+    let mut removed_paths = Vec::new();
+
+    for file in files::get_entries_in_dir(icons_dir).unwrap_or_default() {
+        let path = file.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        let Some(id) = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        if kept_ids.contains(&id) {
+            continue;
+        }
+
+        info!(path = %path.display(), "Removing orphaned icon");
+        match fs::remove_file(&path) {
+            Ok(()) => removed_paths.push(path),
+            Err(error) => error!(path = %path.display(), ?error, "Failed to remove orphaned icon"),
+        }
+    }
+
+    removed_paths
+}
+
+fn remove(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .context(format!("Failed to remove: '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_icon(icons_dir: &Path, file_name: &str) {
+        fs::write(icons_dir.join(file_name), b"icon").unwrap();
+    }
+
+    fn no_trash(_: &Path) -> Result<(), glib::Error> {
+        Err(glib::Error::new(gio::IOErrorEnum::NotSupported, "No trash"))
+    }
+
+    /// Like the profile dirs of flatpak browsers, in the sandbox area of another app
+    #[test]
+    fn without_trash_a_dir_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile_path = dir.path().join("profile");
+        fs::create_dir_all(profile_path.join("Default")).unwrap();
+        fs::write(profile_path.join("Default").join("Preferences"), b"{}").unwrap();
+
+        let outcome = trash_or_remove_with(&profile_path, no_trash).unwrap();
+
+        assert_eq!(outcome, DeleteOutcome::Removed);
+        assert!(!profile_path.exists());
+    }
+
+    #[test]
+    fn without_trash_a_file_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.desktop");
+        fs::write(&path, b"[Desktop Entry]").unwrap();
+
+        let outcome = trash_or_remove_with(&path, no_trash).unwrap();
+
+        assert_eq!(outcome, DeleteOutcome::Removed);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn trashed_path_is_not_removed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.desktop");
+        fs::write(&path, b"[Desktop Entry]").unwrap();
+
+        let outcome = trash_or_remove_with(&path, |_| Ok(())).unwrap();
+
+        assert_eq!(outcome, DeleteOutcome::Trashed);
+        assert!(path.is_file());
+    }
+
+    /// Only a missing trash falls back, other errors keep the file
+    #[test]
+    fn failed_trash_keeps_the_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.desktop");
+        fs::write(&path, b"[Desktop Entry]").unwrap();
+
+        let result = trash_or_remove_with(&path, |_| {
+            Err(glib::Error::new(
+                gio::IOErrorEnum::PermissionDenied,
+                "Permission denied",
+            ))
+        });
+
+        assert!(result.is_err());
+        assert!(path.is_file());
+    }
+
+    #[test]
+    fn orphaned_icons_are_removed() {
+        let icons_dir = tempfile::tempdir().unwrap();
+        write_icon(icons_dir.path(), "saved.png");
+        write_icon(icons_dir.path(), "never-saved.png");
+        let kept_ids = HashSet::from(["saved".to_string()]);
+
+        let removed_paths = remove_orphaned_icons(icons_dir.path(), &kept_ids);
+
+        assert_eq!(removed_paths, [icons_dir.path().join("never-saved.png")]);
+        assert!(icons_dir.path().join("saved.png").is_file());
+    }
+
+    /// A new app that is still being edited has no desktop file yet, its fetched icon stays
+    #[test]
+    fn icons_of_open_editors_are_kept() {
+        let icons_dir = tempfile::tempdir().unwrap();
+        write_icon(icons_dir.path(), "editing.svg");
+        write_icon(icons_dir.path(), "editing.png");
+        let kept_ids = HashSet::from(["editing".to_string()]);
+
+        assert!(remove_orphaned_icons(icons_dir.path(), &kept_ids).is_empty());
+        assert!(icons_dir.path().join("editing.svg").is_file());
+        assert!(icons_dir.path().join("editing.png").is_file());
+    }
+
+    /// An icon saved by a fetch that finished after its editor closed is removed on the next sweep
+    #[test]
+    fn late_icon_of_a_closed_editor_is_removed() {
+        let icons_dir = tempfile::tempdir().unwrap();
+        write_icon(icons_dir.path(), "closed.png");
+
+        remove_orphaned_icons(icons_dir.path(), &HashSet::new());
+
+        assert!(is_empty_dir(icons_dir.path()));
+    }
+
+    #[test]
+    fn dirs_are_left_alone() {
+        let icons_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(icons_dir.path().join("sub-dir")).unwrap();
+
+        assert!(remove_orphaned_icons(icons_dir.path(), &HashSet::new()).is_empty());
+        assert!(icons_dir.path().join("sub-dir").is_dir());
+    }
+
+    #[test]
+    fn missing_dir_removes_nothing() {
+        let icons_dir = tempfile::tempdir().unwrap();
+        let missing_dir = icons_dir.path().join("missing");
+
+        assert!(remove_orphaned_icons(&missing_dir, &HashSet::new()).is_empty());
+    }
+
+    fn is_empty_dir(dir: &Path) -> bool {
+        fs::read_dir(dir).unwrap().next().is_none()
+    }
+}
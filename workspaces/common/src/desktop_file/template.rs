@@ -0,0 +1,269 @@
+use crate::desktop_file::utils;
+use std::collections::HashMap;
+
+/// What a placeholder of a browser desktop file becomes, `None` in the map is off
+#[derive(Clone)]
+pub enum Replacement {
+    /// `%{key}` becomes the text, already escaped by the caller
+    Text(String),
+    /// `%{key ? flags}` becomes the flags
+    Flag,
+    /// `%{key ? flag}` becomes `flag=value` as one argument, a path can have spaces
+    FlagValue(String),
+    /// `%{key ? flag}` becomes `flag=W,H`, `%{key ? width_flag height_flag}` becomes
+    /// `width_flag W height_flag H`
+    Size(u32, u32),
+}
+
+/// What happens to a placeholder with a key that is not in the map
+#[derive(Clone, Copy, PartialEq)]
+pub enum UnknownKeys {
+    /// Left as is, so a typo in a browser config shows up in the file
+    Keep,
+    Strip,
+}
+
+/// Replaces every `%{key}` and `%{key ? flags}` in one pass, so a replaced value is never read
+/// as a placeholder. Flags can contain braces, an unclosed placeholder is left as is. A `%%{`
+/// is an escaped percent of the desktop file followed by a brace, not a placeholder.
+pub fn render(
+    template: &str,
+    replacements: &HashMap<&str, Option<Replacement>>,
+    unknown_keys: UnknownKeys,
+) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = find_placeholder(rest) {
+        rendered.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+
+        let Some(end) = find_closing_brace(placeholder) else {
+            rendered.push_str(placeholder);
+            return rendered;
+        };
+        let (key, flags) = match placeholder[2..end].split_once('?') {
+            Some((key, flags)) => (key.trim(), Some(flags.trim())),
+            None => (placeholder[2..end].trim(), None),
+        };
+
+        match replacements.get(key) {
+            Some(replacement) => {
+                rendered.push_str(&render_placeholder(replacement.as_ref(), flags));
+            }
+            None if unknown_keys == UnknownKeys::Keep => rendered.push_str(&placeholder[..=end]),
+            None => {}
+        }
+        rest = &placeholder[end + 1..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+fn render_placeholder(replacement: Option<&Replacement>, flags: Option<&str>) -> String {
+    let Some(replacement) = replacement else {
+        return String::new();
+    };
+
+    match (replacement, flags) {
+        (Replacement::Text(text), None) => text.clone(),
+        (Replacement::Flag | Replacement::Text(_), Some(flags)) => flags.to_string(),
+        (Replacement::FlagValue(value), Some(flag)) => {
+            utils::escape_value(&utils::escape_exec_arg(&format!("{flag}={value}")))
+        }
+        (Replacement::Size(width, height), Some(flags)) => {
+            match flags.split_whitespace().collect::<Vec<&str>>().as_slice() {
+                [width_flag, height_flag] => {
+                    format!("{width_flag} {width} {height_flag} {height}")
+                }
+                [flag] => format!("{}={width},{height}", flag.trim_end_matches('=')),
+                _ => String::new(),
+            }
+        }
+        // A conditional without flags has nothing to add
+        (Replacement::Flag | Replacement::FlagValue(_) | Replacement::Size(..), None) => {
+            String::new()
+        }
+    }
+}
+
+/// Index of the first `%{` that is not part of a `%%`
+fn find_placeholder(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut index = 0;
+
+    while index + 1 < bytes.len() {
+        match (bytes[index], bytes[index + 1]) {
+            (b'%', b'{') => return Some(index),
+            (b'%', b'%') => index += 2,
+            _ => index += 1,
+        }
+    }
+
+    None
+}
+
+/// Index of the `}` that closes the `%{` at the start, braces inside are counted
+fn find_closing_brace(placeholder: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (index, char) in placeholder.char_indices().skip(1) {
+        match char {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Keys of every placeholder a browser desktop file can use
+pub const KNOWN_KEYS: &[&str] = &[
+    "command",
+    "name",
+    "url",
+    "domain",
+    "domain_path",
+    "icon",
+    "app_id",
+    "is_isolated",
+    "is_maximized",
+    "is_fullscreen",
+    "is_force_dark",
+    "is_private",
+    "window_size",
+];
+
+/// Keys of placeholders that are not in `KNOWN_KEYS`, each key once in order of appearance
+pub fn get_unknown_keys(template: &str) -> Vec<String> {
+    let mut unknown_keys: Vec<String> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = find_placeholder(rest) {
+        let placeholder = &rest[start..];
+        let Some(end) = find_closing_brace(placeholder) else {
+            break;
+        };
+        let key = placeholder[2..end]
+            .split_once('?')
+            .map_or(&placeholder[2..end], |(key, _)| key)
+            .trim();
+
+        if !KNOWN_KEYS.contains(&key) && !unknown_keys.iter().any(|unknown| unknown == key) {
+            unknown_keys.push(key.to_string());
+        }
+        rest = &placeholder[end + 1..];
+    }
+
+    unknown_keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_replacements() -> HashMap<&'static str, Option<Replacement>> {
+        HashMap::from([
+            ("name", Some(Replacement::Text("Mail".to_string()))),
+            (
+                "url",
+                Some(Replacement::Text("https://example.com".to_string())),
+            ),
+            ("is_isolated", Some(Replacement::Flag)),
+            ("is_private", None),
+            ("window_size", Some(Replacement::Size(800, 600))),
+        ])
+    }
+
+    fn render_keep(template: &str) -> String {
+        render(template, &get_replacements(), UnknownKeys::Keep)
+    }
+
+    #[test]
+    fn text_and_flags_are_replaced() {
+        assert_eq!(
+            render_keep("browser %{is_isolated ? --isolated} %{is_private ? --private} %{url}"),
+            "browser --isolated  https://example.com"
+        );
+    }
+
+    #[test]
+    fn size_is_rendered_in_both_forms() {
+        assert_eq!(
+            render_keep("%{window_size ? --window-size=}"),
+            "--window-size=800,600"
+        );
+        assert_eq!(
+            render_keep("%{window_size ? --width --height}"),
+            "--width 800 --height 600"
+        );
+    }
+
+    #[test]
+    fn nested_braces_are_part_of_the_flags() {
+        assert_eq!(
+            render_keep(r#"%{is_isolated ? --prefs={"a":{"b":1}}} %{name}"#),
+            r#"--prefs={"a":{"b":1}} Mail"#
+        );
+    }
+
+    #[test]
+    fn replaced_value_is_not_read_as_a_placeholder() {
+        let replacements = HashMap::from([("name", Some(Replacement::Text("%{url}".to_string())))]);
+
+        assert_eq!(
+            render("%{name}", &replacements, UnknownKeys::Strip),
+            "%{url}"
+        );
+    }
+
+    #[test]
+    fn unclosed_placeholder_is_left_as_is() {
+        assert_eq!(render_keep("%{name} %{url"), "Mail %{url");
+        assert_eq!(
+            render_keep("%{is_isolated ? --prefs={} %{name}"),
+            "%{is_isolated ? --prefs={} %{name}"
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_kept_or_stripped() {
+        let template = "browser %{typo ? --flag} %{name}";
+
+        assert_eq!(render_keep(template), "browser %{typo ? --flag} Mail");
+        assert_eq!(
+            render(template, &get_replacements(), UnknownKeys::Strip),
+            "browser  Mail"
+        );
+    }
+
+    #[test]
+    fn escaped_percent_is_not_a_placeholder() {
+        assert_eq!(render_keep("%%{name} %{name}"), "%%{name} Mail");
+        assert_eq!(render_keep("%%%{name}"), "%%Mail");
+        assert_eq!(render_keep("100%% {name}"), "100%% {name}");
+    }
+
+    #[test]
+    fn closing_brace_counts_nested_braces() {
+        assert_eq!(find_closing_brace("%{name}"), Some(6));
+        assert_eq!(find_closing_brace("%{a ? {b}} rest"), Some(9));
+        assert_eq!(find_closing_brace("%{a ? {b}"), None);
+        assert_eq!(find_closing_brace("%{"), None);
+    }
+
+    #[test]
+    fn unknown_keys_are_listed_once_in_order() {
+        assert_eq!(
+            get_unknown_keys("%{typo} %{url} %{other ? --x} %{typo} %%{escaped} %{unclosed"),
+            vec!["typo".to_string(), "other".to_string()]
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::Browser,
+    config::{self},
+    desktop_file::{
+        DesktopFile, DesktopFileEntries,
+        template::{self, UnknownKeys},
+    },
+    utils::OnceLockExt,
+};
+use freedesktop_desktop_entry::DesktopEntry;
+use semver::Version;
+use std::rc::Rc;
+
+/// A browser desktop file rendered for an example app, to check a template before saving it
+#[derive(Debug, Clone)]
+pub struct TemplatePreview {
+    pub rendered: String,
+    /// Placeholders that are not replaced, likely a typo
+    pub unknown_keys: Vec<String>,
+    /// The rendered file is not a valid desktop file
+    pub parse_error: Option<String>,
+}
+impl TemplatePreview {
+    pub fn is_valid(&self) -> bool {
+        self.unknown_keys.is_empty() && self.parse_error.is_none()
+    }
+}
+
+/// Same substitution as a new web app gets, with example entries instead of a saved app
+impl DesktopFile {
+    const PREVIEW_NAME: &str = "Example";
+    const PREVIEW_APP_ID: &str = "example";
+    const PREVIEW_DOMAIN: &str = "example.com";
+    const PREVIEW_URL_PATH: &str = "app";
+
+    pub fn preview_browser_template(
+        browser: &Rc<Browser>,
+        template: &str,
+        app_dirs: &AppDirs,
+    ) -> TemplatePreview {
+        let entries = DesktopFileEntries {
+            name: Self::PREVIEW_NAME.to_string(),
+            app_id: Self::PREVIEW_APP_ID.to_string(),
+            version: Version::new(0, 0, 0),
+            browser: browser.clone(),
+            url: format!(
+                "https://{}/{}",
+                Self::PREVIEW_DOMAIN,
+                Self::PREVIEW_URL_PATH
+            ),
+            url_path: Self::PREVIEW_URL_PATH.to_string(),
+            domain: Self::PREVIEW_DOMAIN.to_string(),
+            isolate: browser.can_isolate,
+            maximize: browser.can_start_maximized,
+            fullscreen: false,
+            private: false,
+            force_dark: false,
+            window_size: None,
+            icon_path: app_dirs
+                .app_data_icons
+                .join(format!("{}.png", Self::PREVIEW_APP_ID)),
+            profile_path: app_dirs.app_data_profiles.join(Self::PREVIEW_APP_ID),
+        };
+        let app_name_short = config::APP_NAME_SHORT.get_value();
+        let app_id = format!("{}-{}", app_name_short, entries.app_id);
+        // Not installed browsers still get a preview, the command is only an example
+        let command = browser
+            .get_run_command()
+            .unwrap_or_else(|_| browser.config_name.clone());
+
+        let mut browser_template = template.to_string();
+        let exec_url = Self::build_exec_url(&mut browser_template, None, &entries.url);
+        let replacements = Self::build_replacements(&entries, &command, &exec_url, app_id);
+        let rendered = template::render(&browser_template, &replacements, UnknownKeys::Keep);
+
+        let parse_error = match DesktopEntry::from_str(
+            app_dirs.user_applications.join("example.desktop"),
+            &rendered,
+            None::<&[String]>,
+        ) {
+            Ok(desktop_entry) if desktop_entry.exec().is_none() => {
+                Some("No 'Exec' in the 'Desktop Entry' group".to_string())
+            }
+            Ok(_) => None,
+            Err(error) => Some(error.to_string()),
+        };
+
+        TemplatePreview {
+            rendered,
+            unknown_keys: template::get_unknown_keys(template),
+            parse_error,
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{
+    app_dirs::AppDirs,
+    desktop_file::{
+        DeleteOptions, DesktopFile,
+        launcher_backend::{LauncherBackend, PortalBackend},
+        removal,
+    },
+    detached_profiles::DetachedProfiles,
+    utils::files,
+};
+use anyhow::{Context, Result, bail};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, info};
+
+/// A deleted web app with its files in a staging dir, until it is restored or purged.
+/// The launcher is gone right away, autostart and default handlers are undone as well.
+pub struct TrashedWebApp {
+    desktop_file: DesktopFile,
+    staging_dir: PathBuf,
+    /// Original path and the path in the staging dir
+    moved_files: Vec<(PathBuf, PathBuf)>,
+    detached_profile: Option<PathBuf>,
+    was_autostart: bool,
+    registered_mime_types: Vec<String>,
+}
+impl TrashedWebApp {
+    /// Holds the path of a profile that could not be moved, it is removed on purge
+    const PROFILE_PATH_FILE: &str = "profile-path";
+
+    pub fn get_name(&self) -> String {
+        self.desktop_file.get_name().unwrap_or_default()
+    }
+
+    pub fn get_staging_dir(&self) -> PathBuf {
+        self.staging_dir.clone()
+    }
+
+    /// Moves everything back, returns the desktop file as it was
+    pub fn restore(self) -> Result<DesktopFile> {
+        let mut is_error = false;
+
+        for (original_path, staged_path) in self.moved_files.iter().rev() {
+            if let Err(error) = move_path(staged_path, original_path) {
+                error!(
+                    path = %original_path.display(),
+                    "Failed to restore file: {error:?}"
+                );
+                is_error = true;
+            }
+        }
+
+        if self.desktop_file.get_launcher_id().is_some()
+            && let Err(error) = PortalBackend.save(&self.desktop_file)
+        {
+            error!("{error:?}");
+            is_error = true;
+        }
+        self.desktop_file.notify_launchers();
+
+        if let Some(profile_path) = &self.detached_profile {
+            let mut detached_profiles = DetachedProfiles::new(&self.desktop_file.app_dirs);
+            detached_profiles.remove(profile_path);
+            if let Err(error) = detached_profiles.save() {
+                error!("{error:?}");
+                is_error = true;
+            }
+        }
+
+        if self.was_autostart
+            && let Err(error) = self.desktop_file.enable_autostart()
+        {
+            error!("{error:?}");
+            is_error = true;
+        }
+
+        if !self.registered_mime_types.is_empty()
+            && let Err(error) = self
+                .desktop_file
+                .reregister_default_handlers(&self.registered_mime_types)
+        {
+            error!("{error:?}");
+            is_error = true;
+        }
+
+        if let Err(error) = fs::remove_dir_all(&self.staging_dir) {
+            error!(
+                path = %self.staging_dir.display(),
+                "Failed to remove staging dir: {error:?}"
+            );
+        }
+
+        if is_error {
+            bail!("Some files could not be restored, check logs")
+        }
+
+        info!("Restored web app: {}", self.get_name());
+        Ok(self.desktop_file)
+    }
+}
+
+impl DesktopFile {
+    /// Like `delete_with_options`, but the files can be restored until the trash is purged
+    pub fn move_to_trash(&self, options: &DeleteOptions) -> Result<TrashedWebApp> {
+        let id = self.get_id().context("No id on 'DesktopFile'")?;
+        let staging_dir = self.app_dirs.app_data_trash.join(&id);
+        if staging_dir.exists() {
+            purge_staging_dir(&staging_dir)?;
+        }
+        fs::create_dir_all(&staging_dir).context(format!(
+            "Failed to create staging dir: {}",
+            staging_dir.display()
+        ))?;
+
+        let was_autostart = self.is_autostart();
+        let registered_mime_types = self.get_registered_mime_types();
+        let mut trashed_web_app = TrashedWebApp {
+            desktop_file: self.clone(),
+            staging_dir,
+            moved_files: Vec::new(),
+            detached_profile: None,
+            was_autostart: false,
+            registered_mime_types: Vec::new(),
+        };
+
+        if let Err(error) = self.stage_files(&mut trashed_web_app, options) {
+            // Only the files are touched yet, moving them back loses nothing
+            let _ = trashed_web_app.restore();
+            return Err(error);
+        }
+
+        trashed_web_app.was_autostart = was_autostart;
+        trashed_web_app.registered_mime_types = registered_mime_types;
+        if let Err(error) = self.disable_autostart() {
+            error!("{error:?}");
+        }
+        self.restore_previous_handlers(&[]);
+        // The copy is staged, the launcher of the portal is only removed
+        if let Some(launcher_id) = self.get_launcher_id()
+            && let Err(error) = PortalBackend::uninstall_launcher(&launcher_id)
+        {
+            error!("{error:?}");
+        }
+        self.notify_launchers();
+
+        info!(
+            "Moved web app to trash: {}",
+            self.get_name().unwrap_or_default()
+        );
+        Ok(trashed_web_app)
+    }
+
+    fn stage_files(
+        &self,
+        trashed_web_app: &mut TrashedWebApp,
+        options: &DeleteOptions,
+    ) -> Result<()> {
+        let staging_dir = trashed_web_app.staging_dir.clone();
+        // Original names, the trash of the desktop shows them after the purge
+        let mut stage = |path: PathBuf| -> Result<()> {
+            let staged_path = staging_dir.join(path.file_name().unwrap_or_default());
+            move_path(&path, &staged_path)
+                .context(format!("Failed to move to trash: {}", path.display()))?;
+            trashed_web_app.moved_files.push((path, staged_path));
+            Ok(())
+        };
+
+        let desktop_file_path = self.get_path();
+        if desktop_file_path.is_file() {
+            stage(desktop_file_path)?;
+        }
+
+        if let Some(icon_path) = self.get_icon_path()
+            && icon_path.is_file()
+        {
+            // An SVG icon has a rasterized PNG next to it
+            let png_path = icon_path.with_extension("png");
+            if icon_path
+                .extension()
+                .is_some_and(|extension| extension == "svg")
+                && png_path.is_file()
+            {
+                stage(png_path)?;
+            }
+            stage(icon_path)?;
+        }
+
+        let Some(profile_path) = self
+            .get_profile_path()
+            .filter(|profile_path| profile_path.is_dir())
+        else {
+            return Ok(());
+        };
+
+        if !options.delete_profile {
+            self.detach_profile(&profile_path)?;
+            trashed_web_app.detached_profile = Some(profile_path);
+            return Ok(());
+        }
+
+        // Copying a large profile to another filesystem is slow, it is trashed in place instead
+        let staged_profile_path = staging_dir.join(profile_path.file_name().unwrap_or_default());
+        match move_path(&profile_path, &staged_profile_path) {
+            Ok(()) => {
+                trashed_web_app
+                    .moved_files
+                    .push((profile_path, staged_profile_path));
+            }
+            Err(error) if is_crosses_devices(&error) => {
+                debug!(
+                    path = %profile_path.display(),
+                    "Profile is on another filesystem, trashing it in place on purge"
+                );
+                fs::write(
+                    staging_dir.join(TrashedWebApp::PROFILE_PATH_FILE),
+                    profile_path.as_os_str().as_encoded_bytes(),
+                )?;
+            }
+            Err(error) => {
+                return Err(error.context(format!(
+                    "Failed to move profile to trash: {}",
+                    profile_path.display()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Moves the files of a trashed web app to the trash of the desktop, where the undo toast can't
+/// reach them anymore. Without a trash they are removed, so run this off the main thread.
+pub fn purge_staging_dir(staging_dir: &Path) -> Result<()> {
+    let profile_path_file = staging_dir.join(TrashedWebApp::PROFILE_PATH_FILE);
+    if let Ok(profile_path) = fs::read_to_string(&profile_path_file) {
+        let profile_path = Path::new(&profile_path);
+        if profile_path.is_dir() {
+            removal::trash_or_remove(profile_path)?;
+        }
+    }
+
+    for entry in files::get_entries_in_dir(staging_dir)? {
+        let path = entry.path();
+        if path != profile_path_file {
+            removal::trash_or_remove(&path)?;
+        }
+    }
+
+    fs::remove_dir_all(staging_dir).context(format!(
+        "Failed to remove staging dir: {}",
+        staging_dir.display()
+    ))?;
+
+    debug!(path = %staging_dir.display(), "Purged trashed web app");
+    Ok(())
+}
+
+/// Left behind when the app was closed before the undo toast was gone. Listed right away, so a
+/// web app deleted while the purge runs keeps its undo.
+pub fn get_staging_dirs(app_dirs: &AppDirs) -> Vec<PathBuf> {
+    files::get_entries_in_dir(&app_dirs.app_data_trash)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+/// Profiles can be large, run this off the main thread
+pub fn purge_staging_dirs(staging_dirs: &[PathBuf]) {
+    for staging_dir in staging_dirs {
+        if let Err(error) = purge_staging_dir(staging_dir) {
+            error!("{error:?}");
+        }
+    }
+}
+
+/// Rename, or copy a file that is on another filesystem. A dir on another filesystem is an error.
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices && from.is_file() => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)?;
+            Ok(())
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn is_crosses_devices(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|error| error.kind() == io::ErrorKind::CrossesDevices)
+}
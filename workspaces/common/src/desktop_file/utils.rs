@@ -1,25 +1,451 @@
-use std::path::{Path, PathBuf};
+use crate::utils::command;
+use freedesktop_desktop_entry::{DecodeError, DesktopEntry};
+use semver::Version;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
-pub fn map_to_string_option(value: &str) -> Option<String> {
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.to_string())
+/// Parse a desktop entry value, empty values are `None`
+pub trait FromEntryValue: Sized {
+    fn from_entry_value(value: &str) -> Option<Self>;
+}
+
+/// Format a value for a desktop entry
+pub trait ToEntryValue {
+    fn to_entry_value(&self) -> String;
+}
+
+impl FromEntryValue for String {
+    fn from_entry_value(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+}
+
+impl FromEntryValue for bool {
+    fn from_entry_value(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.eq("true"))
+        }
+    }
+}
+
+impl FromEntryValue for u32 {
+    fn from_entry_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl FromEntryValue for u64 {
+    fn from_entry_value(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl FromEntryValue for PathBuf {
+    fn from_entry_value(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            None
+        } else {
+            Some(Path::new(value).to_path_buf())
+        }
+    }
+}
+
+/// An unparsable version is treated as the oldest version, so migrations will run
+impl FromEntryValue for Version {
+    fn from_entry_value(value: &str) -> Option<Self> {
+        Some(Version::parse(value).unwrap_or(Version::new(0, 0, 0)))
+    }
+}
+
+/// Semicolon separated list, like `Categories`. An item can contain an escaped `\;`.
+impl FromEntryValue for Vec<String> {
+    fn from_entry_value(value: &str) -> Option<Self> {
+        let mut list = Vec::new();
+        let mut item = String::new();
+        let mut chars = value.chars();
+        while let Some(char) = chars.next() {
+            match (char, chars.clone().next()) {
+                ('\\', Some(';')) => {
+                    item.push(';');
+                    chars.next();
+                }
+                (';', _) => list.push(std::mem::take(&mut item)),
+                _ => item.push(char),
+            }
+        }
+        list.push(item);
+        list.retain(|item| !item.is_empty());
+
+        if list.is_empty() { None } else { Some(list) }
+    }
+}
+
+impl ToEntryValue for str {
+    fn to_entry_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToEntryValue for String {
+    fn to_entry_value(&self) -> String {
+        self.clone()
+    }
+}
+
+impl ToEntryValue for bool {
+    fn to_entry_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToEntryValue for u32 {
+    fn to_entry_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToEntryValue for u64 {
+    fn to_entry_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToEntryValue for Path {
+    fn to_entry_value(&self) -> String {
+        self.to_string_lossy().to_string()
+    }
+}
+
+impl ToEntryValue for Version {
+    fn to_entry_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToEntryValue for [String] {
+    fn to_entry_value(&self) -> String {
+        self.iter()
+            .map(|item| item.replace(';', "\\;"))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+}
+
+/// `DesktopEntry` rejects `\;`, the escaped separator of a list. It is read as an escaped
+/// backslash instead, so the value keeps the `\;` for `FromEntryValue`.
+pub fn parse_desktop_entry(path: &Path, contents: &str) -> Result<DesktopEntry, DecodeError> {
+    let mut escaped_contents = String::with_capacity(contents.len());
+    let mut chars = contents.chars();
+    while let Some(char) = chars.next() {
+        escaped_contents.push(char);
+        if char == '\\' {
+            match chars.next() {
+                Some(';') => escaped_contents.push_str("\\;"),
+                Some(next_char) => escaped_contents.push(next_char),
+                None => {}
+            }
+        }
+    }
+
+    DesktopEntry::from_str(path, &escaped_contents, None::<&[String]>)
+}
+
+pub fn read_desktop_entry(path: &Path) -> Result<DesktopEntry, DecodeError> {
+    parse_desktop_entry(path, &fs::read_to_string(path)?)
+}
+
+/// Escapes a string for a value in the key file, backslashes and new lines are read as escapes.
+/// A leading space is escaped too, it would be trimmed. The `\;` of a list item is kept.
+pub fn escape_value(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace("\\\\;", "\\;")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r");
+
+    match escaped.strip_prefix(' ') {
+        Some(rest) => format!("\\s{rest}"),
+        None => escaped,
+    }
+}
+
+/// One argument of an `Exec` line, quoted when needed and with a literal '%' doubled
+pub fn escape_exec_arg(arg: &str) -> String {
+    command::quote_exec_arg(arg).replace('%', "%%")
+}
+
+/// Like `escape_exec_arg`, for a value the template already put in quotes, like `--app="%{url}"`
+pub fn escape_quoted_exec_arg(arg: &str) -> String {
+    let mut escaped = String::with_capacity(arg.len());
+    for char in arg.chars() {
+        match char {
+            '"' | '`' | '$' | '\\' => escaped.push('\\'),
+            '%' => escaped.push('%'),
+            _ => {}
+        }
+        escaped.push(char);
     }
+    escaped
 }
 
-pub fn map_to_bool_option(value: &str) -> Option<bool> {
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.eq("true"))
+/// Checks the quoting and field codes of an `Exec` value, launchers refuse a broken one.
+/// Unquoted reserved characters are allowed, launchers don't run a shell and handler urls need them.
+pub fn validate_exec(exec: &str) -> Result<(), String> {
+    let mut chars = exec.chars();
+    let mut is_quoted = false;
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => is_quoted = !is_quoted,
+            '\\' if is_quoted => match chars.next() {
+                Some('"' | '`' | '$' | '\\') => {}
+                _ => return Err("Invalid escape in a quoted argument".to_string()),
+            },
+            '`' | '$' if is_quoted => {
+                return Err(format!("Unescaped '{char}' in a quoted argument"));
+            }
+            '%' => match chars.next() {
+                Some('f' | 'F' | 'u' | 'U' | 'i' | 'c' | 'k') if is_quoted => {
+                    return Err("Field code in a quoted argument".to_string());
+                }
+                // Deprecated codes are still expanded to nothing
+                Some(
+                    '%' | 'f' | 'F' | 'u' | 'U' | 'i' | 'c' | 'k' | 'd' | 'D' | 'n' | 'N' | 'v'
+                    | 'm',
+                ) => {}
+                _ => return Err("A literal '%' must be doubled".to_string()),
+            },
+            _ => {}
+        }
+    }
+
+    if is_quoted {
+        return Err("Unclosed quote".to_string());
     }
+
+    Ok(())
 }
 
-pub fn map_to_path_option(value: &str) -> Option<PathBuf> {
-    if value.is_empty() {
-        None
-    } else {
-        Some(Path::new(value).to_path_buf())
+/// The command of an `Exec` value started without files or urls, like a launcher does.
+/// A doubled '%' is a literal one again, `%i`, `%c` and `%k` are expanded and the other field
+/// codes expand to nothing.
+pub fn expand_field_codes(
+    exec: &str,
+    icon: Option<&str>,
+    name: Option<&str>,
+    location: Option<&Path>,
+) -> String {
+    let mut expanded = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            expanded.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('%') => expanded.push('%'),
+            Some('i') => {
+                if let Some(icon) = icon {
+                    expanded.push_str("--icon ");
+                    expanded.push_str(&command::quote_exec_arg(icon));
+                }
+            }
+            Some('c') => {
+                if let Some(name) = name {
+                    expanded.push_str(&command::quote_exec_arg(name));
+                }
+            }
+            Some('k') => {
+                if let Some(location) = location {
+                    expanded.push_str(&command::quote_exec_arg(&location.to_string_lossy()));
+                }
+            }
+            Some(_) | None => {}
+        }
+    }
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtk::glib;
+
+    fn parse_expanded(exec: &str) -> Vec<String> {
+        let expanded = expand_field_codes(exec, None, None, None);
+        glib::shell_parse_argv(expanded)
+            .unwrap()
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn percent_encoded_url_round_trips() {
+        let url = "https://example.com/a%20b";
+        let exec = format!("browser --app={} %u", escape_exec_arg(url));
+
+        assert_eq!(exec, "browser --app=https://example.com/a%%20b %u");
+        assert!(validate_exec(&exec).is_ok());
+        assert_eq!(parse_expanded(&exec), ["browser", &format!("--app={url}")]);
+    }
+
+    #[test]
+    fn percent_encoded_url_round_trips_in_quotes() {
+        let url = "https://example.com/a%20b?q=\"x\"";
+        let exec = format!("browser \"--app={}\"", escape_quoted_exec_arg(url));
+
+        assert!(validate_exec(&exec).is_ok());
+        assert_eq!(parse_expanded(&exec), ["browser", &format!("--app={url}")]);
+    }
+
+    #[test]
+    fn url_with_reserved_characters_round_trips() {
+        let url = "https://example.com/?a=1&b=%2F";
+        let exec = format!("browser {}", escape_exec_arg(url));
+
+        assert!(validate_exec(&exec).is_ok());
+        assert_eq!(parse_expanded(&exec), ["browser", url]);
+    }
+
+    #[test]
+    fn file_field_codes_expand_to_nothing() {
+        for field_code in ["%f", "%F", "%u", "%U"] {
+            let exec = format!("browser --new-window {field_code}");
+            assert_eq!(parse_expanded(&exec), ["browser", "--new-window"]);
+        }
+    }
+
+    #[test]
+    fn icon_name_and_location_are_expanded() {
+        let expanded = expand_field_codes(
+            "browser %i %c %k",
+            Some("/icons/my app.png"),
+            Some("My App"),
+            Some(Path::new("/apps/app.desktop")),
+        );
+
+        assert_eq!(
+            expanded,
+            "browser --icon \"/icons/my app.png\" \"My App\" /apps/app.desktop"
+        );
+    }
+
+    #[test]
+    fn missing_icon_drops_the_icon_argument() {
+        assert_eq!(
+            expand_field_codes("browser %i", None, None, None),
+            "browser "
+        );
+    }
+
+    #[test]
+    fn single_percent_is_invalid() {
+        assert!(validate_exec("browser https://example.com/a%20b").is_err());
+        assert!(validate_exec("browser \"%u\"").is_err());
+    }
+
+    #[test]
+    fn empty_values_are_none() {
+        assert_eq!(String::from_entry_value(""), None);
+        assert_eq!(bool::from_entry_value(""), None);
+        assert_eq!(u32::from_entry_value(""), None);
+        assert_eq!(PathBuf::from_entry_value(""), None);
+        assert_eq!(Vec::<String>::from_entry_value(""), None);
+        assert_eq!(Vec::<String>::from_entry_value(";;"), None);
+    }
+
+    #[test]
+    fn only_true_is_true() {
+        assert_eq!(bool::from_entry_value("true"), Some(true));
+        assert_eq!(bool::from_entry_value("false"), Some(false));
+        assert_eq!(bool::from_entry_value("yes"), Some(false));
+    }
+
+    #[test]
+    fn invalid_number_is_none() {
+        assert_eq!(u32::from_entry_value("1280"), Some(1280));
+        assert_eq!(u32::from_entry_value("-1"), None);
+        assert_eq!(u64::from_entry_value("12.5"), None);
+    }
+
+    #[test]
+    fn invalid_version_is_the_oldest_version() {
+        assert_eq!(
+            Version::from_entry_value("1.2.3"),
+            Some(Version::new(1, 2, 3))
+        );
+        assert_eq!(
+            Version::from_entry_value("unknown"),
+            Some(Version::new(0, 0, 0))
+        );
+        assert_eq!(Version::from_entry_value(""), Some(Version::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let list = vec!["Network".to_string(), "Office".to_string()];
+        let path = Path::new("/home/user/.local/share/icons/mail.png");
+        let version = Version::new(0, 5, 0);
+
+        assert_eq!(list.to_entry_value(), "Network;Office");
+        assert_eq!(
+            Vec::<String>::from_entry_value("Network;Office;"),
+            Some(list.clone())
+        );
+        assert_eq!(
+            Vec::<String>::from_entry_value(&list.to_entry_value()),
+            Some(list)
+        );
+        assert_eq!(
+            PathBuf::from_entry_value(&path.to_entry_value()),
+            Some(path.to_path_buf())
+        );
+        assert_eq!(
+            Version::from_entry_value(&version.to_entry_value()),
+            Some(version)
+        );
+        assert_eq!(bool::from_entry_value(&true.to_entry_value()), Some(true));
+        assert_eq!(u32::from_entry_value(&720u32.to_entry_value()), Some(720));
+        assert_eq!(
+            String::from_entry_value(&"Mail".to_entry_value()).as_deref(),
+            Some("Mail")
+        );
+    }
+
+    #[test]
+    fn escaped_separator_stays_in_the_list_item() {
+        let list = vec!["Mail".to_string(), "a;b".to_string()];
+
+        assert_eq!(list.to_entry_value(), "Mail;a\\;b");
+        assert_eq!(
+            Vec::<String>::from_entry_value("Mail;a\\;b;"),
+            Some(list.clone())
+        );
+        assert_eq!(escape_value(&list.to_entry_value()), "Mail;a\\;b");
+    }
+
+    #[test]
+    fn file_with_an_escaped_separator_is_parsed() {
+        let desktop_entry = parse_desktop_entry(
+            Path::new("/tmp/mail.desktop"),
+            "[Desktop Entry]\nKeywords=a\\;b;c;\nExec=sh -c \"echo \\\\$HOME\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(desktop_entry.desktop_entry("Keywords"), Some("a\\;b;c;"));
+        assert_eq!(desktop_entry.exec(), Some("sh -c \"echo \\$HOME\""));
     }
 }
@@ -0,0 +1,251 @@
+use crate::app_dirs::AppDirs;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, instrument};
+
+/// A profile that was kept when its web app got removed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DetachedProfile {
+    pub profile_path: PathBuf,
+    pub name: String,
+    pub url: String,
+    pub domain: String,
+    pub browser_id: String,
+    pub browser_base: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct DetachedProfilesYaml {
+    #[serde(default)]
+    pub profiles: Vec<DetachedProfile>,
+}
+
+#[derive(Debug)]
+pub struct DetachedProfiles {
+    pub detached: DetachedProfilesYaml,
+    file_path: PathBuf,
+}
+impl DetachedProfiles {
+    const DETACHED_PROFILES_FILE: &str = "detached-profiles.yml";
+
+    pub fn new(app_dirs: &AppDirs) -> Self {
+        let file_path = app_dirs.app_data.join(Self::DETACHED_PROFILES_FILE);
+
+        let yaml_string = fs::read_to_string(&file_path).unwrap_or_default();
+        let detached: DetachedProfilesYaml = serde_yaml::from_str(&yaml_string)
+            .inspect_err(
+                |error| error!(%error, path = %file_path.display(), "Failed to parse detached profiles yaml file"),
+            )
+            .unwrap_or_default();
+
+        Self {
+            detached,
+            file_path,
+        }
+    }
+
+    pub fn add(&mut self, detached_profile: DetachedProfile) {
+        self.remove(&detached_profile.profile_path);
+        self.detached.profiles.push(detached_profile);
+    }
+
+    pub fn remove(&mut self, profile_path: &Path) {
+        self.detached
+            .profiles
+            .retain(|profile| profile.profile_path != profile_path);
+    }
+
+    /// Find a kept profile that still exists for the same site and browser base
+    pub fn find(&self, domain: &str, browser_base: &str) -> Option<&DetachedProfile> {
+        self.detached.profiles.iter().find(|profile| {
+            profile.domain == domain
+                && profile.browser_base == browser_base
+                && profile.profile_path.is_dir()
+        })
+    }
+
+    #[instrument(err, skip(self))]
+    pub fn save(&self) -> Result<()> {
+        debug!("Saving detached profiles");
+
+        let dir_path = self.file_path.parent().context(format!(
+            "Failed to get parent of detached profiles path: {}",
+            self.file_path.display()
+        ))?;
+
+        if !dir_path.is_dir() {
+            fs::create_dir_all(dir_path).context(format!(
+                "Failed to create data dir for app: {}",
+                dir_path.display()
+            ))?;
+        }
+
+        let yaml_string = serde_yaml::to_string(&self.detached)
+            .context("Failed to parse detached profiles to yaml string")?;
+
+        fs::write(&self.file_path, &yaml_string).context(format!(
+            "Failed to write detached profiles file: {}",
+            self.file_path.display()
+        ))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_app_dirs(dir: &Path) -> AppDirs {
+        AppDirs {
+            app_data: dir.join("data"),
+            ..AppDirs::default()
+        }
+    }
+
+    fn build_profile(profile_path: &Path, domain: &str, browser_base: &str) -> DetachedProfile {
+        DetachedProfile {
+            profile_path: profile_path.to_path_buf(),
+            name: "Mail".to_string(),
+            url: format!("https://{domain}/inbox"),
+            domain: domain.to_string(),
+            browser_id: "org.chromium.Chromium".to_string(),
+            browser_base: browser_base.to_string(),
+        }
+    }
+
+    #[test]
+    fn saved_profiles_are_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        let profile = build_profile(&dir.path().join("profile"), "mail.example.com", "chromium");
+        let mut detached_profiles = DetachedProfiles::new(&app_dirs);
+        detached_profiles.add(profile.clone());
+
+        detached_profiles.save().unwrap();
+
+        assert_eq!(
+            DetachedProfiles::new(&app_dirs).detached.profiles,
+            vec![profile]
+        );
+    }
+
+    #[test]
+    fn missing_or_broken_file_has_no_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+
+        assert!(
+            DetachedProfiles::new(&app_dirs)
+                .detached
+                .profiles
+                .is_empty()
+        );
+
+        fs::create_dir_all(&app_dirs.app_data).unwrap();
+        fs::write(
+            app_dirs
+                .app_data
+                .join(DetachedProfiles::DETACHED_PROFILES_FILE),
+            "profiles: [",
+        )
+        .unwrap();
+
+        assert!(
+            DetachedProfiles::new(&app_dirs)
+                .detached
+                .profiles
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn record_without_profiles_key_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = get_app_dirs(dir.path());
+        fs::create_dir_all(&app_dirs.app_data).unwrap();
+        fs::write(
+            app_dirs
+                .app_data
+                .join(DetachedProfiles::DETACHED_PROFILES_FILE),
+            "{}",
+        )
+        .unwrap();
+
+        assert!(
+            DetachedProfiles::new(&app_dirs)
+                .detached
+                .profiles
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn adding_the_same_path_replaces_the_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile_path = dir.path().join("profile");
+        let mut detached_profiles = DetachedProfiles::new(&get_app_dirs(dir.path()));
+
+        detached_profiles.add(build_profile(&profile_path, "old.example.com", "chromium"));
+        detached_profiles.add(build_profile(&profile_path, "new.example.com", "chromium"));
+
+        assert_eq!(detached_profiles.detached.profiles.len(), 1);
+        assert_eq!(
+            detached_profiles.detached.profiles[0].domain,
+            "new.example.com"
+        );
+
+        detached_profiles.remove(&profile_path);
+
+        assert!(detached_profiles.detached.profiles.is_empty());
+    }
+
+    #[test]
+    fn profile_is_found_by_domain_and_browser_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let chromium_path = dir.path().join("chromium-profile");
+        let firefox_path = dir.path().join("firefox-profile");
+        fs::create_dir_all(&chromium_path).unwrap();
+        fs::create_dir_all(&firefox_path).unwrap();
+        let mut detached_profiles = DetachedProfiles::new(&get_app_dirs(dir.path()));
+        detached_profiles.add(build_profile(
+            &chromium_path,
+            "mail.example.com",
+            "chromium",
+        ));
+        detached_profiles.add(build_profile(&firefox_path, "mail.example.com", "firefox"));
+
+        let found = detached_profiles
+            .find("mail.example.com", "firefox")
+            .unwrap();
+
+        assert_eq!(found.profile_path, firefox_path);
+        assert!(detached_profiles.find("example.com", "chromium").is_none());
+        assert!(
+            detached_profiles
+                .find("mail.example.com", "webkit")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn removed_profile_dir_is_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut detached_profiles = DetachedProfiles::new(&get_app_dirs(dir.path()));
+        detached_profiles.add(build_profile(
+            &dir.path().join("missing"),
+            "mail.example.com",
+            "chromium",
+        ));
+
+        assert!(
+            detached_profiles
+                .find("mail.example.com", "chromium")
+                .is_none()
+        );
+    }
+}
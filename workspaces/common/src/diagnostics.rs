@@ -0,0 +1,136 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::config_layers::{self, ConfigDirs},
+    config::{self},
+    utils::{self, OnceLockExt},
+};
+use freedesktop_desktop_entry::DesktopEntry;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// Paths the checks need, can be sent to a blocking thread unlike `Rc<AppDirs>`
+pub struct DiagnosticsInput {
+    user_applications: PathBuf,
+    has_host_applications: bool,
+    app_data_icons: PathBuf,
+    config_dirs: ConfigDirs,
+}
+impl DiagnosticsInput {
+    pub fn new(app_dirs: &AppDirs) -> Self {
+        Self {
+            user_applications: app_dirs.user_applications.clone(),
+            has_host_applications: app_dirs.has_host_applications,
+            app_data_icons: app_dirs.app_data_icons.clone(),
+            config_dirs: ConfigDirs::new(app_dirs),
+        }
+    }
+}
+
+/// A browser desktop file that can't be used, the browser is skipped on load
+pub struct DesktopTemplateError {
+    pub file_name: String,
+    pub error: String,
+}
+
+/// Environment checks, browser detection is read from `BrowserConfigs` instead
+pub struct DiagnosticsReport {
+    pub user_applications: PathBuf,
+    /// Launchers can't be created without it
+    pub user_applications_error: Option<String>,
+    /// Without it inside the sandbox, launchers are installed through the portal
+    pub has_host_applications: bool,
+    pub is_flatpak_container: bool,
+    /// Inside the sandbox every host command goes through `flatpak-spawn --host`
+    pub has_host_access: bool,
+    pub has_flatpak: bool,
+    pub app_data_icons: PathBuf,
+    pub has_icons_dir: bool,
+    pub desktop_template_count: usize,
+    pub desktop_template_errors: Vec<DesktopTemplateError>,
+}
+
+/// Runs commands and touches the disk, run this off the main thread
+pub fn run_checks(input: &DiagnosticsInput) -> DiagnosticsReport {
+    debug!("Running diagnostics");
+
+    let is_flatpak_container = utils::env::is_flatpak_container();
+    let has_host_access = !is_flatpak_container || is_command_success("true");
+    let has_flatpak = has_host_access && is_command_success("flatpak --version");
+    let (desktop_template_count, desktop_template_errors) = check_desktop_templates(input);
+
+    DiagnosticsReport {
+        user_applications: input.user_applications.clone(),
+        user_applications_error: if input.has_host_applications {
+            check_writable(&input.user_applications).err()
+        } else {
+            None
+        },
+        has_host_applications: input.has_host_applications,
+        is_flatpak_container,
+        has_host_access,
+        has_flatpak,
+        app_data_icons: input.app_data_icons.clone(),
+        has_icons_dir: input.app_data_icons.is_dir(),
+        desktop_template_count,
+        desktop_template_errors,
+    }
+}
+
+/// Gives the sandbox access to the host, so browsers can be detected and started
+pub fn get_flatpak_override_command() -> String {
+    format!(
+        "flatpak override --user --talk-name=org.freedesktop.Flatpak {}",
+        config::APP_ID.get_value()
+    )
+}
+
+/// Lets launchers be saved on the host, instead of asking the portal on every save
+pub fn get_applications_override_command() -> String {
+    format!(
+        "flatpak override --user --filesystem=xdg-data/applications:create {}",
+        config::APP_ID.get_value()
+    )
+}
+
+fn is_command_success(command: &str) -> bool {
+    utils::command::run_command_sync(command).is_ok_and(|response| response.success)
+}
+
+/// Writes and removes a file, permissions alone don't show a read-only filesystem
+fn check_writable(dir: &Path) -> Result<(), String> {
+    let test_path = dir.join(format!(
+        ".{}-write-test",
+        config::APP_NAME_HYPHEN.get_value()
+    ));
+
+    fs::write(&test_path, b"").map_err(|error| error.to_string())?;
+    fs::remove_file(&test_path).map_err(|error| error.to_string())
+}
+
+/// Every browser config needs a parseable desktop file with the same name, in either layer
+fn check_desktop_templates(input: &DiagnosticsInput) -> (usize, Vec<DesktopTemplateError>) {
+    let mut count = 0;
+    let mut errors = Vec::new();
+
+    for layered_config in config_layers::get_layered_configs(&input.config_dirs) {
+        let desktop_file_path = &layered_config.desktop_file_path;
+        let file_name = desktop_file_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        count += 1;
+
+        if let Err(error) = DesktopEntry::from_path(desktop_file_path, None::<&[String]>) {
+            errors.push(DesktopTemplateError {
+                file_name,
+                error: error.to_string(),
+            });
+        }
+    }
+
+    (count, errors)
+}
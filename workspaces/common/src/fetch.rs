@@ -1,19 +1,36 @@
 use anyhow::{Result, bail};
-use gtk::gio::{self};
-use std::time::Duration;
+use gtk::gio::{self, prelude::CancellableExt};
+use std::{io::Read, time::Duration};
 use tracing::{debug, error};
-use ureq::Agent;
+use ureq::{Agent, Body, http};
 
 pub struct Response<T> {
     pub data: T,
     pub mimetype: Option<String>,
 }
 
+/// Result of a reachability check of an url, only informational
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reachability {
+    /// A 2xx or 3xx status
+    Reachable(u16),
+    /// A 4xx or 5xx status
+    HttpError(u16),
+    /// The certificate could not be verified, like a self-signed one
+    TlsFailed,
+    HostNotFound,
+    /// Any other error, like a refused connection or a timeout
+    Unreachable,
+}
+
 pub struct Fetch {
     agent: Agent,
 }
 impl Fetch {
     const FETCH_TIMEOUT: u64 = 5; // Seconds
+    /// The limit of `ureq` when reading a whole body at once
+    const MAX_BODY_LENGTH: u64 = 10 * 1024 * 1024;
+    const READ_CHUNK_LENGTH: usize = 16 * 1024;
 
     pub fn new() -> Self {
         let agent: Agent = Agent::config_builder()
@@ -25,52 +42,172 @@ impl Fetch {
         Self { agent }
     }
 
-    pub async fn get_as_string(&self, url: &str) -> Result<Response<String>> {
+    pub async fn get_as_string(
+        &self,
+        url: &str,
+        cancellable: Option<&gio::Cancellable>,
+    ) -> Result<Response<String>> {
         debug!("Fetching text from url: {url}");
+        let Response { data, mimetype } = self.get(url, cancellable, true).await?;
+
+        Ok(Response {
+            data: String::from_utf8_lossy(&data).to_string(),
+            mimetype,
+        })
+    }
+
+    pub async fn get_as_bytes(
+        &self,
+        url: &str,
+        cancellable: Option<&gio::Cancellable>,
+    ) -> Result<Response<Vec<u8>>> {
+        debug!("Fetching bytes from url: {url}");
+        self.get(url, cancellable, false).await
+    }
+
+    async fn get(
+        &self,
+        url: &str,
+        cancellable: Option<&gio::Cancellable>,
+        is_text: bool,
+    ) -> Result<Response<Vec<u8>>> {
         let agent_clone = self.agent.clone();
         let url = url.to_string();
         let url_clone = url.clone();
+        let cancellable = cancellable.cloned();
+        let cancellable_clone = cancellable.clone();
 
-        match gio::spawn_blocking(move || -> Result<(String, Option<String>)> {
+        let result = gio::spawn_blocking(move || -> Result<(Vec<u8>, Option<String>)> {
+            Self::bail_if_cancelled(cancellable_clone.as_ref())?;
             let mut call = agent_clone.get(url_clone).call()?;
             let body = call.body_mut();
             let mimetype = body.mime_type().map(std::string::ToString::to_string);
-            let response = body.read_to_string()?;
-            Ok((response, mimetype))
+            let reader = body
+                .with_config()
+                .limit(Self::MAX_BODY_LENGTH)
+                .lossy_utf8(is_text)
+                .reader();
+            let data = Self::read_cancellable(reader, cancellable_clone.as_ref())?;
+            Ok((data, mimetype))
         })
-        .await
+        .await;
+
+        if cancellable
+            .as_ref()
+            .is_some_and(CancellableExt::is_cancelled)
         {
-            Ok(Ok((text, mimetype))) => Ok(Response {
-                data: text,
-                mimetype,
-            }),
+            debug!("Fetching '{url}' cancelled");
+            bail!("Fetching '{url}' cancelled")
+        }
+
+        match result {
+            Ok(Ok((data, mimetype))) => Ok(Response { data, mimetype }),
             Ok(Err(error)) => Self::error_handler(&url, &error),
             Err(error) => Self::error_handler(&url, &error),
         }
     }
 
-    pub async fn get_as_bytes(&self, url: &str) -> Result<Response<Vec<u8>>> {
-        debug!("Fetching bytes from url: {url}");
+    /// A failing HEAD is tried again with GET, some servers don't allow HEAD.
+    /// Fails only when cancelled.
+    pub async fn check_reachability(
+        &self,
+        url: &str,
+        cancellable: Option<&gio::Cancellable>,
+    ) -> Result<Reachability> {
+        debug!("Checking reachability of url: {url}");
         let agent_clone = self.agent.clone();
         let url = url.to_string();
         let url_clone = url.clone();
+        let cancellable = cancellable.cloned();
+        let cancellable_clone = cancellable.clone();
 
-        match gio::spawn_blocking(move || -> Result<(Vec<u8>, Option<String>)> {
-            let mut call = agent_clone.get(url_clone).call()?;
-            let body = call.body_mut();
-            let mimetype = body.mime_type().map(std::string::ToString::to_string);
-            let response = body.read_to_vec()?;
-            Ok((response, mimetype))
+        let result = gio::spawn_blocking(move || -> Result<Reachability> {
+            let call = |is_head: bool| {
+                let request = if is_head {
+                    agent_clone.head(&url_clone)
+                } else {
+                    agent_clone.get(&url_clone)
+                };
+                request.config().http_status_as_error(false).build().call()
+            };
+
+            Self::bail_if_cancelled(cancellable_clone.as_ref())?;
+            let reachability = Self::get_reachability(call(true));
+            if !matches!(
+                reachability,
+                Reachability::HttpError(_) | Reachability::Unreachable
+            ) {
+                return Ok(reachability);
+            }
+
+            Self::bail_if_cancelled(cancellable_clone.as_ref())?;
+            Ok(Self::get_reachability(call(false)))
         })
-        .await
+        .await;
+
+        if cancellable
+            .as_ref()
+            .is_some_and(CancellableExt::is_cancelled)
         {
-            Ok(Ok((bytes, mimetype))) => Ok(Response {
-                data: bytes,
-                mimetype,
-            }),
-            Ok(Err(error)) => Self::error_handler(&url, &error),
-            Err(error) => Self::error_handler(&url, &error),
+            debug!("Checking reachability of '{url}' cancelled");
+            bail!("Checking reachability of '{url}' cancelled")
+        }
+
+        let reachability = match result {
+            Ok(Ok(reachability)) => reachability,
+            Ok(Err(error)) => return Self::error_handler(&url, &error),
+            Err(error) => return Self::error_handler(&url, &error),
+        };
+        debug!(?reachability, "Checked reachability of url: {url}");
+
+        Ok(reachability)
+    }
+
+    fn get_reachability(result: Result<http::Response<Body>, ureq::Error>) -> Reachability {
+        match result {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                if status < 400 {
+                    Reachability::Reachable(status)
+                } else {
+                    Reachability::HttpError(status)
+                }
+            }
+            Err(ureq::Error::StatusCode(status)) => Reachability::HttpError(status),
+            Err(ureq::Error::HostNotFound) => Reachability::HostNotFound,
+            Err(ureq::Error::Tls(_) | ureq::Error::Rustls(_)) => Reachability::TlsFailed,
+            Err(error) => {
+                debug!("Url is unreachable: {error:?}");
+                Reachability::Unreachable
+            }
+        }
+    }
+
+    /// Read in chunks, dropping the reader of a cancelled fetch closes the connection
+    fn read_cancellable(
+        mut reader: impl Read,
+        cancellable: Option<&gio::Cancellable>,
+    ) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut chunk = [0; Self::READ_CHUNK_LENGTH];
+
+        loop {
+            Self::bail_if_cancelled(cancellable)?;
+            let length = reader.read(&mut chunk)?;
+            if length == 0 {
+                break;
+            }
+            data.extend_from_slice(&chunk[..length]);
+        }
+
+        Ok(data)
+    }
+
+    fn bail_if_cancelled(cancellable: Option<&gio::Cancellable>) -> Result<()> {
+        if cancellable.is_some_and(CancellableExt::is_cancelled) {
+            bail!("Cancelled")
         }
+        Ok(())
     }
 
     // Any error logged and a anyhow::Error
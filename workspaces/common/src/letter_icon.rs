@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use gtk::{
+    cairo::{self, FontSlant, FontWeight, Format, ImageSurface},
+    gdk_pixbuf::{Colorspace, Pixbuf},
+    glib,
+};
+use std::f64::consts::PI;
+
+/// A colored tile with the first letters of the app name, for sites without any usable icon
+pub struct LetterIcon {
+    pub letters: String,
+    /// Red, green and blue between 0 and 1
+    pub color: (f64, f64, f64),
+}
+impl LetterIcon {
+    pub const SIZE: i32 = 512;
+    /// Relative to the size
+    const CORNER_RADIUS_RATIO: f64 = 0.2;
+    const FONT_FAMILY: &str = "Sans";
+    /// GNOME palette, dark enough for white letters
+    const COLORS: [(u8, u8, u8); 8] = [
+        (0x1c, 0x71, 0xd8),
+        (0x26, 0xa2, 0x69),
+        (0xe5, 0xa5, 0x0a),
+        (0xc6, 0x46, 0x00),
+        (0xc0, 0x1c, 0x28),
+        (0x81, 0x3d, 0x9c),
+        (0x86, 0x5e, 0x3c),
+        (0x5e, 0x5c, 0x64),
+    ];
+
+    /// The color only depends on the domain, so all apps of a site look alike
+    pub fn new(name: &str, domain: &str) -> Self {
+        let domain = domain.trim().trim_start_matches("www.").to_lowercase();
+        let letters = Self::get_letters(name)
+            .or_else(|| Self::get_letters(&domain))
+            .unwrap_or_else(|| "?".to_string());
+
+        let (red, green, blue) = Self::COLORS[Self::hash(&domain) % Self::COLORS.len()];
+        let color = (
+            f64::from(red) / 255.0,
+            f64::from(green) / 255.0,
+            f64::from(blue) / 255.0,
+        );
+
+        Self { letters, color }
+    }
+
+    /// First letter of the first two words, or of the only word
+    fn get_letters(name: &str) -> Option<String> {
+        let letters: String = name
+            .split(|char: char| !char.is_alphanumeric())
+            .filter_map(|word| word.chars().next())
+            .take(2)
+            .flat_map(char::to_uppercase)
+            .collect();
+
+        if letters.is_empty() {
+            None
+        } else {
+            Some(letters)
+        }
+    }
+
+    /// FNV-1a, stable across runs and Rust versions unlike the std hasher
+    fn hash(value: &str) -> usize {
+        let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+        usize::try_from(hash % 1024).unwrap_or_default()
+    }
+
+    pub fn to_pixbuf(&self) -> Result<Pixbuf> {
+        let mut surface = ImageSurface::create(Format::ARgb32, Self::SIZE, Self::SIZE)
+            .context("Failed to create letter icon surface")?;
+
+        let context = cairo::Context::new(&surface).context("Failed to draw letter icon")?;
+        self.draw_tile(&context)?;
+        self.draw_letters(&context)?;
+        drop(context);
+        surface.flush();
+
+        let stride = usize::try_from(surface.stride())?;
+        let surface_data = surface
+            .data()
+            .context("Failed to read letter icon surface")?;
+
+        Ok(Self::surface_data_to_pixbuf(&surface_data, stride))
+    }
+
+    fn draw_tile(&self, context: &cairo::Context) -> Result<()> {
+        let size = f64::from(Self::SIZE);
+        let radius = size * Self::CORNER_RADIUS_RATIO;
+
+        context.new_sub_path();
+        context.arc(size - radius, radius, radius, -PI / 2.0, 0.0);
+        context.arc(size - radius, size - radius, radius, 0.0, PI / 2.0);
+        context.arc(radius, size - radius, radius, PI / 2.0, PI);
+        context.arc(radius, radius, radius, PI, PI * 1.5);
+        context.close_path();
+
+        let (red, green, blue) = self.color;
+        context.set_source_rgb(red, green, blue);
+        context.fill().context("Failed to fill letter icon tile")
+    }
+
+    /// Centered on the ink of the letters, not on the baseline
+    fn draw_letters(&self, context: &cairo::Context) -> Result<()> {
+        let size = f64::from(Self::SIZE);
+        let font_ratio = if self.letters.chars().count() > 1 {
+            0.4
+        } else {
+            0.5
+        };
+
+        context.select_font_face(Self::FONT_FAMILY, FontSlant::Normal, FontWeight::Bold);
+        context.set_font_size(size * font_ratio);
+        let extents = context
+            .text_extents(&self.letters)
+            .context("Failed to measure letter icon text")?;
+
+        context.move_to(
+            (size - extents.width()) / 2.0 - extents.x_bearing(),
+            (size - extents.height()) / 2.0 - extents.y_bearing(),
+        );
+        context.set_source_rgb(1.0, 1.0, 1.0);
+        context
+            .show_text(&self.letters)
+            .context("Failed to draw letter icon text")
+    }
+
+    /// Cairo pixels are premultiplied native endian ARGB, a pixbuf wants straight RGBA
+    #[allow(clippy::cast_possible_truncation)]
+    fn surface_data_to_pixbuf(surface_data: &[u8], stride: usize) -> Pixbuf {
+        let width = Self::SIZE.unsigned_abs() as usize;
+        let mut pixel_data = Vec::with_capacity(width * width * 4);
+
+        for row in surface_data.chunks_exact(stride) {
+            for pixel in row.chunks_exact(4).take(width) {
+                let argb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                let alpha = (argb >> 24) as u8;
+                let unpremultiply = |channel: u32| {
+                    let channel = channel & 0xff;
+                    if alpha == 0 {
+                        0
+                    } else {
+                        ((channel * 255 + u32::from(alpha) / 2) / u32::from(alpha)).min(255) as u8
+                    }
+                };
+
+                pixel_data.extend_from_slice(&[
+                    unpremultiply(argb >> 16),
+                    unpremultiply(argb >> 8),
+                    unpremultiply(argb),
+                    alpha,
+                ]);
+            }
+        }
+
+        Pixbuf::from_bytes(
+            &glib::Bytes::from_owned(pixel_data),
+            Colorspace::Rgb,
+            true,
+            8,
+            Self::SIZE,
+            Self::SIZE,
+            Self::SIZE * 4,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_are_the_first_two_words() {
+        assert_eq!(LetterIcon::new("Google Calendar", "").letters, "GC");
+        assert_eq!(LetterIcon::new("my cool web app", "").letters, "MC");
+        assert_eq!(LetterIcon::new("Slack", "").letters, "S");
+        assert_eq!(LetterIcon::new("ärzte-portal", "").letters, "ÄP");
+    }
+
+    #[test]
+    fn letters_fall_back_to_the_domain() {
+        assert_eq!(LetterIcon::new("  ", "www.example.com").letters, "EC");
+        assert_eq!(LetterIcon::new("", "").letters, "?");
+        assert_eq!(LetterIcon::new("!!", "...").letters, "?");
+    }
+
+    #[test]
+    fn color_only_depends_on_the_domain() {
+        let mail = LetterIcon::new("Mail", "example.com");
+
+        assert_eq!(
+            LetterIcon::new("Calendar", "www.Example.com ").color,
+            mail.color
+        );
+        assert!(
+            ["a.com", "b.com", "c.com", "d.com"]
+                .iter()
+                .any(|domain| LetterIcon::new("Mail", domain).color != mail.color)
+        );
+    }
+
+    #[test]
+    fn tile_has_round_corners_and_the_color() {
+        let letter_icon = LetterIcon {
+            letters: "W".to_string(),
+            color: (1.0, 0.0, 0.0),
+        };
+
+        let pixbuf = letter_icon.to_pixbuf().unwrap();
+        let pixels = pixbuf.read_pixel_bytes();
+        let get_pixel = |x: usize, y: usize| {
+            let index = y * usize::try_from(pixbuf.rowstride()).unwrap() + x * 4;
+            pixels[index..index + 4].to_vec()
+        };
+
+        assert_eq!(pixbuf.width(), LetterIcon::SIZE);
+        assert_eq!(pixbuf.height(), LetterIcon::SIZE);
+        assert!(pixbuf.has_alpha());
+        assert_eq!(get_pixel(0, 0)[3], 0);
+        assert_eq!(get_pixel(40, 256), vec![255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn premultiplied_pixels_are_straightened() {
+        let width = LetterIcon::SIZE.unsigned_abs() as usize;
+        let stride = width * 4;
+        let mut surface_data = vec![0; stride * width];
+        // Half transparent white, premultiplied
+        surface_data[..4].copy_from_slice(&0x8080_8080_u32.to_ne_bytes());
+
+        let pixbuf = LetterIcon::surface_data_to_pixbuf(&surface_data, stride);
+        let pixels = pixbuf.read_pixel_bytes();
+
+        assert_eq!(pixels[..4], [255, 255, 255, 128]);
+        assert_eq!(pixels[4..8], [0, 0, 0, 0]);
+    }
+}
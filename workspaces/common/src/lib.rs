@@ -5,10 +5,17 @@
 
 pub mod app_dirs;
 pub mod assets;
+pub mod backup;
 pub mod browsers;
 pub mod cache_settings;
 pub mod config;
 pub mod desktop_file;
+pub mod detached_profiles;
+pub mod diagnostics;
 pub mod fetch;
+pub mod letter_icon;
+pub mod policy;
+pub mod preferences;
+pub mod site_hints;
 pub mod url;
 pub mod utils;
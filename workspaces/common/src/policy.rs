@@ -0,0 +1,290 @@
+use crate::{
+    app_dirs::AppDirs,
+    config::{self},
+    url::{self},
+    utils::OnceLockExt,
+};
+use ::url::Url;
+use anyhow::{Result, bail};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use tracing::{debug, error, info};
+
+pub static POLICY: OnceLock<Policy> = OnceLock::new();
+
+/// Load the policy once at startup, no policy file means no restrictions
+pub fn init(app_dirs: &AppDirs) {
+    let _ = POLICY.set(Policy::load(&Policy::get_admin_path(), app_dirs));
+}
+
+pub fn get() -> &'static Policy {
+    POLICY.get_or_init(Policy::default)
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct PolicyYaml {
+    /// Only these domain patterns are allowed, e.g. `example.com` or `*.example.com`
+    pub allowlist: Option<Vec<String>>,
+    /// These domain patterns are never allowed, wins over the allowlist
+    pub blocklist: Option<Vec<String>>,
+    #[serde(default)]
+    pub force_isolation: bool,
+    /// Extra browser arguments of web apps are left out of the launcher
+    #[serde(default)]
+    pub disable_custom_args: bool,
+}
+
+#[derive(Default, Debug)]
+pub struct Policy {
+    yaml: PolicyYaml,
+    path: Option<PathBuf>,
+    load_error: Option<String>,
+    /// A malformed allowlist blocks everything
+    is_fail_closed: bool,
+}
+impl Policy {
+    const POLICY_FILE: &str = "policy.yml";
+
+    fn get_admin_path() -> PathBuf {
+        Path::new("/etc")
+            .join(config::APP_NAME_HYPHEN.get_value())
+            .join(Self::POLICY_FILE)
+    }
+
+    /// Admin policy goes before the one in the user config
+    fn load(admin_path: &Path, app_dirs: &AppDirs) -> Self {
+        let user_path = app_dirs.app_config.join(Self::POLICY_FILE);
+
+        let Some(path) = [admin_path.to_path_buf(), user_path]
+            .into_iter()
+            .find(|path| path.is_file())
+        else {
+            debug!("No policy file found");
+            return Self::default();
+        };
+
+        info!(path = %path.display(), "Loading policy file");
+
+        let yaml_result = fs::read_to_string(&path)
+            .map_err(|error| (String::new(), error.to_string()))
+            .and_then(|yaml_string| {
+                serde_yaml::from_str::<PolicyYaml>(&yaml_string)
+                    .map_err(|error| (yaml_string, error.to_string()))
+            });
+
+        match yaml_result {
+            Ok(yaml) => Self {
+                yaml,
+                path: Some(path),
+                load_error: None,
+                is_fail_closed: false,
+            },
+            Err((yaml_string, load_error)) => {
+                // Can't tell what was meant, so only block all when it looks like an allowlist
+                let is_fail_closed = yaml_string.is_empty() || yaml_string.contains("allowlist");
+                error!(
+                    path = %path.display(),
+                    error = load_error,
+                    is_fail_closed,
+                    "Failed to load policy file"
+                );
+
+                Self {
+                    yaml: PolicyYaml::default(),
+                    path: Some(path),
+                    load_error: Some(load_error),
+                    is_fail_closed,
+                }
+            }
+        }
+    }
+
+    pub fn get_path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Set when a policy file exists but could not be read
+    pub fn get_load_error(&self) -> Option<&String> {
+        self.load_error.as_ref()
+    }
+
+    pub fn get_force_isolation(&self) -> bool {
+        self.yaml.force_isolation
+    }
+
+    pub fn get_disable_custom_args(&self) -> bool {
+        self.yaml.disable_custom_args
+    }
+
+    pub fn check_url(&self, url: &str) -> Result<()> {
+        let Some(domain) = Url::parse(url)
+            .ok()
+            .and_then(|url| url.domain().or(url.host_str()).map(str::to_string))
+        else {
+            bail!("Invalid url")
+        };
+
+        self.check_domain(&domain)
+    }
+
+    pub fn check_domain(&self, domain: &str) -> Result<()> {
+        let path = self
+            .path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+
+        if self.is_fail_closed {
+            bail!("Policy file is malformed: {path}")
+        }
+
+        if let Some(blocklist) = &self.yaml.blocklist
+            && Self::matches_any(blocklist, domain)
+        {
+            bail!("'{domain}' is blocked by policy: {path}")
+        }
+
+        if let Some(allowlist) = &self.yaml.allowlist
+            && !Self::matches_any(allowlist, domain)
+        {
+            bail!("'{domain}' is not allowed by policy: {path}")
+        }
+
+        Ok(())
+    }
+
+    fn matches_any(patterns: &[String], domain: &str) -> bool {
+        patterns
+            .iter()
+            .any(|pattern| url::get_domain_pattern_match(pattern, domain).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_policy(yaml: &str) -> Policy {
+        Policy {
+            yaml: serde_yaml::from_str(yaml).unwrap(),
+            ..Policy::default()
+        }
+    }
+
+    /// Policy files in a temp dir, `None` leaves that file out
+    fn load_policy(admin_contents: Option<&str>, user_contents: Option<&str>) -> Policy {
+        config::init();
+        let dir = tempfile::tempdir().unwrap();
+        let admin_path = dir.path().join("admin.yml");
+        let app_dirs = AppDirs {
+            app_config: dir.path().to_path_buf(),
+            ..AppDirs::default()
+        };
+        if let Some(contents) = admin_contents {
+            fs::write(&admin_path, contents).unwrap();
+        }
+        if let Some(contents) = user_contents {
+            fs::write(dir.path().join(Policy::POLICY_FILE), contents).unwrap();
+        }
+
+        Policy::load(&admin_path, &app_dirs)
+    }
+
+    #[test]
+    fn no_policy_allows_everything() {
+        let policy = Policy::default();
+
+        assert!(policy.check_url("https://example.com/").is_ok());
+        assert!(!policy.get_force_isolation());
+    }
+
+    #[test]
+    fn only_allowlisted_domains_are_allowed() {
+        let policy = build_policy("allowlist: [example.com, '*.example.org']");
+
+        assert!(policy.check_url("https://example.com/mail").is_ok());
+        assert!(policy.check_url("https://mail.example.org/").is_ok());
+        assert!(policy.check_url("https://example.org/").is_err());
+        assert!(policy.check_url("https://mail.example.com/").is_err());
+    }
+
+    #[test]
+    fn blocklist_wins_over_the_allowlist() {
+        let policy = build_policy(
+            "allowlist: ['*.example.com']\nblocklist: [ads.example.com]\nforce_isolation: true",
+        );
+
+        assert!(policy.check_url("https://mail.example.com/").is_ok());
+        assert!(policy.check_url("https://ads.example.com/").is_err());
+        assert!(policy.get_force_isolation());
+    }
+
+    #[test]
+    fn invalid_url_is_not_allowed() {
+        assert!(Policy::default().check_url("not a url").is_err());
+    }
+
+    #[test]
+    fn no_policy_file_allows_everything() {
+        let policy = load_policy(None, None);
+
+        assert!(policy.get_path().is_none());
+        assert!(policy.check_domain("example.com").is_ok());
+    }
+
+    #[test]
+    fn user_policy_file_is_loaded() {
+        let policy = load_policy(None, Some("blocklist: [example.com]"));
+
+        assert!(
+            policy
+                .get_path()
+                .is_some_and(|path| path.ends_with(Policy::POLICY_FILE))
+        );
+        assert!(policy.get_load_error().is_none());
+        assert!(policy.check_domain("example.com").is_err());
+        assert!(policy.check_domain("example.org").is_ok());
+    }
+
+    #[test]
+    fn admin_policy_file_goes_before_the_user_policy_file() {
+        let policy = load_policy(
+            Some("blocklist: [example.org]\ndisable_custom_args: true"),
+            Some("blocklist: [example.com]"),
+        );
+
+        assert!(
+            policy
+                .get_path()
+                .is_some_and(|path| path.ends_with("admin.yml"))
+        );
+        assert!(policy.check_domain("example.com").is_ok());
+        assert!(policy.check_domain("example.org").is_err());
+        assert!(policy.get_disable_custom_args());
+    }
+
+    #[test]
+    fn custom_args_are_allowed_by_default() {
+        assert!(!load_policy(None, Some("force_isolation: true")).get_disable_custom_args());
+    }
+
+    #[test]
+    fn malformed_allowlist_blocks_everything() {
+        let policy = load_policy(None, Some("allowlist: [example.com"));
+
+        assert!(policy.get_load_error().is_some());
+        assert!(policy.check_domain("example.com").is_err());
+    }
+
+    #[test]
+    fn malformed_blocklist_blocks_nothing() {
+        let policy = load_policy(None, Some("blocklist: [example.com"));
+
+        assert!(policy.get_load_error().is_some());
+        assert!(policy.check_domain("example.com").is_ok());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::{Browser, BrowserConfigs},
+    desktop_file::{DesktopFile, category::Category},
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, rc::Rc};
+use tracing::{debug, error, instrument};
+
+/// Order of the web app list
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AppSort {
+    #[default]
+    Name,
+    /// Last launch from this app first, apps that were never launched go last by name
+    RecentlyUsed,
+}
+impl AppSort {
+    pub fn get_all() -> [Self; 2] {
+        [Self::Name, Self::RecentlyUsed]
+    }
+}
+
+/// Defaults for new web apps
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PreferencesYaml {
+    /// Browser id, the first installed browser is used when it is not installed
+    #[serde(default)]
+    pub default_browser: Option<String>,
+    #[serde(default)]
+    pub default_isolated: bool,
+    #[serde(default)]
+    pub default_maximized: bool,
+    /// `Categories` value like "AudioVideo;Audio"
+    #[serde(default)]
+    pub default_category: Option<String>,
+    /// Open the page shown when the window closed, instead of Home or Web Apps
+    #[serde(default = "PreferencesYaml::default_restore_last_page")]
+    pub restore_last_page: bool,
+    #[serde(default)]
+    pub app_sort: AppSort,
+    /// Last launch and launch count below the name in the web app list
+    #[serde(default = "PreferencesYaml::default_show_app_usage")]
+    pub show_app_usage: bool,
+}
+impl Default for PreferencesYaml {
+    fn default() -> Self {
+        Self {
+            default_browser: None,
+            default_isolated: false,
+            default_maximized: false,
+            default_category: None,
+            restore_last_page: Self::default_restore_last_page(),
+            app_sort: AppSort::default(),
+            show_app_usage: Self::default_show_app_usage(),
+        }
+    }
+}
+impl PreferencesYaml {
+    fn default_restore_last_page() -> bool {
+        true
+    }
+
+    fn default_show_app_usage() -> bool {
+        true
+    }
+}
+
+/// In the config dir but not a shipped file, so updates and a reset of the config files keep it
+#[derive(Debug)]
+pub struct Preferences {
+    pub settings: PreferencesYaml,
+    settings_path: PathBuf,
+}
+impl Preferences {
+    const PREFERENCES_FILE: &str = "preferences.yml";
+
+    pub fn new(app_dirs: &AppDirs) -> Self {
+        let settings_path = app_dirs.app_config.join(Self::PREFERENCES_FILE);
+
+        let yaml_string = fs::read_to_string(&settings_path).unwrap_or_default();
+        let settings: PreferencesYaml = serde_yaml::from_str(&yaml_string)
+            .inspect_err(
+                |error| error!(%error, path = %settings_path.display(), "Failed to parse preferences yaml file"),
+            )
+            .unwrap_or_default();
+
+        Self {
+            settings,
+            settings_path,
+        }
+    }
+
+    /// Falls back to the first installed browser when the default is gone
+    pub fn get_default_browser(&self, browser_configs: &BrowserConfigs) -> Option<Rc<Browser>> {
+        let installed_browsers: Vec<Rc<Browser>> = browser_configs
+            .get_all_browsers()
+            .into_iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .collect();
+
+        if let Some(browser_id) = &self.settings.default_browser {
+            if let Some(browser) = installed_browsers
+                .iter()
+                .find(|browser| &browser.id == browser_id)
+            {
+                return Some(browser.clone());
+            }
+            debug!(
+                browser_id,
+                "Default browser is not installed, using the first browser"
+            );
+        }
+
+        installed_browsers.first().cloned()
+    }
+
+    pub fn get_default_category(&self) -> Option<Category> {
+        self.settings
+            .default_category
+            .as_ref()
+            .and_then(|categories| Category::from_categories(categories))
+    }
+
+    /// Options the browser can't do are left off
+    pub fn apply_defaults(&self, browser_configs: &BrowserConfigs, desktop_file: &mut DesktopFile) {
+        if let Some(browser) = self.get_default_browser(browser_configs) {
+            desktop_file.set_isolated(self.settings.default_isolated && browser.can_isolate);
+            desktop_file
+                .set_maximized(self.settings.default_maximized && browser.can_start_maximized);
+            desktop_file.set_browser(&browser);
+        }
+        if let Some(category) = self.get_default_category() {
+            desktop_file.set_category(&category);
+        }
+    }
+
+    #[instrument(err, skip(self))]
+    pub fn save(&self) -> Result<()> {
+        debug!("Saving preferences");
+
+        let dir_path = self.settings_path.parent().context(format!(
+            "Failed to get parent of preferences path: {}",
+            self.settings_path.display()
+        ))?;
+
+        if !dir_path.is_dir() {
+            fs::create_dir_all(dir_path).context(format!(
+                "Failed to create config dir for app: {}",
+                dir_path.display()
+            ))?;
+        }
+
+        let yaml_string = serde_yaml::to_string(&self.settings)
+            .context("Failed to parse preferences to yaml string")?;
+
+        fs::write(&self.settings_path, &yaml_string).context(format!(
+            "Failed to write preferences file: {}",
+            self.settings_path.display()
+        ))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets;
+
+    #[test]
+    fn reset_of_the_config_files_keeps_the_preferences() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = AppDirs {
+            app_data: dir.path().join("data"),
+            app_config: dir.path().join("config"),
+            ..AppDirs::default()
+        };
+        assets::init(&app_dirs).unwrap();
+        let mut preferences = Preferences::new(&app_dirs);
+        preferences.settings.default_isolated = true;
+        preferences.settings.app_sort = AppSort::RecentlyUsed;
+        preferences.save().unwrap();
+
+        assets::reset_config_files(&app_dirs).unwrap();
+
+        let preferences = Preferences::new(&app_dirs);
+        assert!(preferences.settings.default_isolated);
+        assert_eq!(preferences.settings.app_sort, AppSort::RecentlyUsed);
+    }
+}
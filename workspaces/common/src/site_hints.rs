@@ -0,0 +1,219 @@
+use crate::{
+    app_dirs::AppDirs,
+    url::{self},
+    utils::{self},
+};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{cell::RefCell, collections::HashMap, fs, path::Path, rc::Rc};
+use tracing::{debug, error};
+
+/// Suggestions for a new web app on a known site, unknown fields are ignored
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SiteHint {
+    /// `example.com` or `*.example.com` for subdomains
+    #[serde(default)]
+    pub domains: Vec<String>,
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub isolate: Option<bool>,
+    /// Per language code, like browser issues
+    #[serde(default)]
+    pub note: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct SiteHintsYaml {
+    #[serde(default)]
+    hints: Vec<SiteHint>,
+}
+
+pub struct SiteHints {
+    hints: RefCell<Vec<SiteHint>>,
+    app_dirs: Rc<AppDirs>,
+}
+impl SiteHints {
+    /// Shipped with the app, extracted on every start
+    const DEFAULT_FILE: &str = "default.yml";
+
+    pub fn new(app_dirs: &Rc<AppDirs>) -> Rc<Self> {
+        Rc::new(Self {
+            hints: RefCell::new(Vec::new()),
+            app_dirs: app_dirs.clone(),
+        })
+    }
+
+    pub fn init(self: &Rc<Self>) {
+        *self.hints.borrow_mut() = self.get_hints_from_files();
+    }
+
+    /// Most specific domain match, on equal matches the last loaded (user) hint wins
+    pub fn get_hint(&self, domain: &str) -> Option<SiteHint> {
+        Self::find_hint(&self.hints.borrow(), domain).cloned()
+    }
+
+    pub fn find_hint<'a>(hints: &'a [SiteHint], domain: &str) -> Option<&'a SiteHint> {
+        hints
+            .iter()
+            .filter_map(|hint| {
+                hint.domains
+                    .iter()
+                    .filter_map(|pattern| url::get_domain_pattern_match(pattern, domain))
+                    .max()
+                    .map(|match_len| (match_len, hint))
+            })
+            .max_by_key(|(match_len, _)| *match_len)
+            .map(|(_, hint)| hint)
+    }
+
+    fn get_hints_from_files(&self) -> Vec<SiteHint> {
+        debug!("Loading site hint files");
+
+        let mut files = utils::files::get_entries_in_dir(&self.app_dirs.app_config_site_hints)
+            .unwrap_or_default();
+        // Shipped hints first, so user files win on equal matches
+        files.sort_by_key(|file| {
+            let file_name = file.file_name().to_string_lossy().to_string();
+            (file_name != Self::DEFAULT_FILE, file_name)
+        });
+
+        let mut hints = Vec::new();
+
+        for file in &files {
+            let file_name = file.file_name().to_string_lossy().to_string();
+            let file_path = file.path();
+            let extension = file_path.extension().unwrap_or_default().to_string_lossy();
+
+            if extension != "yml" && extension != "yaml" {
+                debug!("Not a yml file: '{file_name}'");
+                continue;
+            }
+
+            match Self::read_hints_yaml(&file_path) {
+                Ok(hints_yaml) => {
+                    debug!(
+                        count = hints_yaml.hints.len(),
+                        "Loaded site hints: '{file_name}'"
+                    );
+                    hints.extend(hints_yaml.hints);
+                }
+                Err(error) => {
+                    error!("Failed to parse yml: '{file_name}'. Error: '{error:?}'");
+                }
+            }
+        }
+
+        hints
+    }
+
+    fn read_hints_yaml(file_path: &Path) -> Result<SiteHintsYaml> {
+        let file_string = fs::read_to_string(file_path).context(format!(
+            "Failed to read to string: '{}'",
+            file_path.display()
+        ))?;
+        let hints_yaml = serde_yaml::from_str(&file_string)?;
+
+        Ok(hints_yaml)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_hints(files: &[(&str, &str)]) -> Rc<SiteHints> {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dirs = Rc::new(AppDirs {
+            app_config_site_hints: dir.path().to_path_buf(),
+            ..AppDirs::default()
+        });
+        for (file_name, contents) in files {
+            fs::write(dir.path().join(file_name), contents).unwrap();
+        }
+
+        let site_hints = SiteHints::new(&app_dirs);
+        site_hints.init();
+        site_hints
+    }
+
+    #[test]
+    fn shipped_hints_parse() {
+        let shipped_hints = include_str!("../../../assets/config/site-hints/default.yml");
+
+        let site_hints = load_hints(&[(SiteHints::DEFAULT_FILE, shipped_hints)]);
+
+        assert_eq!(
+            site_hints
+                .get_hint("app.slack.com")
+                .unwrap()
+                .name
+                .as_deref(),
+            Some("Slack")
+        );
+    }
+
+    #[test]
+    fn most_specific_hint_wins() {
+        let site_hints = load_hints(&[(
+            "hints.yml",
+            "hints:\n\
+            - domains: ['*.example.com']\n  name: Example\n\
+            - domains: [mail.example.com]\n  name: Mail\n",
+        )]);
+
+        assert_eq!(
+            site_hints
+                .get_hint("mail.example.com")
+                .unwrap()
+                .name
+                .as_deref(),
+            Some("Mail")
+        );
+        assert_eq!(
+            site_hints
+                .get_hint("docs.example.com")
+                .unwrap()
+                .name
+                .as_deref(),
+            Some("Example")
+        );
+        assert!(site_hints.get_hint("example.com").is_none());
+    }
+
+    #[test]
+    fn user_hint_wins_over_the_shipped_hint() {
+        let site_hints = load_hints(&[
+            (
+                "a-user.yml",
+                "hints:\n- domains: [example.com]\n  name: Mine\n",
+            ),
+            (
+                SiteHints::DEFAULT_FILE,
+                "hints:\n- domains: [example.com]\n  name: Shipped\n",
+            ),
+        ]);
+
+        assert_eq!(
+            site_hints.get_hint("example.com").unwrap().name.as_deref(),
+            Some("Mine")
+        );
+    }
+
+    #[test]
+    fn broken_and_other_files_are_skipped() {
+        let site_hints = load_hints(&[
+            ("broken.yml", "hints: [domains: "),
+            ("notes.txt", "hints:\n- domains: [notes.example.com]\n"),
+            (
+                "hints.yaml",
+                "hints:\n- domains: [example.com]\n  isolate: true\n  icon: mail.png\n",
+            ),
+        ]);
+
+        let hint = site_hints.get_hint("example.com").unwrap();
+
+        assert_eq!(hint.isolate, Some(true));
+        assert!(hint.name.is_none());
+        assert!(site_hints.get_hint("notes.example.com").is_none());
+    }
+}
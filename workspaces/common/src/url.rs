@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use regex::Regex;
 use url::Url;
 
 pub trait UrlExt {
@@ -39,3 +40,78 @@ impl UrlExt for Url {
         self_mut_clone
     }
 }
+
+/// Match a domain against `example.com` or `*.example.com` (subdomains only).
+/// Returns how specific the match is, an exact match wins over a wildcard on the same domain.
+pub fn get_domain_pattern_match(pattern: &str, domain: &str) -> Option<usize> {
+    let normalize = |value: &str| value.trim().trim_end_matches('.').to_lowercase();
+    let pattern = normalize(pattern);
+    let domain = normalize(domain);
+
+    match pattern.strip_prefix("*.") {
+        Some(parent_domain) => domain
+            .ends_with(&format!(".{parent_domain}"))
+            .then_some(parent_domain.len()),
+        None => (domain == pattern).then_some(pattern.len() + 1),
+    }
+}
+
+/// Url typed by the user, a bare domain like `example.com/path` gets `https://`.
+/// The host is lowercased and an IDN is stored as punycode, the path and query are kept.
+pub fn parse_user_input(input: &str) -> Option<Url> {
+    let input = input.trim();
+    let has_host = |url: &Url| url.host_str().is_some_and(|host| !host.is_empty());
+
+    if let Ok(url) = Url::parse(input)
+        && has_host(&url)
+    {
+        return Some(url);
+    }
+
+    let bare_domain_re =
+        Regex::new(r"^(localhost|[\p{L}\p{N}-]+(\.[\p{L}\p{N}-]+)+)(:\d{1,5})?([/?#]\S*)?$")
+            .ok()?;
+    if !bare_domain_re.is_match(input) {
+        return None;
+    }
+
+    Url::parse(&format!("https://{input}"))
+        .ok()
+        .filter(has_host)
+}
+
+/// Like `parse_user_input`, only an http or https url can be a web app
+pub fn parse_web_input(input: &str) -> Option<Url> {
+    parse_user_input(input).filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_pattern_matches_only_the_domain() {
+        assert!(get_domain_pattern_match("example.com", "example.com").is_some());
+        assert!(get_domain_pattern_match("Example.COM.", " example.com").is_some());
+        assert!(get_domain_pattern_match("example.com", "mail.example.com").is_none());
+        assert!(get_domain_pattern_match("example.com", "example.org").is_none());
+    }
+
+    #[test]
+    fn wildcard_pattern_matches_only_subdomains() {
+        assert!(get_domain_pattern_match("*.example.com", "mail.example.com").is_some());
+        assert!(get_domain_pattern_match("*.example.com", "a.b.example.com").is_some());
+        assert!(get_domain_pattern_match("*.example.com", "example.com").is_none());
+        assert!(get_domain_pattern_match("*.example.com", "badexample.com").is_none());
+    }
+
+    #[test]
+    fn exact_match_is_more_specific_than_a_wildcard() {
+        let exact = get_domain_pattern_match("app.slack.com", "app.slack.com").unwrap();
+        let wildcard = get_domain_pattern_match("*.slack.com", "app.slack.com").unwrap();
+        let wider_wildcard = get_domain_pattern_match("*.com", "app.slack.com").unwrap();
+
+        assert!(exact > wildcard);
+        assert!(wildcard > wider_wildcard);
+    }
+}
@@ -3,9 +3,11 @@ use std::sync::OnceLock;
 pub mod files {
     use anyhow::{Context, Result, bail};
     use std::{
-        fs::{self, DirEntry},
+        fs::{self, DirEntry, File},
+        io::Write,
         os,
         path::Path,
+        time::{Duration, SystemTime},
     };
     use tracing::debug;
 
@@ -17,6 +19,69 @@ pub mod files {
             .map_err(std::convert::Into::into)
     }
 
+    /// Total size in bytes of all files in a dir, symlinks are not followed
+    pub fn get_dir_size(dir: &Path) -> u64 {
+        get_entries_in_dir(dir)
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| {
+                let path = entry.path();
+                if path.is_symlink() {
+                    0
+                } else if path.is_dir() {
+                    get_dir_size(&path)
+                } else {
+                    entry.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+                }
+            })
+            .sum()
+    }
+
+    /// Write to a temp file next to the target and rename it, readers never see partial content.
+    /// A symlink is kept, the file it points to is replaced instead.
+    /// On failure the existing file is left untouched.
+    pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+        let path = if path.is_symlink() {
+            fs::canonicalize(path)
+                .context(format!("Failed to resolve symlink: {}", path.display()))?
+        } else {
+            path.to_path_buf()
+        };
+        let file_name = path
+            .file_name()
+            .context(format!("No file name in path: {}", path.display()))?;
+        let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+        // Synced before the rename, a full disk must fail here and not after the swap
+        let write_result = File::create(&temp_path)
+            .and_then(|mut file| file.write_all(contents).and_then(|()| file.sync_all()));
+        if let Err(error) = write_result {
+            let _ = fs::remove_file(&temp_path);
+            bail!(
+                "Failed to write temp file: {}. Error: {error:?}",
+                temp_path.display()
+            );
+        }
+        if let Err(error) = fs::rename(&temp_path, &path) {
+            let _ = fs::remove_file(&temp_path);
+            bail!(
+                "Failed to move temp file to: {}. Error: {error:?}",
+                path.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// File has been modified within the duration, it might still be written to
+    pub fn is_recently_modified(path: &Path, within: Duration) -> bool {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|elapsed| elapsed < within)
+    }
+
     pub fn create_symlink(symlink_path: &Path, target: &Path) -> Result<()> {
         let mut target = target.to_path_buf();
         let a = symlink_path.display().to_string();
@@ -62,10 +127,282 @@ pub mod files {
         debug!(symlink_path = a, target = b, "Already a symlink");
         Ok(())
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn file_is_written_and_replaced() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("app.desktop");
+
+            write_atomic(&path, b"first").unwrap();
+            write_atomic(&path, b"second").unwrap();
+
+            assert_eq!(fs::read(&path).unwrap(), b"second");
+            assert_eq!(get_entries_in_dir(dir.path()).unwrap().len(), 1);
+        }
+
+        #[test]
+        fn symlink_is_kept_and_its_target_is_written() {
+            let dir = tempfile::tempdir().unwrap();
+            let target_path = dir.path().join("target.desktop");
+            let symlink_path = dir.path().join("link.desktop");
+            fs::write(&target_path, b"old").unwrap();
+            os::unix::fs::symlink(&target_path, &symlink_path).unwrap();
+
+            write_atomic(&symlink_path, b"new").unwrap();
+
+            assert!(symlink_path.is_symlink());
+            assert_eq!(fs::read_link(&symlink_path).unwrap(), target_path);
+            assert_eq!(fs::read(&target_path).unwrap(), b"new");
+        }
+
+        #[test]
+        fn failed_write_keeps_the_original() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("app.desktop");
+            fs::write(&path, b"original").unwrap();
+            // The temp file can't be created where a dir is
+            fs::create_dir(dir.path().join(".app.desktop.tmp")).unwrap();
+
+            assert!(write_atomic(&path, b"new").is_err());
+            assert_eq!(fs::read(&path).unwrap(), b"original");
+        }
+
+        #[test]
+        fn failed_rename_removes_the_temp_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("app.desktop");
+            fs::create_dir(&path).unwrap();
+            fs::write(path.join("kept"), b"").unwrap();
+
+            assert!(write_atomic(&path, b"new").is_err());
+            assert!(path.join("kept").is_file());
+            assert!(!dir.path().join(".app.desktop.tmp").exists());
+        }
+
+        #[test]
+        fn only_a_new_file_is_recently_modified() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("firefox.yml");
+            fs::write(&path, b"").unwrap();
+
+            assert!(is_recently_modified(&path, Duration::from_secs(60)));
+
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(120))
+                .unwrap();
+
+            assert!(!is_recently_modified(&path, Duration::from_secs(60)));
+            assert!(!is_recently_modified(
+                &dir.path().join("missing.yml"),
+                Duration::from_secs(60)
+            ));
+        }
+    }
+}
+
+/// Minimal tar (ustar with GNU long names) for bundles and backups, regular files only
+pub mod archive {
+    use anyhow::{Context, Result, bail};
+    use std::{
+        fs::{self, File},
+        io::{self, Read, Write},
+        ops::ControlFlow,
+        path::{Component, Path, PathBuf},
+    };
+
+    const BLOCK_SIZE: usize = 512;
+    const NAME_SIZE: usize = 100;
+    const LONG_NAME: &str = "././@LongLink";
+
+    pub struct TarWriter<W: Write> {
+        writer: W,
+    }
+    impl<W: Write> TarWriter<W> {
+        pub fn new(writer: W) -> Self {
+            Self { writer }
+        }
+
+        pub fn append(&mut self, name: &str, content: &[u8]) -> Result<()> {
+            self.append_reader(name, u64::try_from(content.len())?, content)
+        }
+
+        pub fn append_file(&mut self, name: &str, path: &Path) -> Result<()> {
+            let file = File::open(path).context(format!("Failed to open: '{}'", path.display()))?;
+            let size = file.metadata()?.len();
+
+            self.append_reader(name, size, file)
+        }
+
+        /// All files in the dir and sub dirs, symlinks are skipped
+        pub fn append_dir(&mut self, name: &str, path: &Path) -> Result<()> {
+            for entry in
+                fs::read_dir(path).context(format!("Failed to read: '{}'", path.display()))?
+            {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                let entry_name = format!("{name}/{}", entry.file_name().to_string_lossy());
+
+                if file_type.is_dir() {
+                    self.append_dir(&entry_name, &entry.path())?;
+                } else if file_type.is_file() {
+                    self.append_file(&entry_name, &entry.path())?;
+                }
+            }
+
+            Ok(())
+        }
+
+        pub fn finish(mut self) -> Result<W> {
+            self.writer.write_all(&[0; BLOCK_SIZE * 2])?;
+            self.writer.flush()?;
+
+            Ok(self.writer)
+        }
+
+        fn append_reader(&mut self, name: &str, size: u64, reader: impl Read) -> Result<()> {
+            let mut header_name = name;
+            if name.len() >= NAME_SIZE {
+                let long_name = format!("{name}\0");
+                self.write_header(LONG_NAME, u64::try_from(long_name.len())?, b'L')?;
+                self.writer.write_all(long_name.as_bytes())?;
+                self.write_padding(u64::try_from(long_name.len())?)?;
+
+                let mut end = NAME_SIZE - 1;
+                while !name.is_char_boundary(end) {
+                    end -= 1;
+                }
+                header_name = &name[..end];
+            }
+
+            self.write_header(header_name, size, b'0')?;
+            let written = io::copy(&mut reader.take(size), &mut self.writer)?;
+            if written != size {
+                bail!("File changed while adding it to the archive: '{name}'")
+            }
+            self.write_padding(size)
+        }
+
+        fn write_header(&mut self, name: &str, size: u64, type_flag: u8) -> Result<()> {
+            let mut header = [0_u8; BLOCK_SIZE];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[100..108].copy_from_slice(b"0000644\0");
+            header[108..116].copy_from_slice(b"0000000\0");
+            header[116..124].copy_from_slice(b"0000000\0");
+            header[124..136].copy_from_slice(format!("{size:011o}\0").as_bytes());
+            header[136..148].copy_from_slice(b"00000000000\0");
+            header[156] = type_flag;
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[263..265].copy_from_slice(b"00");
+
+            // Checksum is calculated with the checksum field as spaces
+            header[148..156].copy_from_slice(b"        ");
+            let checksum: u32 = header.iter().map(|byte| u32::from(*byte)).sum();
+            header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+
+            self.writer.write_all(&header)?;
+            Ok(())
+        }
+
+        fn write_padding(&mut self, size: u64) -> Result<()> {
+            let padding = get_padding(size);
+            self.writer.write_all(&vec![0; usize::try_from(padding)?])?;
+            Ok(())
+        }
+    }
+
+    /// Calls `on_entry` for every regular file with a reader of only that file,
+    /// until it breaks or the archive ends
+    pub fn read_entries(
+        mut reader: impl Read,
+        mut on_entry: impl FnMut(&str, &mut dyn Read) -> Result<ControlFlow<()>>,
+    ) -> Result<()> {
+        let mut header = [0_u8; BLOCK_SIZE];
+        let mut long_name: Option<String> = None;
+
+        loop {
+            reader
+                .read_exact(&mut header)
+                .context("Not a valid archive")?;
+            if header.iter().all(|byte| *byte == 0) {
+                break;
+            }
+
+            let name_end = header[..NAME_SIZE]
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(NAME_SIZE);
+            let header_name = String::from_utf8_lossy(&header[..name_end]).to_string();
+            let size_field = String::from_utf8_lossy(&header[124..136]);
+            let size = u64::from_str_radix(size_field.trim_matches(['\0', ' ']), 8)
+                .context(format!("Invalid size of '{header_name}' in archive"))?;
+
+            let mut entry = reader.by_ref().take(size);
+            match header[156] {
+                b'L' => {
+                    let mut name = Vec::new();
+                    entry.read_to_end(&mut name)?;
+                    long_name = Some(
+                        String::from_utf8_lossy(&name)
+                            .trim_end_matches('\0')
+                            .to_string(),
+                    );
+                }
+                b'0' | 0 => {
+                    let name = long_name.take().unwrap_or(header_name);
+                    if on_entry(&name, &mut entry)?.is_break() {
+                        break;
+                    }
+                }
+                // Folders and links are not used
+                _ => {
+                    long_name = None;
+                }
+            }
+
+            // Skip what the entry handler didn't read
+            io::copy(&mut entry, &mut io::sink())?;
+            if entry.limit() != 0 {
+                bail!("Archive is truncated")
+            }
+            io::copy(
+                &mut reader.by_ref().take(get_padding(size)),
+                &mut io::sink(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Entry names end up on disk, only plain relative names are allowed
+    pub fn get_safe_path(root: &Path, name: &str) -> Option<PathBuf> {
+        let relative = Path::new(name);
+        let is_safe = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        if is_safe && !name.is_empty() {
+            Some(root.join(relative))
+        } else {
+            None
+        }
+    }
+
+    fn get_padding(size: u64) -> u64 {
+        let block_size = BLOCK_SIZE as u64;
+        (block_size - size % block_size) % block_size
+    }
 }
 
 pub mod env {
     use anyhow::Context;
+    use gtk::glib;
     use std::{env, str::FromStr};
     use tracing::Level;
 
@@ -95,6 +432,34 @@ pub mod env {
         env::var("container").is_ok_and(|value| value == "flatpak")
     }
 
+    /// The `filesystems` the sandbox was started with, including overrides of the user.
+    /// Empty outside the sandbox.
+    pub fn get_flatpak_filesystems() -> Vec<String> {
+        let key_file = glib::KeyFile::new();
+        if key_file
+            .load_from_file("/.flatpak-info", glib::KeyFileFlags::NONE)
+            .is_err()
+        {
+            return Vec::new();
+        }
+
+        key_file
+            .string_list("Context", "filesystems")
+            .map(|filesystems| {
+                filesystems
+                    .iter()
+                    .map(|filesystem| filesystem.as_str().to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Browsers are detected with `flatpak info` and `which` like before, for when the
+    /// installation dirs are somewhere else
+    pub fn is_command_detection_forced() -> bool {
+        env::var("WAH_DETECT_WITH_COMMANDS").is_ok()
+    }
+
     pub fn get_language() -> Option<String> {
         env::var("LANG").ok().and_then(|language| {
             language
@@ -141,12 +506,33 @@ pub mod log {
 }
 
 pub mod command {
-    use crate::utils::env;
+    use crate::{
+        config,
+        utils::{OnceLockExt, env},
+    };
     use anyhow::{Result, bail};
-    use gtk::glib;
-    use std::{fmt::Write, process::Command};
+    use gtk::{
+        gio::{self, SubprocessFlags},
+        glib,
+    };
+    use rand::{Rng, distributions::Alphanumeric};
+    use std::{
+        ffi::{OsStr, OsString},
+        fmt::Write,
+        fs, io,
+        os::fd::OwnedFd,
+        path::{Path, PathBuf},
+        process::Command,
+        sync::OnceLock,
+        time::Duration,
+    };
     use tracing::debug;
 
+    /// Only the end of stderr is kept, a browser can log a lot while it runs
+    const STDERR_TAIL_MAX_BYTES: usize = 4096;
+    /// The tail is written once the command closes stderr, right after it exits
+    const STDERR_TAIL_WAIT: Duration = Duration::from_secs(1);
+
     pub struct Response {
         pub success: bool,
         pub status: i32,
@@ -154,6 +540,24 @@ pub mod command {
         pub stderr: String,
     }
 
+    pub enum StartupStatus {
+        /// Still running after the watch, or done without an error
+        Started,
+        Failed {
+            stderr_tail: String,
+        },
+    }
+
+    /// A started command that does not depend on this process, it keeps running when the app
+    /// quits. Stderr goes to a `tail` of its own instead of a pipe to the app, a pipe closed
+    /// by a quitting app would end the command. The `tail` only keeps the last bytes in memory
+    /// and writes them to a file when stderr closes, so a long running browser fills nothing.
+    pub struct BackgroundCommand {
+        subprocess: gio::Subprocess,
+        stderr_tail: gio::Subprocess,
+        stderr_path: PathBuf,
+    }
+
     pub fn test_command_available_sync(command: &str) -> bool {
         let run_command = format!("which {command}");
         let run_command = run_command.trim();
@@ -169,17 +573,116 @@ pub mod command {
         true
     }
 
-    pub fn run_command_background(command: &str) -> Result<()> {
+    /// Pass the command to `watch_startup`, it removes the stderr file again
+    pub fn run_command_background(command: &str) -> Result<BackgroundCommand> {
         let mut run_command = String::new();
 
         if env::is_flatpak_container() {
             write!(run_command, "flatpak-spawn --host")?;
         }
+        // A session of its own, signals to the session of the app don't reach the command.
+        // It keeps the pid, so the exit status is still the one of the command.
+        if is_setsid_available() {
+            write!(run_command, " setsid")?;
+        }
         write!(run_command, " {command}")?;
         let run_command = run_command.trim();
 
-        debug!(command = run_command, "Running background command");
-        glib::spawn_command_line_async(run_command).map_err(Into::into)
+        let args = glib::shell_parse_argv(run_command)?;
+        if args.is_empty() {
+            bail!("Incorrect command")
+        }
+        let args: Vec<&OsStr> = args.iter().map(OsString::as_os_str).collect();
+
+        let stderr_path = build_stderr_path();
+        let (stderr_reader, stderr_writer) = io::pipe()?;
+        let stderr_tail = spawn_stderr_tail(stderr_reader.into(), &stderr_path)?;
+
+        // The launcher owns the write end, only the command keeps it open after the spawn
+        let launcher = gio::SubprocessLauncher::new(SubprocessFlags::NONE);
+        launcher.take_stderr_fd(Some(stderr_writer.into()));
+
+        debug!(
+            command = run_command,
+            stderr_path = %stderr_path.display(),
+            "Running background command"
+        );
+        let subprocess = launcher.spawn(&args).inspect_err(|_| {
+            let _ = fs::remove_file(&stderr_path);
+        })?;
+
+        Ok(BackgroundCommand {
+            subprocess,
+            stderr_tail,
+            stderr_path,
+        })
+    }
+
+    /// Runs in the sandbox next to the app, in a session of its own like the command
+    fn spawn_stderr_tail(stderr: OwnedFd, stderr_path: &Path) -> Result<gio::Subprocess> {
+        let max_bytes = STDERR_TAIL_MAX_BYTES.to_string();
+        let mut args = vec!["tail", "-c", &max_bytes];
+        if !env::is_flatpak_container() && is_setsid_available() {
+            args.insert(0, "setsid");
+        }
+        let args: Vec<&OsStr> = args.into_iter().map(OsStr::new).collect();
+
+        let launcher = gio::SubprocessLauncher::new(SubprocessFlags::NONE);
+        launcher.take_stdin_fd(Some(stderr));
+        launcher.set_stdout_file_path(Some(stderr_path));
+
+        Ok(launcher.spawn(&args)?)
+    }
+
+    /// Waits up to `window` for the command to fail, without blocking the main loop
+    pub async fn watch_startup(
+        background_command: &BackgroundCommand,
+        window: Duration,
+    ) -> StartupStatus {
+        let subprocess = &background_command.subprocess;
+        let has_exited = glib::future_with_timeout(window, subprocess.wait_future())
+            .await
+            .is_ok_and(|wait_result| wait_result.is_ok());
+
+        let startup_status = if !has_exited || subprocess.is_successful() {
+            StartupStatus::Started
+        } else {
+            // A child the command left running can hold stderr open, the tail is empty then
+            let _ = glib::future_with_timeout(
+                STDERR_TAIL_WAIT,
+                background_command.stderr_tail.wait_future(),
+            )
+            .await;
+            let stderr = fs::read(&background_command.stderr_path).unwrap_or_default();
+            let stderr_tail = parse_output(&stderr);
+            debug!(stderr_tail, "Background command failed on startup");
+
+            StartupStatus::Failed { stderr_tail }
+        };
+
+        // A running tail keeps its open file, only the name is gone
+        let _ = fs::remove_file(&background_command.stderr_path);
+
+        startup_status
+    }
+
+    /// Checked once, inside the sandbox it is looked up on the host
+    fn is_setsid_available() -> bool {
+        static IS_SETSID_AVAILABLE: OnceLock<bool> = OnceLock::new();
+        *IS_SETSID_AVAILABLE.get_or_init(|| test_command_available_sync("setsid"))
+    }
+
+    fn build_stderr_path() -> PathBuf {
+        let suffix: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+
+        glib::tmp_dir().join(format!(
+            "{}-launch-{suffix}.log",
+            config::APP_NAME_SHORT.get_value()
+        ))
     }
 
     pub fn run_command_sync(command: &str) -> Result<Response> {
@@ -210,9 +713,145 @@ pub mod command {
         Ok(response)
     }
 
+    /// Desktop entry quoting, only when needed to keep simple commands readable
+    pub fn quote_exec_arg(arg: &str) -> String {
+        const RESERVED: &[char] = &[
+            ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#',
+            '(', ')', '`',
+        ];
+
+        if !arg.contains(RESERVED) {
+            return arg.to_string();
+        }
+
+        let mut quoted = String::from('"');
+        for char in arg.chars() {
+            if matches!(char, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(char);
+        }
+        quoted.push('"');
+        quoted
+    }
+
     pub fn parse_output(std_descriptor: &[u8]) -> String {
         String::from_utf8_lossy(std_descriptor).trim().to_string()
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// A context per test, the tests run on their own threads
+        fn watch(background_command: &BackgroundCommand, window: Duration) -> StartupStatus {
+            let context = glib::MainContext::new();
+            context
+                .with_thread_default(|| context.block_on(watch_startup(background_command, window)))
+                .unwrap()
+        }
+
+        /// Field 6 of `/proc/<pid>/stat`, the name in field 2 has no spaces here
+        fn get_session_id(pid: &str) -> Option<String> {
+            let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+            stat.split_whitespace().nth(5).map(str::to_string)
+        }
+
+        #[test]
+        fn failed_command_reports_the_end_of_stderr() {
+            config::init();
+            let background_command =
+                run_command_background("sh -c 'echo \"no display\" >&2; exit 1'").unwrap();
+
+            let startup_status = watch(&background_command, Duration::from_secs(5));
+
+            assert!(matches!(
+                startup_status,
+                StartupStatus::Failed { stderr_tail } if stderr_tail == "no display"
+            ));
+            assert!(!background_command.stderr_path.exists());
+        }
+
+        #[test]
+        fn stderr_of_a_failed_command_is_capped() {
+            config::init();
+            let background_command =
+                run_command_background("sh -c 'yes error | head -c 120000 >&2; exit 1'").unwrap();
+
+            let startup_status = watch(&background_command, Duration::from_secs(5));
+
+            assert!(matches!(
+                startup_status,
+                // The last newline is trimmed
+                StartupStatus::Failed { stderr_tail }
+                    if stderr_tail.ends_with("error\nerror")
+                        && stderr_tail.len() == STDERR_TAIL_MAX_BYTES - 1
+            ));
+        }
+
+        #[test]
+        fn successful_command_is_started() {
+            config::init();
+            let background_command = run_command_background("true").unwrap();
+
+            assert!(matches!(
+                watch(&background_command, Duration::from_secs(5)),
+                StartupStatus::Started
+            ));
+        }
+
+        #[test]
+        fn running_command_has_a_session_of_its_own() {
+            config::init();
+            let background_command = run_command_background("sleep 10").unwrap();
+            let subprocess = &background_command.subprocess;
+            let pid = subprocess.identifier().unwrap().to_string();
+
+            let startup_status = watch(&background_command, Duration::from_millis(200));
+
+            assert!(matches!(startup_status, StartupStatus::Started));
+            assert!(!background_command.stderr_path.exists());
+            if is_setsid_available() {
+                assert_eq!(get_session_id(&pid), Some(pid.clone()));
+            }
+            subprocess.force_exit();
+        }
+
+        #[test]
+        fn command_keeps_running_when_the_handle_is_dropped() {
+            config::init();
+            let background_command = run_command_background("sleep 10").unwrap();
+            let pid = background_command
+                .subprocess
+                .identifier()
+                .unwrap()
+                .to_string();
+            let tail_pid = background_command
+                .stderr_tail
+                .identifier()
+                .unwrap()
+                .to_string();
+
+            let startup_status = watch(&background_command, Duration::from_millis(200));
+            drop(background_command);
+            std::thread::sleep(Duration::from_millis(200));
+
+            assert!(matches!(startup_status, StartupStatus::Started));
+            assert!(Path::new(&format!("/proc/{pid}")).exists());
+            assert!(Path::new(&format!("/proc/{tail_pid}")).exists());
+            let _ = Command::new("kill").arg(&pid).status();
+        }
+
+        #[test]
+        fn only_reserved_characters_are_quoted() {
+            assert_eq!(quote_exec_arg("--kiosk"), "--kiosk");
+            assert_eq!(
+                quote_exec_arg("https://example.com/?a=1&b=$2"),
+                "\"https://example.com/?a=1&b=\\$2\""
+            );
+            assert_eq!(quote_exec_arg("My Mail"), "\"My Mail\"");
+        }
+    }
 }
 
 pub trait OnceLockExt<T> {
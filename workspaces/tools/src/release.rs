@@ -29,6 +29,24 @@ struct Args {
     /// Perform a dry run without making any git changes
     #[arg(long)]
     dry_run: bool,
+    /// Push the release commit and tag to this branch instead of the current branch
+    #[arg(long)]
+    release_branch: Option<String>,
+    /// Flathub repo directory, defaults to `external/<app_id>`
+    #[arg(long)]
+    flathub_repo: Option<PathBuf>,
+    /// Github org of the flathub repo, used for the SSH remote
+    #[arg(long, default_value = "flathub")]
+    flathub_org: String,
+    /// Remote to push the flathub branch to, overrides the org
+    #[arg(long)]
+    flathub_remote: Option<String>,
+    /// Base branch of the flathub PR, defaults to the repo default of `gh`
+    #[arg(long)]
+    pr_base: Option<String>,
+    /// Do not create a flathub release PR
+    #[arg(long)]
+    skip_flathub: bool,
 }
 
 fn main() -> Result<()> {
@@ -59,16 +77,22 @@ fn main() -> Result<()> {
     update_submodules()?;
     create_app_desktop_file()?;
     create_app_icon()?;
+    create_app_dbus_service_file()?;
 
     let (releases_xml, new_version) = generate_changelog()?;
     update_cargo_with_new_version(&new_version)?;
     update_flatpak_manifest(&new_version)?;
     create_app_metainfo_file(&releases_xml, &new_version)?;
     generate_cargo_sources()?;
-    create_release_in_git(&new_version)?;
+    create_release_in_git(&new_version, &args)?;
     validate_metainfo(false)?;
     build_release_flatpak()?;
-    create_flathub_release_pr(&new_version)?;
+
+    if args.skip_flathub {
+        info!("==== Skipping flathub release pr");
+    } else {
+        create_flathub_release_pr(&new_version, &args)?;
+    }
 
     info!("==== Finished release version {new_version}");
 
@@ -134,7 +158,8 @@ fn create_app_desktop_file() -> Result<()> {
     base_desktop_file.add_desktop_entry("Name".to_string(), app_name.clone());
     base_desktop_file.add_desktop_entry("Icon".to_string(), app_id.clone());
     base_desktop_file.add_desktop_entry("StartupWMClass".to_string(), app_id.clone());
-    base_desktop_file.add_desktop_entry("Exec".to_string(), bin_name.clone());
+    // Urls from the launcher or a dropped link open a new web app
+    base_desktop_file.add_desktop_entry("Exec".to_string(), format!("{bin_name} %U"));
     base_desktop_file.add_desktop_entry("Comment".to_string(), app_summary.clone());
 
     fs::write(&save_path, base_desktop_file.to_string()).inspect_err(|err| {
@@ -178,6 +203,35 @@ fn create_app_icon() -> Result<()> {
     Ok(())
 }
 
+/// Lets D-Bus start the app for a call to its interface
+fn create_app_dbus_service_file() -> Result<()> {
+    info!("==== Creating app D-Bus service file");
+
+    let app_id = config::APP_ID.get_value();
+    let bin_name = config::BIN_NAME.get_value();
+    let file_name = dbus_service_file_name();
+    let save_path = assets_desktop_path().join(file_name);
+
+    let service_file = format!(
+        "[D-BUS Service]\nName={app_id}\nExec=/app/bin/{bin_name} --gapplication-service\n"
+    );
+
+    fs::write(&save_path, service_file).inspect_err(|err| {
+        error!(
+            error = err.to_string(),
+            path = &save_path.to_string_lossy().to_string(),
+            "Failed to save D-Bus service file"
+        );
+    })?;
+
+    info!(
+        dbus_service_file = &save_path.to_string_lossy().to_string(),
+        "Created D-Bus service file:"
+    );
+
+    Ok(())
+}
+
 #[allow(clippy::too_many_lines)] // No exports of types from git_cliff...
 fn generate_changelog() -> Result<(String, Version)> {
     info!("==== Generating changelogs");
@@ -429,8 +483,15 @@ fn update_flatpak_manifest(new_version: &Version) -> Result<()> {
     let git_repository = &format!("{}.git", config::REPOSITORY.get_value());
     let git_tag = &format!("v{new_version}");
 
+    // Installs next to the stable build, `config` gives it its own id and dirs with `APP_DEVEL`
+    let devel_app_id = &format!("{app_id}{}", config::DEVEL_APP_ID_SUFFIX);
+    let devel_commands = &format!(
+        "- sed -i 's/{app_id}/{devel_app_id}/g' /app/share/metainfo/{devel_app_id}.metainfo.xml \
+         /app/share/applications/{devel_app_id}.desktop /app/share/dbus-1/services/{devel_app_id}.service"
+    );
+
     let mut manifest = FLATPAK_MANIFEST_IN.to_string();
-    manifest = manifest.replace("%{app_id}", app_id);
+    manifest = manifest.replace("%{base_app_id}", app_id);
     manifest = manifest.replace("%{app_name}", app_name);
     manifest = manifest.replace("%{app_name_dense}", app_name_dense);
     manifest = manifest.replace("%{app_name_short}", app_name_short);
@@ -438,6 +499,9 @@ fn update_flatpak_manifest(new_version: &Version) -> Result<()> {
     manifest = manifest.replace("%{bin_name}", bin_name);
 
     let mut manifest_dev = manifest.clone();
+    manifest_dev = manifest_dev.replace("%{app_id}", devel_app_id);
+    manifest_dev = manifest_dev.replace("%{devel_env}", "APP_DEVEL: \"1\"");
+    manifest_dev = manifest_dev.replace("%{devel_commands}", devel_commands);
     manifest_dev = manifest_dev.replace("%{sources_type}", "dir");
     manifest_dev = manifest_dev.replace("%{sources_location}", "path: ..");
     manifest_dev = manifest_dev.replace("%{git_tag}", "");
@@ -454,6 +518,9 @@ fn update_flatpak_manifest(new_version: &Version) -> Result<()> {
         );
     })?;
 
+    manifest = manifest.replace("%{app_id}", app_id);
+    manifest = manifest.replace("%{devel_env}", "");
+    manifest = manifest.replace("%{devel_commands}", "");
     manifest = manifest.replace("%{sources_type}", "git");
     manifest = manifest.replace("%{sources_location}", &format!("url: {git_repository}"));
     manifest = manifest.replace("%{git_tag}", &format!("tag: {git_tag}"));
@@ -671,24 +738,13 @@ fn generate_cargo_sources() -> Result<()> {
     Ok(())
 }
 
-fn create_release_in_git(new_version: &Version) -> Result<()> {
+fn create_release_in_git(new_version: &Version, args: &Args) -> Result<()> {
     info!("==== Creating release in git");
 
-    let version = format!("v{new_version}");
-
-    let shell_script = &format!(
-        r#"
-        set -e
-        git --no-pager diff --compact-summary --color=always
-        echo ""
-        git commit -a -m "chore(release): {version}" || true
-        git tag -a {version} -m "Release version {new_version}"
-        git push --follow-tags
-    "#
-    );
+    let shell_script = &release_git_script(new_version, args.release_branch.as_deref());
 
     let command = "sh";
-    let args = &["-c", shell_script];
+    let command_args = &["-c", shell_script];
     let error_message = "Failed to create release in git";
 
     if *DRY_RUN.get_value() {
@@ -697,7 +753,7 @@ fn create_release_in_git(new_version: &Version) -> Result<()> {
     }
 
     match Command::new(command)
-        .args(args)
+        .args(command_args)
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .output()
@@ -711,7 +767,7 @@ fn create_release_in_git(new_version: &Version) -> Result<()> {
                 let error = utils::command::parse_output(&output.stderr);
                 error!(
                     command = command,
-                    args = %args.join(" "),
+                    args = %command_args.join(" "),
                     error = %error,
                     error_message,
                 );
@@ -812,11 +868,10 @@ fn validate_metainfo(offline: bool) -> Result<()> {
 }
 
 #[allow(clippy::too_many_lines)] // You're perfect the way you are :)
-fn create_flathub_release_pr(new_version: &Version) -> Result<()> {
+fn create_flathub_release_pr(new_version: &Version, args: &Args) -> Result<()> {
     info!("==== Creating flathub release pr");
 
-    let flathub_repo_dir = &flathub_repo();
-    let app_id = config::APP_ID.get_value();
+    let flathub_repo_dir = &flathub_repo(args);
     let pr_branch = format!("v{new_version}");
 
     // Make changes on a new branch
@@ -825,7 +880,8 @@ fn create_flathub_release_pr(new_version: &Version) -> Result<()> {
         set -e
         git checkout -B {pr_branch}
         echo ""
-    "#
+    "#,
+        pr_branch = shell_quote(&pr_branch)
     );
     let error_message = "Failed to create new branch on flathub repo";
     run_shell_script(shell_script, flathub_repo_dir, error_message)?;
@@ -847,40 +903,35 @@ fn create_flathub_release_pr(new_version: &Version) -> Result<()> {
     fs::copy(cargo_sources, cargo_sources_flathub)?;
 
     let flathub_token = std::env::var("FLATHUB_TOKEN").unwrap_or_default();
-    let mut git_remote = String::from("origin");
-    if is_github_ssh_connected() {
-        git_remote = format!("git@github.com:flathub/{app_id}");
-        println!("Using SSH");
-    } else {
-        println!("Using https");
-    }
+    let git_remote = flathub_git_remote(args);
 
     // Commit changes
-    let shell_script = &format!(
-        r#"
-        set -e
-        git commit -a -m "chore(automated-release): {pr_branch}" || true 
-        git push {git_remote} {pr_branch} --force
-        git fetch
-        echo ""
-    "#
-    );
+    let shell_script = &flathub_push_script(&pr_branch, &git_remote);
+    if *DRY_RUN.get_value() {
+        println!(
+            "Dry-run - Running in {}:\n{shell_script}",
+            flathub_repo_dir.display()
+        );
+    }
     let error_message = "Failed to push new branch on flathub repo";
     run_shell_script(shell_script, flathub_repo_dir, error_message)?;
 
     // Create the PR
-    let pr_title = &format!(r"--title={pr_branch}");
-    let pr_body = &format!(r"--body=Automatic release for {new_version}");
     let command = "gh";
-    let mut args = ["pr", "create", pr_title, pr_body, "--draft"].to_vec();
+    let mut gh_args = flathub_pr_args(new_version, args.pr_base.as_deref());
     let error_message = "Failed to create a new PR on flathub repo";
 
     if *DRY_RUN.get_value() {
         println!("Dry-run - Adding --dry-run to github PR command");
-        args.push("--dry-run");
+        gh_args.push("--dry-run".to_string());
+        println!(
+            "Dry-run - Would have run in {}:\n{command} {}",
+            flathub_repo_dir.display(),
+            gh_args.join(" ")
+        );
     }
     match Command::new(command)
-        .args(&args)
+        .args(&gh_args)
         .current_dir(flathub_repo_dir)
         .env("GH_TOKEN", flathub_token)
         .stdout(Stdio::inherit())
@@ -896,7 +947,7 @@ fn create_flathub_release_pr(new_version: &Version) -> Result<()> {
                 let error = utils::command::parse_output(&output.stderr);
                 error!(
                     command = command,
-                    args = %args.join(" "),
+                    args = %gh_args.join(" "),
                     error = %error,
                     error_message,
                 );
@@ -913,7 +964,9 @@ fn create_flathub_release_pr(new_version: &Version) -> Result<()> {
             r"
             git push -d -f {git_remote} {pr_branch}
             git branch -d -f {pr_branch}
-        "
+        ",
+            git_remote = shell_quote(&git_remote),
+            pr_branch = shell_quote(&pr_branch)
         );
         let error_message = &format!(
             "Dry run - Failed to remove remote branch on {}",
@@ -987,7 +1040,7 @@ fn flatpak_release_manifest() -> PathBuf {
 
 fn flatpak_dev_manifest() -> PathBuf {
     let app_id = config::APP_ID.get_value();
-    let flatpak_dev_manifest_name = &format!("{app_id}.Devel.yml");
+    let flatpak_dev_manifest_name = &format!("{app_id}{}.yml", config::DEVEL_APP_ID_SUFFIX);
     flatpak_path().join(flatpak_dev_manifest_name)
 }
 
@@ -1000,11 +1053,85 @@ fn flatpak_cargo_sources() -> PathBuf {
     flatpak_path().join("cargo-sources.json")
 }
 
-fn flathub_repo() -> PathBuf {
+fn flathub_repo(args: &Args) -> PathBuf {
+    if let Some(flathub_repo) = &args.flathub_repo {
+        return flathub_repo.clone();
+    }
     let app_id = config::APP_ID.get_value();
     project_path().join("external").join(app_id)
 }
 
+fn flathub_git_remote(args: &Args) -> String {
+    if let Some(flathub_remote) = &args.flathub_remote {
+        println!("Using remote: {flathub_remote}");
+        return flathub_remote.clone();
+    }
+
+    if is_github_ssh_connected() {
+        let app_id = config::APP_ID.get_value();
+        println!("Using SSH");
+        format!("git@github.com:{}/{app_id}", args.flathub_org)
+    } else {
+        println!("Using https");
+        String::from("origin")
+    }
+}
+
+/// Single quotes for the shell, so branch names and remotes are passed as is
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn release_git_script(new_version: &Version, release_branch: Option<&str>) -> String {
+    let version = format!("v{new_version}");
+    let push = match release_branch {
+        None => String::from("git push --follow-tags"),
+        Some(release_branch) => format!(
+            "git push --follow-tags origin {}",
+            shell_quote(&format!("HEAD:{release_branch}"))
+        ),
+    };
+
+    format!(
+        r#"
+        set -e
+        git --no-pager diff --compact-summary --color=always
+        echo ""
+        git commit -a -m "chore(release): {version}" || true
+        git tag -a {version} -m "Release version {new_version}"
+        {push}
+    "#
+    )
+}
+
+fn flathub_push_script(pr_branch: &str, git_remote: &str) -> String {
+    format!(
+        r#"
+        set -e
+        git commit -a -m "chore(automated-release): {pr_branch}" || true
+        git push {git_remote} {pr_branch_quoted} --force
+        git fetch
+        echo ""
+    "#,
+        git_remote = shell_quote(git_remote),
+        pr_branch_quoted = shell_quote(pr_branch)
+    )
+}
+
+fn flathub_pr_args(new_version: &Version, pr_base: Option<&str>) -> Vec<String> {
+    let mut args = vec![
+        "pr".to_string(),
+        "create".to_string(),
+        format!("--title=v{new_version}"),
+        format!("--body=Automatic release for {new_version}"),
+        "--draft".to_string(),
+    ];
+    if let Some(pr_base) = pr_base {
+        args.push(format!("--base={pr_base}"));
+    }
+    args
+}
+
 fn desktop_file_name() -> String {
     let app_id = config::APP_ID.get_value();
     let extension = "desktop";
@@ -1013,6 +1140,14 @@ fn desktop_file_name() -> String {
     file_name
 }
 
+fn dbus_service_file_name() -> String {
+    let app_id = config::APP_ID.get_value();
+    let extension = "service";
+    let file_name = format!("{app_id}.{extension}");
+
+    file_name
+}
+
 fn icon_file_name() -> String {
     let app_id = config::APP_ID.get_value();
     let extension = "png";
@@ -1057,3 +1192,83 @@ fn run_shell_script(shell_script: &str, work_dir: &Path, error_message: &str) ->
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_keep_the_current_release_flow() {
+        let args = Args::parse_from(["release"]);
+
+        assert!(args.release_branch.is_none());
+        assert!(args.flathub_repo.is_none());
+        assert_eq!(args.flathub_org, "flathub");
+        assert!(args.flathub_remote.is_none());
+        assert!(args.pr_base.is_none());
+        assert!(!args.skip_flathub);
+    }
+
+    #[test]
+    fn quotes_in_values_are_escaped() {
+        assert_eq!(shell_quote("main"), "'main'");
+        assert_eq!(shell_quote("it's; rm -rf ~"), r"'it'\''s; rm -rf ~'");
+    }
+
+    #[test]
+    fn release_is_pushed_to_the_current_branch_by_default() {
+        let script = release_git_script(&Version::new(1, 2, 3), None);
+
+        assert!(script.contains(r#"git commit -a -m "chore(release): v1.2.3""#));
+        assert!(script.contains(r#"git tag -a v1.2.3 -m "Release version 1.2.3""#));
+        assert!(script.contains("git push --follow-tags\n"));
+    }
+
+    #[test]
+    fn release_is_pushed_to_the_release_branch() {
+        let script = release_git_script(&Version::new(1, 2, 3), Some("release/1.x"));
+
+        assert!(script.contains("git push --follow-tags origin 'HEAD:release/1.x'"));
+    }
+
+    #[test]
+    fn flathub_branch_and_remote_are_quoted() {
+        let script = flathub_push_script("v1.2.3", "git@github.com:my-org/app");
+
+        assert!(script.contains("git push 'git@github.com:my-org/app' 'v1.2.3' --force"));
+    }
+
+    #[test]
+    fn pr_base_is_only_passed_when_set() {
+        let version = Version::new(1, 2, 3);
+
+        assert_eq!(
+            flathub_pr_args(&version, None),
+            vec![
+                "pr",
+                "create",
+                "--title=v1.2.3",
+                "--body=Automatic release for 1.2.3",
+                "--draft"
+            ]
+        );
+        assert_eq!(
+            flathub_pr_args(&version, Some("beta")).last().unwrap(),
+            "--base=beta"
+        );
+    }
+
+    #[test]
+    fn flathub_repo_and_remote_options_win() {
+        let args = Args::parse_from([
+            "release",
+            "--flathub-repo",
+            "/tmp/flathub",
+            "--flathub-remote",
+            "fork",
+        ]);
+
+        assert_eq!(flathub_repo(&args), PathBuf::from("/tmp/flathub"));
+        assert_eq!(flathub_git_remote(&args), "fork");
+    }
+}